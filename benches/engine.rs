@@ -0,0 +1,88 @@
+// Benchmarks for the parts of the game that run the most: making moves, finding wins, finding the
+// moves that can be made, hashing positions and playing whole games. Run them with `cargo bench`.
+//
+// criterion runs each benchmark many times and compares the results with the last run, so it can
+// tell whether a change really made something faster or slower, or if the difference is just
+// noise.
+// More info: https://bheisler.github.io/criterion.rs/book/
+//
+// The modules they measure come from the library in src/lib.rs.
+
+#[macro_use]
+extern crate criterion;
+extern crate tic_tac_toe;
+
+use std::hint::black_box;
+
+use criterion::Criterion;
+
+use tic_tac_toe::engine::Difficulty;
+use tic_tac_toe::game::{Game, Piece};
+use tic_tac_toe::random::Rng;
+use tic_tac_toe::{simulate, solve, symmetry, tt};
+
+// A whole game that ends in a tie, so that every move is made and checked for a win
+const TIE: [(usize, usize); 9] = [
+    (1, 1), (0, 0), (0, 1), (2, 1), (1, 0), (1, 2), (0, 2), (2, 0), (2, 2),
+];
+
+// The position after the first four moves of the tie, with x to move
+fn middle_game() -> Game {
+    let mut game = Game::new();
+    for &(row, col) in &TIE[..4] {
+        game.make_move(row, col).unwrap();
+    }
+    game
+}
+
+fn make_moves(c: &mut Criterion) {
+    c.bench_function("make_move (whole game)", |b| b.iter(|| {
+        let mut game = Game::new();
+        for &(row, col) in &TIE {
+            game.make_move(black_box(row), black_box(col)).unwrap();
+        }
+        game
+    }));
+}
+
+fn win_detection(c: &mut Criterion) {
+    let game = middle_game();
+    c.bench_function("winning_moves", |b| b.iter(|| black_box(&game).winning_moves(Piece::X)));
+}
+
+fn move_generation(c: &mut Criterion) {
+    let game = middle_game();
+    c.bench_function("empty_tiles", |b| b.iter(|| solve::empty_tiles(black_box(&game))));
+}
+
+fn hashing(c: &mut Criterion) {
+    let game = middle_game();
+    c.bench_function("tt::key", |b| b.iter(|| tt::key(black_box(&game))));
+    c.bench_function("canonical_tiles", |b| {
+        b.iter(|| symmetry::canonical_tiles(black_box(game.tiles())))
+    });
+}
+
+// The search that build.rs runs on every position, from the empty board with a fresh table each
+// time so that nothing is remembered from the last run
+fn search(c: &mut Criterion) {
+    let game = Game::new();
+    c.bench_function("best_moves (whole game)", |b| b.iter(|| {
+        solve::best_moves(black_box(&game), &mut tt::Table::with_megabytes(1))
+    }));
+}
+
+fn self_play(c: &mut Criterion) {
+    let mut rng = Rng::new(1);
+    let (easy, perfect) = (Difficulty::Easy.into(), Difficulty::Perfect.into());
+    c.bench_function("self-play easy vs easy", |b| {
+        b.iter(|| simulate::play(easy, easy, false, &mut rng))
+    });
+    c.bench_function("self-play perfect vs perfect", |b| {
+        b.iter(|| simulate::play(perfect, perfect, false, &mut rng))
+    });
+}
+
+criterion_group!(benches, make_moves, win_detection, move_generation, hashing, search,
+    self_play);
+criterion_main!(benches);
@@ -0,0 +1,11 @@
+// Makes the table of perfect moves that engine.rs embeds in the program before the program itself
+// is compiled. The work is done by the tables crate, which the web version uses too.
+
+extern crate tic_tac_toe_tables;
+
+fn main() {
+    tic_tac_toe_tables::generate();
+    // The table only depends on the tables crate, and Cargo already runs this script again
+    // whenever that changes
+    println!("cargo:rerun-if-changed=build.rs");
+}
@@ -0,0 +1,30 @@
+// Embeds the table of perfect moves, just like the terminal and web versions do (see ../build.rs),
+// and writes the C header that declares the functions in src/lib.rs
+
+extern crate cbindgen;
+extern crate tic_tac_toe_tables;
+
+use std::env;
+use std::path::Path;
+
+fn main() {
+    tic_tac_toe_tables::generate();
+
+    // A build must never change the source files, so the header is written with the rest of the
+    // build. The copy next to Cargo.toml is the one that C programs include, and the tests check
+    // that it is the same as this one (see src/lib.rs).
+    let dir = env::var("CARGO_MANIFEST_DIR").expect("Cargo always sets CARGO_MANIFEST_DIR");
+    let out_dir = env::var("OUT_DIR").expect("Cargo always sets OUT_DIR");
+    let config = cbindgen::Config::from_file(Path::new(&dir).join("cbindgen.toml"))
+        .expect("cbindgen.toml should be valid");
+    cbindgen::Builder::new()
+        .with_crate(&dir)
+        .with_config(config)
+        .generate()
+        .expect("the C header should be generated")
+        .write_to_file(Path::new(&out_dir).join("tic_tac_toe.h"));
+
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+    println!("cargo:rerun-if-changed=src/lib.rs");
+}
@@ -0,0 +1,60 @@
+/*
+ * Plays a game of tic-tac-toe between you and the perfect computer player, using the library in
+ * ../src/lib.rs. See ../README.md for how to build it.
+ */
+
+#include <stdio.h>
+#include <time.h>
+
+#include "tic_tac_toe.h"
+
+static void print_board(const TttGame *game) {
+    for (size_t row = 0; row < 3; row++) {
+        for (size_t col = 0; col < 3; col++) {
+            switch (ttt_tile(game, row, col)) {
+                case TTT_PIECE_X: putchar('x'); break;
+                case TTT_PIECE_O: putchar('o'); break;
+                case TTT_PIECE_NONE: putchar('.'); break;
+            }
+        }
+        putchar('\n');
+    }
+}
+
+int main(void) {
+    TttGame *game = ttt_game_new((uint64_t) time(NULL));
+
+    while (ttt_status(game) == TTT_STATUS_IN_PROGRESS) {
+        print_board(game);
+
+        size_t row, col;
+        if (ttt_current_piece(game) == TTT_PIECE_X) {
+            printf("Your move (row and column, from 0 to 2): ");
+            if (scanf("%zu %zu", &row, &col) != 2) {
+                break;
+            }
+        }
+        else {
+            ttt_best_move(game, TTT_DIFFICULTY_PERFECT, &row, &col);
+            printf("The computer plays %zu %zu\n", row, col);
+        }
+
+        switch (ttt_make_move(game, row, col)) {
+            case TTT_MOVE_RESULT_OK: break;
+            case TTT_MOVE_RESULT_INVALID_POSITION: printf("That isn't on the board\n"); break;
+            case TTT_MOVE_RESULT_TILE_NOT_EMPTY: printf("That tile is taken\n"); break;
+            case TTT_MOVE_RESULT_GAME_ALREADY_OVER: break;
+        }
+    }
+
+    print_board(game);
+    switch (ttt_status(game)) {
+        case TTT_STATUS_X_WINS: printf("You win!\n"); break;
+        case TTT_STATUS_O_WINS: printf("The computer wins!\n"); break;
+        case TTT_STATUS_TIE: printf("It's a tie!\n"); break;
+        case TTT_STATUS_IN_PROGRESS: break;
+    }
+
+    ttt_game_free(game);
+    return 0;
+}
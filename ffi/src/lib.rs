@@ -0,0 +1,259 @@
+// The game as a library for C and C++ programs. Rust can export functions that follow the C
+// calling convention (`extern "C"`), which almost every language knows how to call. build.rs
+// uses cbindgen to write tic_tac_toe.h, the header that declares these functions for C. The copy
+// of the header next to Cargo.toml has to be updated by hand whenever they change (see README.md),
+// and the tests fail until it is.
+// More info: https://doc.rust-lang.org/nomicon/ffi.html
+//
+// Just like the web version, the rules and the computer player come straight from the terminal
+// version through `#[path]`, so a C program plays by exactly the same rules.
+//
+// C programs only ever see a pointer to a `TttGame`, never what is inside it. The pointers are
+// written as `Option<&mut TttGame>` here: Rust stores `None` as a null pointer, so the functions
+// look the same to C as if they took a `TttGame *`, but Rust makes us decide what happens when C
+// passes NULL. Passing NULL is always a bug in the C program, so it stops the program with a
+// message instead of crashing somewhere later.
+//
+// cbindgen copies the `///` comments into the header, so those are written for C programmers.
+
+extern crate thiserror;
+
+// The library only uses part of each module, so we tell Rust not to warn about the rest
+#[allow(dead_code)]
+#[path = "../../src/game.rs"]
+mod game;
+#[allow(dead_code)]
+#[path = "../../src/history.rs"]
+mod history;
+#[allow(dead_code)]
+#[path = "../../src/random.rs"]
+mod random;
+#[allow(dead_code)]
+#[path = "../../src/engine.rs"]
+mod engine;
+#[allow(dead_code)]
+#[path = "../../src/tt.rs"]
+mod tt;
+#[allow(dead_code)]
+#[path = "../../src/solve.rs"]
+mod solve;
+
+use engine::Difficulty;
+use game::{Game, MoveError, Piece, Winner};
+use random::Rng;
+
+/// A game, along with the random numbers that the computer player uses to pick between equally
+/// good moves. Made with `ttt_game_new` and freed with `ttt_game_free`.
+pub struct TttGame {
+    game: Game,
+    rng: Rng,
+    /// The number of the error from the last move that was tried, or 0 if it was made
+    last_error: u16,
+}
+
+// `#[repr(C)]` makes these enums the same size as a C enum, with the variants numbered from 0 in
+// the order they are listed
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TttPiece {
+    /// An empty tile
+    None,
+    X,
+    O,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TttStatus {
+    InProgress,
+    XWins,
+    OWins,
+    Tie,
+}
+
+/// What happened when trying to make a move with `ttt_make_move`
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TttMoveResult {
+    Ok,
+    /// The game was already over
+    GameAlreadyOver,
+    /// The row or column was 3 or more
+    InvalidPosition,
+    /// The tile already had a piece on it
+    TileNotEmpty,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TttDifficulty {
+    Easy,
+    Medium,
+    Perfect,
+}
+
+/// Starts a new game with x to move. The seed decides which of several equally good moves the
+/// computer player picks, so the same seed always gives the same games. The game must be freed
+/// with `ttt_game_free` once it isn't needed anymore.
+#[no_mangle]
+pub extern "C" fn ttt_game_new(seed: u64) -> Box<TttGame> {
+    // Giving the Box to C hands over the memory. Rust gets it back in `ttt_game_free`.
+    Box::new(TttGame {game: Game::new(), rng: Rng::new(seed), last_error: 0})
+}
+
+/// Frees a game made with `ttt_game_new`. Freeing NULL does nothing, just like `free` in C.
+#[no_mangle]
+pub extern "C" fn ttt_game_free(game: Option<Box<TttGame>>) {
+    // The Box goes away at the end of this function, which frees the game
+    drop(game);
+}
+
+/// Places the current player's piece at the given row and column (each from 0 to 2). Nothing
+/// changes unless this returns `TTT_MOVE_RESULT_OK`.
+#[no_mangle]
+pub extern "C" fn ttt_make_move(game: Option<&mut TttGame>, row: usize, col: usize)
+    -> TttMoveResult {
+
+    let game = expect_game(game);
+    let result = game.game.make_move(row, col);
+    game.last_error = result.as_ref().err().map_or(0, MoveError::code);
+    match result {
+        Ok(()) => TttMoveResult::Ok,
+        Err(MoveError::GameAlreadyOver) => TttMoveResult::GameAlreadyOver,
+        Err(MoveError::InvalidPosition {..}) => TttMoveResult::InvalidPosition,
+        Err(MoveError::TileNotEmpty {..}) => TttMoveResult::TileNotEmpty,
+    }
+}
+
+/// The number of the error from the last call to `ttt_make_move` on this game, or 0 if the move
+/// was made. These are the same numbers that the terminal version gives in its JSON output, and
+/// they never change: 100 if the game was already over, 101 if the row or column was 3 or more,
+/// and 102 if the tile already had a piece on it.
+#[no_mangle]
+pub extern "C" fn ttt_last_error(game: Option<&TttGame>) -> u16 {
+    expect_game(game).last_error
+}
+
+/// Takes back the last move. Returns false if there were no moves to take back.
+#[no_mangle]
+pub extern "C" fn ttt_undo(game: Option<&mut TttGame>) -> bool {
+    expect_game(game).game.undo().is_some()
+}
+
+/// Whether the game is still going, and if not, how it ended
+#[no_mangle]
+pub extern "C" fn ttt_status(game: Option<&TttGame>) -> TttStatus {
+    match expect_game(game).game.winner() {
+        None => TttStatus::InProgress,
+        Some(Winner::X) => TttStatus::XWins,
+        Some(Winner::O) => TttStatus::OWins,
+        Some(Winner::Tie) => TttStatus::Tie,
+    }
+}
+
+/// The piece whose turn it is
+#[no_mangle]
+pub extern "C" fn ttt_current_piece(game: Option<&TttGame>) -> TttPiece {
+    piece(Some(expect_game(game).game.current_piece()))
+}
+
+/// The piece at the given row and column, or `TTT_PIECE_NONE` if the tile is empty or isn't on
+/// the board
+#[no_mangle]
+pub extern "C" fn ttt_tile(game: Option<&TttGame>, row: usize, col: usize) -> TttPiece {
+    let tiles = expect_game(game).game.tiles();
+    piece(tiles.get(row).and_then(|tiles| tiles.get(col)).cloned().unwrap_or(None))
+}
+
+/// Asks the computer which move it would make for the current player, and writes its row and
+/// column to `row` and `col`. The move isn't made, so it can be used as a hint too. Returns false
+/// (and writes nothing) if the game is already over. Just like the game, `row` and `col` must not
+/// be NULL.
+#[no_mangle]
+pub extern "C" fn ttt_best_move(game: Option<&mut TttGame>, difficulty: TttDifficulty,
+    row: Option<&mut usize>, col: Option<&mut usize>) -> bool {
+
+    let game = expect_game(game);
+    // Both are checked before anything is written, so that a NULL `col` doesn't leave `row`
+    // changed on the way out
+    let row = row.expect("row must not be NULL");
+    let col = col.expect("col must not be NULL");
+    if game.game.is_finished() {
+        return false;
+    }
+    let difficulty = match difficulty {
+        TttDifficulty::Easy => Difficulty::Easy,
+        TttDifficulty::Medium => Difficulty::Medium,
+        TttDifficulty::Perfect => Difficulty::Perfect,
+    };
+    let (best_row, best_col) = engine::choose_move(&game.game, difficulty, &mut game.rng);
+    *row = best_row;
+    *col = best_col;
+    true
+}
+
+// Works for both `&TttGame` and `&mut TttGame`. A panic can't cross into C, so Rust stops the
+// program when it reaches the end of an `extern "C"` function.
+fn expect_game<T>(game: Option<T>) -> T {
+    game.expect("the game must not be NULL")
+}
+
+fn piece(tile: Option<Piece>) -> TttPiece {
+    match tile {
+        Some(Piece::X) => TttPiece::X,
+        Some(Piece::O) => TttPiece::O,
+        None => TttPiece::None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn play_against_the_computer() {
+        let mut game = ttt_game_new(1);
+        assert_eq!(ttt_make_move(Some(&mut game), 1, 1), TttMoveResult::Ok);
+        assert_eq!(ttt_make_move(Some(&mut game), 1, 1), TttMoveResult::TileNotEmpty);
+        assert_eq!(ttt_make_move(Some(&mut game), 3, 0), TttMoveResult::InvalidPosition);
+        assert_eq!(ttt_last_error(Some(&game)), 101);
+        assert_eq!(ttt_tile(Some(&game), 1, 1), TttPiece::X);
+        assert_eq!(ttt_tile(Some(&game), 3, 0), TttPiece::None);
+
+        // The only moves that don't lose against the center are the corners
+        let (mut row, mut col) = (9, 9);
+        assert!(ttt_best_move(Some(&mut game), TttDifficulty::Perfect, Some(&mut row),
+            Some(&mut col)));
+        assert!(row != 1 && col != 1);
+        assert_eq!(ttt_current_piece(Some(&game)), TttPiece::O);
+        assert_eq!(ttt_make_move(Some(&mut game), row, col), TttMoveResult::Ok);
+        assert_eq!(ttt_last_error(Some(&game)), 0);
+
+        assert!(ttt_undo(Some(&mut game)));
+        assert_eq!(ttt_current_piece(Some(&game)), TttPiece::O);
+        assert_eq!(ttt_status(Some(&game)), TttStatus::InProgress);
+        ttt_game_free(Some(game));
+    }
+
+    #[test]
+    fn perfect_games_are_ties() {
+        let mut game = ttt_game_new(7);
+        let (mut row, mut col) = (0, 0);
+        while ttt_best_move(Some(&mut game), TttDifficulty::Perfect, Some(&mut row),
+            Some(&mut col)) {
+            assert_eq!(ttt_make_move(Some(&mut game), row, col), TttMoveResult::Ok);
+        }
+        assert_eq!(ttt_status(Some(&game)), TttStatus::Tie);
+        assert_eq!(ttt_make_move(Some(&mut game), 0, 0), TttMoveResult::GameAlreadyOver);
+        assert_eq!(ttt_last_error(Some(&game)), 100);
+        ttt_game_free(Some(game));
+    }
+
+    #[test]
+    fn header_is_up_to_date() {
+        // build.rs writes the header for the functions as they are now
+        let generated = include_str!(concat!(env!("OUT_DIR"), "/tic_tac_toe.h"));
+        assert!(generated == include_str!("../tic_tac_toe.h"),
+            "tic_tac_toe.h is out of date, see README.md to update it");
+    }
+}
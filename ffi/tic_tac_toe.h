@@ -0,0 +1,122 @@
+#ifndef TIC_TAC_TOE_H
+#define TIC_TAC_TOE_H
+
+/* Written by build.rs from src/lib.rs. Don't change this file by hand. */
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+/**
+ * What happened when trying to make a move with `ttt_make_move`
+ */
+typedef enum TttMoveResult {
+  TTT_MOVE_RESULT_OK,
+  /**
+   * The game was already over
+   */
+  TTT_MOVE_RESULT_GAME_ALREADY_OVER,
+  /**
+   * The row or column was 3 or more
+   */
+  TTT_MOVE_RESULT_INVALID_POSITION,
+  /**
+   * The tile already had a piece on it
+   */
+  TTT_MOVE_RESULT_TILE_NOT_EMPTY,
+} TttMoveResult;
+
+typedef enum TttStatus {
+  TTT_STATUS_IN_PROGRESS,
+  TTT_STATUS_X_WINS,
+  TTT_STATUS_O_WINS,
+  TTT_STATUS_TIE,
+} TttStatus;
+
+typedef enum TttPiece {
+  /**
+   * An empty tile
+   */
+  TTT_PIECE_NONE,
+  TTT_PIECE_X,
+  TTT_PIECE_O,
+} TttPiece;
+
+typedef enum TttDifficulty {
+  TTT_DIFFICULTY_EASY,
+  TTT_DIFFICULTY_MEDIUM,
+  TTT_DIFFICULTY_PERFECT,
+} TttDifficulty;
+
+/**
+ * A game, along with the random numbers that the computer player uses to pick between equally
+ * good moves. Made with `ttt_game_new` and freed with `ttt_game_free`.
+ */
+typedef struct TttGame TttGame;
+
+
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Starts a new game with x to move. The seed decides which of several equally good moves the
+ * computer player picks, so the same seed always gives the same games. The game must be freed
+ * with `ttt_game_free` once it isn't needed anymore.
+ */
+struct TttGame *ttt_game_new(uint64_t seed);
+
+/**
+ * Frees a game made with `ttt_game_new`. Freeing NULL does nothing, just like `free` in C.
+ */
+void ttt_game_free(struct TttGame *game);
+
+/**
+ * Places the current player's piece at the given row and column (each from 0 to 2). Nothing
+ * changes unless this returns `TTT_MOVE_RESULT_OK`.
+ */
+enum TttMoveResult ttt_make_move(struct TttGame *game, size_t row, size_t col);
+
+/**
+ * The number of the error from the last call to `ttt_make_move` on this game, or 0 if the move
+ * was made. These are the same numbers that the terminal version gives in its JSON output, and
+ * they never change: 100 if the game was already over, 101 if the row or column was 3 or more,
+ * and 102 if the tile already had a piece on it.
+ */
+uint16_t ttt_last_error(const struct TttGame *game);
+
+/**
+ * Takes back the last move. Returns false if there were no moves to take back.
+ */
+bool ttt_undo(struct TttGame *game);
+
+/**
+ * Whether the game is still going, and if not, how it ended
+ */
+enum TttStatus ttt_status(const struct TttGame *game);
+
+/**
+ * The piece whose turn it is
+ */
+enum TttPiece ttt_current_piece(const struct TttGame *game);
+
+/**
+ * The piece at the given row and column, or `TTT_PIECE_NONE` if the tile is empty or isn't on
+ * the board
+ */
+enum TttPiece ttt_tile(const struct TttGame *game, size_t row, size_t col);
+
+/**
+ * Asks the computer which move it would make for the current player, and writes its row and
+ * column to `row` and `col`. The move isn't made, so it can be used as a hint too. Returns false
+ * (and writes nothing) if the game is already over. Just like the game, `row` and `col` must not
+ * be NULL.
+ */
+bool ttt_best_move(struct TttGame *game, enum TttDifficulty difficulty, size_t *row, size_t *col);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* TIC_TAC_TOE_H */
@@ -0,0 +1,35 @@
+// Checks that reading or verifying an archive never panics, no matter what the file has in it
+#![no_main]
+#[macro_use]
+extern crate libfuzzer_sys;
+extern crate thiserror;
+
+// Only the parts of these modules needed to read an archive are used
+#[allow(dead_code)]
+#[path = "../../src/archive.rs"]
+mod archive;
+#[allow(dead_code)]
+#[path = "../../src/game.rs"]
+mod game;
+#[allow(dead_code)]
+#[path = "../../src/history.rs"]
+mod history;
+#[allow(dead_code)]
+#[path = "../../src/player.rs"]
+mod player;
+#[allow(dead_code)]
+#[path = "../../src/series.rs"]
+mod series;
+#[allow(dead_code)]
+#[path = "../../src/storage.rs"]
+mod storage;
+
+fuzz_target!(|data: &[u8]| {
+    // Archives are read with `fs::read_to_string`, which fails on anything that isn't UTF-8
+    if let Ok(text) = std::str::from_utf8(data) {
+        let _ = archive::parse_games(text);
+        // The solver isn't built here, since it needs the table that the main crate's build script
+        // makes. Any answer will do for finding panics, so every game is taken to be a tie.
+        let _ = archive::verify(text, |_| game::Winner::Tie);
+    }
+});
@@ -0,0 +1,15 @@
+// Checks that reading a move typed by a player never panics, whatever they type
+#![no_main]
+#[macro_use]
+extern crate libfuzzer_sys;
+
+#[allow(dead_code)]
+#[path = "../../src/notation.rs"]
+mod notation;
+
+fuzz_target!(|data: &[u8]| {
+    // Players can only type valid UTF-8, since every line of input is read into a String first
+    if let Ok(input) = std::str::from_utf8(data) {
+        let _ = notation::parse_move(input);
+    }
+});
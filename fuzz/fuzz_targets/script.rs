@@ -0,0 +1,29 @@
+// Checks that playing the moves given with `--moves` never panics, whatever is in the script
+#![no_main]
+#[macro_use]
+extern crate libfuzzer_sys;
+extern crate thiserror;
+
+#[allow(dead_code)]
+#[path = "../../src/game.rs"]
+mod game;
+#[allow(dead_code)]
+#[path = "../../src/history.rs"]
+mod history;
+#[allow(dead_code)]
+#[path = "../../src/notation.rs"]
+mod notation;
+
+use game::Game;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(script) = std::str::from_utf8(data) {
+        let mut game = Game::new();
+        for notation in notation::script_moves(script) {
+            // Invalid and illegal moves are errors, but they should never be panics
+            if let Ok((row, col)) = notation::parse_move(notation) {
+                let _ = game.make_move(row, col);
+            }
+        }
+    }
+});
@@ -0,0 +1,163 @@
+// The network connections of the chat bots, running on tokio, the async runtime for Rust. A
+// connection sends and receives lines of text (ending with "\r\n", like IRC's), which is all that
+// the IRC bot needs (see src/irc.rs in the terminal version). The Discord bot talks to Discord
+// through a WebSocket instead, which doesn't run here (see src/gateway.rs).
+// More info: https://tokio.rs
+//
+// Only the connection itself runs on the runtime. It has a thread of its own, where reading from
+// the server, writing to it and answering the lines that keep the connection alive (like IRC's
+// PING) all happen at the same time. The game stays synchronous: it takes the lines that came in
+// whenever it is ready for them, and hands over the lines to send without waiting for them to be
+// written. A move that takes the engine a while to find never keeps the server waiting for an
+// answer, and nothing else in the program has to know about async Rust. Games played in the
+// terminal never start a runtime at all.
+//
+// `async` and `.await` need a newer edition of Rust than the one the terminal version uses, which
+// is why this is a crate of its own.
+
+use std::io;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio::runtime::Builder;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+
+// Answers a line from the server right away, on the connection's own thread, by returning the
+// line to send back. Lines that it returns None for are passed on to the game.
+pub type Responder = fn(&str) -> Option<String>;
+
+// What a connection has to tell the game
+#[derive(Debug)]
+pub enum Incoming {
+    /// A line from the server, without the line break at its end
+    Line(String),
+    /// The connection ended, and this is why
+    Closed(io::Error),
+}
+
+#[derive(Debug)]
+pub struct Connection {
+    outgoing: UnboundedSender<String>,
+    incoming: Receiver<Incoming>,
+    thread: JoinHandle<()>,
+}
+
+impl Connection {
+    // Connects to the address (like irc.libera.chat:6667), waiting until the connection is made
+    pub fn open(address: &str, respond: Responder) -> io::Result<Self> {
+        // A runtime that only runs on the thread that starts it is plenty for one connection
+        let runtime = Builder::new_current_thread().enable_io().build()?;
+        let stream = runtime.block_on(TcpStream::connect(address))?;
+        let (outgoing, to_send) = unbounded_channel();
+        let (received, incoming) = mpsc::channel();
+        let thread = thread::spawn(move || {
+            if let Err(error) = runtime.block_on(run(stream, to_send, &received, respond)) {
+                // Nobody is listening anymore if the game already let go of the connection
+                let _ = received.send(Incoming::Closed(error));
+            }
+        });
+        Ok(Self {outgoing, incoming, thread})
+    }
+
+    // Sends a line to the server as soon as the lines before it are sent. The line break is added
+    // here.
+    pub fn send(&self, line: &str) -> io::Result<()> {
+        self.outgoing.send(format!("{}\r\n", line)).map_err(|_| closed())
+    }
+
+    // Waits up to `timeout` for something from the server, and returns None if nothing came
+    pub fn receive(&self, timeout: Duration) -> Option<Incoming> {
+        match self.incoming.recv_timeout(timeout) {
+            Ok(incoming) => Some(incoming),
+            Err(RecvTimeoutError::Timeout) => None,
+            Err(RecvTimeoutError::Disconnected) => Some(Incoming::Closed(closed())),
+        }
+    }
+
+    // Sends every line that is still waiting to be sent, and then closes the connection
+    pub fn close(self) {
+        let Connection {outgoing, thread, ..} = self;
+        // Without anything left to send, the connection stops once the last line is written
+        drop(outgoing);
+        let _ = thread.join();
+    }
+}
+
+// Reads and writes lines until the connection ends. Returns Ok if the game let go of it first.
+async fn run(stream: TcpStream, mut to_send: UnboundedReceiver<String>,
+    received: &mpsc::Sender<Incoming>, respond: Responder) -> io::Result<()> {
+
+    let (reader, mut writer) = stream.into_split();
+    let mut reader = BufReader::new(reader);
+    // A line can arrive in pieces, which are kept here until the rest of it comes
+    let mut line = Vec::new();
+    loop {
+        // `select!` waits for whichever comes first: more to read, or a line to send
+        tokio::select! {
+            read = reader.read_until(b'\n', &mut line) => {
+                if read? == 0 {
+                    return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "connection closed"));
+                }
+                // Most servers use UTF-8, but nothing makes them, so other text is replaced
+                // instead of ending the connection
+                let text = String::from_utf8_lossy(&line).trim_end_matches(['\r', '\n'])
+                    .to_string();
+                line.clear();
+                match respond(&text) {
+                    Some(answer) => writer.write_all(format!("{}\r\n", answer).as_bytes()).await?,
+                    None => {
+                        if received.send(Incoming::Line(text)).is_err() {
+                            return Ok(());
+                        }
+                    },
+                }
+            },
+            next = to_send.recv() => match next {
+                Some(text) => writer.write_all(text.as_bytes()).await?,
+                None => return Ok(()),
+            },
+        }
+    }
+}
+
+fn closed() -> io::Error {
+    io::Error::new(io::ErrorKind::NotConnected, "the connection is closed")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+
+    fn pong(line: &str) -> Option<String> {
+        line.strip_prefix("PING ").map(|token| format!("PONG {}", token))
+    }
+
+    #[test]
+    fn lines_both_ways() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            stream.write_all(b"PING :1\r\nhello\r\n").unwrap();
+            let lines: Vec<_> = BufReader::new(stream).lines().map(Result::unwrap).collect();
+            lines
+        });
+
+        let connection = Connection::open(&address, pong).unwrap();
+        let wait = Duration::from_secs(5);
+        match connection.receive(wait) {
+            Some(Incoming::Line(line)) => assert_eq!(line, "hello"),
+            other => panic!("expected a line, got {:?}", other),
+        }
+        connection.send("QUIT").unwrap();
+        connection.close();
+        // The PING was answered without the game seeing it, before the game's own line
+        assert_eq!(server.join().unwrap(), vec!["PONG :1", "QUIT"]);
+    }
+}
@@ -0,0 +1,14 @@
+// The addon has a perfect computer player too, so it embeds the same table of perfect moves as
+// the terminal version, made by the same crate (see ../build.rs)
+
+extern crate napi_build;
+extern crate tic_tac_toe_tables;
+
+fn main() {
+    tic_tac_toe_tables::generate();
+    // Sets up linking the addon with Node.js, which is different on each platform
+    napi_build::setup();
+    // The table only depends on the tables crate, and Cargo already runs this script again
+    // whenever that changes
+    println!("cargo:rerun-if-changed=build.rs");
+}
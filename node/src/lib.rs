@@ -0,0 +1,250 @@
+// The game as a native addon for Node.js, so that JavaScript and TypeScript running on a server
+// use exactly the same rules and computer players as the terminal version. napi-rs generates the
+// code that lets JavaScript create and use the Rust types marked with `#[napi]`, and its command
+// line tool writes the TypeScript typings (index.d.ts) from them when building (see README.md).
+// More info: https://napi.rs
+//
+// Just like the web version, the rules and the computer players come straight from the terminal
+// version through `#[path]`. Names are changed to camelCase for JavaScript, so `make_move` is
+// called as `makeMove`.
+//
+// napi-rs copies the `///` comments into the typings, so those are written for JavaScript
+// programmers.
+
+// The code that napi-rs generates refers to `::core`, which newer editions of Rust always have,
+// but this crate (like the rest of the game) uses the 2015 edition, which has to ask for it
+extern crate core;
+extern crate napi;
+#[macro_use]
+extern crate napi_derive;
+extern crate thiserror;
+
+// The addon only uses part of each module, so we tell Rust not to warn about the rest
+#[allow(dead_code)]
+#[path = "../../src/game.rs"]
+mod game;
+#[allow(dead_code)]
+#[path = "../../src/history.rs"]
+mod history;
+#[allow(dead_code)]
+#[path = "../../src/random.rs"]
+mod random;
+#[allow(dead_code)]
+#[path = "../../src/engine.rs"]
+mod engine;
+#[allow(dead_code)]
+#[path = "../../src/tt.rs"]
+mod tt;
+#[allow(dead_code)]
+#[path = "../../src/solve.rs"]
+mod solve;
+
+use napi::{Error, Result, Status};
+
+use engine::Difficulty;
+use game::{MoveError, Piece, Winner};
+use random::Rng;
+
+/// A tile of the board. Rows and columns are numbered from 0 to 2, starting at the top left
+/// corner.
+#[napi(object)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Move {
+    pub row: u32,
+    pub col: u32,
+}
+
+impl Move {
+    fn new((row, col): (usize, usize)) -> Self {
+        Move {row: row as u32, col: col as u32}
+    }
+}
+
+/// A game of tic-tac-toe. Pieces and results are the strings "x", "o" and "tie".
+#[napi]
+pub struct Game {
+    game: game::Game,
+}
+
+#[napi]
+impl Game {
+    /// Starts a new game. x moves first unless `first` is "o".
+    #[napi(constructor, ts_args_type = "first?: 'x' | 'o'")]
+    pub fn new(first: Option<String>) -> Result<Self> {
+        let first = match first.as_deref() {
+            None | Some("x") => Piece::X,
+            Some("o") => Piece::O,
+            Some(other) => return Err(invalid_arg(format!("unknown piece {:?}", other))),
+        };
+        Ok(Game {game: game::Game::starting_with(first)})
+    }
+
+    /// Places the current player's piece at the given row and column. Throws an error with the
+    /// code "InvalidArg" if the move can't be made, in which case nothing changes.
+    #[napi]
+    pub fn make_move(&mut self, row: u32, col: u32) -> Result<()> {
+        self.game.make_move(row as usize, col as usize).map_err(|error| match error {
+            MoveError::GameAlreadyOver => invalid_arg("the game is already over".to_string()),
+            MoveError::InvalidPosition {row, col} => {
+                invalid_arg(format!("({}, {}) is not on the board", row, col))
+            },
+            MoveError::TileNotEmpty {other_piece, row, col, ..} => {
+                invalid_arg(format!("({}, {}) already has {} on it", row, col,
+                    piece_name(other_piece)))
+            },
+        })
+    }
+
+    /// Takes back the last move and returns where it was made, or null if there were no moves to
+    /// take back
+    #[napi]
+    pub fn undo(&mut self) -> Option<Move> {
+        self.game.undo().map(Move::new)
+    }
+
+    /// Every move made so far, in the order they were made
+    #[napi]
+    pub fn moves(&self) -> Vec<Move> {
+        self.game.moves().iter().map(Move::new).collect()
+    }
+
+    /// The moves that can be made now: every empty tile, or none once the game is over
+    #[napi]
+    pub fn legal_moves(&self) -> Vec<Move> {
+        if self.game.is_finished() {
+            return Vec::new();
+        }
+        solve::empty_tiles(&self.game).into_iter().map(Move::new).collect()
+    }
+
+    /// The board as an array of rows, with "x", "o" or null for each tile
+    #[napi(ts_return_type = "Array<Array<'x' | 'o' | null>>")]
+    pub fn board(&self) -> Vec<Vec<Option<String>>> {
+        self.game.tiles().iter()
+            .map(|row| row.iter().map(|tile| tile.map(|piece| piece_name(piece).to_string()))
+                .collect())
+            .collect()
+    }
+
+    /// The piece whose turn it is
+    #[napi(getter, ts_return_type = "'x' | 'o'")]
+    pub fn current_piece(&self) -> String {
+        piece_name(self.game.current_piece()).to_string()
+    }
+
+    /// "x", "o" or "tie" once the game is over, and null before that
+    #[napi(getter, ts_return_type = "'x' | 'o' | 'tie' | null")]
+    pub fn winner(&self) -> Option<String> {
+        self.game.winner().map(|winner| winner_name(winner).to_string())
+    }
+
+    #[napi(getter)]
+    pub fn is_finished(&self) -> bool {
+        self.game.is_finished()
+    }
+
+    /// How the game ends from here if both players play perfectly: "x", "o" or "tie"
+    #[napi(ts_return_type = "'x' | 'o' | 'tie'")]
+    pub fn perfect_result(&self) -> String {
+        winner_name(engine::perfect_result(&self.game)).to_string()
+    }
+
+    /// A separate game with the same moves, e.g. to try out moves without changing this one
+    #[napi]
+    pub fn copy(&self) -> Game {
+        Game {game: self.game.clone()}
+    }
+
+    /// A line for each row, with a dot for each empty tile
+    #[napi]
+    #[allow(clippy::inherent_to_string)]
+    pub fn to_string(&self) -> String {
+        self.game.tiles().iter()
+            .map(|row| row.iter().map(|tile| tile.map_or(".", piece_name)).collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// A computer player. The difficulty is "easy" (plays at random), "medium" (wins and blocks when
+/// it can) or "perfect" (never loses, the default). The same seed always makes the same choices.
+#[napi]
+pub struct Engine {
+    difficulty: Difficulty,
+    rng: Rng,
+}
+
+#[napi]
+impl Engine {
+    // JavaScript numbers are all floating point, so the seed is turned into a u64 bit for bit,
+    // just like in the web version
+    #[napi(constructor, ts_args_type = "difficulty?: 'easy' | 'medium' | 'perfect', seed?: number")]
+    pub fn new(difficulty: Option<String>, seed: Option<f64>) -> Result<Self> {
+        let difficulty = match difficulty {
+            None => Difficulty::Perfect,
+            Some(name) => Difficulty::from_name(&name)
+                .ok_or_else(|| invalid_arg(format!("unknown difficulty {:?}", name)))?,
+        };
+        let seed = seed.map_or_else(random::time_seed, f64::to_bits);
+        Ok(Engine {difficulty, rng: Rng::new(seed)})
+    }
+
+    /// The move this player would make for the current player of `game`. The move isn't made.
+    /// Throws an error if the game is already over.
+    #[napi]
+    pub fn choose_move(&mut self, game: &Game) -> Result<Move> {
+        if game.game.is_finished() {
+            return Err(invalid_arg("the game is already over".to_string()));
+        }
+        Ok(Move::new(engine::choose_move(&game.game, self.difficulty, &mut self.rng)))
+    }
+
+    #[napi(getter, ts_return_type = "'easy' | 'medium' | 'perfect'")]
+    pub fn difficulty(&self) -> String {
+        self.difficulty.name().to_string()
+    }
+}
+
+fn invalid_arg(reason: String) -> Error {
+    Error::new(Status::InvalidArg, reason)
+}
+
+fn piece_name(piece: Piece) -> &'static str {
+    match piece {
+        Piece::X => "x",
+        Piece::O => "o",
+    }
+}
+
+fn winner_name(winner: Winner) -> &'static str {
+    match winner {
+        Winner::X => "x",
+        Winner::O => "o",
+        Winner::Tie => "tie",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn play_against_the_engine() {
+        let mut game = Game::new(None).unwrap();
+        game.make_move(1, 1).unwrap();
+        assert!(game.make_move(1, 1).is_err());
+        assert!(game.make_move(3, 0).is_err());
+        assert_eq!(game.board()[1][1], Some("x".to_string()));
+        assert_eq!(game.legal_moves().len(), 8);
+
+        // Two perfect players always tie
+        let mut engine = Engine::new(None, Some(1.0)).unwrap();
+        while !game.is_finished() {
+            let Move {row, col} = engine.choose_move(&game).unwrap();
+            game.make_move(row, col).unwrap();
+        }
+        assert_eq!(game.winner(), Some("tie".to_string()));
+        assert!(engine.choose_move(&game).is_err());
+        assert!(Engine::new(Some("impossible".to_string()), None).is_err());
+    }
+}
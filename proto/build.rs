@@ -0,0 +1,14 @@
+// Turns tictactoe.proto into the Rust types that src/lib.rs includes. prost-build runs protoc to
+// read the file, and writes a Rust struct for each message and a Rust enum for each enum.
+// More info: https://docs.rs/prost-build
+
+extern crate prost_build;
+extern crate protoc_bin_vendored;
+
+fn main() {
+    let protoc = protoc_bin_vendored::protoc_bin_path().expect("protoc should come with the crate");
+    let mut config = prost_build::Config::new();
+    config.protoc_executable(protoc);
+    config.compile_protos(&["tictactoe.proto"], &["."]).expect("tictactoe.proto should be valid");
+    println!("cargo:rerun-if-changed=tictactoe.proto");
+}
@@ -0,0 +1,222 @@
+// Protocol Buffers messages for games of tic-tac-toe, so that programs written in other languages
+// can send games to and from the Rust ones, e.g. over gRPC or through a message queue. The
+// messages are defined in tictactoe.proto, which is all another language needs to generate its
+// own types. build.rs generates the Rust types from the same file.
+// More info: https://protobuf.dev/overview/
+//
+// The generated types are plain data, and they can't stop anyone from filling in a move that
+// doesn't follow the rules. `Game` is the type that knows the rules, so this crate converts
+// between the two, replaying every move of a `GameState` to make sure that it is a real game.
+// Just like the web version, `Game` comes straight from the terminal version through `#[path]`.
+
+// The generated code refers to `::core`, which newer editions of Rust always have, but this crate
+// (like the rest of the game) uses the 2015 edition, which has to ask for it
+extern crate core;
+extern crate prost;
+extern crate thiserror;
+
+#[allow(dead_code)]
+#[path = "../../src/game.rs"]
+mod game;
+#[allow(dead_code)]
+#[path = "../../src/history.rs"]
+mod history;
+
+use std::convert::TryFrom;
+
+use thiserror::Error;
+
+pub use game::{Game, MoveError, Piece, Winner};
+
+// The types generated from tictactoe.proto. They are encoded with `prost::Message::encode` and
+// decoded with `prost::Message::decode`.
+pub mod messages {
+    include!(concat!(env!("OUT_DIR"), "/tictactoe.rs"));
+}
+
+// The reasons a GameState can't be turned into a Game
+#[derive(Debug, Clone, Error)]
+pub enum StateError {
+    /// A field has a value that isn't in its enum, or that doesn't make sense for it (like a
+    /// first piece that isn't X or O)
+    #[error("the {0} field has a value that can't be used")]
+    InvalidField(&'static str),
+    /// One of the moves doesn't follow the rules. `index` counts the moves from 0.
+    #[error("move {index} can't be played: {error}")]
+    InvalidMove { index: usize, #[source] error: MoveError },
+    /// The tiles, current piece or result don't match the moves
+    #[error("the {0} field doesn't match the moves")]
+    Mismatch(&'static str),
+}
+
+impl StateError {
+    // The number that stands for this error. It is in the same list as the numbers of the errors
+    // of the terminal version (see src/error.rs), so a number always means the same thing.
+    pub fn code(&self) -> u16 {
+        match *self {
+            StateError::InvalidField(_) => 600,
+            StateError::InvalidMove {..} => 601,
+            StateError::Mismatch(_) => 602,
+        }
+    }
+}
+
+impl<'a> From<&'a Game> for messages::GameState {
+    fn from(game: &'a Game) -> Self {
+        let moves = game.moves().iter()
+            .map(|(row, col)| messages::Move {row: row as u32, col: col as u32})
+            .collect();
+        let tiles = game.tiles().iter().flat_map(|row| row.iter())
+            .map(|&tile| piece(tile) as i32)
+            .collect();
+        messages::GameState {
+            first_piece: piece(Some(game.first_piece())) as i32,
+            moves,
+            forfeited_by: piece(game.forfeited_by()) as i32,
+            tiles,
+            current_piece: piece(Some(game.current_piece())) as i32,
+            result: result(game.winner()) as i32,
+            history_digest: game.history_digest(),
+        }
+    }
+}
+
+// Replays the moves of the state. The tiles, current piece, result and history digest are
+// optional, since the moves are enough to work them out, but when they are filled in they must
+// match the moves. A digest that doesn't match means that the moves were changed on the way, or
+// that the two programs no longer agree on what the game is.
+impl<'a> TryFrom<&'a messages::GameState> for Game {
+    type Error = StateError;
+
+    fn try_from(state: &'a messages::GameState) -> Result<Self, StateError> {
+        let first_piece = decode_piece(state.first_piece, "first_piece")?
+            .ok_or(StateError::InvalidField("first_piece"))?;
+        let mut game = Game::starting_with(first_piece);
+        for (index, mov) in state.moves.iter().enumerate() {
+            // Rows and columns that don't fit in a usize can't be on the board either
+            let row = usize::try_from(mov.row).unwrap_or(usize::MAX);
+            let col = usize::try_from(mov.col).unwrap_or(usize::MAX);
+            game.make_move(row, col).map_err(|error| StateError::InvalidMove {index, error})?;
+        }
+        if let Some(piece) = decode_piece(state.forfeited_by, "forfeited_by")? {
+            game.forfeit(piece).map_err(|error| StateError::InvalidMove {
+                index: state.moves.len(),
+                error,
+            })?;
+        }
+
+        let expected = messages::GameState::from(&game);
+        if !state.tiles.is_empty() && state.tiles != expected.tiles {
+            return Err(StateError::Mismatch("tiles"));
+        }
+        if state.current_piece != 0 && state.current_piece != expected.current_piece {
+            return Err(StateError::Mismatch("current_piece"));
+        }
+        // A result that isn't filled in means that the game isn't over, so it always has to match
+        if state.result != expected.result {
+            return Err(StateError::Mismatch("result"));
+        }
+        if state.history_digest != 0 && state.history_digest != expected.history_digest {
+            return Err(StateError::Mismatch("history_digest"));
+        }
+        Ok(game)
+    }
+}
+
+fn piece(tile: Option<Piece>) -> messages::Piece {
+    match tile {
+        Some(Piece::X) => messages::Piece::X,
+        Some(Piece::O) => messages::Piece::O,
+        None => messages::Piece::Unspecified,
+    }
+}
+
+fn result(winner: Option<Winner>) -> messages::GameResult {
+    match winner {
+        Some(Winner::X) => messages::GameResult::XWins,
+        Some(Winner::O) => messages::GameResult::OWins,
+        Some(Winner::Tie) => messages::GameResult::Tie,
+        None => messages::GameResult::Unspecified,
+    }
+}
+
+// Enum fields are stored as numbers, so a message from another program can have any number in
+// them, even ones that aren't in the enum
+fn decode_piece(value: i32, field: &'static str) -> Result<Option<Piece>, StateError> {
+    match messages::Piece::try_from(value) {
+        Ok(messages::Piece::X) => Ok(Some(Piece::X)),
+        Ok(messages::Piece::O) => Ok(Some(Piece::O)),
+        Ok(messages::Piece::Unspecified) => Ok(None),
+        Err(_) => Err(StateError::InvalidField(field)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use prost::Message;
+
+    #[test]
+    fn round_trip() {
+        let mut game = Game::starting_with(Piece::O);
+        for &(row, col) in &[(1, 1), (0, 0), (0, 2), (2, 0), (1, 0)] {
+            game.make_move(row, col).unwrap();
+        }
+        let state = messages::GameState::from(&game);
+        assert_eq!(state.tiles.len(), 9);
+        assert_eq!(state.current_piece(), messages::Piece::X);
+
+        let bytes = state.encode_to_vec();
+        let decoded = messages::GameState::decode(&bytes[..]).unwrap();
+        assert_eq!(decoded, state);
+        let replayed = Game::try_from(&decoded).unwrap();
+        assert_eq!(replayed.moves(), game.moves());
+        assert_eq!(replayed.first_piece(), Piece::O);
+        assert_eq!(replayed.winner(), None);
+        assert_eq!(decoded.history_digest, game.history_digest());
+
+        // A forfeit is kept too
+        game.forfeit(Piece::X).unwrap();
+        let replayed = Game::try_from(&messages::GameState::from(&game)).unwrap();
+        assert_eq!(replayed.forfeited_by(), Some(Piece::X));
+        assert_eq!(replayed.winner(), Some(Winner::O));
+    }
+
+    #[test]
+    fn only_the_moves_are_needed() {
+        let state = messages::GameState {
+            first_piece: messages::Piece::X as i32,
+            moves: vec![messages::Move {row: 1, col: 1}],
+            ..Default::default()
+        };
+        assert_eq!(Game::try_from(&state).unwrap().tiles()[1][1], Some(Piece::X));
+    }
+
+    #[test]
+    fn invalid_states() {
+        let mut state = messages::GameState {
+            first_piece: messages::Piece::X as i32,
+            moves: vec![messages::Move {row: 1, col: 1}, messages::Move {row: 1, col: 1}],
+            ..Default::default()
+        };
+        assert!(matches!(Game::try_from(&state),
+            Err(StateError::InvalidMove {index: 1, error: MoveError::TileNotEmpty {..}})));
+
+        state.moves.pop();
+        state.history_digest = 1;
+        assert!(matches!(Game::try_from(&state), Err(StateError::Mismatch("history_digest"))));
+        state.history_digest = 0;
+        state.result = messages::GameResult::Tie as i32;
+        assert!(matches!(Game::try_from(&state), Err(StateError::Mismatch("result"))));
+
+        state.result = 0;
+        state.first_piece = 7;
+        assert!(matches!(Game::try_from(&state), Err(StateError::InvalidField("first_piece"))));
+        state.first_piece = messages::Piece::Unspecified as i32;
+        let error = Game::try_from(&state).unwrap_err();
+        assert!(matches!(error, StateError::InvalidField("first_piece")));
+        assert_eq!(error.code(), 600);
+        assert_eq!(error.to_string(), "the first_piece field has a value that can't be used");
+    }
+}
@@ -0,0 +1,258 @@
+// The game as a Python module, so that it can be played from Python scripts and notebooks, and
+// so that agents written in Python can be trained against the computer players. PyO3 generates
+// the code that lets Python create and use the Rust types marked with `#[pyclass]`.
+// More info: https://pyo3.rs
+//
+// Just like the web version, the rules and the computer players come straight from the terminal
+// version through `#[path]`, so Python plays by exactly the same rules.
+//
+// PyO3 turns the `///` comments into the docstrings that Python's `help()` shows, so those are
+// written for Python programmers.
+
+// The code that PyO3 generates refers to `::core`, which newer editions of Rust always have, but
+// this crate (like the rest of the game) uses the 2015 edition, which has to ask for it
+extern crate core;
+extern crate pyo3;
+extern crate thiserror;
+
+// The module only uses part of each module, so we tell Rust not to warn about the rest
+#[allow(dead_code)]
+#[path = "../../src/game.rs"]
+mod game;
+#[allow(dead_code)]
+#[path = "../../src/history.rs"]
+mod history;
+#[allow(dead_code)]
+#[path = "../../src/random.rs"]
+mod random;
+#[allow(dead_code)]
+#[path = "../../src/engine.rs"]
+mod engine;
+#[allow(dead_code)]
+#[path = "../../src/tt.rs"]
+mod tt;
+#[allow(dead_code)]
+#[path = "../../src/solve.rs"]
+mod solve;
+
+use pyo3::create_exception;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+use engine::Difficulty;
+use game::{Piece, Winner};
+use random::Rng;
+
+// Each kind of `MoveError` is its own exception, so that Python can catch only the ones it
+// expects. They all inherit from `MoveError`, which inherits from Python's `ValueError`.
+create_exception!(tictactoe, MoveError, PyValueError, "A move that can't be made");
+create_exception!(tictactoe, GameAlreadyOver, MoveError, "The game was already over");
+create_exception!(tictactoe, InvalidPosition, MoveError, "The row or column was 3 or more");
+create_exception!(tictactoe, TileNotEmpty, MoveError, "The tile already had a piece on it");
+
+/// A game of tic-tac-toe. Rows and columns are numbered from 0 to 2, starting at the top left
+/// corner. Pieces and results are the strings "x", "o" and "tie".
+#[pyclass(name = "Game", from_py_object)]
+#[derive(Clone)]
+pub struct PyGame {
+    game: game::Game,
+}
+
+#[pymethods]
+impl PyGame {
+    /// Starts a new game. x moves first unless `first` is "o".
+    #[new]
+    #[pyo3(signature = (first = "x"))]
+    fn new(first: &str) -> PyResult<Self> {
+        let first = match first {
+            "x" => Piece::X,
+            "o" => Piece::O,
+            _ => return Err(PyValueError::new_err(format!("unknown piece {:?}", first))),
+        };
+        Ok(PyGame {game: game::Game::starting_with(first)})
+    }
+
+    /// Places the current player's piece at the given row and column. Raises one of the
+    /// `MoveError` exceptions if the move can't be made, in which case nothing changes.
+    fn make_move(&mut self, row: usize, col: usize) -> PyResult<()> {
+        self.game.make_move(row, col).map_err(|error| match error {
+            game::MoveError::GameAlreadyOver => {
+                GameAlreadyOver::new_err("the game is already over")
+            },
+            game::MoveError::InvalidPosition {row, col} => {
+                InvalidPosition::new_err(format!("({}, {}) is not on the board", row, col))
+            },
+            game::MoveError::TileNotEmpty {other_piece, row, col, ..} => {
+                TileNotEmpty::new_err(format!("({}, {}) already has {} on it", row, col,
+                    piece_name(other_piece)))
+            },
+        })
+    }
+
+    /// Takes back the last move and returns where it was made, or None if there were no moves
+    /// to take back
+    fn undo(&mut self) -> Option<(usize, usize)> {
+        self.game.undo()
+    }
+
+    /// Every move made so far, as (row, col) pairs in the order they were made
+    fn moves(&self) -> Vec<(usize, usize)> {
+        self.game.moves().to_vec()
+    }
+
+    /// The moves that can be made now: every empty tile, or none once the game is over
+    fn legal_moves(&self) -> Vec<(usize, usize)> {
+        if self.game.is_finished() {
+            Vec::new()
+        }
+        else {
+            solve::empty_tiles(&self.game)
+        }
+    }
+
+    /// The board as a list of rows, with "x", "o" or None for each tile
+    fn board(&self) -> Vec<Vec<Option<&'static str>>> {
+        self.game.tiles().iter()
+            .map(|row| row.iter().map(|tile| tile.map(piece_name)).collect())
+            .collect()
+    }
+
+    /// The piece whose turn it is
+    #[getter]
+    fn current_piece(&self) -> &'static str {
+        piece_name(self.game.current_piece())
+    }
+
+    /// "x", "o" or "tie" once the game is over, and None before that
+    #[getter]
+    fn winner(&self) -> Option<&'static str> {
+        self.game.winner().map(winner_name)
+    }
+
+    #[getter]
+    fn is_finished(&self) -> bool {
+        self.game.is_finished()
+    }
+
+    /// How the game ends from here if both players play perfectly: "x", "o" or "tie"
+    fn perfect_result(&self) -> &'static str {
+        winner_name(engine::perfect_result(&self.game))
+    }
+
+    fn copy(&self) -> Self {
+        self.clone()
+    }
+
+    fn __copy__(&self) -> Self {
+        self.clone()
+    }
+
+    // What `print(game)` shows: a line for each row, with a dot for each empty tile
+    fn __str__(&self) -> String {
+        self.game.tiles().iter()
+            .map(|row| row.iter().map(|tile| tile.map_or(".", piece_name)).collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn __repr__(&self) -> String {
+        let moves: Vec<_> = self.game.moves().iter()
+            .map(|(row, col)| format!("({}, {})", row, col))
+            .collect();
+        format!("<Game moves=[{}]>", moves.join(", "))
+    }
+}
+
+/// A computer player. The difficulty is "easy" (plays at random), "medium" (wins and blocks when
+/// it can) or "perfect" (never loses). The same seed always makes the same choices, which makes
+/// experiments repeatable.
+#[pyclass(name = "Engine")]
+pub struct PyEngine {
+    difficulty: Difficulty,
+    rng: Rng,
+}
+
+#[pymethods]
+impl PyEngine {
+    #[new]
+    #[pyo3(signature = (difficulty = "perfect", seed = None))]
+    fn new(difficulty: &str, seed: Option<u64>) -> PyResult<Self> {
+        let difficulty = Difficulty::from_name(difficulty).ok_or_else(|| {
+            PyValueError::new_err(format!("unknown difficulty {:?}", difficulty))
+        })?;
+        Ok(PyEngine {difficulty, rng: Rng::new(seed.unwrap_or_else(random::time_seed))})
+    }
+
+    /// The move this player would make for the current player of `game`, as a (row, col) pair.
+    /// The move isn't made. Raises `GameAlreadyOver` if the game is over.
+    fn choose_move(&mut self, game: &PyGame) -> PyResult<(usize, usize)> {
+        if game.game.is_finished() {
+            return Err(GameAlreadyOver::new_err("the game is already over"));
+        }
+        Ok(engine::choose_move(&game.game, self.difficulty, &mut self.rng))
+    }
+
+    #[getter]
+    fn difficulty(&self) -> &'static str {
+        self.difficulty.name()
+    }
+}
+
+fn piece_name(piece: Piece) -> &'static str {
+    match piece {
+        Piece::X => "x",
+        Piece::O => "o",
+    }
+}
+
+fn winner_name(winner: Winner) -> &'static str {
+    match winner {
+        Winner::X => "x",
+        Winner::O => "o",
+        Winner::Tie => "tie",
+    }
+}
+
+/// The rules and computer players of tic-tac-toe
+#[pymodule]
+fn tictactoe(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    let py = module.py();
+    module.add_class::<PyGame>()?;
+    module.add_class::<PyEngine>()?;
+    module.add("MoveError", py.get_type::<MoveError>())?;
+    module.add("GameAlreadyOver", py.get_type::<GameAlreadyOver>())?;
+    module.add("InvalidPosition", py.get_type::<InvalidPosition>())?;
+    module.add("TileNotEmpty", py.get_type::<TileNotEmpty>())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn play_against_the_engine() {
+        Python::initialize();
+        Python::attach(|py| {
+            let mut game = PyGame::new("x").unwrap();
+            game.make_move(1, 1).unwrap();
+            let error = game.make_move(1, 1).unwrap_err();
+            assert!(error.is_instance_of::<TileNotEmpty>(py));
+            assert!(error.is_instance_of::<MoveError>(py));
+            assert!(game.make_move(3, 0).unwrap_err().is_instance_of::<InvalidPosition>(py));
+            assert_eq!(game.board()[1][1], Some("x"));
+            assert_eq!(game.legal_moves().len(), 8);
+
+            // Two perfect players always tie
+            let mut engine = PyEngine::new("perfect", Some(1)).unwrap();
+            while !game.is_finished() {
+                let (row, col) = engine.choose_move(&game).unwrap();
+                game.make_move(row, col).unwrap();
+            }
+            assert_eq!(game.winner(), Some("tie"));
+            assert!(game.legal_moves().is_empty());
+            assert!(engine.choose_move(&game).unwrap_err().is_instance_of::<GameAlreadyOver>(py));
+            assert!(PyEngine::new("impossible", None).is_err());
+        });
+    }
+}
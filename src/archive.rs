@@ -0,0 +1,1063 @@
+// The archive is a plain text file that finished games are appended to. Each line is one record
+// made of a record kind followed by `key=value` fields. A plain text format means you can open the
+// archive in any text editor and see exactly what was saved.
+//
+// A single game looks like this:
+//
+//     game first=x winner=x moves=2B,1A,1C,3A,2A,3C,2C
+//
+// Players who chose a name are linked to the game with `x_name` and `o_name` fields. Spaces and
+// `%` in a name are written as `%20` and `%25` so that a name can't be mistaken for the next
+// field: `game first=x winner=o x_name=Ada o_name=Grace%20H moves=...`
+//
+// Games played by the computer record the seed of the random number generator, so that they can be
+// played again exactly the same way with `--seed`: `game first=x winner=x seed=42 moves=...`
+//
+// If a player lost by forfeiting (for example by running out of time), a `forfeit` field records
+// which piece they were using: `game first=x winner=o forfeit=x moves=2B`
+//
+// Computer games that were ended as soon as their result was certain record why in an
+// `adjudicated` field: `dead` when nobody could get three in a row anymore, or `solved` when the
+// solver proved the result. The moves stop where the game was ended:
+// `game first=x winner=tie adjudicated=solved moves=`
+//
+// A game that was paused records when each pause started (in seconds since 1970-01-01, UTC) and
+// how many seconds it lasted: `game first=x winner=x pauses=1792108800+300 moves=...`
+//
+// Games played at the prompt record when each move was made (in seconds since 1970-01-01, UTC) and
+// how many milliseconds its player thought about it, in the same order as the moves. A move that
+// wasn't timed is written as `-`: `game first=x winner=x times=1792108804+4213,-,... moves=...`
+//
+// Tags (see `Tags` in game.rs) are written as `KEY:VALUE` pairs separated by `;`. The tags of the
+// game are in a `tags` field, and the tags of each move are in a `move_tags` field, in the same
+// order as the moves and separated by commas, with `-` for a move without tags:
+// `game first=x winner=x tags=event:club%20night move_tags=-,engine:perfect moves=...`
+// Just like in names, the characters that would end the value early (spaces, `%`, `,`, `;` and
+// `:`) are written as `%` followed by their number in hexadecimal.
+//
+// Typing `rematch` after a game starts another one with the players trading pieces, as part of
+// the same match. Each game of the rematch records which rematch it was, so that the games can be
+// put back together without ever changing what was already saved:
+//
+//     game first=x winner=x x_name=Ada o_name=Grace moves=...
+//     game first=x winner=tie x_name=Grace o_name=Ada rematch=1 moves=...
+//
+// Every game records the digest of its moves (see `Game::history_digest`), as 16 hexadecimal
+// digits, so that moves that were changed after the game was saved can be found:
+// `game first=x winner=x digest=5b2e0c9a8f1d7e63 moves=...`
+//
+// A game that was saved part way through (by pressing Ctrl-C) has no winner yet:
+// `game first=x winner=none moves=2B,1A`
+//
+// A best-of-N series is stored as one match entity: a `match` line, the games that were played,
+// and an `end` line to close it.
+//
+//     match best_of=3 games=2 x=2 o=0 ties=0 winner=x
+//     game first=x winner=x moves=...
+//     game first=o winner=x moves=...
+//     end
+//
+// The `match` line of a series that was played as a rematch has the `rematch` field instead of its
+// games.
+
+use std::fmt;
+use std::io::{self, Write};
+use std::str;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use game::{Adjudication, Game, MoveTime, Pause, Piece, Tags, Winner};
+use player::{PlayerProfile, Players};
+use series::Series;
+use storage::{self, Recovery};
+
+#[derive(Debug, Clone)]
+pub struct Archive {
+    path: PathBuf,
+    /// The seed saved with every game, if the games depend on random numbers
+    seed: Option<u64>,
+    /// The rematch number saved with every game or series, if they are part of a rematch
+    rematch: Option<u32>,
+}
+
+impl Archive {
+    // `impl Into<PathBuf>` lets the caller pass a String, a &str, a PathBuf or anything else that
+    // can be converted into a path.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {path: path.into(), seed: None, rematch: None}
+    }
+
+    // The same archive, except that every game saved to it records the given seed
+    pub fn with_seed(self, seed: u64) -> Self {
+        Self {seed: Some(seed), ..self}
+    }
+
+    // The same archive, except that every game or series saved to it is recorded as the given
+    // rematch (1 for the first one)
+    pub fn with_rematch(self, rematch: u32) -> Self {
+        Self {rematch: Some(rematch), ..self}
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    // Appends a single finished game to the archive. Returns what was done about the archive if it
+    // turned out to be corrupt (see storage.rs).
+    pub fn record_game(&self, game: &Game, players: &Players) -> io::Result<Option<Recovery>> {
+        // We build the whole record in memory first so that we only write to the file once
+        let mut record = Vec::new();
+        write_game(&mut record, game, players, self.seed, self.rematch)?;
+        self.append(&record)
+    }
+
+    // Appends a finished series and all of its games to the archive as one match entity
+    pub fn record_series(&self, series: &Series, players: &Players)
+        -> io::Result<Option<Recovery>> {
+
+        let mut record = Vec::new();
+        write_series(&mut record, series, players, self.seed, self.rematch)?;
+        self.append(&record)
+    }
+
+    // Reads back every game in the archive, including the games of each series. An archive that
+    // doesn't exist yet has no games in it. Lines that we don't understand are skipped, so that an
+    // archive written by a newer version of the program can still be read.
+    pub fn games(&self) -> io::Result<Vec<ArchivedGame>> {
+        self.load().map(|(games, _)| games)
+    }
+
+    // The same as `games`, along with what was done about the archive if it was corrupt
+    pub fn load(&self) -> io::Result<(Vec<ArchivedGame>, Option<Recovery>)> {
+        let loaded = storage::read(&self.path)?;
+        let games = loaded.text.map_or_else(Vec::new, |text| parse_games(&text));
+        Ok((games, loaded.recovery))
+    }
+
+    fn append(&self, record: &[u8]) -> io::Result<Option<Recovery>> {
+        // The whole archive is saved again with the record at its end, instead of adding to the
+        // file in place, so that a save that is cut off can't leave half of a game behind (see
+        // storage.rs). The games saved before are never changed.
+        let loaded = storage::read(&self.path)?;
+        let mut text = loaded.text.unwrap_or_default().into_bytes();
+        text.extend_from_slice(record);
+        storage::save(&self.path, &text)?;
+        Ok(loaded.recovery)
+    }
+}
+
+// A game read back from the archive, along with the names of the players who played it
+#[derive(Debug, Clone)]
+pub struct ArchivedGame {
+    pub game: Game,
+    /// Only the names are saved in the archive, so the glyphs are always None
+    pub players: Players,
+}
+
+// A search for the games in the archive with a tag, written as `KEY` to find the tag with any value
+// or `KEY=VALUE` to find only that value
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TagQuery {
+    pub key: String,
+    pub value: Option<String>,
+}
+
+impl TagQuery {
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut parts = text.splitn(2, '=');
+        let key = parts.next().filter(|key| is_tag_key(key))?.to_string();
+        match parts.next() {
+            Some("") => None,
+            value => Some(Self {key, value: value.map(str::to_string)}),
+        }
+    }
+
+    // Returns whether the game itself has the tag, along with the moves that have it (counting
+    // from 0). The game matches the search if either one does.
+    pub fn find(&self, game: &Game) -> (bool, Vec<usize>) {
+        let moves = (0..game.moves().len())
+            .filter(|&index| game.move_tags(index).is_some_and(|tags| self.matches(tags)))
+            .collect();
+        (self.matches(game.tags()), moves)
+    }
+
+    fn matches(&self, tags: &Tags) -> bool {
+        match (tags.get(&self.key), &self.value) {
+            (Some(value), Some(wanted)) => value == wanted,
+            (Some(_), None) => true,
+            (None, _) => false,
+        }
+    }
+}
+
+// Shows the search the way it is written on the command line
+impl fmt::Display for TagQuery {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.value {
+            Some(ref value) => write!(f, "{}={}", self.key, value),
+            None => write!(f, "{}", self.key),
+        }
+    }
+}
+
+// Reads a tag written as `KEY=VALUE`, like the ones given with `--tag`
+pub fn parse_tag(text: &str) -> Option<(String, String)> {
+    let mut parts = text.splitn(2, '=');
+    let key = parts.next().filter(|key| is_tag_key(key))?;
+    let value = parts.next().filter(|value| !value.is_empty())?;
+    Some((key.to_string(), value.to_string()))
+}
+
+// Keys are kept short and simple, since they are what the tags are searched by: letters, digits,
+// `-`, `_` and `.`
+fn is_tag_key(key: &str) -> bool {
+    !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || "-_.".contains(c))
+}
+
+// Writing to any `impl Write` instead of a file directly lets us test this code using a Vec<u8>
+fn write_game(out: &mut impl Write, game: &Game, players: &Players, seed: Option<u64>,
+    rematch: Option<u32>) -> io::Result<()> {
+
+    // The `?` operator returns early from this function if writing fails
+    write!(out, "game first={} winner={} ", piece_name(game.first_piece()),
+        winner_name(game.winner()))?;
+    if let Some(piece) = game.forfeited_by() {
+        write!(out, "forfeit={} ", piece_name(piece))?;
+    }
+    if let Some(reason) = game.adjudicated() {
+        write!(out, "adjudicated={} ", adjudication_name(reason))?;
+    }
+    for &piece in &[Piece::X, Piece::O] {
+        if let Some(ref name) = players.profile(piece).name {
+            write!(out, "{}_name={} ", piece_name(piece), encode_name(name))?;
+        }
+    }
+    if let Some(seed) = seed {
+        write!(out, "seed={} ", seed)?;
+    }
+    if let Some(rematch) = rematch {
+        write!(out, "rematch={} ", rematch)?;
+    }
+    if !game.pauses().is_empty() {
+        let pauses: Vec<_> = game.pauses().iter()
+            .map(|pause| format!("{}+{}", pause.started, pause.seconds))
+            .collect();
+        write!(out, "pauses={} ", pauses.join(","))?;
+    }
+    let timed = (0..game.moves().len()).rposition(|index| game.move_time(index).is_some());
+    if let Some(last) = timed {
+        let times: Vec<_> = (0..=last).map(|index| match game.move_time(index) {
+            Some(time) => format!("{}+{}", time.made, time.thinking.as_millis()),
+            None => "-".to_string(),
+        }).collect();
+        write!(out, "times={} ", times.join(","))?;
+    }
+    if !game.tags().is_empty() {
+        write!(out, "tags={} ", write_tags(game.tags()))?;
+    }
+    let tagged = (0..game.moves().len()).rposition(|index| game.move_tags(index).is_some());
+    if let Some(last) = tagged {
+        let tags: Vec<_> = (0..=last).map(|index| match game.move_tags(index) {
+            Some(tags) => write_tags(tags),
+            None => "-".to_string(),
+        }).collect();
+        write!(out, "move_tags={} ", tags.join(","))?;
+    }
+    write!(out, "digest={} moves=", write_digest(game.history_digest()))?;
+    for (i, (row, col)) in game.moves().iter().enumerate() {
+        if i > 0 {
+            write!(out, ",")?;
+        }
+        write!(out, "{}{}", row + 1, (b'A' + col as u8) as char)?;
+    }
+    writeln!(out)
+}
+
+fn write_series(out: &mut impl Write, series: &Series, players: &Players, seed: Option<u64>,
+    rematch: Option<u32>) -> io::Result<()> {
+
+    let score = series.score();
+    write!(out, "match best_of={} games={} x={} o={} ties={} winner={}", series.best_of(),
+        series.games().len(), score.x, score.o, score.ties, winner_name(series.winner()))?;
+    if let Some(rematch) = rematch {
+        write!(out, " rematch={}", rematch)?;
+    }
+    writeln!(out)?;
+    // The games are part of the match, so only the match line says which rematch it was
+    for game in series.games() {
+        write_game(out, game, players, seed, None)?;
+    }
+    writeln!(out, "end")
+}
+
+// Reads every game in the text of an archive, skipping the lines that aren't games we understand
+pub fn parse_games(text: &str) -> Vec<ArchivedGame> {
+    text.lines().filter_map(parse_game).collect()
+}
+
+// A game read back for `archive audit`, along with the winner that was saved with it
+#[derive(Debug, Clone)]
+pub struct SavedGame {
+    /// The line of the archive that the game is on, counting from 1
+    pub line: usize,
+    /// The game as its moves play out, which isn't over if the saved winner is wrong about that
+    pub archived: ArchivedGame,
+    pub saved: Option<Winner>,
+}
+
+// Reads every game in the archive, keeping the winner that was saved with each one even when the
+// moves lead somewhere else. Lines that can't be read at all are skipped, since `verify` is the
+// one that reports those.
+pub fn parse_saved_games(text: &str) -> Vec<SavedGame> {
+    text.lines().enumerate().filter_map(|(i, line)| {
+        read_game(line).map(|(archived, saved)| SavedGame {line: i + 1, archived, saved})
+    }).collect()
+}
+
+// Reads a `game` line by playing its moves on a new board. That way the game we get back is
+// exactly the one that was saved, and a line with an impossible game in it is skipped. Games that
+// were saved before they were finished are skipped too, since they have no result yet.
+fn parse_game(line: &str) -> Option<ArchivedGame> {
+    read_game(line).map(|(archived, _)| archived).filter(|archived| archived.game.is_finished())
+}
+
+// Plays the moves of a `game` line, returning the game along with the winner that was saved with
+// it. The game isn't over if it was saved part way through.
+fn read_game(line: &str) -> Option<(ArchivedGame, Option<Winner>)> {
+    let mut fields = line.split(' ');
+    if fields.next() != Some("game") {
+        return None;
+    }
+
+    let (mut first, mut winner, mut forfeit, mut moves) = (None, None, None, None);
+    let (mut adjudicated, mut pauses, mut times) = (None, Vec::new(), Vec::new());
+    let (mut tags, mut move_tags) = (Tags::new(), Vec::new());
+    let (mut x, mut o) = (PlayerProfile::default(), PlayerProfile::default());
+    for field in fields {
+        let mut parts = field.splitn(2, '=');
+        match (parts.next(), parts.next()) {
+            (Some("first"), Some(value)) => first = parse_piece(value),
+            (Some("winner"), Some(value)) => winner = parse_winner(value),
+            (Some("forfeit"), Some(value)) => forfeit = parse_piece(value),
+            (Some("adjudicated"), Some(value)) => adjudicated = parse_adjudication(value),
+            (Some("x_name"), Some(value)) => x.name = Some(decode_name(value)),
+            (Some("o_name"), Some(value)) => o.name = Some(decode_name(value)),
+            (Some("pauses"), Some(value)) => pauses = parse_pauses(value).unwrap_or_default(),
+            (Some("times"), Some(value)) => times = parse_times(value).unwrap_or_default(),
+            (Some("tags"), Some(value)) => tags = parse_tags(value).unwrap_or_default(),
+            (Some("move_tags"), Some(value)) => {
+                move_tags = parse_move_tags(value).unwrap_or_default()
+            },
+            (Some("moves"), Some(value)) => moves = Some(value),
+            _ => {},
+        }
+    }
+
+    let mut game = Game::starting_with(first?);
+    for (i, notation) in moves?.split(',').filter(|m| !m.is_empty()).enumerate() {
+        let (row, col) = parse_position(notation)?;
+        game.make_move(row, col).ok()?;
+        if let Some(&Some(time)) = times.get(i) {
+            game.time_last_move(time);
+        }
+        for (key, value) in move_tags.get(i).into_iter().flatten() {
+            game.tag_last_move(key.as_str(), value.as_str());
+        }
+    }
+    for (key, value) in tags {
+        game.tag(key, value);
+    }
+    if let Some(piece) = forfeit {
+        game.forfeit(piece).ok()?;
+    }
+    // The winner is worked out again by playing the moves, unless the game was adjudicated before
+    // the moves could decide it
+    if let Some(reason) = adjudicated {
+        game.adjudicate(winner??, reason).ok()?;
+    }
+    for pause in pauses {
+        game.record_pause(pause);
+    }
+    // A winner that is missing or can't be read counts as no winner at all
+    Some((ArchivedGame {game, players: Players::new(x, o)}, winner.flatten()))
+}
+
+// Something wrong with a line of an archive, found by `verify`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Problem {
+    /// The line isn't a record that the archive has
+    UnknownRecord,
+    /// A field that every record of its kind has is missing
+    MissingField(&'static str),
+    /// A field has a value that it can't have
+    InvalidField { field: &'static str, value: String },
+    /// A move isn't written as a position on the board. Moves are numbered from 1.
+    InvalidMove { number: usize, notation: String },
+    /// A move can't be played, because its tile is taken or the game is already over
+    IllegalMove { number: usize, notation: String },
+    /// Playing the moves gives a different result from the one that was saved
+    WrongWinner { saved: Option<Winner>, actual: Option<Winner> },
+    /// The moves don't have the digest that was saved with them, so they were changed after the
+    /// game was saved
+    WrongDigest { saved: u64, actual: u64 },
+    /// The player who forfeited couldn't have forfeited at that point
+    InvalidForfeit(Piece),
+    /// The game couldn't have been adjudicated with its result for this reason
+    InvalidAdjudication(Adjudication),
+    /// The totals on a `match` line don't match the games that follow it
+    WrongMatchTotals,
+    /// A `match` line comes before the `end` of the match before it, or there is no `end`
+    UnclosedMatch,
+    /// An `end` line without a `match` before it
+    UnexpectedEnd,
+}
+
+// The result of checking every line of an archive
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Verification {
+    /// The number of games that were checked
+    pub games: usize,
+    /// Everything that was found to be wrong, along with the line it is on (counting from 1)
+    pub problems: Vec<(usize, Problem)>,
+}
+
+// Checks an archive line by line. Every game is played again from its moves, so that a move that
+// couldn't have been played, or a result that doesn't match the moves, is found on the line where
+// it happens. Matches are checked against the games inside them. Unlike `parse_games`, nothing is
+// skipped: a line that isn't a record at all is a problem too, since it most likely means that the
+// file was damaged. `solve` is how the game ends with perfect play (see engine.rs), which is what
+// the result of a game that was adjudicated as solved has to be.
+pub fn verify(text: &str, solve: fn(&Game) -> Winner) -> Verification {
+    let mut verification = Verification::default();
+    // The line and the totals of the match that is still open, along with the totals of the
+    // games played in it so far
+    let mut open_match: Option<(usize, [u32; 4], [u32; 4])> = None;
+
+    for (i, line) in text.lines().enumerate() {
+        let number = i + 1;
+        let kind = line.split(' ').next().unwrap_or_default();
+        match kind {
+            "game" => {
+                verification.games += 1;
+                match verify_game(line, solve) {
+                    Ok(winner) => if let Some((_, _, ref mut totals)) = open_match {
+                        totals[0] += 1;
+                        match winner {
+                            Some(Winner::X) => totals[1] += 1,
+                            Some(Winner::O) => totals[2] += 1,
+                            Some(Winner::Tie) => totals[3] += 1,
+                            None => {},
+                        }
+                    },
+                    Err(problem) => verification.problems.push((number, problem)),
+                }
+            },
+            "match" => {
+                if let Some((start, _, _)) = open_match {
+                    verification.problems.push((start, Problem::UnclosedMatch));
+                }
+                match verify_match(line) {
+                    Ok(saved) => open_match = Some((number, saved, [0; 4])),
+                    Err(problem) => {
+                        verification.problems.push((number, problem));
+                        open_match = None;
+                    },
+                }
+            },
+            "end" => match open_match.take() {
+                Some((start, saved, totals)) if saved != totals => {
+                    verification.problems.push((start, Problem::WrongMatchTotals));
+                },
+                Some(_) => {},
+                None => verification.problems.push((number, Problem::UnexpectedEnd)),
+            },
+            // Blank lines don't hurt anything
+            "" => {},
+            _ => verification.problems.push((number, Problem::UnknownRecord)),
+        }
+    }
+    if let Some((start, _, _)) = open_match {
+        verification.problems.push((start, Problem::UnclosedMatch));
+    }
+    // Problems with a match are only found at its end, so they are put back in line order
+    verification.problems.sort_by_key(|&(line, _)| line);
+    verification
+}
+
+// Checks a `game` line by playing its moves, returning the result of the game
+fn verify_game(line: &str, solve: fn(&Game) -> Winner) -> Result<Option<Winner>, Problem> {
+    let (mut first, mut winner, mut forfeit, mut moves) = (None, None, None, None);
+    let (mut adjudicated, mut pauses, mut times, mut rematch) = (None, None, None, None);
+    let (mut tags, mut move_tags, mut digest) = (None, None, None);
+    for field in line.split(' ').skip(1) {
+        let mut parts = field.splitn(2, '=');
+        match (parts.next(), parts.next()) {
+            (Some("first"), Some(value)) => first = Some(value),
+            (Some("winner"), Some(value)) => winner = Some(value),
+            (Some("forfeit"), Some(value)) => forfeit = Some(value),
+            (Some("adjudicated"), Some(value)) => adjudicated = Some(value),
+            (Some("pauses"), Some(value)) => pauses = Some(value),
+            (Some("times"), Some(value)) => times = Some(value),
+            (Some("rematch"), Some(value)) => rematch = Some(value),
+            (Some("tags"), Some(value)) => tags = Some(value),
+            (Some("move_tags"), Some(value)) => move_tags = Some(value),
+            (Some("digest"), Some(value)) => digest = Some(value),
+            (Some("moves"), Some(value)) => moves = Some(value),
+            // Names and seeds can be anything
+            _ => {},
+        }
+    }
+
+    let invalid = |field, value: &str| Problem::InvalidField {field, value: value.to_string()};
+    let first = first.ok_or(Problem::MissingField("first"))?;
+    let first = parse_piece(first).ok_or_else(|| invalid("first", first))?;
+    let winner = winner.ok_or(Problem::MissingField("winner"))?;
+    let saved = parse_winner(winner).ok_or_else(|| invalid("winner", winner))?;
+    let forfeit = match forfeit {
+        Some(value) => Some(parse_piece(value).ok_or_else(|| invalid("forfeit", value))?),
+        None => None,
+    };
+    let adjudicated = match adjudicated {
+        Some(value) => {
+            Some(parse_adjudication(value).ok_or_else(|| invalid("adjudicated", value))?)
+        },
+        None => None,
+    };
+    if let Some(value) = pauses {
+        parse_pauses(value).ok_or_else(|| invalid("pauses", value))?;
+    }
+    if let Some(value) = rematch {
+        parse_rematch(value).ok_or_else(|| invalid("rematch", value))?;
+    }
+    if let Some(value) = tags {
+        parse_tags(value).ok_or_else(|| invalid("tags", value))?;
+    }
+    let digest = match digest {
+        Some(value) => Some(parse_digest(value).ok_or_else(|| invalid("digest", value))?),
+        None => None,
+    };
+
+    let mut game = Game::starting_with(first);
+    let moves = moves.ok_or(Problem::MissingField("moves"))?;
+    for (i, notation) in moves.split(',').filter(|m| !m.is_empty()).enumerate() {
+        let number = i + 1;
+        let (row, col) = parse_position(notation).ok_or_else(|| {
+            Problem::InvalidMove {number, notation: notation.to_string()}
+        })?;
+        game.make_move(row, col).map_err(|_| {
+            Problem::IllegalMove {number, notation: notation.to_string()}
+        })?;
+    }
+    // Games saved before the digest was added don't have one, so there is nothing to check
+    if let Some(saved) = digest {
+        let actual = game.history_digest();
+        if saved != actual {
+            return Err(Problem::WrongDigest {saved, actual});
+        }
+    }
+    if let Some(piece) = forfeit {
+        game.forfeit(piece).map_err(|_| Problem::InvalidForfeit(piece))?;
+    }
+    // The saved result has to be the one the game was certain to end with
+    if let Some(reason) = adjudicated {
+        let certain = match (saved, reason) {
+            _ if game.is_finished() => None,
+            (Some(Winner::Tie), Adjudication::DeadPosition) if game.is_dead() => saved,
+            (Some(winner), Adjudication::Solved) if solve(&game) == winner => saved,
+            _ => None,
+        };
+        let winner = certain.ok_or(Problem::InvalidAdjudication(reason))?;
+        game.adjudicate(winner, reason).expect("the game isn't over yet");
+    }
+
+    // Every time belongs to one of the moves
+    if let Some(value) = times {
+        match parse_times(value) {
+            Some(ref times) if times.len() <= game.moves().len() => {},
+            _ => return Err(invalid("times", value)),
+        }
+    }
+    // Just like the times, every move's tags belong to one of the moves
+    if let Some(value) = move_tags {
+        match parse_move_tags(value) {
+            Some(ref tags) if tags.len() <= game.moves().len() => {},
+            _ => return Err(invalid("move_tags", value)),
+        }
+    }
+
+    if game.winner() != saved {
+        return Err(Problem::WrongWinner {saved, actual: game.winner()});
+    }
+    Ok(saved)
+}
+
+// Reads the totals of a `match` line: the number of games, x's wins, o's wins and the ties
+pub fn verify_match(line: &str) -> Result<[u32; 4], Problem> {
+    let names = ["games", "x", "o", "ties"];
+    let mut totals = [None; 4];
+    for field in line.split(' ').skip(1) {
+        let mut parts = field.splitn(2, '=');
+        if let (Some(name), Some(value)) = (parts.next(), parts.next()) {
+            if let Some(i) = names.iter().position(|&known| known == name) {
+                totals[i] = Some(value.parse().map_err(|_| {
+                    Problem::InvalidField {field: names[i], value: value.to_string()}
+                })?);
+            }
+            else if name == "rematch" && parse_rematch(value).is_none() {
+                return Err(Problem::InvalidField {field: "rematch", value: value.to_string()});
+            }
+        }
+    }
+
+    let mut result = [0; 4];
+    for (i, total) in totals.iter().enumerate() {
+        result[i] = total.ok_or(Problem::MissingField(names[i]))?;
+    }
+    Ok(result)
+}
+
+fn piece_name(piece: Piece) -> &'static str {
+    match piece {
+        Piece::X => "x",
+        Piece::O => "o",
+    }
+}
+
+fn parse_piece(name: &str) -> Option<Piece> {
+    match name {
+        "x" => Some(Piece::X),
+        "o" => Some(Piece::O),
+        _ => None,
+    }
+}
+
+// Positions are written as the row number followed by the column letter, e.g. 2B
+fn parse_position(notation: &str) -> Option<(usize, usize)> {
+    let bytes = notation.as_bytes();
+    if bytes.len() == 2 && (b'1'..=b'3').contains(&bytes[0]) && (b'A'..=b'C').contains(&bytes[1]) {
+        Some(((bytes[0] - b'1') as usize, (bytes[1] - b'A') as usize))
+    }
+    else {
+        None
+    }
+}
+
+// Pauses are written as `STARTED+SECONDS`, separated by commas
+fn parse_pauses(text: &str) -> Option<Vec<Pause>> {
+    text.split(',').map(|pause| {
+        let mut parts = pause.splitn(2, '+');
+        let started = parts.next()?.parse().ok()?;
+        let seconds = parts.next()?.parse().ok()?;
+        Some(Pause {started, seconds})
+    }).collect()
+}
+
+// Times are written as `MADE+MILLISECONDS`, or `-` for a move that wasn't timed, separated by
+// commas
+fn parse_times(text: &str) -> Option<Vec<Option<MoveTime>>> {
+    text.split(',').map(|time| {
+        if time == "-" {
+            return Some(None);
+        }
+        let mut parts = time.splitn(2, '+');
+        let made = parts.next()?.parse().ok()?;
+        let thinking = Duration::from_millis(parts.next()?.parse().ok()?);
+        Some(Some(MoveTime {made, thinking}))
+    }).collect()
+}
+
+// Tags are written as `KEY:VALUE`, separated by `;`
+fn write_tags(tags: &Tags) -> String {
+    let tags: Vec<_> = tags.iter()
+        .map(|(key, value)| format!("{}:{}", encode_tag(key), encode_tag(value)))
+        .collect();
+    tags.join(";")
+}
+
+fn parse_tags(text: &str) -> Option<Tags> {
+    text.split(';').map(|tag| {
+        let mut parts = tag.splitn(2, ':');
+        let key = decode_tag(parts.next()?)?;
+        let value = decode_tag(parts.next()?)?;
+        if is_tag_key(&key) { Some((key, value)) } else { None }
+    }).collect()
+}
+
+// The tags of each move, or `-` for a move without tags, separated by commas
+fn parse_move_tags(text: &str) -> Option<Vec<Tags>> {
+    text.split(',').map(|tags| match tags {
+        "-" => Some(Tags::new()),
+        tags => parse_tags(tags),
+    }).collect()
+}
+
+// Rematches are numbered from 1
+// A digest is always written with all 16 of its digits, so that every record lines up
+fn write_digest(digest: u64) -> String {
+    format!("{:016x}", digest)
+}
+
+fn parse_digest(text: &str) -> Option<u64> {
+    if text.len() != 16 {
+        return None;
+    }
+    u64::from_str_radix(text, 16).ok()
+}
+
+fn adjudication_name(reason: Adjudication) -> &'static str {
+    match reason {
+        Adjudication::DeadPosition => "dead",
+        Adjudication::Solved => "solved",
+    }
+}
+
+fn parse_adjudication(name: &str) -> Option<Adjudication> {
+    match name {
+        "dead" => Some(Adjudication::DeadPosition),
+        "solved" => Some(Adjudication::Solved),
+        _ => None,
+    }
+}
+
+fn parse_rematch(text: &str) -> Option<u32> {
+    text.parse().ok().filter(|&rematch| rematch > 0)
+}
+
+// Names are written with `%` escapes for the characters that would break up the record
+fn encode_name(name: &str) -> String {
+    name.replace('%', "%25").replace(' ', "%20")
+}
+
+fn decode_name(name: &str) -> String {
+    // `%25` is replaced last, so that a name containing "%20" comes back unchanged
+    name.replace("%20", " ").replace("%25", "%")
+}
+
+// Unlike names, tags can have any character in them, so everything that isn't plain text is
+// written as `%` and the number of each of its bytes
+fn encode_tag(text: &str) -> String {
+    let mut encoded = String::new();
+    for c in text.chars() {
+        if c.is_control() || "% ,;:=".contains(c) {
+            let mut bytes = [0; 4];
+            for byte in c.encode_utf8(&mut bytes).bytes() {
+                encoded.push_str(&format!("%{:02X}", byte));
+            }
+        }
+        else {
+            encoded.push(c);
+        }
+    }
+    encoded
+}
+
+// Returns None if a `%` isn't followed by two hexadecimal digits or the bytes aren't UTF-8
+fn decode_tag(text: &str) -> Option<String> {
+    let mut bytes = Vec::new();
+    let mut rest = text.as_bytes();
+    while let Some((&byte, after)) = rest.split_first() {
+        if byte == b'%' {
+            let hex = after.get(..2).filter(|hex| hex.iter().all(u8::is_ascii_hexdigit))?;
+            bytes.push(u8::from_str_radix(str::from_utf8(hex).ok()?, 16).ok()?);
+            rest = &after[2..];
+        }
+        else {
+            bytes.push(byte);
+            rest = after;
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+// The opposite of winner_name. Returns None if the name isn't one that winner_name gives, and
+// Some(None) for a game without a winner.
+fn parse_winner(name: &str) -> Option<Option<Winner>> {
+    match name {
+        "x" => Some(Some(Winner::X)),
+        "o" => Some(Some(Winner::O)),
+        "tie" => Some(Some(Winner::Tie)),
+        "none" => Some(None),
+        _ => None,
+    }
+}
+
+fn winner_name(winner: Option<Winner>) -> &'static str {
+    match winner {
+        Some(Winner::X) => "x",
+        Some(Winner::O) => "o",
+        Some(Winner::Tie) => "tie",
+        // A game that was never finished has no winner
+        None => "none",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use engine::perfect_result;
+
+    #[test]
+    fn game_record() {
+        let mut game = Game::new();
+        game.make_move(0, 0).unwrap();
+        game.make_move(1, 0).unwrap();
+        game.make_move(0, 1).unwrap();
+        game.make_move(1, 1).unwrap();
+        game.make_move(0, 2).unwrap();
+
+        let mut out = Vec::new();
+        write_game(&mut out, &game, &Players::default(), None, None).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(),
+            "game first=x winner=x digest=e3a3539ef4a1c781 moves=1A,2A,1B,2B,1C\n");
+
+        let mut out = Vec::new();
+        write_game(&mut out, &game, &Players::default(), Some(42), None).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(),
+            "game first=x winner=x seed=42 digest=e3a3539ef4a1c781 moves=1A,2A,1B,2B,1C\n");
+    }
+
+    #[test]
+    fn read_back_named_players() {
+        let mut game = Game::starting_with(Piece::O);
+        game.make_move(1, 1).unwrap();
+        game.forfeit(Piece::X).unwrap();
+        let ada = PlayerProfile {name: Some("Ada 100%".to_string()), glyph: None};
+        let players = Players::new(ada, PlayerProfile::default());
+
+        let mut out = Vec::new();
+        write_game(&mut out, &game, &players, None, None).unwrap();
+        let line = String::from_utf8(out).unwrap();
+        assert_eq!(line,
+            "game first=o winner=o forfeit=x x_name=Ada%20100%25 digest=c06092f838d6413f \
+            moves=2B\n");
+
+        let archived = parse_game(line.trim_end()).unwrap();
+        assert_eq!(archived.players, players);
+        assert_eq!(archived.game.moves(), game.moves());
+        assert_eq!(archived.game.winner(), Some(Winner::O));
+
+        // Unfinished games and other kinds of records are skipped
+        assert!(parse_game("game first=x winner=none moves=2B").is_none());
+        assert!(parse_game("match best_of=3 games=2 x=2 o=0 ties=0 winner=x").is_none());
+    }
+
+    #[test]
+    fn pauses_are_saved() {
+        let mut game = Game::new();
+        game.make_move(1, 1).unwrap();
+        game.record_pause(Pause {started: 1792108800, seconds: 300});
+        game.record_pause(Pause {started: 1792109400, seconds: 45});
+        game.forfeit(Piece::O).unwrap();
+
+        let mut out = Vec::new();
+        write_game(&mut out, &game, &Players::default(), None, None).unwrap();
+        let line = String::from_utf8(out).unwrap();
+        assert_eq!(line,
+            "game first=x winner=x forfeit=o pauses=1792108800+300,1792109400+45 \
+            digest=675a000dbb0010ee moves=2B\n");
+        assert_eq!(parse_game(line.trim_end()).unwrap().game.pauses(), game.pauses());
+
+        assert_eq!(verify(&line, perfect_result).problems, []);
+        let invalid = "game first=x winner=none pauses=soon moves=2B";
+        assert_eq!(verify(invalid, perfect_result).problems, [
+            (1, Problem::InvalidField {field: "pauses", value: "soon".to_string()}),
+        ]);
+    }
+
+    #[test]
+    fn tags_are_saved() {
+        let mut game = Game::new();
+        game.tag("event", "club night: 50% off");
+        game.make_move(1, 1).unwrap();
+        game.make_move(0, 0).unwrap();
+        game.tag_last_move("engine", "perfect");
+        game.tag_last_move("clock", "12.3s");
+        game.forfeit(Piece::X).unwrap();
+
+        let mut out = Vec::new();
+        write_game(&mut out, &game, &Players::default(), None, None).unwrap();
+        let line = String::from_utf8(out).unwrap();
+        assert_eq!(line, "game first=x winner=o forfeit=x tags=event:club%20night%3A%2050%25%20off \
+            move_tags=-,clock:12.3s;engine:perfect digest=3e7b19cc611d736c moves=2B,1A\n");
+
+        let archived = parse_game(line.trim_end()).unwrap().game;
+        assert_eq!(archived.tags(), game.tags());
+        assert_eq!(archived.move_tags(0), None);
+        assert_eq!(archived.move_tags(1), game.move_tags(1));
+        assert!(verify(&line, perfect_result).problems.is_empty());
+        let invalid = "game first=x winner=none move_tags=-,-,a:b moves=2B";
+        assert_eq!(verify(invalid, perfect_result).problems, [
+            (1, Problem::InvalidField {field: "move_tags", value: "-,-,a:b".to_string()}),
+        ]);
+
+        // Searching finds the tag on the game or on any of its moves
+        let query = TagQuery::parse("engine=perfect").unwrap();
+        assert_eq!(query.find(&archived), (false, vec![1]));
+        assert_eq!(TagQuery::parse("event").unwrap().find(&archived), (true, vec![]));
+        assert_eq!(TagQuery::parse("engine=random").unwrap().find(&archived), (false, vec![]));
+    }
+
+    #[test]
+    fn times_are_saved() {
+        let mut game = Game::new();
+        game.make_move(1, 1).unwrap();
+        game.make_move(0, 0).unwrap();
+        let time = MoveTime {made: 1792108804, thinking: Duration::from_millis(4213)};
+        game.time_last_move(time);
+        game.make_move(0, 1).unwrap();
+        game.forfeit(Piece::O).unwrap();
+
+        let mut out = Vec::new();
+        write_game(&mut out, &game, &Players::default(), None, None).unwrap();
+        let line = String::from_utf8(out).unwrap();
+        // The last move wasn't timed, so its time is left out instead of being written as `-`
+        assert_eq!(line,
+            "game first=x winner=x forfeit=o times=-,1792108804+4213 digest=db1d8ca9dea3fbf3 \
+            moves=2B,1A,1B\n");
+        let archived = parse_game(line.trim_end()).unwrap().game;
+        assert_eq!((archived.move_time(0), archived.move_time(1)), (None, Some(time)));
+
+        assert_eq!(verify(&line, perfect_result).problems, []);
+        let invalid = "game first=x winner=none times=-,-,5+1 moves=2B,1A";
+        assert_eq!(verify(invalid, perfect_result).problems, [
+            (1, Problem::InvalidField {field: "times", value: "-,-,5+1".to_string()}),
+        ]);
+    }
+
+    #[test]
+    fn rematches_are_saved() {
+        let mut game = Game::new();
+        game.make_move(1, 1).unwrap();
+        game.forfeit(Piece::O).unwrap();
+        let mut series = Series::starting_with(1, Piece::X);
+        series.record(game.clone());
+
+        let mut out = Vec::new();
+        write_game(&mut out, &game, &Players::default(), Some(7), Some(2)).unwrap();
+        write_series(&mut out, &series, &Players::default(), None, Some(3)).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert_eq!(text, "\
+game first=x winner=x forfeit=o seed=7 rematch=2 digest=675a000dbb0010ee moves=2B
+match best_of=1 games=1 x=1 o=0 ties=0 winner=x rematch=3
+game first=x winner=x forfeit=o digest=675a000dbb0010ee moves=2B
+end
+");
+        assert_eq!(parse_games(&text).len(), 2);
+        assert_eq!(verify(&text, perfect_result).problems, []);
+
+        let text = "\
+game first=x winner=none rematch=again moves=2B
+match best_of=1 games=0 x=0 o=0 ties=0 winner=none rematch=0
+";
+        assert_eq!(verify(text, perfect_result).problems, [
+            (1, Problem::InvalidField {field: "rematch", value: "again".to_string()}),
+            (2, Problem::InvalidField {field: "rematch", value: "0".to_string()}),
+        ]);
+    }
+
+    #[test]
+    fn adjudications_are_saved() {
+        let mut game = Game::new();
+        game.make_move(1, 1).unwrap();
+        game.adjudicate(Winner::Tie, Adjudication::Solved).unwrap();
+
+        let mut out = Vec::new();
+        write_game(&mut out, &game, &Players::default(), Some(7), None).unwrap();
+        let line = String::from_utf8(out).unwrap();
+        assert_eq!(line,
+            "game first=x winner=tie adjudicated=solved seed=7 digest=675a000dbb0010ee moves=2B\n");
+        let archived = parse_game(line.trim_end()).unwrap().game;
+        assert_eq!(archived.winner(), Some(Winner::Tie));
+        assert_eq!(archived.adjudicated(), Some(Adjudication::Solved));
+        assert_eq!(verify(&line, perfect_result).problems, []);
+
+        // Taking the center doesn't win for x, and no line is blocked yet
+        let text = "\
+game first=x winner=x adjudicated=solved moves=2B
+game first=x winner=tie adjudicated=dead moves=2B
+game first=x winner=none adjudicated=solved moves=2B
+game first=x winner=tie adjudicated=soon moves=2B
+";
+        assert_eq!(verify(text, perfect_result).problems, [
+            (1, Problem::InvalidAdjudication(Adjudication::Solved)),
+            (2, Problem::InvalidAdjudication(Adjudication::DeadPosition)),
+            (3, Problem::InvalidAdjudication(Adjudication::Solved)),
+            (4, Problem::InvalidField {field: "adjudicated", value: "soon".to_string()}),
+        ]);
+    }
+
+    #[test]
+    fn digests_are_checked() {
+        // The same tiles in another order, which still gives x the win
+        let mut swapped = Game::new();
+        for &(row, col) in &[(0, 1), (1, 0), (0, 0), (1, 1), (0, 2)] {
+            swapped.make_move(row, col).unwrap();
+        }
+        let text = "\
+game first=x winner=x digest=e3a3539ef4a1c781 moves=1A,2A,1B,2B,1C
+game first=x winner=x digest=e3a3539ef4a1c781 moves=1B,2A,1A,2B,1C
+game first=x winner=x digest=e3a3539e moves=1A,2A,1B,2B,1C
+game first=x winner=x moves=1B,2A,1A,2B,1C
+";
+        assert_eq!(verify(text, perfect_result).problems, [
+            (2, Problem::WrongDigest {saved: 0xe3a3_539e_f4a1_c781,
+                actual: swapped.history_digest()}),
+            (3, Problem::InvalidField {field: "digest", value: "e3a3539e".to_string()}),
+        ]);
+    }
+
+    #[test]
+    fn verify_finds_corruption() {
+        let text = "\
+match best_of=3 games=2 x=2 o=0 ties=0 winner=x
+game first=x winner=x moves=1A,2A,1B,2B,1C
+game first=o winner=x forfeit=o moves=2B
+end
+game first=x winner=none moves=2B,1A
+";
+        let verification = verify(text, perfect_result);
+        assert_eq!(verification.games, 3);
+        assert_eq!(verification.problems, []);
+
+        let text = "\
+match best_of=3 games=2 x=2 o=0 ties=0 winner=x
+game first=x winner=x moves=1A,2A,1B,2B,2B
+game first=x winner=o moves=1A,2A,1B,2B,1C
+end
+game first=x winner=none moves=2B,4D
+gme first=x
+end
+";
+        assert_eq!(verify(text, perfect_result).problems, [
+            // Only one of the games in the match counted
+            (1, Problem::WrongMatchTotals),
+            (2, Problem::IllegalMove {number: 5, notation: "2B".to_string()}),
+            (3, Problem::WrongWinner {saved: Some(Winner::O), actual: Some(Winner::X)}),
+            (5, Problem::InvalidMove {number: 2, notation: "4D".to_string()}),
+            (6, Problem::UnknownRecord),
+            (7, Problem::UnexpectedEnd),
+        ]);
+    }
+
+    #[test]
+    fn series_record() {
+        let mut game = Game::starting_with(Piece::O);
+        game.make_move(0, 0).unwrap();
+        game.make_move(1, 0).unwrap();
+        game.make_move(0, 1).unwrap();
+        game.make_move(1, 1).unwrap();
+        game.make_move(0, 2).unwrap();
+
+        let mut series = Series::starting_with(1, Piece::O);
+        series.record(game);
+
+        let mut out = Vec::new();
+        write_series(&mut out, &series, &Players::default(), None, None).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "\
+match best_of=1 games=1 x=0 o=1 ties=0 winner=o
+game first=o winner=o digest=3fab9c715b8332a1 moves=1A,2A,1B,2B,1C
+end
+");
+    }
+}
@@ -0,0 +1,222 @@
+// The arena plays two computer players against each other over and over, to find out which one is
+// stronger and by how much. This is how changes to the engine are checked: a change that makes
+// the engine a little better only shows up over many games.
+//
+// Instead of always playing a fixed number of games, the arena stops as soon as the results are
+// clear enough, using a sequential probability ratio test (SPRT). We pick two hypotheses about the
+// Elo difference between the players ("the first player is no better" and "the first player is at
+// least this much better") and after every game we check how much more likely the results are
+// under one hypothesis than under the other. Once one of them is far more likely, the test stops.
+// A big difference is found after a few games, while a small one takes many more.
+// More info: https://en.wikipedia.org/wiki/Sequential_probability_ratio_test
+
+use engine::Profile;
+use game::Winner;
+use random::Rng;
+use rating;
+use simulate;
+
+// The chance of accepting that the first player is better when it isn't (alpha), and of missing
+// an improvement that is really there (beta)
+const FALSE_POSITIVE_RATE: f64 = 0.05;
+const FALSE_NEGATIVE_RATE: f64 = 0.05;
+// How many standard errors a result can be away from the measured one while still being inside
+// the 95% confidence interval
+const CONFIDENCE_Z: f64 = 1.96;
+
+// What the test has decided so far
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    /// The first player is at most `elo0` stronger than the second one
+    Rejected,
+    /// The first player is at least `elo1` stronger than the second one
+    Accepted,
+    /// There aren't enough games yet to tell
+    Undecided,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sprt {
+    /// The Elo difference if the first player isn't better
+    pub elo0: f64,
+    /// The Elo difference if the first player is better
+    pub elo1: f64,
+}
+
+impl Sprt {
+    // Decides whether the results are clear enough to stop
+    pub fn verdict(&self, results: &Results) -> Verdict {
+        let lower = (FALSE_NEGATIVE_RATE / (1.0 - FALSE_POSITIVE_RATE)).ln();
+        let upper = ((1.0 - FALSE_NEGATIVE_RATE) / FALSE_POSITIVE_RATE).ln();
+        match self.log_likelihood_ratio(results) {
+            Some(llr) if llr >= upper => Verdict::Accepted,
+            Some(llr) if llr <= lower => Verdict::Rejected,
+            _ => Verdict::Undecided,
+        }
+    }
+
+    // How much more likely the results are if the first player is better than if it isn't, as a
+    // logarithm. Positive numbers favor the first player being better.
+    //
+    // This uses the normal approximation from the engine testing framework Fishtest, which treats
+    // each game as a score of 1, ½ or 0 and only needs the average and the spread of the scores.
+    // Returns None before the first game.
+    //
+    // While every game has had the same result, the spread is zero, and dividing by it would make
+    // a single game decide the test. Instead, we use the spread that the results would have with
+    // one more win and one more loss. A player who wins every game still gets accepted, just after
+    // enough games for it not to be luck.
+    pub fn log_likelihood_ratio(&self, results: &Results) -> Option<f64> {
+        let games = f64::from(results.games());
+        let (mean, mut variance) = results.mean_and_variance()?;
+        if variance <= 0.0 {
+            let spread = Results {wins: results.wins + 1, losses: results.losses + 1, ..*results};
+            variance = spread.mean_and_variance()?.1;
+        }
+        let (score0, score1) = (rating::expected_score(self.elo0),
+            rating::expected_score(self.elo1));
+        Some(games * (score1 - score0) * (2.0 * mean - score0 - score1) / (2.0 * variance))
+    }
+}
+
+// The results of the games from the point of view of the first player
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Results {
+    pub wins: u32,
+    pub losses: u32,
+    pub ties: u32,
+}
+
+impl Results {
+    pub fn games(&self) -> u32 {
+        self.wins + self.losses + self.ties
+    }
+
+    pub fn record(&mut self, score: Score) {
+        match score {
+            Score::Win => self.wins += 1,
+            Score::Loss => self.losses += 1,
+            Score::Tie => self.ties += 1,
+        }
+    }
+
+    // The Elo difference between the players that best explains the results, along with the
+    // lowest and highest differences inside the 95% confidence interval. Returns None before
+    // the first game. A player who won (or lost) every game is infinitely stronger (or weaker)
+    // as far as the formula is concerned, so some of the numbers can be infinite.
+    pub fn elo_difference(&self) -> Option<(f64, f64, f64)> {
+        let (mean, variance) = self.mean_and_variance()?;
+        let margin = CONFIDENCE_Z * (variance / f64::from(self.games())).sqrt();
+        // Nobody can score less than nothing or more than every point, even at the edge of the
+        // confidence interval
+        let (low, high) = ((mean - margin).max(0.0), (mean + margin).min(1.0));
+        Some((rating::elo_difference(mean), rating::elo_difference(low),
+            rating::elo_difference(high)))
+    }
+
+    // The average score per game and how spread out the scores are
+    fn mean_and_variance(&self) -> Option<(f64, f64)> {
+        if self.games() == 0 {
+            return None;
+        }
+        let games = f64::from(self.games());
+        let (wins, ties) = (f64::from(self.wins) / games, f64::from(self.ties) / games);
+        let mean = wins + ties / 2.0;
+        // The average of the squared scores minus the square of the average score
+        let variance = wins + ties / 4.0 - mean * mean;
+        Some((mean, variance))
+    }
+}
+
+// How a single game went for the first player
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Score {
+    Win,
+    Loss,
+    Tie,
+}
+
+// Plays the `number`th game between the two players and returns how it went for `first`. The
+// players take turns playing x, so neither gets the advantage of going first more often. An
+// adjudicated game (see simulate.rs) ends the same way, only sooner.
+pub fn play(first: Profile, second: Profile, number: u32, adjudicate: bool,
+    rng: &mut Rng) -> Score {
+
+    let first_is_x = number.is_multiple_of(2);
+    let (x, o) = if first_is_x { (first, second) } else { (second, first) };
+    let game = simulate::play(x, o, adjudicate, rng);
+    match (game.winner(), first_is_x) {
+        (Some(Winner::Tie), _) => Score::Tie,
+        (Some(Winner::X), true) | (Some(Winner::O), false) => Score::Win,
+        (Some(_), _) => Score::Loss,
+        (None, _) => unreachable!("the game should be finished"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use engine::Difficulty;
+
+    #[test]
+    fn elo_difference_and_bounds() {
+        assert_eq!(Results::default().elo_difference(), None);
+
+        // Scoring 75% is worth about 191 Elo
+        let results = Results {wins: 65, losses: 15, ties: 20};
+        let (elo, low, high) = results.elo_difference().unwrap();
+        assert_eq!(elo.round(), 191.0);
+        assert!(low < elo && elo < high);
+
+        // Losing most games can't put the bottom of the interval below a score of zero
+        let (_, low, _) = Results {wins: 0, losses: 7, ties: 1}.elo_difference().unwrap();
+        assert_eq!(low, f64::NEG_INFINITY);
+
+        // An even score means the players are equally strong
+        let even = Results {wins: 10, losses: 10, ties: 0};
+        assert_eq!(even.elo_difference().unwrap().0, 0.0);
+    }
+
+    #[test]
+    fn sprt_verdicts() {
+        let sprt = Sprt {elo0: 0.0, elo1: 20.0};
+        // A handful of games doesn't prove anything
+        assert_eq!(sprt.verdict(&Results {wins: 3, losses: 2, ties: 5}), Verdict::Undecided);
+        // Every game being a tie means the players are just as strong, but that takes a while to
+        // be sure of
+        assert_eq!(sprt.verdict(&Results {wins: 0, losses: 0, ties: 50}), Verdict::Undecided);
+        assert_eq!(sprt.verdict(&Results {wins: 0, losses: 0, ties: 100}), Verdict::Rejected);
+        assert_eq!(sprt.verdict(&Results {wins: 80, losses: 10, ties: 10}), Verdict::Accepted);
+        assert_eq!(sprt.verdict(&Results {wins: 10, losses: 80, ties: 10}), Verdict::Rejected);
+    }
+
+    #[test]
+    fn sprt_one_sided() {
+        let sprt = Sprt {elo0: 0.0, elo1: 20.0};
+        // A player that wins every game is better, once it has won often enough not to be luck
+        assert_eq!(sprt.verdict(&Results {wins: 1, losses: 0, ties: 0}), Verdict::Undecided);
+        assert_eq!(sprt.verdict(&Results {wins: 30, losses: 0, ties: 0}), Verdict::Accepted);
+        assert_eq!(sprt.verdict(&Results {wins: 0, losses: 30, ties: 0}), Verdict::Rejected);
+
+        // The arena stops early when one player is much stronger than the other
+        let mut rng = Rng::new(7);
+        let (perfect, easy) = (Difficulty::Perfect.into(), Difficulty::Easy.into());
+        let mut results = Results::default();
+        while sprt.verdict(&results) == Verdict::Undecided {
+            results.record(play(perfect, easy, results.games(), false, &mut rng));
+            assert!(results.games() <= 100, "the arena should have stopped");
+        }
+        assert_eq!(sprt.verdict(&results), Verdict::Accepted);
+    }
+
+    #[test]
+    fn perfect_never_loses() {
+        let mut rng = Rng::new(7);
+        let (perfect, easy) = (Difficulty::Perfect.into(), Difficulty::Easy.into());
+        for number in 0..4 {
+            let score = play(perfect, easy, number, false, &mut rng);
+            assert_ne!(score, Score::Loss);
+        }
+    }
+}
@@ -0,0 +1,131 @@
+// The consistency check of `archive audit FILE`, for after the rules or the engine have changed.
+// Every game in the archive is played again with the program as it is now, which finds:
+//
+// * games whose saved winner isn't the one that `Game` works out from their moves
+// * moves made by an engine that should always play one of the best moves, but that weren't one
+//   of the best moves according to the solver (see solve.rs)
+//
+// Neither should ever happen, so anything found means that one of the changes made the program
+// disagree with the games it played before. Only the moves of the perfect difficulty and of the
+// engine profiles that play just like it are checked, since the others miss the best moves on
+// purpose. The engine that made each move is known from its `engine` tag.
+
+use archive;
+use engine::{self, Difficulty, Profile};
+use game::{Game, Tags, Winner};
+
+// Something found by `audit`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Finding {
+    /// Playing the moves gives a different result from the one that was saved
+    WrongWinner { saved: Option<Winner>, actual: Option<Winner> },
+    /// An engine that plays perfectly made a move that wasn't one of the best moves. Moves are
+    /// numbered from 1.
+    NotOptimal {
+        number: usize,
+        row: usize,
+        col: usize,
+        /// The name of the engine, as it is in the move's tag
+        engine: String,
+        /// The moves that would have been best instead
+        best: Vec<(usize, usize)>,
+    },
+}
+
+// Everything found in an archive
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Audit {
+    /// The number of games that were played again
+    pub games: usize,
+    /// The number of moves by engines that play perfectly that were checked
+    pub moves: usize,
+    /// What was found, along with the line it is on (counting from 1)
+    pub findings: Vec<(usize, Finding)>,
+}
+
+// Plays every game in the archive again. `profiles` are the engine profiles from the preferences,
+// which tell which of the engine names that aren't difficulties play perfectly.
+pub fn audit(text: &str, profiles: &[(String, Profile)]) -> Audit {
+    let mut audit = Audit::default();
+    for saved in archive::parse_saved_games(text) {
+        audit.games += 1;
+        let game = &saved.archived.game;
+        if game.winner() != saved.saved {
+            let finding = Finding::WrongWinner {saved: saved.saved, actual: game.winner()};
+            audit.findings.push((saved.line, finding));
+        }
+
+        let mut position = Game::starting_with(game.first_piece()).with_rules(game.rules());
+        for (i, (row, col)) in game.moves().iter().enumerate() {
+            if let Some(engine) = perfect_engine(game.move_tags(i), profiles) {
+                audit.moves += 1;
+                let best = engine::perfect_moves(&position);
+                if !best.contains(&(row, col)) {
+                    let engine = engine.to_string();
+                    let finding = Finding::NotOptimal {number: i + 1, row, col, engine, best};
+                    audit.findings.push((saved.line, finding));
+                }
+            }
+            position.make_move(row, col).expect("the game was played with these moves");
+        }
+    }
+    audit
+}
+
+// The name of the engine that made a move, if it is one that should always play one of the best
+// moves. Names that are neither a difficulty nor one of the profiles can't be checked.
+fn perfect_engine<'a>(tags: Option<&'a Tags>, profiles: &[(String, Profile)])
+    -> Option<&'a str> {
+
+    let name = tags?.get("engine")?;
+    let perfect = match Difficulty::from_name(name) {
+        Some(difficulty) => difficulty == Difficulty::Perfect,
+        None => profiles.iter()
+            .any(|(profile, settings)| profile == name && settings.is_perfect()),
+    };
+    Some(name.as_str()).filter(|_| perfect)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use engine::Evaluator;
+
+    #[test]
+    fn wrong_winners() {
+        let text = "\
+game first=x winner=x moves=1A,2A,1B,2B,1C
+game first=x winner=o moves=1A,2A,1B,2B,1C
+game first=x winner=x moves=1A,2A
+game first=x winner=none moves=1A,2A
+";
+        let audit = audit(text, &[]);
+        assert_eq!(audit.games, 4);
+        assert_eq!(audit.findings, [
+            (2, Finding::WrongWinner {saved: Some(Winner::O), actual: Some(Winner::X)}),
+            (3, Finding::WrongWinner {saved: Some(Winner::X), actual: None}),
+        ]);
+    }
+
+    #[test]
+    fn engine_moves() {
+        // o's second move at 3C lets x win at 1C, which the perfect engine would have blocked.
+        // The easy engine misses it on purpose, and so does a profile that only looks one move
+        // ahead, but a profile that plays just like the perfect engine is held to the same
+        // standard.
+        let line = |engine: &str| format!("game first=x winner=x \
+            move_tags=-,engine:perfect,-,engine:{} moves=1A,2B,1B,3C,1C", engine);
+        let text = [line("perfect"), line("easy"), line("shallow"), line("careful")].join("\n");
+        let shallow = Profile {depth: Some(1), ..Difficulty::Perfect.into()};
+        let careful = Profile {evaluator: Evaluator::Neutral, ..Difficulty::Perfect.into()};
+        let profiles = [("shallow".to_string(), shallow), ("careful".to_string(), careful)];
+
+        let audit = audit(&text, &profiles);
+        // The perfect engine's first move at 2B is fine everywhere
+        assert_eq!(audit.moves, 6);
+        let not_optimal = |engine: &str| Finding::NotOptimal {number: 4, row: 2, col: 2,
+            engine: engine.to_string(), best: vec![(0, 2)]};
+        assert_eq!(audit.findings, [(1, not_optimal("perfect")), (4, not_optimal("careful"))]);
+    }
+}
@@ -0,0 +1,98 @@
+// A second, much smaller program with nothing in it but the computer player and the Go Text
+// Protocol (see gtp.rs). It is for servers, test harnesses and chat bots that drive the engine
+// from another program, where the menus, renderers, languages and everything else in the terminal
+// version would never be used. It reads one GTP command per line until it is told to `quit` or
+// its input runs out:
+//
+//     cargo run --bin ttt-engine -- --difficulty medium
+//
+// Just like the web version, it uses `#[path]` to include the modules it needs straight from the
+// terminal version, so the rules and the engine are exactly the same in both programs.
+
+extern crate thiserror;
+
+// Only part of each module is used by the engine
+#[allow(dead_code)]
+#[path = "../game.rs"]
+mod game;
+#[allow(dead_code)]
+#[path = "../history.rs"]
+mod history;
+#[allow(dead_code)]
+#[path = "../random.rs"]
+mod random;
+#[allow(dead_code)]
+#[path = "../engine.rs"]
+mod engine;
+#[allow(dead_code)]
+#[path = "../tt.rs"]
+mod tt;
+#[allow(dead_code)]
+#[path = "../solve.rs"]
+mod solve;
+#[path = "../gtp.rs"]
+mod gtp;
+
+use std::env;
+use std::io::{self, BufRead, Write};
+use std::process;
+
+use engine::Difficulty;
+use random::Rng;
+
+const USAGE: &str = "Usage: ttt-engine [--difficulty easy|medium|perfect] [--seed NUMBER]";
+
+fn main() {
+    let (difficulty, seed) = match parse_args(env::args().skip(1)) {
+        Ok(args) => args,
+        Err(error) => {
+            eprintln!("{}\n{}", error, USAGE);
+            process::exit(2);
+        },
+    };
+    let mut rng = Rng::new(seed.unwrap_or_else(random::time_seed));
+    let mut engine = gtp::Engine::new(difficulty);
+
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    for line in stdin.lock().lines() {
+        // Input that can't be read (or isn't UTF-8) ends the session, just like running out of it
+        let line = match line {
+            Ok(line) => line,
+            Err(_) => break,
+        };
+        if let Some(response) = engine.respond(&line, &mut rng) {
+            // Flushing after every answer matters, since the controller waits for it before
+            // sending the next command. If the controller went away, there is no one to answer.
+            let written = write!(out, "{}", response.text).and_then(|_| out.flush());
+            if written.is_err() || response.quit {
+                break;
+            }
+        }
+    }
+}
+
+// The difficulty (perfect unless `--difficulty` says otherwise) and the seed, if there is one
+fn parse_args<I: Iterator<Item=String>>(mut args: I) -> Result<(Difficulty, Option<u64>), String> {
+    let mut difficulty = Difficulty::Perfect;
+    let mut seed = None;
+    while let Some(arg) = args.next() {
+        let value = args.next().ok_or_else(|| match &arg[..] {
+            "--difficulty" | "--seed" => format!("Missing a value for {}", arg),
+            _ => format!("Unknown argument: {}", arg),
+        })?;
+        match &arg[..] {
+            "--difficulty" => {
+                difficulty = Difficulty::from_name(&value)
+                    .ok_or_else(|| format!("Invalid value for --difficulty: {}", value))?;
+            },
+            "--seed" => {
+                seed = Some(value.parse()
+                    .map_err(|_| format!("Invalid value for --seed: {}", value))?);
+            },
+            _ => return Err(format!("Unknown argument: {}", arg)),
+        }
+    }
+    Ok((difficulty, seed))
+}
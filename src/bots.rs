@@ -0,0 +1,833 @@
+// Hosts games in chat channels, for the `discord` and `irc` commands. Anyone in a channel can
+// start a game against someone else or against the computer, and then the players move by typing a
+// tile like 2B. On Discord, the board is a grid of emoji in a single message that the bot edits
+// after every move, so the channel doesn't fill up with boards:
+//
+//     !ttt play @Grace
+//
+//     Ada ❌ vs ⭕ Grace
+//     ⬛ 🇦 🇧 🇨
+//     1️⃣ ⬜ ⬜ ⬜
+//     2️⃣ ⬜ ❌ ⬜
+//     3️⃣ ⬜ ⬜ ⬜
+//     It's Grace's turn.
+//
+// Messages on IRC can't be edited (or contain emoji that every client can show), so the board is
+// drawn as text, just like in the terminal, and sent again after every move.
+//
+// Once a game is over, either of its players can ask for a rematch with `!ttt rematch`. The players
+// trade pieces, and the score of the games they played so far is shown above the board.
+//
+// Each channel has its own game, so many games can be played at once. Finished games between two
+// people are saved to the archive with the names of the players, which is where their ratings
+// come from (see rating.rs).
+//
+// This module only decides what to say. The connections are in gateway.rs (Discord, which is only
+// built with the `discord` feature) and irc.rs, so everything here can be tested without a
+// network.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use archive::Archive;
+use bus::{self, Event};
+use clock;
+use coach::{self, Level};
+use engine::{self, Difficulty};
+use game::{Game, MoveError, Pause, Piece, Winner};
+use locale::{Lang, Message};
+use notation;
+use player::{PlayerProfile, Players};
+use random::Rng;
+use rating;
+use render::{self, Layout};
+use series::Score;
+use storage::Recovery;
+use theme::Theme;
+
+// Every command starts with this, so that the bot ignores everything else people say (except for
+// moves in a game they are playing)
+const PREFIX: &str = "!ttt";
+
+// The chat service that a bot is connected to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Chat {
+    #[cfg_attr(not(feature = "discord"), allow(dead_code))]
+    Discord,
+    #[cfg_attr(not(feature = "irc"), allow(dead_code))]
+    Irc,
+}
+
+impl Chat {
+    // The name that the games played over this chat are tagged with (see `Tags` in game.rs)
+    fn name(self) -> &'static str {
+        match self {
+            Chat::Discord => "discord",
+            Chat::Irc => "irc",
+        }
+    }
+}
+
+// Someone in a chat. The name is what their rating is saved under: the unique username on Discord,
+// or the nickname on IRC (which is also their ID, since IRC doesn't have any other).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct User {
+    pub id: String,
+    pub name: String,
+}
+
+// A message sent to a channel that the bot can see
+#[derive(Debug, Clone)]
+pub struct ChatMessage<'a> {
+    pub channel: &'a str,
+    pub author: &'a User,
+    pub content: &'a str,
+    /// The people mentioned in the message (e.g. with @Grace on Discord), not including any bots
+    pub mentions: &'a [User],
+}
+
+// What the bot says in the channel that a message came from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Reply {
+    /// A new message
+    Send(String),
+    /// A new message with the board, which the next moves edit (see `Bot::board_sent`)
+    SendBoard(String),
+    /// The board message that was sent earlier, with new text
+    EditBoard { message: String, text: String },
+}
+
+#[derive(Debug, Clone)]
+pub struct Bot {
+    chat: Chat,
+    /// The game in each channel, by the ID of the channel
+    tables: HashMap<String, Table>,
+    /// How well the computer plays
+    difficulty: Difficulty,
+    /// How much the coach says about the moves in the channels that haven't chosen for themselves,
+    /// or None for no coach
+    coach: Option<Level>,
+    /// The coach chosen with `!ttt coach` in each channel, by the ID of the channel
+    coaches: HashMap<String, Option<Level>>,
+    /// Where finished games are saved, if anywhere
+    archive: Option<Archive>,
+    lang: Lang,
+}
+
+// A game in a channel, and who is playing it
+#[derive(Debug, Clone)]
+struct Table {
+    game: Game,
+    x: Seat,
+    o: Seat,
+    /// The ID of the message with the board, once it has been sent
+    board_message: Option<String>,
+    /// The player who paused the game and when they did (see `clock::wall_clock`), while the
+    /// game is paused
+    paused: Option<(Piece, u64)>,
+    /// The number of the rematch (1 for the first one), if the game is a rematch
+    rematch: Option<u32>,
+    /// The score of the games played before this one in the same match, by the pieces that the
+    /// players have in this game
+    score: Score,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Seat {
+    Person(User),
+    Computer,
+}
+
+impl Table {
+    fn seat(&self, piece: Piece) -> &Seat {
+        match piece {
+            Piece::X => &self.x,
+            Piece::O => &self.o,
+        }
+    }
+
+    // The piece that the given person is playing, if they are playing at all
+    fn piece_of(&self, user: &User) -> Option<Piece> {
+        [Piece::X, Piece::O].iter().cloned()
+            .find(|&piece| *self.seat(piece) == Seat::Person(user.clone()))
+    }
+
+    // Goes on with the game if it is paused, saving the pause with it. Returns how long the pause
+    // lasted.
+    fn resume(&mut self) -> Option<Duration> {
+        let (_, started) = self.paused.take()?;
+        let seconds = clock::wall_clock().saturating_sub(started);
+        self.game.record_pause(Pause {started, seconds});
+        Some(Duration::from_secs(seconds))
+    }
+}
+
+impl Bot {
+    pub fn new(chat: Chat, difficulty: Difficulty, archive: Option<Archive>, lang: Lang) -> Self {
+        Bot {
+            chat,
+            tables: HashMap::new(),
+            difficulty,
+            coach: None,
+            coaches: HashMap::new(),
+            archive,
+            lang,
+        }
+    }
+
+    // The same bot, except that it coaches every channel at the given level
+    pub fn with_coach(self, coach: Option<Level>) -> Self {
+        Self {coach, ..self}
+    }
+
+    // Answers a message. Most messages have nothing to do with the bot, so they get no replies.
+    pub fn handle(&mut self, message: &ChatMessage, rng: &mut Rng) -> Vec<Reply> {
+        bus::publish(Event::ChatMessage {channel: message.channel, author: &message.author.name});
+        let content = message.content.trim();
+        let mut words = content.split_whitespace();
+        if words.next() != Some(PREFIX) {
+            return self.play_move(message, rng);
+        }
+
+        let (chat, lang) = (self.chat, self.lang);
+        match words.next() {
+            Some("play") => self.start(message, words.next()),
+            Some("resign") => self.resign(message),
+            Some("pause") => self.pause(message),
+            Some("resume") => self.resume(message),
+            Some("rematch") => self.rematch(message, rng),
+            Some("board") => match self.tables.get_mut(message.channel) {
+                // The old board message will be out of sight soon, so the new one takes its place
+                Some(table) => {
+                    table.board_message = None;
+                    vec![Reply::SendBoard(board_text(table, chat, lang))]
+                },
+                None => vec![Reply::Send(lang.text(Message::ChatNoGame))],
+            },
+            Some("ratings") => vec![Reply::Send(self.ratings())],
+            Some("coach") => {
+                let level = match words.next() {
+                    Some("off") => None,
+                    Some(name) => match Level::from_name(name) {
+                        Some(level) => Some(level),
+                        None => return vec![Reply::Send(lang.text(Message::ChatCoachHelp))],
+                    },
+                    None => return vec![Reply::Send(lang.text(Message::ChatCoachHelp))],
+                };
+                self.coaches.insert(message.channel.to_string(), level);
+                vec![Reply::Send(lang.text(Message::ChatCoach(level)))]
+            },
+            _ => {
+                let mention = chat == Chat::Discord;
+                vec![Reply::Send(lang.text(Message::ChatHelp {mention}))]
+            },
+        }
+    }
+
+    // Remembers which message has the board, so that the next moves edit it. Only Discord can edit
+    // messages.
+    #[cfg_attr(not(feature = "discord"), allow(dead_code))]
+    pub fn board_sent(&mut self, channel: &str, message: String) {
+        if let Some(table) = self.tables.get_mut(channel) {
+            table.board_message = Some(message);
+        }
+    }
+
+    // Starts a game against the first person mentioned, or against the computer if nobody was.
+    // IRC has no mentions, so the opponent is whoever has the nickname after `play`.
+    fn start(&mut self, message: &ChatMessage, opponent: Option<&str>) -> Vec<Reply> {
+        let lang = self.lang;
+        if self.tables.get(message.channel).is_some_and(|table| !table.game.is_finished()) {
+            return vec![Reply::Send(lang.text(Message::ChatGameInProgress))];
+        }
+        let opponent = match self.chat {
+            Chat::Discord => message.mentions.first().cloned(),
+            Chat::Irc => opponent.map(|nick| User {id: nick.to_string(), name: nick.to_string()}),
+        };
+        let opponent = match opponent {
+            Some(ref user) if user == message.author => {
+                return vec![Reply::Send(lang.text(Message::ChatPlayYourself))];
+            },
+            Some(user) => Seat::Person(user),
+            None => Seat::Computer,
+        };
+
+        debug!(channel = message.channel, player = %message.author.name, "started a game");
+        let table = Table {
+            game: Game::new(),
+            x: Seat::Person(message.author.clone()),
+            o: opponent,
+            board_message: None,
+            paused: None,
+            rematch: None,
+            score: Score::default(),
+        };
+        let text = board_text(&table, self.chat, lang);
+        self.tables.insert(message.channel.to_string(), table);
+        vec![Reply::SendBoard(text)]
+    }
+
+    // Starts the next game of the match in the channel, with the players trading pieces. The score
+    // goes with the players, so each one keeps their wins.
+    fn rematch(&mut self, message: &ChatMessage, rng: &mut Rng) -> Vec<Reply> {
+        let lang = self.lang;
+        let table = match self.tables.get(message.channel) {
+            Some(table) if table.game.is_finished() => table,
+            Some(_) => return vec![Reply::Send(lang.text(Message::ChatGameInProgress))],
+            None => return vec![Reply::Send(lang.text(Message::ChatNoGame))],
+        };
+        if table.piece_of(message.author).is_none() {
+            return vec![Reply::Send(lang.text(Message::ChatNotPlaying))];
+        }
+
+        let mut score = table.score;
+        score.add(table.game.winner().expect("the game is finished"));
+        debug!(channel = message.channel, player = %message.author.name, "started a rematch");
+        let mut table = Table {
+            game: Game::new(),
+            x: table.o.clone(),
+            o: table.x.clone(),
+            board_message: None,
+            paused: None,
+            rematch: Some(table.rematch.map_or(1, |rematch| rematch + 1)),
+            score: score.swapped(),
+        };
+        // The computer goes first if it is x now
+        if table.x == Seat::Computer {
+            let (row, col) = engine::choose_move(&table.game, self.difficulty, rng);
+            table.game.make_move(row, col).expect("the engine should only choose empty tiles");
+        }
+        let text = board_text(&table, self.chat, lang);
+        self.tables.insert(message.channel.to_string(), table);
+        vec![Reply::SendBoard(text)]
+    }
+
+    fn resign(&mut self, message: &ChatMessage) -> Vec<Reply> {
+        let (chat, lang) = (self.chat, self.lang);
+        let table = match self.tables.get_mut(message.channel) {
+            Some(table) if !table.game.is_finished() => table,
+            _ => return vec![Reply::Send(lang.text(Message::ChatNoGame))],
+        };
+        let piece = match table.piece_of(message.author) {
+            Some(piece) => piece,
+            None => return vec![Reply::Send(lang.text(Message::ChatNotPlaying))],
+        };
+        // Resigning ends the pause too, so that the pause is saved with the game
+        table.resume();
+        table.game.forfeit(piece).expect("a game that isn't over can always be forfeited");
+
+        let mut replies = vec![board_reply(table, chat, lang)];
+        replies.extend(self.finish(message.channel));
+        replies
+    }
+
+    // Puts the game on hold until one of its players goes on with it. Nothing can be played while
+    // the game is paused, and the board is hidden so that nobody can think about it in the
+    // meantime.
+    fn pause(&mut self, message: &ChatMessage) -> Vec<Reply> {
+        let (chat, lang) = (self.chat, self.lang);
+        let table = match self.tables.get_mut(message.channel) {
+            Some(table) if !table.game.is_finished() => table,
+            _ => return vec![Reply::Send(lang.text(Message::ChatNoGame))],
+        };
+        let piece = match table.piece_of(message.author) {
+            Some(piece) => piece,
+            None => return vec![Reply::Send(lang.text(Message::ChatNotPlaying))],
+        };
+        if table.paused.is_some() {
+            return vec![Reply::Send(lang.text(Message::ChatStillPaused))];
+        }
+        table.paused = Some((piece, clock::wall_clock()));
+        vec![board_reply(table, chat, lang)]
+    }
+
+    // Either player can go on with a paused game, not only the one who paused it
+    fn resume(&mut self, message: &ChatMessage) -> Vec<Reply> {
+        let (chat, lang) = (self.chat, self.lang);
+        let table = match self.tables.get_mut(message.channel) {
+            Some(table) if !table.game.is_finished() => table,
+            _ => return vec![Reply::Send(lang.text(Message::ChatNoGame))],
+        };
+        if table.piece_of(message.author).is_none() {
+            return vec![Reply::Send(lang.text(Message::ChatNotPlaying))];
+        }
+        match table.resume() {
+            Some(length) => {
+                let resumed = lang.text(Message::Resumed(length));
+                vec![board_reply(table, chat, lang), Reply::Send(resumed)]
+            },
+            None => vec![Reply::Send(lang.text(Message::ChatNotPaused))],
+        }
+    }
+
+    // Makes the move in the message if it is from the player whose turn it is, followed by the
+    // computer's move if it is playing
+    fn play_move(&mut self, message: &ChatMessage, rng: &mut Rng) -> Vec<Reply> {
+        let (chat, lang) = (self.chat, self.lang);
+        let difficulty = self.difficulty;
+        let coach = self.coaches.get(message.channel).cloned().unwrap_or(self.coach);
+        let table = match self.tables.get_mut(message.channel) {
+            Some(table) if !table.game.is_finished() => table,
+            _ => return Vec::new(),
+        };
+        // People talk about all sorts of things during a game, so only moves get an answer
+        let (row, col) = match notation::parse_move(message.content.trim()) {
+            Ok(tile) => tile,
+            Err(_) => return Vec::new(),
+        };
+        let piece = match table.piece_of(message.author) {
+            Some(piece) => piece,
+            None => return Vec::new(),
+        };
+        if table.paused.is_some() {
+            return vec![Reply::Send(lang.text(Message::ChatStillPaused))];
+        }
+        let current = table.game.current_piece();
+        if piece != current {
+            let player = seat_name(table.seat(current), lang);
+            return vec![Reply::Send(lang.text(Message::ChatTurn(&player)))];
+        }
+
+        match table.game.make_move(row, col) {
+            Ok(()) => {},
+            Err(MoveError::TileNotEmpty {other_piece, row, col, since}) => {
+                let other_player = seat_name(table.seat(other_piece), lang);
+                let message = Message::TileNotEmpty {row, col, other_player: &other_player, since};
+                return vec![Reply::Send(lang.text(message))];
+            },
+            Err(error) => unreachable!("bug: parsed moves are always on the board: {:?}", error),
+        }
+        // The coach talks about the player's move before the computer answers it
+        let comment = coach.and_then(|level| coach::comment(&table.game, level)).map(|comment| {
+            lang.text(Message::Coach {
+                comment,
+                player: &seat_name(table.seat(piece), lang),
+                opponent: &seat_name(table.seat(piece.other()), lang),
+            })
+        });
+        if !table.game.is_finished() && *table.seat(table.game.current_piece()) == Seat::Computer {
+            let (row, col) = engine::choose_move(&table.game, difficulty, rng);
+            table.game.make_move(row, col).expect("the engine should only choose empty tiles");
+        }
+
+        let mut replies = vec![board_reply(table, chat, lang)];
+        replies.extend(comment.map(Reply::Send));
+        if table.game.is_finished() {
+            replies.extend(self.finish(message.channel));
+        }
+        replies
+    }
+
+    // Saves the finished game in the channel to the archive and reports how the ratings of its
+    // players changed
+    fn finish(&self, channel: &str) -> Vec<Reply> {
+        let (table, archive) = match (self.tables.get(channel), self.archive.as_ref()) {
+            (Some(table), Some(archive)) => (table, archive),
+            _ => return Vec::new(),
+        };
+        // Only people have names, since only games between people are rated. A Discord username
+        // that is too long to be a name here can't be rated either.
+        let profile = |piece| PlayerProfile {
+            name: match *table.seat(piece) {
+                Seat::Person(ref user) => PlayerProfile::validate_name(&user.name).ok(),
+                Seat::Computer => None,
+            },
+            glyph: None,
+        };
+        let players = Players::new(profile(Piece::X), profile(Piece::O));
+
+        let before = archive.load().map(|(games, recovery)| {
+            log_recovery(archive, recovery);
+            rating::compute(&games)
+        }).unwrap_or_default();
+        // A rematch is saved as part of the same match as the games before it
+        let archive = match table.rematch {
+            Some(rematch) => archive.clone().with_rematch(rematch),
+            None => archive.clone(),
+        };
+        // The game is saved with where it was played, without changing the one at the table
+        let mut game = table.game.clone();
+        game.tag("source", self.chat.name());
+        match archive.record_game(&game, &players) {
+            Ok(recovery) => log_recovery(&archive, recovery),
+            Err(error) => {
+                warn!(path = %archive.path().display(), %error, "failed to save a game");
+                return Vec::new();
+            },
+        }
+        let after = match archive.games() {
+            Ok(games) => rating::compute(&games),
+            Err(_) => return Vec::new(),
+        };
+
+        let changes: Vec<_> = [Piece::X, Piece::O].iter().filter_map(|&piece| {
+            let name = players.profile(piece).name.as_ref()?;
+            let rated = after.get(name)?;
+            let old = before.get(name).map_or(rating::INITIAL_RATING, |rated| rated.rating);
+            Some(self.lang.text(Message::RatingChange {
+                player: name,
+                rating: rated.rating.round() as i32,
+                change: (rated.rating.round() - old.round()) as i32,
+            }))
+        }).collect();
+        if changes.is_empty() {
+            Vec::new()
+        }
+        else {
+            vec![Reply::Send(changes.join("\n"))]
+        }
+    }
+
+    // The ratings of everyone in the archive, from highest to lowest
+    fn ratings(&self) -> String {
+        let lang = self.lang;
+        let games = match self.archive.as_ref().map(|archive| archive.games()) {
+            Some(Ok(games)) => games,
+            Some(Err(_)) | None => return lang.text(Message::ChatNoRatings),
+        };
+        let mut ratings: Vec<_> = rating::compute(&games).into_iter().collect();
+        if ratings.is_empty() {
+            return lang.text(Message::ChatNoRatings);
+        }
+        ratings.sort_by(|(_, a), (_, b)| b.rating.total_cmp(&a.rating));
+
+        let mut lines = vec![lang.text(Message::ChatRatingsHeader)];
+        for (i, (name, rated)) in ratings.iter().enumerate() {
+            lines.push(lang.text(Message::PlayerRating {
+                rank: i + 1,
+                name,
+                rating: rated.rating.round() as i32,
+                games: rated.games,
+            }));
+        }
+        lines.join("\n")
+    }
+}
+
+// Edits the board message if there is one, or sends a new one if there isn't. On IRC there never
+// is, since messages there can't be edited.
+fn board_reply(table: &Table, chat: Chat, lang: Lang) -> Reply {
+    let text = board_text(table, chat, lang);
+    match table.board_message {
+        Some(ref message) => Reply::EditBoard {message: message.clone(), text},
+        None => Reply::SendBoard(text),
+    }
+}
+
+// The players, the board and what happens next
+fn board_text(table: &Table, chat: Chat, lang: Lang) -> String {
+    let x = seat_name(&table.x, lang);
+    let o = seat_name(&table.o, lang);
+    let mut lines = vec![match chat {
+        Chat::Discord => lang.text(Message::ChatVersus {
+            x: &format!("{} ❌", x),
+            o: &format!("⭕ {}", o),
+        }),
+        Chat::Irc => lang.text(Message::ChatVersus {
+            x: &format!("{} (x)", x),
+            o: &format!("{} (o)", o),
+        }),
+    }];
+    if table.rematch.is_some() {
+        lines.push(lang.text(Message::SeriesScore {x: &x, o: &o, score: table.score}));
+    }
+
+    // The board stays hidden for as long as the game is paused
+    if let Some((piece, _)) = table.paused {
+        lines.push(lang.text(Message::ChatPaused(&seat_name(table.seat(piece), lang))));
+        return lines.join("\n");
+    }
+
+    match chat {
+        // The labels are the same as the terminal's (see notation.rs), drawn with emoji
+        Chat::Discord => {
+            let style = notation::style();
+            let labels = style.labels();
+            if let Some((columns, _)) = labels {
+                let columns: Vec<_> = columns.iter().map(|label| emoji(label)).collect();
+                lines.push(format!("⬛ {}", columns.join(" ")));
+            }
+            for (row, tiles) in table.game.tiles().iter().enumerate() {
+                let tiles: Vec<_> = tiles.iter().enumerate().map(|(col, tile)| match *tile {
+                    Some(Piece::X) => "❌".to_string(),
+                    Some(Piece::O) => "⭕".to_string(),
+                    None if labels.is_none() => emoji(&style.write(row, col)),
+                    None => "⬜".to_string(),
+                }).collect();
+                let tiles = tiles.join(" ");
+                lines.push(match labels {
+                    Some((_, rows)) => format!("{} {}", emoji(rows[row]), tiles),
+                    None => tiles,
+                });
+            }
+        },
+        // The same grid as in the terminal, with plain letters for the pieces and dots for the
+        // empty tiles, since those look the same in every font
+        Chat::Irc => {
+            let theme = Theme {empty: ".", ..Theme::default()};
+            let players = Players::new(
+                PlayerProfile {name: None, glyph: Some("x".to_string())},
+                PlayerProfile {name: None, glyph: Some("o".to_string())},
+            );
+            let grid = render::grid(table.game.tiles(), table.game.winning_line(), &players,
+                &theme, Layout::Compact);
+            lines.extend(grid.lines().filter(|line| !line.is_empty()).map(str::to_string));
+        },
+    }
+
+    if let Some(piece) = table.game.forfeited_by() {
+        lines.push(lang.text(Message::ChatResigned(&seat_name(table.seat(piece), lang))));
+    }
+    lines.push(match table.game.winner() {
+        Some(Winner::X) => lang.text(Message::GameWon(&x)),
+        Some(Winner::O) => lang.text(Message::GameWon(&o)),
+        Some(Winner::Tie) => lang.text(Message::GameTied),
+        None => {
+            let current = seat_name(table.seat(table.game.current_piece()), lang);
+            lang.text(Message::ChatTurn(&current))
+        },
+    });
+    lines.join("\n")
+}
+
+// The emoji for a label of the board: a letter like 🇦 or a number like 1️⃣
+fn emoji(label: &str) -> String {
+    label.chars().map(|c| match c {
+        // The regional indicator symbols come one after the other, in the order of the alphabet
+        'A'..='Z' => char::from_u32(0x1F1E6 + (c as u32 - 'A' as u32)).into_iter().collect(),
+        // A digit followed by these two characters is drawn on a key
+        c => format!("{}\u{FE0F}\u{20E3}", c),
+    }).collect()
+}
+
+fn seat_name(seat: &Seat, lang: Lang) -> String {
+    match *seat {
+        Seat::Person(ref user) => user.name.clone(),
+        Seat::Computer => lang.text(Message::ChatComputer),
+    }
+}
+
+// Nobody at the chat can do anything about a corrupt archive, so it is only logged for whoever runs
+// the bot (see storage.rs)
+fn log_recovery(archive: &Archive, recovery: Option<Recovery>) {
+    if let Some(recovery) = recovery {
+        warn!(path = %archive.path().display(), quarantined = %recovery.quarantined.display(),
+            restored = recovery.restored, "the archive was corrupt");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::env;
+    use std::fs;
+    use std::process;
+
+    fn user(name: &str) -> User {
+        User {id: format!("{}-id", name), name: name.to_string()}
+    }
+
+    // Sends a message to the channel "general"
+    fn say(bot: &mut Bot, author: &User, content: &str, mentions: Vec<User>) -> Vec<Reply> {
+        let message = ChatMessage {channel: "general", author, content, mentions: &mentions};
+        bot.handle(&message, &mut Rng::new(1))
+    }
+
+    #[test]
+    fn play_between_two_people() {
+        let (ada, grace) = (user("Ada"), user("Grace"));
+        let mut bot = Bot::new(Chat::Discord, Difficulty::Perfect, None, Lang::English);
+        assert!(say(&mut bot, &ada, "2B", vec![]).is_empty(), "there is no game yet");
+
+        let replies = say(&mut bot, &ada, "!ttt play @Grace", vec![grace.clone()]);
+        match replies[..] {
+            [Reply::SendBoard(ref text)] => {
+                assert_eq!(text, "Ada ❌ vs ⭕ Grace\n⬛ 🇦 🇧 🇨\n1️⃣ ⬜ ⬜ ⬜\n2️⃣ ⬜ ⬜ ⬜\n\
+                    3️⃣ ⬜ ⬜ ⬜\nIt's Ada's turn.");
+            },
+            _ => panic!("expected a board, got {:?}", replies),
+        }
+        bot.board_sent("general", "42".to_string());
+
+        assert!(say(&mut bot, &grace, "hello!", vec![]).is_empty());
+        assert_eq!(say(&mut bot, &grace, "2B", vec![]),
+            vec![Reply::Send("It's Ada's turn.".to_string())]);
+        match say(&mut bot, &ada, " b2 ", vec![])[..] {
+            [Reply::EditBoard {ref message, ref text}] => {
+                assert_eq!(message, "42");
+                assert!(text.contains("2️⃣ ⬜ ❌ ⬜") && text.ends_with("It's Grace's turn."));
+            },
+            ref replies => panic!("expected the board to be edited, got {:?}", replies),
+        }
+        match say(&mut bot, &grace, "2B", vec![])[..] {
+            [Reply::Send(ref text)] => {
+                assert!(text.contains("has had piece Ada in it since move 1"))
+            },
+            ref replies => panic!("expected an error, got {:?}", replies),
+        }
+
+        assert_eq!(say(&mut bot, &user("Alan"), "!ttt resign", vec![]),
+            vec![Reply::Send("You aren't playing in this game.".to_string())]);
+        match say(&mut bot, &grace, "!ttt resign", vec![])[..] {
+            [Reply::EditBoard {ref text, ..}] => {
+                assert!(text.ends_with("Grace resigned.\nAda wins!"), "{}", text);
+            },
+            ref replies => panic!("expected the board to be edited, got {:?}", replies),
+        }
+    }
+
+    #[test]
+    fn pause_and_resume() {
+        let (ada, grace) = (user("Ada"), user("Grace"));
+        let mut bot = Bot::new(Chat::Discord, Difficulty::Perfect, None, Lang::English);
+        assert_eq!(say(&mut bot, &ada, "!ttt pause", vec![]),
+            vec![Reply::Send(
+            "There is no game in this channel. Start one with !ttt play.".to_string())]);
+        say(&mut bot, &ada, "!ttt play @Grace", vec![grace.clone()]);
+        say(&mut bot, &ada, "2B", vec![]);
+
+        assert_eq!(say(&mut bot, &user("Alan"), "!ttt pause", vec![]),
+            vec![Reply::Send("You aren't playing in this game.".to_string())]);
+        assert_eq!(say(&mut bot, &grace, "!ttt pause", vec![]), vec![Reply::SendBoard(
+            "Ada ❌ vs ⭕ Grace\nGrace paused the game. Type !ttt resume to go on.".to_string())]);
+        let paused = "The game is paused. Type !ttt resume to go on.".to_string();
+        let paused = vec![Reply::Send(paused)];
+        assert_eq!(say(&mut bot, &grace, "1A", vec![]), paused);
+        assert_eq!(say(&mut bot, &ada, "!ttt pause", vec![]), paused);
+
+        // Either player can go on with the game
+        match say(&mut bot, &ada, "!ttt resume", vec![])[..] {
+            [Reply::SendBoard(ref board), Reply::Send(ref text)] => {
+                assert!(board.ends_with("It's Grace's turn."), "{}", board);
+                assert!(text.starts_with("The game goes on after a pause of "), "{}", text);
+            },
+            ref replies => panic!("expected the board, got {:?}", replies),
+        }
+        assert_eq!(bot.tables["general"].game.pauses().len(), 1);
+        assert_eq!(say(&mut bot, &ada, "!ttt resume", vec![]),
+            vec![Reply::Send("The game isn't paused.".to_string())]);
+    }
+
+    #[test]
+    fn coach_comments_on_moves() {
+        let (ada, grace) = (user("Ada"), user("Grace"));
+        let mut bot = Bot::new(Chat::Discord, Difficulty::Perfect, None, Lang::English)
+            .with_coach(Some(Level::Low));
+        say(&mut bot, &ada, "!ttt play @Grace", vec![grace.clone()]);
+        for &(player, tile) in &[(&ada, "1A"), (&grace, "2A"), (&ada, "1B")] {
+            say(&mut bot, player, tile, vec![]);
+        }
+        // Grace doesn't block at 1C
+        let replies = say(&mut bot, &grace, "2B", vec![]);
+        assert_eq!(replies.last(),
+            Some(&Reply::Send("Coach: Grace left 1C open. Ada can win there.".to_string())));
+
+        assert_eq!(say(&mut bot, &ada, "!ttt coach loud", vec![]),
+            vec![Reply::Send("Type !ttt coach low, medium, high or off.".to_string())]);
+        assert_eq!(say(&mut bot, &ada, "!ttt coach off", vec![]),
+            vec![Reply::Send("The coach is off in this channel.".to_string())]);
+        // Ada misses the win, but the coach has nothing to say anymore
+        assert_eq!(say(&mut bot, &ada, "3C", vec![]).len(), 1);
+    }
+
+    #[test]
+    fn play_against_the_computer() {
+        let ada = user("Ada");
+        let mut bot = Bot::new(Chat::Discord, Difficulty::Perfect, None, Lang::English);
+        assert_eq!(say(&mut bot, &ada, "!ttt play @Ada", vec![ada.clone()]),
+            vec![Reply::Send("You can't play against yourself.".to_string())]);
+        say(&mut bot, &ada, "!ttt play", vec![]);
+        assert_eq!(say(&mut bot, &ada, "!ttt play", vec![]),
+            vec![Reply::Send("There is already a game in this channel.".to_string())]);
+
+        // The computer answers every move right away, and it never loses
+        let mut text = String::new();
+        for tile in &["1A", "1B", "1C", "2A", "2B", "2C", "3A", "3B", "3C"] {
+            if let [Reply::SendBoard(ref board)] = say(&mut bot, &ada, tile, vec![])[..] {
+                text = board.clone();
+            }
+        }
+        assert!(text.starts_with("Ada ❌ vs ⭕ Computer"), "{}", text);
+        assert!(text.ends_with("Tie!") || text.ends_with("Computer wins!"), "{}", text);
+    }
+
+    #[test]
+    fn rematch_swaps_the_pieces() {
+        let path = env::temp_dir().join(format!("tic-tac-toe-rematch-{}", process::id()));
+        let _ = fs::remove_file(&path);
+        let (ada, grace) = (user("Ada"), user("Grace"));
+        let archive = Some(Archive::new(&path));
+        let mut bot = Bot::new(Chat::Discord, Difficulty::Perfect, archive, Lang::English);
+        assert_eq!(say(&mut bot, &ada, "!ttt rematch", vec![]), vec![Reply::Send(
+            "There is no game in this channel. Start one with !ttt play.".to_string())]);
+        say(&mut bot, &ada, "!ttt play @Grace", vec![grace.clone()]);
+        assert_eq!(say(&mut bot, &grace, "!ttt rematch", vec![]),
+            vec![Reply::Send("There is already a game in this channel.".to_string())]);
+        say(&mut bot, &ada, "!ttt resign", vec![]);
+        assert_eq!(say(&mut bot, &user("Alan"), "!ttt rematch", vec![]),
+            vec![Reply::Send("You aren't playing in this game.".to_string())]);
+
+        // Grace won as o, so she keeps her win as x
+        match say(&mut bot, &grace, "!ttt rematch", vec![])[..] {
+            [Reply::SendBoard(ref board)] => assert!(board.starts_with(
+                "Grace ❌ vs ⭕ Ada\nScore: Grace 1 - Ada 0 (ties: 0)\n"), "{}", board),
+            ref replies => panic!("expected the board, got {:?}", replies),
+        }
+        say(&mut bot, &ada, "!ttt resign", vec![]);
+        match say(&mut bot, &ada, "!ttt rematch", vec![])[..] {
+            [Reply::SendBoard(ref board)] => assert!(board.starts_with(
+                "Ada ❌ vs ⭕ Grace\nScore: Ada 0 - Grace 2 (ties: 0)\n"), "{}", board),
+            ref replies => panic!("expected the board, got {:?}", replies),
+        }
+
+        let saved = fs::read_to_string(&path).unwrap();
+        let lines: Vec<_> = saved.lines().collect();
+        assert_eq!(lines, [
+            "game first=x winner=o forfeit=x x_name=Ada o_name=Grace tags=source:discord \
+            digest=af63bc4c8601b62c moves=",
+            "game first=x winner=x forfeit=o x_name=Grace o_name=Ada rematch=1 tags=source:discord \
+            digest=af63bc4c8601b62c moves=",
+        ]);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn results_feed_the_ratings() {
+        let path = env::temp_dir().join(format!("tic-tac-toe-bots-{}", process::id()));
+        let _ = fs::remove_file(&path);
+        let (ada, grace) = (user("Ada"), user("Grace"));
+        let archive = Some(Archive::new(&path));
+        let mut bot = Bot::new(Chat::Discord, Difficulty::Perfect, archive, Lang::English);
+        assert_eq!(say(&mut bot, &ada, "!ttt ratings", vec![]), vec![Reply::Send(
+            "Nobody has a rating yet. Ratings come from games between two people.".to_string())]);
+
+        say(&mut bot, &ada, "!ttt play", vec![grace.clone()]);
+        for &(player, tile) in &[(&ada, "1A"), (&grace, "2A"), (&ada, "1B"), (&grace, "2B")] {
+            say(&mut bot, player, tile, vec![]);
+        }
+        let replies = say(&mut bot, &ada, "1C", vec![]);
+        assert_eq!(replies.last(), Some(&Reply::Send(
+            "Ada's rating: 1216 (+16)\nGrace's rating: 1184 (-16)".to_string())));
+        assert_eq!(say(&mut bot, &ada, "!ttt ratings", vec![]), vec![Reply::Send(
+            "Ratings:\n1. Ada - 1216 (1 game)\n2. Grace - 1184 (1 game)".to_string())]);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn irc_boards_are_text() {
+        let mut bot = Bot::new(Chat::Irc, Difficulty::Perfect, None, Lang::English);
+        let (ada, grace) = (User {id: "ada".to_string(), name: "ada".to_string()},
+            User {id: "grace".to_string(), name: "grace".to_string()});
+        say(&mut bot, &ada, "!ttt play grace", vec![]);
+        say(&mut bot, &ada, "2B", vec![]);
+        // Without any way to edit the board, every move sends a new one
+        assert_eq!(say(&mut bot, &grace, "1a", vec![]), vec![Reply::SendBoard(
+            "ada (x) vs grace (o)\n   A B C\n 1 o . .\n 2 . x .\n 3 . . .\nIt's ada's turn."
+                .to_string())]);
+        match say(&mut bot, &ada, "!ttt help", vec![])[..] {
+            [Reply::Send(ref text)] => assert!(text.contains("!ttt play someone - ")),
+            ref replies => panic!("expected the help, got {:?}", replies),
+        }
+    }
+}
@@ -0,0 +1,200 @@
+// Shows the game to spectators, like the viewers of a stream, by keeping a file up to date with the
+// board (`--broadcast FILE`). Streaming programs like OBS can put a text file on the stream and
+// show it again whenever it changes, and anyone else can follow along with `watch cat FILE`.
+//
+// So that the players can't find out anything by watching the stream, the broadcast can be kept
+// behind the game with `--broadcast-delay`, either by a number of moves (`2moves`) or by a time
+// written the same way as the other times (`30s` or `2m`):
+//
+//     --broadcast board.txt --broadcast-delay 2moves
+//
+// The broadcaster follows the game through the event bus (see bus.rs) and keeps its own copy of
+// every position it hasn't shown yet, so the game never waits for it and nothing about the game
+// changes because it is being broadcast.
+//
+// Once a game is over, a broadcast that is behind by some moves catches up right away, since there
+// is nothing left to give away. A broadcast that is behind by some time shows the end of the game
+// once that time is up, as long as the program is still running by then.
+
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use bus::{Event, Subscriber};
+use clock;
+use game::{Game, Piece, Winner};
+use locale::{Lang, Message};
+use player::Players;
+use render::{self, GameView, Layout, Variant};
+use storage;
+use theme::Theme;
+
+// How far behind the game the broadcast is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Delay {
+    /// The broadcast shows the position from this many moves ago
+    Moves(usize),
+    /// The broadcast shows each position this long after it happened
+    Time(Duration),
+}
+
+impl Delay {
+    // Reads a delay like "2moves", "1move", "30s" or "2m". A number on its own is in seconds,
+    // just like the other times.
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        if let Some(moves) = text.strip_suffix("moves").or_else(|| text.strip_suffix("move")) {
+            return moves.trim().parse().ok().map(Delay::Moves);
+        }
+        clock::parse_duration(text).map(Delay::Time)
+    }
+}
+
+pub struct Broadcaster {
+    path: PathBuf,
+    lang: Lang,
+    theme: Theme,
+    delay: Option<Delay>,
+    /// The positions that are waiting to be shown, oldest first, when the delay is in moves
+    pending: VecDeque<String>,
+    /// The thread that shows each position once its time is up, when the delay is a time
+    timed: Option<Sender<(Instant, String)>>,
+    /// The players of the last move, which are still the players when a move is taken back
+    players: Players,
+}
+
+impl Broadcaster {
+    // Starts a broadcast to the file at `path`. Anything left in the file from an earlier
+    // broadcast is cleared, so that spectators don't mistake it for the game that is starting.
+    pub fn new(path: PathBuf, delay: Option<Delay>, lang: Lang, theme: Theme) -> Self {
+        write(&path, "");
+        let timed = match delay {
+            Some(Delay::Time(_)) => {
+                // A channel sends the positions to the thread in the order they happened, so
+                // they are shown in that order too
+                let (sender, receiver) = mpsc::channel::<(Instant, String)>();
+                let path = path.clone();
+                thread::spawn(move || {
+                    for (due, text) in receiver {
+                        // `saturating_duration_since` is zero if that time has already come
+                        thread::sleep(due.saturating_duration_since(Instant::now()));
+                        write(&path, &text);
+                    }
+                });
+                Some(sender)
+            },
+            _ => None,
+        };
+        let (pending, players) = (VecDeque::new(), Players::default());
+        Self {path, lang, theme, delay, pending, timed, players}
+    }
+
+    // Shows a position now, or keeps it until the delay is up
+    fn broadcast(&mut self, game: &Game) {
+        let text = self.describe(game);
+        match self.delay {
+            Some(Delay::Moves(moves)) => {
+                if game.is_finished() {
+                    // The positions that were still waiting are skipped, and the end of the game is
+                    // shown right away
+                    self.pending.clear();
+                    return write(&self.path, &text);
+                }
+                self.pending.push_back(text);
+                if self.pending.len() > moves {
+                    let text = self.pending.pop_front().expect("there are positions waiting");
+                    write(&self.path, &text);
+                }
+            },
+            Some(Delay::Time(delay)) => {
+                let sender = self.timed.as_ref().expect("timed broadcasts have a thread");
+                // The thread only stops once the broadcaster is gone, so sending can't fail
+                let _ = sender.send((Instant::now() + delay, text));
+            },
+            None => write(&self.path, &text),
+        }
+    }
+
+    // The status of the game above the board, and the result once the game is over
+    fn describe(&self, game: &Game) -> String {
+        let players = &self.players;
+        let status = GameView::new(game, players, Variant::Classic).status(self.lang);
+        let grid = render::grid(game.tiles(), game.winning_line(), players, &self.theme,
+            Layout::Compact);
+        let result = match game.winner() {
+            Some(Winner::X) => self.lang.text(Message::GameWon(&players.label(Piece::X))),
+            Some(Winner::O) => self.lang.text(Message::GameWon(&players.label(Piece::O))),
+            Some(Winner::Tie) => self.lang.text(Message::GameTied),
+            None => return format!("{}\n{}", status, grid),
+        };
+        format!("{}\n{}{}\n", status, grid, result)
+    }
+}
+
+impl Subscriber for Broadcaster {
+    fn notify(&mut self, event: &Event) {
+        match *event {
+            Event::MoveMade {game, players, ..} | Event::GameOver {game, players} => {
+                self.players = players.clone();
+                self.broadcast(game);
+            },
+            Event::MoveUndone {game, ..} => self.broadcast(game),
+            _ => {},
+        }
+    }
+}
+
+// Replaces the whole file at once, so that spectators never see half of a board. The game goes on
+// even if the broadcast can't be written.
+fn write(path: &Path, text: &str) {
+    if let Err(error) = storage::write(path, text.as_bytes()) {
+        warn!(path = %path.display(), %error, "the broadcast couldn't be written");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::env;
+    use std::fs;
+    use std::process;
+
+    #[test]
+    fn delays() {
+        assert_eq!(Delay::parse("2moves"), Some(Delay::Moves(2)));
+        assert_eq!(Delay::parse("1 move"), Some(Delay::Moves(1)));
+        assert_eq!(Delay::parse("30s"), Some(Delay::Time(Duration::from_secs(30))));
+        assert_eq!(Delay::parse("2m"), Some(Delay::Time(Duration::from_secs(120))));
+        assert_eq!(Delay::parse("lots"), None);
+        assert_eq!(Delay::parse("-1moves"), None);
+    }
+
+    #[test]
+    fn behind_by_moves() {
+        let path = env::temp_dir().join(format!("tic-tac-toe-broadcast-{}", process::id()));
+        let mut broadcaster = Broadcaster::new(path.clone(), Some(Delay::Moves(1)), Lang::English,
+            Theme::default());
+        let players = Players::default();
+        let mut game = Game::new();
+        let mut positions = Vec::new();
+        // x wins on the last of these moves, with three in a row from 1C to 3A
+        for &(row, col) in &[(1, 1), (0, 0), (0, 2), (1, 0), (2, 0)] {
+            game.make_move(row, col).unwrap();
+            broadcaster.notify(&Event::MoveMade {game: &game, players: &players, row, col});
+            positions.push((broadcaster.describe(&game), fs::read_to_string(&path).unwrap()));
+        }
+
+        // Each position is shown once the move after it is made
+        assert_eq!(positions[0].1, "");
+        for i in 1..4 {
+            assert_eq!(positions[i].1, positions[i - 1].0);
+        }
+        // The winning move is shown right away, since the game is over
+        assert_eq!(positions[4].1, positions[4].0);
+        assert!(positions[4].1.ends_with("x wins!\n"));
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,97 @@
+// The event bus that the parts of the program use to tell each other what happened. The places
+// where things happen (a move being made, a clock running, a message arriving from a chat) publish
+// an event, and every part that cares about it subscribes once, when the program starts. Neither
+// side knows about the other, so something new (like a recorder or a server) can follow the games
+// by subscribing, without the game loop calling it directly.
+//
+// Just like the summary in summary.rs, the subscribers are kept in a `static`, since events happen
+// deep inside the program and the subscribers live until it exits. Events are delivered right
+// away, in the order that the subscribers subscribed, on the thread that published them.
+
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+
+use command::Command;
+use game::{Game, Piece};
+use player::Players;
+
+static SUBSCRIBERS: Mutex<Vec<Box<dyn Subscriber>>> = Mutex::new(Vec::new());
+
+// Everything that can be published. The events only borrow what they are about, so publishing
+// one doesn't copy anything. Subscribers that want to keep something copy it themselves.
+#[derive(Debug, Clone, Copy)]
+pub enum Event<'a> {
+    /// A move was made on the board, by a person or a computer player
+    MoveMade { game: &'a Game, players: &'a Players, row: usize, col: usize },
+    /// The move at this tile was taken back
+    MoveUndone { game: &'a Game, row: usize, col: usize },
+    /// A player typed a command at the move prompt instead of a move
+    Command(&'a Command),
+    /// A computer player finished thinking and chose a move, which is published as a `MoveMade`
+    /// right after
+    ComputerMoved { piece: Piece, players: &'a Players, row: usize, col: usize },
+    /// Moves arrived from somewhere else, like the file of a game played by mail (see watch.rs),
+    /// and now it is this player's turn
+    TurnArrived { piece: Piece, players: &'a Players },
+    /// Time came off of a player's clock, leaving them with `remaining`
+    ClockTick { piece: Piece, remaining: Duration },
+    /// A message arrived from a chat network (see bots.rs), which only happens when the program
+    /// is built with one of the chat bots
+    #[cfg_attr(not(any(feature = "discord", feature = "irc")), allow(dead_code))]
+    ChatMessage { channel: &'a str, author: &'a str },
+    /// A game finished, however it ended
+    GameOver { game: &'a Game, players: &'a Players },
+}
+
+// Something that is told about every event. `Send` lets events be published from any thread.
+pub trait Subscriber: Send {
+    // Called once for every event. Publishing another event from here would wait forever for the
+    // subscribers to be free, so subscribers only take note of what happened.
+    fn notify(&mut self, event: &Event);
+}
+
+// Tells the subscriber about every event that is published from now on
+pub fn subscribe<S: Subscriber + 'static>(subscriber: S) {
+    subscribers().push(Box::new(subscriber));
+}
+
+// Tells every subscriber about the event
+pub fn publish(event: Event) {
+    for subscriber in subscribers().iter_mut() {
+        subscriber.notify(&event);
+    }
+}
+
+fn subscribers() -> MutexGuard<'static, Vec<Box<dyn Subscriber>>> {
+    SUBSCRIBERS.lock().expect("subscribers lock was poisoned")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::Arc;
+
+    // Remembers the authors of the chat messages in one channel. Other tests may publish events
+    // at the same time, so everything else is ignored.
+    struct Listener(Arc<Mutex<Vec<String>>>);
+
+    impl Subscriber for Listener {
+        fn notify(&mut self, event: &Event) {
+            if let Event::ChatMessage {channel: "#bus-test", author, ..} = *event {
+                self.0.lock().unwrap().push(author.to_string());
+            }
+        }
+    }
+
+    #[test]
+    fn subscribers_hear_later_events() {
+        let chat = |author| Event::ChatMessage {channel: "#bus-test", author};
+        publish(chat("Ada"));
+        let heard = Arc::new(Mutex::new(Vec::new()));
+        subscribe(Listener(heard.clone()));
+        publish(chat("Grace"));
+        publish(Event::ChatMessage {channel: "#other", author: "Alan"});
+        assert_eq!(*heard.lock().unwrap(), vec!["Grace"]);
+    }
+}
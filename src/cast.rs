@@ -0,0 +1,168 @@
+// Records everything the program shows in the terminal, along with when it was shown, so that a
+// session can be replayed later with asciinema (https://asciinema.org). The recording is saved in
+// the asciicast v2 format: a line of JSON describing the terminal, followed by one line of JSON
+// for each piece of output.
+//
+//     {"version": 2, "width": 80, "height": 24, "timestamp": 1700000000}
+//     [0.002104, "o", "Welcome to Tic-Tac-Toe!\r\n"]
+//     [1.520733, "o", "2B\r\n"]
+//
+// More info: https://docs.asciinema.org/manual/asciicast/v2/
+//
+// The program prints from a lot of different places, so instead of passing a recording to all of
+// them, we keep it in a `static`. Output that should be recorded is printed with the `out!`,
+// `outln!`, `eout!` and `eoutln!` macros below instead of `print!`, `println!`, `eprint!` and
+// `eprintln!`.
+
+use std::cell::RefCell;
+use std::fs::File;
+use std::io::{self, LineWriter, Write};
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use json;
+use terminal;
+
+// The terminal size stored in the recording when the real size isn't known
+const DEFAULT_SIZE: (usize, usize) = (80, 24);
+
+static RECORDING: OnceLock<Mutex<Recording<LineWriter<File>>>> = OnceLock::new();
+
+thread_local! {
+    // Where `print` and `eprint` write instead of stdout and stderr, if anywhere (see `redirect`).
+    // Each thread has its own, so that tests running at the same time don't mix up their output.
+    static REDIRECT: RefCell<Option<Box<dyn Write>>> = RefCell::new(None);
+}
+
+// These macros work just like `print!`, `println!`, `eprint!` and `eprintln!`, except that they
+// also add what they print to the recording (if there is one). `#[macro_use]` on `mod cast` in
+// main.rs makes them available to every module declared after it.
+// More info: https://doc.rust-lang.org/book/second-edition/appendix-04-macros.html
+macro_rules! out {
+    ($($arg:tt)*) => ($crate::cast::print(&format!($($arg)*)));
+}
+
+macro_rules! outln {
+    () => (out!("\n"));
+    ($($arg:tt)*) => (out!("{}\n", format!($($arg)*)));
+}
+
+macro_rules! eout {
+    ($($arg:tt)*) => ($crate::cast::eprint(&format!($($arg)*)));
+}
+
+macro_rules! eoutln {
+    () => (eout!("\n"));
+    ($($arg:tt)*) => (eout!("{}\n", format!($($arg)*)));
+}
+
+// Writes the events of a recording to any `impl Write`, which lets us test it with a Vec<u8>
+pub struct Recording<W: Write> {
+    out: W,
+    /// When the recording started. Every event is timed from here.
+    start: Instant,
+}
+
+impl<W: Write> Recording<W> {
+    // Starts a recording by writing the header that describes the terminal
+    pub fn new(mut out: W, (width, height): (usize, usize), timestamp: u64) -> io::Result<Self> {
+        writeln!(out, r#"{{"version": 2, "width": {}, "height": {}, "timestamp": {}}}"#,
+            width, height, timestamp)?;
+        Ok(Self {out, start: Instant::now()})
+    }
+
+    // Adds some output to the recording, timed from the start of the recording
+    pub fn output(&mut self, text: &str) -> io::Result<()> {
+        let time = self.start.elapsed();
+        self.event(time, text)
+    }
+
+    fn event(&mut self, time: Duration, text: &str) -> io::Result<()> {
+        // We print "\n" to end a line, but the terminal turns that into "\r\n" before showing it,
+        // so that the cursor also goes back to the start of the line. The recording needs to
+        // have what the terminal got, otherwise the replay would draw lines like a staircase.
+        let text = text.replace('\n', "\r\n");
+        writeln!(self.out, r#"[{:.6}, "o", {}]"#, time.as_secs_f64(), json::string(&text))
+    }
+}
+
+// Starts recording to the file at `path`, replacing the file if it already exists
+pub fn start(path: &Path) -> io::Result<()> {
+    // LineWriter writes each event to the file as soon as its line is complete, so the recording
+    // is still useful if the program is stopped suddenly
+    let file = LineWriter::new(File::create(path)?);
+    let size = terminal::size().unwrap_or(DEFAULT_SIZE);
+    // The time the recording started, in seconds since the start of 1970
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |time| time.as_secs());
+    let recording = Recording::new(file, size, timestamp)?;
+    // Only fails if a recording was already started, which can't happen since main() only calls
+    // this once
+    if RECORDING.set(Mutex::new(recording)).is_err() {
+        unreachable!("the recording should only be started once");
+    }
+    Ok(())
+}
+
+// Adds some text to the recording without printing it. This is for text that the terminal shows
+// without our help, like the lines that the players type.
+pub fn record(text: &str) {
+    if let Some(recording) = RECORDING.get() {
+        // A recording that fails part way through shouldn't stop anyone from playing, so errors
+        // are ignored here
+        let _ = recording.lock().expect("recording lock was poisoned").output(text);
+    }
+}
+
+// Sends everything that the current thread prints to `out` instead of stdout and stderr. The tests
+// use this to check what a whole session showed the players.
+#[cfg(test)]
+pub fn redirect<W: Write + 'static>(out: W) {
+    REDIRECT.with(|redirect| *redirect.borrow_mut() = Some(Box::new(out)));
+}
+
+// Writes the text to the redirected output if the current thread has one. Returns false if it
+// doesn't, so that the text can be printed as usual.
+fn write_redirected(text: &str) -> bool {
+    REDIRECT.with(|redirect| match *redirect.borrow_mut() {
+        Some(ref mut out) => {
+            out.write_all(text.as_bytes()).expect("Failed to write output");
+            true
+        },
+        None => false,
+    })
+}
+
+// Prints to stdout and records it. Use `out!` and `outln!` instead of calling this directly.
+pub fn print(text: &str) {
+    if !write_redirected(text) {
+        print!("{}", text);
+    }
+    record(text);
+}
+
+// Prints to stderr and records it. Use `eout!` and `eoutln!` instead of calling this directly.
+pub fn eprint(text: &str) {
+    if !write_redirected(text) {
+        eprint!("{}", text);
+    }
+    record(text);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn asciicast_lines() {
+        let mut recording = Recording::new(Vec::new(), (80, 24), 1700000000).unwrap();
+        recording.event(Duration::from_millis(1500), "Your move: \"2B\"\n").unwrap();
+
+        let text = String::from_utf8(recording.out).unwrap();
+        let lines: Vec<_> = text.lines().collect();
+        assert_eq!(lines, [
+            r#"{"version": 2, "width": 80, "height": 24, "timestamp": 1700000000}"#,
+            r#"[1.500000, "o", "Your move: \"2B\"\r\n"]"#,
+        ]);
+    }
+}
@@ -0,0 +1,293 @@
+// A closer look at an archive than `verify` gives, for the `archive check` command. On top of the
+// problems that `verify` finds by playing every game again (see archive.rs), this finds:
+//
+// * games that were saved more than once. Two games count as the same if they have the same
+//   first piece, result and moves, up to turning and flipping the board (see symmetry.rs), and
+//   the same times of their moves and pauses if they were timed. The games of a match or a
+//   rematch are never counted, since the games of a series can go the same way more than once.
+// * fields that this version of the program doesn't know about, which most likely means that the
+//   archive was written by a newer (or much older) version. Those fields are skipped when the
+//   archive is read, so whatever they hold is lost to this version.
+//
+// Some problems can be fixed without guessing, because the right answer is already in the
+// archive. `fix` only makes those changes and leaves everything else as it is:
+//
+// * match totals that don't add up are counted again from the games of the match
+// * a match without an `end` is closed before the next match starts (or at the end of the file)
+// * an `end` without a match is taken out
+
+use std::collections::HashMap;
+
+use archive::{self, Problem};
+use engine;
+use game::{Piece, Winner};
+use symmetry;
+
+// The fields that each kind of record can have
+const GAME_FIELDS: &[&str] = &["first", "winner", "forfeit", "adjudicated", "x_name", "o_name",
+    "seed", "rematch", "pauses", "times", "tags", "move_tags", "digest", "moves"];
+const MATCH_FIELDS: &[&str] = &["best_of", "games", "x", "o", "ties", "winner", "rematch"];
+// The fields of a match line with the totals, in the order that `verify_match` gives them
+const TOTALS: [&str; 4] = ["games", "x", "o", "ties"];
+
+// Something found by `check`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Finding {
+    /// Something that `verify` finds
+    Problem(Problem),
+    /// The same game as the one on this earlier line, up to turning and flipping the board
+    Duplicate(usize),
+    /// A field that isn't part of the archive format, at least not in this version of it
+    UnknownField(String),
+}
+
+// A change made by `fix`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Repair {
+    /// The totals of the match were counted again
+    MatchTotals,
+    /// An `end` was added after the last game of the match
+    ClosedMatch,
+    /// An `end` without a match was taken out
+    RemovedEnd,
+}
+
+// Everything found in an archive
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Check {
+    /// The number of games that were checked
+    pub games: usize,
+    /// What was found, along with the line it is on (counting from 1)
+    pub findings: Vec<(usize, Finding)>,
+}
+
+pub fn check(text: &str) -> Check {
+    let verification = archive::verify(text, engine::perfect_result);
+    let mut findings: Vec<_> = verification.problems.into_iter()
+        .map(|(line, problem)| (line, Finding::Problem(problem)))
+        .collect();
+
+    // The line of the first game with each key
+    let mut seen = HashMap::new();
+    // Whether the lines are inside a match
+    let mut in_match = false;
+    for (i, line) in text.lines().enumerate() {
+        let mut fields = line.split(' ');
+        let known = match fields.next() {
+            Some("game") => GAME_FIELDS,
+            Some("match") => {
+                in_match = true;
+                MATCH_FIELDS
+            },
+            Some("end") => {
+                in_match = false;
+                continue;
+            },
+            _ => continue,
+        };
+        for field in fields {
+            let name = field.split('=').next().unwrap_or_default();
+            if !known.contains(&name) {
+                findings.push((i + 1, Finding::UnknownField(name.to_string())));
+            }
+        }
+
+        if let Some(key) = game_key(line).filter(|_| !in_match) {
+            match seen.get(&key) {
+                Some(&first) => findings.push((i + 1, Finding::Duplicate(first))),
+                None => {
+                    seen.insert(key, i + 1);
+                },
+            }
+        }
+    }
+    // The problems came first, so everything is put back in line order. The sort is stable, so
+    // the findings on the same line stay in the same order.
+    findings.sort_by_key(|&(line, _)| line);
+    Check {games: verification.games, findings}
+}
+
+// What makes a game the same as another one: its first piece, its result, its canonical moves
+// and the `times` and `pauses` fields as they were saved. Those hold when each move was made and
+// each pause started, so two timed games are only the same if one is a copy of the other.
+type GameKey = (Piece, Option<Winner>, Vec<(usize, usize)>, Option<String>, Option<String>);
+
+// Only the games that can be read back from the archive have a key. Games of a rematch don't
+// either, since they are part of a series just like the games inside a match.
+fn game_key(line: &str) -> Option<GameKey> {
+    let field = |name: &str| line.split(' ').skip(1)
+        .find_map(|field| field.strip_prefix(name)?.strip_prefix('='))
+        .map(str::to_string);
+    if field("rematch").is_some() {
+        return None;
+    }
+    let archived = archive::parse_games(line).pop()?;
+    let game = archived.game;
+    let moves = symmetry::canonical(&game.moves().to_vec());
+    Some((game.first_piece(), game.winner(), moves, field("times"), field("pauses")))
+}
+
+// Makes the changes that can be made safely (see the top of this file). Returns the fixed text and
+// the changes that were made, along with the line (of the original text) that each one is about.
+pub fn fix(text: &str) -> (String, Vec<(usize, Repair)>) {
+    let mut lines: Vec<String> = Vec::new();
+    let mut repairs = Vec::new();
+    // The match that is still open: its index in `lines`, its line in the original text and the
+    // totals of its games so far. The totals are None if one of the games can't be read, since
+    // then they can't be counted.
+    let mut open: Option<(usize, usize, Option<[u32; 4]>)> = None;
+
+    for (i, line) in text.lines().enumerate() {
+        match line.split(' ').next().unwrap_or_default() {
+            "game" => if let Some((_, _, ref mut totals)) = open {
+                let winner = archive::parse_games(line).pop().and_then(|game| game.game.winner());
+                *totals = match (*totals, winner) {
+                    (Some(mut totals), Some(winner)) => {
+                        totals[0] += 1;
+                        match winner {
+                            Winner::X => totals[1] += 1,
+                            Winner::O => totals[2] += 1,
+                            Winner::Tie => totals[3] += 1,
+                        }
+                        Some(totals)
+                    },
+                    _ => None,
+                };
+            },
+            "match" => {
+                if let Some(open) = open.take() {
+                    close_match(&mut lines, &mut repairs, open, true);
+                }
+                open = Some((lines.len(), i + 1, Some([0; 4])));
+            },
+            "end" => match open.take() {
+                Some(open) => close_match(&mut lines, &mut repairs, open, false),
+                None => {
+                    repairs.push((i + 1, Repair::RemovedEnd));
+                    continue;
+                },
+            },
+            _ => {},
+        }
+        lines.push(line.to_string());
+    }
+    if let Some(open) = open.take() {
+        close_match(&mut lines, &mut repairs, open, true);
+    }
+
+    repairs.sort_by_key(|&(line, _)| line);
+    let mut text = lines.join("\n");
+    if !lines.is_empty() {
+        text.push('\n');
+    }
+    (text, repairs)
+}
+
+// Counts the totals of a match again if they are wrong, and adds its `end` if it is missing
+fn close_match(lines: &mut Vec<String>, repairs: &mut Vec<(usize, Repair)>,
+    (index, line, totals): (usize, usize, Option<[u32; 4]>), missing_end: bool) {
+
+    if let (Ok(saved), Some(totals)) = (archive::verify_match(&lines[index]), totals) {
+        if saved != totals {
+            lines[index] = lines[index].split(' ').map(|field| {
+                let name = field.split('=').next().unwrap_or_default();
+                match TOTALS.iter().position(|&total| total == name) {
+                    Some(i) => format!("{}={}", name, totals[i]),
+                    None => field.to_string(),
+                }
+            }).collect::<Vec<_>>().join(" ");
+            repairs.push((line, Repair::MatchTotals));
+        }
+    }
+    if missing_end {
+        lines.push("end".to_string());
+        repairs.push((line, Repair::ClosedMatch));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn duplicates() {
+        let text = "game first=x winner=x moves=1A,2B,1B,3C,1C\n\
+            game first=x winner=x seed=4 moves=1C,2B,1B,3A,1A\n\
+            game first=o winner=o moves=1A,2B,1B,3C,1C\n";
+        let found = check(text);
+        assert_eq!(found.games, 3);
+        // The second game is the first one flipped left to right, but o started the third game
+        assert_eq!(found.findings, [(2, Finding::Duplicate(1))]);
+
+        // Games that were timed are only the same if they were played at the same time
+        let text = "game first=x winner=x times=1792108804+900,-,1792108811+700,-,1792108815+500 \
+            moves=1A,2B,1B,3C,1C\n\
+            game first=x winner=x times=1792109004+900,-,1792109011+700,-,1792109015+500 \
+            moves=1A,2B,1B,3C,1C\n";
+        assert_eq!(check(text).findings, []);
+    }
+
+    #[test]
+    fn series_are_not_duplicates() {
+        // The games of a series can go the same way, whether it is a match or a rematch
+        let text = "match best_of=3 games=2 x=2 o=0 ties=0 winner=x\n\
+            game first=x winner=x moves=1A,2B,1B,3C,1C\n\
+            game first=x winner=x moves=1A,2B,1B,3C,1C\n\
+            end\n\
+            game first=x winner=x rematch=1 moves=1A,2B,1B,3C,1C\n\
+            game first=x winner=x rematch=2 moves=1A,2B,1B,3C,1C\n";
+        let check = check(text);
+        assert_eq!(check.games, 4);
+        assert_eq!(check.findings, []);
+    }
+
+    #[test]
+    fn unknown_fields() {
+        let text = "match best_of=1 games=1 x=1 o=0 ties=0 winner=x venue=club\n\
+            game first=x winner=x colour=red moves=1A,2B,1B,3C,1C\n\
+            end\n";
+        assert_eq!(check(text).findings, [
+            (1, Finding::UnknownField("venue".to_string())),
+            (2, Finding::UnknownField("colour".to_string())),
+        ]);
+    }
+
+    #[test]
+    fn problems() {
+        let text = "game first=x winner=o moves=1A,2B,1B,3C,1C\nend\n";
+        let findings = check(text).findings;
+        assert!(matches!(findings[0], (1, Finding::Problem(Problem::WrongWinner {..}))));
+        assert_eq!(findings[1], (2, Finding::Problem(Problem::UnexpectedEnd)));
+    }
+
+    #[test]
+    fn fixes() {
+        let text = "match best_of=3 games=1 x=1 o=0 ties=0 winner=x\n\
+            game first=x winner=x moves=1A,2B,1B,3C,1C\n\
+            game first=o winner=o moves=1A,2B,1B,3C,1C\n\
+            end\n\
+            end\n\
+            match best_of=1 games=1 x=0 o=0 ties=1 winner=none\n\
+            game first=x winner=tie moves=2B,1A,3C,1C,1B,3B,2A,2C,3A\n";
+        let (fixed, repairs) = fix(text);
+        assert_eq!(repairs, [
+            (1, Repair::MatchTotals),
+            (5, Repair::RemovedEnd),
+            (6, Repair::ClosedMatch),
+        ]);
+        assert!(fixed.starts_with("match best_of=3 games=2 x=1 o=1 ties=0 winner=x\n"));
+        assert!(fixed.ends_with("3A\nend\n"));
+        assert!(check(&fixed).findings.is_empty());
+        // Fixing it again doesn't change anything
+        assert_eq!(fix(&fixed), (fixed.clone(), Vec::new()));
+    }
+
+    #[test]
+    fn unsafe_fixes() {
+        // The game in the match can't be read, so its totals are left alone
+        let text = "match best_of=1 games=1 x=0 o=1 ties=0 winner=o\n\
+            game first=x winner=x moves=1A,1A\n\
+            end\n";
+        assert_eq!(fix(text), (text.to_string(), Vec::new()));
+    }
+}
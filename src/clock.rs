@@ -0,0 +1,220 @@
+// Chess clocks give each player a limited amount of time to think. The clock of the player whose
+// turn it is counts down, and if it reaches zero (the "flag falls") that player loses the game.
+//
+// Games on a kiosk or at an exhibition can also have an idle timeout (see `Idle`), so that a game
+// that someone walked away from doesn't wait forever for the next visitor.
+
+use std::fmt;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use game::Piece;
+
+// How much time each player gets. With an increment, the player also gets a little time back after
+// every move they make, which is a popular way to keep games from ending in a frantic scramble.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeControl {
+    pub base: Duration,
+    pub increment: Duration,
+}
+
+impl TimeControl {
+    // Parses time controls written as `BASE` or `BASE+INCREMENT`. Each time is a number of
+    // seconds, optionally followed by `s` for seconds or `m` for minutes. For example, "5m" is five
+    // minutes with no increment and "3m+2s" (or "180+2") is three minutes plus two seconds for
+    // every move. Returns None if the text isn't a valid time control.
+    pub fn parse(text: &str) -> Option<Self> {
+        // `splitn(2, ...)` splits at the first `+` only, giving us at most two parts
+        let mut parts = text.splitn(2, '+');
+        let base = parse_duration(parts.next()?)?;
+        let increment = match parts.next() {
+            Some(increment) => parse_duration(increment)?,
+            None => Duration::from_secs(0),
+        };
+
+        // A clock that starts with no time on it would end every game before it began
+        if base == Duration::from_secs(0) {
+            return None;
+        }
+        Some(Self {base, increment})
+    }
+}
+
+// What happens when a person doesn't move in time. Unlike a chess clock, the timeout starts over on
+// every turn, and whenever the player types anything (like a command) before they move.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Idle {
+    pub timeout: Duration,
+    pub action: IdleAction,
+}
+
+impl Idle {
+    // Parses a timeout written the same way as the times of a time control, like "90" or "2m"
+    pub fn parse_timeout(text: &str) -> Option<Duration> {
+        parse_duration(text).filter(|&timeout| timeout > Duration::from_secs(0))
+    }
+}
+
+// What is done for a player who didn't move in time
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IdleAction {
+    /// The computer makes a reasonable move for them, and the game goes on
+    #[default]
+    Move,
+    /// They lose the game
+    Forfeit,
+}
+
+impl IdleAction {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match &name.to_lowercase()[..] {
+            "move" => Some(IdleAction::Move),
+            "forfeit" => Some(IdleAction::Forfeit),
+            _ => None,
+        }
+    }
+}
+
+// Parses a single time like "90", "90s" or "5m"
+pub fn parse_duration(text: &str) -> Option<Duration> {
+    let text = text.trim();
+    // Only the last character can be a unit, so we check for it and remove it if it is there
+    let (number, multiplier) = if let Some(minutes) = text.strip_suffix('m') {
+        (minutes, 60)
+    }
+    else if let Some(seconds) = text.strip_suffix('s') {
+        (seconds, 1)
+    }
+    else {
+        (text, 1)
+    };
+    // `.ok()?` converts a failed parse into None and returns it from this function right away
+    let number: u64 = number.parse().ok()?;
+    Some(Duration::from_secs(number * multiplier))
+}
+
+// The two clocks used during a single game
+#[derive(Debug, Clone)]
+pub struct Clocks {
+    control: TimeControl,
+    x_remaining: Duration,
+    o_remaining: Duration,
+}
+
+impl Clocks {
+    pub fn new(control: TimeControl) -> Self {
+        Self {
+            control,
+            x_remaining: control.base,
+            o_remaining: control.base,
+        }
+    }
+
+    pub fn remaining(&self, piece: Piece) -> Duration {
+        match piece {
+            Piece::X => self.x_remaining,
+            Piece::O => self.o_remaining,
+        }
+    }
+
+    // Returns a mutable reference so that the other methods can change either clock without
+    // having to write out a match every time
+    fn remaining_mut(&mut self, piece: Piece) -> &mut Duration {
+        match piece {
+            Piece::X => &mut self.x_remaining,
+            Piece::O => &mut self.o_remaining,
+        }
+    }
+
+    // Takes time off of a player's clock. The clock stops at zero instead of going negative.
+    pub fn spend(&mut self, piece: Piece, elapsed: Duration) {
+        let remaining = self.remaining_mut(piece);
+        *remaining = remaining.checked_sub(elapsed).unwrap_or_else(|| Duration::from_secs(0));
+    }
+
+    // Called after a player completes a move to give them their increment
+    pub fn add_increment(&mut self, piece: Piece) {
+        let increment = self.control.increment;
+        *self.remaining_mut(piece) += increment;
+    }
+
+    // A player's flag falls when their clock runs out of time
+    pub fn flag_fell(&self, piece: Piece) -> bool {
+        self.remaining(piece) == Duration::from_secs(0)
+    }
+}
+
+// The time on the wall clock, in seconds since 1970-01-01 (UTC). The chess clocks above only count
+// how long the players think, so pauses use this instead to save when they happened.
+pub fn wall_clock() -> u64 {
+    // A system clock set to before 1970 is broken anyway, so it counts as 1970
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+    now.as_secs()
+}
+
+// This wrapper type lets us display a Duration in the familiar "minutes:seconds" format used by
+// chess clocks. Rust doesn't let us implement Display for Duration directly because we didn't
+// define either of them, so we define our own type around it instead. This is called the
+// "newtype" pattern.
+#[derive(Debug, Clone, Copy)]
+pub struct ClockTime(pub Duration);
+
+impl fmt::Display for ClockTime {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // We round up so that a clock only shows 0:00 once it has really run out
+        let mut seconds = self.0.as_secs();
+        if self.0.subsec_nanos() > 0 {
+            seconds += 1;
+        }
+        // `{:02}` pads the number with zeros so that it is always at least two digits wide
+        write!(f, "{}:{:02}", seconds / 60, seconds % 60)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_time_controls() {
+        assert_eq!(TimeControl::parse("5m"), Some(TimeControl {
+            base: Duration::from_secs(300),
+            increment: Duration::from_secs(0),
+        }));
+        assert_eq!(TimeControl::parse("3m+2s"), Some(TimeControl {
+            base: Duration::from_secs(180),
+            increment: Duration::from_secs(2),
+        }));
+        assert_eq!(TimeControl::parse("180+2"), TimeControl::parse("3m+2s"));
+        assert_eq!(TimeControl::parse("0"), None);
+        assert_eq!(TimeControl::parse("fast"), None);
+        assert_eq!(TimeControl::parse("5m+"), None);
+    }
+
+    #[test]
+    fn idle_timeouts() {
+        assert_eq!(Idle::parse_timeout("2m"), Some(Duration::from_secs(120)));
+        assert_eq!(Idle::parse_timeout("0"), None);
+        assert_eq!(IdleAction::from_name("Forfeit"), Some(IdleAction::Forfeit));
+        assert_eq!(IdleAction::from_name("wait"), None);
+    }
+
+    #[test]
+    fn spending_time() {
+        let mut clocks = Clocks::new(TimeControl::parse("10+1").unwrap());
+        clocks.spend(Piece::X, Duration::from_secs(4));
+        clocks.add_increment(Piece::X);
+        assert_eq!(clocks.remaining(Piece::X), Duration::from_secs(7));
+        assert_eq!(clocks.remaining(Piece::O), Duration::from_secs(10));
+
+        clocks.spend(Piece::O, Duration::from_secs(11));
+        assert!(clocks.flag_fell(Piece::O));
+        assert!(!clocks.flag_fell(Piece::X));
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(ClockTime(Duration::from_secs(65)).to_string(), "1:05");
+        assert_eq!(ClockTime(Duration::from_millis(500)).to_string(), "0:01");
+        assert_eq!(ClockTime(Duration::from_secs(0)).to_string(), "0:00");
+    }
+}
@@ -0,0 +1,297 @@
+// A club night for the `club` command: several games going on at once in the same terminal, each
+// at its own table with its own players, clocks and moves. Only one table has the focus at a time,
+// and that is the game that the move prompt plays in. At the prompt, `games` lists the tables,
+// `switch N` gives the focus to table N (or to the next table, without a number) and `new` sets
+// up another table.
+//
+// A single game is played the same way, at a club with only one table that can't be joined by
+// any others (see `Club::single`). That way there is only one loop that plays games.
+//
+// The clocks of a table only run while it has the focus, since the players at the other tables
+// can't move while somebody else is at the prompt.
+//
+// A simul (`simul NAMES`) is a club where one player, the giver, plays x at every table against a
+// different opponent at each. Like at a chess simul, the giver goes from board to board, so the
+// focus moves on to the next table as soon as the giver has made their move (see
+// `Club::next_board`) instead of when the players ask for it.
+
+use std::time::Duration;
+
+use clock::Clocks;
+use game::{Game, Piece};
+use locale::{Lang, Message};
+use player::{PlayerProfile, Players};
+use render::{self, Layout, Variant};
+use session::Settings;
+use terminal;
+use theme::Theme;
+
+// The width of the terminal that the overview of a simul is drawn for when there is no terminal
+const DEFAULT_COLUMNS: usize = 80;
+
+// A game at one of the tables, along with everything that belongs to it alone
+#[derive(Debug, Clone)]
+pub struct Table {
+    /// The number that the table goes by in `games` and `switch`, counting from 1
+    pub id: usize,
+    pub game: Game,
+    /// The players, computer players and time control of this table
+    pub settings: Settings,
+    pub clocks: Option<Clocks>,
+    /// In blindfold mode, the number of times the players can still look at the board
+    pub shows_left: Option<u32>,
+    /// When the players went back to look at an earlier position, the number of moves that had
+    /// been made in it. None means that the game is shown as it is.
+    pub browsing: Option<usize>,
+    /// How long the current player has thought about their move so far. A turn can take several
+    /// prompts when the player types commands, so this adds up the time spent at each of them.
+    pub thinking: Duration,
+}
+
+impl Table {
+    // Every game starts with fresh clocks (if the players are using clocks at all)
+    pub fn new(id: usize, game: Game, settings: Settings) -> Self {
+        Self {
+            id,
+            game,
+            clocks: settings.time_control.map(Clocks::new),
+            shows_left: settings.blindfold_shows,
+            browsing: None,
+            thinking: Duration::from_secs(0),
+            settings,
+        }
+    }
+
+    // How the board is drawn at this table
+    pub fn variant(&self) -> Variant {
+        if self.shows_left.is_some() {
+            Variant::Blindfold
+        }
+        else {
+            Variant::Classic
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Club {
+    tables: Vec<Table>,
+    /// The index of the table with the focus
+    focus: usize,
+    /// The id that the next table gets. Ids are never reused, so that a game keeps its number
+    /// even after the games before it are over.
+    next_id: usize,
+    /// The settings that new tables start from
+    settings: Settings,
+    /// False if no other tables can be set up
+    open: bool,
+    /// True if the tables are the boards of a simul
+    simul: bool,
+    /// How the boards are drawn in the overview of a simul
+    theme: Theme,
+}
+
+impl Club {
+    // A club with one table where the game is played with the given settings
+    pub fn new(game: Game, settings: Settings) -> Self {
+        let table = Table::new(1, game, settings.clone());
+        let theme = Theme::default();
+        Self {tables: vec![table], focus: 0, next_id: 2, settings, open: true, simul: false, theme}
+    }
+
+    // A club where only the one game can be played
+    pub fn single(game: Game, settings: Settings) -> Self {
+        Self {open: false, ..Self::new(game, settings)}
+    }
+
+    // A simul with a board for each of the opponents, who all play o. The giver plays x with the
+    // given settings, which say whether the giver is a computer player.
+    pub fn simul(settings: Settings, opponents: Vec<PlayerProfile>, theme: Theme) -> Self {
+        let giver = settings.players.profile(Piece::X).clone();
+        let tables = opponents.into_iter().zip(1..).map(|(opponent, id)| {
+            let settings = Settings {
+                first_piece: Piece::X,
+                players: Players::new(giver.clone(), opponent),
+                o_engine: None,
+                ..settings.clone()
+            };
+            Table::new(id, settings.new_game(Piece::X), settings)
+        }).collect::<Vec<_>>();
+        let next_id = tables.len() + 1;
+        Self {tables, focus: 0, next_id, settings, open: false, simul: true, theme}
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    pub fn is_simul(&self) -> bool {
+        self.simul
+    }
+
+    pub fn tables(&self) -> &[Table] {
+        &self.tables
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tables.is_empty()
+    }
+
+    // The table with the focus. Every club has one until its last game is over.
+    pub fn focused(&self) -> &Table {
+        &self.tables[self.focus]
+    }
+
+    pub fn focused_mut(&mut self) -> &mut Table {
+        &mut self.tables[self.focus]
+    }
+
+    // Sets up a new table for the given players and gives it the focus. The rest of the settings
+    // (like the clocks) are the same as at the first table. Returns the id of the new table.
+    pub fn open_table(&mut self, players: Players) -> usize {
+        let settings = Settings {
+            first_piece: Piece::X,
+            players,
+            x_engine: None,
+            o_engine: None,
+            ..self.settings.clone()
+        };
+        let id = self.next_id;
+        self.next_id += 1;
+        self.tables.push(Table::new(id, settings.new_game(Piece::X), settings));
+        self.focus = self.tables.len() - 1;
+        id
+    }
+
+    // Gives the focus to the table with the given id, or to the next table if there is no id.
+    // Returns false if there is no table with that id.
+    pub fn switch(&mut self, id: Option<usize>) -> bool {
+        let index = match id {
+            Some(id) => self.tables.iter().position(|table| table.id == id),
+            None => Some((self.focus + 1) % self.tables.len()),
+        };
+        match index {
+            Some(index) => {
+                self.focus = index;
+                true
+            },
+            None => false,
+        }
+    }
+
+    // Gives the focus to the next board of a simul once the giver has moved at this one. Returns
+    // true if the giver is back at the first board, which starts another round.
+    pub fn next_board(&mut self) -> bool {
+        self.switch(None);
+        self.focus == 0
+    }
+
+    // Draws every board of a simul next to each other, each with its opponent's name above it, and
+    // the board that the giver is at marked. As many boards go across as fit in the terminal.
+    pub fn overview(&self, lang: Lang) -> String {
+        let focused = self.focused().id;
+        let drawings: Vec<_> = self.tables.iter().map(|table| {
+            let (game, players) = (&table.game, &table.settings.players);
+            let opponent = &players.label(Piece::O);
+            let title = lang.text(Message::SimulBoard {id: table.id, opponent,
+                focused: table.id == focused});
+            let grid = render::grid(game.tiles(), game.winning_line(), players, &self.theme,
+                Layout::Compact);
+            (title, grid)
+        }).collect();
+        let columns = terminal::size().map_or(DEFAULT_COLUMNS, |(columns, _)| columns);
+        render::side_by_side(&drawings, columns)
+    }
+
+    // Takes away the table with the focus once its game is over. The focus goes to the table
+    // after it, or to the first table if it was the last one.
+    pub fn close_focused(&mut self) -> Table {
+        let table = self.tables.remove(self.focus);
+        if self.focus == self.tables.len() {
+            self.focus = 0;
+        }
+        table
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use options::Options;
+
+    fn settings() -> Settings {
+        Settings::from_options(&Options::default())
+    }
+
+    fn profile(name: &str) -> PlayerProfile {
+        PlayerProfile {name: Some(name.to_string()), glyph: None}
+    }
+
+    fn players(x: &str, o: &str) -> Players {
+        Players::new(profile(x), profile(o))
+    }
+
+    #[test]
+    fn focus() {
+        let mut club = Club::new(Game::new(), settings());
+        assert_eq!(club.open_table(players("Ada", "Grace")), 2);
+        assert_eq!(club.open_table(players("Alan", "Barbara")), 3);
+        assert_eq!(club.focused().id, 3);
+        assert!(club.switch(Some(1)));
+        club.focused_mut().game.make_move(1, 1).unwrap();
+        assert!(!club.switch(Some(4)));
+        assert_eq!(club.focused().id, 1);
+
+        // Every table keeps its own game
+        assert!(club.switch(None));
+        assert_eq!((club.focused().id, club.focused().game.moves().len()), (2, 0));
+        assert_eq!(club.focused().settings.players.name(Piece::O), "Grace");
+        assert!(club.switch(None) && club.switch(None));
+        assert_eq!(club.tables()[0].game.moves().len(), 1);
+    }
+
+    #[test]
+    fn closing_tables() {
+        let mut club = Club::new(Game::new(), settings());
+        club.open_table(players("Ada", "Grace"));
+        club.open_table(players("Alan", "Barbara"));
+        club.switch(Some(2));
+        assert_eq!(club.close_focused().id, 2);
+        assert_eq!(club.focused().id, 3);
+        assert_eq!(club.close_focused().id, 3);
+        assert_eq!(club.focused().id, 1);
+        // Ids aren't reused, even for tables set up after others are gone
+        assert_eq!(club.open_table(players("Ada", "Alan")), 4);
+        club.close_focused();
+        club.close_focused();
+        assert!(club.is_empty());
+    }
+
+    #[test]
+    fn simul() {
+        let mut settings = settings();
+        settings.players = players("Magnus", "nobody");
+        let opponents = vec![profile("Ada"), profile("Grace"), profile("Alan")];
+        let mut club = Club::simul(settings, opponents, Theme::default());
+        assert!(club.is_simul() && !club.is_open());
+        assert_eq!(club.tables().len(), 3);
+        let names: Vec<_> = club.tables().iter()
+            .map(|table| table.settings.players.name(Piece::O).to_string())
+            .collect();
+        assert_eq!(names, ["Ada", "Grace", "Alan"]);
+        assert_eq!(club.focused().settings.players.name(Piece::X), "Magnus");
+
+        // The giver goes round the boards in order, and starts over after the last one
+        assert!(!club.next_board());
+        assert_eq!(club.focused().id, 2);
+        assert!(!club.next_board());
+        assert!(club.next_board());
+        assert_eq!(club.focused().id, 1);
+
+        club.focused_mut().game.make_move(1, 1).unwrap();
+        let overview = club.overview(Lang::English);
+        assert!(overview.starts_with("> Board 1: Ada"));
+        assert!(overview.contains("  Board 3: Alan"));
+    }
+}
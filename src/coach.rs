@@ -0,0 +1,151 @@
+// The coach comments on each move as it is played, the way a friend watching over your shoulder
+// would: "x left 2B open, so o can fork". The comments come from the same threats that the hints
+// and the tutorial look at (see `Game::winning_moves` and `puzzle::idea`), so the coach never
+// needs to search ahead and always talks about something the players can see on the board.
+//
+// How much the coach says depends on its level:
+//
+// * low only points out moves that lose right away: a missed win, or a threat left unblocked
+// * medium also warns about leaving the other player a fork
+// * high also praises the good moves: blocks, threats and forks
+//
+// The coach is attached to a game by wrapping the renderer (see `CoachRenderer` in render.rs),
+// which sees every move that is made. The chat bots ask it about each move in the same way.
+
+use game::Game;
+use puzzle::{self, Idea};
+
+// How much the coach says
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Low,
+    Medium,
+    High,
+}
+
+impl Level {
+    // Finds a level by the name used on the command line
+    pub fn from_name(name: &str) -> Option<Self> {
+        match &name.to_lowercase()[..] {
+            "low" => Some(Level::Low),
+            "medium" => Some(Level::Medium),
+            "high" => Some(Level::High),
+            _ => None,
+        }
+    }
+
+    // The name used for this level on the command line
+    pub fn name(self) -> &'static str {
+        match self {
+            Level::Low => "low",
+            Level::Medium => "medium",
+            Level::High => "high",
+        }
+    }
+}
+
+// What the coach says about a move. The player who made the move is the one being talked about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comment {
+    /// The player could have won right away on this tile instead
+    MissedWin(usize, usize),
+    /// The other player can now win on this tile
+    LeftWin(usize, usize),
+    /// The other player can now make two threats at once on this tile
+    LeftFork(usize, usize),
+    /// The move stopped the other player from winning
+    Block,
+    /// The move threatens to win on this tile
+    Threat(usize, usize),
+    /// The move made two threats at once
+    Fork,
+}
+
+// What the coach says about the last move of the game, if anything. There is nothing left to say
+// once the game is over, since the result speaks for itself.
+pub fn comment(game: &Game, level: Level) -> Option<Comment> {
+    if game.is_finished() {
+        return None;
+    }
+    let mut before = game.clone();
+    let (row, col) = before.undo()?;
+    let piece = before.current_piece();
+    let opponent = piece.other();
+
+    // The mistakes come first, since they matter the most
+    if let Some(&(row, col)) = before.winning_moves(piece).first() {
+        return Some(Comment::MissedWin(row, col));
+    }
+    let threats = game.winning_moves(piece);
+    if let Some(&(row, col)) = game.winning_moves(opponent).first() {
+        return Some(Comment::LeftWin(row, col));
+    }
+    // A fork only works if the other player doesn't have to block a threat first
+    if level >= Level::Medium && threats.is_empty() {
+        if let Some((row, col)) = empty_tiles(game).find(|&(row, col)| {
+            puzzle::idea(game, row, col) == Idea::Fork
+        }) {
+            return Some(Comment::LeftFork(row, col));
+        }
+    }
+
+    if level < Level::High {
+        return None;
+    }
+    match threats.len() {
+        0 if before.winning_moves(opponent).contains(&(row, col)) => Some(Comment::Block),
+        0 => None,
+        1 => Some(Comment::Threat(threats[0].0, threats[0].1)),
+        _ => Some(Comment::Fork),
+    }
+}
+
+fn empty_tiles(game: &Game) -> impl Iterator<Item=(usize, usize)> + '_ {
+    game.tiles().iter().enumerate().flat_map(|(row, tiles)| {
+        tiles.iter().enumerate().filter(|(_, tile)| tile.is_none()).map(move |(col, _)| (row, col))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Plays the moves from an empty board with x going first
+    fn played(moves: &[(usize, usize)]) -> Game {
+        let mut game = Game::new();
+        for &(row, col) in moves {
+            game.make_move(row, col).unwrap();
+        }
+        game
+    }
+
+    #[test]
+    fn mistakes() {
+        // x could have won at 1C but played 3C, which also leaves o's threat at 2C alone
+        let game = played(&[(0, 0), (1, 0), (0, 1), (1, 1), (2, 2)]);
+        assert_eq!(comment(&game, Level::Low), Some(Comment::MissedWin(0, 2)));
+        let game = played(&[(0, 0), (1, 0), (2, 2), (1, 1)]);
+        assert_eq!(comment(&game, Level::Low), None);
+        let game = played(&[(0, 0), (1, 0), (2, 2), (1, 1), (2, 0)]);
+        assert_eq!(comment(&game, Level::Low), Some(Comment::LeftWin(1, 2)));
+
+        // o has to block at 3C, but that leaves x a fork at 2A
+        let game = played(&[(1, 1), (0, 1), (0, 0), (2, 2)]);
+        assert_eq!(comment(&game, Level::Low), None);
+        assert_eq!(comment(&game, Level::Medium), Some(Comment::LeftFork(1, 0)));
+        // Taking a corner against x's opposite corners threatens to win, so x can't fork yet
+        let game = played(&[(0, 0), (1, 1), (2, 2), (0, 2)]);
+        assert_eq!(comment(&game, Level::Medium), None);
+    }
+
+    #[test]
+    fn good_moves() {
+        let game = played(&[(0, 0), (1, 1), (0, 1), (0, 2)]);
+        assert_eq!(comment(&game, Level::Medium), None);
+        assert_eq!(comment(&game, Level::High), Some(Comment::Threat(2, 0)));
+        let game = played(&[(0, 0), (1, 1), (2, 2), (0, 2), (2, 0)]);
+        assert_eq!(comment(&game, Level::High), Some(Comment::Fork));
+        let game = played(&[(1, 1), (0, 0), (0, 1), (2, 1)]);
+        assert_eq!(comment(&game, Level::High), Some(Comment::Block));
+    }
+}
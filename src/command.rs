@@ -0,0 +1,99 @@
+// Besides typing in a move, players can type a command at the move prompt. Commands are whole
+// words so that they can never be mistaken for a move like "1A".
+
+// The commands that can be typed instead of a move
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    /// Suggest a move that wins, or one that stops the other player from winning
+    Hint,
+    /// Take back the last move that was made
+    Undo,
+    /// Show the board (in blindfold mode the board is hidden otherwise)
+    Show,
+    /// Look at the position before the one being shown. Nothing can be played until the player
+    /// goes forward to the game as it is again.
+    Back,
+    /// Look at the position after the one being shown
+    Forward,
+    /// Hide the board and stop the clocks until the players are ready to go on
+    Pause,
+    /// List the games at every table of a club night (see club.rs)
+    Games,
+    /// Go to the game at the table with this number, or to the next table without a number
+    Switch(Option<usize>),
+    /// Set up another table at a club night
+    New,
+    /// Show the code of the game so far, so that it can be shared (see share.rs)
+    Code,
+}
+
+// Every word that is accepted as a command. This is used to tab-complete commands when line
+// editing is enabled.
+#[cfg_attr(not(feature = "line-editing"), allow(dead_code))]
+pub const COMMAND_NAMES: &[&str] = &[
+    "hint", "undo", "show", "back", "forward", "pause", "games", "switch", "new", "code",
+    "indice", "annuler", "montrer", "reculer", "avancer", "parties", "changer", "nouvelle",
+];
+
+impl Command {
+    // Converts what the user typed into a command. Just like the answers to "Play again?", the
+    // commands are accepted in any of the languages we support. Returns None if the input isn't a
+    // command (it may still be a move).
+    pub fn parse(input: &str) -> Option<Self> {
+        let input = input.trim().to_lowercase();
+        // Switching to another table is the only command that takes a value
+        let mut words = input.split_whitespace();
+        if let (Some("switch") | Some("changer"), Some(id), None) =
+            (words.next(), words.next(), words.next()) {
+
+            return id.trim_start_matches('#').parse().ok().map(|id| Command::Switch(Some(id)));
+        }
+
+        match &input[..] {
+            "hint" | "indice" => Some(Command::Hint),
+            "undo" | "annuler" => Some(Command::Undo),
+            "show" | "montrer" => Some(Command::Show),
+            "back" | "reculer" => Some(Command::Back),
+            "forward" | "avancer" => Some(Command::Forward),
+            // The word is the same in French
+            "pause" => Some(Command::Pause),
+            "games" | "parties" => Some(Command::Games),
+            "switch" | "changer" => Some(Command::Switch(None)),
+            "new" | "nouvelle" => Some(Command::New),
+            // This word is the same in French too
+            "code" => Some(Command::Code),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_commands() {
+        assert_eq!(Command::parse("hint"), Some(Command::Hint));
+        assert_eq!(Command::parse(" Undo "), Some(Command::Undo));
+        assert_eq!(Command::parse("annuler"), Some(Command::Undo));
+        assert_eq!(Command::parse("Back"), Some(Command::Back));
+        assert_eq!(Command::parse("avancer"), Some(Command::Forward));
+        assert_eq!(Command::parse("PAUSE"), Some(Command::Pause));
+        assert_eq!(Command::parse("Code"), Some(Command::Code));
+        assert_eq!(Command::parse("1A"), None);
+
+        assert_eq!(Command::parse("games"), Some(Command::Games));
+        assert_eq!(Command::parse("switch"), Some(Command::Switch(None)));
+        assert_eq!(Command::parse("Switch 2"), Some(Command::Switch(Some(2))));
+        assert_eq!(Command::parse("changer #3"), Some(Command::Switch(Some(3))));
+        assert_eq!(Command::parse("switch two"), None);
+        assert_eq!(Command::parse("switch 2 3"), None);
+    }
+
+    #[test]
+    fn every_name_is_a_command() {
+        for name in COMMAND_NAMES {
+            assert!(Command::parse(name).is_some(), "'{}' is not a command", name);
+        }
+    }
+}
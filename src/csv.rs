@@ -0,0 +1,33 @@
+// Just enough CSV (comma-separated values) to save tables that spreadsheets can open. Crates like
+// `csv` can also read CSV files and handle every dialect of the format, but we only ever write
+// simple tables, so two small functions are enough.
+// More info about the format: https://www.rfc-editor.org/rfc/rfc4180
+
+// A single value, with quotes around it if it has a character that means something in CSV. Names
+// can have commas in them, for example. Quotes inside a quoted value are written twice.
+pub fn field(text: &str) -> String {
+    if text.contains(&[',', '"', '\n', '\r'][..]) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    }
+    else {
+        text.to_string()
+    }
+}
+
+// A whole line of values, separated by commas. The line doesn't end with a newline.
+pub fn row<S: AsRef<str>>(values: &[S]) -> String {
+    values.iter().map(|value| field(value.as_ref())).collect::<Vec<_>>().join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quoting() {
+        assert_eq!(field("Ada"), "Ada");
+        assert_eq!(field("Ada, the first"), "\"Ada, the first\"");
+        assert_eq!(field("\"Al\""), "\"\"\"Al\"\"\"");
+        assert_eq!(row(&["1", "Ada, the first", ""]), "1,\"Ada, the first\",");
+    }
+}
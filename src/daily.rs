@@ -0,0 +1,190 @@
+// The daily puzzle for the `daily` command. Everyone who plays on the same day gets the same
+// puzzle, since it is picked from the generated puzzles (see generate.rs) with a random number
+// generator seeded by the date instead of the time. Solving the daily puzzle on days in a row
+// builds up a streak, which is kept in the preferences file along with the player's other
+// settings (see prefs.rs).
+//
+// Once the puzzle is solved, the game shows a result that can be pasted into a chat without
+// giving the puzzle away, like the one from Wordle. Each row is one of the player's moves, with a
+// square for every try at it:
+//
+//     tic-tac-toe daily 2026-10-16
+//     ⬛🟩
+//     🟨
+//
+// A black square is a move that was taken back, a yellow one is a right move found with a hint,
+// and a green one is a right move found without any help.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use generate;
+use puzzle::{Puzzle, Try};
+use random::Rng;
+
+// The number of seconds in a day. The days start at midnight UTC, so that everyone gets the same
+// puzzle at the same time wherever they are.
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+// The days in a row that the daily puzzle was solved on. Days are counted from 1970-01-01.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Streak {
+    /// The last day the daily puzzle was solved on
+    pub last: Option<u64>,
+    /// The days in a row up to and including `last`
+    pub current: u32,
+    /// The longest streak so far
+    pub best: u32,
+}
+
+impl Streak {
+    // Counts the puzzle of the given day as solved. Solving it again on the same day doesn't make
+    // the streak any longer.
+    pub fn solved(&mut self, day: u64) {
+        self.current = match self.last {
+            Some(last) if last == day => self.current,
+            Some(last) if last + 1 == day => self.current + 1,
+            _ => 1,
+        };
+        self.last = Some(day);
+        self.best = self.best.max(self.current);
+    }
+}
+
+// The day it is now, counted from 1970-01-01
+pub fn today() -> u64 {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+    now.as_secs() / SECONDS_PER_DAY
+}
+
+// The puzzle for the given day. It only depends on the day, so it is the same for everyone.
+pub fn puzzle(day: u64) -> Puzzle {
+    let mut puzzles = generate::every_position();
+    // Each day gets its own stream of the same seed, so that the puzzles of days next to each
+    // other have nothing to do with each other
+    let index = Rng::stream(0, day).below(puzzles.len());
+    puzzles.swap_remove(index)
+}
+
+// The result to share once the day's puzzle is solved, with one row of squares for each move
+pub fn share(day: u64, tries: &[Try]) -> String {
+    let mut text = format!("tic-tac-toe daily {}", date(day));
+    let mut row = String::new();
+    for &attempt in tries {
+        row.push(match attempt {
+            Try::Wrong => '⬛',
+            Try::Hinted => '🟨',
+            Try::Right => '🟩',
+        });
+        if attempt != Try::Wrong {
+            text.push('\n');
+            text.push_str(&row);
+            row.clear();
+        }
+    }
+    text
+}
+
+// Writes a day as a date like "2026-10-16"
+pub fn date(day: u64) -> String {
+    let (year, month, day) = civil_from_days(day);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+// Reads a date like "2026-10-16" as a day. Returns None for anything else, including dates
+// before 1970 and dates that don't exist, like 2026-02-30.
+pub fn parse_date(text: &str) -> Option<u64> {
+    let mut parts = text.splitn(3, '-').map(|part| part.parse::<u64>().ok());
+    let (year, month, day) = match (parts.next(), parts.next(), parts.next()) {
+        (Some(Some(year)), Some(Some(month)), Some(Some(day))) => (year, month, day),
+        _ => return None,
+    };
+    if year < 1970 || !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    let days = days_from_civil(year, month, day);
+    // Going back and forth only gives the same date if the date exists
+    if civil_from_days(days) == (year, month, day) {
+        Some(days)
+    }
+    else {
+        None
+    }
+}
+
+// Converting between days and dates has to take the different lengths of the months and the leap
+// years into account. These are Howard Hinnant's algorithms, which count in "eras" of 400 years
+// starting in March, so that the leap day comes at the end of each year.
+// More info: https://howardhinnant.github.io/date_algorithms.html
+fn days_from_civil(year: u64, month: u64, day: u64) -> u64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year / 400;
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    // 719468 is the number of days from 0000-03-01 to 1970-01-01
+    era * 146097 + day_of_era - 719468
+}
+
+fn civil_from_days(days: u64) -> (u64, u64, u64) {
+    let days = days + 719468;
+    let era = days / 146097;
+    let day_of_era = days - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_from_march = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_from_march + 2) / 5 + 1;
+    // The months were counted from March, so January and February belong to the next year
+    let (month, year) = if month_from_march < 10 {
+        (month_from_march + 3, year_of_era + era * 400)
+    }
+    else {
+        (month_from_march - 9, year_of_era + era * 400 + 1)
+    };
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dates() {
+        assert_eq!(date(0), "1970-01-01");
+        assert_eq!(parse_date("2026-10-16"), Some(20742));
+        assert_eq!(date(20742), "2026-10-16");
+        assert_eq!(parse_date("2024-02-29").map(date), Some("2024-02-29".to_string()));
+        assert_eq!(parse_date("2026-02-29"), None);
+        assert_eq!(parse_date("1969-12-31"), None);
+        assert_eq!(parse_date("2026-10"), None);
+    }
+
+    #[test]
+    fn same_puzzle_all_day() {
+        let first = puzzle(20742);
+        let again = puzzle(20742);
+        assert_eq!(first.game.tiles(), again.game.tiles());
+        assert_eq!((first.goal, first.moves), (again.goal, again.moves));
+        // Not every day can have a different puzzle, but a whole week of the same one would mean
+        // that the day isn't used at all
+        assert!((20743..20750).any(|day| puzzle(day).game.tiles() != first.game.tiles()));
+    }
+
+    #[test]
+    fn streaks() {
+        let mut streak = Streak::default();
+        streak.solved(10);
+        streak.solved(11);
+        streak.solved(11);
+        assert_eq!((streak.current, streak.best), (2, 2));
+        // Missing a day starts the streak over
+        streak.solved(13);
+        assert_eq!((streak.current, streak.best), (1, 2));
+    }
+
+    #[test]
+    fn share_rows() {
+        let tries = [Try::Wrong, Try::Right, Try::Hinted];
+        assert_eq!(share(20742, &tries), "tic-tac-toe daily 2026-10-16\n⬛🟩\n🟨");
+    }
+}
@@ -0,0 +1,104 @@
+// Comparing two games move by move, for the `diff` command. This is handy for seeing where a new
+// version of a computer player starts to play differently from the old one.
+//
+// Two games that only differ by turning or flipping the board (see symmetry.rs) are really the
+// same game, so the moves are compared up to symmetry: the games go the same way for as long as
+// their moves so far have the same canonical form.
+
+use game::{Game, Winner};
+use symmetry;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diff {
+    /// The number of moves at the start of both games that are the same, up to turning and
+    /// flipping the board
+    pub common: usize,
+    /// True if those moves were played on exactly the same tiles, rather than on a turned or
+    /// flipped board
+    pub exact: bool,
+    /// The first moves that aren't the same, in the first game and in the second. None if one of
+    /// the games ran out of moves first.
+    pub diverged: Option<((usize, usize), (usize, usize))>,
+    /// The number of moves in each game
+    pub lengths: (usize, usize),
+    /// The result of each game, or None if it isn't over
+    pub results: (Option<Winner>, Option<Winner>),
+}
+
+impl Diff {
+    // Returns true if both games have the same moves (up to symmetry) and the same result
+    pub fn is_same(&self) -> bool {
+        self.diverged.is_none() && self.lengths.0 == self.lengths.1
+            && self.results.0 == self.results.1
+    }
+}
+
+pub fn diff(first: &Game, second: &Game) -> Diff {
+    let (a, b) = (first.moves().to_vec(), second.moves().to_vec());
+    // Games with different first pieces never have a move in common, since the same tiles would
+    // belong to different players
+    let shortest = if first.first_piece() == second.first_piece() {
+        a.len().min(b.len())
+    }
+    else {
+        0
+    };
+    let common = (0..=shortest).rev()
+        .find(|&length| symmetry::canonical(&a[..length]) == symmetry::canonical(&b[..length]))
+        .unwrap_or(0);
+    Diff {
+        common,
+        exact: a[..common] == b[..common],
+        diverged: a.get(common).and_then(|&x| b.get(common).map(|&y| (x, y))),
+        lengths: (a.len(), b.len()),
+        results: (first.winner(), second.winner()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use game::Piece;
+
+    fn game(first_piece: Piece, moves: &[(usize, usize)]) -> Game {
+        let mut game = Game::starting_with(first_piece);
+        for &(row, col) in moves {
+            game.make_move(row, col).unwrap();
+        }
+        game
+    }
+
+    #[test]
+    fn divergence() {
+        let first = game(Piece::X, &[(1, 1), (0, 0), (2, 2)]);
+        let second = game(Piece::X, &[(1, 1), (0, 0), (0, 2), (2, 0)]);
+        let diff = diff(&first, &second);
+        assert_eq!(diff.common, 2);
+        assert!(diff.exact);
+        assert_eq!(diff.diverged, Some(((2, 2), (0, 2))));
+        assert_eq!(diff.lengths, (3, 4));
+        assert!(!diff.is_same());
+    }
+
+    #[test]
+    fn symmetry() {
+        // The second game is the first one flipped left to right
+        let first = game(Piece::X, &[(0, 0), (1, 1), (0, 1)]);
+        let second = game(Piece::X, &[(0, 2), (1, 1), (0, 1)]);
+        let diff = diff(&first, &second);
+        assert_eq!((diff.common, diff.exact, diff.diverged), (3, false, None));
+        assert!(diff.is_same());
+
+        // The edge next to the corner can't be turned into the far corner
+        let third = game(Piece::X, &[(0, 2), (1, 1), (2, 2)]);
+        let diff = super::diff(&first, &third);
+        assert_eq!((diff.common, diff.diverged), (2, Some(((0, 1), (2, 2)))));
+    }
+
+    #[test]
+    fn different_first_pieces() {
+        let diff = diff(&game(Piece::X, &[(1, 1)]), &game(Piece::O, &[(1, 1)]));
+        assert_eq!((diff.common, diff.diverged), (0, Some(((1, 1), (1, 1)))));
+    }
+}
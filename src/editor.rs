@@ -0,0 +1,131 @@
+// The line editor from the `rustyline` crate gives the move prompt arrow-key editing, a history of
+// what was typed earlier, and tab completion for commands like `hint` and `undo`. This module is
+// only compiled when the `line-editing` feature is enabled:
+//
+//     cargo run --features line-editing
+
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::Instant;
+
+use rustyline::{Context, Editor, Helper};
+use rustyline::error::ReadlineError;
+use rustyline::completion::Completer;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::DefaultHistory;
+use rustyline::validate::Validator;
+
+use cast;
+use command::COMMAND_NAMES;
+use input::{Input, ReadError, receive};
+use interrupt;
+
+// Reads lines using rustyline's line editor. Like StdinInput, the editor runs on its own thread so
+// that we can stop waiting when a clock runs out. The editor needs to know the prompt before it
+// starts reading, so we send it each prompt and it sends back the line that was typed.
+pub struct EditorInput {
+    prompts: Sender<String>,
+    lines: Receiver<Option<String>>,
+    // True while the editor is still waiting for the answer to an earlier prompt. This happens
+    // when a player runs out of time part way through typing their move. The editor is still
+    // showing that prompt, so we wait for its answer instead of asking again.
+    waiting: bool,
+}
+
+impl EditorInput {
+    // Returns None if the terminal can't be used for line editing
+    pub fn new() -> Option<Self> {
+        let mut editor: Editor<CommandCompleter, DefaultHistory> = Editor::new().ok()?;
+        editor.set_helper(Some(CommandCompleter));
+
+        let (prompts, prompt_receiver) = mpsc::channel::<String>();
+        let (sender, lines) = mpsc::channel();
+        thread::spawn(move || {
+            for prompt in prompt_receiver {
+                let line = match editor.readline(&prompt) {
+                    Ok(line) => line,
+                    // The editor reads Ctrl-C itself instead of letting it become a signal, so
+                    // we pass it on and wait to be asked for the next line
+                    Err(ReadlineError::Interrupted) => {
+                        interrupt::request();
+                        continue;
+                    },
+                    // Ctrl-D ends the input, but the player might not have meant to quit, so we
+                    // still read the next line if we are asked to
+                    Err(ReadlineError::Eof) => {
+                        if sender.send(None).is_err() {
+                            break;
+                        }
+                        continue;
+                    },
+                    // Any other error ends the input for good, just like reaching the end of
+                    // stdin does
+                    Err(_) => break,
+                };
+                // Blank lines aren't worth scrolling back through with the arrow keys
+                if !line.trim().is_empty() {
+                    // Adding to the history can only fail when it is backed by a file
+                    let _ = editor.add_history_entry(line.trim());
+                }
+                if sender.send(Some(line)).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Some(Self {prompts, lines, waiting: false})
+    }
+}
+
+impl Input for EditorInput {
+    fn read_line(&mut self, prompt: &str, deadline: Option<Instant>) -> Result<String, ReadError> {
+        if !self.waiting {
+            // The editor draws the prompt itself, so we only need to record it
+            cast::record(prompt);
+            // Sending only fails if the editor thread has stopped, and then `receive` below will
+            // report that the input was closed
+            let _ = self.prompts.send(prompt.to_string());
+            self.waiting = true;
+        }
+
+        let result = receive(&self.lines, deadline);
+        // Running out of time is the only way to stop waiting while the editor is still reading
+        if result != Err(ReadError::TimedOut) {
+            self.waiting = false;
+        }
+        result
+    }
+}
+
+// Tells rustyline how to complete the word before the cursor when tab is pressed. Rustyline can
+// also show hints, highlight the line and check it before accepting it, but we are happy with the
+// defaults for all of those.
+struct CommandCompleter;
+
+impl Completer for CommandCompleter {
+    type Candidate = String;
+
+    fn complete(&self, line: &str, pos: usize, _ctx: &Context)
+        -> rustyline::Result<(usize, Vec<String>)> {
+
+        // The word being completed starts after the last space before the cursor
+        let start = line[..pos].rfind(' ').map(|i| i + 1).unwrap_or(0);
+        let word = line[start..pos].to_lowercase();
+        let candidates = COMMAND_NAMES.iter()
+            .filter(|name| name.starts_with(&word[..]))
+            .map(|name| name.to_string())
+            .collect();
+        Ok((start, candidates))
+    }
+}
+
+impl Hinter for CommandCompleter {
+    type Hint = String;
+}
+
+impl Highlighter for CommandCompleter {}
+
+impl Validator for CommandCompleter {}
+
+impl Helper for CommandCompleter {}
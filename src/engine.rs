@@ -0,0 +1,485 @@
+// The engine picks moves for computer players. Each difficulty uses a different strategy:
+//
+// * easy plays anywhere at random
+// * medium wins when it can and blocks when it has to, and otherwise plays at random
+// * perfect always plays one of the best moves, and never loses
+//
+// The perfect player doesn't search for its moves while it plays. build.rs searches every
+// position with solve.rs when the program is compiled, and the table it writes is included right
+// here, so finding the best moves only takes looking them up.
+//
+// The table only knows the normal rules, so games played by homebrew rules (see WinCondition in
+// game.rs) are searched while they are played instead. Every search on a thread shares one
+// transposition table (see tt.rs), so the positions scored for one move are still there for the
+// next. The table starts over when the rules change, since the scores of one variant mean nothing
+// in another.
+//
+// Besides the three difficulties, players can set up their own computer players in the
+// preferences file (see prefs.rs) and refer to them by name, like `--o engine:aggressive`. Each of
+// these profiles picks one of the difficulties' ways of playing as its algorithm, and can change
+// how it plays:
+//
+//     engine.aggressive.algorithm = minimax
+//     engine.aggressive.depth = 2
+//     engine.aggressive.evaluator = lines
+//     engine.aggressive.randomness = 10
+//
+// * `depth` only looks that many moves ahead (counting its own move) instead of to the end of
+//   the game
+// * `time` keeps looking one move further ahead until the time, like `200ms`, is up
+// * `evaluator` decides how good the positions it stops looking at are: `neutral` calls them all a
+//   tie, and `lines` counts the lines each player could still get three in a row on
+// * `randomness` is the percentage of moves played at random instead
+//
+// The depth, time and evaluator only change how the minimax algorithm plays.
+
+use std::cell::RefCell;
+use std::time::{Duration, Instant};
+
+use game::{Game, Piece, Winner};
+use random::Rng;
+use solve::{self, empty_tiles};
+use tt::{self, Table};
+
+// What a win is worth to the search. Evaluators score the positions the search stops at well below
+// this, so that a sure win always beats a position that only looks good.
+const WIN_SCORE: i32 = 100;
+
+// Defines PERFECT_MOVES and PERFECT_RESULTS, which have an entry for every key from tt::key. The
+// entry in PERFECT_MOVES has bit `row * 3 + col` set for each of the best moves, and the one in
+// PERFECT_RESULTS is how the game ends with perfect play. `include!` pastes the file in as if we
+// had written it here ourselves.
+include!(concat!(env!("OUT_DIR"), "/perfect.rs"));
+
+thread_local! {
+    // The table for searching games played by homebrew rules, along with the name of the rules
+    // that its scores are for. A whole game fits easily in a megabyte.
+    static SEARCH_TABLE: RefCell<(&'static str, Table)> =
+        RefCell::new(("", Table::with_megabytes(1)));
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Perfect,
+}
+
+impl Difficulty {
+    // Finds a difficulty by the name used on the command line
+    pub fn from_name(name: &str) -> Option<Self> {
+        match &name.to_lowercase()[..] {
+            // Each difficulty can also be called by the way it picks its moves
+            "easy" | "random" => Some(Difficulty::Easy),
+            "medium" => Some(Difficulty::Medium),
+            "perfect" | "minimax" => Some(Difficulty::Perfect),
+            _ => None,
+        }
+    }
+
+    // The name used for this difficulty on the command line
+    pub fn name(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Medium => "medium",
+            Difficulty::Perfect => "perfect",
+        }
+    }
+}
+
+// How the search scores the positions it stops looking at before the game is over
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Evaluator {
+    /// Every unfinished position is as good as a tie
+    #[default]
+    Neutral,
+    /// The lines the player could still get three in a row on, minus their opponent's
+    Lines,
+}
+
+impl Evaluator {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match &name.to_lowercase()[..] {
+            "neutral" => Some(Evaluator::Neutral),
+            "lines" => Some(Evaluator::Lines),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Evaluator::Neutral => "neutral",
+            Evaluator::Lines => "lines",
+        }
+    }
+
+    // How good the position is for `piece`
+    fn score(self, game: &Game, piece: Piece) -> i32 {
+        match self {
+            Evaluator::Neutral => 0,
+            Evaluator::Lines => {
+                game.open_lines(piece) as i32 - game.open_lines(piece.other()) as i32
+            },
+        }
+    }
+}
+
+// The settings of a computer player defined in the preferences. Without any changes, a profile
+// plays just like its algorithm's difficulty.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Profile {
+    /// The difficulty whose way of choosing moves this profile uses
+    pub algorithm: Difficulty,
+    /// The most moves that minimax looks ahead, or None to look to the end of the game
+    pub depth: Option<u32>,
+    /// How long minimax can keep looking further ahead, or None to not stop early
+    pub time: Option<Duration>,
+    /// How minimax scores the positions it stops looking at
+    pub evaluator: Evaluator,
+    /// The percentage of moves that are played at random instead
+    pub randomness: u32,
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Difficulty::Perfect.into()
+    }
+}
+
+// Lets every difficulty be used wherever a profile is expected
+impl From<Difficulty> for Profile {
+    fn from(algorithm: Difficulty) -> Self {
+        Profile {algorithm, depth: None, time: None, evaluator: Evaluator::Neutral, randomness: 0}
+    }
+}
+
+impl Profile {
+    // Changes one setting, given by the name it has in the preferences file. Returns None if there
+    // is no such setting or the value isn't valid for it.
+    pub fn set(&mut self, setting: &str, value: &str) -> Option<()> {
+        match setting {
+            "algorithm" => self.algorithm = Difficulty::from_name(value)?,
+            "depth" => self.depth = Some(value.parse().ok().filter(|&depth| depth > 0)?),
+            "time" => self.time = Some(parse_time(value).filter(|time| !time.is_zero())?),
+            "evaluator" => self.evaluator = Evaluator::from_name(value)?,
+            "randomness" => {
+                let percent = value.strip_suffix('%').unwrap_or(value).trim();
+                self.randomness = percent.parse().ok().filter(|&percent| percent <= 100)?;
+            },
+            _ => return None,
+        }
+        Some(())
+    }
+
+    // The settings as they are written in the preferences file, leaving out the ones that are the
+    // same as for the algorithm's difficulty
+    pub fn settings(&self) -> Vec<(&'static str, String)> {
+        let mut settings = vec![("algorithm", self.algorithm.name().to_string())];
+        if let Some(depth) = self.depth {
+            settings.push(("depth", depth.to_string()));
+        }
+        if let Some(time) = self.time {
+            settings.push(("time", format!("{}ms", time.as_millis())));
+        }
+        if self.evaluator != Evaluator::default() {
+            settings.push(("evaluator", self.evaluator.name().to_string()));
+        }
+        if self.randomness > 0 {
+            settings.push(("randomness", self.randomness.to_string()));
+        }
+        settings
+    }
+
+    // Whether this profile always plays one of the best moves, so that the result of its games
+    // can be known ahead of time (see simulate.rs)
+    pub fn is_perfect(&self) -> bool {
+        *self == Difficulty::Perfect.into()
+    }
+}
+
+// Reads a time like "500ms" or "2s"
+fn parse_time(text: &str) -> Option<Duration> {
+    if let Some(millis) = text.strip_suffix("ms") {
+        Some(Duration::from_millis(millis.trim().parse().ok()?))
+    }
+    else {
+        let seconds = text.strip_suffix('s').unwrap_or(text).trim();
+        Some(Duration::from_secs(seconds.parse().ok()?))
+    }
+}
+
+// Picks a move for the current player the way the profile says to. A profile made from a
+// difficulty picks exactly the same moves as `choose_move` would.
+pub fn choose_profile_move(game: &Game, profile: &Profile, rng: &mut Rng) -> (usize, usize) {
+    assert!(!game.is_finished(), "cannot choose a move in a finished game");
+    // Only rolling the dice when there is randomness keeps the other profiles' games the same
+    if profile.randomness > 0 && rng.below(100) < profile.randomness as usize {
+        return choose_move(game, Difficulty::Easy, rng);
+    }
+    let searches = profile.depth.is_some() || profile.time.is_some();
+    if profile.algorithm != Difficulty::Perfect || !searches {
+        return choose_move(game, profile.algorithm, rng);
+    }
+
+    // Iterative deepening: each search looks one move further ahead than the one before it, until
+    // the depth or the time runs out. The time is only checked between searches, since even the
+    // deepest one takes a moment at most.
+    let started = Instant::now();
+    let tiles_left = empty_tiles(game).len() as u32;
+    let depth = profile.depth.unwrap_or(tiles_left).min(tiles_left);
+    let mut best = search_moves(game, 1, profile.evaluator);
+    for depth in 2..=depth {
+        if profile.time.is_some_and(|time| started.elapsed() >= time) {
+            break;
+        }
+        best = search_moves(game, depth, profile.evaluator);
+    }
+    rng.choose(&best).expect("a game that isn't finished has an empty tile")
+}
+
+// The moves that look best when searching `depth` moves ahead, counting the move itself
+fn search_moves(game: &Game, depth: u32, evaluator: Evaluator) -> Vec<(usize, usize)> {
+    let piece = game.current_piece();
+    let scores: Vec<_> = empty_tiles(game).into_iter().map(|(row, col)| {
+        let score = -search(&play(game, row, col), piece.other(), depth - 1, evaluator);
+        ((row, col), score)
+    }).collect();
+    let best = scores.iter().map(|&(_, score)| score).max()
+        .expect("a game that isn't finished has an empty tile");
+    scores.into_iter().filter(|&(_, score)| score == best).map(|(tile, _)| tile).collect()
+}
+
+// How good the game is for `piece`, the player about to move, looking `depth` moves ahead. This is
+// the same negamax search as in solve.rs, except that it can stop before the end of the game.
+fn search(game: &Game, piece: Piece, depth: u32, evaluator: Evaluator) -> i32 {
+    // Just like in solve.rs, winning sooner is worth more
+    let tiles_left = 9 - game.moves().len() as i32;
+    match game.winner() {
+        Some(Winner::Tie) => return 0,
+        Some(won) if won == winner(piece) => return WIN_SCORE + tiles_left,
+        Some(_) => return -(WIN_SCORE + tiles_left),
+        None => {},
+    }
+    if depth == 0 {
+        return evaluator.score(game, piece);
+    }
+    empty_tiles(game).into_iter()
+        .map(|(row, col)| -search(&play(game, row, col), piece.other(), depth - 1, evaluator))
+        .max()
+        .expect("a game that isn't finished has an empty tile")
+}
+
+// A copy of the game with one more move made in it
+fn play(game: &Game, row: usize, col: usize) -> Game {
+    let mut next = game.clone();
+    next.make_move(row, col).expect("only empty tiles are searched");
+    next
+}
+
+fn winner(piece: Piece) -> Winner {
+    match piece {
+        Piece::X => Winner::X,
+        Piece::O => Winner::O,
+    }
+}
+
+// Picks a move for the current player. When several moves are equally good, one of them is picked
+// at random so that the computer doesn't play the same game every time.
+pub fn choose_move(game: &Game, difficulty: Difficulty, rng: &mut Rng) -> (usize, usize) {
+    assert!(!game.is_finished(), "cannot choose a move in a finished game");
+    let piece = game.current_piece();
+
+    let candidates = match difficulty {
+        Difficulty::Easy => empty_tiles(game),
+        Difficulty::Medium => {
+            let wins = game.winning_moves(piece);
+            let blocks = game.winning_moves(piece.other());
+            // `if` is an expression, so each branch gives back the list of moves to pick from
+            if !wins.is_empty() {
+                wins
+            }
+            else if !blocks.is_empty() {
+                blocks
+            }
+            else {
+                empty_tiles(game)
+            }
+        },
+        Difficulty::Perfect => perfect_moves(game),
+    };
+
+    rng.choose(&candidates).expect("a game that isn't finished has an empty tile")
+}
+
+// How the game ends if both players play perfectly from here on
+pub fn perfect_result(game: &Game) -> Winner {
+    if let Some(winner) = game.winner() {
+        return winner;
+    }
+    if game.rules().is_some() {
+        return with_search_table(game, |table| solve::perfect_result(game, table));
+    }
+    PERFECT_RESULTS[tt::key(game) as usize].expect("every position that can happen is in the table")
+}
+
+// All of the moves that lead to the best possible result for the current player, in the same
+// order as the tiles
+pub fn perfect_moves(game: &Game) -> Vec<(usize, usize)> {
+    if game.rules().is_some() {
+        return with_search_table(game, |table| solve::best_moves(game, table));
+    }
+    let mask = PERFECT_MOVES[tt::key(game) as usize];
+    (0..9).filter(|tile| mask & (1 << tile) != 0).map(|tile| (tile / 3, tile % 3)).collect()
+}
+
+// Searches a game played by homebrew rules with the table for this thread. Each search makes the
+// scores from the searches before it older, so they are the first to be replaced.
+fn with_search_table<T>(game: &Game, search: impl FnOnce(&mut Table) -> T) -> T {
+    let rules = game.rules().expect("only games played by homebrew rules are searched").name();
+    SEARCH_TABLE.with(|current| {
+        let (ref mut current_rules, ref mut table) = *current.borrow_mut();
+        if *current_rules == rules {
+            table.new_search();
+        }
+        else {
+            *current_rules = rules;
+            *table = Table::with_megabytes(1);
+        }
+        search(table)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::HashSet;
+
+    use game::{self, Piece, Tiles, WinCondition};
+
+    // Builds a game by playing the given moves in order
+    fn play(moves: &[(usize, usize)]) -> Game {
+        let mut game = Game::new();
+        for &(row, col) in moves {
+            game.make_move(row, col).unwrap();
+        }
+        game
+    }
+
+    #[test]
+    fn difficulty_names() {
+        assert_eq!(Difficulty::from_name("Perfect"), Some(Difficulty::Perfect));
+        assert_eq!(Difficulty::from_name("impossible"), None);
+        assert_eq!(Difficulty::from_name("minimax"), Some(Difficulty::Perfect));
+        assert_eq!(Difficulty::from_name(Difficulty::Easy.name()), Some(Difficulty::Easy));
+    }
+
+    #[test]
+    fn medium_wins_and_blocks() {
+        let mut rng = Rng::new(1);
+        // x can win at 1C
+        let game = play(&[(0, 0), (1, 0), (0, 1), (1, 1)]);
+        assert_eq!(choose_move(&game, Difficulty::Medium, &mut rng), (0, 2));
+        // o has to block x at 1C
+        let game = play(&[(0, 0), (1, 0), (0, 1)]);
+        assert_eq!(choose_move(&game, Difficulty::Medium, &mut rng), (0, 2));
+    }
+
+    #[test]
+    fn profiles() {
+        let mut profile = Profile::default();
+        assert!(profile.is_perfect());
+        assert_eq!(profile.set("depth", "1"), Some(()));
+        assert_eq!(profile.set("evaluator", "lines"), Some(()));
+        assert_eq!(profile.set("randomness", "101%"), None);
+        assert_eq!(profile.set("mood", "angry"), None);
+        assert!(!profile.is_perfect());
+
+        // Looking one move ahead, the center leaves o the fewest lines
+        let mut rng = Rng::new(1);
+        assert_eq!(choose_profile_move(&Game::new(), &profile, &mut rng), (1, 1));
+        // Looking two moves ahead, o sees that x can win at 1C and blocks it
+        profile.set("depth", "2");
+        let game = play(&[(0, 0), (1, 1), (0, 1)]);
+        assert_eq!(choose_profile_move(&game, &profile, &mut rng), (0, 2));
+        // Completely random moves are still legal
+        profile.set("randomness", "100");
+        let (row, col) = choose_profile_move(&game, &profile, &mut rng);
+        assert!(game.tiles()[row][col].is_none());
+    }
+
+    #[test]
+    fn table_matches_the_search() {
+        // Every position that can happen with either player going first, each checked once
+        let mut positions = vec![Game::new(), Game::starting_with(Piece::O)];
+        let mut checked = HashSet::new();
+        let mut table = Table::with_megabytes(1);
+        while let Some(game) = positions.pop() {
+            if !checked.insert(tt::key(&game)) {
+                continue;
+            }
+            table.new_search();
+            assert_eq!(perfect_result(&game), solve::perfect_result(&game, &mut table));
+            if game.is_finished() {
+                continue;
+            }
+            assert_eq!(perfect_moves(&game), solve::best_moves(&game, &mut table));
+            for (row, col) in empty_tiles(&game) {
+                let mut next = game.clone();
+                next.make_move(row, col).unwrap();
+                positions.push(next);
+            }
+        }
+        // The 5478 positions that can happen when x goes first, and as many again when o does
+        assert_eq!(checked.len(), 5478 * 2);
+    }
+
+    // The normal rules under another name, which are searched just like homebrew rules
+    #[derive(Debug)]
+    struct Renamed(&'static str);
+
+    impl WinCondition for Renamed {
+        fn winner(&self, tiles: &Tiles, _last: Piece) -> Option<Winner> {
+            game::three_in_a_row(tiles).map(winner)
+        }
+
+        fn name(&self) -> &'static str {
+            self.0
+        }
+    }
+
+    #[test]
+    fn searches_share_a_table() {
+        let first = Game::new().with_rules(Some(&Renamed("first")));
+        let mut reply = first.clone();
+        reply.make_move(1, 1).unwrap();
+        let has_score = |game: &Game| {
+            SEARCH_TABLE.with(|current| current.borrow().1.get(tt::key(game)).is_some())
+        };
+
+        // Searching the first move scores the positions after it, which the search for the reply
+        // then finds already in the table
+        assert!(!has_score(&reply));
+        let moves = perfect_moves(&first);
+        assert!(has_score(&reply));
+        assert_eq!(perfect_moves(&reply), solve::best_moves(&reply, &mut Table::with_megabytes(1)));
+        assert_eq!(moves, solve::best_moves(&first, &mut Table::with_megabytes(1)));
+
+        // The scores are thrown away when the rules change
+        let mut second = Game::new().with_rules(Some(&Renamed("second")));
+        second.make_move(0, 0).unwrap();
+        perfect_result(&second);
+        assert!(!has_score(&reply));
+    }
+
+    #[test]
+    fn perfect_play_is_a_tie() {
+        let mut rng = Rng::new(7);
+        let mut game = Game::new();
+        while !game.is_finished() {
+            let (row, col) = choose_move(&game, Difficulty::Perfect, &mut rng);
+            game.make_move(row, col).unwrap();
+        }
+        assert_eq!(game.winner(), Some(Winner::Tie));
+    }
+}
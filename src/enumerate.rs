@@ -0,0 +1,138 @@
+// Walks every position that can be reached from a starting position, counting each one once no
+// matter how many different orders of moves lead to it. There are 5478 positions reachable from
+// the empty board, or 765 if boards that only differ by turning or flipping are counted as one
+// (see symmetry.rs).
+//
+// While walking, the result of perfect play is worked out for every position from the results of
+// the positions after it. That is a second, independent solution of the game, so comparing it to
+// the engine's table of perfect moves checks both of them. Since the results are looked up by
+// position, a mistake in telling positions apart (or in finding their canonical form) would show
+// up here too.
+
+use std::collections::HashMap;
+
+use engine;
+use game::{Game, Piece, Tiles, Winner};
+use symmetry;
+
+// How many positions ended in each way
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Counts {
+    pub x_wins: u32,
+    pub o_wins: u32,
+    pub ties: u32,
+    /// Positions where the game isn't over yet
+    pub ongoing: u32,
+}
+
+impl Counts {
+    pub fn total(&self) -> u32 {
+        self.x_wins + self.o_wins + self.ties + self.ongoing
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Enumeration {
+    pub counts: Counts,
+    /// The moves leading to each position where the engine disagrees with the enumeration about
+    /// how the game ends with perfect play. This should always be empty.
+    pub mismatches: Vec<Vec<(usize, usize)>>,
+}
+
+// Counts every position reachable from `start`. With `symmetry`, positions that only differ by
+// turning or flipping the board are counted once.
+pub fn enumerate(start: &Game, symmetry: bool) -> Enumeration {
+    let mut enumeration = Enumeration::default();
+    visit(start, symmetry, &mut HashMap::new(), &mut enumeration);
+    enumeration
+}
+
+// Visits a position and everything after it, returning how the game ends with perfect play. Every
+// position that was already visited is in `results`, so it is only counted once.
+fn visit(game: &Game, symmetry: bool, results: &mut HashMap<Tiles, Winner>,
+    enumeration: &mut Enumeration) -> Winner {
+
+    // The tiles are enough to tell positions apart, since they also decide whose turn it is and
+    // whether someone has won
+    let key = if symmetry { symmetry::canonical_tiles(game.tiles()) } else { *game.tiles() };
+    if let Some(&result) = results.get(&key) {
+        return result;
+    }
+
+    let counts = &mut enumeration.counts;
+    let result = match game.winner() {
+        Some(winner) => {
+            match winner {
+                Winner::X => counts.x_wins += 1,
+                Winner::O => counts.o_wins += 1,
+                Winner::Tie => counts.ties += 1,
+            }
+            winner
+        },
+        None => {
+            counts.ongoing += 1;
+            let mut next_results = Vec::new();
+            for row in 0..3 {
+                for col in 0..3 {
+                    let mut next = game.clone();
+                    if next.make_move(row, col).is_ok() {
+                        next_results.push(visit(&next, symmetry, results, enumeration));
+                    }
+                }
+            }
+
+            let result = best_result(game.current_piece(), &next_results);
+            if engine::perfect_result(game) != result {
+                enumeration.mismatches.push(game.moves().to_vec());
+            }
+            result
+        },
+    };
+    results.insert(key, result);
+    result
+}
+
+// The best of the results that `piece` can choose between: winning if it can, and otherwise a tie
+// if it can avoid losing
+fn best_result(piece: Piece, results: &[Winner]) -> Winner {
+    let win = match piece {
+        Piece::X => Winner::X,
+        Piece::O => Winner::O,
+    };
+    if results.contains(&win) {
+        win
+    }
+    else if results.contains(&Winner::Tie) {
+        Winner::Tie
+    }
+    else {
+        // Every move loses
+        results[0]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn modulo_symmetry() {
+        let enumeration = enumerate(&Game::new(), true);
+        assert_eq!(enumeration.counts, Counts {x_wins: 91, o_wins: 44, ties: 3, ongoing: 627});
+        assert_eq!(enumeration.counts.total(), 765);
+        assert!(enumeration.mismatches.is_empty());
+    }
+
+    #[test]
+    fn from_a_position() {
+        let mut game = Game::new();
+        for &(row, col) in &[(0, 0), (1, 1), (2, 2), (0, 2), (2, 0), (1, 0)] {
+            game.make_move(row, col).unwrap();
+        }
+        // x can win right away at 3B. Otherwise, o can win at 2C, or the game goes on. Both ways
+        // of filling the last three tiles with a tie end up with the same board.
+        let enumeration = enumerate(&game, false);
+        assert_eq!(enumeration.counts, Counts {x_wins: 2, o_wins: 1, ties: 1, ongoing: 6});
+        assert!(enumeration.mismatches.is_empty());
+    }
+}
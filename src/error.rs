@@ -0,0 +1,239 @@
+// Every error that the program reports has a name, like `tile_not_empty`, and a number, like 102.
+// Both are part of the JSON output (see `JsonRenderer`), and they never change once they are given
+// out, so other programs can check for them instead of reading the messages, which are translated
+// and may be reworded at any time. The numbers are grouped by where the error comes from:
+//
+// * 1xx - the rules: moves that can't be played and positions that can't be reached
+// * 2xx - the command line: options, player names and glyphs, and game codes
+// * 3xx - what the players type, like answers and commands
+// * 4xx - files: the archive, the preferences and everything that is saved or exported
+// * 5xx - the connections of the chat bots
+// * 6xx - the Protocol Buffers messages (see proto/src/lib.rs)
+// * 7xx - plugins (see plugin.rs)
+//
+// The error types (like `MoveError` in game.rs) know their own numbers through a `code` method, so
+// that the libraries for other languages can give them out too. The errors that don't have a type
+// of their own are listed here.
+
+use game::{MoveError, PositionError};
+use input::ReadError;
+use options::OptionsError;
+use player::ProfileError;
+use plugin::PluginError;
+use share::CodeError;
+#[cfg(feature = "discord")]
+use gateway::GatewayError;
+#[cfg(feature = "irc")]
+use irc::IrcError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ErrorCode {
+    /// A short name for the kind of error, e.g. "tile_not_empty"
+    pub name: &'static str,
+    /// The number of the error. Errors with the same name can have different numbers, like the
+    /// different reasons that a position can't be reached.
+    pub number: u16,
+}
+
+const fn code(name: &'static str, number: u16) -> ErrorCode {
+    ErrorCode {name, number}
+}
+
+// The rules
+pub const POSITION_OVER: ErrorCode = code("position_over", 120);
+
+// What the players type
+pub const INVALID_ANSWER: ErrorCode = code("invalid_answer", 310);
+pub const INVALID_MOVE: ErrorCode = code("invalid_move", 311);
+pub const INVALID_EDIT: ErrorCode = code("invalid_edit", 312);
+pub const WRONG_MOVE: ErrorCode = code("wrong_move", 313);
+pub const NOTHING_TO_UNDO: ErrorCode = code("nothing_to_undo", 314);
+pub const NO_EARLIER_POSITION: ErrorCode = code("no_earlier_position", 315);
+pub const NO_SHOWS_LEFT: ErrorCode = code("no_shows_left", 316);
+pub const BROWSING: ErrorCode = code("browsing", 317);
+pub const NOT_BROWSING: ErrorCode = code("not_browsing", 318);
+pub const NOT_AT_A_CLUB: ErrorCode = code("not_at_a_club", 319);
+pub const NO_SUCH_TABLE: ErrorCode = code("no_such_table", 320);
+
+// Files
+pub const ARCHIVE_FAILED: ErrorCode = code("archive_failed", 400);
+pub const EXPORT_FAILED: ErrorCode = code("export_failed", 401);
+pub const PREFS_FAILED: ErrorCode = code("prefs_failed", 402);
+pub const NO_PREFS_DIR: ErrorCode = code("no_prefs_dir", 403);
+pub const PROFILE_FAILED: ErrorCode = code("profile_failed", 404);
+pub const PUZZLES_FAILED: ErrorCode = code("puzzles_failed", 405);
+pub const INVALID_PUZZLE: ErrorCode = code("invalid_puzzle", 406);
+pub const WATCH_FAILED: ErrorCode = code("watch_failed", 407);
+pub const READ_FAILED: ErrorCode = code("read_failed", 408);
+#[cfg(not(feature = "images"))]
+pub const IMAGES_UNAVAILABLE: ErrorCode = code("images_unavailable", 409);
+
+// The chat bots
+#[cfg(not(feature = "discord"))]
+pub const DISCORD_UNAVAILABLE: ErrorCode = code("discord_unavailable", 503);
+#[cfg(feature = "discord")]
+pub const NO_TOKEN: ErrorCode = code("no_token", 504);
+#[cfg(not(feature = "irc"))]
+pub const IRC_UNAVAILABLE: ErrorCode = code("irc_unavailable", 505);
+
+// Plugins
+#[cfg(not(feature = "plugins"))]
+pub const PLUGINS_UNAVAILABLE: ErrorCode = code("plugins_unavailable", 709);
+
+impl<'a> From<&'a MoveError> for ErrorCode {
+    fn from(error: &'a MoveError) -> Self {
+        let name = match *error {
+            MoveError::GameAlreadyOver => "game_over",
+            MoveError::InvalidPosition {..} => "not_on_the_board",
+            MoveError::TileNotEmpty {..} => "tile_not_empty",
+        };
+        code(name, error.code())
+    }
+}
+
+impl<'a> From<&'a PositionError> for ErrorCode {
+    fn from(error: &'a PositionError) -> Self {
+        code("invalid_position", error.code())
+    }
+}
+
+impl<'a> From<&'a OptionsError> for ErrorCode {
+    fn from(error: &'a OptionsError) -> Self {
+        let name = match *error {
+            OptionsError::UnknownArgument(_) => "unknown_argument",
+            OptionsError::MissingValue(_) => "missing_value",
+            OptionsError::InvalidValue {..} => "invalid_value",
+            OptionsError::Requires {..} => "option_requires",
+        };
+        code(name, error.code())
+    }
+}
+
+impl<'a> From<&'a ProfileError> for ErrorCode {
+    fn from(error: &'a ProfileError) -> Self {
+        let name = match *error {
+            ProfileError::EmptyName => "empty_name",
+            ProfileError::NameTooLong => "name_too_long",
+            ProfileError::InvalidGlyph => "invalid_glyph",
+        };
+        code(name, error.code())
+    }
+}
+
+impl<'a> From<&'a CodeError> for ErrorCode {
+    fn from(error: &'a CodeError) -> Self {
+        code("invalid_code", error.code())
+    }
+}
+
+impl<'a> From<&'a ReadError> for ErrorCode {
+    fn from(error: &'a ReadError) -> Self {
+        let name = match *error {
+            ReadError::TimedOut => "timed_out",
+            ReadError::Closed => "input_closed",
+            ReadError::Interrupted => "interrupted",
+        };
+        code(name, error.code())
+    }
+}
+
+impl<'a> From<&'a PluginError> for ErrorCode {
+    fn from(error: &'a PluginError) -> Self {
+        let name = match *error {
+            PluginError::Load(_) => "plugin_failed",
+            PluginError::WrongVersion(_) => "plugin_abi_version",
+            PluginError::MissingFunction(_) => "plugin_missing_function",
+            PluginError::InvalidName => "invalid_plugin_name",
+            PluginError::NothingProvided => "empty_plugin",
+            PluginError::Duplicate(_) => "duplicate_plugin",
+            PluginError::NoRenderer(_) | PluginError::NoPlayer(_) => "no_such_plugin",
+            PluginError::InvalidMove(_) => "invalid_plugin_move",
+        };
+        code(name, error.code())
+    }
+}
+
+#[cfg(feature = "discord")]
+impl<'a> From<&'a GatewayError> for ErrorCode {
+    fn from(error: &'a GatewayError) -> Self {
+        let name = match *error {
+            GatewayError::Disconnected(_) => "discord_disconnected",
+            GatewayError::Rejected(_) => "discord_rejected",
+            GatewayError::Interrupted => "interrupted",
+        };
+        code(name, error.code())
+    }
+}
+
+#[cfg(feature = "irc")]
+impl<'a> From<&'a IrcError> for ErrorCode {
+    fn from(error: &'a IrcError) -> Self {
+        let name = match *error {
+            IrcError::Disconnected(_) => "irc_disconnected",
+            IrcError::NickInUse => "nick_in_use",
+            IrcError::Interrupted => "interrupted",
+        };
+        code(name, error.code())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::HashMap;
+
+    use game::Piece;
+
+    #[test]
+    fn numbers_are_unique() {
+        let codes: Vec<ErrorCode> = vec![
+            POSITION_OVER, INVALID_ANSWER, INVALID_MOVE, INVALID_EDIT, WRONG_MOVE, NOTHING_TO_UNDO,
+            NO_EARLIER_POSITION, NO_SHOWS_LEFT, BROWSING, NOT_BROWSING, NOT_AT_A_CLUB,
+            NO_SUCH_TABLE, ARCHIVE_FAILED, EXPORT_FAILED, PREFS_FAILED, NO_PREFS_DIR,
+            PROFILE_FAILED, PUZZLES_FAILED, INVALID_PUZZLE, WATCH_FAILED, READ_FAILED,
+            (&MoveError::GameAlreadyOver).into(),
+            (&MoveError::InvalidPosition {row: 3, col: 0}).into(),
+            (&MoveError::TileNotEmpty {other_piece: Piece::X, row: 0, col: 0, since: 1}).into(),
+            (&PositionError::PieceCounts {x: 2, o: 0, next: Piece::X}).into(),
+            (&PositionError::Unreachable).into(),
+            (&OptionsError::UnknownArgument(String::new())).into(),
+            (&OptionsError::MissingValue(String::new())).into(),
+            (&OptionsError::InvalidValue {option: String::new(), value: String::new()}).into(),
+            (&OptionsError::Requires {option: String::new(), required: String::new()}).into(),
+            (&ProfileError::EmptyName).into(),
+            (&ProfileError::NameTooLong).into(),
+            (&ProfileError::InvalidGlyph).into(),
+            (&CodeError::InvalidCharacter('1')).into(),
+            (&CodeError::InvalidLength).into(),
+            (&CodeError::UnknownVersion(2)).into(),
+            (&CodeError::InvalidMove).into(),
+            (&CodeError::UnknownRules(3)).into(),
+            (&ReadError::TimedOut).into(),
+            (&ReadError::Closed).into(),
+            (&ReadError::Interrupted).into(),
+            (&PluginError::Load(String::new())).into(),
+            (&PluginError::WrongVersion(0)).into(),
+            (&PluginError::MissingFunction("")).into(),
+            (&PluginError::InvalidName).into(),
+            (&PluginError::NothingProvided).into(),
+            (&PluginError::Duplicate(String::new())).into(),
+            (&PluginError::NoRenderer(String::new())).into(),
+            (&PluginError::NoPlayer(String::new())).into(),
+            (&PluginError::InvalidMove(9)).into(),
+            #[cfg(feature = "irc")]
+            (&IrcError::Disconnected(String::new())).into(),
+            #[cfg(feature = "irc")]
+            (&IrcError::NickInUse).into(),
+        ];
+        // The same number always has the same name, but a name can have several numbers
+        let mut names = HashMap::new();
+        for code in &codes {
+            assert_eq!(*names.entry(code.number).or_insert(code.name), code.name, "{:?}", code);
+        }
+        assert_eq!(names.len(), codes.len());
+        // Ctrl-C is the same error wherever it is pressed
+        #[cfg(feature = "irc")]
+        assert_eq!(ErrorCode::from(&IrcError::Interrupted), (&ReadError::Interrupted).into());
+    }
+}
@@ -0,0 +1,200 @@
+// The opening explorer of the `explore` command walks down the game tree one move at a time,
+// starting from the empty board. At every position, each move that can be played is listed along
+// with what it leads to if both players play perfectly (see solve.rs) and how it went in the games
+// in the archive, if there is one.
+//
+// Moves that only differ by turning or flipping the board (see symmetry.rs) lead to the same game,
+// so they are listed together, like the four corners of the empty board. The games in the archive
+// are matched up the same way, by the canonical form of the moves they started with, so a game
+// that started in the bottom right corner counts for the top left corner too.
+
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+use archive::ArchivedGame;
+use engine;
+use game::{Game, Piece, Winner};
+use notation::parse_move;
+use openings::OpeningResults;
+use solve::empty_tiles;
+use symmetry;
+
+// What a move leads to with perfect play, for the player who makes it. The best verdict comes
+// first, so that sorting puts the best moves at the top.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Verdict {
+    Win,
+    Draw,
+    Loss,
+}
+
+// One of the moves that can be played in a position
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Candidate {
+    /// The tiles that are all the same move seen from a different side of the board, in the
+    /// order of the tiles
+    pub tiles: Vec<(usize, usize)>,
+    pub verdict: Verdict,
+    /// How the games in the archive that made this move went, for the player who made it
+    pub results: OpeningResults,
+}
+
+// What can be typed at the explorer's prompt
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step {
+    /// Play a move to see the position after it
+    Play(usize, usize),
+    /// Take back the last move
+    Back,
+    /// Stop exploring
+    Done,
+}
+
+impl Step {
+    // Converts what the user typed into a step. Returns None if it wasn't recognized.
+    pub fn parse(input: &str) -> Option<Self> {
+        match &input.trim().to_lowercase()[..] {
+            "b" | "back" | "p" | "précédent" | "precedent" => Some(Step::Back),
+            "q" | "quit" | "quitter" => Some(Step::Done),
+            tile => parse_move(tile).ok().map(|(row, col)| Step::Play(row, col)),
+        }
+    }
+}
+
+pub struct Explorer {
+    /// The results of the games in the archive, by the canonical form of each list of moves that
+    /// one of them started with. The results are for the player who made the last of the moves.
+    lines: HashMap<Vec<(usize, usize)>, OpeningResults>,
+}
+
+impl Explorer {
+    pub fn new(games: &[ArchivedGame]) -> Self {
+        let mut lines: HashMap<_, OpeningResults> = HashMap::new();
+        for archived in games {
+            let game = &archived.game;
+            // The solver's verdicts are for the normal rules, and a homebrew game can't be
+            // compared with them
+            if game.rules().is_some() {
+                continue;
+            }
+            let moves: Vec<_> = game.moves().iter().collect();
+            for length in 1..=moves.len() {
+                // The players take turns, so the first player made every other move
+                let piece = if length % 2 == 1 {
+                    game.first_piece()
+                }
+                else {
+                    game.first_piece().other()
+                };
+                let results = lines.entry(symmetry::canonical(&moves[..length])).or_default();
+                match (game.winner(), piece) {
+                    (Some(Winner::Tie), _) => results.ties += 1,
+                    (Some(Winner::X), Piece::X) | (Some(Winner::O), Piece::O) => results.wins += 1,
+                    (Some(_), _) => results.losses += 1,
+                    (None, _) => unreachable!("archived games should be finished"),
+                }
+            }
+        }
+        Self {lines}
+    }
+
+    // Every move that can be played in the position, with the moves that are the same under
+    // symmetry together. The best moves come first, and the ones played most often in the
+    // archive come first among moves that are just as good.
+    pub fn candidates(&self, position: &Game) -> Vec<Candidate> {
+        let piece = position.current_piece();
+        let moves: Vec<_> = position.moves().iter().collect();
+        let mut candidates: Vec<(Vec<(usize, usize)>, Candidate)> = Vec::new();
+        for (row, col) in empty_tiles(position) {
+            let mut line = moves.clone();
+            line.push((row, col));
+            let line = symmetry::canonical(&line);
+            // The same move from another side of the board was already looked at
+            if let Some((_, candidate)) = candidates.iter_mut().find(|(other, _)| *other == line) {
+                candidate.tiles.push((row, col));
+                continue;
+            }
+
+            let mut next = position.clone();
+            next.make_move(row, col).expect("empty tiles are always valid moves");
+            let verdict = match engine::perfect_result(&next) {
+                Winner::Tie => Verdict::Draw,
+                Winner::X if piece == Piece::X => Verdict::Win,
+                Winner::O if piece == Piece::O => Verdict::Win,
+                _ => Verdict::Loss,
+            };
+            let results = self.lines.get(&line).copied().unwrap_or_default();
+            candidates.push((line, Candidate {tiles: vec![(row, col)], verdict, results}));
+        }
+
+        let mut candidates: Vec<_> = candidates.into_iter().map(|(_, candidate)| candidate)
+            .collect();
+        // `sort_by_key` keeps moves that are equal in the order of their tiles. `Reverse` puts the
+        // moves played most often first.
+        candidates.sort_by_key(|candidate| (candidate.verdict, Reverse(candidate.results.games())));
+        candidates
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use player::Players;
+
+    fn archived(first: Piece, moves: &[(usize, usize)]) -> ArchivedGame {
+        let mut game = Game::starting_with(first);
+        for &(row, col) in moves {
+            game.make_move(row, col).unwrap();
+        }
+        ArchivedGame {game, players: Players::default()}
+    }
+
+    #[test]
+    fn empty_board() {
+        // The first player takes the top row after starting in the top left corner
+        let corner_win = [(0, 0), (1, 0), (0, 1), (1, 1), (0, 2)];
+        // The same game turned upside down, started by o
+        let flipped_win = [(2, 0), (1, 0), (2, 1), (1, 1), (2, 2)];
+        let explorer = Explorer::new(&[archived(Piece::X, &corner_win),
+            archived(Piece::O, &flipped_win)]);
+        let candidates = explorer.candidates(&Game::new());
+
+        // Every move draws, so the corners come first since they were played, and then the
+        // other moves in the order of their tiles
+        let tiles: Vec<_> = candidates.iter().map(|candidate| candidate.tiles.clone()).collect();
+        assert_eq!(tiles, [
+            vec![(0, 0), (0, 2), (2, 0), (2, 2)],
+            vec![(0, 1), (1, 0), (1, 2), (2, 1)],
+            vec![(1, 1)],
+        ]);
+        assert!(candidates.iter().all(|candidate| candidate.verdict == Verdict::Draw));
+        assert_eq!(candidates[0].results, OpeningResults {wins: 2, ties: 0, losses: 0});
+        assert_eq!(candidates[2].results.games(), 0);
+    }
+
+    #[test]
+    fn verdicts_and_replies() {
+        let explorer = Explorer::new(&[archived(Piece::X, &[(0, 0), (1, 0), (0, 1), (1, 1),
+            (0, 2)])]);
+        let mut game = Game::new();
+        game.make_move(0, 0).unwrap();
+        let candidates = explorer.candidates(&game);
+        // Only the center holds the draw against a corner opening
+        assert_eq!(candidates[0].tiles, [(1, 1)]);
+        assert_eq!(candidates[0].verdict, Verdict::Draw);
+        assert!(candidates[1..].iter().all(|candidate| candidate.verdict == Verdict::Loss));
+        // The reply below the corner is the same as the one beside it, and o lost with it
+        let edge = candidates.iter().find(|candidate| candidate.tiles.contains(&(1, 0))).unwrap();
+        assert_eq!(edge.tiles, [(0, 1), (1, 0)]);
+        assert_eq!(edge.results, OpeningResults {wins: 0, ties: 0, losses: 1});
+    }
+
+    #[test]
+    fn steps() {
+        assert_eq!(Step::parse(" 2b "), Some(Step::Play(1, 1)));
+        assert_eq!(Step::parse("Back"), Some(Step::Back));
+        assert_eq!(Step::parse("quitter"), Some(Step::Done));
+        assert_eq!(Step::parse("4D"), None);
+    }
+}
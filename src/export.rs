@@ -0,0 +1,168 @@
+// Exports the archive as tables that can be opened in a spreadsheet, for clubs that want to look
+// at their results in their own way. There are three tables:
+//
+// * `games` has one row for every game in the archive
+// * `profiles` has the lifetime stats of every named player (see stats.rs)
+// * `ratings` has the rating of every named player (see rating.rs)
+//
+// Just like the stats, the tables are worked out from the archive every time they are exported.
+
+use std::io::{self, Write};
+
+use archive::ArchivedGame;
+use csv;
+use game::{Piece, Winner};
+use notation;
+use rating;
+use stats;
+
+// The tables that can be exported
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Table {
+    Games,
+    Profiles,
+    Ratings,
+}
+
+impl Table {
+    // Finds a table by the name used on the command line
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "games" => Some(Table::Games),
+            "profiles" => Some(Table::Profiles),
+            "ratings" => Some(Table::Ratings),
+            _ => None,
+        }
+    }
+}
+
+// The formats that tables and training data (see training.rs) can be exported in. Tables can only
+// be exported as CSV, since NPZ files are made for arrays of numbers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Npz,
+}
+
+impl ExportFormat {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "csv" => Some(ExportFormat::Csv),
+            "npz" => Some(ExportFormat::Npz),
+            _ => None,
+        }
+    }
+}
+
+// Every game in the archive is played with the standard rules. The column is there so that the
+// table keeps the same shape if other rules are ever added.
+const RULESET: &str = "classic";
+
+// Writes the table as CSV, starting with a line that names the columns
+pub fn write_csv<W: Write>(mut out: W, table: Table, games: &[ArchivedGame]) -> io::Result<()> {
+    match table {
+        Table::Games => {
+            writeln!(out, "game,ruleset,x,o,first,result,forfeit,move_count,moves")?;
+            for (i, archived) in games.iter().enumerate() {
+                let game = &archived.game;
+                // Players without a name are left empty
+                let name = |piece| archived.players.profile(piece).name.clone().unwrap_or_default();
+                let moves: Vec<_> = game.moves().iter().map(notation).collect();
+                writeln!(out, "{}", csv::row(&[
+                    (i + 1).to_string(),
+                    RULESET.to_string(),
+                    name(Piece::X),
+                    name(Piece::O),
+                    piece_name(game.first_piece()).to_string(),
+                    result_name(game.winner()).to_string(),
+                    game.forfeited_by().map(piece_name).unwrap_or_default().to_string(),
+                    moves.len().to_string(),
+                    moves.join(" "),
+                ]))?;
+            }
+        },
+
+        Table::Profiles => {
+            writeln!(out, "player,games,wins,losses,ties,best_win_streak,favorite_opening")?;
+            for (name, stats) in stats::compute(games) {
+                let opening = stats.favorite_opening().map(|(tile, _)| notation(tile));
+                writeln!(out, "{}", csv::row(&[
+                    name,
+                    stats.games().to_string(),
+                    stats.wins.to_string(),
+                    stats.losses.to_string(),
+                    stats.ties.to_string(),
+                    stats.best_win_streak.to_string(),
+                    opening.unwrap_or_default(),
+                ]))?;
+            }
+        },
+
+        Table::Ratings => {
+            writeln!(out, "rank,player,rating,games")?;
+            let mut ratings: Vec<_> = rating::compute(games).into_iter().collect();
+            // From the highest rating to the lowest, just like the `ratings` command
+            ratings.sort_by(|(_, a), (_, b)| b.rating.total_cmp(&a.rating));
+            for (i, (name, rated)) in ratings.into_iter().enumerate() {
+                writeln!(out, "{}", csv::row(&[
+                    (i + 1).to_string(),
+                    name,
+                    format!("{:.1}", rated.rating),
+                    rated.games.to_string(),
+                ]))?;
+            }
+        },
+    }
+    Ok(())
+}
+
+// Positions are written in the coordinate style that the player chose, e.g. 2B
+fn notation((row, col): (usize, usize)) -> String {
+    notation::write_move(row, col)
+}
+
+fn piece_name(piece: Piece) -> &'static str {
+    match piece {
+        Piece::X => "x",
+        Piece::O => "o",
+    }
+}
+
+fn result_name(winner: Option<Winner>) -> &'static str {
+    match winner {
+        Some(Winner::X) => "x",
+        Some(Winner::O) => "o",
+        Some(Winner::Tie) => "tie",
+        None => unreachable!("archived games should be finished"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use game::Game;
+    use player::{PlayerProfile, Players};
+
+    #[test]
+    fn games_and_profiles() {
+        let mut game = Game::new();
+        for &(row, col) in &[(1, 1), (0, 0)] {
+            game.make_move(row, col).unwrap();
+        }
+        game.forfeit(Piece::O).unwrap();
+        let ada = PlayerProfile {name: Some("Ada, the first".to_string()), glyph: None};
+        let games = [ArchivedGame {game, players: Players::new(ada, PlayerProfile::default())}];
+
+        let mut table = Vec::new();
+        write_csv(&mut table, Table::Games, &games).unwrap();
+        assert_eq!(String::from_utf8(table).unwrap(), "\
+            game,ruleset,x,o,first,result,forfeit,move_count,moves\n\
+            1,classic,\"Ada, the first\",,x,x,o,2,2B 1A\n");
+
+        let mut table = Vec::new();
+        write_csv(&mut table, Table::Profiles, &games).unwrap();
+        assert_eq!(String::from_utf8(table).unwrap(), "\
+            player,games,wins,losses,ties,best_win_streak,favorite_opening\n\
+            \"Ada, the first\",1,1,0,0,1,2B\n");
+    }
+}
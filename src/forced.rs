@@ -0,0 +1,144 @@
+// Announces when the game has become a forced result: a position where one player wins no matter
+// what the other one does ("x has a forced win in 3 moves"), or where neither player can win
+// anymore as long as both of them play well. The players who want to hear about it choose so with
+// `--announce-forced`, so that it never spoils a game for someone who would rather work it out.
+//
+// The announcements are made by wrapping the renderer (see `ForcedRenderer` in render.rs), just
+// like the coach's comments, and each one is only made when the result changed since the last
+// turn of the player it is for. A draw is what tic-tac-toe is with good play, so it is only
+// announced after a player threw away a win.
+//
+// Finding how many moves a win takes needs a search (see solve.rs), which the lookup table of the
+// perfect player doesn't have. The search keeps its transposition table from one turn to the next,
+// so every position after the first is mostly looked up instead of searched again.
+
+use game::{Game, Piece, Winner};
+use solve::{self, empty_tiles};
+use tt::Table;
+
+// The result that a game is certain to end with if the players play well
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Forced {
+    /// The player wins no matter what the other one does, in this many more moves of their own.
+    /// Some homebrew rules can leave a player with no moves that don't lose right away, in which
+    /// case the other player doesn't need any more moves at all.
+    Win { piece: Piece, moves: usize },
+    /// Neither player can win if the other one plays well
+    Draw,
+}
+
+impl Forced {
+    // Whether this and the other result are the same, without counting the moves that a win takes
+    fn same_as(self, other: Forced) -> bool {
+        match (self, other) {
+            (Forced::Win {piece: a, ..}, Forced::Win {piece: b, ..}) => a == b,
+            (Forced::Draw, Forced::Draw) => true,
+            _ => false,
+        }
+    }
+}
+
+// The table to keep between turns. A whole game fits easily in a megabyte.
+pub fn table() -> Table {
+    Table::with_megabytes(1)
+}
+
+// The result that the game is certain to end with, or None if it is already over
+pub fn forced(game: &Game, table: &mut Table) -> Option<Forced> {
+    if game.is_finished() {
+        return None;
+    }
+    let piece = game.current_piece();
+    if let Some(moves) = solve::moves_to_win(game, table) {
+        return Some(Forced::Win {piece, moves});
+    }
+    if solve::perfect_result(game, table) == Winner::Tie {
+        return Some(Forced::Draw);
+    }
+
+    // Every move loses, and the best of them makes the other player take as long as possible
+    let other = piece.other();
+    let moves = empty_tiles(game).into_iter().filter_map(|(row, col)| {
+        let mut next = game.clone();
+        next.make_move(row, col).expect("empty tiles are always valid moves");
+        match next.winner() {
+            Some(winner) if winner == winner_of(other) => Some(0),
+            Some(_) => None,
+            None => solve::moves_to_win(&next, table),
+        }
+    }).max().expect("a player who can't avoid losing has a move that loses");
+    Some(Forced::Win {piece: other, moves})
+}
+
+// The forced result to announce to the player whose turn it is, if it changed since their last
+// turn (or since the start of the game, on their first turn)
+pub fn news(game: &Game, table: &mut Table) -> Option<Forced> {
+    let now = forced(game, table)?;
+    // Both players have moved since the player's last turn
+    let mut before = game.clone();
+    let earlier = if before.undo().is_some() && before.undo().is_some() {
+        forced(&before, table).unwrap_or(Forced::Draw)
+    }
+    else {
+        Forced::Draw
+    };
+    if now.same_as(earlier) { None } else { Some(now) }
+}
+
+fn winner_of(piece: Piece) -> Winner {
+    match piece {
+        Piece::X => Winner::X,
+        Piece::O => Winner::O,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rules::Rules;
+
+    fn play(game: Game, moves: &[(usize, usize)]) -> Game {
+        moves.iter().fold(game, |mut game, &(row, col)| {
+            game.make_move(row, col).unwrap();
+            game
+        })
+    }
+
+    #[test]
+    fn forced_results() {
+        let mut table = table();
+        assert_eq!(forced(&Game::new(), &mut table), Some(Forced::Draw));
+
+        // o answered the corner with an edge, so x can set up a fork
+        let game = play(Game::new(), &[(0, 0), (0, 1)]);
+        assert_eq!(forced(&game, &mut table), Some(Forced::Win {piece: Piece::X, moves: 3}));
+        // o is to move and can only make x's win take as long as possible
+        let game = play(game, &[(1, 1)]);
+        assert_eq!(forced(&game, &mut table), Some(Forced::Win {piece: Piece::X, moves: 2}));
+        let game = play(game, &[(2, 2), (1, 0)]);
+        assert_eq!(forced(&game, &mut table), Some(Forced::Win {piece: Piece::X, moves: 1}));
+        assert_eq!(forced(&play(game, &[(1, 2), (2, 0)]), &mut table), None);
+
+        // With misère rules, taking the middle is what loses for o. The same positions are worth
+        // something else under other rules, so they get a table of their own.
+        let misere = Game::new().with_rules(Rules::Misere.condition());
+        let game = play(misere, &[(0, 1), (1, 1)]);
+        assert_eq!(forced(&game, &mut super::table()),
+            Some(Forced::Win {piece: Piece::X, moves: 3}));
+    }
+
+    #[test]
+    fn only_changes_are_news() {
+        let mut table = table();
+        assert_eq!(news(&Game::new(), &mut table), None);
+        // o's mistake is news to x right away
+        let game = play(Game::new(), &[(0, 0), (0, 1)]);
+        assert_eq!(news(&game, &mut table), Some(Forced::Win {piece: Piece::X, moves: 3}));
+        // ...and to o on their next turn, but not again after that
+        let game = play(game, &[(1, 1)]);
+        assert_eq!(news(&game, &mut table), Some(Forced::Win {piece: Piece::X, moves: 2}));
+        let game = play(game, &[(2, 2), (1, 0)]);
+        assert_eq!(news(&game, &mut table), None);
+    }
+}
@@ -6,6 +6,14 @@
 // correct value.
 const BOARD_SIZE: usize = 3;
 
+use std::collections::BTreeMap;
+use std::fmt;
+use std::time::Duration;
+
+use thiserror::Error;
+
+use history::History;
+
 // We want to use an enum for piece because we can either have one piece or the other on a tile,
 // but never both at the same time
 // `derive` automatically derives certain useful traits. These make this custom type that we've
@@ -53,6 +61,25 @@ pub type Tile = Option<Piece>;
 // tiles[1][2] accesses the second row and third column of the board.
 pub type Tiles = [[Tile; BOARD_SIZE]; BOARD_SIZE];
 
+// There are only 8 ways to win on a 3x3 board: 3 rows, 3 columns and 2 diagonals. Each line below
+// lists the (row, col) positions of its three tiles.
+const LINES: [[(usize, usize); 3]; 8] = [
+    [(0, 0), (0, 1), (0, 2)],
+    [(1, 0), (1, 1), (1, 2)],
+    [(2, 0), (2, 1), (2, 2)],
+    [(0, 0), (1, 0), (2, 0)],
+    [(0, 1), (1, 1), (2, 1)],
+    [(0, 2), (1, 2), (2, 2)],
+    [(0, 0), (1, 1), (2, 2)],
+    [(0, 2), (1, 1), (2, 0)],
+];
+
+// The FNV-1a hash that the history digest is made of. It is simple enough to write again in any
+// language, which is what lets other programs check the digests too.
+// More info: http://www.isthe.com/chongo/tech/comp/fnv/
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
 // There are three possibilities for the winner at the end of the game. We represent them as an
 // enum because only one of them can ever occur at a given time.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -62,24 +89,127 @@ pub enum Winner {
     Tie,
 }
 
+// Returns the piece that has three in a row on `tiles`, if either of them does
+pub fn three_in_a_row(tiles: &Tiles) -> Option<Piece> {
+    LINES.iter().find_map(|line| {
+        let [a, b, c] = line.map(|(row, col)| tiles[row][col]);
+        if a == b && b == c { a } else { None }
+    })
+}
+
+// Homebrew variants of the game (like the ones in rules.rs) decide for themselves when a game is
+// over by implementing this trait. A game that is given one with `Game::with_rules` asks it after
+// every move instead of looking for three in a row, and everything that looks ahead, like the
+// solver and the perfect computer player, goes by what it says too.
+//
+// The variants are shared by every copy of the game (and by the threads that play games at the
+// same time), which is why they are `&'static` and `Sync`.
+pub trait WinCondition: fmt::Debug + Sync {
+    // The result of the game right after `last` put a piece on `tiles`, or None if the game goes
+    // on. A full board is a tie unless this says otherwise.
+    fn winner(&self, tiles: &Tiles, last: Piece) -> Option<Winner>;
+
+    // The name that the variant is chosen with on the command line, so that the rules of a game
+    // can be written down along with its moves
+    fn name(&self) -> &'static str;
+}
+
 // This type represents the possible errors that can occur when making a move
-#[derive(Debug, Clone)]
+// Deriving `Error` with the thiserror crate writes the `Display` and `std::error::Error` impls
+// for us, using the `#[error(...)]` message of each variant. The messages are in English for logs
+// and other programs; the player sees the translated ones from locale.rs.
+#[derive(Debug, Clone, Error)]
 pub enum MoveError {
     // Putting /// instead of // means that Rust's documentation tool will automatically pickup
     // that comment and use it when generating beautiful documentation for this module.
 
     /// The game was already over when a move was attempted
+    #[error("the game is already over")]
     GameAlreadyOver,
 
     // Fields allow us to provide more information about what happened
 
     /// The position provided was invalid
+    #[error("there is no tile at row {row}, column {col}")]
     InvalidPosition { row: usize, col: usize },
 
-    /// The tile already contained another piece
-    TileNotEmpty { other_piece: Piece, row: usize, col: usize },
+    /// The tile already contained another piece, which was put there by move number `since`
+    /// (counting from 1)
+    #[error("the tile at row {row}, column {col} has had a piece on it since move {since}")]
+    TileNotEmpty { other_piece: Piece, row: usize, col: usize, since: usize },
 }
 
+impl MoveError {
+    // The number that stands for this error in the JSON output and the C library. These numbers
+    // never change once they are given out (see error.rs for the numbers of every error).
+    pub fn code(&self) -> u16 {
+        match *self {
+            MoveError::GameAlreadyOver => 100,
+            MoveError::InvalidPosition {..} => 101,
+            MoveError::TileNotEmpty {..} => 102,
+        }
+    }
+}
+
+// The reasons a position can't be turned into a game with `Game::from_position`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum PositionError {
+    /// The players take turns, so the player who moves next must have as many pieces on the
+    /// board as the other player, or one fewer
+    #[error("{x} x and {o} o can't be on the board with {next:?} to move")]
+    PieceCounts { x: usize, o: usize, next: Piece },
+
+    /// No game can reach the position, e.g. because both players have three in a row, or
+    /// because the game would have ended before the last piece was placed
+    #[error("no game can reach the position")]
+    Unreachable,
+}
+
+impl PositionError {
+    // The number that stands for this error (see `MoveError::code`)
+    pub fn code(&self) -> u16 {
+        match *self {
+            PositionError::PieceCounts {..} => 110,
+            PositionError::Unreachable => 111,
+        }
+    }
+}
+
+// The reasons that a game can be ended before the board decides it, because its result is
+// already certain (see `Game::adjudicate`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Adjudication {
+    /// Every line has both pieces in it, so nobody can get three in a row anymore
+    DeadPosition,
+    /// The solver proved the result, and the player it favours is certain to get it
+    Solved,
+}
+
+// A time that the game was put on hold with the `pause` command. The times are from the wall
+// clock instead of the players' chess clocks, so that they say when the game was interrupted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pause {
+    /// When the pause started, in seconds since 1970-01-01 (UTC)
+    pub started: u64,
+    /// How long the game was paused for, in seconds
+    pub seconds: u64,
+}
+
+// When a move was made and how long the player thought about it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MoveTime {
+    /// When the move was made, in seconds since 1970-01-01 (UTC)
+    pub made: u64,
+    /// How long the player thought about the move, not counting any pauses
+    pub thinking: Duration,
+}
+
+// Extra facts about a game or a move, as `key = value` pairs (like `source = network` or
+// `engine = perfect`). The game doesn't use them for anything. They are only there to be saved
+// along with it, so that whoever looks at the game later knows where it came from. A BTreeMap
+// keeps the keys sorted, so the tags are always saved in the same order.
+pub type Tags = BTreeMap<String, String>;
+
 #[derive(Debug, Clone)]
 pub struct Game {
     tiles: Tiles,
@@ -94,6 +224,31 @@ pub struct Game {
     // other than None once, it will no longer be possible to write a program that violates the
     // invariant stated above.
     winner: Option<Winner>,
+    // The piece that made the first move. This is usually X, but when playing several games in a
+    // row we let the players take turns going first.
+    first_piece: Piece,
+    // Every move that has been made so far, in the order they were played. The moves are packed
+    // into a single number (see history.rs), so a game takes up very little memory.
+    moves: History,
+    // If a player lost without the board deciding the game (e.g. they ran out of time), this is
+    // the piece that player was using
+    forfeited_by: Option<Piece>,
+    // If the game was ended early because its result was already certain, this is why
+    adjudicated: Option<Adjudication>,
+    // Every time the game was paused, in the order they happened. The pauses don't change the
+    // game itself, but they are saved along with it.
+    pauses: Vec<Pause>,
+    // The time of each move, in the same order as the moves. The History only has room for the
+    // positions, so the times are kept here instead. Moves that nobody timed, like the ones read
+    // from a file, have None, and the list ends at the last move that was timed.
+    times: Vec<Option<MoveTime>>,
+    // The tags of the whole game
+    tags: Tags,
+    // The tags of each move, in the same order as the moves. Just like the times, the list ends at
+    // the last move that was tagged.
+    move_tags: Vec<Tags>,
+    // The homebrew rules that decide when the game is over, or None for the normal ones
+    rules: Option<&'static dyn WinCondition>,
 }
 
 impl Game {
@@ -114,7 +269,89 @@ impl Game {
             // Rust will warn us before our program even tries to run if we forget that this value
             // might be None.
             winner: None,
+            first_piece: Piece::X,
+            moves: History::new(),
+            forfeited_by: None,
+            adjudicated: None,
+            pauses: Vec::new(),
+            times: Vec::new(),
+            tags: Tags::new(),
+            move_tags: Vec::new(),
+            rules: None,
+        }
+    }
+
+    // Plays this game by homebrew rules instead of the normal ones (see WinCondition). This is
+    // meant to be used on a new game, since the moves already made aren't checked again.
+    pub fn with_rules(self, rules: Option<&'static dyn WinCondition>) -> Self {
+        Self {rules, ..self}
+    }
+
+    // This creates a new game where the given piece makes the first move instead of X
+    pub fn starting_with(first_piece: Piece) -> Self {
+        // The "struct update syntax" `..Self::new()` fills in every field we didn't list from a
+        // brand new game. That way we only have to write down what is different.
+        Self {
+            current_piece: first_piece,
+            first_piece,
+            ..Self::new()
+        }
+    }
+
+    // Makes a game that has reached the given position with `next` to move. A game remembers
+    // every move it was played with, so this looks for an order of moves that gets there without
+    // the game ending on the way. Any order will do, since the rest of the game only depends on
+    // the tiles and who moves next.
+    pub fn from_position(tiles: &Tiles, next: Piece) -> Result<Self, PositionError> {
+        let count = |piece| tiles.iter().flatten().filter(|&&tile| tile == Some(piece)).count();
+        let (x, o) = (count(Piece::X), count(Piece::O));
+        let (ours, theirs) = if next == Piece::X { (x, o) } else { (o, x) };
+        // If both players have the same number of pieces, the next player also went first.
+        // Otherwise the other player went first and is one piece ahead.
+        let first_piece = if ours == theirs {
+            next
+        }
+        else if ours + 1 == theirs {
+            next.other()
+        }
+        else {
+            return Err(PositionError::PieceCounts {x, o, next});
+        };
+
+        let mut game = Self::starting_with(first_piece);
+        if game.replay_position(tiles, x + o) {
+            Ok(game)
+        }
+        else {
+            Err(PositionError::Unreachable)
+        }
+    }
+
+    // Tries every order of placing the pieces on `tiles` until one of them places all of them.
+    // This is a "backtracking" search: each move that doesn't work out is taken back again with
+    // `undo` before trying the next one. There are at most 5 x pieces and 4 o pieces, so there
+    // are never more than 5! * 4! = 2880 orders to try.
+    fn replay_position(&mut self, tiles: &Tiles, pieces: usize) -> bool {
+        if self.moves.len() == pieces {
+            return true;
+        }
+        // Only the very last piece may finish the game
+        if self.is_finished() {
+            return false;
         }
+        for row in 0..tiles.len() {
+            for col in 0..tiles[0].len() {
+                if tiles[row][col] != Some(self.current_piece) || self.tiles[row][col].is_some() {
+                    continue;
+                }
+                self.make_move(row, col).expect("the tile was checked to be empty");
+                if self.replay_position(tiles, pieces) {
+                    return true;
+                }
+                self.undo();
+            }
+        }
+        false
     }
 
     // `&mut self` reflects that we plan to modify this struct in this method. Rust will ensure
@@ -145,7 +382,11 @@ impl Game {
         else if let Some(other_piece) = self.tiles[row][col] {
             // The pattern match allows us to check if there is a potential value and extract it
             // in one quick sweep. This makes writing the next line very easy!
-            return Err(MoveError::TileNotEmpty {other_piece, row, col});
+            // The history tells us which move put the piece there, so that the player can be told
+            // exactly what happened to the tile.
+            let since = self.moves.iter().position(|tile| tile == (row, col))
+                .expect("every piece on the board was put there by a move") + 1;
+            return Err(MoveError::TileNotEmpty {other_piece, row, col, since});
         }
 
         // Now that we've done all of the error checking, we can proceed with making the move and
@@ -153,14 +394,20 @@ impl Game {
 
         // Here we store the current piece at the correct location in self.tiles
         self.tiles[row][col] = Some(self.current_piece);
+        // We also remember the move so that the game can be saved or reviewed later
+        self.moves.push((row, col));
 
         // Notice that since we don't publically expose a way to set the current piece, we can
         // always be sure that it will be updated correctly and according the rules we expect.
         self.current_piece = self.current_piece.other();
 
         // After making a move, it may be that someone won the game. We'll use another method for
-        // that since this one is getting quite long.
-        self.update_winner(row, col);
+        // that since this one is getting quite long. Homebrew rules decide that for themselves.
+        match self.rules {
+            Some(rules) => self.winner = rules.winner(&self.tiles, self.current_piece.other())
+                .or_else(|| if self.is_full() { Some(Winner::Tie) } else { None }),
+            None => self.update_winner(row, col),
+        }
 
         // Now that everything is complete, we can go ahead and return our "nothing" value `()`
         // called "unit" to indicate that this operation was a success. We construct a Result type
@@ -168,6 +415,54 @@ impl Game {
         Ok(())
     }
 
+    // Ends the game early with the given piece losing. This is used when a player runs out of
+    // time, since the board alone can't tell us who won in that case.
+    pub fn forfeit(&mut self, piece: Piece) -> Result<(), MoveError> {
+        if self.is_finished() {
+            return Err(MoveError::GameAlreadyOver);
+        }
+
+        self.forfeited_by = Some(piece);
+        self.winner = Some(match piece.other() {
+            Piece::X => Winner::X,
+            Piece::O => Winner::O,
+        });
+        Ok(())
+    }
+
+    // Ends the game early with a result that it was certain to reach anyway. Just like a forfeit,
+    // the board alone doesn't say how the game ended, so the reason is kept with it.
+    pub fn adjudicate(&mut self, winner: Winner, reason: Adjudication) -> Result<(), MoveError> {
+        if self.is_finished() {
+            return Err(MoveError::GameAlreadyOver);
+        }
+
+        self.adjudicated = Some(reason);
+        self.winner = Some(winner);
+        Ok(())
+    }
+
+    // Takes back the most recent move and returns where it was played. Returns None if there are
+    // no moves to take back or if the game was forfeited or adjudicated, since neither of those
+    // can be taken back.
+    pub fn undo(&mut self) -> Option<(usize, usize)> {
+        if self.forfeited_by.is_some() || self.adjudicated.is_some() {
+            return None;
+        }
+
+        // `pop` removes the last move, or returns None if there are none. The `?` then returns
+        // None from this function for us.
+        let (row, col) = self.moves.pop()?;
+        self.tiles[row][col] = None;
+        self.times.truncate(self.moves.len());
+        self.move_tags.truncate(self.moves.len());
+        // The player who made the move gets to make it again
+        self.current_piece = self.current_piece.other();
+        // Only the last move can finish a game, so without it there can't be a winner
+        self.winner = None;
+        Some((row, col))
+    }
+
     // We use a private method to separate code that shouldn't be accessed publically
     fn update_winner(&mut self, row: usize, col: usize) {
         // To find a potential winner, we only need to check the row, column and (maybe) diagonal
@@ -292,6 +587,11 @@ impl Game {
         });
     }
 
+    // Returns true if there is a piece on every tile
+    fn is_full(&self) -> bool {
+        self.tiles.iter().flatten().all(|tile| tile.is_some())
+    }
+
     // We can define helpful accessor functions for common questions that will be asked about this
     // type. This makes it so that people using this type won't have to rely on how the type is
     // represented.
@@ -327,6 +627,195 @@ impl Game {
         // field of this struct.
         &self.tiles
     }
+
+    // Returns every empty tile where `piece` would immediately win the game if it were placed
+    // there. This is what players call a "threat". If a player has two threats at once, their
+    // opponent can only block one of them.
+    pub fn winning_moves(&self, piece: Piece) -> Vec<(usize, usize)> {
+        let mut moves = Vec::new();
+        if self.is_finished() {
+            // Nobody can move once the game is over
+            return moves;
+        }
+        // Homebrew rules can't be read line by line, so each empty tile is tried instead
+        if let Some(rules) = self.rules {
+            let win = match piece {
+                Piece::X => Winner::X,
+                Piece::O => Winner::O,
+            };
+            for (row, col) in (0..9).map(|tile| (tile / 3, tile % 3)) {
+                let mut tiles = self.tiles;
+                if tiles[row][col].is_none() {
+                    tiles[row][col] = Some(piece);
+                    if rules.winner(&tiles, piece) == Some(win) {
+                        moves.push((row, col));
+                    }
+                }
+            }
+            return moves;
+        }
+
+        for line in &LINES {
+            // A line is a threat if it contains two of our pieces and one empty tile
+            let ours = line.iter()
+                .filter(|&&(row, col)| self.tiles[row][col] == Some(piece))
+                .count();
+            let empty = line.iter().find(|&&(row, col)| self.tiles[row][col].is_none());
+            if let (2, Some(&pos)) = (ours, empty) {
+                // The same tile can complete more than one line, but we only want to list it once
+                if !moves.contains(&pos) {
+                    moves.push(pos);
+                }
+            }
+        }
+        moves
+    }
+
+    // Returns true if every line has both pieces in it. Nobody can get three in a row anymore, so
+    // however the game is played from here, it can only end in a tie. Under homebrew rules, a
+    // position is never known to be dead, since three in a row might not be what wins.
+    pub fn is_dead(&self) -> bool {
+        if self.rules.is_some() {
+            return false;
+        }
+        LINES.iter().all(|line| {
+            let has = |piece| line.iter().any(|&(row, col)| self.tiles[row][col] == Some(piece));
+            has(Piece::X) && has(Piece::O)
+        })
+    }
+
+    // Returns the number of lines that `piece` could still get three in a row on, because the
+    // other piece isn't in them yet
+    pub fn open_lines(&self, piece: Piece) -> usize {
+        let other = Some(piece.other());
+        LINES.iter().filter(|line| line.iter().all(|&(row, col)| self.tiles[row][col] != other))
+            .count()
+    }
+
+    // Returns the three tiles that won the game, or None if nobody has three in a row (including
+    // when the game was won by a forfeit or is played by homebrew rules)
+    pub fn winning_line(&self) -> Option<[(usize, usize); 3]> {
+        if self.rules.is_some() {
+            return None;
+        }
+        LINES.iter().find(|line| {
+            let [a, b, c] = **line;
+            let tile = |(row, col): (usize, usize)| self.tiles[row][col];
+            tile(a).is_some() && tile(a) == tile(b) && tile(b) == tile(c)
+        }).cloned()
+    }
+
+    // Returns the homebrew rules that the game is played by, or None for the normal ones
+    pub fn rules(&self) -> Option<&'static dyn WinCondition> {
+        self.rules
+    }
+
+    // Returns the piece that made the first move of this game
+    pub fn first_piece(&self) -> Piece {
+        self.first_piece
+    }
+
+    // Returns the piece of the player that forfeited the game, if that is how the game ended
+    pub fn forfeited_by(&self) -> Option<Piece> {
+        self.forfeited_by
+    }
+
+    // Returns why the game was ended early, if that is how the game ended
+    pub fn adjudicated(&self) -> Option<Adjudication> {
+        self.adjudicated
+    }
+
+    // Remembers that the game was paused. Only finished pauses are recorded, since a pause that
+    // is still going doesn't have a length yet.
+    pub fn record_pause(&mut self, pause: Pause) {
+        self.pauses.push(pause);
+    }
+
+    // Returns every time the game was paused, from the first to the last
+    pub fn pauses(&self) -> &[Pause] {
+        &self.pauses
+    }
+
+    // Records when the last move was made and how long its player thought about it
+    pub fn time_last_move(&mut self, time: MoveTime) {
+        let last = self.moves.len().checked_sub(1).expect("a move must be made before it is timed");
+        // Any moves before it that weren't timed are left without a time
+        self.times.resize(last, None);
+        self.times.push(Some(time));
+    }
+
+    // Returns the time of the move with the given index (counting from 0), if it was timed
+    pub fn move_time(&self, index: usize) -> Option<MoveTime> {
+        self.times.get(index).cloned().flatten()
+    }
+
+    // Tags the whole game, replacing the value that the key had before if it already had one
+    pub fn tag(&mut self, key: impl Into<String>, value: impl Into<String>) {
+        self.tags.insert(key.into(), value.into());
+    }
+
+    // Returns the tags of the whole game
+    pub fn tags(&self) -> &Tags {
+        &self.tags
+    }
+
+    // Tags the last move, replacing the value that the key had before if it already had one
+    pub fn tag_last_move(&mut self, key: impl Into<String>, value: impl Into<String>) {
+        let last = self.moves.len().checked_sub(1)
+            .expect("a move must be made before it is tagged");
+        // Any moves before it that weren't tagged are left without tags
+        self.move_tags.resize(last + 1, Tags::new());
+        self.move_tags[last].insert(key.into(), value.into());
+    }
+
+    // Returns the tags of the move with the given index (counting from 0), if it has any
+    pub fn move_tags(&self, index: usize) -> Option<&Tags> {
+        self.move_tags.get(index).filter(|tags| !tags.is_empty())
+    }
+
+    // Returns every move made so far. A History is only 8 bytes and implements Copy, so we can
+    // return it by value. Use `iter()` on it (or a `for` loop) to get the (row, col) pairs.
+    pub fn moves(&self) -> History {
+        self.moves
+    }
+
+    // A fingerprint of the whole history of the game, for checking that two copies of a game are
+    // the same without comparing every move. The digests form a chain: the digest starts out as
+    // the hash of the first piece, and each move replaces it with the hash of the digest before it
+    // together with that move. Changing, adding or removing any move, even the first one, changes
+    // the digest of every move after it, so a game that went out of sync or was tampered with has
+    // a different digest.
+    //
+    // The hash is FNV-1a. The first piece is hashed as one byte (1 for x, 2 for o), and each move
+    // as the 8 bytes of the digest before it (least significant first) followed by one byte for
+    // its tile (row * 3 + col).
+    pub fn history_digest(&self) -> u64 {
+        let first = match self.first_piece {
+            Piece::X => 1,
+            Piece::O => 2,
+        };
+        self.moves.iter().fold(fnv1a(&[first]), |digest, (row, col)| {
+            let mut bytes = [0; 9];
+            bytes[..8].copy_from_slice(&digest.to_le_bytes());
+            bytes[8] = (row * BOARD_SIZE + col) as u8;
+            fnv1a(&bytes)
+        })
+    }
+}
+
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(FNV_OFFSET_BASIS, |hash, &byte| {
+        // `wrapping_mul` lets the multiplication overflow instead of panicking
+        (hash ^ byte as u64).wrapping_mul(FNV_PRIME)
+    })
+}
+
+// Libraries that use Game (like the one in proto/) let other code make a game with
+// `Game::default()` too, which is what generic code that needs "an empty value" uses
+impl Default for Game {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 // These are tests! Rust has testing built-in so you get a streamlined experience that encourages
@@ -416,4 +905,169 @@ mod tests {
         game.make_move(1, 1).unwrap();
         assert_eq!(game.winner().unwrap(), Winner::Tie);
     }
+
+    #[test]
+    fn starting_with_o() {
+        let mut game = Game::starting_with(Piece::O);
+        assert_eq!(game.current_piece(), Piece::O);
+        game.make_move(1, 1).unwrap();
+        assert_eq!(game.tiles()[1][1], Some(Piece::O));
+        assert_eq!(game.current_piece(), Piece::X);
+        assert_eq!(game.first_piece(), Piece::O);
+    }
+
+    #[test]
+    fn winning_moves() {
+        let mut game = Game::new();
+        game.make_move(0, 0).unwrap();
+        game.make_move(1, 0).unwrap();
+        game.make_move(1, 1).unwrap();
+        assert_eq!(game.winning_moves(Piece::X), vec![(2, 2)]);
+        assert!(game.winning_moves(Piece::O).is_empty());
+
+        // After o blocks, x creates a "fork" with two threats at once
+        game.make_move(2, 2).unwrap();
+        game.make_move(0, 2).unwrap();
+        let mut threats = game.winning_moves(Piece::X);
+        threats.sort();
+        assert_eq!(threats, vec![(0, 1), (2, 0)]);
+    }
+
+    #[test]
+    fn forfeit() {
+        let mut game = Game::new();
+        game.make_move(1, 1).unwrap();
+        game.forfeit(Piece::O).unwrap();
+        assert_eq!(game.winner(), Some(Winner::X));
+        assert_eq!(game.forfeited_by(), Some(Piece::O));
+        // The game is over, so no one can move or forfeit anymore
+        assert!(game.make_move(0, 0).is_err());
+        assert!(game.forfeit(Piece::X).is_err());
+    }
+
+    #[test]
+    fn adjudicate() {
+        let mut game = Game::new();
+        for &(row, col) in &[(0, 0), (0, 1), (0, 2), (1, 1), (1, 0), (2, 0), (2, 1)] {
+            game.make_move(row, col).unwrap();
+            assert!(!game.is_dead());
+        }
+        // Every line is blocked now, even though 3C is still empty
+        game.make_move(1, 2).unwrap();
+        assert!(game.is_dead() && !game.is_finished());
+        game.adjudicate(Winner::Tie, Adjudication::DeadPosition).unwrap();
+        assert_eq!(game.winner(), Some(Winner::Tie));
+        assert_eq!(game.adjudicated(), Some(Adjudication::DeadPosition));
+        // Just like a forfeit, an adjudication is final
+        assert_eq!(game.undo(), None);
+        assert!(game.adjudicate(Winner::X, Adjudication::Solved).is_err());
+    }
+
+    #[test]
+    fn moves_are_recorded() {
+        let mut game = Game::new();
+        game.make_move(0, 0).unwrap();
+        game.make_move(2, 1).unwrap();
+        // Invalid moves should not be recorded
+        assert!(game.make_move(0, 0).is_err());
+        assert_eq!(game.moves().to_vec(), [(0, 0), (2, 1)]);
+    }
+
+    #[test]
+    fn history_digest() {
+        let mut game = Game::new();
+        let empty = game.history_digest();
+        assert_ne!(empty, Game::starting_with(Piece::O).history_digest());
+        game.make_move(0, 0).unwrap();
+        game.make_move(2, 1).unwrap();
+        let digest = game.history_digest();
+        // The same moves in another order are a different history
+        let mut swapped = Game::new();
+        swapped.make_move(2, 1).unwrap();
+        swapped.make_move(0, 0).unwrap();
+        assert_ne!(swapped.history_digest(), digest);
+        // Taking a move back goes back to the digest from before it
+        game.undo();
+        game.undo();
+        assert_eq!(game.history_digest(), empty);
+        // The digest never changes, so that saved digests can still be checked
+        assert_eq!(empty, 0xaf63_bc4c_8601_b62c);
+    }
+
+    #[test]
+    fn undo() {
+        let mut game = Game::new();
+        assert_eq!(game.undo(), None);
+        game.make_move(0, 0).unwrap();
+        game.make_move(2, 1).unwrap();
+        assert_eq!(game.undo(), Some((2, 1)));
+        assert_eq!(game.current_piece(), Piece::O);
+        assert_eq!(game.tiles()[2][1], None);
+        assert_eq!(game.moves().to_vec(), [(0, 0)]);
+    }
+
+    #[test]
+    fn move_times() {
+        let time = MoveTime {made: 1792108800, thinking: Duration::from_millis(4200)};
+        let mut game = Game::new();
+        game.make_move(0, 0).unwrap();
+        game.make_move(2, 1).unwrap();
+        game.time_last_move(time);
+        let times: Vec<_> = (0..3).map(|index| game.move_time(index)).collect();
+        assert_eq!(times, [None, Some(time), None]);
+        // Taking back a move takes back its time too
+        game.undo();
+        game.make_move(1, 1).unwrap();
+        assert_eq!(game.move_time(1), None);
+    }
+
+    #[test]
+    fn tags() {
+        let mut game = Game::new();
+        game.tag("event", "club night");
+        game.make_move(0, 0).unwrap();
+        game.make_move(2, 1).unwrap();
+        game.tag_last_move("engine", "perfect");
+        game.tag_last_move("source", "irc");
+        assert_eq!(game.tags().get("event").map(String::as_str), Some("club night"));
+        assert_eq!(game.move_tags(0), None);
+        assert_eq!(game.move_tags(1).unwrap().len(), 2);
+        // Taking back a move takes back its tags too, but not the game's
+        game.undo();
+        game.make_move(1, 1).unwrap();
+        assert_eq!(game.move_tags(1), None);
+        assert_eq!(game.tags().len(), 1);
+    }
+
+    #[test]
+    fn winning_line() {
+        let mut game = Game::new();
+        for &(row, col) in &[(0, 2), (0, 0), (1, 1), (0, 1), (2, 0)] {
+            assert_eq!(game.winning_line(), None);
+            game.make_move(row, col).unwrap();
+        }
+        assert_eq!(game.winning_line(), Some([(0, 2), (1, 1), (2, 0)]));
+    }
+
+    #[test]
+    fn from_position() {
+        let x = Some(Piece::X);
+        let o = Some(Piece::O);
+        let tiles = [[x, o, None], [None, x, None], [None, None, None]];
+        let game = Game::from_position(&tiles, Piece::O).unwrap();
+        assert_eq!(game.tiles(), &tiles);
+        assert_eq!((game.first_piece(), game.current_piece()), (Piece::X, Piece::O));
+        // x has a piece more than o, so it can't be x's turn
+        let game = Game::from_position(&tiles, Piece::X);
+        assert_eq!(game.unwrap_err(), PositionError::PieceCounts {x: 2, o: 1, next: Piece::X});
+
+        // x's last move has to be the one that won
+        let tiles = [[x, x, x], [o, o, None], [None, None, None]];
+        let game = Game::from_position(&tiles, Piece::O).unwrap();
+        assert_eq!(game.winner(), Some(Winner::X));
+        // Both players can't have won
+        let tiles = [[x, x, x], [o, o, o], [None, None, None]];
+        let game = Game::from_position(&tiles, Piece::X);
+        assert_eq!(game.unwrap_err(), PositionError::Unreachable);
+    }
 }
@@ -0,0 +1,264 @@
+// The connection to Discord for the `discord` command, which is only built with the `discord`
+// feature. Discord sends the bot events like new messages through a WebSocket (the "gateway"),
+// and the bot answers through Discord's HTTP API.
+// More info: https://discord.com/developers/docs/events/gateway
+//
+// Crates like `serenity` handle every kind of event that Discord has, but they are built on async
+// Rust, which needs a newer edition of Rust than the one this crate uses. The bot only needs a
+// couple of events, so a WebSocket, an HTTP client and a JSON parser are plenty. Waiting for an
+// event times out every so often, which gives the bot a chance to send the heartbeats that
+// Discord expects and to notice when Ctrl-C is pressed.
+//
+// Unlike the IRC bot's connection (see net/src/lib.rs), this one doesn't run on tokio. A WebSocket
+// over TLS on tokio needs async versions of both (like tokio-tungstenite and tokio-rustls), and
+// until the bot has those, the gateway stays a blocking connection that is only read while the
+// bot isn't busy with a move. Discord allows plenty of time for the heartbeats, so a slow move only
+// delays the answers to other messages.
+
+use std::env;
+use std::io;
+use std::time::{Duration, Instant};
+
+use serde_json::{self, Value};
+use thiserror::Error;
+use tungstenite::{self, Message as WsMessage};
+use tungstenite::stream::MaybeTlsStream;
+use ureq;
+
+use bots::User;
+use interrupt;
+use json;
+
+const GATEWAY_URL: &str = "wss://gateway.discord.gg/?v=10&encoding=json";
+const API_URL: &str = "https://discord.com/api/v10";
+// The events the bot wants: messages in servers (1 << 9) and what they say (1 << 15). Bots have to
+// be allowed to read what messages say in the Discord developer portal.
+const INTENTS: u32 = 1 << 9 | 1 << 15;
+// How long we wait for an event before checking whether a heartbeat is due or Ctrl-C was pressed
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+// What Discord tells the bot about
+#[derive(Debug, Clone)]
+pub enum Event {
+    /// The bot is connected and logged in as the given user
+    Ready(User),
+    /// Someone other than a bot sent a message
+    Message { channel: String, author: User, content: String, mentions: Vec<User> },
+}
+
+// The reasons that `Client::listen` stops
+#[derive(Debug, Clone, Error)]
+pub enum GatewayError {
+    /// The connection failed or was lost, or Discord asked the bot to connect again
+    #[error("disconnected from Discord: {0}")]
+    Disconnected(String),
+    /// Discord closed the connection for a reason that connecting again won't fix, like a token
+    /// that isn't valid
+    #[error("Discord closed the connection: {0}")]
+    Rejected(String),
+    /// Ctrl-C was pressed
+    #[error("interrupted")]
+    Interrupted,
+}
+
+impl GatewayError {
+    // The number that stands for this error (see error.rs)
+    pub fn code(&self) -> u16 {
+        match *self {
+            GatewayError::Disconnected(_) => 500,
+            GatewayError::Rejected(_) => 501,
+            GatewayError::Interrupted => 302,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Client {
+    agent: ureq::Agent,
+    /// The token of the bot, from the Discord developer portal
+    token: String,
+}
+
+impl Client {
+    pub fn new(token: String) -> Self {
+        Client {agent: ureq::Agent::new_with_defaults(), token}
+    }
+
+    // Sends a message to a channel and returns the ID of the new message. Mentions in the text
+    // are shown as names without notifying anyone.
+    pub fn send_message(&self, channel: &str, text: &str) -> Result<String, ureq::Error> {
+        let body = format!("{{\"content\":{},\"allowed_mentions\":{{\"parse\":[]}}}}",
+            json::string(text));
+        let url = format!("{}/channels/{}/messages", API_URL, channel);
+        let response = self.agent.post(&url)
+            .header("Authorization", &format!("Bot {}", self.token))
+            .header("User-Agent", user_agent())
+            .content_type("application/json")
+            .send(&body)?
+            .into_body()
+            .read_to_string()?;
+        let message: Value = serde_json::from_str(&response).unwrap_or_default();
+        Ok(message["id"].as_str().unwrap_or_default().to_string())
+    }
+
+    // Changes the text of a message that the bot sent earlier
+    pub fn edit_message(&self, channel: &str, message: &str,
+        text: &str) -> Result<(), ureq::Error> {
+
+        let body = format!("{{\"content\":{}}}", json::string(text));
+        let url = format!("{}/channels/{}/messages/{}", API_URL, channel, message);
+        self.agent.patch(&url)
+            .header("Authorization", &format!("Bot {}", self.token))
+            .header("User-Agent", user_agent())
+            .content_type("application/json")
+            .send(&body)?;
+        Ok(())
+    }
+
+    // Connects to the gateway and calls `on_event` for every event until the connection ends.
+    // Connections always end eventually, so this only ever returns the reason why.
+    pub fn listen(&self, mut on_event: impl FnMut(Event)) -> GatewayError {
+        let (mut socket, _) = match tungstenite::connect(GATEWAY_URL) {
+            Ok(connection) => connection,
+            Err(error) => return GatewayError::Disconnected(error.to_string()),
+        };
+        let timeout = match *socket.get_ref() {
+            MaybeTlsStream::Plain(ref stream) => stream.set_read_timeout(Some(POLL_INTERVAL)),
+            MaybeTlsStream::Rustls(ref stream) => {
+                stream.get_ref().set_read_timeout(Some(POLL_INTERVAL))
+            },
+            _ => Ok(()),
+        };
+        if let Err(error) = timeout {
+            return GatewayError::Disconnected(error.to_string());
+        }
+
+        // Discord says how often it wants a heartbeat once we are connected. Each heartbeat
+        // includes the number of the last event we got, so that Discord knows what we missed.
+        let mut heartbeat: Option<(Duration, Instant)> = None;
+        let mut sequence = Value::Null;
+        loop {
+            if interrupt::take() {
+                let _ = socket.close(None);
+                return GatewayError::Interrupted;
+            }
+            if let Some((interval, due)) = heartbeat {
+                if Instant::now() >= due {
+                    let payload = format!("{{\"op\":1,\"d\":{}}}", sequence);
+                    if let Err(error) = socket.send(WsMessage::text(payload)) {
+                        return GatewayError::Disconnected(error.to_string());
+                    }
+                    heartbeat = Some((interval, due + interval));
+                }
+            }
+
+            let text = match socket.read() {
+                Ok(WsMessage::Text(text)) => text,
+                Ok(WsMessage::Close(frame)) => {
+                    let (code, reason) = frame.map(|frame| {
+                        (u16::from(frame.code), frame.reason.to_string())
+                    }).unwrap_or_default();
+                    return closed(code, reason);
+                },
+                Ok(_) => continue,
+                Err(tungstenite::Error::Io(ref error)) if timed_out(error) => continue,
+                Err(error) => return GatewayError::Disconnected(error.to_string()),
+            };
+            let payload: Value = match serde_json::from_str(text.as_str()) {
+                Ok(payload) => payload,
+                Err(error) => return GatewayError::Disconnected(error.to_string()),
+            };
+            if payload["s"].is_u64() {
+                sequence = payload["s"].clone();
+            }
+
+            match payload["op"].as_u64() {
+                // Hello: the first thing Discord sends, after which we log in
+                Some(10) => {
+                    let interval = payload["d"]["heartbeat_interval"].as_u64().unwrap_or(41250);
+                    let interval = Duration::from_millis(interval);
+                    heartbeat = Some((interval, Instant::now() + interval));
+                    let identify = format!("{{\"op\":2,\"d\":{{\"token\":{},\"intents\":{},\
+                        \"properties\":{{\"os\":{},\"browser\":{},\"device\":{}}}}}}}",
+                        json::string(&self.token), INTENTS, json::string(env::consts::OS),
+                        json::string(env!("CARGO_PKG_NAME")), json::string(env!("CARGO_PKG_NAME")));
+                    if let Err(error) = socket.send(WsMessage::text(identify)) {
+                        return GatewayError::Disconnected(error.to_string());
+                    }
+                },
+                // Discord wants a heartbeat right away
+                Some(1) => if let Some((interval, _)) = heartbeat {
+                    heartbeat = Some((interval, Instant::now()));
+                },
+                // Reconnect and Invalid Session: Discord wants us to start over
+                Some(7) | Some(9) => {
+                    let _ = socket.close(None);
+                    return GatewayError::Disconnected("asked to reconnect".to_string());
+                },
+                // Dispatch: an event happened
+                Some(0) => {
+                    let data = &payload["d"];
+                    match payload["t"].as_str() {
+                        Some("READY") => if let Some(user) = user(&data["user"]) {
+                            on_event(Event::Ready(user));
+                        },
+                        Some("MESSAGE_CREATE") => if let Some(event) = message(data) {
+                            on_event(event);
+                        },
+                        _ => {},
+                    }
+                },
+                _ => {},
+            }
+        }
+    }
+}
+
+// The close codes that mean the bot can't connect until something is changed, like the token or
+// the intents allowed in the developer portal
+fn closed(code: u16, reason: String) -> GatewayError {
+    let reason = format!("{} {}", code, reason);
+    match code {
+        4004 | 4010..=4014 => GatewayError::Rejected(reason),
+        _ => GatewayError::Disconnected(reason),
+    }
+}
+
+// Reading gives up with one of these errors when nothing arrives within POLL_INTERVAL, depending
+// on the platform
+fn timed_out(error: &io::Error) -> bool {
+    error.kind() == io::ErrorKind::WouldBlock || error.kind() == io::ErrorKind::TimedOut
+}
+
+// Discord asks every bot to say what it is and where to find out more about it
+fn user_agent() -> String {
+    format!("DiscordBot (https://github.com/sunjay/tic-tac-toe, {})", env!("CARGO_PKG_VERSION"))
+}
+
+fn user(value: &Value) -> Option<User> {
+    Some(User {
+        id: value["id"].as_str()?.to_string(),
+        name: value["username"].as_str()?.to_string(),
+    })
+}
+
+// A user who isn't a bot
+fn person(value: &Value) -> Option<User> {
+    if value["bot"].as_bool() == Some(true) {
+        return None;
+    }
+    user(value)
+}
+
+// A message sent by someone other than a bot, including the bot itself
+fn message(data: &Value) -> Option<Event> {
+    let mentions = data["mentions"].as_array()
+        .map(|mentions| mentions.iter().filter_map(person).collect())
+        .unwrap_or_default();
+    Some(Event::Message {
+        channel: data["channel_id"].as_str()?.to_string(),
+        author: person(&data["author"])?,
+        content: data["content"].as_str()?.to_string(),
+        mentions,
+    })
+}
@@ -0,0 +1,155 @@
+// Finds puzzles for the `generate-puzzles` command. A position makes a good puzzle when there is
+// only one move that wins in time (which is how forks and win-in-2 puzzles come about), or when
+// most of the moves lose, like when there is a threat that has to be blocked. The solver decides
+// both, so every puzzle that comes out of here can be solved.
+//
+// The positions come from every position that can be reached from the empty board, or from the
+// games in an archive, which finds the kinds of positions that the players there actually get
+// into. Positions that only differ by turning or flipping the board would be the same puzzle, so
+// only the first one of them is kept (see symmetry.rs).
+
+use std::collections::HashSet;
+
+use game::{Game, Piece, Tiles, Winner};
+use puzzle::{self, Attempt, Goal, Idea, Level, Puzzle};
+use solve;
+use symmetry;
+use tt::Table;
+
+// The most moves a win puzzle asks for. Longer wins mostly start from nearly empty boards, where
+// the first move is hard to explain.
+const MAX_WIN_IN: usize = 3;
+
+// Looks for puzzles in the positions it is given, skipping any it has already seen
+struct Generator {
+    seen: HashSet<(Tiles, Piece)>,
+    table: Table,
+    puzzles: Vec<Puzzle>,
+}
+
+// The puzzles in every position that can be reached from the empty board, from the easiest to the
+// hardest
+pub fn every_position() -> Vec<Puzzle> {
+    let mut generator = Generator::new();
+    let mut seen = HashSet::new();
+    visit(&Game::new(), &mut seen, &mut generator);
+    generator.finish()
+}
+
+// The puzzles in the positions that the games went through, from the easiest to the hardest
+pub fn from_games<'a>(games: impl IntoIterator<Item=&'a Game>) -> Vec<Puzzle> {
+    let mut generator = Generator::new();
+    for game in games {
+        let mut position = Game::starting_with(game.first_piece());
+        for (row, col) in game.moves() {
+            generator.consider(&position);
+            position.make_move(row, col).expect("the game was played with these moves");
+        }
+    }
+    generator.finish()
+}
+
+// Visits a position and everything after it, in the order of the tiles. A position that can be
+// reached in more than one way is only visited the first time.
+fn visit(game: &Game, seen: &mut HashSet<Tiles>, generator: &mut Generator) {
+    if game.is_finished() || !seen.insert(*game.tiles()) {
+        return;
+    }
+    generator.consider(game);
+    for (row, col) in solve::empty_tiles(game) {
+        let mut next = game.clone();
+        next.make_move(row, col).expect("empty tiles are always valid moves");
+        visit(&next, seen, generator);
+    }
+}
+
+impl Generator {
+    fn new() -> Self {
+        Self {seen: HashSet::new(), table: Table::with_megabytes(1), puzzles: Vec::new()}
+    }
+
+    fn consider(&mut self, game: &Game) {
+        let key = (symmetry::canonical_tiles(game.tiles()), game.current_piece());
+        if game.is_finished() || !self.seen.insert(key) {
+            return;
+        }
+
+        let piece = game.current_piece();
+        let (goal, moves) = match solve::moves_to_win(game, &mut self.table) {
+            // Winning right away is only a puzzle when the other player is threatening to win
+            // too, so that blocking them is the mistake to avoid
+            Some(1) if game.winning_moves(piece.other()).is_empty() => return,
+            Some(moves) if moves <= MAX_WIN_IN => (Goal::Win, moves),
+            Some(_) => return,
+            None if solve::perfect_result(game, &mut self.table) == Winner::Tie => (Goal::Draw, 1),
+            // There is nothing left to find for a player who has already lost
+            None => return,
+        };
+        let mut puzzle = Puzzle {game: game.clone(), goal, moves, level: None, theme: None};
+
+        let solutions = Attempt::new(&puzzle).solutions();
+        let good = match puzzle.goal {
+            Goal::Win => solutions.len() == 1,
+            // At most half of the moves may hold the draw
+            Goal::Draw => solutions.len() * 2 <= solve::empty_tiles(game).len(),
+        };
+        if !good {
+            return;
+        }
+        let (row, col) = solutions[0];
+        let theme = puzzle::idea(game, row, col);
+        puzzle.level = Some(level(puzzle.goal, puzzle.moves, theme));
+        puzzle.theme = Some(theme);
+        self.puzzles.push(puzzle);
+    }
+
+    fn finish(mut self) -> Vec<Puzzle> {
+        // Sorting is stable, so puzzles at the same level stay in the order they were found in
+        self.puzzles.sort_by_key(|puzzle| puzzle.level);
+        self.puzzles
+    }
+}
+
+// How hard a puzzle is. Winning right away and blocking are easy to see, while forks and threats
+// take looking a move ahead, and longer wins and quiet moves take looking further than that.
+fn level(goal: Goal, moves: usize, theme: Idea) -> Level {
+    match (goal, moves, theme) {
+        (Goal::Win, 1, _) | (Goal::Draw, _, Idea::Block) => Level::Easy,
+        (Goal::Win, 2, _) | (Goal::Draw, _, Idea::Fork) | (Goal::Draw, _, Idea::Threat) => {
+            Level::Medium
+        },
+        _ => Level::Hard,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_puzzle_can_be_solved() {
+        let puzzles = every_position();
+        assert!(puzzles.iter().any(|puzzle| puzzle.theme == Some(Idea::Fork)));
+        assert!(puzzles.iter().any(|puzzle| puzzle.theme == Some(Idea::Block)));
+        assert!(puzzles.iter().any(|puzzle| puzzle.goal == Goal::Win && puzzle.moves == 2));
+        assert!(puzzles.windows(2).all(|pair| pair[0].level <= pair[1].level));
+
+        // Writing the puzzles and reading them again checks every one of them with the solver
+        let text: Vec<_> = puzzles.iter().map(puzzle::write).collect();
+        assert_eq!(puzzle::parse(&text.join("\n")).unwrap().len(), puzzles.len());
+    }
+
+    #[test]
+    fn puzzles_from_games() {
+        // x forked with 3A and won at 2A. Before 3A, x had to block at 3A to win in 2.
+        let mut game = Game::new();
+        for &(row, col) in &[(0, 0), (1, 1), (2, 2), (0, 2), (2, 0), (2, 1), (1, 0)] {
+            game.make_move(row, col).unwrap();
+        }
+        let puzzles = from_games(vec![&game, &game]);
+        let fork = puzzles.iter().find(|puzzle| puzzle.game.moves().len() == 4).unwrap();
+        assert_eq!((fork.goal, fork.moves, fork.theme), (Goal::Win, 2, Some(Idea::Block)));
+        // The same game twice gives the same puzzles once
+        assert_eq!(puzzles.len(), from_games(vec![&game]).len());
+    }
+}
@@ -0,0 +1,320 @@
+// Speaks a dialect of the Go Text Protocol (GTP), for the `gtp` command. GTP was made so that Go
+// programs could be driven by graphical boards, test harnesses and tournament managers, and many
+// of those work with other board games too. A controller sends one command per line, and the
+// engine answers each one with `=` followed by the result, or `?` followed by an error, and then
+// an empty line:
+//
+//     play black B2
+//     =
+//
+//     genmove white
+//     = A3
+//
+// Black is x and white is o, since black moves first in Go. Tiles are written the way GTP writes
+// them, with the column letter first and the rows counted from the bottom, so A1 is the bottom
+// left corner. This isn't the same as the notation everywhere else in the program, where 1A is
+// the top left corner.
+// More info: https://www.lysator.liu.se/~gunnar/gtp/gtp2-spec-draft2/gtp2-spec.html
+//
+// The answers are for other programs, so they are never translated.
+
+use engine::{self, Difficulty};
+use game::{Game, MoveError, Piece, Winner};
+use random::Rng;
+
+// The only board size this engine can play on
+const BOARD_SIZE: usize = 3;
+// Every command that `respond` knows, in the order `list_commands` lists them
+const COMMANDS: [&str; 14] = [
+    "protocol_version", "name", "version", "known_command", "list_commands", "quit", "boardsize",
+    "clear_board", "komi", "play", "genmove", "undo", "showboard", "final_score",
+];
+
+#[derive(Debug, Clone)]
+pub struct Engine {
+    game: Game,
+    /// How well `genmove` plays
+    difficulty: Difficulty,
+}
+
+// The answer to one command
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Response {
+    /// The text to send back, ending with the empty line that finishes every answer
+    pub text: String,
+    /// Whether the controller asked the engine to stop (`quit`)
+    pub quit: bool,
+}
+
+impl Engine {
+    pub fn new(difficulty: Difficulty) -> Self {
+        Engine {game: Game::new(), difficulty}
+    }
+
+    // Answers a line sent by the controller. Empty lines and comments (which start with #) get no
+    // answer at all.
+    pub fn respond(&mut self, line: &str, rng: &mut Rng) -> Option<Response> {
+        // Anything after a # is a comment, and control characters other than tabs are ignored
+        let line = line.split('#').next().unwrap_or_default();
+        let line: String = line.chars()
+            .filter(|&c| !c.is_control() || c == '\t')
+            .map(|c| if c == '\t' { ' ' } else { c })
+            .collect();
+        let mut words = line.split_whitespace();
+        let mut first = words.next()?;
+
+        // Commands can start with a number, which the answer repeats so that the controller knows
+        // which command it belongs to
+        let mut id = None;
+        if first.bytes().all(|byte| byte.is_ascii_digit()) {
+            id = Some(first);
+            first = match words.next() {
+                Some(command) => command,
+                None => {
+                    let text = failure(id, "missing command".to_string());
+                    return Some(Response {text, quit: false});
+                },
+            };
+        }
+        let args: Vec<_> = words.collect();
+
+        let result = self.run(first, &args, rng);
+        let quit = first == "quit" && result.is_ok();
+        Some(Response {
+            text: match result {
+                Ok(text) => success(id, text),
+                Err(error) => failure(id, error),
+            },
+            quit,
+        })
+    }
+
+    fn run(&mut self, command: &str, args: &[&str], rng: &mut Rng) -> Result<String, String> {
+        match command {
+            "protocol_version" => Ok("2".to_string()),
+            "name" => Ok(env!("CARGO_PKG_NAME").to_string()),
+            "version" => Ok(env!("CARGO_PKG_VERSION").to_string()),
+            "known_command" => {
+                let name = one_arg(args)?;
+                Ok(COMMANDS.contains(&name).to_string())
+            },
+            "list_commands" => Ok(COMMANDS.join("\n")),
+            "quit" => Ok(String::new()),
+            "boardsize" => {
+                let size = one_arg(args)?;
+                match size.parse::<usize>() {
+                    Ok(BOARD_SIZE) => {
+                        self.game = Game::new();
+                        Ok(String::new())
+                    },
+                    Ok(_) => Err("unacceptable size".to_string()),
+                    Err(_) => Err("boardsize not an integer".to_string()),
+                }
+            },
+            "clear_board" => {
+                self.game = Game::new();
+                Ok(String::new())
+            },
+            // There is no komi in tic-tac-toe, but controllers send it anyway, so it is accepted
+            // and ignored
+            "komi" => {
+                let komi = one_arg(args)?;
+                match komi.parse::<f64>() {
+                    Ok(_) => Ok(String::new()),
+                    Err(_) => Err("komi not a float".to_string()),
+                }
+            },
+            "play" => {
+                let (color, vertex) = match *args {
+                    [color, vertex] => (color, vertex),
+                    _ => return Err("invalid color or coordinate".to_string()),
+                };
+                // Once the game is over, it is nobody's turn
+                if !self.game.is_finished() {
+                    self.color_to_move(color)?;
+                }
+                let (row, col) = parse_vertex(vertex)
+                    .ok_or_else(|| "invalid color or coordinate".to_string())?;
+                self.game.make_move(row, col).map(|()| String::new()).map_err(|error| {
+                    match error {
+                        MoveError::GameAlreadyOver => "illegal move: the game is over",
+                        MoveError::InvalidPosition {..} => "illegal move: not on the board",
+                        MoveError::TileNotEmpty {..} => "illegal move: the tile is taken",
+                    }.to_string()
+                })
+            },
+            "genmove" => {
+                let color = one_arg(args)?;
+                if self.game.is_finished() {
+                    return Err("the game is over".to_string());
+                }
+                self.color_to_move(color)?;
+                let (row, col) = engine::choose_move(&self.game, self.difficulty, rng);
+                self.game.make_move(row, col).expect("the engine should only choose empty tiles");
+                Ok(vertex((row, col)))
+            },
+            "undo" => match self.game.undo() {
+                Some(_) => Ok(String::new()),
+                None => Err("cannot undo".to_string()),
+            },
+            "showboard" => Ok(self.board()),
+            // The winner gets a score of 1, since there is no way to win by more in tic-tac-toe
+            "final_score" => match self.game.winner() {
+                Some(Winner::X) => Ok("B+1".to_string()),
+                Some(Winner::O) => Ok("W+1".to_string()),
+                Some(Winner::Tie) => Ok("0".to_string()),
+                None => Err("cannot score".to_string()),
+            },
+            _ => Err("unknown command".to_string()),
+        }
+    }
+
+    // Checks that it is the turn of the given color. In Go either color can play at any time, but
+    // tic-tac-toe is only ever played in turns.
+    fn color_to_move(&self, color: &str) -> Result<(), String> {
+        let piece = parse_color(color).ok_or_else(|| "invalid color".to_string())?;
+        if piece != self.game.current_piece() {
+            return Err(format!("illegal move: it is {}'s turn",
+                color_name(self.game.current_piece())));
+        }
+        Ok(())
+    }
+
+    // The board drawn with the GTP coordinates around it, starting on a new line
+    fn board(&self) -> String {
+        let letters = "   A B C";
+        let mut board = format!("\n{}\n", letters);
+        for (row, tiles) in self.game.tiles().iter().enumerate() {
+            let number = BOARD_SIZE - row;
+            let tiles: Vec<_> = tiles.iter().map(|tile| match *tile {
+                Some(Piece::X) => "X",
+                Some(Piece::O) => "O",
+                None => ".",
+            }).collect();
+            board.push_str(&format!(" {} {} {}\n", number, tiles.join(" "), number));
+        }
+        board.push_str(letters);
+        board
+    }
+}
+
+fn success(id: Option<&str>, text: String) -> String {
+    answer('=', id, text)
+}
+
+fn failure(id: Option<&str>, error: String) -> String {
+    answer('?', id, error)
+}
+
+fn answer(status: char, id: Option<&str>, text: String) -> String {
+    let separator = if text.is_empty() || text.starts_with('\n') { "" } else { " " };
+    format!("{}{}{}{}\n\n", status, id.unwrap_or_default(), separator, text)
+}
+
+fn one_arg<'a>(args: &[&'a str]) -> Result<&'a str, String> {
+    match *args {
+        [arg] => Ok(arg),
+        _ => Err("syntax error".to_string()),
+    }
+}
+
+fn parse_color(color: &str) -> Option<Piece> {
+    match &color.to_lowercase()[..] {
+        "b" | "black" => Some(Piece::X),
+        "w" | "white" => Some(Piece::O),
+        _ => None,
+    }
+}
+
+fn color_name(piece: Piece) -> &'static str {
+    match piece {
+        Piece::X => "black",
+        Piece::O => "white",
+    }
+}
+
+// Reads a tile like B2, with the column letter first and the rows counted from the bottom
+fn parse_vertex(vertex: &str) -> Option<(usize, usize)> {
+    let bytes = vertex.as_bytes();
+    if bytes.len() != 2 {
+        return None;
+    }
+    let col = match bytes[0].to_ascii_uppercase() {
+        letter @ b'A'..=b'C' => (letter - b'A') as usize,
+        _ => return None,
+    };
+    let row = match bytes[1] {
+        digit @ b'1'..=b'3' => BOARD_SIZE - (digit - b'0') as usize,
+        _ => return None,
+    };
+    Some((row, col))
+}
+
+fn vertex((row, col): (usize, usize)) -> String {
+    format!("{}{}", (b'A' + col as u8) as char, BOARD_SIZE - row)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Sends each line to a new engine and puts the answers together
+    fn session(lines: &[&str]) -> String {
+        let mut engine = Engine::new(Difficulty::Perfect);
+        let mut rng = Rng::new(1);
+        lines.iter().filter_map(|line| engine.respond(line, &mut rng)).map(|response| {
+            response.text
+        }).collect()
+    }
+
+    #[test]
+    fn play_and_show_the_board() {
+        let answers = session(&["1 boardsize 3", "# a comment", "", "play black A1", "play b c3",
+            "2 play white A1", "play w B2", "showboard"]);
+        assert_eq!(answers, "=1\n\n=\n\n\
+            ? illegal move: it is white's turn\n\n\
+            ?2 illegal move: the tile is taken\n\n\
+            =\n\n\
+            =\n   A B C\n 3 . . . 3\n 2 . O . 2\n 1 X . . 1\n   A B C\n\n");
+    }
+
+    #[test]
+    fn genmove_and_scoring() {
+        let mut engine = Engine::new(Difficulty::Perfect);
+        let mut rng = Rng::new(1);
+        let mut respond = |line: &str| engine.respond(line, &mut rng).unwrap();
+        assert_eq!(respond("final_score").text, "? cannot score\n\n");
+        // Two perfect players always tie
+        for turn in 0..9 {
+            let color = if turn % 2 == 0 { "black" } else { "white" };
+            let answer = respond(&format!("genmove {}", color)).text;
+            assert!(parse_vertex(answer.trim_start_matches("= ").trim()).is_some(), "{}", answer);
+        }
+        assert_eq!(respond("genmove black").text, "? the game is over\n\n");
+        assert_eq!(respond("final_score").text, "= 0\n\n");
+        assert_eq!(respond("undo").text, "=\n\n");
+        assert_eq!(respond("final_score").text, "? cannot score\n\n");
+    }
+
+    #[test]
+    fn administrative_commands() {
+        let answers = session(&["protocol_version", "known_command genmove", "known_command move",
+            "boardsize 19", "komi 6.5", "fly", "5"]);
+        assert_eq!(answers, "= 2\n\n= true\n\n= false\n\n? unacceptable size\n\n=\n\n\
+            ? unknown command\n\n?5 missing command\n\n");
+
+        let mut engine = Engine::new(Difficulty::Easy);
+        let quit = engine.respond("3 quit", &mut Rng::new(1)).unwrap();
+        assert_eq!(quit, Response {text: "=3\n\n".to_string(), quit: true});
+    }
+
+    #[test]
+    fn vertices() {
+        assert_eq!(parse_vertex("A1"), Some((2, 0)));
+        assert_eq!(parse_vertex("c3"), Some((0, 2)));
+        assert_eq!(parse_vertex("D1"), None);
+        assert_eq!(parse_vertex("A4"), None);
+        assert_eq!(parse_vertex("pass"), None);
+        assert_eq!(vertex((0, 1)), "B3");
+    }
+}
@@ -0,0 +1,200 @@
+// The moves of a game, packed into a single number. A 3x3 board only has 9 tiles, so the tile of
+// each move fits in 4 bits, and even a whole game only needs 36 of the 64 bits in a u64. Compared
+// to a Vec of (row, col) pairs, which takes 16 bytes for every move plus the Vec itself, this keeps
+// millions of simulated or archived games cheap to hold in memory. It also means that a history
+// is `Copy`, just like the tiles of the board.
+//
+// Each move is stored as the index of its tile plus one (row * 3 + col + 1), starting from the
+// lowest bits. That leaves 0 to mean "no move", so the number of moves can be worked out from the
+// bits alone and doesn't have to be stored separately.
+
+// The number of bits used for each move
+const MOVE_BITS: u32 = 4;
+// The most moves that fit in a u64
+const MAX_MOVES: usize = 64 / MOVE_BITS as usize;
+// The board is always 3x3 (see BOARD_SIZE in game.rs)
+const BOARD_SIZE: usize = 3;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct History {
+    bits: u64,
+}
+
+impl History {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // The number of moves. Every move is stored as a number other than 0, so the moves end where
+    // the last bit that is set does.
+    pub fn len(self) -> usize {
+        let used_bits = 64 - self.bits.leading_zeros();
+        used_bits.div_ceil(MOVE_BITS) as usize
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.bits == 0
+    }
+
+    // Adds a move to the end of the history. Panics if the position isn't on the board, or if
+    // the history is already full, neither of which can happen in a game of tic-tac-toe.
+    pub fn push(&mut self, (row, col): (usize, usize)) {
+        assert!(row < BOARD_SIZE && col < BOARD_SIZE, "moves should be on the board");
+        let length = self.len();
+        assert!(length < MAX_MOVES, "the history should have room for another move");
+        let code = (row * BOARD_SIZE + col + 1) as u64;
+        self.bits |= code << (length as u32 * MOVE_BITS);
+    }
+
+    // Removes the last move and returns it, or returns None if there are no moves
+    pub fn pop(&mut self) -> Option<(usize, usize)> {
+        let length = self.len();
+        if length == 0 {
+            return None;
+        }
+        let shift = (length as u32 - 1) * MOVE_BITS;
+        let code = self.bits >> shift;
+        self.bits &= !(code << shift);
+        Some(decode(code))
+    }
+
+    // The first move, if there is one
+    pub fn first(self) -> Option<(usize, usize)> {
+        self.iter().next()
+    }
+
+    // Goes through the moves in the order they were played, as (row, col) pairs
+    pub fn iter(self) -> Moves {
+        Moves {bits: self.bits}
+    }
+
+    // Unpacks the moves into a Vec, for code that needs to slice or change them
+    pub fn to_vec(self) -> Vec<(usize, usize)> {
+        self.iter().collect()
+    }
+
+    // The packed moves, for code that stores them somewhere else (like the game codes in
+    // share.rs)
+    pub fn bits(self) -> u64 {
+        self.bits
+    }
+
+    // Unpacks moves that were stored with `bits`. Returns None if the bits aren't moves on the
+    // board, or if there is a gap between two of the moves.
+    pub fn from_bits(bits: u64) -> Option<Self> {
+        let history = History {bits};
+        let tiles = (BOARD_SIZE * BOARD_SIZE) as u64;
+        let valid = (0..history.len()).all(|i| {
+            let code = (bits >> (i as u32 * MOVE_BITS)) & ((1 << MOVE_BITS) - 1);
+            (1..=tiles).contains(&code)
+        });
+        if valid {
+            Some(history)
+        }
+        else {
+            None
+        }
+    }
+}
+
+// Turns the number a move is stored as back into a (row, col) pair
+fn decode(code: u64) -> (usize, usize) {
+    let index = code as usize - 1;
+    (index / BOARD_SIZE, index % BOARD_SIZE)
+}
+
+// An iterator over the moves of a history. It only holds a copy of the bits, so it doesn't borrow
+// the history and the game it came from.
+#[derive(Debug, Clone)]
+pub struct Moves {
+    /// The moves that haven't been returned yet, with the next one in the lowest bits
+    bits: u64,
+}
+
+impl Iterator for Moves {
+    type Item = (usize, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let code = self.bits & ((1 << MOVE_BITS) - 1);
+        if code == 0 {
+            return None;
+        }
+        self.bits >>= MOVE_BITS;
+        Some(decode(code))
+    }
+
+    // The number of moves left is known exactly, which lets `collect` allocate the right amount
+    // and makes `len` available on the iterator
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let length = History {bits: self.bits}.len();
+        (length, Some(length))
+    }
+}
+
+impl ExactSizeIterator for Moves {}
+
+// This lets a history be used directly in a `for` loop
+impl IntoIterator for History {
+    type Item = (usize, usize);
+    type IntoIter = Moves;
+
+    fn into_iter(self) -> Moves {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn push_and_pop() {
+        let mut history = History::new();
+        assert!(history.is_empty());
+        assert_eq!(history.pop(), None);
+
+        let moves = [(1, 1), (0, 0), (2, 2), (0, 2), (2, 0), (1, 0), (1, 2), (0, 1), (2, 1)];
+        for (i, &tile) in moves.iter().enumerate() {
+            history.push(tile);
+            assert_eq!(history.len(), i + 1);
+        }
+        assert_eq!(history.to_vec(), moves);
+        assert_eq!(history.first(), Some((1, 1)));
+        assert_eq!(history.iter().len(), 9);
+
+        assert_eq!(history.pop(), Some((2, 1)));
+        assert_eq!(history.pop(), Some((0, 1)));
+        assert_eq!(history.to_vec(), &moves[..7]);
+        // Popping and pushing the same move gives the same history back
+        history.push((0, 1));
+        assert_eq!(history.to_vec(), &moves[..8]);
+    }
+
+    #[test]
+    fn the_top_left_tile() {
+        // The first tile is stored as 1 rather than 0, so it still counts as a move
+        let mut history = History::new();
+        history.push((0, 0));
+        history.push((0, 0));
+        assert_eq!(history.len(), 2);
+        assert_eq!(history.to_vec(), [(0, 0), (0, 0)]);
+    }
+
+    #[test]
+    fn bits() {
+        let mut history = History::new();
+        history.push((1, 1));
+        history.push((2, 0));
+        assert_eq!(History::from_bits(history.bits()), Some(history));
+        assert_eq!(History::from_bits(0), Some(History::new()));
+        // A tile past the end of the board, and a gap before the last move
+        assert_eq!(History::from_bits(0xa5), None);
+        assert_eq!(History::from_bits(0x505), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "on the board")]
+    fn off_the_board() {
+        History::new().push((3, 0));
+    }
+}
@@ -0,0 +1,369 @@
+// Everything the players type comes in through an Input. By default we read plain lines from stdin,
+// but when the program is built with the `line-editing` feature and a person is typing at a
+// terminal, we use a line editor instead (see editor.rs). The tests read from a string instead, so
+// that they can play out whole sessions without a terminal.
+//
+// The end of the input means different things depending on where it comes from. Moves piped in
+// from a file or another program run out once and for all, and that is how those sessions
+// normally end. A person at a terminal can end the input with Ctrl-D by accident, and then the
+// terminal goes on reading afterwards, so it is worth asking them whether they really want to quit
+// (see `--on-eof`).
+
+#[cfg(test)]
+use std::io::BufRead;
+use std::io::{self, IsTerminal, Write};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use thiserror::Error;
+
+#[cfg(feature = "line-editing")]
+use editor::EditorInput;
+use interrupt;
+
+// How often we check whether Ctrl-C was pressed while waiting for input
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+// A source of lines typed by the players
+pub trait Input {
+    // Shows the prompt and waits for the next line, which is returned without its trailing
+    // newline. If there is a deadline, we stop waiting once it has passed.
+    fn read_line(&mut self, prompt: &str, deadline: Option<Instant>) -> Result<String, ReadError>;
+}
+
+// The reasons that no line was read
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum ReadError {
+    /// The deadline passed before a line was entered
+    #[error("the time ran out before a line was entered")]
+    TimedOut,
+    /// There is nothing left to read (e.g. the user pressed Ctrl-D)
+    #[error("there is nothing left to read")]
+    Closed,
+    /// The user pressed Ctrl-C
+    #[error("interrupted")]
+    Interrupted,
+}
+
+impl ReadError {
+    // The number that stands for this error (see error.rs). Ctrl-C has the same number wherever
+    // it is pressed.
+    pub fn code(&self) -> u16 {
+        match *self {
+            ReadError::TimedOut => 300,
+            ReadError::Closed => 301,
+            ReadError::Interrupted => 302,
+        }
+    }
+}
+
+// What to do when the input ends
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OnEof {
+    /// End the session, just like after the last game
+    Quit,
+    /// Ask whether to quit, and carry on if the answer is no
+    Confirm,
+    /// End the session, but report it as an error, for scripts that should always finish their
+    /// games
+    Fail,
+}
+
+impl OnEof {
+    // Finds the behavior with the name used for it on the command line
+    pub fn from_name(name: &str) -> Option<Self> {
+        match &name.to_lowercase()[..] {
+            "quit" => Some(OnEof::Quit),
+            "confirm" => Some(OnEof::Confirm),
+            "fail" => Some(OnEof::Fail),
+            _ => None,
+        }
+    }
+
+    // What happens when nothing was chosen: people at a terminal are asked, and everything else
+    // quits
+    pub fn default_for(interactive: bool) -> Self {
+        if interactive { OnEof::Confirm } else { OnEof::Quit }
+    }
+}
+
+// Returns true if a person is typing at a terminal, rather than the lines being piped in
+pub fn is_interactive() -> bool {
+    io::stdin().is_terminal()
+}
+
+// Picks the best Input available. Prompts are printed to stderr instead of stdout when stdout is
+// being read by another program (see `--output json`).
+pub fn open(prompts_to_stderr: bool) -> Box<dyn Input + Send> {
+    // The line editor always draws its prompt on the terminal, so we only use it when a person
+    // is typing and the prompts are meant to be shown on stdout anyway
+    #[cfg(feature = "line-editing")]
+    {
+        if !prompts_to_stderr && is_interactive() {
+            if let Some(editor) = EditorInput::new() {
+                return Box::new(editor);
+            }
+        }
+    }
+
+    Box::new(StdinInput::new(prompts_to_stderr))
+}
+
+// Reads plain lines from stdin.
+//
+// Reading from stdin makes the program wait until a line is entered, and there is no way to
+// stop waiting part way through. To be able to give up when a clock runs out, we do the reading
+// on a separate thread. That thread sends each line it reads through a "channel" and the rest of
+// the program receives them on the other end, where it can decide how long it wants to wait.
+// More info: https://doc.rust-lang.org/book/second-edition/ch16-02-message-passing.html
+//
+// Each message on the channel is a line, or None when the input ends. A terminal can be read again
+// after Ctrl-D, so we go on reading until the input ends twice in a row, which is what pressing
+// Ctrl-D again to confirm quitting does. Anything else can only end once.
+pub struct StdinInput {
+    prompts_to_stderr: bool,
+    lines: Receiver<Option<String>>,
+}
+
+impl StdinInput {
+    pub fn new(prompts_to_stderr: bool) -> Self {
+        let (sender, lines) = mpsc::channel();
+        let interactive = is_interactive();
+        // `move` gives ownership of the sender to the new thread. When the thread ends, the sender
+        // is dropped and the receiver finds out that no more lines are coming.
+        thread::spawn(move || send_lines(read_stdin_line, interactive, &sender));
+        Self {prompts_to_stderr, lines}
+    }
+}
+
+// Sends every line that `read` returns, and None each time the input ends, until the input has
+// ended for good
+fn send_lines<F>(mut read: F, interactive: bool, sender: &Sender<Option<String>>)
+    where F: FnMut() -> Option<String> {
+
+    let mut ended = false;
+    loop {
+        let line = read();
+        let last = line.is_none() && (ended || !interactive);
+        ended = line.is_none();
+        // We also stop if sending fails, since that means nobody is listening anymore
+        if sender.send(line).is_err() || last {
+            break;
+        }
+    }
+}
+
+impl Input for StdinInput {
+    fn read_line(&mut self, prompt: &str, deadline: Option<Instant>) -> Result<String, ReadError> {
+        // Rust supports convenient `print!` and `println!` macros which support easy and
+        // customizable formatting of values from your program. Here we are just using them to
+        // prompt for some values that we want the user of our program to provide. The `out!` and
+        // `eout!` macros work like `print!` and `eprint!`, and also add the prompt to the
+        // recording made with `--record-cast` (see cast.rs).
+        //
+        // Line-buffering is when something waits until it sees a new line character before
+        // actually writing to its designated destination. Rust's stdout is line-buffered by
+        // default, so `print!` does not produce any output unless we "flush" the contents of
+        // stdout's buffer.
+        // expect() is how we "ignore" any error that could occur during this process. If an error
+        // does occur, the program will exit with the message we provided.
+        if self.prompts_to_stderr {
+            eout!("{}", prompt);
+            io::stderr().flush().expect("Failed to flush stderr");
+        }
+        else {
+            out!("{}", prompt);
+            io::stdout().flush().expect("Failed to flush stdout");
+        }
+
+        receive(&self.lines, deadline)
+    }
+}
+
+// Reads lines from any `impl BufRead`, like a Cursor over a string of lines typed in advance. The
+// lines are always ready, so there is nothing to wait for and the deadline is ignored.
+#[cfg(test)]
+pub struct ReaderInput<R: BufRead> {
+    lines: R,
+}
+
+#[cfg(test)]
+impl<R: BufRead> ReaderInput<R> {
+    pub fn new(lines: R) -> Self {
+        Self {lines}
+    }
+}
+
+#[cfg(test)]
+impl<R: BufRead> Input for ReaderInput<R> {
+    fn read_line(&mut self, prompt: &str, _deadline: Option<Instant>) -> Result<String, ReadError> {
+        out!("{}", prompt);
+        let mut line = String::new();
+        match self.lines.read_line(&mut line) {
+            // Reading nothing at all means that we reached the end of the lines
+            Ok(0) | Err(_) => Err(ReadError::Closed),
+            Ok(_) => {
+                let len_without_newline = line.trim_end().len();
+                line.truncate(len_without_newline);
+                Ok(line)
+            },
+        }
+    }
+}
+
+// Waits for the next line from an input thread, giving up at the deadline if there is one. The
+// thread sends None when the input ends.
+pub fn receive(lines: &Receiver<Option<String>>, deadline: Option<Instant>)
+    -> Result<String, ReadError> {
+
+    // Ctrl-C doesn't wake us up, so we never wait longer than POLL_INTERVAL before checking for
+    // it again
+    loop {
+        if interrupt::take() {
+            return Err(ReadError::Interrupted);
+        }
+
+        // `recv_timeout` waits until the deadline at most. `saturating_duration_since` gives us
+        // zero instead of panicking if the deadline has already passed.
+        let wait = match deadline {
+            Some(deadline) => deadline.saturating_duration_since(Instant::now()).min(POLL_INTERVAL),
+            None => POLL_INTERVAL,
+        };
+
+        match lines.recv_timeout(wait) {
+            Ok(Some(line)) => return Ok(line),
+            // The input thread stops sending lines once the input ends for good
+            Ok(None) | Err(RecvTimeoutError::Disconnected) => return Err(ReadError::Closed),
+            // Waiting a little while without getting a line only matters once the deadline passes
+            Err(RecvTimeoutError::Timeout) => match deadline {
+                Some(deadline) if Instant::now() >= deadline => return Err(ReadError::TimedOut),
+                _ => {},
+            },
+        }
+    }
+}
+
+// Reads a single line from stdin, returning None at the end of the input
+fn read_stdin_line() -> Option<String> {
+    // This creates a new growable/heap-allocated string. The `mut` after `let` declares that we
+    // plan to modify the string. Saying this explicitly lets the compiler automatically check that
+    // we don't modify any variables that we don't intend to. Many languages encourage you to use
+    // `const` or `final` on pretty much everything until you don't need to. In Rust, that
+    // behaviour is by default.
+    let mut input = String::new();
+    // Here, we read a line of input from the standard input stream stdin. `&mut input` passes a
+    // mutable reference to the String in the input variable. This allows the function to modify
+    // input without taking ownership of its value. That way we can return it from this function
+    // afterwards.
+    // expect() is a function that takes a Result value and exits the program with an error message
+    // if the Result value is anything other than Ok(...). This in a way is "ignoring" any error
+    // that can occur while reading input. However, instead of ignoring it implicitly, we explciitly
+    // call out that we intend to just exit the program with an error if this operation fails. This
+    // is one of the ways that Rust gives you control. Don't want to deal with a potential failure?
+    // You don't have to! But it's really nice to know where the error came from if something ever
+    // does go wrong and you want to figure out why.
+    io::stdin().read_line(&mut input).expect("Failed to read input");
+
+    // An empty string will only be returned if we reach the end of input (otherwise we always
+    // receive at least a newline character).
+    if input.is_empty() {
+        return None;
+    }
+
+    // read_line leaves the trailing newline on the string, so we remove it using truncate. By
+    // modifying the string in place, we avoid copying its contents after it was just allocated.
+    let len_without_newline = input.trim_end().len();
+    input.truncate(len_without_newline);
+
+    Some(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::io::Cursor;
+
+    // Sends the given lines (None for the end of the input) the way the stdin thread would, and
+    // returns everything that was sent before the input ended for good
+    fn send_all(lines: &[Option<&str>], interactive: bool) -> Vec<Option<String>> {
+        let (sender, receiver) = mpsc::channel();
+        let mut lines = lines.iter().map(|line| line.map(String::from));
+        // Once the given lines run out, the input goes on ending
+        send_lines(|| lines.next().unwrap_or(None), interactive, &sender);
+        drop(sender);
+        receiver.iter().collect()
+    }
+
+    #[test]
+    fn on_eof_names() {
+        for &(name, on_eof) in &[("quit", OnEof::Quit), ("confirm", OnEof::Confirm),
+            ("fail", OnEof::Fail)] {
+            assert_eq!(OnEof::from_name(name), Some(on_eof));
+            assert_eq!(OnEof::from_name(&name.to_uppercase()), Some(on_eof));
+        }
+        assert_eq!(OnEof::from_name("ask"), None);
+    }
+
+    #[test]
+    fn on_eof_defaults() {
+        // People at a terminal are asked, and everything else quits. Failing is never the default.
+        assert_eq!(OnEof::default_for(true), OnEof::Confirm);
+        assert_eq!(OnEof::default_for(false), OnEof::Quit);
+    }
+
+    #[test]
+    fn piped_input_ends_once() {
+        // Whatever comes after the end is never read
+        let sent = send_all(&[Some("2B"), None, Some("1A")], false);
+        assert_eq!(sent, vec![Some("2B".to_string()), None]);
+    }
+
+    #[test]
+    fn terminal_ends_after_double_eof() {
+        // Ending the input once at a terminal (the first Ctrl-D, which asks whether to quit)
+        // still lets the player carry on typing
+        let lines = [Some("2B"), None, Some("n"), Some("1A"), None, None, Some("3C")];
+        let sent = send_all(&lines, true);
+        assert_eq!(sent, vec![
+            Some("2B".to_string()),
+            None,
+            Some("n".to_string()),
+            Some("1A".to_string()),
+            None,
+            None,
+        ]);
+    }
+
+    #[test]
+    fn receive_lines() {
+        let (sender, receiver) = mpsc::channel();
+        for line in [Some("2B".to_string()), None, Some("n".to_string())] {
+            sender.send(line).unwrap();
+        }
+        drop(sender);
+        assert_eq!(receive(&receiver, None), Ok("2B".to_string()));
+        // Each end of the input is reported, and what comes after it can still be read
+        assert_eq!(receive(&receiver, None), Err(ReadError::Closed));
+        assert_eq!(receive(&receiver, None), Ok("n".to_string()));
+        // Once nothing more can be sent, the input stays closed
+        assert_eq!(receive(&receiver, None), Err(ReadError::Closed));
+        assert_eq!(receive(&receiver, None), Err(ReadError::Closed));
+    }
+
+    #[test]
+    fn receive_times_out() {
+        let (_sender, receiver) = mpsc::channel();
+        let deadline = Instant::now() + Duration::from_millis(10);
+        assert_eq!(receive(&receiver, Some(deadline)), Err(ReadError::TimedOut));
+    }
+
+    #[test]
+    fn reader_input() {
+        let mut input = ReaderInput::new(Cursor::new("2B\r\n1A"));
+        assert_eq!(input.read_line("", None), Ok("2B".to_string()));
+        assert_eq!(input.read_line("", None), Ok("1A".to_string()));
+        assert_eq!(input.read_line("", None), Err(ReadError::Closed));
+    }
+}
@@ -0,0 +1,62 @@
+// Pressing Ctrl-C normally ends a program right away. That would throw away the game being played,
+// so instead we catch the "interrupt" signal (SIGINT) and just remember that it happened. The code
+// that waits for input checks for it regularly and gives the players a chance to save their game.
+//
+// Crates like `ctrlc` do this for every platform, but all we need is the C standard library's
+// `signal` function, which is available on both Unix-like systems and Windows.
+
+// Atomics can be safely changed from a signal handler and read from any thread
+use std::sync::atomic::{AtomicBool, Ordering};
+
+// This is the same number on every platform that Rust supports
+const SIGINT: i32 = 2;
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+// `extern "C"` lets us call a function written in C. The compiler can't check that we called it
+// correctly, which is why calling it requires an `unsafe` block.
+extern "C" {
+    fn signal(signum: i32, handler: extern "C" fn(i32)) -> usize;
+}
+
+// Called by the operating system when Ctrl-C is pressed. Very few things are safe to do while
+// handling a signal, so we only set a flag and do the rest of the work later.
+extern "C" fn on_interrupt(_signum: i32) {
+    INTERRUPTED.store(true, Ordering::SeqCst);
+    // Some platforms go back to the default behaviour after the first signal, so we ask to be
+    // called again the next time too
+    install();
+}
+
+// Starts catching Ctrl-C. Until this is called, Ctrl-C ends the program immediately.
+pub fn install() {
+    // Safe because `on_interrupt` only does things that are allowed in a signal handler
+    unsafe {
+        signal(SIGINT, on_interrupt);
+    }
+}
+
+// Records an interrupt that didn't come from a signal. The line editor reads Ctrl-C as a key
+// press instead of letting it become a signal.
+#[cfg_attr(not(any(test, feature = "line-editing")), allow(dead_code))]
+pub fn request() {
+    INTERRUPTED.store(true, Ordering::SeqCst);
+}
+
+// Returns true if Ctrl-C was pressed since the last time this was called
+pub fn take() -> bool {
+    // `swap` reads the old value and clears the flag in one step, so no interrupt can be missed
+    INTERRUPTED.swap(false, Ordering::SeqCst)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interrupts_are_taken_once() {
+        request();
+        assert!(take());
+        assert!(!take());
+    }
+}
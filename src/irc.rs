@@ -0,0 +1,247 @@
+// The connection to an IRC server for the `irc` command. IRC is a plain text protocol: the client
+// and the server send each other one command per line, like `PRIVMSG #games :2B` to say "2B" in the
+// channel #games. That makes it simple enough to speak with nothing but a TCP connection.
+// More info: https://modern.ircdocs.horse
+//
+// The connection itself runs on tokio in the net crate (see net/src/lib.rs), so the server's
+// PINGs are answered even while the bot is busy working out a move. Everything else happens here,
+// one line at a time. Only unencrypted connections are supported, which most servers still accept
+// on port 6667. Just like with Discord, waiting for a line times out every so often to check
+// whether Ctrl-C was pressed.
+
+use std::io;
+use std::time::Duration;
+
+use tic_tac_toe_net::{Connection, Incoming};
+use thiserror::Error;
+
+use bots::User;
+use interrupt;
+
+// The port that IRC servers accept unencrypted connections on
+const DEFAULT_PORT: u16 = 6667;
+// How long we wait for a line before checking whether Ctrl-C was pressed
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+// What the server tells the bot about
+#[derive(Debug, Clone)]
+pub enum Event {
+    /// The server accepted the bot's nickname, and the bot joined its channels
+    Welcome,
+    /// Someone said something in one of the channels
+    Message { channel: String, author: User, content: String },
+}
+
+// The reasons that `Client::listen` stops
+#[derive(Debug, Clone, Error)]
+pub enum IrcError {
+    /// The connection failed or was lost
+    #[error("disconnected from the server: {0}")]
+    Disconnected(String),
+    /// Someone else on the server already has the bot's nickname
+    #[error("the nickname is already in use")]
+    NickInUse,
+    /// Ctrl-C was pressed
+    #[error("interrupted")]
+    Interrupted,
+}
+
+impl IrcError {
+    // The number that stands for this error (see error.rs)
+    pub fn code(&self) -> u16 {
+        match *self {
+            IrcError::Disconnected(_) => 510,
+            IrcError::NickInUse => 511,
+            IrcError::Interrupted => 302,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Client {
+    /// The server to connect to, like irc.libera.chat or irc.libera.chat:6667
+    server: String,
+    nick: String,
+    /// The channels to join, like #games
+    channels: Vec<String>,
+}
+
+// Says things in channels while the bot is connected
+#[derive(Debug)]
+pub struct Sender<'a> {
+    connection: &'a Connection,
+}
+
+// A line sent by the server, split into its parts. Every part borrows from the line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Line<'a> {
+    /// Who sent the line, like `Ada!ada@example.com`, if it came from someone other than the server
+    prefix: Option<&'a str>,
+    command: &'a str,
+    params: Vec<&'a str>,
+}
+
+impl<'a> Sender<'a> {
+    // Sends each line of the text as its own message, since a message can't have more than one
+    pub fn say(&mut self, channel: &str, text: &str) -> io::Result<()> {
+        for line in text.lines().filter(|line| !line.is_empty()) {
+            send(self.connection, &format!("PRIVMSG {} :{}", channel, line))?;
+        }
+        Ok(())
+    }
+}
+
+impl Client {
+    pub fn new(server: String, nick: String, channels: Vec<String>) -> Self {
+        Client {server, nick, channels}
+    }
+
+    pub fn server(&self) -> &str {
+        &self.server
+    }
+
+    pub fn nick(&self) -> &str {
+        &self.nick
+    }
+
+    // Connects to the server and calls `on_event` for every event until the connection ends.
+    // Connections always end eventually, so this only ever returns the reason why.
+    pub fn listen(&self, mut on_event: impl FnMut(Event, &mut Sender)) -> IrcError {
+        match self.connect(&mut on_event) {
+            Ok(error) => error,
+            Err(error) => IrcError::Disconnected(error.to_string()),
+        }
+    }
+
+    fn connect(&self, on_event: &mut impl FnMut(Event, &mut Sender)) -> io::Result<IrcError> {
+        // A server without a port uses the usual one
+        let address = if self.server.contains(':') {
+            self.server.clone()
+        }
+        else {
+            format!("{}:{}", self.server, DEFAULT_PORT)
+        };
+        let connection = Connection::open(&address, pong)?;
+        send(&connection, &format!("NICK {}", self.nick))?;
+        send(&connection, &format!("USER {} 0 * :{}", self.nick, env!("CARGO_PKG_NAME")))?;
+
+        loop {
+            if interrupt::take() {
+                send(&connection, "QUIT")?;
+                connection.close();
+                return Ok(IrcError::Interrupted);
+            }
+            let text = match connection.receive(POLL_INTERVAL) {
+                Some(Incoming::Line(text)) => text,
+                Some(Incoming::Closed(error)) => return Err(error),
+                None => continue,
+            };
+            let line = match parse_line(&text) {
+                Some(line) => line,
+                None => continue,
+            };
+            debug!(line = %text, "received an IRC line");
+
+            match (line.command, &line.params[..]) {
+                // Welcome: the nickname was accepted, so we can join the channels
+                ("001", _) => {
+                    send(&connection, &format!("JOIN {}", self.channels.join(",")))?;
+                    on_event(Event::Welcome, &mut Sender {connection: &connection});
+                },
+                ("433", _) => return Ok(IrcError::NickInUse),
+                ("ERROR", params) => {
+                    let reason = params.first().unwrap_or(&"").to_string();
+                    return Ok(IrcError::Disconnected(reason));
+                },
+                // Only messages to channels are answered, not private ones
+                ("PRIVMSG", &[target, content]) if target.starts_with(&['#', '&'][..]) => {
+                    let nick = match line.prefix.and_then(|prefix| prefix.split('!').next()) {
+                        Some(nick) => nick.to_string(),
+                        None => continue,
+                    };
+                    let event = Event::Message {
+                        channel: target.to_string(),
+                        author: User {id: nick.clone(), name: nick},
+                        content: content.to_string(),
+                    };
+                    on_event(event, &mut Sender {connection: &connection});
+                },
+                _ => {},
+            }
+        }
+    }
+}
+
+// The server checks that we are still there with a PING, which the connection answers on its own
+// as soon as it arrives (see net/src/lib.rs)
+fn pong(text: &str) -> Option<String> {
+    let line = parse_line(text)?;
+    match (line.command, &line.params[..]) {
+        ("PING", params) => Some(format!("PONG :{}", params.first().unwrap_or(&""))),
+        _ => None,
+    }
+}
+
+fn send(connection: &Connection, line: &str) -> io::Result<()> {
+    // A line break in the text would start a new command, so it can't be sent as part of a line
+    let line: String = line.chars().filter(|&c| c != '\r' && c != '\n').collect();
+    connection.send(&line)
+}
+
+// Splits a line like `:Ada!ada@example.com PRIVMSG #games :hello there` into its parts. Tags (which
+// start with @) are skipped, since the bot never asks for them. Everything after a ` :` is one
+// parameter, even if it has spaces in it.
+fn parse_line<'a>(line: &'a str) -> Option<Line<'a>> {
+    let mut rest = line;
+    if rest.starts_with('@') {
+        rest = rest.split_once(' ')?.1;
+    }
+    let mut prefix = None;
+    if let Some(after) = rest.strip_prefix(':') {
+        let (source, after) = after.split_once(' ')?;
+        prefix = Some(source);
+        rest = after;
+    }
+
+    let (rest, trailing) = match rest.find(" :") {
+        Some(index) => (&rest[..index], Some(&rest[index + 2..])),
+        None => (rest, None),
+    };
+    let mut words = rest.split(' ').filter(|word| !word.is_empty());
+    let command = words.next()?;
+    let params = words.chain(trailing).collect();
+    Some(Line {prefix, command, params})
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lines() {
+        assert_eq!(parse_line(":Ada!ada@example.com PRIVMSG #games :play @Grace now"), Some(Line {
+            prefix: Some("Ada!ada@example.com"),
+            command: "PRIVMSG",
+            params: vec!["#games", "play @Grace now"],
+        }));
+        assert_eq!(parse_line("PING :irc.example.com"), Some(Line {
+            prefix: None,
+            command: "PING",
+            params: vec!["irc.example.com"],
+        }));
+        assert_eq!(parse_line("@time=2024 :server 001 ttt :Welcome"), Some(Line {
+            prefix: Some("server"),
+            command: "001",
+            params: vec!["ttt", "Welcome"],
+        }));
+        assert_eq!(parse_line(""), None);
+        assert_eq!(parse_line(":server"), None);
+    }
+
+    #[test]
+    fn pings() {
+        assert_eq!(pong("PING :irc.example.com"), Some("PONG :irc.example.com".to_string()));
+        assert_eq!(pong(":server PING :12345"), Some("PONG :12345".to_string()));
+        assert_eq!(pong(":Ada!ada@example.com PRIVMSG #games :PING"), None);
+    }
+}
@@ -0,0 +1,76 @@
+// Just enough JSON to describe what is happening in a game. Crates like `serde_json` can handle
+// any JSON you can think of, but we only ever write a few kinds of values, so a handful of small
+// functions does the job. Each function returns a String containing valid JSON that can be pasted
+// into a larger JSON object using `format!`.
+// More info about the format: https://www.json.org/
+
+use game::{Piece, Tiles, Winner};
+
+// A JSON string, with quotes around it and any special characters escaped
+pub fn string(text: &str) -> String {
+    let mut json = String::with_capacity(text.len() + 2);
+    json.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            // Any other control character is written as its character code, e.g. \u0007
+            c if c.is_control() => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+// `"x"` or `"o"`, or `null` if there is no piece
+pub fn piece(piece: Option<Piece>) -> String {
+    match piece {
+        Some(Piece::X) => string("x"),
+        Some(Piece::O) => string("o"),
+        None => "null".to_string(),
+    }
+}
+
+// `"x"`, `"o"` or `"tie"`, or `null` if the game isn't over yet
+pub fn winner(winner: Option<Winner>) -> String {
+    match winner {
+        Some(Winner::X) => string("x"),
+        Some(Winner::O) => string("o"),
+        Some(Winner::Tie) => string("tie"),
+        None => "null".to_string(),
+    }
+}
+
+// The board as an array of rows, each of which is an array of tiles
+pub fn tiles(tiles: &Tiles) -> String {
+    // `collect` can join an iterator of Strings into a Vec which we then join with commas
+    let rows: Vec<_> = tiles.iter().map(|row| {
+        let row: Vec<_> = row.iter().map(|&tile| piece(tile)).collect();
+        format!("[{}]", row.join(","))
+    }).collect();
+    format!("[{}]", rows.join(","))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strings_are_escaped() {
+        assert_eq!(string("Ada"), r#""Ada""#);
+        assert_eq!(string("say \"hi\"\n"), r#""say \"hi\"\n""#);
+        assert_eq!(string("a\\b\u{7}"), r#""a\\b\u0007""#);
+    }
+
+    #[test]
+    fn board() {
+        let mut board = Tiles::default();
+        board[0][0] = Some(Piece::X);
+        board[1][2] = Some(Piece::O);
+        assert_eq!(tiles(&board), r#"[["x",null,null],[null,null,"o"],[null,null,null]]"#);
+    }
+}
@@ -0,0 +1,17 @@
+// The rules and the computer player as a library, for the benchmarks in benches/ to measure. The
+// terminal version (main.rs) declares the same modules itself, so this only builds the ones the
+// benchmarks need.
+
+#[cfg(feature = "parallel")]
+extern crate rayon;
+extern crate thiserror;
+
+pub mod game;
+pub mod history;
+pub mod random;
+pub mod engine;
+pub mod tt;
+pub mod solve;
+pub mod symmetry;
+pub mod simulate;
+pub mod parallel;
@@ -0,0 +1,2250 @@
+// This module contains every message that the program shows to its users, translated into each
+// of the languages we support. Keeping all of the text in one place means that the rest of the
+// code never has to know which language is being used.
+//
+// Each message is a variant of the Message enum. Because we `match` on every message for every
+// language, Rust will refuse to compile the program if we add a message and forget to translate
+// it. There are crates like `fluent` which support much more complicated translations, but this
+// simple approach is plenty for a program of this size.
+//
+// Positions on the board (like "1A") are always written the same way in every language so that
+// anything the program prints can be typed back in as a move. They are written in the coordinate
+// style that the player chose (see notation.rs), including in the examples that messages give.
+
+use std::env;
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+
+use archive::{Problem, TagQuery};
+use audit::Finding;
+use check::Repair;
+use clock::ClockTime;
+use coach::{Comment, Level};
+use game::{Adjudication, Game, Piece, PositionError, Winner};
+use notation::{self, CoordinateStyle, Reason};
+use options::OptionsError;
+use plugin::PluginError;
+use render::Variant;
+use series::Score;
+use openings::OpeningResults;
+use explore::Verdict;
+use puzzle::{self, Goal, Hint, Idea};
+use review::Mark;
+use rules::Rules;
+use engine::Difficulty;
+use enumerate::Counts;
+use simulate::Simulation;
+use solve::Phase;
+use speedrun::{Finish, RunTime};
+use storage::Recovery;
+use stats::{Outcome, PlayerStats, Record};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lang {
+    English,
+    French,
+}
+
+// Every message that can be shown to the user. The lifetime `'a` lets some messages borrow the
+// values they display instead of copying them. Players are passed in as the text that should be
+// shown for them (see `Players::label`) so that custom names work in every language.
+// Without either of the chat bots (the `discord` and `irc` features), the messages in chats are
+// never shown.
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(not(any(feature = "discord", feature = "irc")), allow(dead_code))]
+pub enum Message<'a> {
+    /// Asks the user to type in their move
+    EnterMove,
+    /// Shows whose turn it is
+    CurrentPiece(&'a str),
+    /// The text the user entered could not be understood as a move, and why
+    InvalidMove { input: &'a str, reason: &'a Reason },
+    /// The user tried to place a piece on a tile that was already taken, by move number `since`
+    TileNotEmpty { row: usize, col: usize, other_player: &'a str, since: usize },
+    /// Announces the winner of a finished game
+    GameWon(&'a str),
+    /// Announces that a finished game was a tie
+    GameTied,
+    /// Shown before each game in a series
+    GameHeader { number: usize, best_of: u32, first_player: &'a str },
+    /// Shown after each game in a series
+    SeriesScore { x: &'a str, o: &'a str, score: Score },
+    /// Announces the result of a finished series
+    SeriesOver { winner: Winner, x: &'a str, o: &'a str, score: Score },
+    /// A finished game could not be saved to the archive
+    ArchiveFailed { path: &'a Path, error: &'a io::Error },
+    /// Asks what to do once a game or series is over
+    PlayAgain,
+    PlayAgainHelp,
+    /// Asks for the number of games in a series, showing the current value
+    NumberOfGames(u32),
+    NumberOfGamesHelp,
+    /// Asks who should go first, showing the current value
+    FirstPiece(Piece),
+    FirstPieceHelp,
+    /// The command line arguments were invalid
+    Options(&'a OptionsError),
+    /// Describes one row of the board in words (used by the screen reader renderer)
+    /// Each tile is the name of the player whose piece is there, or None if it is empty.
+    RowDescription { row: usize, tiles: &'a [Option<&'a str>] },
+    /// Announces a move that was just made
+    MovePlayed { player: &'a str, row: usize, col: usize },
+    /// Warns that a player could win by playing at a position
+    Threat { player: &'a str, row: usize, col: usize },
+    /// The status header shown above the board. `clocks` is the time x and o have left, if they
+    /// are playing with clocks.
+    Status {
+        move_number: usize,
+        x: &'a str,
+        o: &'a str,
+        variant: Variant,
+        clocks: Option<(ClockTime, ClockTime)>,
+    },
+    /// A player ran out of time and lost the game
+    FlagFell(&'a str),
+    /// A scripted game ran out of moves before it was finished
+    GameUnfinished,
+    /// A move in a scripted game couldn't be understood
+    ScriptInvalidMove { number: usize, notation: &'a str },
+    /// A move in a scripted game was rejected, for the reason in `message` (the same one that is
+    /// given when the move is typed in)
+    ScriptMoveRejected { number: usize, message: &'a str },
+    /// A scripted game had more moves after the game was over
+    ScriptGameOver { number: usize, notation: &'a str },
+    /// The file containing the moves for a scripted game couldn't be read
+    MovesFileFailed { path: &'a Path, error: &'a io::Error },
+    /// Suggests a move that wins the game right away (the `hint` command)
+    HintWin { row: usize, col: usize },
+    /// Suggests a move that stops the other player from winning on their next turn
+    HintBlock { row: usize, col: usize },
+    /// There is no move that wins or blocks right now
+    NoHint,
+    /// A move was taken back (the `undo` command)
+    MoveUndone { row: usize, col: usize },
+    NothingToUndo,
+    /// A player went back to look at an earlier position, the one after `moves` of the `total`
+    /// moves of the game
+    Browsing { moves: usize, total: usize },
+    /// A player tried to move while looking at an earlier position
+    BrowsingNoMoves,
+    /// A player tried to go back from the position before the first move
+    NoEarlierPosition,
+    /// A player tried to go forward while looking at the game as it is
+    NotBrowsing,
+    /// Asks whether to save the unfinished game to the archive after Ctrl-C was pressed
+    SaveBeforeQuitting(&'a Path),
+    /// Asks where to save the unfinished game after Ctrl-C was pressed when there is no archive
+    SaveToFile,
+    YesNoHelp,
+    /// The unfinished game was saved
+    GameSaved(&'a Path),
+    /// Shown at the start of the tutorial
+    TutorialIntro { student: &'a str, tutor: &'a str },
+    /// Tutorial advice: the student can win with this move
+    TutorialWin { row: usize, col: usize },
+    /// Tutorial advice: the student has to block the opponent here
+    TutorialBlock { row: usize, col: usize, opponent: &'a str },
+    /// Tutorial advice: this move makes two threats at once
+    TutorialFork { row: usize, col: usize },
+    /// Tutorial advice: this move is on `lines` of the ways to win
+    TutorialLines { row: usize, col: usize, lines: usize },
+    /// The student's last move made two threats at once
+    TutorialForkMade { opponent: &'a str },
+    /// The tutorial announces its own move
+    TutorialTutorMove { row: usize, col: usize },
+    /// The student played something other than the move from the lesson
+    TutorialWrongMove { row: usize, col: usize },
+    /// Shown at the end of the tutorial
+    TutorialDone,
+    /// Explains blindfold mode at the start of the session
+    BlindfoldIntro { shows: u32 },
+    /// The board was shown with the `show` command in blindfold mode
+    ShowsLeft(u32),
+    /// The players already used up all of their chances to see the board this game
+    NoShowsLeft,
+    /// An image of the board was saved
+    Exported(&'a Path),
+    /// An image of the board couldn't be saved
+    ExportFailed { path: &'a Path, error: &'a io::Error },
+    /// The game tree was saved as a GraphViz graph with the given number of positions
+    TreeExported { path: &'a Path, positions: usize },
+    /// The game tree couldn't be saved
+    TreeExportFailed { path: &'a Path, error: &'a io::Error },
+    /// The file for `--record-cast` couldn't be created
+    CastFailed { path: &'a Path, error: &'a io::Error },
+    /// The file for `--log-file` couldn't be opened
+    LogFailed { path: &'a Path, error: &'a io::Error },
+    /// The preferences file couldn't be read or written
+    PrefsFailed { path: &'a Path, error: &'a io::Error },
+    /// The options were saved as the preferences for next time
+    PrefsSaved(&'a Path),
+    /// There is nowhere to save the preferences on this system
+    NoPrefsDir,
+    /// Shown above the board during each game of the demo. `games` is the number of games that
+    /// will be played, or None if the demo goes on forever.
+    DemoGame { number: usize, games: Option<u32>, first_player: &'a str },
+    /// Explains how to stop the demo
+    DemoHelp,
+    /// The archive couldn't be read
+    ArchiveReadFailed { path: &'a Path, error: &'a io::Error },
+    /// The archive (or its backup) couldn't be written
+    ArchiveWriteFailed { path: &'a Path, error: &'a io::Error },
+    /// Introduces the stats of the named players in an archive
+    StatsHeader(&'a Path),
+    /// The games a named player has played and how they went
+    PlayerStats { name: &'a str, stats: &'a PlayerStats },
+    /// The move a player starts with most often, and how many times they did
+    FavoriteOpening { row: usize, col: usize, games: u32 },
+    /// How the player's latest games went, e.g. 3 wins in a row
+    Streak { outcome: Outcome, games: u32, best_win_streak: u32 },
+    /// The archive doesn't have any games with a named player in it
+    NoStats(&'a Path),
+    /// A player's rating after the games that were just played, and how much it changed
+    RatingChange { player: &'a str, rating: i32, change: i32 },
+    /// Introduces the ratings of the named players in an archive
+    RatingsHeader(&'a Path),
+    /// A player's place in the ratings, their rating and the number of rated games they played
+    PlayerRating { rank: usize, name: &'a str, rating: i32, games: u32 },
+    /// The archive doesn't have any games between two named players in it
+    NoRatings(&'a Path),
+    /// The seed of the random numbers used by the computer players
+    Seed(u64),
+    /// Introduces the games that two players played against each other
+    HeadToHead { a: &'a str, b: &'a str, path: &'a Path, games: u32 },
+    /// How the games between two players went for the first of them, either when they played
+    /// the given piece or overall
+    HeadToHeadRecord { a: &'a str, b: &'a str, piece: Option<Piece>, record: Record },
+    /// The average number of moves in the games between two players
+    HeadToHeadLength(f64),
+    /// Two players never played each other
+    NoHeadToHead { a: &'a str, b: &'a str, path: &'a Path },
+    /// Introduces the results of each opening in an archive
+    OpeningsHeader(&'a Path),
+    /// How the games that started with the given moves went for the player who went first
+    Opening { moves: &'a [(usize, usize)], results: &'a OpeningResults },
+    /// The archive doesn't have any games with moves in it
+    NoOpenings(&'a Path),
+    /// Announces the next game of a tournament and who is playing it
+    TournamentGame { number: usize, games: usize, x: &'a str, o: &'a str },
+    /// Introduces the crosstable at the end of a tournament
+    TournamentOver,
+    /// The heading of the column with the names of the entrants in the crosstable
+    CrosstableEntrant,
+    /// The heading of the column with each entrant's total score in the crosstable
+    CrosstableScore,
+    /// Announces the next round of a Swiss tournament
+    SwissRound { round: u32, rounds: u32 },
+    /// An entrant sits out a round of a Swiss tournament because there is nobody left to play
+    SwissBye(&'a str),
+    /// Introduces the standings after a round of a Swiss tournament
+    SwissStandings(u32),
+    /// The heading of the column with the tie-break score in the standings of a Swiss tournament
+    StandingsBuchholz,
+    /// Introduces a match between two computer players in the arena
+    ArenaStart { first: &'a str, second: &'a str, elo0: f64, elo1: f64, max_games: u32 },
+    /// How the games in the arena went so far for the first player
+    ArenaResults { games: u32, wins: u32, losses: u32, ties: u32 },
+    /// The Elo difference between the players in the arena and its 95% confidence interval
+    ArenaElo { elo: f64, low: f64, high: f64 },
+    /// The test found that the first player is at least `elo` stronger than the second one
+    ArenaAccepted { first: &'a str, second: &'a str, elo: f64 },
+    /// The test found that the first player is at most `elo` stronger than the second one
+    ArenaRejected { first: &'a str, second: &'a str, elo: f64 },
+    /// The arena played as many games as it was allowed to without the test deciding
+    ArenaUndecided(u32),
+    /// The first line of the summary shown at the end of a session
+    SessionSummary { games: u32, average_moves: f64, duration: ClockTime },
+    /// How the session went for one of the players
+    SessionPlayer { player: &'a str, wins: u32, losses: u32, ties: u32 },
+    /// The session summary couldn't be added to the CSV file
+    SummaryCsvFailed { path: &'a Path, error: &'a io::Error },
+    /// The number of leaves in the game tree at the given depth and how long counting them took
+    Perft { depth: u32, leaves: u64, time: Duration },
+    /// How many reachable positions ended in each way, and whether boards that only differ by
+    /// turning or flipping were counted once
+    Enumeration { counts: &'a Counts, symmetry: bool },
+    /// The engine agrees with the enumeration about every position still being played
+    EnumerationVerified(u32),
+    /// The engine disagrees with the enumeration about the position after the given moves
+    EnumerationMismatch(&'a [(usize, usize)]),
+    /// How many positions a profiled search visited and how long it took
+    ProfileSearch { positions: u64, time: Duration },
+    /// How long one phase of a profiled search took in total, and what percentage of the whole
+    /// search that is. None is the time that wasn't spent in any phase.
+    ProfilePhase { phase: Option<Phase>, time: Duration, share: f64 },
+    /// The folded stacks of a profile were saved for making a flame graph
+    ProfileSaved(&'a Path),
+    /// The folded stacks of a profile couldn't be saved
+    ProfileFailed { path: &'a Path, error: &'a io::Error },
+    /// Announces a simulation and the computer players in it
+    SimulationStart { games: u32, x: &'a str, o: &'a str },
+    /// How often each player has won so far in a simulation
+    SimulationResults(&'a Simulation),
+    /// How many moves the games of a simulation took
+    SimulationLengths(&'a Simulation),
+    /// How long a simulation took
+    SimulationTime(&'a Simulation),
+    /// Something wrong with the given line of an archive that is being verified
+    VerifyProblem { line: usize, problem: &'a Problem },
+    /// Every game in the archive checked out
+    VerifyPassed { path: &'a Path, games: usize },
+    /// The archive has the given number of problems
+    VerifyFailed { path: &'a Path, problems: usize },
+    /// Something that the program no longer agrees with on the given line of an archive that is
+    /// being audited
+    AuditFinding { line: usize, finding: &'a Finding },
+    /// Every game in the archive was played again without finding anything. `moves` is the number
+    /// of engine moves that were checked.
+    AuditPassed { path: &'a Path, games: usize, moves: usize },
+    /// The given number of things were found in the archive
+    AuditFailed { path: &'a Path, findings: usize },
+    /// The commands that the chat bots understand. On some chats, people are mentioned with an @.
+    ChatHelp { mention: bool },
+    /// The players of a game in a chat channel, along with their pieces
+    ChatVersus { x: &'a str, o: &'a str },
+    /// The name of the computer when it plays in a chat channel
+    ChatComputer,
+    /// Whose turn it is in a chat channel
+    ChatTurn(&'a str),
+    /// A player in a chat channel gave up
+    ChatResigned(&'a str),
+    /// Someone tried to start a game in a chat channel that already has one going
+    ChatGameInProgress,
+    /// There is no game going on in the chat channel
+    ChatNoGame,
+    /// Someone who isn't playing tried to resign
+    ChatNotPlaying,
+    /// Someone challenged themselves
+    ChatPlayYourself,
+    /// Introduces the ratings of the players in a chat
+    ChatRatingsHeader,
+    /// There are no ratings to show in a chat, either because there is no archive or because no
+    /// two people have played each other yet
+    ChatNoRatings,
+    /// Explains how puzzles work at the start of the `puzzle` command
+    PuzzleIntro,
+    /// Introduces a puzzle and what the player has to do in it
+    PuzzleHeader { number: usize, total: usize, player: &'a str, goal: Goal, moves: usize },
+    /// The player's move doesn't reach the goal of the puzzle
+    PuzzleWrongMove { row: usize, col: usize, goal: Goal },
+    /// The other player answered the player's move in a puzzle
+    PuzzleReply { row: usize, col: usize },
+    PuzzleHint(Hint),
+    /// The puzzle was solved with the given number of hints
+    PuzzleSolved { hints: usize },
+    /// Every puzzle was solved
+    PuzzlesDone(usize),
+    /// A file of puzzles couldn't be read
+    PuzzleReadFailed { path: &'a Path, error: &'a io::Error },
+    /// The given line of a file of puzzles isn't a puzzle that can be played
+    PuzzleProblem { path: &'a Path, line: usize, problem: &'a puzzle::Problem },
+    /// Introduces the puzzle of the day and what the player has to do in it
+    DailyHeader { date: &'a str, player: &'a str, goal: Goal, moves: usize },
+    /// The days in a row that the daily puzzle was solved on, counting today
+    DailyStreak { current: u32, best: u32 },
+    /// Shown before the result of the daily puzzle
+    DailyShare,
+    /// Explains how to set up a position at the start of the `setup` command
+    SetupIntro,
+    /// Shown above the position being set up, with the player who moves next
+    SetupStatus(&'a str),
+    /// Asks for a tile to change or a command in the position editor
+    EnterEdit,
+    /// What was typed in the position editor isn't a tile or a command
+    InvalidEdit(&'a str),
+    /// The position that was set up can't be played or analyzed
+    PositionInvalid(PositionError),
+    /// The position that was set up is already over, so there is nothing to play
+    PositionOver,
+    /// How the position ends if both players play perfectly (None for a tie), and the moves that
+    /// get that result
+    PositionAnalysis { winner: Option<&'a str>, moves: &'a [(usize, usize)] },
+    /// Explains how to step through the review of a game
+    ReviewIntro,
+    /// A move of the game being reviewed, how good it was, and the better moves if there were any
+    ReviewMove {
+        player: &'a str,
+        row: usize,
+        col: usize,
+        mark: Mark,
+        better: &'a [(usize, usize)],
+    },
+    /// Asks where to go next in the review
+    ReviewPrompt,
+    /// The answer to ReviewPrompt wasn't recognized
+    ReviewHelp,
+    /// How many of a player's moves were the best, ok and mistakes, in that order
+    ReviewSummary { player: &'a str, counts: [usize; 3] },
+    /// How long a player thought about the move being reviewed
+    ThinkTime(Duration),
+    /// How long a player thought about their timed moves in all, and how many of them there were
+    ReviewThinking { player: &'a str, total: Duration, moves: usize },
+    /// The board is hidden and the clocks are stopped until the players go on
+    Paused,
+    /// Asks the players to press enter once they are ready to go on with a paused game
+    EnterResume,
+    /// A paused game goes on, after the pause lasted the given time
+    Resumed(Duration),
+    /// A player paused the game in a chat channel
+    ChatPaused(&'a str),
+    /// The game in a chat channel is paused, so nothing can be played until it goes on
+    ChatStillPaused,
+    /// Someone tried to go on with a game in a chat channel that isn't paused
+    ChatNotPaused,
+    /// What the coach says about the move that a player just made
+    Coach { comment: Comment, player: &'a str, opponent: &'a str },
+    /// The player wins no matter what the other one does, in this many more moves of their own
+    /// (see forced.rs)
+    ForcedWin { player: &'a str, moves: usize },
+    /// Neither player can win anymore if the other one plays well
+    ForcedDraw,
+    /// The coach of a chat channel was turned on at the given level, or turned off
+    ChatCoach(Option<Level>),
+    /// How to choose the coach of a chat channel, after `!ttt coach` with something else
+    ChatCoachHelp,
+    /// Explains the speedrun before its first game
+    SpeedrunIntro { wins: u32, difficulty: Difficulty },
+    /// Shown before each game of a speedrun, along with the games won so far
+    SpeedrunGame { number: u32, wins: u32, needed: u32 },
+    /// The time of a speedrun so far
+    SpeedrunTime(Duration),
+    /// The gauntlet was won, after the given number of games
+    SpeedrunDone { time: Duration, games: u32 },
+    /// How the time of a speedrun compares to the player's best time
+    SpeedrunFinish(Finish),
+    /// Only players with a name have a best time
+    SpeedrunNoName,
+    /// Shown before the best times against a difficulty
+    SpeedrunLeaderboard(Difficulty),
+    /// One player's best time on the leaderboard
+    SpeedrunEntry { rank: usize, name: &'a str, time: Duration },
+    /// A computer game was ended early because its result was already certain
+    Adjudicated(Adjudication),
+    /// How many of the games of a simulation were adjudicated
+    SimulationAdjudicated(&'a Simulation),
+    /// Explains the commands of a club night before its first game
+    ClubIntro,
+    /// One of the tables of a club night, in the list shown by `games`
+    ClubTable {
+        id: usize,
+        /// True for the table whose game is being played
+        focused: bool,
+        x: &'a str,
+        o: &'a str,
+        moves: usize,
+        /// The player whose turn it is at the table
+        current: &'a str,
+    },
+    /// The game at a table of a club night is over, and the players go on at the other tables
+    ClubGameOver(usize),
+    /// The move prompt now plays the game at this table
+    ClubSwitched(usize),
+    /// There is no table with the id that the players typed
+    ClubNoTable(usize),
+    /// `games`, `switch` and `new` were typed outside of a club night
+    ClubOnly,
+    /// Asks for the name of a player at a new table
+    ClubPlayerName(Piece),
+    /// Shown before offering to save the game at a table, when there are several
+    ClubSaving(usize),
+    /// Explains how a simul is played before its first move
+    SimulIntro { giver: &'a str, boards: usize },
+    /// The title above one of the boards of a simul in the overview of every board
+    SimulBoard {
+        id: usize,
+        opponent: &'a str,
+        /// True for the board that the giver is at
+        focused: bool,
+    },
+    /// The giver of a simul went on to the board with this id
+    SimulNextBoard(usize),
+    /// `switch` and `new` were typed at a simul, where the boards come up in turn
+    SimulFixed,
+    /// How the giver did once every game of a simul is over
+    SimulResult { giver: &'a str, wins: usize, ties: usize, losses: usize },
+    /// Explains the opening explorer before its first position
+    ExploreIntro,
+    /// Shown above the moves that a player can make in the position being explored
+    ExploreMoves(&'a str),
+    /// One of the moves in the position being explored, with the tiles that are the same move
+    /// from another side of the board. The results are None when there is no archive.
+    ExploreCandidate {
+        tiles: &'a [(usize, usize)],
+        verdict: Verdict,
+        results: Option<OpeningResults>,
+    },
+    /// Asks where to go next in the explorer
+    ExplorePrompt,
+    /// The answer to ExplorePrompt wasn't recognized
+    ExploreHelp,
+    /// Shown above the board of a game being watched
+    WatchHelp(&'a Path),
+    /// The file of a game being watched doesn't exist yet
+    WatchWaiting(&'a Path),
+    /// The code of a game, which can be given to `--from-code` (see share.rs)
+    GameCode(&'a str),
+    /// A link to a game, made from its code
+    GameLink(&'a str),
+    /// Shown before playing or reviewing the game of a code, with the number of moves in it
+    CodeLoaded(usize),
+    /// The QR code of a game was saved as a PNG image
+    #[cfg_attr(not(feature = "images"), allow(dead_code))]
+    QrExported(&'a Path),
+    #[cfg_attr(not(feature = "images"), allow(dead_code))]
+    QrExportFailed { path: &'a Path, error: &'a io::Error },
+    /// The program was built without the `images` feature, so it can't draw PNG images, GIF
+    /// animations or QR codes
+    #[cfg_attr(feature = "images", allow(dead_code))]
+    ImagesUnavailable,
+    /// A game to compare is neither a file nor a game code
+    DiffInvalidGame(&'a str),
+    /// Both games have the same moves, up to turning and flipping the board. `exact` is false if
+    /// one of them is played on a turned or flipped board.
+    DiffSame { moves: usize, exact: bool },
+    /// The first move (counting from 1) where the games go different ways, and what was played
+    /// there in each of them
+    DiffDiverged { number: usize, first: (usize, usize), second: (usize, usize), exact: bool },
+    /// One of the games has every move of the other one and then goes on
+    DiffLonger { common: usize, first_longer: bool },
+    /// The result and the number of moves of each game
+    DiffResults { results: (Option<Winner>, Option<Winner>), lengths: (usize, usize) },
+    /// A game in an archive is the same as the one on an earlier line, up to symmetry
+    CheckDuplicate { line: usize, first: usize },
+    /// A field in an archive that this version of the program doesn't know
+    CheckUnknownField { line: usize, field: &'a str },
+    /// Something that `archive check --fix` changed
+    CheckRepaired { line: usize, repair: Repair },
+    /// Shown once the archive was fixed, with where the archive was copied to before
+    CheckFixed { backup: &'a Path, repairs: usize },
+    /// The training data was saved, with the number of samples and of the games they came from
+    TrainingExported { path: &'a Path, samples: usize, games: usize },
+    TrainingExportFailed { path: &'a Path, error: &'a io::Error },
+    /// Asks whether to quit after the input ended at a terminal (e.g. Ctrl-D was pressed)
+    ConfirmQuit,
+    /// The input ended before the session was over, with `--on-eof fail`
+    InputClosed,
+    /// The plugin in this file couldn't be loaded. The reason is in English, since it often comes
+    /// from the operating system.
+    #[cfg_attr(not(feature = "plugins"), allow(dead_code))]
+    PluginSkipped { path: &'a Path, error: &'a PluginError },
+    /// A plugin asked for on the command line isn't there
+    #[cfg_attr(not(feature = "plugins"), allow(dead_code))]
+    PluginMissing(&'a PluginError),
+    /// A computer player plugin chose a move that can't be played
+    PluginMoveFailed { name: &'a str, error: &'a PluginError },
+    /// The program was built without the `plugins` feature
+    #[cfg_attr(feature = "plugins", allow(dead_code))]
+    PluginsUnavailable,
+    /// Games played by homebrew rules aren't saved, so the archive given on the command line
+    /// isn't used
+    RulesNotArchived(Rules),
+    /// A saved file was corrupt, so it was moved out of the way (see storage.rs)
+    FileRecovered { path: &'a Path, recovery: &'a Recovery },
+    /// The player didn't move before the idle timeout, so the computer played for them
+    IdleMoved { player: &'a str, row: usize, col: usize },
+    /// The player didn't move before the idle timeout, so they lost the game
+    IdleForfeit(&'a str),
+    /// A game found by `archive tagged`, numbered from 1 among the games of the archive, along
+    /// with the moves that have the tag (counting from 0)
+    TaggedGame { number: usize, game: &'a Game, moves: &'a [usize] },
+    /// How many of the games in the archive `archive tagged` found
+    TaggedFound { query: &'a TagQuery, found: usize, games: usize },
+    // Just like the Discord messages below, only some of the IRC messages are used, depending on
+    // whether the program was built with the `irc` feature
+    /// The program was built without the `irc` feature
+    #[cfg_attr(feature = "irc", allow(dead_code))]
+    IrcUnavailable,
+    /// The IRC bot connected to the server and is joining its channels
+    #[cfg_attr(not(feature = "irc"), allow(dead_code))]
+    IrcConnected { server: &'a str, nick: &'a str },
+    /// The connection to the IRC server was lost, and the bot will try again
+    #[cfg_attr(not(feature = "irc"), allow(dead_code))]
+    IrcDisconnected(&'a str),
+    /// Someone else on the IRC server already has the bot's nickname
+    #[cfg_attr(not(feature = "irc"), allow(dead_code))]
+    IrcNickInUse(&'a str),
+    // Only one of the next two groups of messages is ever used, depending on whether the program
+    // was built with the `discord` feature
+    /// The program was built without the `discord` feature
+    #[cfg_attr(feature = "discord", allow(dead_code))]
+    DiscordUnavailable,
+    /// The bot needs a token to connect to Discord
+    #[cfg_attr(not(feature = "discord"), allow(dead_code))]
+    DiscordNoToken,
+    /// The bot connected to Discord with the given name
+    #[cfg_attr(not(feature = "discord"), allow(dead_code))]
+    DiscordConnected(&'a str),
+    /// The connection to Discord was lost, and the bot will try again
+    #[cfg_attr(not(feature = "discord"), allow(dead_code))]
+    DiscordDisconnected(&'a str),
+    /// Discord refused to let the bot connect, so there is no point trying again
+    #[cfg_attr(not(feature = "discord"), allow(dead_code))]
+    DiscordRejected(&'a str),
+}
+
+impl Lang {
+    // Finds the language for a code like "fr", "en_US" or "fr_CA.UTF-8". Only the part before the
+    // region or encoding matters to us. Returns None if we don't support that language.
+    pub fn from_code(code: &str) -> Option<Self> {
+        // `split` returns an iterator, and the first item is everything before the first `_`,
+        // `-` or `.` (or the whole string if there are none of those characters)
+        let language = code.split(&['_', '-', '.'][..]).next().unwrap_or("");
+        match &language.to_lowercase()[..] {
+            "en" => Some(Lang::English),
+            "fr" => Some(Lang::French),
+            _ => None,
+        }
+    }
+
+    // The code used for this language on the command line
+    pub fn code(self) -> &'static str {
+        match self {
+            Lang::English => "en",
+            Lang::French => "fr",
+        }
+    }
+
+    // Uses the LANG environment variable to pick a language, falling back to English if it isn't
+    // set or names a language we don't support
+    pub fn from_env() -> Self {
+        env::var("LANG").ok()
+            .and_then(|code| Self::from_code(&code))
+            .unwrap_or(Lang::English)
+    }
+
+    // Returns the text of the given message in this language
+    pub fn text(self, message: Message) -> String {
+        match self {
+            Lang::English => english(message),
+            Lang::French => french(message),
+        }
+    }
+}
+
+fn english(message: Message) -> String {
+    match message {
+        Message::EnterMove => format!("Enter move (e.g. {}): ", position(0, 0)),
+        Message::CurrentPiece(player) => format!("Current piece: {}", player),
+        Message::InvalidMove {input, reason} => match *reason {
+            Reason::Unreadable => format!("Invalid move: '{}'. Please try again.", input),
+            Reason::NoSuchRow(ref row) => format!(
+                "Invalid move: '{}'. Row {} doesn't exist on a 3-tall board.", input, row),
+            Reason::NoSuchColumn(ref col) => format!(
+                "Invalid move: '{}'. Column {} doesn't exist on a 3-wide board.", input, col),
+            Reason::NoSuchKey(ref key) => format!(
+                "Invalid move: '{}'. Key {} doesn't exist: the keys go from 1 to 9.", input, key),
+        },
+        Message::TileNotEmpty {row, col, other_player, since} => format!(
+            "The tile at position {} has had piece {} in it since move {}!",
+            position(row, col), other_player, since),
+        Message::GameWon(player) => format!("{} wins!", player),
+        Message::GameTied => "Tie!".to_string(),
+        Message::GameHeader {number, best_of, first_player} => format!(
+            "Game {} of {} ({} goes first)", number, best_of, first_player),
+        Message::SeriesScore {x, o, score} => format!(
+            "Score: {} {} - {} {} (ties: {})", x, score.x, o, score.o, score.ties),
+        Message::SeriesOver {winner: Winner::X, x, score, ..} => format!(
+            "{} wins the match {}-{}!", x, score.x, score.o),
+        Message::SeriesOver {winner: Winner::O, o, score, ..} => format!(
+            "{} wins the match {}-{}!", o, score.o, score.x),
+        Message::SeriesOver {winner: Winner::Tie, score, ..} => format!(
+            "The match is tied {}-{}!", score.x, score.o),
+        Message::ArchiveFailed {path, error} => format!(
+            "Failed to save to archive '{}': {}", path.display(), error),
+        Message::PlayAgain => {
+            "Play again? (same settings / swap pieces / rematch / change settings / analyze the \
+            game / share the game / quit): ".to_string()
+        },
+        Message::PlayAgainHelp => {
+            "Please enter one of: same, swap, rematch, change, analyze, share, or quit.".to_string()
+        },
+        Message::NumberOfGames(current) => format!(
+            "Number of games (1 for a single game) [{}]: ", current),
+        Message::NumberOfGamesHelp => "Please enter a number greater than zero.".to_string(),
+        Message::FirstPiece(current) => format!(
+            "Who goes first? (x/o) [{}]: ", piece_name(current)),
+        Message::FirstPieceHelp => "Please enter either x or o.".to_string(),
+        Message::Options(OptionsError::UnknownArgument(arg)) => format!(
+            "Unknown argument: '{}'", arg),
+        Message::Options(OptionsError::MissingValue(option)) => format!(
+            "Missing value for '{}'", option),
+        Message::Options(OptionsError::InvalidValue {option, value}) => format!(
+            "Invalid value for '{}': '{}'", option, value),
+        Message::Options(OptionsError::Requires {option, required}) => format!(
+            "'{}' can only be used together with '{}'", option, required),
+        Message::RowDescription {row, tiles} => format!(
+            "Row {}: {}.", row + 1, describe_tiles(tiles, "empty")),
+        Message::MovePlayed {player, row, col} => format!(
+            "{} played {}.", player, position(row, col)),
+        Message::Threat {player, row, col} => format!(
+            "{} threatens to win at {}.", player, position(row, col)),
+        Message::Status {move_number, x, o, variant, clocks} => {
+            let variant = match variant {
+                Variant::Classic => "Classic",
+                Variant::Blindfold => "Blindfold",
+                Variant::Tutorial => "Tutorial",
+                Variant::Demo => "Demo",
+                Variant::Puzzle => "Puzzle",
+                Variant::Review => "Review",
+            };
+            let status = format!("Move {} | {} vs {} | {}", move_number, x, o, variant);
+            match clocks {
+                Some((x_time, o_time)) => format!(
+                    "{} | Clocks: {} {} - {} {}", status, x, x_time, o, o_time),
+                None => status,
+            }
+        },
+        Message::FlagFell(player) => format!("{} ran out of time!", player),
+        Message::GameUnfinished => "The game is not over yet.".to_string(),
+        Message::ScriptInvalidMove {number, notation} => format!(
+            "Move {} ('{}') is not a valid move.", number, notation),
+        Message::ScriptMoveRejected {number, message} => format!("Move {}: {}", number, message),
+        Message::ScriptGameOver {number, notation} => format!(
+            "Move {} ('{}') is illegal: the game is already over.", number, notation),
+        Message::MovesFileFailed {path, error} => format!(
+            "Failed to read moves from '{}': {}", path.display(), error),
+        Message::HintWin {row, col} => format!("Hint: play {} to win!", position(row, col)),
+        Message::HintBlock {row, col} => format!(
+            "Hint: play {} to stop your opponent from winning.", position(row, col)),
+        Message::NoHint => "Hint: there is nothing to win or block right now.".to_string(),
+        Message::MoveUndone {row, col} => format!("Took back the move at {}.", position(row, col)),
+        Message::NothingToUndo => "There are no moves to take back.".to_string(),
+        Message::Browsing {moves: 0, ..} => "Looking back at the board before the first move. \
+            Type 'forward' to go on, or 'undo' to take back every move.".to_string(),
+        Message::Browsing {moves, total} => format!(
+            "Looking back at the board after move {} of {}. Type 'forward' to go on, or 'undo' \
+            to take back every move after this one.", moves, total),
+        Message::BrowsingNoMoves => "You can't play on an earlier board. Type 'forward' until \
+            you are back at the game, or 'undo' to play from here.".to_string(),
+        Message::NoEarlierPosition => "This is the board before the first move.".to_string(),
+        Message::NotBrowsing => "This is the game as it is now. Type 'back' to look at an \
+            earlier board.".to_string(),
+        Message::SaveBeforeQuitting(path) => format!(
+            "Save the unfinished game to '{}' before quitting? (y/n): ", path.display()),
+        Message::SaveToFile => "Enter a file to save the unfinished game to, or press enter to \
+            quit without saving: ".to_string(),
+        Message::YesNoHelp => "Please answer y or n.".to_string(),
+        Message::GameSaved(path) => format!(
+            "Saved the unfinished game to '{}'.", path.display()),
+        Message::TutorialIntro {student, tutor} => format!(
+            "Welcome to the tutorial! You are playing {} and I am playing {}. Before each of \
+            your moves, I'll tell you what to play and why. Moves are written as {}.", student,
+            tutor, match notation::style() {
+                CoordinateStyle::Letter => "the row number followed by the column letter, like 2B",
+                CoordinateStyle::Numeric => "the row number and the column number separated by \
+                    a comma, like 2,2",
+                CoordinateStyle::Keypad => "the number of the tile on a phone's keypad, from 1 in \
+                    the top left corner to 9 in the bottom right, like 5",
+            }),
+        Message::TutorialWin {row, col} => format!(
+            "You have two in a row! Play {} to get three in a row and win.", position(row, col)),
+        Message::TutorialBlock {row, col, opponent} => format!(
+            "You must block at {} because {} has two in a row and will win there on their next \
+            turn otherwise.", position(row, col), opponent),
+        Message::TutorialFork {row, col} => format!(
+            "Play {}. It makes two threats at once, which is called a \"fork\".",
+            position(row, col)),
+        Message::TutorialLines {row, col, lines} => format!(
+            "Play {}. That tile is part of {} of the 8 ways to win, which gives you lots of \
+            options later.", position(row, col), lines),
+        Message::TutorialForkMade {opponent} => format!(
+            "You now have two ways to win! This is called a \"fork\". {} can only block one of \
+            them.", opponent),
+        Message::TutorialTutorMove {row, col} => format!("I played {}.", position(row, col)),
+        Message::TutorialWrongMove {row, col} => format!(
+            "For this lesson, please play {}.", position(row, col)),
+        Message::TutorialDone => "Well done, you finished the tutorial! Run the game without \
+            --tutorial to play against a friend.".to_string(),
+        Message::BlindfoldIntro {shows} => format!(
+            "Blindfold mode: the board is hidden, so keep track of the moves in your head. Type \
+            'show' to see the board (looks per game: {}).", shows),
+        Message::ShowsLeft(0) => "That was your last look at the board this game.".to_string(),
+        Message::ShowsLeft(1) => "You can look at the board 1 more time this game.".to_string(),
+        Message::ShowsLeft(shows) => format!(
+            "You can look at the board {} more times this game.", shows),
+        Message::NoShowsLeft => "You can't look at the board again until the next game."
+            .to_string(),
+        Message::Exported(path) => format!("Saved the board to '{}'.", path.display()),
+        Message::ExportFailed {path, error} => format!(
+            "Failed to save the board to '{}': {}", path.display(), error),
+        Message::TreeExported {path, positions: 1} => format!(
+            "Saved a game tree of 1 position to '{}'.", path.display()),
+        Message::TreeExported {path, positions} => format!(
+            "Saved a game tree of {} positions to '{}'.", positions, path.display()),
+        Message::TreeExportFailed {path, error} => format!(
+            "Failed to save the game tree to '{}': {}", path.display(), error),
+        Message::CastFailed {path, error} => format!(
+            "Failed to start recording to '{}': {}", path.display(), error),
+        Message::LogFailed {path, error} => format!(
+            "Failed to open the log file '{}': {}", path.display(), error),
+        Message::PrefsFailed {path, error} => format!(
+            "Failed to use the preferences in '{}': {}", path.display(), error),
+        Message::PrefsSaved(path) => format!("Saved your preferences to '{}'.", path.display()),
+        Message::NoPrefsDir => "Couldn't find a directory to save preferences in.".to_string(),
+        Message::DemoGame {number, games: Some(games), first_player} => format!(
+            "Demo game {} of {} ({} goes first)", number, games, first_player),
+        Message::DemoGame {number, games: None, first_player} => format!(
+            "Demo game {} ({} goes first)", number, first_player),
+        Message::DemoHelp => "Press Ctrl-C to stop the demo.".to_string(),
+        Message::ArchiveReadFailed {path, error} => format!(
+            "Failed to read the archive '{}': {}", path.display(), error),
+        Message::ArchiveWriteFailed {path, error} => format!(
+            "Failed to write the archive '{}': {}", path.display(), error),
+        Message::StatsHeader(path) => format!("Stats for the games in '{}':", path.display()),
+        Message::PlayerStats {name, stats} => format!(
+            "{}: played {}, won {}, lost {}, tied {}",
+            name, stats.games(), stats.wins, stats.losses, stats.ties),
+        Message::FavoriteOpening {row, col, games: 1} => format!(
+            "  Favorite opening: {} (1 game)", position(row, col)),
+        Message::FavoriteOpening {row, col, games} => format!(
+            "  Favorite opening: {} ({} games)", position(row, col), games),
+        Message::Streak {outcome, games, best_win_streak} => {
+            let outcome = match (outcome, games) {
+                (Outcome::Win, 1) => "win",
+                (Outcome::Win, _) => "wins",
+                (Outcome::Loss, 1) => "loss",
+                (Outcome::Loss, _) => "losses",
+                (Outcome::Tie, 1) => "tie",
+                (Outcome::Tie, _) => "ties",
+            };
+            format!("  Current streak: {} {} (longest winning streak: {})",
+                games, outcome, best_win_streak)
+        },
+        Message::Seed(seed) => format!(
+            "Random seed: {} (use --seed {} to play the same games again)", seed, seed),
+        Message::HeadToHead {a, b, path, games: 1} => format!(
+            "{} vs {} in '{}': 1 game", a, b, path.display()),
+        Message::HeadToHead {a, b, path, games} => format!(
+            "{} vs {} in '{}': {} games", a, b, path.display(), games),
+        Message::HeadToHeadRecord {a, b, piece, record} => format!(
+            "  {}: {} won {}, {} won {}, tied {}", match piece {
+                Some(piece) => format!("{} playing {}", a, piece_name(piece)),
+                None => "Overall".to_string(),
+            }, a, record.wins, b, record.losses, record.ties),
+        Message::HeadToHeadLength(moves) => format!("  Average game length: {:.1} moves", moves),
+        Message::NoHeadToHead {a, b, path} => format!(
+            "{} and {} haven't played each other in '{}' yet.", a, b, path.display()),
+        Message::OpeningsHeader(path) => format!(
+            "Openings in '{}', with the results for the player who went first:", path.display()),
+        Message::Opening {moves, results} if results.games() == 1 => format!(
+            "{}: 1 game, {}", describe_moves(moves), describe_percentages(results,
+            ["wins", "ties", "losses"], "%")),
+        Message::Opening {moves, results} => format!(
+            "{}: {} games, {}", describe_moves(moves), results.games(),
+            describe_percentages(results, ["wins", "ties", "losses"], "%")),
+        Message::NoOpenings(path) => format!(
+            "There are no games with moves in '{}' yet.", path.display()),
+        Message::NoStats(path) => format!(
+            "There are no games by named players in '{}' yet. Players can choose a name with \
+            --x-name and --o-name.", path.display()),
+        Message::RatingChange {player, rating, change} => format!(
+            "{}'s rating: {} ({:+})", player, rating, change),
+        Message::RatingsHeader(path) => format!("Ratings for the games in '{}':", path.display()),
+        Message::PlayerRating {rank, name, rating, games: 1} => format!(
+            "{}. {} - {} (1 game)", rank, name, rating),
+        Message::PlayerRating {rank, name, rating, games} => format!(
+            "{}. {} - {} ({} games)", rank, name, rating, games),
+        Message::NoRatings(path) => format!(
+            "There are no games between two named players in '{}' yet. Players can choose a name \
+            with --x-name and --o-name.", path.display()),
+        Message::TournamentGame {number, games, x, o} => format!(
+            "Tournament game {} of {}: {} (x) vs {} (o)", number, games, x, o),
+        Message::TournamentOver => "The tournament is over! Final standings:".to_string(),
+        Message::CrosstableEntrant => "Entrant".to_string(),
+        Message::CrosstableScore => "Score".to_string(),
+        Message::SwissRound {round, rounds} => format!("Round {} of {}", round, rounds),
+        Message::SwissBye(name) => format!(
+            "{} sits out this round and scores as if they had won both games.", name),
+        Message::SwissStandings(round) => format!("Standings after round {}:", round),
+        Message::StandingsBuchholz => "Buchholz".to_string(),
+        Message::ArenaStart {first, second, elo0, elo1, max_games} => format!(
+            "Playing {} against {} until it is clear whether the difference is {:+} or {:+} Elo \
+            (at most {} games)...", first, second, elo0, elo1, max_games),
+        Message::ArenaResults {games, wins, losses, ties} => format!(
+            "After {} games: wins {}, losses {}, ties {}", games, wins, losses, ties),
+        Message::ArenaElo {elo, low, high} => format!(
+            "Elo difference: {:+.1} (95% confidence: {:+.1} to {:+.1})", elo, low, high),
+        Message::ArenaAccepted {first, second, elo} => format!(
+            "{} is at least {:+} Elo stronger than {}.", first, elo, second),
+        Message::ArenaRejected {first, second, elo} => format!(
+            "{} is no more than {:+} Elo stronger than {}.", first, elo, second),
+        Message::ArenaUndecided(games) => format!(
+            "The test didn't reach a decision within {} games.", games),
+        Message::SessionSummary {games: 1, average_moves, duration} => format!(
+            "This session: 1 game of {:.0} moves in {}", average_moves,
+            duration),
+        Message::SessionSummary {games, average_moves, duration} => format!(
+            "This session: {} games, {:.1} moves per game on average, in {}", games,
+            average_moves, duration),
+        Message::SessionPlayer {player, wins, losses, ties} => format!(
+            "{}: wins {}, losses {}, ties {}", player, wins, losses, ties),
+        Message::SummaryCsvFailed {path, error} => format!(
+            "Failed to add the session summary to '{}': {}", path.display(), error),
+        Message::Perft {depth, leaves, time} => format!(
+            "perft {}: {} leaves in {:.3}s", depth, leaves, time.as_secs_f64()),
+        Message::Enumeration {counts, symmetry} => format!(
+            "{} positions{}: {} won by x, {} won by o, {} ties, {} still being played",
+            counts.total(), if symmetry { " up to symmetry" } else { "" }, counts.x_wins,
+            counts.o_wins, counts.ties, counts.ongoing),
+        Message::EnumerationVerified(1) => {
+            "The engine agrees about the 1 position still being played.".to_string()
+        },
+        Message::EnumerationVerified(positions) => format!(
+            "The engine agrees about all {} positions still being played.", positions),
+        Message::EnumerationMismatch(&[]) => {
+            "The engine disagrees about the starting position.".to_string()
+        },
+        Message::EnumerationMismatch(moves) => format!(
+            "The engine disagrees about the position after {}.", describe_moves(moves)),
+        Message::ProfileSearch {positions: 1, time} => format!(
+            "Searched 1 position in {:.3} ms", millis(time)),
+        Message::ProfileSearch {positions, time} => format!(
+            "Searched {} positions in {:.3} ms ({:.0} positions per second)", positions,
+            millis(time), per_second(positions, time)),
+        Message::ProfilePhase {phase, time, share} => format!("  {}: {:.3} ms ({:.1}%)",
+            match phase {
+                Some(Phase::Evaluation) => "evaluation",
+                Some(Phase::TableProbe) => "table probes",
+                Some(Phase::MoveGeneration) => "move generation",
+                Some(Phase::MakeMove) => "making moves",
+                None => "everything else",
+            }, millis(time), share),
+        Message::ProfileSaved(path) => format!(
+            "Saved the folded stacks to '{}'. Make a flame graph of them with \
+            `inferno-flamegraph {} > profile.svg`.", path.display(), path.display()),
+        Message::ProfileFailed {path, error} => format!(
+            "Failed to save the folded stacks to '{}': {}", path.display(), error),
+        Message::SimulationStart {games: 1, x, o} => format!(
+            "Simulating 1 game: {} (x) against {} (o)", x, o),
+        Message::SimulationStart {games, x, o} => format!(
+            "Simulating {} games: {} (x) against {} (o)", games, x, o),
+        Message::SimulationResults(simulation) => format!(
+            "After {} games: x won {}, o won {}, ties {}", simulation.games(),
+            describe_share(simulation.x_wins, simulation.games(), "%"),
+            describe_share(simulation.o_wins, simulation.games(), "%"),
+            describe_share(simulation.ties, simulation.games(), "%")),
+        Message::SimulationLengths(simulation) => format!(
+            "Game lengths: {}", describe_lengths(simulation, "moves", "%")),
+        Message::SimulationTime(simulation) => match simulation.games_per_second() {
+            Some(speed) => format!("Time: {:.3}s, {:.0} games per second",
+                simulation.time.as_secs_f64(), speed),
+            None => "Time: too short to measure".to_string(),
+        },
+        Message::VerifyProblem {line, problem} => format!("Line {}: {}", line, match *problem {
+            Problem::UnknownRecord => "not a game, match or end".to_string(),
+            Problem::MissingField(field) => format!("the '{}' field is missing", field),
+            Problem::InvalidField {field, ref value} => format!(
+                "'{}' isn't a valid value for '{}'", value, field),
+            Problem::InvalidMove {number, ref notation} => format!(
+                "move {} ('{}') isn't a tile on the board", number, notation),
+            Problem::IllegalMove {number, ref notation} => format!(
+                "move {} ('{}') can't be played there", number, notation),
+            Problem::WrongWinner {saved, actual} => format!(
+                "the saved result is {}, but the moves give {}",
+                describe_result(saved, ENGLISH_RESULTS), describe_result(actual, ENGLISH_RESULTS)),
+            Problem::WrongDigest {saved, actual} => format!(
+                "the moves were changed after the game was saved (the saved digest is {:016x}, \
+                but the moves give {:016x})", saved, actual),
+            Problem::InvalidForfeit(piece) => format!(
+                "{} couldn't have forfeited the game", piece_name(piece)),
+            Problem::InvalidAdjudication(Adjudication::DeadPosition) => {
+                "the game wasn't a tie that nobody could win anymore".to_string()
+            },
+            Problem::InvalidAdjudication(Adjudication::Solved) => {
+                "the solver doesn't give the saved result".to_string()
+            },
+            Problem::WrongMatchTotals => {
+                "the match totals don't match the games in it".to_string()
+            },
+            Problem::UnclosedMatch => "the match has no end".to_string(),
+            Problem::UnexpectedEnd => "an end without a match".to_string(),
+        }),
+        Message::VerifyPassed {path, games: 1} => format!(
+            "The 1 game in '{}' checks out.", path.display()),
+        Message::VerifyPassed {path, games} => format!(
+            "All {} games in '{}' check out.", games, path.display()),
+        Message::VerifyFailed {path, problems: 1} => format!(
+            "Found 1 problem in '{}'.", path.display()),
+        Message::VerifyFailed {path, problems} => format!(
+            "Found {} problems in '{}'.", problems, path.display()),
+        Message::AuditFinding {line, finding} => format!("Line {}: {}", line, match *finding {
+            Finding::WrongWinner {saved, actual} => format!(
+                "the saved result is {}, but the moves give {}",
+                describe_result(saved, ENGLISH_RESULTS), describe_result(actual, ENGLISH_RESULTS)),
+            Finding::NotOptimal {number, row, col, ref engine, ref best} => format!(
+                "move {} ({}) by the '{}' engine wasn't one of the best moves: {}",
+                number, position(row, col), engine, describe_moves(best)),
+        }),
+        Message::AuditPassed {path, games, moves} => format!(
+            "Played {} {} in '{}' again and checked {} engine {}: everything agrees.",
+            games, if games == 1 { "game" } else { "games" }, path.display(),
+            moves, if moves == 1 { "move" } else { "moves" }),
+        Message::AuditFailed {path, findings: 1} => format!(
+            "Found 1 disagreement in '{}'.", path.display()),
+        Message::AuditFailed {path, findings} => format!(
+            "Found {} disagreements in '{}'.", findings, path.display()),
+        Message::ChatHelp {mention} => format!("Commands:\n\
+            !ttt play - play against the computer\n\
+            !ttt play {} - play against someone else\n\
+            !ttt resign - give up the game you are playing\n\
+            !ttt pause - pause the game you are playing\n\
+            !ttt resume - go on with a paused game\n\
+            !ttt rematch - play the last game again with the pieces swapped\n\
+            !ttt board - show the board again\n\
+            !ttt ratings - show everyone's rating\n\
+            !ttt coach low|medium|high|off - comment on the moves in this channel\n\
+            During a game, type a tile like {} to move there.",
+            if mention { "@someone" } else { "someone" }, position(1, 1)),
+        Message::ChatVersus {x, o} => format!("{} vs {}", x, o),
+        Message::ChatComputer => "Computer".to_string(),
+        Message::ChatTurn(player) => format!("It's {}'s turn.", player),
+        Message::ChatResigned(player) => format!("{} resigned.", player),
+        Message::ChatGameInProgress => "There is already a game in this channel.".to_string(),
+        Message::ChatNoGame => {
+            "There is no game in this channel. Start one with !ttt play.".to_string()
+        },
+        Message::ChatNotPlaying => "You aren't playing in this game.".to_string(),
+        Message::ChatPlayYourself => "You can't play against yourself.".to_string(),
+        Message::ChatRatingsHeader => "Ratings:".to_string(),
+        Message::ChatNoRatings => {
+            "Nobody has a rating yet. Ratings come from games between two people.".to_string()
+        },
+        Message::PuzzleIntro => "Each puzzle is a position where you have to find the best \
+            moves. I play the other side as well as I can. Type 'hint' if you are stuck: each \
+            hint gives away a little more.".to_string(),
+        Message::PuzzleHeader {number, total, player, goal: Goal::Win, moves: 1} => format!(
+            "Puzzle {} of {}: {} to play and win right away.", number, total, player),
+        Message::PuzzleHeader {number, total, player, goal: Goal::Win, moves} => format!(
+            "Puzzle {} of {}: {} to play and win within {} moves.", number, total, player, moves),
+        Message::PuzzleHeader {number, total, player, goal: Goal::Draw, moves: 1} => format!(
+            "Puzzle {} of {}: {} to play. Find the only moves that don't lose.", number, total,
+            player),
+        Message::PuzzleHeader {number, total, player, goal: Goal::Draw, moves} => format!(
+            "Puzzle {} of {}: {} to play. Don't give away a win for the next {} moves.", number,
+            total, player, moves),
+        Message::PuzzleWrongMove {row, col, goal: Goal::Win} => format!(
+            "{} doesn't force a win in time. Try again.", position(row, col)),
+        Message::PuzzleWrongMove {row, col, goal: Goal::Draw} => format!(
+            "{} loses against the best defense. Try again.", position(row, col)),
+        Message::PuzzleReply {row, col} => format!("I answered {}.", position(row, col)),
+        Message::PuzzleHint(Hint::Idea(idea)) => match idea {
+            Idea::Win => "You can win right away.",
+            Idea::Block => "Your opponent is about to win. Start there.",
+            Idea::Fork => "Look for a move that makes two threats at once.",
+            Idea::Threat => "Look for a move that makes a threat your opponent has to block.",
+            Idea::Quiet => "The right move doesn't make a threat yet. Think about what your \
+                opponent wants to do.",
+        }.to_string(),
+        Message::PuzzleHint(Hint::Row(row)) => format!("The move is in row {}.", row + 1),
+        Message::PuzzleHint(Hint::Move(row, col)) => format!("Play {}.", position(row, col)),
+        Message::PuzzleSolved {hints: 0} => "Solved!".to_string(),
+        Message::PuzzleSolved {hints: 1} => "Solved, with 1 hint.".to_string(),
+        Message::PuzzleSolved {hints} => format!("Solved, with {} hints.", hints),
+        Message::PuzzlesDone(0) => "There were no puzzles to solve.".to_string(),
+        Message::PuzzlesDone(1) => "You solved the puzzle!".to_string(),
+        Message::PuzzlesDone(total) => format!("You solved all {} puzzles!", total),
+        Message::PuzzleReadFailed {path, error} => format!(
+            "Failed to read the puzzles in '{}': {}", path.display(), error),
+        Message::PuzzleProblem {path, line, problem} => format!(
+            "Line {} of '{}' isn't a puzzle that can be played: {}", line, path.display(),
+            match *problem {
+                puzzle::Problem::UnknownRecord => "it isn't a puzzle".to_string(),
+                puzzle::Problem::MissingField(field) => {
+                    format!("the '{}' field is missing", field)
+                },
+                puzzle::Problem::InvalidField {field, ref value} => format!(
+                    "'{}' isn't a valid value for '{}'", value, field),
+                puzzle::Problem::IllegalMove {number, ref notation} => format!(
+                    "move {} ('{}') can't be played there", number, notation),
+                puzzle::Problem::NoSolution => "the goal can't be reached in time".to_string(),
+            }),
+        Message::DailyHeader {date, player, goal: Goal::Win, moves: 1} => format!(
+            "Puzzle of {}: {} to play and win right away.", date, player),
+        Message::DailyHeader {date, player, goal: Goal::Win, moves} => format!(
+            "Puzzle of {}: {} to play and win within {} moves.", date, player, moves),
+        Message::DailyHeader {date, player, goal: Goal::Draw, moves: 1} => format!(
+            "Puzzle of {}: {} to play. Find the only moves that don't lose.", date, player),
+        Message::DailyHeader {date, player, goal: Goal::Draw, moves} => format!(
+            "Puzzle of {}: {} to play. Don't give away a win for the next {} moves.", date,
+            player, moves),
+        Message::DailyStreak {current: 1, best} => format!(
+            "You solved the puzzle of the day. Come back tomorrow to start a streak! (Best \
+            streak: {})", best),
+        Message::DailyStreak {current, best} => format!(
+            "You solved the puzzle of the day {} days in a row! (Best streak: {})", current, best),
+        Message::DailyShare => "Share your result:".to_string(),
+        Message::SetupIntro => format!("Set up a position, then play it or analyze it. Type a \
+            tile like {} to change what is on it (empty, x, o, then empty again), 'next x' or \
+            'next o' to choose who moves next, 'clear' to empty the board, 'analyze' to find the \
+            best moves and 'play' to start a game.", position(0, 0)),
+        Message::SetupStatus(player) => format!("Setting up a position | {} moves next", player),
+        Message::EnterEdit => "Enter a tile or a command: ".to_string(),
+        Message::InvalidEdit(input) => format!(
+            "'{}' isn't a tile or a command. Please try again.", input),
+        Message::PositionInvalid(PositionError::PieceCounts {x, o, next}) => format!(
+            "With {} x and {} o on the board, it can't be {}'s turn. The player who moves next \
+            must have as many pieces as the other player, or one fewer.", x, o, piece_name(next)),
+        Message::PositionInvalid(PositionError::Unreachable) => "No game can reach this \
+            position: the game is over as soon as a player has three in a row.".to_string(),
+        Message::PositionOver => "The game is already over in this position.".to_string(),
+        Message::PositionAnalysis {winner: Some(winner), moves} => format!(
+            "With perfect play, {} wins. Best moves: {}", winner, describe_moves(moves)),
+        Message::PositionAnalysis {winner: None, moves} => format!(
+            "With perfect play, the game is a tie. Best moves: {}", describe_moves(moves)),
+        Message::ReviewIntro => "Let's go through the game. Each move is shown on the board it \
+            was played on. Press enter for the next move, type 'back' for the one before, or \
+            'quit' to stop.".to_string(),
+        Message::ReviewMove {player, row, col, mark: Mark::Best, ..} => format!(
+            "{} played {}: one of the best moves.", player, position(row, col)),
+        Message::ReviewMove {player, row, col, mark: Mark::Ok, better} => format!(
+            "{} played {}: ok, but {} was better.", player, position(row, col),
+            describe_moves(better)),
+        Message::ReviewMove {player, row, col, mark: Mark::Mistake, better} => format!(
+            "{} played {}: a mistake. Better: {}", player, position(row, col),
+            describe_moves(better)),
+        Message::ReviewPrompt => "Next, back or quit? ".to_string(),
+        Message::ReviewHelp => "Please enter one of: next, back, or quit.".to_string(),
+        Message::ReviewSummary {player, counts: [best, ok, mistakes]} => format!(
+            "{}: {} best, {} ok, {} {}", player, best, ok, mistakes,
+            if mistakes == 1 { "mistake" } else { "mistakes" }),
+        Message::ThinkTime(time) => format!("Thought for {:.1}s.", time.as_secs_f64()),
+        Message::ReviewThinking {player, total, moves} => format!(
+            "{} thought for {:.1}s in all, {:.1}s a move on average.", player,
+            total.as_secs_f64(), total.as_secs_f64() / moves as f64),
+        Message::Paused => "The game is paused. The board is hidden and the clocks are stopped."
+            .to_string(),
+        Message::EnterResume => "Press enter to go on: ".to_string(),
+        Message::Resumed(length) => format!("The game goes on after a pause of {}.",
+            ClockTime(length)),
+        Message::ChatPaused(player) => format!(
+            "{} paused the game. Type !ttt resume to go on.", player),
+        Message::ChatStillPaused => "The game is paused. Type !ttt resume to go on.".to_string(),
+        Message::ChatNotPaused => "The game isn't paused.".to_string(),
+        Message::ForcedWin {player, moves: 0} => format!("{} wins whatever happens now.", player),
+        Message::ForcedWin {player, moves: 1} => format!(
+            "{} has a forced win: their next move wins.", player),
+        Message::ForcedWin {player, moves} => format!(
+            "{} has a forced win in {} moves.", player, moves),
+        Message::ForcedDraw => {
+            "The game is a forced draw now: neither player can win if the other one plays well."
+                .to_string()
+        },
+        Message::Coach {comment, player, opponent} => match comment {
+            Comment::MissedWin(row, col) => format!(
+                "Coach: {} could have won at {}.", player, position(row, col)),
+            Comment::LeftWin(row, col) => format!(
+                "Coach: {} left {} open. {} can win there.", player, position(row, col), opponent),
+            Comment::LeftFork(row, col) => format!(
+                "Coach: {} left {} open. {} can fork there.", player, position(row, col), opponent),
+            Comment::Block => format!("Coach: good block by {}.", player),
+            Comment::Threat(row, col) => format!(
+                "Coach: {} threatens to win at {}.", player, position(row, col)),
+            Comment::Fork => format!(
+                "Coach: a fork! {} has two ways to win and {} can only block one.", player,
+                opponent),
+        },
+        Message::ChatCoach(Some(level)) => {
+            format!("The coach is on in this channel ({}).", level.name())
+        },
+        Message::ChatCoach(None) => "The coach is off in this channel.".to_string(),
+        Message::ChatCoachHelp => "Type !ttt coach low, medium, high or off.".to_string(),
+        Message::SpeedrunIntro {wins, difficulty} => format!(
+            "Speedrun: beat the {} computer {} times as fast as you can. Games you don't win \
+            don't count, but their time does.", difficulty.name(), wins),
+        Message::SpeedrunGame {number, wins, needed} => format!(
+            "Game {} ({} of {} wins)", number, wins, needed),
+        Message::SpeedrunTime(time) => format!("Time so far: {}", RunTime(time)),
+        Message::SpeedrunDone {time, games} => format!(
+            "You beat the gauntlet in {} ({} games)!", RunTime(time), games),
+        Message::SpeedrunFinish(Finish::NewBest {previous: None}) => {
+            "That's your first time on the leaderboard.".to_string()
+        },
+        Message::SpeedrunFinish(Finish::NewBest {previous: Some(previous)}) => format!(
+            "That's a new best time! Your old best was {}.", RunTime(previous)),
+        Message::SpeedrunFinish(Finish::Slower {best}) => format!(
+            "Your best time is still {}.", RunTime(best)),
+        Message::SpeedrunNoName => {
+            "Choose a name with --x-name to keep your best times.".to_string()
+        },
+        Message::SpeedrunLeaderboard(difficulty) => format!(
+            "Best times against the {} computer:", difficulty.name()),
+        Message::SpeedrunEntry {rank, name, time} => format!(
+            "{}. {} - {}", rank, name, RunTime(time)),
+        Message::Adjudicated(Adjudication::DeadPosition) => {
+            "Nobody can get three in a row anymore, so the game was ended early.".to_string()
+        },
+        Message::Adjudicated(Adjudication::Solved) => {
+            "The result was certain with perfect play, so the game was ended early.".to_string()
+        },
+        Message::SimulationAdjudicated(simulation) => format!(
+            "Adjudicated: {} of the games ({})", simulation.adjudicated,
+            describe_share(simulation.adjudicated, simulation.games(), "%")),
+        Message::ClubIntro => "Welcome to the club! Type 'new' at any move prompt to start \
+            another game, 'games' to see every game and 'switch N' to go to game N.".to_string(),
+        Message::ClubTable {id, focused, x, o, moves, current} => format!(
+            "{} Game {}: {} against {}, {} {}, {} to play", if focused { ">" } else { " " }, id, x,
+            o, moves, if moves == 1 { "move" } else { "moves" }, current),
+        Message::ClubGameOver(id) => format!("Game {} is over. The other games:", id),
+        Message::ClubSwitched(id) => format!("Now playing game {}.", id),
+        Message::ClubNoTable(id) => format!("There is no game {}. Type 'games' to see them.", id),
+        Message::ClubOnly => {
+            "Only one game is being played. Start the program with 'club' to play several at \
+            once.".to_string()
+        },
+        Message::ClubPlayerName(piece) => format!(
+            "Name of the player with {} (leave blank for none): ", piece_name(piece)),
+        Message::ClubSaving(id) => format!("Game {}:", id),
+        Message::SimulIntro {giver, boards} => format!(
+            "{} is playing {} boards at once. After each of their moves, the next board comes up. \
+            Type 'games' to see every board.", giver, boards),
+        Message::SimulBoard {id, opponent, focused} => format!(
+            "{} Board {}: {}", if focused { ">" } else { " " }, id, opponent),
+        Message::SimulNextBoard(id) => format!("On to board {}.", id),
+        Message::SimulFixed => {
+            "In a simul, the boards come up in turn and no others can be started.".to_string()
+        },
+        Message::SimulResult {giver, wins, ties, losses} => format!(
+            "The simul is over. {} won {}, tied {} and lost {}.", giver, wins, ties, losses),
+        Message::ExploreIntro => "Let's explore the game. Every move that can be played is \
+            listed with how the game ends if both players play perfectly after it, and how it went \
+            in the archive. Moves that are the same after turning or flipping the board are listed \
+            together.".to_string(),
+        Message::ExploreMoves(player) => format!("Moves for {}:", player),
+        Message::ExploreCandidate {tiles, verdict, results} => {
+            let verdict = match verdict {
+                Verdict::Win => "wins with perfect play",
+                Verdict::Draw => "draws with perfect play",
+                Verdict::Loss => "loses with perfect play",
+            };
+            let played = match results {
+                None => String::new(),
+                Some(results) if results.games() == 0 => {
+                    "; never played in the archive".to_string()
+                },
+                Some(results) => format!("; {} {}, {}", results.games(),
+                    if results.games() == 1 { "game" } else { "games" },
+                    describe_percentages(&results, ["wins", "ties", "losses"], "%")),
+            };
+            format!("  {}: {}{}", describe_alternatives(tiles), verdict, played)
+        },
+        Message::ExplorePrompt => "Move, back or quit? ".to_string(),
+        Message::ExploreHelp => format!(
+            "Please enter one of: a move like {}, back, or quit.", position(0, 0)),
+        Message::WatchHelp(path) => format!(
+            "Watching '{}' for new moves. Press Ctrl-C to stop.", path.display()),
+        Message::WatchWaiting(path) => format!(
+            "Waiting for '{}' to be created...", path.display()),
+        Message::GameCode(code) => format!("Game code: {}", code),
+        Message::GameLink(link) => format!("Link: {}", link),
+        Message::CodeLoaded(1) => "Loaded a game with 1 move.".to_string(),
+        Message::CodeLoaded(moves) => format!("Loaded a game with {} moves.", moves),
+        Message::QrExported(path) => format!("Saved the QR code to '{}'.", path.display()),
+        Message::QrExportFailed {path, error} => format!(
+            "Failed to save the QR code to '{}': {}", path.display(), error),
+        Message::ImagesUnavailable => {
+            "This copy of the game was built without PNG images, GIF animations and QR codes. \
+            Build it with `--features images` to use them.".to_string()
+        },
+        Message::DiffInvalidGame(source) => format!(
+            "'{}' is neither a file of moves nor a game code.", source),
+        Message::DiffSame {moves, exact} => format!("Both games have the same {} moves{}.", moves,
+            if exact { "" } else { ", on a turned or flipped board" }),
+        Message::DiffDiverged {number, first, second, exact} => format!(
+            "The games diverge at move {}: the first game played {} and the second played {}.{}",
+            number, position(first.0, first.1), position(second.0, second.1),
+            if exact || number == 1 {
+                ""
+            }
+            else {
+                " The moves before it are the same on a turned or flipped board."
+            }),
+        Message::DiffLonger {common, first_longer} => format!(
+            "The {} game goes on after the {} moves that both games have.",
+            if first_longer { "first" } else { "second" }, common),
+        Message::DiffResults {results, lengths} => format!(
+            "Results: {} after {} moves and {} after {} moves{}.",
+            describe_result(results.0, ENGLISH_RESULTS), lengths.0,
+            describe_result(results.1, ENGLISH_RESULTS), lengths.1,
+            if results.0 == results.1 { ", the same result" } else { "" }),
+        Message::CheckDuplicate {line, first} => format!(
+            "Line {}: the same game as line {} (up to turning or flipping the board)", line, first),
+        Message::CheckUnknownField {line, field} => format!(
+            "Line {}: '{}' isn't a field that this version knows about", line, field),
+        Message::CheckRepaired {line, repair} => format!("Line {}: {}", line, match repair {
+            Repair::MatchTotals => "counted the match totals again",
+            Repair::ClosedMatch => "added the end of the match",
+            Repair::RemovedEnd => "took out the end without a match",
+        }),
+        Message::CheckFixed {backup, repairs: 1} => format!(
+            "Fixed 1 problem. The archive as it was is saved in '{}'.", backup.display()),
+        Message::CheckFixed {backup, repairs} => format!(
+            "Fixed {} problems. The archive as it was is saved in '{}'.", repairs,
+            backup.display()),
+        Message::TrainingExported {path, samples, games: 1} => format!(
+            "Saved {} training samples from 1 game to '{}'.", samples, path.display()),
+        Message::TrainingExported {path, samples, games} => format!(
+            "Saved {} training samples from {} games to '{}'.", samples, games, path.display()),
+        Message::TrainingExportFailed {path, error} => format!(
+            "Failed to save the training data to '{}': {}", path.display(), error),
+        Message::ConfirmQuit => "The input ended. Quit? (y/n): ".to_string(),
+        Message::InputClosed => "The input ended before the session was over.".to_string(),
+        Message::PluginSkipped {path, error} => format!(
+            "Skipped the plugin '{}': {}", path.display(), error),
+        Message::PluginMissing(PluginError::NoRenderer(name)) => format!(
+            "There is no renderer plugin named '{}'.", name),
+        Message::PluginMissing(PluginError::NoPlayer(name)) => format!(
+            "There is no computer player plugin named '{}'.", name),
+        Message::PluginMissing(error) => error.to_string(),
+        Message::PluginMoveFailed {name, error} => format!(
+            "The plugin {} can't play that move ({}), so it forfeits the game.", name, error),
+        Message::PluginsUnavailable => {
+            "This copy of the game was built without plugins. Build it with \
+            `--features plugins` to use them.".to_string()
+        },
+        Message::RulesNotArchived(rules) => format!(
+            "Games played by the {} rules aren't saved to the archive.", rules.name()),
+        Message::FileRecovered {path, recovery} if recovery.restored => format!(
+            "'{}' was corrupt, so it was moved to '{}' and its backup was used instead.",
+            path.display(), recovery.quarantined.display()),
+        Message::FileRecovered {path, recovery} => format!(
+            "'{}' was corrupt and had no backup, so it was moved to '{}' and starts over empty.",
+            path.display(), recovery.quarantined.display()),
+        Message::IdleMoved {player, row, col} => format!(
+            "{} didn't move in time, so the computer played {} for them.", player,
+            position(row, col)),
+        Message::IdleForfeit(player) => format!(
+            "{} didn't move in time and loses the game.", player),
+        Message::TaggedGame {number, game, moves} => format!("Game {}: {}{}{}", number,
+            describe_moves(&game.moves().to_vec()), describe_tags(game),
+            describe_tagged_moves(moves, "move", "moves")),
+        Message::TaggedFound {query, found: 1, games} => format!(
+            "1 of {} games is tagged {}.", games, query),
+        Message::TaggedFound {query, found, games} => format!(
+            "{} of {} games are tagged {}.", found, games, query),
+        Message::IrcUnavailable => {
+            "This copy of the game was built without the IRC bot. Build it with \
+            `--features irc` to use it.".to_string()
+        },
+        Message::IrcConnected {server, nick} => format!("Connected to {} as {}.", server, nick),
+        Message::IrcDisconnected(error) => format!(
+            "Lost the connection to the IRC server ({}). Reconnecting...", error),
+        Message::IrcNickInUse(nick) => format!(
+            "Someone else is already using the nickname {}. Choose another one with --nick.",
+            nick),
+        Message::DiscordUnavailable => {
+            "This copy of the game was built without the Discord bot. Build it with \
+            `--features discord` to use it.".to_string()
+        },
+        Message::DiscordNoToken => {
+            "Set DISCORD_TOKEN to the token of the bot to connect to Discord.".to_string()
+        },
+        Message::DiscordConnected(name) => format!("Connected to Discord as {}.", name),
+        Message::DiscordDisconnected(error) => format!(
+            "Lost the connection to Discord ({}). Reconnecting...", error),
+        Message::DiscordRejected(error) => format!("Discord refused the connection: {}", error),
+    }
+}
+
+fn french(message: Message) -> String {
+    match message {
+        Message::EnterMove => format!("Entrez un coup (ex. {}) : ", position(0, 0)),
+        Message::CurrentPiece(player) => format!("Pièce actuelle : {}", player),
+        Message::InvalidMove {input, reason} => match *reason {
+            Reason::Unreadable => format!("Coup invalide : '{}'. Veuillez réessayer.", input),
+            Reason::NoSuchRow(ref row) => format!(
+                "Coup invalide : '{}'. La ligne {} n'existe pas sur un plateau de 3 lignes.",
+                input, row),
+            Reason::NoSuchColumn(ref col) => format!(
+                "Coup invalide : '{}'. La colonne {} n'existe pas sur un plateau de 3 colonnes.",
+                input, col),
+            Reason::NoSuchKey(ref key) => format!(
+                "Coup invalide : '{}'. La touche {} n'existe pas : les touches vont de 1 à 9.",
+                input, key),
+        },
+        Message::TileNotEmpty {row, col, other_player, since} => format!(
+            "La case {} contient déjà la pièce {} depuis le coup {} !",
+            position(row, col), other_player, since),
+        Message::GameWon(player) => format!("{} gagne !", player),
+        Message::GameTied => "Égalité !".to_string(),
+        Message::GameHeader {number, best_of, first_player} => format!(
+            "Partie {} sur {} ({} commence)", number, best_of, first_player),
+        Message::SeriesScore {x, o, score} => format!(
+            "Score : {} {} - {} {} (égalités : {})", x, score.x, o, score.o, score.ties),
+        Message::SeriesOver {winner: Winner::X, x, score, ..} => format!(
+            "{} remporte le match {}-{} !", x, score.x, score.o),
+        Message::SeriesOver {winner: Winner::O, o, score, ..} => format!(
+            "{} remporte le match {}-{} !", o, score.o, score.x),
+        Message::SeriesOver {winner: Winner::Tie, score, ..} => format!(
+            "Le match se termine sur une égalité {}-{} !", score.x, score.o),
+        Message::ArchiveFailed {path, error} => format!(
+            "Impossible d'enregistrer dans l'archive '{}' : {}", path.display(), error),
+        Message::PlayAgain => {
+            "Rejouer ? (rejouer / échanger les pièces / revanche / changer les réglages / \
+            analyser la partie / partager la partie / quitter) : ".to_string()
+        },
+        Message::PlayAgainHelp => {
+            "Veuillez entrer : rejouer, échanger, revanche, changer, analyser, partager ou quitter."
+                .to_string()
+        },
+        Message::NumberOfGames(current) => format!(
+            "Nombre de parties (1 pour une seule partie) [{}] : ", current),
+        Message::NumberOfGamesHelp => "Veuillez entrer un nombre supérieur à zéro.".to_string(),
+        Message::FirstPiece(current) => format!(
+            "Qui commence ? (x/o) [{}] : ", piece_name(current)),
+        Message::FirstPieceHelp => "Veuillez entrer x ou o.".to_string(),
+        Message::Options(OptionsError::UnknownArgument(arg)) => format!(
+            "Argument inconnu : '{}'", arg),
+        Message::Options(OptionsError::MissingValue(option)) => format!(
+            "Valeur manquante pour '{}'", option),
+        Message::Options(OptionsError::InvalidValue {option, value}) => format!(
+            "Valeur invalide pour '{}' : '{}'", option, value),
+        Message::Options(OptionsError::Requires {option, required}) => format!(
+            "'{}' ne peut être utilisé qu'avec '{}'", option, required),
+        Message::RowDescription {row, tiles} => format!(
+            "Ligne {} : {}.", row + 1, describe_tiles(tiles, "vide")),
+        Message::MovePlayed {player, row, col} => format!(
+            "{} a joué {}.", player, position(row, col)),
+        Message::Threat {player, row, col} => format!(
+            "{} menace de gagner en {}.", player, position(row, col)),
+        Message::Status {move_number, x, o, variant, clocks} => {
+            let variant = match variant {
+                Variant::Classic => "Classique",
+                Variant::Blindfold => "À l'aveugle",
+                Variant::Tutorial => "Tutoriel",
+                Variant::Demo => "Démonstration",
+                Variant::Puzzle => "Problème",
+                Variant::Review => "Analyse",
+            };
+            let status = format!("Coup {} | {} contre {} | {}", move_number, x, o, variant);
+            match clocks {
+                Some((x_time, o_time)) => format!(
+                    "{} | Pendules : {} {} - {} {}", status, x, x_time, o, o_time),
+                None => status,
+            }
+        },
+        Message::FlagFell(player) => format!("{} n'a plus de temps !", player),
+        Message::GameUnfinished => "La partie n'est pas encore terminée.".to_string(),
+        Message::ScriptInvalidMove {number, notation} => format!(
+            "Le coup {} ('{}') n'est pas un coup valide.", number, notation),
+        Message::ScriptMoveRejected {number, message} => format!(
+            "Coup {} : {}", number, message),
+        Message::ScriptGameOver {number, notation} => format!(
+            "Le coup {} ('{}') est illégal : la partie est déjà terminée.", number, notation),
+        Message::MovesFileFailed {path, error} => format!(
+            "Impossible de lire les coups depuis '{}' : {}", path.display(), error),
+        Message::HintWin {row, col} => format!(
+            "Indice : jouez {} pour gagner !", position(row, col)),
+        Message::HintBlock {row, col} => format!(
+            "Indice : jouez {} pour empêcher votre adversaire de gagner.", position(row, col)),
+        Message::NoHint => "Indice : il n'y a rien à gagner ni à bloquer pour le moment."
+            .to_string(),
+        Message::MoveUndone {row, col} => format!(
+            "Le coup en {} a été annulé.", position(row, col)),
+        Message::NothingToUndo => "Il n'y a aucun coup à annuler.".to_string(),
+        Message::Browsing {moves: 0, ..} => "Vous regardez le plateau avant le premier coup. \
+            Tapez 'avancer' pour continuer, ou 'annuler' pour annuler tous les coups.".to_string(),
+        Message::Browsing {moves, total} => format!(
+            "Vous regardez le plateau après le coup {} sur {}. Tapez 'avancer' pour continuer, \
+            ou 'annuler' pour annuler tous les coups qui suivent.", moves, total),
+        Message::BrowsingNoMoves => "Vous ne pouvez pas jouer sur un plateau précédent. Tapez \
+            'avancer' jusqu'à revenir à la partie, ou 'annuler' pour jouer d'ici.".to_string(),
+        Message::NoEarlierPosition => "C'est le plateau avant le premier coup.".to_string(),
+        Message::NotBrowsing => "C'est la partie telle qu'elle est. Tapez 'reculer' pour \
+            regarder un plateau précédent.".to_string(),
+        Message::SaveBeforeQuitting(path) => format!(
+            "Enregistrer la partie inachevée dans '{}' avant de quitter ? (o/n) : ",
+            path.display()),
+        Message::SaveToFile => "Entrez un fichier où enregistrer la partie inachevée, ou \
+            appuyez sur Entrée pour quitter sans enregistrer : ".to_string(),
+        Message::YesNoHelp => "Veuillez répondre o ou n.".to_string(),
+        Message::GameSaved(path) => format!(
+            "Partie inachevée enregistrée dans '{}'.", path.display()),
+        Message::TutorialIntro {student, tutor} => format!(
+            "Bienvenue dans le tutoriel ! Vous jouez {} et je joue {}. Avant chacun de vos \
+            coups, je vous dirai quoi jouer et pourquoi. Les coups s'écrivent avec {}.", student,
+            tutor, match notation::style() {
+                CoordinateStyle::Letter => "le numéro de la ligne suivi de la lettre de la \
+                    colonne, comme 2B",
+                CoordinateStyle::Numeric => "le numéro de la ligne et le numéro de la colonne \
+                    séparés par une virgule, comme 2,2",
+                CoordinateStyle::Keypad => "le numéro de la case sur le clavier d'un téléphone, \
+                    de 1 dans le coin en haut à gauche à 9 dans le coin en bas à droite, comme 5",
+            }),
+        Message::TutorialWin {row, col} => format!(
+            "Vous avez deux pièces alignées ! Jouez {} pour en aligner trois et gagner.",
+            position(row, col)),
+        Message::TutorialBlock {row, col, opponent} => format!(
+            "Vous devez bloquer en {} car {} a deux pièces alignées et gagnera là au prochain \
+            tour sinon.", position(row, col), opponent),
+        Message::TutorialFork {row, col} => format!(
+            "Jouez {}. Ce coup crée deux menaces à la fois, ce qu'on appelle une \"fourchette\".",
+            position(row, col)),
+        Message::TutorialLines {row, col, lines} => format!(
+            "Jouez {}. Cette case fait partie de {} des 8 façons de gagner, ce qui vous laisse \
+            beaucoup de possibilités pour la suite.", position(row, col), lines),
+        Message::TutorialForkMade {opponent} => format!(
+            "Vous avez maintenant deux façons de gagner ! C'est ce qu'on appelle une \
+            \"fourchette\". {} ne peut en bloquer qu'une.", opponent),
+        Message::TutorialTutorMove {row, col} => format!("J'ai joué {}.", position(row, col)),
+        Message::TutorialWrongMove {row, col} => format!(
+            "Pour cette leçon, veuillez jouer {}.", position(row, col)),
+        Message::TutorialDone => "Bravo, vous avez terminé le tutoriel ! Lancez le jeu sans \
+            --tutorial pour jouer contre un ami.".to_string(),
+        Message::BlindfoldIntro {shows} => format!(
+            "Mode à l'aveugle : le plateau est caché, retenez donc les coups de tête. Tapez \
+            'montrer' pour voir le plateau (regards par partie : {}).", shows),
+        Message::ShowsLeft(0) => "C'était votre dernier regard sur le plateau pour cette \
+            partie.".to_string(),
+        Message::ShowsLeft(shows) => format!(
+            "Vous pouvez encore regarder le plateau {} fois pendant cette partie.", shows),
+        Message::NoShowsLeft => "Vous ne pourrez plus regarder le plateau avant la prochaine \
+            partie.".to_string(),
+        Message::Exported(path) => format!(
+            "Le plateau a été enregistré dans '{}'.", path.display()),
+        Message::ExportFailed {path, error} => format!(
+            "Impossible d'enregistrer le plateau dans '{}' : {}", path.display(), error),
+        Message::TreeExported {path, positions: 1} => format!(
+            "Un arbre de 1 position a été enregistré dans '{}'.", path.display()),
+        Message::TreeExported {path, positions} => format!(
+            "Un arbre de {} positions a été enregistré dans '{}'.", positions, path.display()),
+        Message::TreeExportFailed {path, error} => format!(
+            "Impossible d'enregistrer l'arbre dans '{}' : {}", path.display(), error),
+        Message::CastFailed {path, error} => format!(
+            "Impossible d'enregistrer la session dans '{}' : {}", path.display(), error),
+        Message::LogFailed {path, error} => format!(
+            "Impossible d'ouvrir le journal '{}' : {}", path.display(), error),
+        Message::PrefsFailed {path, error} => format!(
+            "Impossible d'utiliser les préférences de '{}' : {}", path.display(), error),
+        Message::PrefsSaved(path) => format!(
+            "Vos préférences ont été enregistrées dans '{}'.", path.display()),
+        Message::NoPrefsDir => "Aucun dossier trouvé pour enregistrer les préférences."
+            .to_string(),
+        Message::DemoGame {number, games: Some(games), first_player} => format!(
+            "Partie de démonstration {} sur {} ({} commence)", number, games, first_player),
+        Message::DemoGame {number, games: None, first_player} => format!(
+            "Partie de démonstration {} ({} commence)", number, first_player),
+        Message::DemoHelp => "Appuyez sur Ctrl-C pour arrêter la démonstration.".to_string(),
+        Message::ArchiveReadFailed {path, error} => format!(
+            "Impossible de lire l'archive '{}' : {}", path.display(), error),
+        Message::ArchiveWriteFailed {path, error} => format!(
+            "Impossible d'écrire l'archive '{}' : {}", path.display(), error),
+        Message::StatsHeader(path) => format!(
+            "Statistiques des parties de '{}' :", path.display()),
+        Message::PlayerStats {name, stats} => format!(
+            "{} : parties {}, victoires {}, défaites {}, nulles {}",
+            name, stats.games(), stats.wins, stats.losses, stats.ties),
+        Message::FavoriteOpening {row, col, games: 1} => format!(
+            "  Ouverture préférée : {} (1 partie)", position(row, col)),
+        Message::FavoriteOpening {row, col, games} => format!(
+            "  Ouverture préférée : {} ({} parties)", position(row, col), games),
+        Message::Streak {outcome, games, best_win_streak} => {
+            let outcome = match (outcome, games) {
+                (Outcome::Win, 1) => "victoire",
+                (Outcome::Win, _) => "victoires",
+                (Outcome::Loss, 1) => "défaite",
+                (Outcome::Loss, _) => "défaites",
+                (Outcome::Tie, 1) => "partie nulle",
+                (Outcome::Tie, _) => "parties nulles",
+            };
+            format!("  Série en cours : {} {} (plus longue série de victoires : {})",
+                games, outcome, best_win_streak)
+        },
+        Message::NoStats(path) => format!(
+            "Il n'y a pas encore de parties de joueurs nommés dans '{}'. Les joueurs peuvent \
+            choisir un nom avec --x-name et --o-name.", path.display()),
+        Message::RatingChange {player, rating, change} => format!(
+            "Classement Elo de {} : {} ({:+})", player, rating, change),
+        Message::RatingsHeader(path) => format!(
+            "Classements Elo des parties de '{}' :", path.display()),
+        Message::PlayerRating {rank, name, rating, games: 1} => format!(
+            "{}. {} - {} (1 partie)", rank, name, rating),
+        Message::PlayerRating {rank, name, rating, games} => format!(
+            "{}. {} - {} ({} parties)", rank, name, rating, games),
+        Message::Seed(seed) => format!(
+            "Graine aléatoire : {} (utilisez --seed {} pour rejouer les mêmes parties)", seed,
+            seed),
+        Message::HeadToHead {a, b, path, games: 1} => format!(
+            "{} contre {} dans '{}' : 1 partie", a, b, path.display()),
+        Message::HeadToHead {a, b, path, games} => format!(
+            "{} contre {} dans '{}' : {} parties", a, b, path.display(), games),
+        Message::HeadToHeadRecord {a, b, piece, record} => format!(
+            "  {} : {} a gagné {}, {} a gagné {}, nuls {}", match piece {
+                Some(piece) => format!("{} avec {}", a, piece_name(piece)),
+                None => "Au total".to_string(),
+            }, a, record.wins, b, record.losses, record.ties),
+        Message::HeadToHeadLength(moves) => format!(
+            "  Durée moyenne d'une partie : {:.1} coups", moves),
+        Message::NoHeadToHead {a, b, path} => format!(
+            "{} et {} ne se sont pas encore affrontés dans '{}'.", a, b, path.display()),
+        Message::OpeningsHeader(path) => format!(
+            "Ouvertures de '{}', avec les résultats du joueur qui a commencé :", path.display()),
+        Message::Opening {moves, results} if results.games() == 1 => format!(
+            "{} : 1 partie, {}", describe_moves(moves), describe_percentages(results,
+            ["victoires", "nuls", "défaites"], " %")),
+        Message::Opening {moves, results} => format!(
+            "{} : {} parties, {}", describe_moves(moves), results.games(),
+            describe_percentages(results, ["victoires", "nuls", "défaites"], " %")),
+        Message::NoOpenings(path) => format!(
+            "Il n'y a pas encore de parties avec des coups dans '{}'.", path.display()),
+        Message::NoRatings(path) => format!(
+            "Il n'y a pas encore de parties entre deux joueurs nommés dans '{}'. Les joueurs \
+            peuvent choisir un nom avec --x-name et --o-name.", path.display()),
+        Message::TournamentGame {number, games, x, o} => format!(
+            "Partie {} sur {} du tournoi : {} (x) contre {} (o)", number, games, x, o),
+        Message::TournamentOver => "Le tournoi est terminé ! Classement final :".to_string(),
+        Message::CrosstableEntrant => "Participant".to_string(),
+        Message::CrosstableScore => "Points".to_string(),
+        Message::SwissRound {round, rounds} => format!("Ronde {} sur {}", round, rounds),
+        Message::SwissBye(name) => format!(
+            "{} est exempt de cette ronde et marque comme s'il avait gagné les deux parties.",
+            name),
+        Message::SwissStandings(round) => format!("Classement après la ronde {} :", round),
+        Message::StandingsBuchholz => "Buchholz".to_string(),
+        Message::ArenaStart {first, second, elo0, elo1, max_games} => format!(
+            "{} joue contre {} jusqu'à ce qu'on sache si l'écart est de {:+} ou {:+} Elo \
+            ({} parties au plus)...", first, second, elo0, elo1, max_games),
+        Message::ArenaResults {games, wins, losses, ties} => format!(
+            "Après {} parties : victoires {}, défaites {}, nuls {}", games, wins, losses, ties),
+        Message::ArenaElo {elo, low, high} => format!(
+            "Écart Elo : {:+.1} (confiance à 95 % : de {:+.1} à {:+.1})", elo, low, high),
+        Message::ArenaAccepted {first, second, elo} => format!(
+            "{} a au moins {:+} Elo de plus que {}.", first, elo, second),
+        Message::ArenaRejected {first, second, elo} => format!(
+            "{} n'a pas plus de {:+} Elo de plus que {}.", first, elo, second),
+        Message::ArenaUndecided(games) => format!(
+            "Le test n'a pas pu trancher en {} parties.", games),
+        Message::SessionSummary {games: 1, average_moves, duration} => format!(
+            "Cette session : 1 partie de {:.0} coups en {}", average_moves,
+            duration),
+        Message::SessionSummary {games, average_moves, duration} => format!(
+            "Cette session : {} parties, {:.1} coups par partie en moyenne, en {}", games,
+            average_moves, duration),
+        Message::SessionPlayer {player, wins, losses, ties} => format!(
+            "{} : victoires {}, défaites {}, nuls {}", player, wins, losses, ties),
+        Message::SummaryCsvFailed {path, error} => format!(
+            "Impossible d'ajouter le résumé de la session à '{}' : {}", path.display(), error),
+        Message::Perft {depth, leaves, time} => format!(
+            "perft {} : {} feuilles en {:.3} s", depth, leaves, time.as_secs_f64()),
+        Message::Enumeration {counts, symmetry} => format!(
+            "{} positions{} : {} gagnées par x, {} gagnées par o, {} nulles, {} en cours",
+            counts.total(), if symmetry { " aux symétries près" } else { "" }, counts.x_wins,
+            counts.o_wins, counts.ties, counts.ongoing),
+        Message::EnumerationVerified(1) => {
+            "Le moteur est d'accord sur la seule position en cours.".to_string()
+        },
+        Message::EnumerationVerified(positions) => format!(
+            "Le moteur est d'accord sur les {} positions en cours.", positions),
+        Message::EnumerationMismatch(&[]) => {
+            "Le moteur n'est pas d'accord sur la position de départ.".to_string()
+        },
+        Message::EnumerationMismatch(moves) => format!(
+            "Le moteur n'est pas d'accord sur la position après {}.", describe_moves(moves)),
+        Message::ProfileSearch {positions: 1, time} => format!(
+            "1 position explorée en {:.3} ms", millis(time)),
+        Message::ProfileSearch {positions, time} => format!(
+            "{} positions explorées en {:.3} ms ({:.0} positions par seconde)", positions,
+            millis(time), per_second(positions, time)),
+        Message::ProfilePhase {phase, time, share} => format!("  {} : {:.3} ms ({:.1} %)",
+            match phase {
+                Some(Phase::Evaluation) => "évaluation",
+                Some(Phase::TableProbe) => "consultations de la table",
+                Some(Phase::MoveGeneration) => "génération des coups",
+                Some(Phase::MakeMove) => "coups joués",
+                None => "tout le reste",
+            }, millis(time), share),
+        Message::ProfileSaved(path) => format!(
+            "Les piles ont été enregistrées dans '{}'. Faites-en un flame graph avec \
+            `inferno-flamegraph {} > profile.svg`.", path.display(), path.display()),
+        Message::ProfileFailed {path, error} => format!(
+            "Impossible d'enregistrer les piles dans '{}' : {}", path.display(), error),
+        Message::SimulationStart {games: 1, x, o} => format!(
+            "Simulation d'une partie : {} (x) contre {} (o)", x, o),
+        Message::SimulationStart {games, x, o} => format!(
+            "Simulation de {} parties : {} (x) contre {} (o)", games, x, o),
+        Message::SimulationResults(simulation) => format!(
+            "Après {} parties : x a gagné {}, o a gagné {}, nuls {}", simulation.games(),
+            describe_share(simulation.x_wins, simulation.games(), " %"),
+            describe_share(simulation.o_wins, simulation.games(), " %"),
+            describe_share(simulation.ties, simulation.games(), " %")),
+        Message::SimulationLengths(simulation) => format!(
+            "Durée des parties : {}", describe_lengths(simulation, "coups", " %")),
+        Message::SimulationTime(simulation) => match simulation.games_per_second() {
+            Some(speed) => format!("Temps : {:.3} s, {:.0} parties par seconde",
+                simulation.time.as_secs_f64(), speed),
+            None => "Temps : trop court pour être mesuré".to_string(),
+        },
+        Message::VerifyProblem {line, problem} => format!("Ligne {} : {}", line, match *problem {
+            Problem::UnknownRecord => "ni une partie, ni un match, ni une fin".to_string(),
+            Problem::MissingField(field) => format!("le champ '{}' est manquant", field),
+            Problem::InvalidField {field, ref value} => format!(
+                "'{}' n'est pas une valeur valide pour '{}'", value, field),
+            Problem::InvalidMove {number, ref notation} => format!(
+                "le coup {} ('{}') n'est pas une case du plateau", number, notation),
+            Problem::IllegalMove {number, ref notation} => format!(
+                "le coup {} ('{}') ne peut pas être joué", number, notation),
+            Problem::WrongWinner {saved, actual} => format!(
+                "le résultat enregistré est {}, mais les coups donnent {}",
+                describe_result(saved, FRENCH_RESULTS), describe_result(actual, FRENCH_RESULTS)),
+            Problem::WrongDigest {saved, actual} => format!(
+                "les coups ont été modifiés après l'enregistrement de la partie (l'empreinte \
+                enregistrée est {:016x}, mais les coups donnent {:016x})", saved, actual),
+            Problem::InvalidForfeit(piece) => format!(
+                "{} n'a pas pu abandonner la partie", piece_name(piece)),
+            Problem::InvalidAdjudication(Adjudication::DeadPosition) => {
+                "la partie n'était pas nulle d'avance".to_string()
+            },
+            Problem::InvalidAdjudication(Adjudication::Solved) => {
+                "le solveur ne donne pas le résultat enregistré".to_string()
+            },
+            Problem::WrongMatchTotals => {
+                "les totaux du match ne correspondent pas à ses parties".to_string()
+            },
+            Problem::UnclosedMatch => "le match n'a pas de fin".to_string(),
+            Problem::UnexpectedEnd => "une fin sans match".to_string(),
+        }),
+        Message::VerifyPassed {path, games: 1} => format!(
+            "La partie de '{}' est valide.", path.display()),
+        Message::VerifyPassed {path, games} => format!(
+            "Les {} parties de '{}' sont valides.", games, path.display()),
+        Message::VerifyFailed {path, problems: 1} => format!(
+            "1 problème trouvé dans '{}'.", path.display()),
+        Message::VerifyFailed {path, problems} => format!(
+            "{} problèmes trouvés dans '{}'.", problems, path.display()),
+        Message::AuditFinding {line, finding} => format!("Ligne {} : {}", line, match *finding {
+            Finding::WrongWinner {saved, actual} => format!(
+                "le résultat enregistré est {}, mais les coups donnent {}",
+                describe_result(saved, FRENCH_RESULTS), describe_result(actual, FRENCH_RESULTS)),
+            Finding::NotOptimal {number, row, col, ref engine, ref best} => format!(
+                "le coup {} ({}) du moteur '{}' n'était pas un des meilleurs coups : {}",
+                number, position(row, col), engine, describe_moves(best)),
+        }),
+        Message::AuditPassed {path, games, moves} => format!(
+            "Parties de '{}' rejouées : {}, coups de moteur vérifiés : {}. Tout concorde.",
+            path.display(), games, moves),
+        Message::AuditFailed {path, findings: 1} => format!(
+            "1 désaccord trouvé dans '{}'.", path.display()),
+        Message::AuditFailed {path, findings} => format!(
+            "{} désaccords trouvés dans '{}'.", findings, path.display()),
+        Message::ChatHelp {mention} => format!("Commandes :\n\
+            !ttt play - jouer contre l'ordinateur\n\
+            !ttt play {} - jouer contre quelqu'un d'autre\n\
+            !ttt resign - abandonner la partie que vous jouez\n\
+            !ttt pause - mettre en pause la partie que vous jouez\n\
+            !ttt resume - reprendre une partie en pause\n\
+            !ttt rematch - rejouer la dernière partie en échangeant les pièces\n\
+            !ttt board - afficher le plateau à nouveau\n\
+            !ttt ratings - afficher le classement Elo de tout le monde\n\
+            !ttt coach low|medium|high|off - commenter les coups dans ce salon\n\
+            Pendant une partie, tapez une case comme {} pour y jouer.",
+            if mention { "@quelqu'un" } else { "quelqu'un" }, position(1, 1)),
+        Message::ChatVersus {x, o} => format!("{} contre {}", x, o),
+        Message::ChatComputer => "Ordinateur".to_string(),
+        Message::ChatTurn(player) => format!("C'est au tour de {}.", player),
+        Message::ChatResigned(player) => format!("{} a abandonné.", player),
+        Message::ChatGameInProgress => {
+            "Il y a déjà une partie dans ce salon.".to_string()
+        },
+        Message::ChatNoGame => {
+            "Il n'y a pas de partie dans ce salon. Commencez-en une avec !ttt play.".to_string()
+        },
+        Message::ChatNotPlaying => "Vous ne jouez pas dans cette partie.".to_string(),
+        Message::ChatPlayYourself => "Vous ne pouvez pas jouer contre vous-même.".to_string(),
+        Message::ChatRatingsHeader => "Classements Elo :".to_string(),
+        Message::ChatNoRatings => {
+            "Personne n'a encore de classement Elo. Il vient des parties entre deux personnes."
+                .to_string()
+        },
+        Message::PuzzleIntro => "Chaque problème est une position où vous devez trouver les \
+            meilleurs coups. Je joue l'autre camp du mieux que je peux. Tapez 'indice' si vous \
+            êtes bloqué : chaque indice en dévoile un peu plus.".to_string(),
+        Message::PuzzleHeader {number, total, player, goal: Goal::Win, moves: 1} => format!(
+            "Problème {} sur {} : {} joue et gagne tout de suite.", number, total, player),
+        Message::PuzzleHeader {number, total, player, goal: Goal::Win, moves} => format!(
+            "Problème {} sur {} : {} joue et gagne en {} coups au plus.", number, total, player,
+            moves),
+        Message::PuzzleHeader {number, total, player, goal: Goal::Draw, moves: 1} => format!(
+            "Problème {} sur {} : {} joue. Trouvez les seuls coups qui ne perdent pas.", number,
+            total, player),
+        Message::PuzzleHeader {number, total, player, goal: Goal::Draw, moves} => format!(
+            "Problème {} sur {} : {} joue. Ne laissez pas de victoire à l'adversaire pendant les \
+            {} prochains coups.", number, total, player, moves),
+        Message::PuzzleWrongMove {row, col, goal: Goal::Win} => format!(
+            "{} ne force pas la victoire à temps. Réessayez.", position(row, col)),
+        Message::PuzzleWrongMove {row, col, goal: Goal::Draw} => format!(
+            "{} perd contre la meilleure défense. Réessayez.", position(row, col)),
+        Message::PuzzleReply {row, col} => format!("J'ai répondu {}.", position(row, col)),
+        Message::PuzzleHint(Hint::Idea(idea)) => match idea {
+            Idea::Win => "Vous pouvez gagner tout de suite.",
+            Idea::Block => "Votre adversaire est sur le point de gagner. Commencez par là.",
+            Idea::Fork => "Cherchez un coup qui fait deux menaces à la fois.",
+            Idea::Threat => "Cherchez un coup qui fait une menace que votre adversaire doit \
+                bloquer.",
+            Idea::Quiet => "Le bon coup ne fait pas encore de menace. Pensez à ce que votre \
+                adversaire veut faire.",
+        }.to_string(),
+        Message::PuzzleHint(Hint::Row(row)) => format!("Le coup est dans la ligne {}.", row + 1),
+        Message::PuzzleHint(Hint::Move(row, col)) => format!("Jouez {}.", position(row, col)),
+        Message::PuzzleSolved {hints: 0} => "Résolu !".to_string(),
+        Message::PuzzleSolved {hints: 1} => "Résolu, avec 1 indice.".to_string(),
+        Message::PuzzleSolved {hints} => format!("Résolu, avec {} indices.", hints),
+        Message::PuzzlesDone(0) => "Il n'y avait aucun problème à résoudre.".to_string(),
+        Message::PuzzlesDone(1) => "Vous avez résolu le problème !".to_string(),
+        Message::PuzzlesDone(total) => format!(
+            "Vous avez résolu les {} problèmes !", total),
+        Message::PuzzleReadFailed {path, error} => format!(
+            "Impossible de lire les problèmes de '{}' : {}", path.display(), error),
+        Message::PuzzleProblem {path, line, problem} => format!(
+            "La ligne {} de '{}' n'est pas un problème jouable : {}", line, path.display(),
+            match *problem {
+                puzzle::Problem::UnknownRecord => "ce n'est pas un problème".to_string(),
+                puzzle::Problem::MissingField(field) => {
+                    format!("le champ '{}' est manquant", field)
+                },
+                puzzle::Problem::InvalidField {field, ref value} => format!(
+                    "'{}' n'est pas une valeur valide pour '{}'", value, field),
+                puzzle::Problem::IllegalMove {number, ref notation} => format!(
+                    "le coup {} ('{}') ne peut pas être joué", number, notation),
+                puzzle::Problem::NoSolution => {
+                    "le but ne peut pas être atteint à temps".to_string()
+                },
+            }),
+        Message::DailyHeader {date, player, goal: Goal::Win, moves: 1} => format!(
+            "Problème du {} : {} joue et gagne tout de suite.", date, player),
+        Message::DailyHeader {date, player, goal: Goal::Win, moves} => format!(
+            "Problème du {} : {} joue et gagne en {} coups au plus.", date, player, moves),
+        Message::DailyHeader {date, player, goal: Goal::Draw, moves: 1} => format!(
+            "Problème du {} : {} joue. Trouvez les seuls coups qui ne perdent pas.", date, player),
+        Message::DailyHeader {date, player, goal: Goal::Draw, moves} => format!(
+            "Problème du {} : {} joue. Ne laissez pas de victoire à l'adversaire pendant les {} \
+            prochains coups.", date, player, moves),
+        Message::DailyStreak {current: 1, best} => format!(
+            "Vous avez résolu le problème du jour. Revenez demain pour commencer une série ! \
+            (Meilleure série : {})", best),
+        Message::DailyStreak {current, best} => format!(
+            "Vous avez résolu le problème du jour {} jours de suite ! (Meilleure série : {})",
+            current, best),
+        Message::DailyShare => "Partagez votre résultat :".to_string(),
+        Message::SetupIntro => format!("Préparez une position, puis jouez-la ou analysez-la. \
+            Tapez une case comme {} pour changer ce qu'elle contient (vide, x, o, puis vide à \
+            nouveau), 'suivant x' ou 'suivant o' pour choisir qui joue ensuite, 'vider' pour \
+            vider le plateau, 'analyser' pour trouver les meilleurs coups et 'jouer' pour \
+            commencer une partie.", position(0, 0)),
+        Message::SetupStatus(player) => format!(
+            "Préparation d'une position | {} joue ensuite", player),
+        Message::EnterEdit => "Entrez une case ou une commande : ".to_string(),
+        Message::InvalidEdit(input) => format!(
+            "'{}' n'est ni une case ni une commande. Veuillez réessayer.", input),
+        Message::PositionInvalid(PositionError::PieceCounts {x, o, next}) => format!(
+            "Avec {} x et {} o sur le plateau, ce ne peut pas être à {} de jouer. Le joueur qui \
+            joue ensuite doit avoir autant de pièces que l'autre, ou une de moins.", x, o,
+            piece_name(next)),
+        Message::PositionInvalid(PositionError::Unreachable) => "Aucune partie ne peut arriver \
+            à cette position : la partie est finie dès qu'un joueur a trois pièces alignées."
+            .to_string(),
+        Message::PositionOver => "La partie est déjà finie dans cette position.".to_string(),
+        Message::PositionAnalysis {winner: Some(winner), moves} => format!(
+            "Avec un jeu parfait, {} gagne. Meilleurs coups : {}", winner, describe_moves(moves)),
+        Message::PositionAnalysis {winner: None, moves} => format!(
+            "Avec un jeu parfait, la partie est nulle. Meilleurs coups : {}",
+            describe_moves(moves)),
+        Message::ReviewIntro => "Reprenons la partie. Chaque coup est montré sur le plateau où \
+            il a été joué. Appuyez sur entrée pour le coup suivant, tapez 'précédent' pour celui \
+            d'avant, ou 'quitter' pour arrêter.".to_string(),
+        Message::ReviewMove {player, row, col, mark: Mark::Best, ..} => format!(
+            "{} a joué {} : un des meilleurs coups.", player, position(row, col)),
+        Message::ReviewMove {player, row, col, mark: Mark::Ok, better} => format!(
+            "{} a joué {} : correct, mais {} était mieux.", player, position(row, col),
+            describe_moves(better)),
+        Message::ReviewMove {player, row, col, mark: Mark::Mistake, better} => format!(
+            "{} a joué {} : une erreur. Mieux : {}", player, position(row, col),
+            describe_moves(better)),
+        Message::ReviewPrompt => "Suivant, précédent ou quitter ? ".to_string(),
+        Message::ReviewHelp => {
+            "Veuillez entrer : suivant, précédent ou quitter.".to_string()
+        },
+        Message::ReviewSummary {player, counts: [best, ok, mistakes]} => format!(
+            "{} : {} meilleurs, {} corrects, {} {}", player, best, ok, mistakes,
+            if mistakes == 1 { "erreur" } else { "erreurs" }),
+        Message::ThinkTime(time) => format!("Réfléchi pendant {:.1} s.", time.as_secs_f64()),
+        Message::ReviewThinking {player, total, moves} => format!(
+            "{} a réfléchi {:.1} s en tout, {:.1} s par coup en moyenne.", player,
+            total.as_secs_f64(), total.as_secs_f64() / moves as f64),
+        Message::Paused => "La partie est en pause. Le plateau est caché et les pendules sont \
+            arrêtées.".to_string(),
+        Message::EnterResume => "Appuyez sur Entrée pour continuer : ".to_string(),
+        Message::Resumed(length) => format!("La partie reprend après une pause de {}.",
+            ClockTime(length)),
+        Message::ChatPaused(player) => format!(
+            "{} a mis la partie en pause. Tapez !ttt resume pour continuer.", player),
+        Message::ChatStillPaused => {
+            "La partie est en pause. Tapez !ttt resume pour continuer.".to_string()
+        },
+        Message::ChatNotPaused => "La partie n'est pas en pause.".to_string(),
+        Message::ForcedWin {player, moves: 0} => format!("{} gagne quoi qu'il arrive.", player),
+        Message::ForcedWin {player, moves: 1} => format!(
+            "{} a un gain forcé : son prochain coup gagne.", player),
+        Message::ForcedWin {player, moves} => format!(
+            "{} a un gain forcé en {} coups.", player, moves),
+        Message::ForcedDraw => {
+            "La partie est maintenant nulle d'office : aucun joueur ne peut gagner si l'autre joue \
+            bien.".to_string()
+        },
+        Message::Coach {comment, player, opponent} => match comment {
+            Comment::MissedWin(row, col) => format!(
+                "Entraîneur : {} pouvait gagner en {}.", player, position(row, col)),
+            Comment::LeftWin(row, col) => format!(
+                "Entraîneur : {} a laissé {} libre. {} peut y gagner.", player,
+                position(row, col), opponent),
+            Comment::LeftFork(row, col) => format!(
+                "Entraîneur : {} a laissé {} libre. {} peut y faire une fourchette.", player,
+                position(row, col), opponent),
+            Comment::Block => format!("Entraîneur : bien bloqué par {}.", player),
+            Comment::Threat(row, col) => format!(
+                "Entraîneur : {} menace de gagner en {}.", player, position(row, col)),
+            Comment::Fork => format!(
+                "Entraîneur : une fourchette ! {} a deux façons de gagner et {} ne peut en bloquer \
+                qu'une.", player, opponent),
+        },
+        Message::ChatCoach(Some(level)) => {
+            format!("L'entraîneur est activé dans ce salon ({}).", level.name())
+        },
+        Message::ChatCoach(None) => "L'entraîneur est désactivé dans ce salon.".to_string(),
+        Message::ChatCoachHelp => "Tapez !ttt coach low, medium, high ou off.".to_string(),
+        Message::SpeedrunIntro {wins, difficulty} => format!(
+            "Speedrun : battez l'ordinateur {} {} fois le plus vite possible. Les parties que \
+            vous ne gagnez pas ne comptent pas, mais leur temps compte.", difficulty.name(), wins),
+        Message::SpeedrunGame {number, wins, needed} => format!(
+            "Partie {} ({} victoires sur {})", number, wins, needed),
+        Message::SpeedrunTime(time) => format!("Temps jusqu'ici : {}", RunTime(time)),
+        Message::SpeedrunDone {time, games} => format!(
+            "Vous avez terminé en {} ({} parties) !", RunTime(time), games),
+        Message::SpeedrunFinish(Finish::NewBest {previous: None}) => {
+            "C'est votre premier temps au classement.".to_string()
+        },
+        Message::SpeedrunFinish(Finish::NewBest {previous: Some(previous)}) => format!(
+            "C'est votre meilleur temps ! L'ancien était {}.", RunTime(previous)),
+        Message::SpeedrunFinish(Finish::Slower {best}) => format!(
+            "Votre meilleur temps reste {}.", RunTime(best)),
+        Message::SpeedrunNoName => {
+            "Choisissez un nom avec --x-name pour garder vos meilleurs temps.".to_string()
+        },
+        Message::SpeedrunLeaderboard(difficulty) => format!(
+            "Meilleurs temps contre l'ordinateur {} :", difficulty.name()),
+        Message::SpeedrunEntry {rank, name, time} => format!(
+            "{}. {} - {}", rank, name, RunTime(time)),
+        Message::Adjudicated(Adjudication::DeadPosition) => {
+            "Plus personne ne peut aligner trois pions, la partie a donc été arrêtée.".to_string()
+        },
+        Message::Adjudicated(Adjudication::Solved) => {
+            "Le résultat était certain avec un jeu parfait, la partie a donc été arrêtée."
+                .to_string()
+        },
+        Message::SimulationAdjudicated(simulation) => format!(
+            "Arbitrées : {} des parties ({})", simulation.adjudicated,
+            describe_share(simulation.adjudicated, simulation.games(), " %")),
+        Message::ClubIntro => "Bienvenue au club ! Tapez 'nouvelle' à la place d'un coup pour \
+            commencer une autre partie, 'parties' pour voir toutes les parties et 'changer N' pour \
+            aller à la partie N.".to_string(),
+        Message::ClubTable {id, focused, x, o, moves, current} => format!(
+            "{} Partie {} : {} contre {}, {} {}, au tour de {}", if focused { ">" } else { " " },
+            id, x, o, moves, if moves == 1 { "coup" } else { "coups" }, current),
+        Message::ClubGameOver(id) => format!("La partie {} est terminée. Les autres parties :", id),
+        Message::ClubSwitched(id) => format!("Vous jouez maintenant la partie {}.", id),
+        Message::ClubNoTable(id) => format!(
+            "Il n'y a pas de partie {}. Tapez 'parties' pour les voir.", id),
+        Message::ClubOnly => {
+            "Une seule partie est en cours. Lancez le programme avec 'club' pour en jouer \
+            plusieurs à la fois.".to_string()
+        },
+        Message::ClubPlayerName(piece) => format!(
+            "Nom du joueur avec {} (laissez vide pour aucun) : ", piece_name(piece)),
+        Message::ClubSaving(id) => format!("Partie {} :", id),
+        Message::SimulIntro {giver, boards} => format!(
+            "{} joue {} parties à la fois. Après chacun de ses coups, on passe au plateau \
+            suivant. Tapez 'parties' pour voir tous les plateaux.", giver, boards),
+        Message::SimulBoard {id, opponent, focused} => format!(
+            "{} Plateau {} : {}", if focused { ">" } else { " " }, id, opponent),
+        Message::SimulNextBoard(id) => format!("Au plateau {}.", id),
+        Message::SimulFixed => {
+            "Dans une simultanée, les plateaux viennent à tour de rôle et aucun autre ne peut être \
+            commencé.".to_string()
+        },
+        Message::SimulResult {giver, wins, ties, losses} => format!(
+            "La simultanée est terminée. {} a gagné {}, fait {} nulles et perdu {}.", giver, wins,
+            ties, losses),
+        Message::ExploreIntro => "Explorons le jeu. Chaque coup possible est affiché avec la fin \
+            de la partie si les deux joueurs jouent parfaitement ensuite, et ce qu'il a donné dans \
+            l'archive. Les coups identiques après avoir tourné ou retourné le plateau sont \
+            regroupés.".to_string(),
+        Message::ExploreMoves(player) => format!("Coups pour {} :", player),
+        Message::ExploreCandidate {tiles, verdict, results} => {
+            let verdict = match verdict {
+                Verdict::Win => "gagne avec un jeu parfait",
+                Verdict::Draw => "nul avec un jeu parfait",
+                Verdict::Loss => "perd avec un jeu parfait",
+            };
+            let played = match results {
+                None => String::new(),
+                Some(results) if results.games() == 0 => {
+                    " ; jamais joué dans l'archive".to_string()
+                },
+                Some(results) => format!(" ; {} {}, {}", results.games(),
+                    if results.games() == 1 { "partie" } else { "parties" },
+                    describe_percentages(&results, ["victoires", "nuls", "défaites"], " %")),
+            };
+            format!("  {} : {}{}", describe_alternatives(tiles), verdict, played)
+        },
+        Message::ExplorePrompt => "Coup, précédent ou quitter ? ".to_string(),
+        Message::ExploreHelp => format!(
+            "Veuillez entrer : un coup comme {}, précédent ou quitter.", position(0, 0)),
+        Message::WatchHelp(path) => format!(
+            "Suivi des nouveaux coups de '{}'. Appuyez sur Ctrl-C pour arrêter.", path.display()),
+        Message::WatchWaiting(path) => format!(
+            "En attente de la création de '{}'...", path.display()),
+        Message::GameCode(code) => format!("Code de la partie : {}", code),
+        Message::GameLink(link) => format!("Lien : {}", link),
+        Message::CodeLoaded(moves) if moves <= 1 => {
+            format!("Partie chargée avec {} coup.", moves)
+        },
+        Message::CodeLoaded(moves) => format!("Partie chargée avec {} coups.", moves),
+        Message::QrExported(path) => format!(
+            "Le code QR a été enregistré dans '{}'.", path.display()),
+        Message::QrExportFailed {path, error} => format!(
+            "Impossible d'enregistrer le code QR dans '{}' : {}", path.display(), error),
+        Message::ImagesUnavailable => {
+            "Cette copie du jeu a été compilée sans les images PNG, les animations GIF et les \
+            codes QR. Compilez-la avec `--features images` pour les utiliser.".to_string()
+        },
+        Message::DiffInvalidGame(source) => format!(
+            "'{}' n'est ni un fichier de coups ni un code de partie.", source),
+        Message::DiffSame {moves, exact} => format!("Les deux parties ont les mêmes {} coups{}.",
+            moves, if exact { "" } else { ", sur un plateau tourné ou retourné" }),
+        Message::DiffDiverged {number, first, second, exact} => format!(
+            "Les parties divergent au coup {} : la première partie a joué {} et la seconde {}.{}",
+            number, position(first.0, first.1), position(second.0, second.1),
+            if exact || number == 1 {
+                ""
+            }
+            else {
+                " Les coups précédents sont les mêmes sur un plateau tourné ou retourné."
+            }),
+        Message::DiffLonger {common, first_longer} => format!(
+            "La {} partie continue après les {} coups que les deux parties ont en commun.",
+            if first_longer { "première" } else { "seconde" }, common),
+        Message::DiffResults {results, lengths} => format!(
+            "Résultats : {} après {} coups et {} après {} coups{}.",
+            describe_result(results.0, FRENCH_RESULTS), lengths.0,
+            describe_result(results.1, FRENCH_RESULTS), lengths.1,
+            if results.0 == results.1 { ", le même résultat" } else { "" }),
+        Message::CheckDuplicate {line, first} => format!(
+            "Ligne {} : la même partie qu'à la ligne {} (en tournant ou retournant le plateau)",
+            line, first),
+        Message::CheckUnknownField {line, field} => format!(
+            "Ligne {} : '{}' n'est pas un champ connu de cette version", line, field),
+        Message::CheckRepaired {line, repair} => format!("Ligne {} : {}", line, match repair {
+            Repair::MatchTotals => "totaux du match recomptés",
+            Repair::ClosedMatch => "fin du match ajoutée",
+            Repair::RemovedEnd => "fin sans match retirée",
+        }),
+        Message::CheckFixed {backup, repairs: 1} => format!(
+            "1 problème corrigé. L'archive d'origine est enregistrée dans '{}'.", backup.display()),
+        Message::CheckFixed {backup, repairs} => format!(
+            "{} problèmes corrigés. L'archive d'origine est enregistrée dans '{}'.", repairs,
+            backup.display()),
+        Message::TrainingExported {path, samples, games: 1} => format!(
+            "{} exemples d'entraînement tirés de 1 partie ont été enregistrés dans '{}'.", samples,
+            path.display()),
+        Message::TrainingExported {path, samples, games} => format!(
+            "{} exemples d'entraînement tirés de {} parties ont été enregistrés dans '{}'.",
+            samples, games, path.display()),
+        Message::TrainingExportFailed {path, error} => format!(
+            "Impossible d'enregistrer les données d'entraînement dans '{}' : {}", path.display(),
+            error),
+        Message::ConfirmQuit => "L'entrée est terminée. Quitter ? (o/n) : ".to_string(),
+        Message::InputClosed => "L'entrée s'est terminée avant la fin de la session.".to_string(),
+        Message::PluginSkipped {path, error} => format!(
+            "Le plugin '{}' a été ignoré : {}", path.display(), error),
+        Message::PluginMissing(PluginError::NoRenderer(name)) => format!(
+            "Aucun plugin d'affichage ne s'appelle '{}'.", name),
+        Message::PluginMissing(PluginError::NoPlayer(name)) => format!(
+            "Aucun plugin de joueur ordinateur ne s'appelle '{}'.", name),
+        Message::PluginMissing(error) => error.to_string(),
+        Message::PluginMoveFailed {name, error} => format!(
+            "Le plugin {} ne peut pas jouer ce coup ({}), il perd donc la partie par forfait.",
+            name, error),
+        Message::PluginsUnavailable => {
+            "Cette copie du jeu a été compilée sans les plugins. Compilez-la avec \
+            `--features plugins` pour les utiliser.".to_string()
+        },
+        Message::RulesNotArchived(rules) => format!(
+            "Les parties jouées avec les règles {} ne sont pas enregistrées dans l'archive.",
+            rules.name()),
+        Message::FileRecovered {path, recovery} if recovery.restored => format!(
+            "'{}' était corrompu, il a donc été déplacé vers '{}' et sa sauvegarde a été utilisée \
+            à sa place.", path.display(), recovery.quarantined.display()),
+        Message::FileRecovered {path, recovery} => format!(
+            "'{}' était corrompu et n'avait pas de sauvegarde, il a donc été déplacé vers '{}' et \
+            repart de zéro.", path.display(), recovery.quarantined.display()),
+        Message::IdleMoved {player, row, col} => format!(
+            "{} n'a pas joué à temps, alors l'ordinateur a joué {} à sa place.", player,
+            position(row, col)),
+        Message::IdleForfeit(player) => format!(
+            "{} n'a pas joué à temps et perd la partie.", player),
+        Message::TaggedGame {number, game, moves} => format!("Partie {} : {}{}{}", number,
+            describe_moves(&game.moves().to_vec()), describe_tags(game),
+            describe_tagged_moves(moves, "coup", "coups")),
+        Message::TaggedFound {query, found, games} if found < 2 => format!(
+            "{} partie sur {} a le tag {}.", found, games, query),
+        Message::TaggedFound {query, found, games} => format!(
+            "{} parties sur {} ont le tag {}.", found, games, query),
+        Message::IrcUnavailable => {
+            "Cette copie du jeu a été compilée sans le bot IRC. Compilez-la avec \
+            `--features irc` pour l'utiliser.".to_string()
+        },
+        Message::IrcConnected {server, nick} => format!(
+            "Connecté à {} en tant que {}.", server, nick),
+        Message::IrcDisconnected(error) => format!(
+            "Connexion au serveur IRC perdue ({}). Reconnexion...", error),
+        Message::IrcNickInUse(nick) => format!(
+            "Quelqu'un utilise déjà le pseudo {}. Choisissez-en un autre avec --nick.", nick),
+        Message::DiscordUnavailable => {
+            "Cette copie du jeu a été compilée sans le bot Discord. Compilez-la avec \
+            `--features discord` pour l'utiliser.".to_string()
+        },
+        Message::DiscordNoToken => {
+            "Donnez le jeton du bot à DISCORD_TOKEN pour vous connecter à Discord.".to_string()
+        },
+        Message::DiscordConnected(name) => format!("Connecté à Discord en tant que {}.", name),
+        Message::DiscordDisconnected(error) => format!(
+            "Connexion à Discord perdue ({}). Reconnexion...", error),
+        Message::DiscordRejected(error) => format!("Discord a refusé la connexion : {}", error),
+    }
+}
+
+// The words for each result of a game: x won, o won, a tie, and a game that isn't over
+const ENGLISH_RESULTS: [&str; 4] = ["x won", "o won", "a tie", "unfinished"];
+const FRENCH_RESULTS: [&str; 4] = ["victoire de x", "victoire de o", "égalité", "inachevée"];
+
+// Picks the word for the result of a game. `names` are the words for x winning, o winning, a tie
+// and a game that isn't over, in that order.
+fn describe_result(winner: Option<Winner>, names: [&'static str; 4]) -> &'static str {
+    match winner {
+        Some(Winner::X) => names[0],
+        Some(Winner::O) => names[1],
+        Some(Winner::Tie) => names[2],
+        None => names[3],
+    }
+}
+
+// The pieces are called "x" and "o" in every language
+fn piece_name(piece: Piece) -> &'static str {
+    match piece {
+        Piece::X => "x",
+        Piece::O => "o",
+    }
+}
+
+// Lists the tiles of a row separated by commas, using the given word for empty tiles
+fn describe_tiles(tiles: &[Option<&str>], empty: &str) -> String {
+    // `map` turns each tile into a word, and `collect` gathers those words into a Vec so that we
+    // can `join` them together
+    tiles.iter().map(|tile| tile.unwrap_or(empty)).collect::<Vec<_>>().join(", ")
+}
+
+// Formats a position the same way that moves are entered (e.g. "1A", or in whichever style the
+// player chose) so that it stays the same in every language
+fn position(row: usize, col: usize) -> String {
+    notation::write_move(row, col)
+}
+
+// Lists tiles that are all the same move, like "1A/1C/3A/3C"
+fn describe_alternatives(tiles: &[(usize, usize)]) -> String {
+    tiles.iter().map(|&(row, col)| position(row, col)).collect::<Vec<_>>().join("/")
+}
+
+// Lists the moves in the order they were played, like "1A 2B"
+fn describe_moves(moves: &[(usize, usize)]) -> String {
+    moves.iter().map(|&(row, col)| position(row, col)).collect::<Vec<_>>().join(" ")
+}
+
+// The tags of a game in brackets, or nothing if it has none
+fn describe_tags(game: &Game) -> String {
+    if game.tags().is_empty() {
+        return String::new();
+    }
+    let tags: Vec<_> = game.tags().iter().map(|(key, value)| format!("{}={}", key, value))
+        .collect();
+    format!(" [{}]", tags.join(", "))
+}
+
+// The numbers of the moves (counting from 1), or nothing if there are none
+fn describe_tagged_moves(moves: &[usize], one: &str, many: &str) -> String {
+    let numbers: Vec<_> = moves.iter().map(|index| (index + 1).to_string()).collect();
+    match numbers.len() {
+        0 => String::new(),
+        1 => format!("; {} {}", one, numbers[0]),
+        _ => format!("; {} {}", many, numbers.join(", ")),
+    }
+}
+
+// A duration in milliseconds, with the fraction of a millisecond
+fn millis(time: Duration) -> f64 {
+    time.as_secs_f64() * 1000.0
+}
+
+// How many of something happened every second over the given time
+fn per_second(count: u64, time: Duration) -> f64 {
+    count as f64 / time.as_secs_f64().max(f64::EPSILON)
+}
+
+// Shows a count as a share of the total with one decimal, like "12.5%". `sign` is the percent sign
+// along with any space that goes before it.
+fn describe_share(count: u32, total: u32, sign: &str) -> String {
+    format!("{:.1}{}", f64::from(count) * 100.0 / f64::from(total.max(1)), sign)
+}
+
+// Shows the share of the games that ended after each number of moves, like "5 moves 10.0%, ...".
+// Numbers of moves that no game ended after are left out.
+fn describe_lengths(simulation: &Simulation, moves: &str, sign: &str) -> String {
+    let parts: Vec<_> = simulation.lengths.iter().enumerate().filter(|&(_, &count)| count > 0)
+        .map(|(length, &count)| {
+            format!("{} {} {}", length, moves, describe_share(count, simulation.games(), sign))
+        }).collect();
+    parts.join(", ")
+}
+
+// Shows the share of the games that were won, tied and lost as whole percentages, like
+// "wins 50%, ties 25%, losses 25%". `labels` are the words for wins, ties and losses, and `sign`
+// is the percent sign along with any space that goes before it. Because of rounding, the
+// percentages don't always add up to exactly 100.
+fn describe_percentages(results: &OpeningResults, labels: [&str; 3], sign: &str) -> String {
+    let games = f64::from(results.games().max(1));
+    let counts = [results.wins, results.ties, results.losses];
+    let parts: Vec<_> = labels.iter().zip(&counts).map(|(label, &count)| {
+        format!("{} {}{}", label, (f64::from(count) * 100.0 / games).round(), sign)
+    }).collect();
+    parts.join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn language_codes() {
+        assert_eq!(Lang::from_code("en"), Some(Lang::English));
+        assert_eq!(Lang::from_code("en_US.UTF-8"), Some(Lang::English));
+        assert_eq!(Lang::from_code("fr_CA"), Some(Lang::French));
+        assert_eq!(Lang::from_code("FR"), Some(Lang::French));
+        assert_eq!(Lang::from_code("C"), None);
+        assert_eq!(Lang::from_code(""), None);
+    }
+
+    #[test]
+    fn positions_are_not_translated() {
+        let message = Message::TileNotEmpty {row: 1, col: 2, other_player: "o", since: 3};
+        assert!(Lang::English.text(message).contains("2C"));
+        assert!(Lang::French.text(message).contains("2C"));
+    }
+}
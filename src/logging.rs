@@ -0,0 +1,78 @@
+// Diagnostics for the people working on the program, as opposed to the messages shown to the
+// players. They are written with the `tracing` crate: the program reports what it is doing as
+// "events" (e.g. "the engine chose a move" or "a game was saved to the archive"), and groups the
+// events that belong together (like all of the games of a tournament) into "spans". Each event has
+// a level, from `error` for things that went wrong to `trace` for every little detail.
+// More info: https://docs.rs/tracing
+//
+// Nothing is written unless `--log-level` or `--log-file` is given. Reporting an event when
+// nobody is listening costs almost nothing, so the events can stay in the code all of the time.
+
+use std::fs::OpenOptions;
+use std::io;
+use std::path::Path;
+use std::sync::Mutex;
+
+use tracing::level_filters::LevelFilter;
+
+use bus::{Event, Subscriber};
+
+// The level used when a log file is given without `--log-level`
+const DEFAULT_LEVEL: LevelFilter = LevelFilter::INFO;
+
+// Starts writing every event at the given level (or a more important one) to stderr. Only one of
+// these functions should be called, and only once.
+pub fn to_stderr(level: LevelFilter) {
+    tracing_subscriber::fmt()
+        .with_max_level(level)
+        // The module that each event came from isn't very interesting in a program this size
+        .with_target(false)
+        .with_writer(io::stderr)
+        .init();
+}
+
+// Starts writing every event at the given level (or a more important one) to the end of the file
+// at `path`. Just like the archive, the log is only ever added to, so that the logs of earlier
+// sessions are kept.
+pub fn to_file(path: &Path, level: Option<LevelFilter>) -> io::Result<()> {
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+    tracing_subscriber::fmt()
+        .with_max_level(level.unwrap_or(DEFAULT_LEVEL))
+        .with_target(false)
+        // The Mutex lets every thread write to the file without mixing up their lines
+        .with_writer(Mutex::new(file))
+        // Colors would only show up as strange characters in a file
+        .with_ansi(false)
+        .init();
+    Ok(())
+}
+
+// Logs what happens in the games as it is published on the event bus (see bus.rs), so that the
+// game loop doesn't have to log each thing itself
+pub struct Games;
+
+impl Subscriber for Games {
+    fn notify(&mut self, event: &Event) {
+        match *event {
+            Event::MoveMade {game, players, row, col} => {
+                let piece = game.tiles()[row][col].expect("a piece was just placed there");
+                debug!(player = players.name(piece), row, col, "move made")
+            },
+            Event::MoveUndone {game, row, col} => {
+                debug!(row, col, moves = game.moves().len(), "move taken back")
+            },
+            Event::ComputerMoved {piece, row, col, ..} => {
+                debug!(?piece, row, col, "the computer finished thinking")
+            },
+            Event::TurnArrived {piece, ..} => debug!(?piece, "moves arrived"),
+            Event::Command(command) => debug!(?command, "command typed"),
+            Event::ClockTick {piece, remaining} => {
+                trace!(?piece, remaining = remaining.as_secs_f64(), "clock ran")
+            },
+            Event::ChatMessage {channel, author, ..} => debug!(channel, author, "chat message"),
+            Event::GameOver {game, ..} => {
+                info!(winner = ?game.winner(), moves = game.moves().len(), "game finished")
+            },
+        }
+    }
+}
@@ -1,62 +1,3287 @@
+// `extern crate` tells the Rust compiler that we are using a crate (a library) that was listed
+// in Cargo.toml. Some crates are optional, so we only use them when their feature is enabled.
+#[cfg(feature = "line-editing")]
+extern crate rustyline;
+#[cfg(feature = "discord")]
+extern crate tungstenite;
+#[cfg(feature = "discord")]
+extern crate ureq;
+#[cfg(feature = "discord")]
+extern crate serde_json;
+#[cfg(feature = "images")]
+extern crate png;
+#[cfg(feature = "images")]
+extern crate gif;
+#[cfg(feature = "images")]
+extern crate qrcode;
+extern crate crc32fast;
+#[cfg(feature = "parallel")]
+extern crate rayon;
+#[cfg(feature = "plugins")]
+extern crate libloading;
+extern crate thiserror;
+#[cfg(feature = "irc")]
+extern crate tic_tac_toe_net;
+// `#[macro_use]` lets every module use the macros that report events, like `info!` and `debug!`
+#[macro_use]
+extern crate tracing;
+extern crate tracing_subscriber;
+// The generators in testing.rs are always there for our own tests
+#[cfg(any(test, feature = "testing"))]
+extern crate proptest;
+// Only the snapshot tests use insta
+#[cfg(test)]
+#[macro_use]
+extern crate insta;
+
 // This tells the Rust compiler that there is a module called "game" in a file called "game.rs"
 // Conventions like this make it really easy to write code fast. If you want to customize that
 // behaviour, Rust gives you the power to do that too.
+// `#[macro_use]` lets every module after this one use the macros defined in cast.rs
+#[macro_use]
+mod cast;
 mod game;
+mod history;
+mod archive;
+mod audit;
+mod storage;
+mod options;
+mod series;
+mod session;
+mod locale;
+mod render;
+mod player;
+mod clock;
+mod json;
+mod command;
+mod input;
+mod interrupt;
+mod tutorial;
+mod random;
+mod engine;
+mod rules;
+mod theme;
+mod prefs;
+mod terminal;
+// Drawing PNG images and GIF animations needs the `images` feature
+#[cfg(feature = "images")]
+mod raster;
+mod stats;
+mod rating;
+mod tournament;
+mod arena;
+mod summary;
+mod symmetry;
+mod openings;
+mod explore;
+mod csv;
+mod export;
+mod notation;
+mod perft;
+mod simulate;
+mod logging;
+mod bus;
+mod profile;
+mod tree;
+mod gtp;
+mod puzzle;
+mod generate;
+mod daily;
+mod setup;
+mod review;
+mod coach;
+mod forced;
+mod notify;
+mod broadcast;
+mod speedrun;
+mod club;
+mod watch;
+mod share;
+#[cfg(feature = "images")]
+mod qr;
+mod diff;
+mod check;
+mod error;
+mod training;
+// Without the `plugins` feature, nothing is ever loaded, so none of the plugins are ever found
+#[cfg_attr(not(feature = "plugins"), allow(dead_code))]
+mod plugin;
+// The chat bots can be tested without connecting to any chat, so this is always built
+#[cfg_attr(not(any(feature = "discord", feature = "irc")), allow(dead_code))]
+mod bots;
+// The connection to IRC servers needs the `irc` feature
+#[cfg(feature = "irc")]
+mod irc;
+// Only the connection to Discord needs the `discord` feature
+#[cfg(feature = "discord")]
+mod gateway;
+// Most of the search and its transposition table only run in build.rs, the tests and `profile`,
+// since the perfect computer player looks its moves up in the table that build.rs makes
+#[cfg_attr(not(test), allow(dead_code))]
+mod solve;
+#[cfg_attr(not(test), allow(dead_code))]
+mod tt;
+mod enumerate;
+mod parallel;
+// Outside of the tests, nothing in the program uses the generators
+#[cfg(any(test, feature = "testing"))]
+#[cfg_attr(not(test), allow(dead_code))]
+mod testing;
+// The `cfg` attribute only includes this module when the `line-editing` feature is enabled
+#[cfg(feature = "line-editing")]
+mod editor;
+
+// This is how we "import" a module from the standard library. A module is a group of functions and
+// types. "std" stands for "standard library" and "io" stands for "input/output". We will use this
+// module to read input from the user of our application.
+use std::io;
+// We use the process::exit function to quit the program when we need to.
+use std::process;
+// fs::read_to_string lets us read the moves for a scripted game from a file
+use std::fs;
+use std::path::{Path, PathBuf};
+// env::args() gives us the command line arguments that were passed to the program
+use std::env;
+// The input is shared by every prompt in the program, so we keep it in a `thread_local!` (see INPUT
+// below). A RefCell lets us change what is inside of it even though it is shared, and a Cell does
+// the same for values that are simply copied in and out.
+use std::cell::{Cell, RefCell};
+// thread::sleep lets the demo pause between moves so that people can follow along
+use std::thread;
+use std::time::{Duration, Instant};
+// A BTreeMap keeps the ratings of the players sorted by name
+use std::collections::BTreeMap;
+
+// This is how we import names from our own module. Notice that there is no "std::" prefix.
+// For more information on modules, see:
+// https://doc.rust-lang.org/book/second-edition/ch07-00-modules.html
+use game::{Game, MoveTime, Pause, Piece, Winner, MoveError};
+use archive::{Archive, ArchivedGame, TagQuery};
+use bus::Event;
+use options::{Options, MoveScript, OutputFormat};
+use series::{Series, Score};
+use session::{Settings, Computer, PlayAgain, parse_yes_no};
+use locale::{Lang, Message};
+use error::ErrorCode;
+use render::{Renderer, GridRenderer, SpokenRenderer, JsonRenderer, BlindfoldRenderer,
+    CoachRenderer, ForcedRenderer};
+use render::{GameView, Rejection, Variant};
+use clock::{Clocks, IdleAction};
+use player::{PlayerProfile, Players};
+use command::Command;
+use input::{Input, OnEof, ReadError};
+use engine::{Difficulty, Profile};
+use prefs::Prefs;
+use theme::Theme;
+use tournament::{Crosstable, Entrant};
+use plugin::PluginError;
+use arena::{Results, Sprt, Verdict};
+use simulate::Simulation;
+use random::Rng;
+use parallel::*;
+use export::{ExportFormat, Table};
+use notation::{InvalidMove, parse_move, script_moves};
+use notify::Notifier;
+use broadcast::Broadcaster;
+use puzzle::{Attempt, Progress, Puzzle};
+use setup::{Edit, Setup};
+use review::Step;
+use explore::Explorer;
+use rules::Rules;
+use coach::Level;
+use speedrun::{Finish, Run};
+use storage::Recovery;
+use club::Club;
+use watch::Watch;
+use check::Finding;
+
+// What a player can do when it is their turn: either make a move or run one of the commands
+#[derive(Debug, Clone, Copy)]
+enum Turn {
+    Move(usize, usize),
+    Command(Command),
+}
+
+// The reasons that the players stopped before they were done. Instead of exiting the program
+// from wherever this happens, we return it all the way back up to main(), which exits with the
+// right code once the summary has been shown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Quit {
+    /// There is nothing left to read (e.g. the user pressed Ctrl-D)
+    Closed,
+    /// The user pressed Ctrl-C
+    Interrupted,
+}
+
+impl Quit {
+    fn exit_code(self) -> i32 {
+        match self {
+            // Running out of input is how a session normally ends when the moves are piped in
+            Quit::Closed => 0,
+            // By convention, programs stopped with Ctrl-C exit with the code 130
+            Quit::Interrupted => 130,
+        }
+    }
+}
+
+thread_local! {
+    // Where every line the players type comes from. It starts out empty and is filled in at the
+    // start of main(), after we know which kind of input to use. Each thread has its own, which
+    // lets the tests give every session they play its own lines to read.
+    static INPUT: RefCell<Option<Box<dyn Input>>> = RefCell::new(None);
+    // What to do when the input ends (see `--on-eof`), and the language to ask in. The tests
+    // always quit, just like running out of piped input does.
+    static ON_EOF: Cell<(OnEof, Lang)> = const { Cell::new((OnEof::Quit, Lang::English)) };
+}
+
+// How long the demo waits between moves unless `--delay` says otherwise
+const DEMO_DELAY: Duration = Duration::from_millis(800);
+// The result of each demo game stays on the screen for this many times as long as a move does
+const DEMO_RESULT_PAUSE: u32 = 4;
+// The entrants of a tournament unless `--entrants` says otherwise: one of each difficulty
+const DEFAULT_ENTRANTS: &str = "easy,medium,perfect";
+// The width and height of PNG and GIF images in pixels unless `--image-size` says otherwise
+const DEFAULT_IMAGE_SIZE: u32 = 600;
+// How long each position is shown in a GIF unless `--frame-delay` says otherwise
+const DEFAULT_FRAME_DELAY: Duration = Duration::from_millis(1000);
+// The Elo differences that the arena decides between unless `--sprt` says otherwise: is the first
+// player no better than the second, or at least 20 points better?
+const DEFAULT_SPRT: Sprt = Sprt {elo0: 0.0, elo1: 20.0};
+// The most games the arena plays unless `--max-games` says otherwise. Two players that always tie
+// never give the test anything to decide on, so it has to stop somewhere.
+const DEFAULT_MAX_GAMES: u32 = 1000;
+// How often the arena shows how the games are going
+const ARENA_PROGRESS_GAMES: u32 = 100;
+// How many moves deep an exported game tree goes unless `--depth` says otherwise. Even with the
+// positions that only differ by turning or flipping drawn once, there are 54 positions up to
+// three moves deep and over 700 in the whole tree.
+const DEFAULT_TREE_DEPTH: u32 = 2;
+// How many games a simulation plays unless `--games` says otherwise
+const DEFAULT_SIMULATION_GAMES: u32 = 1000;
+// How often a simulation shows how the games are going
+const SIMULATION_PROGRESS_GAMES: u32 = 10000;
+// How often `watch` checks the file for new moves
+const WATCH_INTERVAL: Duration = Duration::from_millis(250);
+// How well the computer plays for a player who didn't move before `--idle-timeout`: well enough
+// that the move makes sense, without playing the rest of the game for them perfectly
+const IDLE_DIFFICULTY: Difficulty = Difficulty::Medium;
+// How long the chat bots wait before connecting again after losing their connection
+#[cfg(any(feature = "discord", feature = "irc"))]
+const BOT_RECONNECT_DELAY: Duration = Duration::from_secs(5);
+// The nickname of the IRC bot unless `--nick` says otherwise
+#[cfg(feature = "irc")]
+const DEFAULT_NICK: &str = "tictactoe";
+
+// The main function is where Rust starts running our program from. No code is allowed outside of
+// functions so that you can rely on the code in main() running first.
+fn main() {
+    // Preferences saved in an earlier session fill in the options that aren't given this time.
+    // Not being able to read them shouldn't stop anyone from playing, so we only warn about it.
+    let prefs_path = prefs::path();
+    let prefs = match prefs_path {
+        Some(ref path) => match Prefs::load(path) {
+            Ok((prefs, recovery)) => {
+                // Logging hasn't started yet, so this is only shown
+                if let Some(ref recovery) = recovery {
+                    let lang = prefs.lang.unwrap_or_else(Lang::from_env);
+                    eoutln!("{}", lang.text(Message::FileRecovered {path, recovery}));
+                }
+                prefs
+            },
+            Err(error) => {
+                eoutln!("{}", Lang::from_env().text(Message::PrefsFailed {path, error: &error}));
+                Prefs::default()
+            },
+        },
+        None => Prefs::default(),
+    };
+
+    // The first argument is always the name of the program itself, so we skip it
+    let options = match Options::parse(env::args().skip(1), &prefs) {
+        Ok(options) => options,
+        Err(err) => {
+            // The options couldn't be read, but the language might still be on the command line.
+            // Otherwise, the preferences or the environment decide which language to use.
+            let args: Vec<_> = env::args().skip(1).collect();
+            let lang = requested_lang(&args).or(prefs.lang).unwrap_or_else(Lang::from_env);
+            // A program that asked for JSON gets the error as JSON too
+            if args.windows(2).any(|pair| pair[0] == "--output" && pair[1] == "json") {
+                JsonRenderer::default().error((&err).into(), &lang.text(Message::Options(&err)));
+            }
+            else {
+                eoutln!("{}", lang.text(Message::Options(&err)));
+            }
+            // By convention, an exit code of 2 means that the program was used incorrectly
+            process::exit(2);
+        },
+    };
+
+    // The language from the command line takes priority over the one from the environment
+    let lang = options.lang.unwrap_or_else(Lang::from_env);
+    // Everything from here on reads and writes moves in the style that the player chose
+    notation::set_style(options.coordinates.unwrap_or_default());
+
+    // Logging starts as early as it can, so that everything after this point can be logged
+    if let Some(ref path) = options.log_file {
+        if let Err(error) = logging::to_file(path, options.log_level) {
+            eoutln!("{}", lang.text(Message::LogFailed {path, error: &error}));
+            process::exit(2);
+        }
+    }
+    else if let Some(level) = options.log_level {
+        logging::to_stderr(level);
+    }
+    bus::subscribe(logging::Games);
+    if !options.notify.is_empty() {
+        bus::subscribe(Notifier::new(options.notify.clone(), lang));
+    }
+    if let Some(ref path) = options.broadcast {
+        let theme = options.theme.unwrap_or_default();
+        bus::subscribe(Broadcaster::new(path.clone(), options.broadcast_delay, lang, theme));
+    }
+    info!(version = env!("CARGO_PKG_VERSION"), "started");
+
+    if options.save_prefs {
+        match prefs_path {
+            Some(ref path) => match Prefs::from_options(&options, &prefs).save(path) {
+                Ok(()) => {
+                    debug!(path = %path.display(), "saved the preferences");
+                    eoutln!("{}", lang.text(Message::PrefsSaved(path)))
+                },
+                Err(error) => {
+                    warn!(path = %path.display(), %error, "failed to save the preferences");
+                    eoutln!("{}", lang.text(Message::PrefsFailed {path, error: &error}))
+                },
+            },
+            None => eoutln!("{}", lang.text(Message::NoPrefsDir)),
+        }
+    }
+
+    // Everything after this point is recorded, so that the whole game can be replayed
+    if let Some(ref path) = options.record_cast {
+        if let Err(error) = cast::start(path) {
+            eoutln!("{}", lang.text(Message::CastFailed {path, error: &error}));
+            process::exit(2);
+        }
+    }
+
+    // Everything random comes from this seed. Showing it lets anyone play the same games again.
+    let seed = options.seed.unwrap_or_else(random::time_seed);
+    random::seed_shared(seed);
+    debug!(seed, "seeded the random numbers");
+
+    // `map` converts the Option<PathBuf> into an Option<Archive> only if a path was provided.
+    // Games played by homebrew rules would count towards the stats, ratings and openings of the
+    // normal game, so they are never saved.
+    let archive = if options.archive.is_some() && options.rules != Rules::Classic {
+        eoutln!("{}", lang.text(Message::RulesNotArchived(options.rules)));
+        None
+    }
+    else {
+        options.archive.clone().map(Archive::new)
+    };
+
+    // Plugins are loaded before the renderer is chosen, since it may be one of them. Anything
+    // that went wrong is reported once there is a renderer to report it with.
+    let skipped = load_plugins(&options);
+
+    // A Box lets us choose which renderer to use while the program is running. `dyn Renderer`
+    // means "some type that implements Renderer" and Rust figures out which one at runtime.
+    // JSON output is for other programs, so it takes priority over the renderers meant for people.
+    // A renderer plugin that isn't there gets its error printed as JSON instead.
+    let renderer: Box<dyn Renderer> = if options.output == OutputFormat::Json {
+        let plugin = options.renderer_plugin.as_ref().and_then(|name| plugin::renderer(name).ok());
+        Box::new(plugin.map_or_else(JsonRenderer::default, JsonRenderer::to_plugin))
+    }
+    else if options.screen_reader {
+        Box::new(SpokenRenderer::new(lang))
+    }
+    else {
+        let theme = options.theme.unwrap_or_default();
+        // The glyphs of the theme and the way the screen is cleared depend on the terminal
+        let capabilities = terminal::setup();
+        Box::new(GridRenderer::new(lang, theme, options.layout).with_capabilities(capabilities))
+    };
+
+    // Blindfold mode hides the board of whichever renderer the players are using. Programs reading
+    // the JSON output have no memory to train, so the board is never hidden from them.
+    let renderer: Box<dyn Renderer> = if options.blindfold && options.output != OutputFormat::Json {
+        Box::new(BlindfoldRenderer::new(renderer, lang))
+    }
+    else {
+        renderer
+    };
+    // The coach goes on the outside, so that it comments on every move no matter how the board
+    // is shown
+    let renderer: Box<dyn Renderer> = match options.coach {
+        Some(level) => Box::new(CoachRenderer::new(renderer, level, lang)),
+        None => renderer,
+    };
+    let renderer: Box<dyn Renderer> = if options.announce_forced.is_empty() {
+        renderer
+    }
+    else {
+        Box::new(ForcedRenderer::new(renderer, options.announce_forced.clone(), lang))
+    };
+    if !check_plugins(&options, &skipped, &*renderer, lang) {
+        process::exit(2);
+    }
+
+    // The stats, ratings, openings and exports are worked out from the archive, so there is
+    // nothing to play. Options::parse makes sure that there is an archive to read.
+    if options.stats || options.ratings || options.openings || options.export.is_some() {
+        if let Some(ref archive) = archive {
+            let code = if let Some(table) = options.export {
+                let format = options.export_format.unwrap_or(ExportFormat::Csv);
+                export_table(archive, table, format, &*renderer, lang)
+            }
+            else if let Some((ref a, ref b)) = options.versus {
+                show_head_to_head(archive, (a, b), &*renderer, lang)
+            }
+            else if options.stats {
+                show_stats(archive, &*renderer, lang)
+            }
+            else if options.ratings {
+                show_ratings(archive, &*renderer, lang)
+            }
+            else {
+                show_openings(archive, &*renderer, lang)
+            };
+            process::exit(code);
+        }
+    }
+
+    // Verifying an archive only reads it
+    if let Some(ref path) = options.verify {
+        process::exit(show_verification(path, &*renderer, lang));
+    }
+
+    // Checking one only changes it when asked to fix it
+    if let Some(ref path) = options.check {
+        process::exit(check_archive(path, options.fix, &*renderer, lang));
+    }
+    if let Some((ref query, ref path)) = options.tagged {
+        process::exit(show_tagged(query, path, &*renderer, lang));
+    }
+    if let Some(ref path) = options.audit {
+        process::exit(show_audit(path, &prefs.engines, &*renderer, lang));
+    }
+
+    // Neither does finding puzzles, which looks through the archive's games if there is one
+    if options.generate_puzzles {
+        process::exit(generate_puzzles(archive.as_ref(), &*renderer, lang));
+    }
+
+    // The arena only has computer players, so it doesn't need any input either
+    if let Some((ref first, ref second)) = options.engines {
+        let sprt = options.sprt.unwrap_or(DEFAULT_SPRT);
+        let max_games = options.max_games.unwrap_or(DEFAULT_MAX_GAMES);
+        renderer.info(&lang.text(Message::Seed(seed)));
+        play_arena((first, second), sprt, max_games, options.adjudicate, &*renderer, lang);
+        return;
+    }
+
+    // Training data comes from the archive or from simulated games, so it doesn't either
+    if let Some(ref path) = options.export_training {
+        let games = match archive {
+            Some(ref archive) => match read_archive(archive, &*renderer, lang) {
+                Some(games) => games.into_iter().map(|archived| archived.game).collect(),
+                None => process::exit(2),
+            },
+            None => {
+                let (x, o) = simulation_engines(&options);
+                let games = options.games.unwrap_or(DEFAULT_SIMULATION_GAMES);
+                renderer.info(&lang.text(Message::Seed(seed)));
+                simulate::games(engine_profile(&x), engine_profile(&o), 0..games, seed)
+            },
+        };
+        let format = options.export_format.unwrap_or(ExportFormat::Csv);
+        process::exit(export_training(&games, path, format, options.augment, &*renderer, lang));
+    }
+
+    // Neither do simulations
+    if options.simulate {
+        let (x, o) = simulation_engines(&options);
+        let games = options.games.unwrap_or(DEFAULT_SIMULATION_GAMES);
+        renderer.info(&lang.text(Message::Seed(seed)));
+        play_simulation((&x, &o), games, seed, options.adjudicate, &*renderer, lang);
+        return;
+    }
+
+    // Nor do the chat bots, since everything the players type comes from the chat. The computer
+    // plays in every game there.
+    if options.discord {
+        let difficulty = options.difficulty.unwrap_or(Difficulty::Perfect);
+        let archive = seed_session(archive, seed, &*renderer, lang);
+        process::exit(run_discord(difficulty, options.coach, archive, &*renderer, lang));
+    }
+    if let Some(ref server) = options.irc {
+        let archive = seed_session(archive, seed, &*renderer, lang);
+        process::exit(run_irc(server, &options, archive, &*renderer, lang));
+    }
+
+    // In JSON mode stdout is only for JSON, so the prompts are shown on stderr instead
+    let prompts_to_stderr = options.output == OutputFormat::Json;
+    set_input(input::open(prompts_to_stderr));
+
+    let settings = Settings::from_options(&options);
+
+    // A controller drives the engine with GTP commands until it says `quit` or stops sending them
+    if options.gtp {
+        let difficulty = options.difficulty.unwrap_or(Difficulty::Perfect);
+        process::exit(run_gtp(difficulty));
+    }
+
+    // Controllers don't answer questions, but people do. That only matters from here on, since
+    // everything above either doesn't read any input or is driven by another program.
+    let on_eof = options.on_eof.unwrap_or_else(|| OnEof::default_for(input::is_interactive()));
+    ON_EOF.with(|current| current.set((on_eof, lang)));
+
+    // Counting the game tree, enumerating positions, profiling the search and exporting the game
+    // tree all start from the position that the moves lead to, if there are any
+    if options.perft.is_some() || options.enumerate || options.profile || options.export_tree {
+        let moves = options.script.as_ref().map(|script| read_script(script, lang))
+            .unwrap_or_default();
+        let game = match script_position(&moves, &settings, lang) {
+            Ok(game) => game,
+            Err(error) => {
+                error.report(&*renderer);
+                process::exit(1);
+            },
+        };
+        process::exit(if let Some(depth) = options.perft {
+            show_perft(&game, depth, &*renderer, lang)
+        }
+        else if options.enumerate {
+            show_enumeration(&game, options.symmetry, &*renderer, lang)
+        }
+        else if options.profile {
+            show_profile(&game, options.folded.as_deref(), &*renderer, lang)
+        }
+        else {
+            let depth = options.depth.unwrap_or(DEFAULT_TREE_DEPTH);
+            export_tree(&game, depth, options.dot.as_deref(), &*renderer, lang)
+        });
+    }
+
+    // Comparing games only reads them
+    if let Some((ref first, ref second)) = options.diff {
+        process::exit(show_diff((first, second), &settings, &*renderer, lang));
+    }
+
+    // Sharing a game (or showing the QR code of it) only needs the moves, so nothing is played
+    if options.share || options.export_qr {
+        let game = match options.from_code {
+            Some(ref game) => game.clone(),
+            None => {
+                let script = options.script.as_ref().expect("sharing requires --moves");
+                match script_position(&read_script(script, lang), &settings, lang) {
+                    Ok(game) => game,
+                    Err(error) => {
+                        error.report(&*renderer);
+                        process::exit(1);
+                    },
+                }
+            },
+        };
+        if options.export_qr {
+            let size = options.image_size.unwrap_or(DEFAULT_IMAGE_SIZE);
+            let png = options.export_png.as_deref();
+            process::exit(export_qr(&game, &settings, png, size, &*renderer, lang));
+        }
+        show_code(&game, &settings, &*renderer, lang);
+        return;
+    }
+
+    // When the moves are provided up front, we play them all without asking for any input and
+    // then exit. This lets the program be used from shell scripts and tests.
+    if let Some(ref script) = options.script {
+        let moves = read_script(script, lang);
+        // `as_deref` turns each Option<PathBuf> into the Option<&Path> that Exports expects
+        let exports = Exports {
+            svg: options.export_svg.as_deref(),
+            png: options.export_png.as_deref(),
+            gif: options.export_gif.as_deref(),
+            image_size: options.image_size.unwrap_or(DEFAULT_IMAGE_SIZE),
+            frame_delay: options.frame_delay.unwrap_or(DEFAULT_FRAME_DELAY),
+            theme: options.theme.unwrap_or_default(),
+        };
+        let code = play_script(&moves, &settings, archive.as_ref(), &exports, &*renderer, lang);
+        process::exit(code);
+    }
+
+    // Watching a game only shows what is written to the file, so it doesn't need any input. It
+    // goes on until Ctrl-C is pressed.
+    if let Some(ref path) = options.watch {
+        process::exit(watch_game(path, &settings, &*renderer, lang));
+    }
+
+    // The tutorial is a single guided game, so there is no point asking to play again afterwards
+    if options.tutorial {
+        if let Err(quit) = play_tutorial(&settings.players, &*renderer, lang) {
+            quit_program(quit, &*renderer, lang);
+        }
+        return;
+    }
+
+    // Once every puzzle is solved there is nothing left to play
+    if options.puzzle {
+        let puzzles = match load_puzzles(options.puzzles.as_deref(), &*renderer, lang) {
+            Some(puzzles) => puzzles,
+            None => process::exit(2),
+        };
+        // The computer picks its defenses at random
+        seed_session(None, seed, &*renderer, lang);
+        if let Err(quit) = play_puzzles(&puzzles, &settings.players, &*renderer, lang) {
+            quit_program(quit, &*renderer, lang);
+        }
+        return;
+    }
+
+    // There is only one puzzle a day, so the program ends once it is solved
+    if options.daily {
+        seed_session(None, seed, &*renderer, lang);
+        let mut prefs = prefs;
+        if let Err(quit) = play_daily(&mut prefs, prefs_path.as_deref(), &settings.players,
+            &*renderer, lang) {
+            quit_program(quit, &*renderer, lang);
+        }
+        return;
+    }
+
+    // The run is over once the gauntlet is won, so there is nothing to ask afterwards
+    if options.speedrun {
+        // A preferred difficulty that can't be beaten (see Options::parse) is left for the other
+        // modes, and the run is against the easy computer instead
+        let difficulty = options.difficulty.filter(|&difficulty| speedrun::can_race(difficulty))
+            .unwrap_or(Difficulty::Easy);
+        // Players can press Ctrl-C to save the game they are playing, just like in a normal game
+        interrupt::install();
+        let archive = seed_session(archive, seed, &*renderer, lang);
+        let mut prefs = prefs;
+        if let Err(quit) = play_speedrun(&mut prefs, prefs_path.as_deref(), &settings, difficulty,
+            archive.as_ref(), &*renderer, lang) {
+            quit_program(quit, &*renderer, lang);
+        }
+        return;
+    }
+
+    // The explorer only looks at games, so it ends when the player is done with it
+    if options.explore {
+        let games = match archive {
+            Some(ref archive) => match read_archive(archive, &*renderer, lang) {
+                Some(games) => games,
+                None => process::exit(2),
+            },
+            None => Vec::new(),
+        };
+        let explorer = Explorer::new(&games);
+        if let Err(quit) = explore_game(&explorer, archive.is_some(), &settings.players,
+            &*renderer, lang) {
+            quit_program(quit, &*renderer, lang);
+        }
+        return;
+    }
+
+    // The game played from the position is a one-off, so there is no point asking to play again
+    if options.setup {
+        if let Err(quit) = play_setup(&settings, &*renderer, lang) {
+            quit_program(quit, &*renderer, lang);
+        }
+        return;
+    }
+
+    // Like a game played from a position, a game from a code is a one-off
+    if let Some(ref game) = options.from_code {
+        if let Err(quit) = play_from_code(game.clone(), &settings, &*renderer, lang) {
+            quit_program(quit, &*renderer, lang);
+        }
+        return;
+    }
+
+    // Nobody plays in the demo, so there's nothing to ask once it is over
+    if options.demo {
+        let difficulty = options.difficulty.unwrap_or(Difficulty::Medium);
+        let delay = options.delay.unwrap_or(DEMO_DELAY);
+        play_demo(&settings.players, difficulty, delay, options.games, seed, &*renderer, lang);
+        return;
+    }
+
+    // A tournament is over once every game has been played, so there is no point asking to play
+    // again afterwards
+    if options.tournament {
+        let entrants = options.entrants.clone().unwrap_or_else(|| {
+            tournament::parse_entrants(DEFAULT_ENTRANTS, &[])
+                .expect("default entrants should be valid")
+        });
+        // People taking part in the tournament can press Ctrl-C to save their game and quit
+        interrupt::install();
+        let archive = seed_session(archive, seed, &*renderer, lang);
+        let result = match options.swiss {
+            Some(rounds) => {
+                play_swiss(&entrants, rounds, seed, &settings, archive.as_ref(), &*renderer, lang)
+            },
+            None => {
+                play_tournament(&entrants, seed, &settings, archive.as_ref(), &*renderer, lang)
+            },
+        };
+        if let Err(quit) = result {
+            quit_program(quit, &*renderer, lang);
+        }
+        return;
+    }
+
+    if let Some(shows) = settings.blindfold_shows {
+        renderer.info(&lang.text(Message::BlindfoldIntro {shows}));
+        renderer.info("");
+    }
+    let giver_uses_random = options.simul.is_some()
+        && options.giver.as_ref().is_some_and(Computer::uses_random);
+    let archive = if settings.uses_random() || giver_uses_random {
+        seed_session(archive, seed, &*renderer, lang)
+    }
+    else {
+        archive
+    };
+
+    // From now on, pressing Ctrl-C gives the players a chance to save their game before quitting
+    interrupt::install();
+    // Every game played from here on is added to the summary shown when the program exits
+    summary::start(lang, options.output == OutputFormat::Json, options.summary_csv.clone());
+
+    // The summary is shown however the session ends, even if the players quit part way through.
+    // A club night or a simul is over once every game is, so there is no point asking to play
+    // again.
+    let result = if let Some(ref opponents) = options.simul {
+        let theme = options.theme.unwrap_or_default();
+        let giver = options.giver.clone();
+        play_simul(opponents, giver, &settings, theme, archive.as_ref(), &*renderer, lang)
+    }
+    else if options.club {
+        play_club(&settings, archive.as_ref(), &*renderer, lang)
+    }
+    else {
+        play_session(settings, archive.as_ref(), &*renderer, lang)
+    };
+    summary::finish();
+    if let Err(quit) = result {
+        info!(?quit, "session ended early");
+        quit_program(quit, &*renderer, lang);
+    }
+}
+
+// Computer players pick their moves with the shared random numbers (see random.rs), so a session
+// that they play in can only be played again the same way with its seed. The seed is shown before
+// the first game and saved with every game. Returns the archive to save the games to.
+fn seed_session(archive: Option<Archive>, seed: u64, renderer: &dyn Renderer,
+    lang: Lang) -> Option<Archive> {
+
+    renderer.info(&lang.text(Message::Seed(seed)));
+    renderer.info("");
+    archive.map(|archive| archive.with_seed(seed))
+}
+
+// The language chosen with `--lang` in arguments that couldn't all be read, if there is one. Just
+// like when they are read, the last one counts.
+fn requested_lang(args: &[String]) -> Option<Lang> {
+    args.windows(2).rev().filter(|pair| pair[0] == "--lang")
+        .find_map(|pair| Lang::from_code(&pair[1]))
+}
+
+// Ends the program after the session was cut short. Programs reading the output are told when that
+// was because the input ended, and with `--on-eof fail` it counts as an error.
+fn quit_program(quit: Quit, renderer: &dyn Renderer, lang: Lang) -> ! {
+    if quit == Quit::Closed {
+        renderer.input_closed();
+        if ON_EOF.with(Cell::get).0 == OnEof::Fail {
+            renderer.error((&ReadError::Closed).into(), &lang.text(Message::InputClosed));
+            process::exit(1);
+        }
+    }
+    process::exit(quit.exit_code())
+}
+
+// Loads the plugins in the directory from `--plugins`, or the default one. Returns the ones that
+// were skipped, along with the reason.
+#[cfg(feature = "plugins")]
+fn load_plugins(options: &Options) -> Vec<(PathBuf, PluginError)> {
+    let dir = match options.plugins.clone().or_else(plugin::default_dir) {
+        Some(dir) => dir,
+        None => return Vec::new(),
+    };
+    let skipped = plugin::load_all(&dir);
+    for plugin in plugin::all() {
+        debug!(name = %plugin.name, path = %plugin.path.display(), renderer = plugin.is_renderer(),
+            player = plugin.is_player(), "loaded a plugin");
+    }
+    info!(dir = %dir.display(), loaded = plugin::all().len(), skipped = skipped.len(),
+        "loaded the plugins");
+    skipped
+}
+
+// Without the `plugins` feature, there is nothing to load (see check_plugins)
+#[cfg(not(feature = "plugins"))]
+fn load_plugins(_options: &Options) -> Vec<(PathBuf, PluginError)> {
+    Vec::new()
+}
+
+// The plugins that the command line asks for, or why they can't be used
+fn requested_plugins(options: &Options) -> Vec<Result<&'static plugin::Plugin, PluginError>> {
+    let renderers = options.renderer_plugin.iter().map(|name| plugin::renderer(name));
+    let engines = options.entrants.iter().flatten().map(|entrant| &entrant.engine)
+        .chain(Some(&options.giver));
+    let players = engines.filter_map(|engine| match *engine {
+        Some(Computer::Plugin(ref name)) => Some(plugin::player(name)),
+        _ => None,
+    });
+    renderers.chain(players).collect()
+}
+
+// Reports the plugins that were skipped, and makes sure that every plugin that the command line
+// asks for is there. Returns false if one of them isn't.
+#[cfg(feature = "plugins")]
+fn check_plugins(options: &Options, skipped: &[(PathBuf, PluginError)], renderer: &dyn Renderer,
+    lang: Lang) -> bool {
+
+    for (path, error) in skipped {
+        warn!(path = %path.display(), %error, "skipped a plugin");
+        renderer.error(error.into(), &lang.text(Message::PluginSkipped {path, error}));
+    }
+    match requested_plugins(options).into_iter().find_map(Result::err) {
+        Some(error) => {
+            renderer.error((&error).into(), &lang.text(Message::PluginMissing(&error)));
+            false
+        },
+        None => true,
+    }
+}
+
+// Without the `plugins` feature, asking for a plugin (or where to find them) is an error
+#[cfg(not(feature = "plugins"))]
+fn check_plugins(options: &Options, _skipped: &[(PathBuf, PluginError)],
+    renderer: &dyn Renderer, lang: Lang) -> bool {
+
+    if options.plugins.is_some() || !requested_plugins(options).is_empty() {
+        renderer.error(error::PLUGINS_UNAVAILABLE, &lang.text(Message::PluginsUnavailable));
+        return false;
+    }
+    true
+}
+
+// Replaces the input that every prompt reads from (see INPUT above)
+fn set_input(input: Box<dyn Input>) {
+    INPUT.with(|current| *current.borrow_mut() = Some(input));
+}
+
+// Plays rounds until the players decide to quit. The session owns the settings so that they can
+// be reused (or changed) between games.
+fn play_session(mut settings: Settings, archive: Option<&Archive>, renderer: &dyn Renderer,
+    lang: Lang) -> Result<(), Quit> {
+
+    // The score of the match so far, carried from one rematch to the next
+    let mut score = Score::default();
+    loop {
+        // The `?` operator returns the error right away if there is one, which is how quitting
+        // part way through a round ends the whole session
+        let games = play_round(&settings, archive, renderer, lang)?;
+        let game = games.last().expect("every round has at least one game");
+
+        loop {
+            match prompt_play_again(renderer, lang)? {
+                // Reviewing the game doesn't start another one, so we ask again afterwards
+                PlayAgain::Review => {
+                    review_game(game, &settings.players, renderer, lang)?;
+                    continue;
+                },
+                PlayAgain::Share => {
+                    show_code(game, &settings, renderer, lang);
+                    continue;
+                },
+                PlayAgain::Rematch => {
+                    for game in &games {
+                        score.add(game.winner().expect("every game of a round is finished"));
+                    }
+                    // The players take their wins with them when they trade pieces
+                    settings.players.swap();
+                    score = score.swapped();
+                    settings.rematch = Some(settings.rematch.map_or(1, |rematch| rematch + 1));
+                    renderer.info("");
+                    renderer.info(&lang.text(Message::SeriesScore {
+                        x: settings.players.name(Piece::X),
+                        o: settings.players.name(Piece::O),
+                        score,
+                    }));
+                    break;
+                },
+                PlayAgain::SameSettings => {},
+                PlayAgain::SwapPieces => settings.players.swap(),
+                PlayAgain::ChangeSettings => settings = prompt_settings(settings, renderer, lang)?,
+                PlayAgain::Quit => return Ok(()),
+            }
+            // Anything other than a rematch starts a new match
+            score = Score::default();
+            settings.rematch = None;
+            break;
+        }
+        renderer.info("");
+    }
+}
+
+// Gets the moves of a script, reading them from a file if needed. The program can't go on without
+// them, so it exits if the file can't be read.
+fn read_script(script: &MoveScript, lang: Lang) -> String {
+    match *script {
+        MoveScript::Inline(ref moves) => moves.clone(),
+        // `read_to_string` reads the whole file into a String in one go
+        MoveScript::File(ref path) => match fs::read_to_string(path) {
+            Ok(moves) => moves,
+            Err(error) => {
+                eoutln!("{}", lang.text(Message::MovesFileFailed {path, error: &error}));
+                process::exit(2);
+            },
+        },
+    }
+}
+
+// The images of the final board to save after playing a script
+struct Exports<'a> {
+    svg: Option<&'a Path>,
+    png: Option<&'a Path>,
+    /// An animation of every position in the game
+    gif: Option<&'a Path>,
+    /// The width and height of the PNG and GIF images in pixels
+    #[cfg_attr(not(feature = "images"), allow(dead_code))]
+    image_size: u32,
+    /// How long each position is shown in the animation
+    #[cfg_attr(not(feature = "images"), allow(dead_code))]
+    frame_delay: Duration,
+    /// The colors of every image
+    theme: Theme,
+}
+
+// Plays a game using the moves in `script` and prints the final board and result. Moves can be
+// separated by spaces, newlines or commas. Afterwards, an image of the final board is saved for
+// each path in `exports`. Returns the exit code for the program: 0 if every move was played, 1 if
+// one of the moves was invalid or illegal, or 2 if an image couldn't be saved.
+fn play_script(script: &str, settings: &Settings, archive: Option<&Archive>, exports: &Exports,
+    renderer: &dyn Renderer, lang: Lang) -> i32 {
+
+    let players = &settings.players;
+    let mut game = settings.new_game(settings.first_piece);
+    // Every position of the game, for the frames of the animation
+    let mut positions = vec![game.clone()];
+
+    for (i, notation) in script_moves(script).enumerate() {
+        match play_script_move(&mut game, i + 1, notation, players, lang) {
+            Ok((row, col)) => {
+                renderer.move_made(&game, players, row, col);
+                if exports.gif.is_some() {
+                    positions.push(game.clone());
+                }
+            },
+            Err(error) => {
+                renderer.board(&GameView::new(&game, players, Variant::Classic));
+                error.report(renderer);
+                return 1;
+            },
+        }
+    }
+
+    // Running out of moves before the end of the game isn't an error. It's a handy way to look at
+    // a position part way through a game.
+    let view = GameView::new(&game, players, Variant::Classic);
+    renderer.game_over(&view, &result_message(&game, players, lang));
+
+    // Only finished games are saved, just like when playing interactively
+    if let Some(archive) = archive {
+        if game.is_finished() {
+            save_to_archive(archive, archive.record_game(&game, players), lang);
+        }
+    }
+
+    // The images are only drawn if they are going to be saved
+    let svg = exports.svg.map(|path| (path, render::to_svg(&game, &exports.theme).into_bytes()));
+    #[cfg(feature = "images")]
+    let png = exports.png.map(|path| (path, render::to_png(&game, &exports.theme,
+        exports.image_size)));
+    #[cfg(feature = "images")]
+    let gif = exports.gif.map(|path| (path, render::to_gif(&positions, &exports.theme,
+        exports.image_size, exports.frame_delay)));
+    // Without the `images` feature, SVG is the only kind of image that can be saved
+    #[cfg(not(feature = "images"))]
+    let (png, gif) = match (exports.png, exports.gif) {
+        (None, None) => (None, None),
+        _ => {
+            renderer.error(error::IMAGES_UNAVAILABLE, &lang.text(Message::ImagesUnavailable));
+            return 2;
+        },
+    };
+    for (path, image) in svg.into_iter().chain(png).chain(gif) {
+        match storage::write(path, &image) {
+            Ok(()) => renderer.info(&lang.text(Message::Exported(path))),
+            Err(error) => {
+                let message = lang.text(Message::ExportFailed {path, error: &error});
+                renderer.error(error::EXPORT_FAILED, &message);
+                return 2;
+            },
+        }
+    }
+    0
+}
+
+// Shows the game in the file at `path`, drawing the board again whenever the file changes (see
+// watch.rs). This only stops if the file can't be read, so the returned exit code is always 2.
+fn watch_game(path: &Path, settings: &Settings, renderer: &dyn Renderer, lang: Lang) -> i32 {
+    let players = &settings.players;
+    let mut game = Game::starting_with(settings.first_piece);
+    let mut watch = Watch::default();
+    // The file might not be there yet, if the game hasn't started
+    let mut waiting = false;
+    loop {
+        let update = match watch::poll(&mut watch, &mut game, path) {
+            Ok(update) => update,
+            Err(ref error) if error.kind() == io::ErrorKind::NotFound => {
+                if !waiting {
+                    renderer.info(&lang.text(Message::WatchWaiting(path)));
+                    waiting = true;
+                }
+                None
+            },
+            Err(error) => {
+                let message = lang.text(Message::MovesFileFailed {path, error: &error});
+                renderer.error(error::WATCH_FAILED, &message);
+                return 2;
+            },
+        };
+
+        if let Some(update) = update {
+            waiting = false;
+            debug!(moves = update.moves.len(), restarted = update.restarted, "the file changed");
+            // Clearing the screen before drawing makes the board look like it is changing in
+            // place, just like in the demo
+            renderer.clear();
+            renderer.info(&lang.text(Message::WatchHelp(path)));
+            renderer.info("");
+            // Each new move is announced on the board it was played on
+            let before = game.moves().len() - update.moves.len();
+            for (i, &(row, col)) in update.moves.iter().enumerate() {
+                renderer.move_made(&position_after(&game, before + i + 1), players, row, col);
+            }
+            if game.is_finished() {
+                let view = GameView::new(&game, players, Variant::Classic);
+                renderer.game_over(&view, &result_message(&game, players, lang));
+            }
+            else {
+                renderer.board(&GameView::new(&game, players, Variant::Classic));
+                let player = players.label(game.current_piece());
+                renderer.turn(&game, players, &lang.text(Message::CurrentPiece(&player)));
+                if !update.moves.is_empty() {
+                    bus::publish(Event::TurnArrived {piece: game.current_piece(), players});
+                }
+            }
+            if let Some((number, ref notation)) = update.invalid {
+                let message = lang.text(Message::ScriptInvalidMove {number, notation});
+                renderer.error(error::INVALID_MOVE, &message);
+            }
+        }
+        thread::sleep(WATCH_INTERVAL);
+    }
+}
+
+// A move from a script that couldn't be played, with the error code and message to show
+struct ScriptError {
+    code: ErrorCode,
+    message: String,
+    // Why the move was rejected, for the renderers that explain it (see Rejection). A move after
+    // the end of the game has nothing more to explain.
+    rejected: Option<ScriptRejection>,
+}
+
+// The same as Rejection, but owning what it refers to
+enum ScriptRejection {
+    Invalid(InvalidMove),
+    Occupied { row: usize, col: usize, piece: Piece, since: usize },
+}
+
+impl ScriptError {
+    // Shows the error just like a move that was typed in would be rejected
+    fn report(&self, renderer: &dyn Renderer) {
+        match self.rejected {
+            Some(ScriptRejection::Invalid(InvalidMove(ref input, ref reason))) => {
+                renderer.move_rejected(self.code, &self.message, Rejection::Invalid {input, reason})
+            },
+            Some(ScriptRejection::Occupied {row, col, piece, since}) => {
+                renderer.move_rejected(self.code, &self.message,
+                    Rejection::Occupied {row, col, piece, since})
+            },
+            None => renderer.error(self.code, &self.message),
+        }
+    }
+}
+
+// Plays the move with the given number (counting from 1) from a script. Returns the tile it was
+// played on, or the error to show if it couldn't be played.
+fn play_script_move(game: &mut Game, number: usize, notation: &str, players: &Players,
+    lang: Lang) -> Result<(usize, usize), ScriptError> {
+
+    // The reason is the same one that is given when the move is typed in, along with the number
+    // of the move so that it can be found in the script
+    let rejected = |code, reason: &str, rejected| ScriptError {
+        code,
+        message: lang.text(Message::ScriptMoveRejected {number, message: reason}),
+        rejected: Some(rejected),
+    };
+    let (row, col) = match parse_move(notation) {
+        Ok(tile) => tile,
+        Err(InvalidMove(input, reason)) => {
+            let message = lang.text(Message::InvalidMove {input: &input, reason: &reason});
+            let invalid = ScriptRejection::Invalid(InvalidMove(input, reason));
+            return Err(rejected(error::INVALID_MOVE, &message, invalid));
+        },
+    };
+    match game.make_move(row, col) {
+        Ok(()) => Ok((row, col)),
+        Err(ref error @ MoveError::TileNotEmpty {other_piece, since, ..}) => {
+            let other_player = &players.label(other_piece);
+            let message = lang.text(Message::TileNotEmpty {row, col, other_player, since});
+            let occupied = ScriptRejection::Occupied {row, col, piece: other_piece, since};
+            Err(rejected(error.into(), &message, occupied))
+        },
+        Err(ref error @ MoveError::GameAlreadyOver) => Err(ScriptError {
+            code: error.into(),
+            message: lang.text(Message::ScriptGameOver {number, notation}),
+            rejected: None,
+        }),
+        // parse_move only returns positions that are on the board
+        Err(MoveError::InvalidPosition {row, col}) => {
+            unreachable!("parse_move returned an invalid position ({}, {})", row, col)
+        },
+    }
+}
+
+// The position reached by playing the moves in `script`, without showing anything. If a move can't
+// be played, gives back the error to show.
+fn script_position(script: &str, settings: &Settings, lang: Lang) -> Result<Game, ScriptError> {
+    let mut game = settings.new_game(settings.first_piece);
+    for (i, notation) in script_moves(script).enumerate() {
+        play_script_move(&mut game, i + 1, notation, &settings.players, lang)?;
+    }
+    Ok(game)
+}
+
+// Answers GTP commands until the controller says `quit` or stops sending them. Returns the exit
+// code for the program.
+fn run_gtp(difficulty: Difficulty) -> i32 {
+    let mut engine = gtp::Engine::new(difficulty);
+    loop {
+        // A controller doesn't need any prompts, so there is nothing to show before each command
+        let line = match read_line("") {
+            Ok(line) => line,
+            Err(quit) => return quit.exit_code(),
+        };
+        debug!(command = %line, "received a GTP command");
+        if let Some(response) = engine.respond(&line, &mut random::shared()) {
+            out!("{}", response.text);
+            if response.quit {
+                return 0;
+            }
+        }
+    }
+}
+
+// Hosts games in Discord channels until Ctrl-C is pressed or Discord refuses to let the bot
+// connect. The token of the bot comes from the environment, since anything on the command line can
+// be seen by everyone else using the same computer. Returns the exit code for the program.
+#[cfg(feature = "discord")]
+fn run_discord(difficulty: Difficulty, coach: Option<Level>, archive: Option<Archive>,
+    renderer: &dyn Renderer, lang: Lang) -> i32 {
+
+    let token = match env::var("DISCORD_TOKEN") {
+        Ok(ref token) if !token.trim().is_empty() => token.trim().to_string(),
+        _ => {
+            renderer.error(error::NO_TOKEN, &lang.text(Message::DiscordNoToken));
+            return 2;
+        },
+    };
+    interrupt::install();
+    let client = gateway::Client::new(token);
+    // The games carry on after the bot connects again, so the bot outlives every connection
+    let mut bot = bots::Bot::new(bots::Chat::Discord, difficulty, archive, lang).with_coach(coach);
+    loop {
+        let error = client.listen(|event| match event {
+            gateway::Event::Ready(user) => {
+                renderer.info(&lang.text(Message::DiscordConnected(&user.name)));
+            },
+            gateway::Event::Message {channel, author, content, mentions} => {
+                let message = bots::ChatMessage {
+                    channel: &channel,
+                    author: &author,
+                    content: &content,
+                    mentions: &mentions,
+                };
+                for reply in bot.handle(&message, &mut random::shared()) {
+                    // A message that couldn't be sent is lost, but the game can carry on
+                    let result = match reply {
+                        bots::Reply::Send(text) => {
+                            client.send_message(&channel, &text).map(|_| ())
+                        },
+                        bots::Reply::SendBoard(text) => client.send_message(&channel, &text)
+                            .map(|message| bot.board_sent(&channel, message)),
+                        bots::Reply::EditBoard {message, text} => {
+                            client.edit_message(&channel, &message, &text)
+                        },
+                    };
+                    if let Err(error) = result {
+                        warn!(%channel, %error, "failed to send a message to Discord");
+                    }
+                }
+            },
+        });
+        let code = ErrorCode::from(&error);
+        match error {
+            gateway::GatewayError::Interrupted => return Quit::Interrupted.exit_code(),
+            gateway::GatewayError::Rejected(reason) => {
+                renderer.error(code, &lang.text(Message::DiscordRejected(&reason)));
+                return 2;
+            },
+            gateway::GatewayError::Disconnected(reason) => {
+                warn!(%reason, "lost the connection to Discord");
+                renderer.error(code, &lang.text(Message::DiscordDisconnected(&reason)));
+                thread::sleep(BOT_RECONNECT_DELAY);
+            },
+        }
+    }
+}
+
+// Without the `discord` feature, there is no way to connect to Discord
+#[cfg(not(feature = "discord"))]
+fn run_discord(_difficulty: Difficulty, _coach: Option<Level>, _archive: Option<Archive>,
+    renderer: &dyn Renderer, lang: Lang) -> i32 {
+
+    renderer.error(error::DISCORD_UNAVAILABLE, &lang.text(Message::DiscordUnavailable));
+    2
+}
+
+// Hosts games in the channels of an IRC server until Ctrl-C is pressed or the server won't accept
+// the bot's nickname. The nickname, the channels and how the bot plays come from the options.
+// Returns the exit code for the program.
+#[cfg(feature = "irc")]
+fn run_irc(server: &str, options: &Options, archive: Option<Archive>, renderer: &dyn Renderer,
+    lang: Lang) -> i32 {
+
+    let client = irc::Client::new(
+        server.to_string(),
+        options.nick.clone().unwrap_or_else(|| DEFAULT_NICK.to_string()),
+        options.channels.clone().unwrap_or_default(),
+    );
+    let difficulty = options.difficulty.unwrap_or(Difficulty::Perfect);
+    interrupt::install();
+    let mut bot = bots::Bot::new(bots::Chat::Irc, difficulty, archive, lang)
+        .with_coach(options.coach);
+    loop {
+        let error = client.listen(|event, sender| match event {
+            irc::Event::Welcome => {
+                renderer.info(&lang.text(Message::IrcConnected {
+                    server: client.server(),
+                    nick: client.nick(),
+                }));
+            },
+            irc::Event::Message {channel, author, content} => {
+                let message = bots::ChatMessage {
+                    channel: &channel,
+                    author: &author,
+                    content: &content,
+                    mentions: &[],
+                };
+                // Messages can't be edited on IRC, so every board is a new message
+                for reply in bot.handle(&message, &mut random::shared()) {
+                    let text = match reply {
+                        bots::Reply::Send(text) | bots::Reply::SendBoard(text) => text,
+                        bots::Reply::EditBoard {text, ..} => text,
+                    };
+                    if let Err(error) = sender.say(&channel, &text) {
+                        warn!(%channel, %error, "failed to send a message to IRC");
+                    }
+                }
+            },
+        });
+        let code = ErrorCode::from(&error);
+        match error {
+            irc::IrcError::Interrupted => return Quit::Interrupted.exit_code(),
+            irc::IrcError::NickInUse => {
+                renderer.error(code, &lang.text(Message::IrcNickInUse(client.nick())));
+                return 2;
+            },
+            irc::IrcError::Disconnected(reason) => {
+                warn!(%reason, "lost the connection to the IRC server");
+                renderer.error(code, &lang.text(Message::IrcDisconnected(&reason)));
+                thread::sleep(BOT_RECONNECT_DELAY);
+            },
+        }
+    }
+}
+
+// Without the `irc` feature, there is no way to connect to IRC servers
+#[cfg(not(feature = "irc"))]
+fn run_irc(_server: &str, _options: &Options, _archive: Option<Archive>, renderer: &dyn Renderer,
+    lang: Lang) -> i32 {
+
+    renderer.error(error::IRC_UNAVAILABLE, &lang.text(Message::IrcUnavailable));
+    2
+}
+
+// Counts the leaves of the game tree `depth` moves deep, starting from the given position. Returns
+// the exit code for the program, which is always 0.
+fn show_perft(game: &Game, depth: u32, renderer: &dyn Renderer, lang: Lang) -> i32 {
+    let start = Instant::now();
+    let leaves = perft::perft(game, depth);
+    renderer.info(&lang.text(Message::Perft {depth, leaves, time: start.elapsed()}));
+    0
+}
+
+// Counts every position reachable from the given one and checks the engine against them. Returns
+// the exit code for the program: 0 if the engine agrees about every position, or 1 if it doesn't.
+// Searches for the best moves from the position while measuring the search, then shows how long
+// each part of it took and saves the folded stacks (if there is somewhere to save them)
+fn show_profile(game: &Game, folded: Option<&Path>, renderer: &dyn Renderer, lang: Lang) -> i32 {
+    let profile = profile::profile(game);
+    let (positions, time) = (profile.total_positions(), profile.total);
+    renderer.info(&lang.text(Message::ProfileSearch {positions, time}));
+
+    // Every phase, followed by the time that wasn't spent in any of them
+    let phases = solve::Phase::ALL.iter().map(|&phase| (Some(phase), profile.phase_time(phase)));
+    for (phase, time) in phases.chain(Some((None, profile.other_time()))) {
+        let share = 100.0 * time.as_secs_f64() / profile.total.as_secs_f64().max(f64::EPSILON);
+        renderer.info(&lang.text(Message::ProfilePhase {phase, time, share}));
+    }
+
+    if let Some(path) = folded {
+        let mut stacks = Vec::new();
+        profile.write_folded(&mut stacks).expect("writing to a Vec should never fail");
+        if let Err(error) = storage::write(path, &stacks) {
+            let message = lang.text(Message::ProfileFailed {path, error: &error});
+            renderer.error(error::PROFILE_FAILED, &message);
+            return 2;
+        }
+        renderer.info(&lang.text(Message::ProfileSaved(path)));
+    }
+    0
+}
+
+// Writes the game tree from the position to the file, or to stdout if there is no file
+fn export_tree(game: &Game, depth: u32, path: Option<&Path>, renderer: &dyn Renderer,
+    lang: Lang) -> i32 {
+
+    let tree = tree::Tree::new(game, depth);
+    let path = match path {
+        Some(path) => path,
+        None => {
+            out!("{}", tree.to_dot());
+            return 0;
+        },
+    };
+    match storage::write(path, tree.to_dot().as_bytes()) {
+        Ok(()) => {
+            renderer.info(&lang.text(Message::TreeExported {path, positions: tree.positions()}));
+            0
+        },
+        Err(error) => {
+            let message = lang.text(Message::TreeExportFailed {path, error: &error});
+            renderer.error(error::EXPORT_FAILED, &message);
+            2
+        },
+    }
+}
+
+fn show_enumeration(game: &Game, symmetry: bool, renderer: &dyn Renderer, lang: Lang) -> i32 {
+    let enumeration = enumerate::enumerate(game, symmetry);
+    let counts = &enumeration.counts;
+    renderer.info(&lang.text(Message::Enumeration {counts, symmetry}));
+    if enumeration.mismatches.is_empty() {
+        renderer.info(&lang.text(Message::EnumerationVerified(counts.ongoing)));
+        return 0;
+    }
+
+    for moves in &enumeration.mismatches {
+        renderer.info(&lang.text(Message::EnumerationMismatch(moves)));
+    }
+    1
+}
+
+// Replays every game in the archive at `path` and shows each problem found along the way. Returns
+// the exit code for the program: 0 if every game checks out, 1 if there were problems, or 2 if the
+// file couldn't be read.
+fn show_verification(path: &Path, renderer: &dyn Renderer, lang: Lang) -> i32 {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(error) => {
+            let message = lang.text(Message::ArchiveReadFailed {path, error: &error});
+            renderer.error(error::ARCHIVE_FAILED, &message);
+            return 2;
+        },
+    };
+
+    let verification = archive::verify(&text, engine::perfect_result);
+    if verification.problems.is_empty() {
+        renderer.info(&lang.text(Message::VerifyPassed {path, games: verification.games}));
+        return 0;
+    }
+
+    for &(line, ref problem) in &verification.problems {
+        renderer.info(&lang.text(Message::VerifyProblem {line, problem}));
+    }
+    let problems = verification.problems.len();
+    renderer.info(&lang.text(Message::VerifyFailed {path, problems}));
+    1
+}
+
+// Plays every game in the archive at `path` again and shows what the program no longer agrees with
+// (see audit.rs). `profiles` are the engine profiles from the preferences. Returns the exit code
+// for the program: 0 if everything agrees, 1 if something doesn't, or 2 if the file couldn't be
+// read.
+fn show_audit(path: &Path, profiles: &[(String, Profile)], renderer: &dyn Renderer, lang: Lang)
+    -> i32 {
+
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(error) => {
+            let message = lang.text(Message::ArchiveReadFailed {path, error: &error});
+            renderer.error(error::ARCHIVE_FAILED, &message);
+            return 2;
+        },
+    };
+
+    let audit = audit::audit(&text, profiles);
+    if audit.findings.is_empty() {
+        let (games, moves) = (audit.games, audit.moves);
+        renderer.info(&lang.text(Message::AuditPassed {path, games, moves}));
+        return 0;
+    }
+
+    for &(line, ref finding) in &audit.findings {
+        renderer.info(&lang.text(Message::AuditFinding {line, finding}));
+    }
+    let findings = audit.findings.len();
+    renderer.info(&lang.text(Message::AuditFailed {path, findings}));
+    1
+}
+
+// Lists the games in the archive at `path` that have the tag the query is looking for, on the game
+// itself or on one of its moves. Returns the exit code for the program: 0 if any games were found,
+// 1 if none were, or 2 if the archive couldn't be read.
+fn show_tagged(query: &TagQuery, path: &Path, renderer: &dyn Renderer, lang: Lang) -> i32 {
+    let games = match read_archive(&Archive::new(path), renderer, lang) {
+        Some(games) => games,
+        None => return 2,
+    };
+
+    let mut found = 0;
+    for (i, archived) in games.iter().enumerate() {
+        let (tagged, moves) = query.find(&archived.game);
+        if tagged || !moves.is_empty() {
+            found += 1;
+            let game = &archived.game;
+            renderer.info(&lang.text(Message::TaggedGame {number: i + 1, game, moves: &moves}));
+        }
+    }
+    renderer.info(&lang.text(Message::TaggedFound {query, found, games: games.len()}));
+    if found > 0 { 0 } else { 1 }
+}
+
+// Shows everything that `archive check` finds in the archive at `path` (see check.rs), and fixes
+// what can be fixed safely if `fix` is true. The archive is copied to a backup file before it is
+// changed. Returns the exit code for the program: 0 if nothing is left to fix, 1 if something is
+// and 2 if the archive couldn't be read or written.
+fn check_archive(path: &Path, fix: bool, renderer: &dyn Renderer, lang: Lang) -> i32 {
+    let mut text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(error) => {
+            let message = lang.text(Message::ArchiveReadFailed {path, error: &error});
+            renderer.error(error::ARCHIVE_FAILED, &message);
+            return 2;
+        },
+    };
+
+    if fix {
+        let (fixed, repairs) = check::fix(&text);
+        if !repairs.is_empty() {
+            let backup = storage::backup_path(path);
+            if let Err(error) = storage::save(path, fixed.as_bytes()) {
+                let message = lang.text(Message::ArchiveWriteFailed {path, error: &error});
+                renderer.error(error::ARCHIVE_FAILED, &message);
+                return 2;
+            }
+            for &(line, repair) in &repairs {
+                renderer.info(&lang.text(Message::CheckRepaired {line, repair}));
+            }
+            let repairs = repairs.len();
+            info!(path = %path.display(), repairs, "fixed the archive");
+            renderer.info(&lang.text(Message::CheckFixed {backup: &backup, repairs}));
+            renderer.info("");
+            text = fixed;
+        }
+    }
+
+    let check = check::check(&text);
+    if check.findings.is_empty() {
+        renderer.info(&lang.text(Message::VerifyPassed {path, games: check.games}));
+        return 0;
+    }
+    for &(line, ref finding) in &check.findings {
+        renderer.info(&lang.text(match *finding {
+            Finding::Problem(ref problem) => Message::VerifyProblem {line, problem},
+            Finding::Duplicate(first) => Message::CheckDuplicate {line, first},
+            Finding::UnknownField(ref field) => Message::CheckUnknownField {line, field},
+        }));
+    }
+    let problems = check.findings.len();
+    renderer.info(&lang.text(Message::VerifyFailed {path, problems}));
+    1
+}
+
+// Writes the puzzles that the generator finds to stdout, in the format that `--puzzles` reads.
+// Returns the exit code for the program: 0 if the puzzles were written, or 2 if the archive
+// couldn't be read.
+fn generate_puzzles(archive: Option<&Archive>, renderer: &dyn Renderer, lang: Lang) -> i32 {
+    let puzzles = match archive {
+        Some(archive) => match read_archive(archive, renderer, lang) {
+            Some(games) => generate::from_games(games.iter().map(|archived| &archived.game)),
+            None => return 2,
+        },
+        None => generate::every_position(),
+    };
+    for puzzle in &puzzles {
+        outln!("{}", puzzle::write(puzzle));
+    }
+    0
+}
+
+// Shows the lifetime stats of every named player in the archive. Returns the exit code for the
+// program: 0 if the archive could be read, or 2 if it couldn't.
+fn show_stats(archive: &Archive, renderer: &dyn Renderer, lang: Lang) -> i32 {
+    let games = match read_archive(archive, renderer, lang) {
+        Some(games) => games,
+        None => return 2,
+    };
+
+    let stats = stats::compute(&games);
+    if stats.is_empty() {
+        renderer.info(&lang.text(Message::NoStats(archive.path())));
+        return 0;
+    }
+
+    renderer.info(&lang.text(Message::StatsHeader(archive.path())));
+    for (name, stats) in &stats {
+        renderer.info("");
+        renderer.info(&lang.text(Message::PlayerStats {name, stats}));
+        if let Some(((row, col), games)) = stats.favorite_opening() {
+            renderer.info(&lang.text(Message::FavoriteOpening {row, col, games}));
+        }
+        if let Some((outcome, games)) = stats.streak {
+            let best_win_streak = stats.best_win_streak;
+            renderer.info(&lang.text(Message::Streak {outcome, games, best_win_streak}));
+        }
+    }
+    0
+}
+
+// Shows how the games between the players named `a` and `b` went for `a`, both overall and for
+// each piece. Returns the exit code for the program, just like show_stats.
+fn show_head_to_head(archive: &Archive, (a, b): (&str, &str), renderer: &dyn Renderer,
+    lang: Lang) -> i32 {
+
+    let games = match read_archive(archive, renderer, lang) {
+        Some(games) => games,
+        None => return 2,
+    };
+
+    let result = stats::head_to_head(&games, a, b);
+    let total = result.total();
+    let path = archive.path();
+    if total.games() == 0 {
+        renderer.info(&lang.text(Message::NoHeadToHead {a, b, path}));
+        return 0;
+    }
+
+    renderer.info(&lang.text(Message::HeadToHead {a, b, path, games: total.games()}));
+    for &(piece, record) in &[(None, total), (Some(Piece::X), result.as_x),
+        (Some(Piece::O), result.as_o)] {
+
+        renderer.info(&lang.text(Message::HeadToHeadRecord {a, b, piece, record}));
+    }
+    renderer.info(&lang.text(Message::HeadToHeadLength(result.average_moves())));
+    0
+}
+
+// Shows every named player in the archive from the highest rating to the lowest. Returns the exit
+// code for the program, just like show_stats.
+fn show_ratings(archive: &Archive, renderer: &dyn Renderer, lang: Lang) -> i32 {
+    let games = match read_archive(archive, renderer, lang) {
+        Some(games) => games,
+        None => return 2,
+    };
+
+    let mut ratings: Vec<_> = rating::compute(&games).into_iter().collect();
+    if ratings.is_empty() {
+        renderer.info(&lang.text(Message::NoRatings(archive.path())));
+        return 0;
+    }
+    // `total_cmp` compares two f64 values. `b` before `a` sorts from highest to lowest.
+    ratings.sort_by(|(_, a), (_, b)| b.rating.total_cmp(&a.rating));
+
+    renderer.info(&lang.text(Message::RatingsHeader(archive.path())));
+    for (i, (name, rated)) in ratings.iter().enumerate() {
+        renderer.info(&lang.text(Message::PlayerRating {
+            rank: i + 1,
+            name,
+            rating: rated.rating.round() as i32,
+            games: rated.games,
+        }));
+    }
+    0
+}
+
+// Reads every game in the archive, reporting an error if it can't be read
+// Lists how every opening in the archive went, with the replies to each first move right after
+// it. Returns the exit code for the program, just like show_stats.
+fn show_openings(archive: &Archive, renderer: &dyn Renderer, lang: Lang) -> i32 {
+    let games = match read_archive(archive, renderer, lang) {
+        Some(games) => games,
+        None => return 2,
+    };
+
+    let openings = openings::compute(&games);
+    if openings.is_empty() {
+        renderer.info(&lang.text(Message::NoOpenings(archive.path())));
+        return 0;
+    }
+
+    renderer.info(&lang.text(Message::OpeningsHeader(archive.path())));
+    for (moves, results) in &openings {
+        // Replies are indented under the first move they answer
+        let text = lang.text(Message::Opening {moves, results});
+        if moves.len() == 1 {
+            renderer.info("");
+            renderer.info(&text);
+        }
+        else {
+            renderer.info(&format!("  {}", text));
+        }
+    }
+    0
+}
+
+// Writes a table of the archive to stdout, so that it can be redirected to a file. Returns the
+// exit code for the program, just like show_stats.
+fn export_table(archive: &Archive, table: Table, format: ExportFormat, renderer: &dyn Renderer,
+    lang: Lang) -> i32 {
+
+    let games = match read_archive(archive, renderer, lang) {
+        Some(games) => games,
+        None => return 2,
+    };
+
+    let mut text = Vec::new();
+    let result = match format {
+        ExportFormat::Csv => export::write_csv(&mut text, table, &games),
+        ExportFormat::Npz => unreachable!("Options::parse only allows CSV for tables"),
+    };
+    // Writing to a Vec<u8> can't fail
+    result.expect("writing to memory should succeed");
+    out!("{}", String::from_utf8_lossy(&text));
+    0
+}
+
+// Writes a sample of every position of the games to the file, for training machine learning
+// models (see training.rs)
+fn export_training(games: &[Game], path: &Path, format: ExportFormat, augment: bool,
+    renderer: &dyn Renderer, lang: Lang) -> i32 {
+
+    // Each game's samples are worked out on their own, but they stay in the order of the games
+    let samples = games.par_iter().map(|game| training::samples(game, augment))
+        .collect::<Vec<_>>().concat();
+    let bytes = match format {
+        ExportFormat::Csv => {
+            let mut text = Vec::new();
+            // Writing to a Vec<u8> can't fail
+            training::write_csv(&mut text, &samples).expect("writing to memory should succeed");
+            text
+        },
+        ExportFormat::Npz => training::to_npz(&samples),
+    };
+    match storage::write(path, &bytes) {
+        Ok(()) => {
+            let (samples, games) = (samples.len(), games.len());
+            renderer.info(&lang.text(Message::TrainingExported {path, samples, games}));
+            0
+        },
+        Err(error) => {
+            let message = lang.text(Message::TrainingExportFailed {path, error: &error});
+            renderer.error(error::EXPORT_FAILED, &message);
+            2
+        },
+    }
+}
+
+fn read_archive(archive: &Archive, renderer: &dyn Renderer, lang: Lang)
+    -> Option<Vec<ArchivedGame>> {
+
+    match archive.load() {
+        Ok((games, recovery)) => {
+            debug!(path = %archive.path().display(), games = games.len(), "read the archive");
+            if let Some(ref recovery) = recovery {
+                report_recovery(archive.path(), recovery, lang);
+            }
+            Some(games)
+        },
+        Err(error) => {
+            let path = archive.path();
+            error!(path = %path.display(), %error, "failed to read the archive");
+            let message = lang.text(Message::ArchiveReadFailed {path, error: &error});
+            renderer.error(error::ARCHIVE_FAILED, &message);
+            None
+        },
+    }
+}
+
+// Walks a new player through the lesson from the tutorial module. The student has to play the
+// moves of the lesson, and the tutorial explains each one before they make it.
+fn play_tutorial(players: &Players, renderer: &dyn Renderer, lang: Lang) -> Result<(), Quit> {
+    let student = tutorial::STUDENT;
+    let mut game = Game::starting_with(student);
+
+    renderer.info(&lang.text(Message::TutorialIntro {
+        student: &players.label(student),
+        tutor: &players.label(student.other()),
+    }));
+    renderer.info("");
+
+    for &(row, col) in &tutorial::SCRIPT {
+        if game.current_piece() == student {
+            renderer.board(&GameView::new(&game, players, Variant::Tutorial));
+            let advice = lang.text(tutorial::advice(&game, players, row, col));
+            renderer.info(&advice);
+
+            // Keep asking until the student plays the move from the lesson. There is no clock
+            // in the tutorial, so prompt_move always returns something.
+            loop {
+                match prompt_move(renderer, lang, None) {
+                    Ok(Some(Turn::Move(r, c))) if (r, c) == (row, col) => break,
+                    Ok(Some(Turn::Move(..))) => {
+                        let message = lang.text(Message::TutorialWrongMove {row, col});
+                        renderer.error(error::WRONG_MOVE, &message)
+                    },
+                    // Asking for a hint repeats the advice. Undo isn't part of the lesson.
+                    Ok(Some(Turn::Command(_))) => renderer.info(&advice),
+                    Ok(None) => unreachable!("the tutorial has no clock"),
+                    // The tutorial isn't worth saving, so Ctrl-C quits right away
+                    Err(Quit::Interrupted) => {
+                        outln!();
+                        return Err(Quit::Interrupted);
+                    },
+                    Err(quit) => return Err(quit),
+                }
+            }
+        }
+        else {
+            renderer.info(&lang.text(Message::TutorialTutorMove {row, col}));
+        }
+
+        game.make_move(row, col).expect("tutorial moves should always be valid");
+        renderer.move_made(&game, players, row, col);
+        if game.current_piece() != student {
+            if let Some(message) = tutorial::commentary(&game, players) {
+                renderer.info(&lang.text(message));
+            }
+        }
+    }
+
+    let view = GameView::new(&game, players, Variant::Tutorial);
+    renderer.game_over(&view, &result_message(&game, players, lang));
+    renderer.info(&lang.text(Message::TutorialDone));
+    Ok(())
+}
+
+// Reads the puzzles in each file, or the puzzles that come with the game if there are no files.
+// Returns None once it has shown why a file couldn't be used.
+fn load_puzzles(paths: Option<&[PathBuf]>, renderer: &dyn Renderer,
+    lang: Lang) -> Option<Vec<Puzzle>> {
+
+    let paths = match paths {
+        Some(paths) => paths,
+        None => {
+            return Some(puzzle::parse(puzzle::BUNDLED).expect("bundled puzzles should be valid"))
+        },
+    };
+    let mut puzzles = Vec::new();
+    for path in paths {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(error) => {
+                let message = lang.text(Message::PuzzleReadFailed {path, error: &error});
+                renderer.error(error::PUZZLES_FAILED, &message);
+                return None;
+            },
+        };
+        match puzzle::parse(&text) {
+            Ok(more) => puzzles.extend(more),
+            Err((line, problem)) => {
+                let message = lang.text(Message::PuzzleProblem {path, line, problem: &problem});
+                renderer.error(error::INVALID_PUZZLE, &message);
+                return None;
+            },
+        }
+    }
+    Some(puzzles)
+}
+
+// Gives the player each puzzle in turn
+fn play_puzzles(puzzles: &[Puzzle], players: &Players, renderer: &dyn Renderer,
+    lang: Lang) -> Result<(), Quit> {
+
+    renderer.info(&lang.text(Message::PuzzleIntro));
+    for (i, puzzle) in puzzles.iter().enumerate() {
+        renderer.info("");
+        renderer.info(&lang.text(Message::PuzzleHeader {
+            number: i + 1,
+            total: puzzles.len(),
+            player: &players.label(puzzle.game.current_piece()),
+            goal: puzzle.goal,
+            moves: puzzle.moves,
+        }));
+        play_puzzle(puzzle, players, renderer, lang)?;
+    }
+
+    renderer.info("");
+    renderer.info(&lang.text(Message::PuzzlesDone(puzzles.len())));
+    Ok(())
+}
+
+// Gives the player the puzzle of the day. Solving it makes their streak longer, which is saved
+// in the preferences right away, and gives them a result they can share.
+fn play_daily(prefs: &mut Prefs, prefs_path: Option<&Path>, players: &Players,
+    renderer: &dyn Renderer, lang: Lang) -> Result<(), Quit> {
+
+    let today = daily::today();
+    let puzzle = daily::puzzle(today);
+    renderer.info(&lang.text(Message::PuzzleIntro));
+    renderer.info("");
+    renderer.info(&lang.text(Message::DailyHeader {
+        date: &daily::date(today),
+        player: &players.label(puzzle.game.current_piece()),
+        goal: puzzle.goal,
+        moves: puzzle.moves,
+    }));
+    let attempt = play_puzzle(&puzzle, players, renderer, lang)?;
+
+    prefs.streak.solved(today);
+    info!(streak = prefs.streak.current, "solved the daily puzzle");
+    // Not being able to save the streak shouldn't take away the result, so we only warn about it
+    match prefs_path {
+        Some(path) => if let Err(error) = prefs.save(path) {
+            warn!(path = %path.display(), %error, "failed to save the daily streak");
+            let message = lang.text(Message::PrefsFailed {path, error: &error});
+            renderer.error(error::PREFS_FAILED, &message);
+        },
+        None => renderer.error(error::NO_PREFS_DIR, &lang.text(Message::NoPrefsDir)),
+    }
+
+    renderer.info("");
+    renderer.info(&lang.text(Message::DailyStreak {
+        current: prefs.streak.current,
+        best: prefs.streak.best,
+    }));
+    renderer.info(&lang.text(Message::DailyShare));
+    renderer.info(&daily::share(today, attempt.tries()));
+    Ok(())
+}
+
+// Plays games against the computer until the player has won enough of them to finish the
+// gauntlet, timing every game. A named player's time goes on the leaderboard in the preferences if
+// it is their best one.
+fn play_speedrun(prefs: &mut Prefs, prefs_path: Option<&Path>, settings: &Settings,
+    difficulty: Difficulty, archive: Option<&Archive>, renderer: &dyn Renderer,
+    lang: Lang) -> Result<(), Quit> {
+
+    // The player is always x, so that their name is the one from their preferences
+    let player = Piece::X;
+    let mut settings = settings.clone();
+    settings.o_engine = Some(Computer::Engine(difficulty));
+    renderer.info(&lang.text(Message::SpeedrunIntro {wins: speedrun::WINS, difficulty}));
+
+    let mut run = Run::default();
+    while !run.is_finished() {
+        renderer.info("");
+        renderer.info(&lang.text(Message::SpeedrunGame {
+            number: run.games + 1,
+            wins: run.wins,
+            needed: speedrun::WINS,
+        }));
+        let game = Game::starting_with(run.next_first_piece(player));
+        // Only the game itself is timed, so reading the messages in between is free
+        let started = Instant::now();
+        let game = play_game(game, &settings, archive, renderer, lang)?;
+        run.record(&game, player, started.elapsed());
+        if let Some(archive) = archive {
+            save_to_archive(archive, archive.record_game(&game, &settings.players), lang);
+        }
+        renderer.info(&lang.text(Message::SpeedrunTime(run.time)));
+    }
+
+    renderer.info("");
+    renderer.info(&lang.text(Message::SpeedrunDone {time: run.time, games: run.games}));
+    info!(time_ms = run.time.as_millis() as u64, games = run.games, "finished a speedrun");
+    let name = match settings.players.profile(player).name {
+        Some(ref name) => name,
+        None => {
+            renderer.info(&lang.text(Message::SpeedrunNoName));
+            return Ok(());
+        },
+    };
+    let finish = prefs.speedruns.record(name, difficulty, run.time);
+    renderer.info(&lang.text(Message::SpeedrunFinish(finish)));
+    if let Finish::NewBest {..} = finish {
+        // Not being able to save the time shouldn't take away the result, so we only warn about it
+        match prefs_path {
+            Some(path) => if let Err(error) = prefs.save(path) {
+                warn!(path = %path.display(), %error, "failed to save the speedrun time");
+                let message = lang.text(Message::PrefsFailed {path, error: &error});
+                renderer.error(error::PREFS_FAILED, &message);
+            },
+            None => renderer.error(error::NO_PREFS_DIR, &lang.text(Message::NoPrefsDir)),
+        }
+    }
+
+    renderer.info("");
+    renderer.info(&lang.text(Message::SpeedrunLeaderboard(difficulty)));
+    for (i, best) in prefs.speedruns.ranking(difficulty).iter().enumerate() {
+        renderer.info(&lang.text(Message::SpeedrunEntry {
+            rank: i + 1,
+            name: &best.name,
+            time: best.time,
+        }));
+    }
+    Ok(())
+}
+
+// Plays one puzzle until it is solved. A move that doesn't reach the goal is taken back so that
+// the player can try again, and the solver answers every move that does.
+fn play_puzzle(puzzle: &Puzzle, players: &Players, renderer: &dyn Renderer,
+    lang: Lang) -> Result<Attempt, Quit> {
+
+    let mut attempt = Attempt::new(puzzle);
+    while !attempt.is_solved() {
+        renderer.board(&GameView::new(attempt.game(), players, Variant::Puzzle));
+        // There is no clock in a puzzle, so prompt_move always returns something
+        let (row, col) = match prompt_move(renderer, lang, None) {
+            Ok(Some(Turn::Move(row, col))) => (row, col),
+            // Undoing and showing the board don't mean anything here, so every command asks for
+            // the next hint
+            Ok(Some(Turn::Command(_))) => {
+                renderer.info(&lang.text(Message::PuzzleHint(attempt.hint())));
+                continue;
+            },
+            Ok(None) => unreachable!("puzzles have no clock"),
+            // Puzzles aren't worth saving, so Ctrl-C quits right away
+            Err(Quit::Interrupted) => {
+                outln!();
+                return Err(Quit::Interrupted);
+            },
+            Err(quit) => return Err(quit),
+        };
+
+        match attempt.play(row, col, &mut random::shared()) {
+            Ok(Progress::Right(Some((row, col)))) => {
+                renderer.info(&lang.text(Message::PuzzleReply {row, col}))
+            },
+            Ok(Progress::Right(None)) => {},
+            Ok(Progress::Wrong) => {
+                let message = lang.text(Message::PuzzleWrongMove {row, col, goal: puzzle.goal});
+                renderer.error(error::WRONG_MOVE, &message)
+            },
+            Err(ref error @ MoveError::TileNotEmpty {other_piece, row, col, since}) => {
+                let other_player = &players.label(other_piece);
+                let message = lang.text(Message::TileNotEmpty {row, col, other_player, since});
+                let rejection = Rejection::Occupied {row, col, piece: other_piece, since};
+                renderer.move_rejected(error.into(), &message, rejection)
+            },
+            Err(error) => unreachable!("puzzle moves are on the board: {:?}", error),
+        }
+    }
+
+    let view = GameView::new(attempt.game(), players, Variant::Puzzle);
+    renderer.game_over(&view, &lang.text(Message::PuzzleSolved {hints: attempt.hints_used()}));
+    Ok(attempt)
+}
+
+// Lets the player set up a position one tile at a time, analyze it with the table of perfect
+// moves, and then play a game from it. The position is only checked when it is used, so that it
+// can be changed in any order.
+fn play_setup(settings: &Settings, renderer: &dyn Renderer, lang: Lang) -> Result<(), Quit> {
+    let players = &settings.players;
+    let mut setup = Setup::new();
+    renderer.info(&lang.text(Message::SetupIntro));
+    loop {
+        renderer.info("");
+        let status = lang.text(Message::SetupStatus(&players.label(setup.next())));
+        renderer.position(setup.tiles(), setup.next(), players, &status);
+
+        let line = match read_line(&lang.text(Message::EnterEdit)) {
+            Ok(line) => line,
+            // There is no game to save yet, so Ctrl-C quits right away
+            Err(Quit::Interrupted) => {
+                outln!();
+                return Err(Quit::Interrupted);
+            },
+            Err(quit) => return Err(quit),
+        };
+        let edit = match Edit::parse(&line) {
+            Some(edit) => edit,
+            None => {
+                renderer.error(error::INVALID_EDIT, &lang.text(Message::InvalidEdit(line.trim())));
+                continue;
+            },
+        };
+        setup.apply(edit);
+        if edit != Edit::Play && edit != Edit::Analyze {
+            continue;
+        }
+
+        let game = match setup.game() {
+            Ok(ref game) if game.is_finished() => {
+                renderer.error(error::POSITION_OVER, &lang.text(Message::PositionOver));
+                continue;
+            },
+            Ok(game) => game,
+            Err(error) => {
+                renderer.error((&error).into(), &lang.text(Message::PositionInvalid(error)));
+                continue;
+            },
+        };
+        if edit == Edit::Analyze {
+            let winner = match engine::perfect_result(&game) {
+                Winner::X => Some(players.label(Piece::X)),
+                Winner::O => Some(players.label(Piece::O)),
+                Winner::Tie => None,
+            };
+            renderer.info(&lang.text(Message::PositionAnalysis {
+                winner: winner.as_deref(),
+                moves: &engine::perfect_moves(&game),
+            }));
+            continue;
+        }
+
+        debug!(moves = ?game.moves().iter().collect::<Vec<_>>(), "playing a position");
+        // The moves that lead to the position were made up, so the game isn't archived
+        play_game(game, settings, None, renderer, lang)?;
+        return Ok(());
+    }
+}
+
+// Walks through the game from the empty board (see explore.rs). Every position lists the moves
+// that can be played in it, and the player picks one to see the position after it. The results
+// from the archive are only shown if there is one.
+fn explore_game(explorer: &Explorer, archived: bool, players: &Players, renderer: &dyn Renderer,
+    lang: Lang) -> Result<(), Quit> {
+
+    let mut position = Game::new();
+    renderer.info(&lang.text(Message::ExploreIntro));
+    loop {
+        renderer.info("");
+        renderer.board(&GameView::new(&position, players, Variant::Classic));
+        if position.is_finished() {
+            renderer.info(&result_message(&position, players, lang));
+        }
+        else {
+            let player = players.label(position.current_piece());
+            renderer.info(&lang.text(Message::ExploreMoves(&player)));
+            for candidate in explorer.candidates(&position) {
+                renderer.info(&lang.text(Message::ExploreCandidate {
+                    tiles: &candidate.tiles,
+                    verdict: candidate.verdict,
+                    results: Some(candidate.results).filter(|_| archived),
+                }));
+            }
+        }
+
+        let line = read_line(&lang.text(Message::ExplorePrompt))?;
+        let (row, col) = match explore::Step::parse(&line) {
+            Some(explore::Step::Play(row, col)) => (row, col),
+            Some(explore::Step::Back) => {
+                if position.undo().is_none() {
+                    renderer.error(error::NOTHING_TO_UNDO, &lang.text(Message::NothingToUndo));
+                }
+                continue;
+            },
+            Some(explore::Step::Done) => return Ok(()),
+            None => {
+                renderer.error(error::INVALID_ANSWER, &lang.text(Message::ExploreHelp));
+                continue;
+            },
+        };
+        match position.make_move(row, col) {
+            Ok(()) => debug!(row, col, "explored a move"),
+            Err(MoveError::GameAlreadyOver) => {
+                renderer.error(error::POSITION_OVER, &lang.text(Message::PositionOver))
+            },
+            Err(ref error @ MoveError::TileNotEmpty {other_piece, row, col, since}) => {
+                let other_player = &players.label(other_piece);
+                let message = lang.text(Message::TileNotEmpty {row, col, other_player, since});
+                let rejection = Rejection::Occupied {row, col, piece: other_piece, since};
+                renderer.move_rejected(error.into(), &message, rejection)
+            },
+            Err(MoveError::InvalidPosition {..}) => {
+                unreachable!("Step::parse only reads tiles that are on the board")
+            },
+        }
+    }
+}
+
+// Carries on playing the game of a code from where it was left, or steps through it with the
+// solver's opinion of every move if it is already over
+fn play_from_code(game: Game, settings: &Settings, renderer: &dyn Renderer,
+    lang: Lang) -> Result<(), Quit> {
+
+    renderer.info(&lang.text(Message::CodeLoaded(game.moves().len())));
+    if game.is_finished() {
+        return review_game(&game, &settings.players, renderer, lang);
+    }
+    debug!(moves = ?game.moves().iter().collect::<Vec<_>>(), "playing a game from a code");
+    // Nobody knows who made the moves before the code, so the game isn't archived
+    let game = play_game(game, settings, None, renderer, lang)?;
+    show_code(&game, settings, renderer, lang);
+    Ok(())
+}
+
+// Shows the code of a game, and the link to it if there is an address to make links with
+fn show_code(game: &Game, settings: &Settings, renderer: &dyn Renderer, lang: Lang) {
+    let code = share::encode(game);
+    renderer.info(&lang.text(Message::GameCode(&code)));
+    if let Some(ref base) = settings.share_url {
+        renderer.info(&lang.text(Message::GameLink(&share::permalink(base, &code))));
+    }
+}
+
+// Compares two games, each given as a file of moves or a game code (see diff.rs). Returns the exit
+// code for the program: 0 if the games are the same, 1 if they aren't and 2 if one of them couldn't
+// be read.
+fn show_diff((first, second): (&str, &str), settings: &Settings, renderer: &dyn Renderer,
+    lang: Lang) -> i32 {
+
+    let mut games = Vec::new();
+    for source in &[first, second] {
+        match load_game(source, settings, lang) {
+            Ok(game) => games.push(game),
+            Err((code, error)) => {
+                renderer.error(code, &error);
+                return 2;
+            },
+        }
+    }
+    let diff = diff::diff(&games[0], &games[1]);
+    debug!(?diff, "compared two games");
+
+    let (exact, common) = (diff.exact, diff.common);
+    renderer.info(&lang.text(match diff.diverged {
+        Some((first, second)) => Message::DiffDiverged {number: common + 1, first, second, exact},
+        None if diff.lengths.0 == diff.lengths.1 => Message::DiffSame {moves: common, exact},
+        None => Message::DiffLonger {common, first_longer: diff.lengths.0 > diff.lengths.1},
+    }));
+    renderer.info(&lang.text(Message::DiffResults {results: diff.results, lengths: diff.lengths}));
+    if diff.is_same() { 0 } else { 1 }
+}
+
+// Reads a game from a file of moves (like `--moves-file`), or from a game code if there is no file
+// by that name. If it can't be read, gives back the error code and message to show.
+fn load_game(source: &str, settings: &Settings,
+    lang: Lang) -> Result<Game, (ErrorCode, String)> {
+
+    let path = Path::new(source);
+    if path.exists() {
+        let moves = fs::read_to_string(path).map_err(|error| {
+            (error::READ_FAILED, lang.text(Message::MovesFileFailed {path, error: &error}))
+        })?;
+        return script_position(&moves, settings, lang).map_err(|error| (error.code, error.message));
+    }
+    share::decode(source)
+        .map_err(|error| ((&error).into(), lang.text(Message::DiffInvalidGame(source))))
+}
+
+// Shows the QR code of a game's code (or of its link, if there is an address to make links with)
+// in the terminal, or saves it as a PNG image that is `size` pixels wide. Returns the exit code for
+// the program.
+#[cfg(feature = "images")]
+fn export_qr(game: &Game, settings: &Settings, png: Option<&Path>, size: u32,
+    renderer: &dyn Renderer, lang: Lang) -> i32 {
+
+    let code = share::encode(game);
+    let text = match settings.share_url {
+        Some(ref base) => share::permalink(base, &code),
+        None => code,
+    };
+    let path = match png {
+        Some(path) => path,
+        None => {
+            for line in qr::to_text(&text) {
+                renderer.info(&line);
+            }
+            show_code(game, settings, renderer, lang);
+            return 0;
+        },
+    };
+    match storage::write(path, &qr::to_png(&text, size)) {
+        Ok(()) => {
+            renderer.info(&lang.text(Message::QrExported(path)));
+            0
+        },
+        Err(error) => {
+            let message = lang.text(Message::QrExportFailed {path, error: &error});
+            renderer.error(error::EXPORT_FAILED, &message);
+            2
+        },
+    }
+}
+
+// Without the `images` feature, there is no way to draw QR codes
+#[cfg(not(feature = "images"))]
+fn export_qr(_game: &Game, _settings: &Settings, _png: Option<&Path>, _size: u32,
+    renderer: &dyn Renderer, lang: Lang) -> i32 {
+
+    renderer.error(error::IMAGES_UNAVAILABLE, &lang.text(Message::ImagesUnavailable));
+    2
+}
+
+// Lets the computer play against itself, redrawing the board in place after every move. The demo
+// goes on until `games` games have been played, or forever if there is no limit. The seed is only
+// shown, so that the same demo can be played again.
+fn play_demo(players: &Players, difficulty: Difficulty, delay: Duration, games: Option<u32>,
+    seed: u64, renderer: &dyn Renderer, lang: Lang) {
+
+    let mut score = Score::default();
+    let mut first_piece = Piece::X;
+
+    // `1..` counts up forever, so `take` is what stops the demo after the last game
+    let limit = games.map_or(usize::MAX, |games| games as usize);
+    for number in (1..).take(limit) {
+        let mut game = Game::starting_with(first_piece);
+        let header = lang.text(Message::DemoGame {
+            number,
+            games,
+            first_player: &players.label(first_piece),
+        });
+
+        // Clearing the screen before drawing makes the board look like it is animating in place
+        // instead of scrolling by
+        let show_header = |score: Score| {
+            renderer.clear();
+            renderer.info(&header);
+            renderer.info(&lang.text(Message::SeriesScore {
+                x: &players.label(Piece::X),
+                o: &players.label(Piece::O),
+                score,
+            }));
+            renderer.info(&lang.text(Message::DemoHelp));
+            renderer.info(&lang.text(Message::Seed(seed)));
+            renderer.info("");
+        };
+
+        while !game.is_finished() {
+            show_header(score);
+            renderer.board(&GameView::new(&game, players, Variant::Demo));
+            thread::sleep(delay);
+
+            let (row, col) = computer_move(&game, difficulty);
+            game.make_move(row, col).expect("the engine should only choose empty tiles");
+            renderer.move_made(&game, players, row, col);
+        }
+
+        match game.winner() {
+            Some(Winner::X) => score.x += 1,
+            Some(Winner::O) => score.o += 1,
+            Some(Winner::Tie) => score.ties += 1,
+            None => unreachable!("the demo game should be finished"),
+        }
+        show_header(score);
+        let view = GameView::new(&game, players, Variant::Demo);
+        renderer.game_over(&view, &result_message(&game, players, lang));
+        // There's no need to wait after the last game since nothing comes after it
+        if number < limit {
+            thread::sleep(delay * DEMO_RESULT_PAUSE);
+        }
+
+        // Take turns going first so that both players get the advantage equally often
+        first_piece = first_piece.other();
+    }
+}
+
+// Plays every game of a round-robin tournament, saving each one to the archive (if there is one),
+// and then shows the crosstable
+fn play_tournament(entrants: &[Entrant], seed: u64, settings: &Settings,
+    archive: Option<&Archive>, renderer: &dyn Renderer, lang: Lang) -> Result<(), Quit> {
+
+    // Everything logged until the function returns (and `_span` is dropped) is part of the
+    // tournament
+    let _span = info_span!("tournament", seed, entrants = entrants.len()).entered();
+    let names = entrants.iter().map(|entrant| entrant.name.clone()).collect();
+    let mut table = Crosstable::new(names);
+    let pairings = tournament::pairings(entrants.len());
+    let mut played = play_computer_games(&pairings, 0, entrants, seed, settings)
+        .map(Vec::into_iter);
+
+    for (i, &(x, o)) in pairings.iter().enumerate() {
+        let winner = play_tournament_game((&entrants[x], &entrants[o]), (i + 1, pairings.len()),
+            played.as_mut().and_then(Iterator::next), settings, archive, renderer, lang)?;
+        table.record(x, o, winner);
+    }
+
+    renderer.info(&lang.text(Message::TournamentOver));
+    let labels = (lang.text(Message::CrosstableEntrant), lang.text(Message::CrosstableScore));
+    for line in table.lines((&labels.0, &labels.1)) {
+        renderer.info(&line);
+    }
+    Ok(())
+}
+
+// Plays a Swiss tournament with the given number of rounds, showing the standings after each one.
+// Every pair of entrants plays two games per round, one with each piece.
+fn play_swiss(entrants: &[Entrant], rounds: u32, seed: u64, settings: &Settings,
+    archive: Option<&Archive>, renderer: &dyn Renderer, lang: Lang) -> Result<(), Quit> {
+
+    let _span = info_span!("swiss", seed, entrants = entrants.len(), rounds).entered();
+    let names = entrants.iter().map(|entrant| entrant.name.clone()).collect();
+    let mut table = Crosstable::new(names);
+    // With an odd number of entrants, one of them sits out each round
+    let games = rounds as usize * (entrants.len() / 2) * 2;
+    let labels = (lang.text(Message::CrosstableEntrant), lang.text(Message::CrosstableScore),
+        lang.text(Message::StandingsBuchholz));
+    let mut played = 0;
+
+    for round in 1..=rounds {
+        let _round = info_span!("round", round).entered();
+        renderer.info(&lang.text(Message::SwissRound {round, rounds}));
+        renderer.info("");
+
+        let (pairs, bye) = tournament::swiss_pairings(&table);
+        if let Some(entrant) = bye {
+            debug!(entrant = %entrants[entrant].name, "bye");
+            renderer.info(&lang.text(Message::SwissBye(&entrants[entrant].name)));
+            renderer.info("");
+            table.record_bye(entrant);
+        }
+        let round_games: Vec<_> = pairs.into_iter().flat_map(|(a, b)| vec![(a, b), (b, a)])
+            .collect();
+        let mut round_played = play_computer_games(&round_games, played, entrants, seed, settings)
+            .map(Vec::into_iter);
+        for (x, o) in round_games {
+            played += 1;
+            let winner = play_tournament_game((&entrants[x], &entrants[o]), (played, games),
+                round_played.as_mut().and_then(Iterator::next), settings, archive, renderer,
+                lang)?;
+            table.record(x, o, winner);
+        }
+
+        let heading = if round == rounds {
+            Message::TournamentOver
+        }
+        else {
+            Message::SwissStandings(round)
+        };
+        renderer.info(&lang.text(heading));
+        for line in table.standings((&labels.0, &labels.1, &labels.2)) {
+            renderer.info(&line);
+        }
+        renderer.info("");
+    }
+    Ok(())
+}
+
+// The computer players of a simulation, which are easy unless `--x` or `--o` says otherwise
+fn simulation_engines(options: &Options) -> (Computer, Computer) {
+    let engine = |computer: &Option<Computer>| {
+        computer.clone().unwrap_or(Computer::Engine(Difficulty::Easy))
+    };
+    (engine(&options.x_engine), engine(&options.o_engine))
+}
+
+// How the engine plays for a computer player that Options::parse made sure isn't a plugin
+fn engine_profile(computer: &Computer) -> Profile {
+    computer.profile().expect("only the engine can play in simulations and the arena")
+}
+
+// Plays games between two computer players, taking turns going first, until the SPRT decides which
+// of its two Elo differences the results fit or `max_games` have been played. The games are not
+// shown since there can be a lot of them.
+fn play_arena((first, second): (&Computer, &Computer), sprt: Sprt, max_games: u32,
+    adjudicate: bool, renderer: &dyn Renderer, lang: Lang) {
+
+    let (first_name, second_name) = (first.name(), second.name());
+    let (first, second) = (engine_profile(first), engine_profile(second));
+    renderer.info(&lang.text(Message::ArenaStart {
+        first: first_name,
+        second: second_name,
+        elo0: sprt.elo0,
+        elo1: sprt.elo1,
+        max_games,
+    }));
+
+    let mut results = Results::default();
+    let mut verdict = Verdict::Undecided;
+    while verdict == Verdict::Undecided && results.games() < max_games {
+        results.record(arena::play(first, second, results.games(), adjudicate,
+            &mut random::shared()));
+        verdict = sprt.verdict(&results);
+        // The results are shown at the end anyway
+        let finished = verdict != Verdict::Undecided || results.games() == max_games;
+        if results.games().is_multiple_of(ARENA_PROGRESS_GAMES) && !finished {
+            show_arena_results(&results, renderer, lang);
+        }
+    }
+
+    info!(games = results.games(), wins = results.wins, losses = results.losses,
+        ties = results.ties, ?verdict, "arena finished");
+    renderer.info("");
+    show_arena_results(&results, renderer, lang);
+    if let Some((elo, low, high)) = results.elo_difference() {
+        renderer.info(&lang.text(Message::ArenaElo {elo, low, high}));
+    }
+    renderer.info(&lang.text(match verdict {
+        Verdict::Accepted => {
+            Message::ArenaAccepted {first: first_name, second: second_name, elo: sprt.elo1}
+        },
+        Verdict::Rejected => {
+            Message::ArenaRejected {first: first_name, second: second_name, elo: sprt.elo0}
+        },
+        Verdict::Undecided => Message::ArenaUndecided(results.games()),
+    }));
+}
+
+fn show_arena_results(results: &Results, renderer: &dyn Renderer, lang: Lang) {
+    renderer.info(&lang.text(Message::ArenaResults {
+        games: results.games(),
+        wins: results.wins,
+        losses: results.losses,
+        ties: results.ties,
+    }));
+}
+
+// Plays `games` games between the two computer players as fast as possible, showing the results as
+// it goes along and once more at the end. The games are played in parallel, in batches so that
+// there is something to show while a long simulation runs.
+fn play_simulation((x, o): (&Computer, &Computer), games: u32, seed: u64, adjudicate: bool,
+    renderer: &dyn Renderer, lang: Lang) {
+
+    renderer.info(&lang.text(Message::SimulationStart {games, x: x.name(), o: o.name()}));
+    let (x, o) = (engine_profile(x), engine_profile(o));
+    let mut simulation = Simulation::default();
+    let start = Instant::now();
+    let mut played = 0;
+    while played < games {
+        let batch = played..games.min(played + SIMULATION_PROGRESS_GAMES);
+        played = batch.end;
+        debug!(games = ?batch, "simulating");
+        simulation.add(&simulate::simulate(x, o, batch, seed, adjudicate));
+        if played < games {
+            renderer.info(&lang.text(Message::SimulationResults(&simulation)));
+        }
+    }
+    simulation.time = start.elapsed();
+    info!(games, seconds = simulation.time.as_secs_f64(), "simulation finished");
+
+    renderer.info("");
+    renderer.info(&lang.text(Message::SimulationResults(&simulation)));
+    renderer.info(&lang.text(Message::SimulationLengths(&simulation)));
+    if adjudicate {
+        renderer.info(&lang.text(Message::SimulationAdjudicated(&simulation)));
+    }
+    renderer.info(&lang.text(Message::SimulationTime(&simulation)));
+}
 
-// This is how we "import" a module from the standard library. A module is a group of functions and
-// types. "std" stands for "standard library" and "io" stands for "input/output". We will use this
-// module to read input from the user of our application.
-// The import "self" imports the name "io" itself, and "Write" imports the "Write trait" which we
-// need to flush stdout below.
-use std::io::{self, Write};
-// We use the process::exit function to quit the program when we need to.
-use std::process;
+// Plays the games between the given pairs of entrants (x first) all at once, if every one of them
+// is a computer player. Game `i` of the list is game `first + i` of the tournament, counting from
+// zero, which picks its random number stream. That way the games come out the same for the same
+// seed, no matter how many cores play them. Returns None if a person plays in any of the games,
+// since people can only play one game at a time. The same goes for plugins, which may not expect
+// to be asked for several moves at once. The games are played by the rules in the settings, and
+// end as soon as their result is certain if the settings say to adjudicate them.
+fn play_computer_games(pairs: &[(usize, usize)], first: usize, entrants: &[Entrant],
+    seed: u64, settings: &Settings) -> Option<Vec<Game>> {
 
-// This is how we import names from our own module. Notice that there is no "std::" prefix.
-// For more information on modules, see:
-// https://doc.rust-lang.org/book/second-edition/ch07-00-modules.html
-use game::{Game, Piece, Winner, Tiles, MoveError};
+    let engine = |entrant: &Entrant| entrant.engine.as_ref().and_then(Computer::profile);
+    let engines: Option<Vec<_>> = pairs.iter()
+        .map(|&(x, o)| Some((engine(&entrants[x])?, engine(&entrants[o])?)))
+        .collect();
+    let games: Vec<_> = engines?.into_par_iter().enumerate().map(|(i, (x, o))| {
+        let rng = &mut Rng::stream(seed, (first + i) as u64);
+        simulate::play_from(settings.new_game(Piece::X), x, o, settings.adjudicate, rng)
+    }).collect();
+    debug!(first, games = games.len(), "played the computer games ahead of time");
+    Some(games)
+}
 
-// This type is used to provide an error when the user provides an invalid move string. If we
-// wanted to avoid copying the invalid string, we could use &str instead and Rust would enforce at
-// compile time that the reference remained valid until any instance of InvalidPiece containing it
-// goes out of scope. String is used for the same of simplicity. By marking the type stored in this
-// struct as `pub`, its value can be freely accessed even in patterns (for example, match
-// statements).
-#[derive(Debug, Clone)]
-pub struct InvalidMove(pub String);
+// Plays one game of a tournament between the given entrants (x first), announcing it as game
+// `number` of `games`. If the game was already played by computer players, it is only shown.
+// Returns how the game ended.
+fn play_tournament_game((x, o): (&Entrant, &Entrant), (number, games): (usize, usize),
+    played: Option<Game>, settings: &Settings, archive: Option<&Archive>,
+    renderer: &dyn Renderer, lang: Lang) -> Result<Winner, Quit> {
 
-// The main function is where Rust starts running our program from. No code is allowed outside of
-// functions so that you can rely on the code in main() running first.
-fn main() {
-    // The constructor for Game creates a new, empty Tic-Tac-Toe board. `mut` signals that we plan
-    // to modify the value of the game variable. Rust will tell us if we forget to use this and
-    // warn us if we use it but it isn't needed.
-    let mut game = Game::new();
-
-    // Let's continuously prompt the user for input using a loop until the game is finished
-    while !game.is_finished() {
-        // First, print out the current board
-        print_tiles(game.tiles());
-
-        // Inform the user of who's turn it currently is
-        // match will enforce that we do not forget any case and the string that it produces will
-        // replace `{}` in the printed string.
-        println!("Current piece: {}", match game.current_piece() {
-            Piece::X => "x",
-            Piece::O => "o",
-        });
+    let _span = info_span!("game", number, x = %x.name, o = %o.name).entered();
+    renderer.info(&lang.text(Message::TournamentGame {number, games, x: &x.name, o: &o.name}));
+    renderer.info("");
+
+    // Each game is played with the entrants' names, so that it is saved under their names in
+    // the archive and counts towards their stats and ratings. The rest of the settings (like
+    // the clocks) are the same for every game.
+    let profile = |entrant: &Entrant| {
+        PlayerProfile {name: Some(entrant.name.clone()), glyph: None}
+    };
+    let game_settings = Settings {
+        first_piece: Piece::X,
+        players: Players::new(profile(x), profile(o)),
+        x_engine: x.engine.clone(),
+        o_engine: o.engine.clone(),
+        ..settings.clone()
+    };
+    let game = match played {
+        Some(game) => {
+            replay_game(&game, &game_settings, renderer, lang);
+            game
+        },
+        None => {
+            let game = game_settings.new_game(Piece::X);
+            play_game(game, &game_settings, archive, renderer, lang)?
+        },
+    };
+    if let Some(archive) = archive {
+        save_to_archive(archive, archive.record_game(&game, &game_settings.players), lang);
+    }
+    renderer.info("");
+    Ok(game.winner().expect("game should be finished"))
+}
+
+// Plays either a single game or a whole series depending on the settings, and then saves the
+// result to the archive (if there is one)
+fn play_round(settings: &Settings, archive: Option<&Archive>, renderer: &dyn Renderer,
+    lang: Lang) -> Result<Vec<Game>, Quit> {
+
+    // The ratings from before this round, so that we can show how much they changed afterwards.
+    // Not being able to read the archive only means that we can't show the ratings.
+    let ratings_before = archive.and_then(|archive| archive.games().ok())
+        .map(|games| rating::compute(&games));
+    // A rematch is saved as part of the same match as the games before it
+    let archive = archive.map(|archive| match settings.rematch {
+        Some(rematch) => archive.clone().with_rematch(rematch),
+        None => archive.clone(),
+    });
+    let archive = archive.as_ref();
+
+    // The games of the round are given back so that the last one can be reviewed and so that a
+    // rematch can add them to the score
+    let games = if settings.best_of > 1 {
+        let series = play_series(settings, archive, renderer, lang)?;
+        if let Some(archive) = archive {
+            save_to_archive(archive, archive.record_series(&series, &settings.players), lang);
+        }
+        series.games().to_vec()
+    }
+    else {
+        let game = settings.new_game(settings.first_piece);
+        let game = play_game(game, settings, archive, renderer, lang)?;
+        if let Some(archive) = archive {
+            save_to_archive(archive, archive.record_game(&game, &settings.players), lang);
+        }
+        vec![game]
+    };
+
+    if let (Some(archive), Some(before)) = (archive, ratings_before) {
+        show_rating_changes(archive, &before, &settings.players, renderer, lang);
+    }
+    Ok(games)
+}
+
+// Shows each player's new rating and how much it changed since `before`. Nothing is shown unless
+// both players have a name, since only those games are rated.
+fn show_rating_changes(archive: &Archive, before: &BTreeMap<String, rating::Rating>,
+    players: &Players, renderer: &dyn Renderer, lang: Lang) {
+
+    let after = match archive.games() {
+        Ok(games) => rating::compute(&games),
+        Err(_) => return,
+    };
+    for &piece in &[Piece::X, Piece::O] {
+        let name = match players.profile(piece).name {
+            Some(ref name) => name,
+            None => return,
+        };
+        if let Some(rated) = after.get(name) {
+            let old = before.get(name).map_or(rating::INITIAL_RATING, |rated| rated.rating);
+            renderer.info(&lang.text(Message::RatingChange {
+                player: name,
+                rating: rated.rating.round() as i32,
+                change: (rated.rating.round() - old.round()) as i32,
+            }));
+        }
+    }
+}
+
+// Asks the players what they want to do now that the game is over
+fn prompt_play_again(renderer: &dyn Renderer, lang: Lang) -> Result<PlayAgain, Quit> {
+    loop {
+        match PlayAgain::parse(&read_line(&lang.text(Message::PlayAgain))?) {
+            Some(answer) => break Ok(answer),
+            None => renderer.error(error::INVALID_ANSWER, &lang.text(Message::PlayAgainHelp)),
+        }
+    }
+}
+
+// Steps through a finished game one move at a time. Each move is shown on the board it was played
+// on, marked the way the solver sees it, along with the better moves if there were any. The
+// player can go back and forth through the moves, and the review ends with how many moves of
+// each kind both players made.
+fn review_game(game: &Game, players: &Players, renderer: &dyn Renderer,
+    lang: Lang) -> Result<(), Quit> {
+
+    let reviewed = review::review(game);
+    renderer.info("");
+    renderer.info(&lang.text(Message::ReviewIntro));
+    let mut i = 0;
+    while i < reviewed.len() {
+        let mv = &reviewed[i];
+        let position = position_after(game, i);
+        renderer.board(&GameView::new(&position, players, Variant::Review));
+        renderer.info(&lang.text(Message::ReviewMove {
+            player: &players.label(mv.piece),
+            row: mv.row,
+            col: mv.col,
+            mark: mv.mark,
+            better: &mv.better,
+        }));
+        if let Some(time) = mv.thinking {
+            renderer.info(&lang.text(Message::ThinkTime(time)));
+        }
+
+        let step = loop {
+            match Step::parse(&read_line(&lang.text(Message::ReviewPrompt))?) {
+                Some(step) => break step,
+                None => renderer.error(error::INVALID_ANSWER, &lang.text(Message::ReviewHelp)),
+            }
+        };
+        match step {
+            Step::Next => i += 1,
+            // Going back from the first move shows it again
+            Step::Back => i = i.saturating_sub(1),
+            Step::Done => break,
+        }
+    }
+
+    for &piece in &[Piece::X, Piece::O] {
+        renderer.info(&lang.text(Message::ReviewSummary {
+            player: &players.label(piece),
+            counts: review::counts(&reviewed, piece),
+        }));
+        if let Some((total, moves)) = review::thinking(&reviewed, piece) {
+            let player = &players.label(piece);
+            renderer.info(&lang.text(Message::ReviewThinking {player, total, moves}));
+        }
+    }
+    renderer.info("");
+    Ok(())
+}
+
+// Asks for each setting in turn. Just pressing enter keeps the current value.
+fn prompt_settings(current: Settings, renderer: &dyn Renderer, lang: Lang)
+    -> Result<Settings, Quit> {
+
+    // Start from a copy of the current settings and then change the ones we ask about
+    let mut settings = current.clone();
+
+    settings.best_of = loop {
+        let line = read_line(&lang.text(Message::NumberOfGames(current.best_of)))?;
+        if line.trim().is_empty() {
+            break current.best_of;
+        }
+        match line.trim().parse() {
+            Ok(best_of) if best_of > 0 => break best_of,
+            _ => renderer.error(error::INVALID_ANSWER, &lang.text(Message::NumberOfGamesHelp)),
+        }
+    };
+
+    settings.first_piece = loop {
+        let line = read_line(&lang.text(Message::FirstPiece(current.first_piece)))?;
+        match &line.trim().to_lowercase()[..] {
+            "" => break current.first_piece,
+            "x" => break Piece::X,
+            "o" => break Piece::O,
+            _ => renderer.error(error::INVALID_ANSWER, &lang.text(Message::FirstPieceHelp)),
+        }
+    };
+
+    Ok(settings)
+}
+
+// Plays up to `settings.best_of` games, letting the players take turns going first, and announces
+// the winner of the whole series at the end
+fn play_series(settings: &Settings, archive: Option<&Archive>, renderer: &dyn Renderer,
+    lang: Lang) -> Result<Series, Quit> {
+
+    let players = &settings.players;
+    let mut series = Series::starting_with(settings.best_of, settings.first_piece);
+
+    while !series.is_finished() {
+        let first_piece = series.next_first_piece();
+        renderer.info(&lang.text(Message::GameHeader {
+            number: series.games().len() + 1,
+            best_of: settings.best_of,
+            first_player: &players.label(first_piece),
+        }));
+        renderer.info("");
+
+        let game = play_game(settings.new_game(first_piece), settings, archive, renderer, lang)?;
+        series.record(game);
+
+        renderer.info(&lang.text(Message::SeriesScore {
+            x: players.name(Piece::X),
+            o: players.name(Piece::O),
+            score: series.score(),
+        }));
+        renderer.info("");
+    }
+
+    let winner = series.winner().expect("finished series should have winner");
+    renderer.info(&lang.text(Message::SeriesOver {
+        winner,
+        x: players.name(Piece::X),
+        o: players.name(Piece::O),
+        score: series.score(),
+    }));
+
+    Ok(series)
+}
+
+// Saving is not essential to playing, so if it fails we let the user know instead of exiting
+fn save_to_archive(archive: &Archive, result: io::Result<Option<Recovery>>, lang: Lang) {
+    let path = archive.path();
+    match result {
+        Ok(recovery) => {
+            debug!(path = %path.display(), "saved to the archive");
+            if let Some(ref recovery) = recovery {
+                report_recovery(path, recovery, lang);
+            }
+        },
+        Err(error) => {
+            warn!(path = %path.display(), %error, "failed to save to the archive");
+            eoutln!("{}", lang.text(Message::ArchiveFailed {path, error: &error}));
+        },
+    }
+}
+
+// Lets the player know that a saved file was corrupt and what was done about it (see storage.rs)
+fn report_recovery(path: &Path, recovery: &Recovery, lang: Lang) {
+    warn!(path = %path.display(), quarantined = %recovery.quarantined.display(),
+        restored = recovery.restored, "the file was corrupt");
+    eoutln!("{}", lang.text(Message::FileRecovered {path, recovery}));
+}
+
+// Called when Ctrl-C is pressed during a game. Offers to save the unfinished game and then returns
+// how the players quit, which is usually Ctrl-C but can also be the end of the input if it runs out
+// while we ask about saving.
+fn quit_game(game: &Game, settings: &Settings, archive: Option<&Archive>, renderer: &dyn Renderer,
+    lang: Lang) -> Quit {
+
+    info!(moves = game.moves().len(), "game interrupted");
+    renderer.info("");
+    // If no moves were made yet, there is nothing worth saving
+    let save_to = if game.moves().is_empty() {
+        None
+    }
+    else {
+        match archive {
+            Some(archive) if settings.autosave => Some(archive.clone()),
+            // `loop` keeps asking until we get a yes or a no
+            Some(archive) => loop {
+                let prompt = lang.text(Message::SaveBeforeQuitting(archive.path()));
+                let line = match read_line(&prompt) {
+                    Ok(line) => line,
+                    Err(quit) => return quit,
+                };
+                match parse_yes_no(&line) {
+                    Some(true) => break Some(archive.clone()),
+                    Some(false) => break None,
+                    None => renderer.error(error::INVALID_ANSWER, &lang.text(Message::YesNoHelp)),
+                }
+            },
+            // Without an archive, we ask where to save the game instead
+            None => {
+                let line = match read_line(&lang.text(Message::SaveToFile)) {
+                    Ok(line) => line,
+                    Err(quit) => return quit,
+                };
+                let path = line.trim();
+                if path.is_empty() {
+                    None
+                }
+                else {
+                    Some(Archive::new(path))
+                }
+            },
+        }
+    };
+
+    if let Some(archive) = save_to {
+        match archive.record_game(game, &settings.players) {
+            Ok(recovery) => {
+                info!(path = %archive.path().display(), "saved the unfinished game");
+                if let Some(ref recovery) = recovery {
+                    report_recovery(archive.path(), recovery, lang);
+                }
+                renderer.info(&lang.text(Message::GameSaved(archive.path())))
+            },
+            result => save_to_archive(&archive, result, lang),
+        }
+    }
+
+    Quit::Interrupted
+}
+
+// Plays a single game from start to finish and returns it once it is over, or returns how the
+// players quit if they didn't finish it
+fn play_game(game: Game, settings: &Settings, archive: Option<&Archive>,
+    renderer: &dyn Renderer, lang: Lang) -> Result<Game, Quit> {
+
+    // A single game is played at a club with only one table (see club.rs)
+    let mut club = Club::single(game, settings.clone());
+    let table = play_tables(&mut club, archive, renderer, lang)?;
+    Ok(table.game)
+}
+
+// Plays a club night: the players can set up a table for every game they want to play at once
+// and go from one to another at the move prompt. Each game is saved to the archive (if there is
+// one) as soon as it is over, and the night is over once every game is.
+fn play_club(settings: &Settings, archive: Option<&Archive>, renderer: &dyn Renderer,
+    lang: Lang) -> Result<(), Quit> {
+
+    let _span = info_span!("club").entered();
+    renderer.info(&lang.text(Message::ClubIntro));
+    renderer.info("");
+    let mut club = Club::new(settings.new_game(settings.first_piece), settings.clone());
+    while !club.is_empty() {
+        let table = play_tables(&mut club, archive, renderer, lang)?;
+        if let Some(archive) = archive {
+            save_to_archive(archive, archive.record_game(&table.game, &table.settings.players),
+                lang);
+        }
+        renderer.info("");
+        if !club.is_empty() {
+            renderer.info(&lang.text(Message::ClubGameOver(table.id)));
+            show_tables(&club, renderer, lang);
+            renderer.info("");
+        }
+    }
+    Ok(())
+}
+
+// Gives a simul: the giver plays x at a board against each of the opponents, moving on to the
+// next board after each of their moves (see club.rs). Each game is saved to the archive as soon as
+// it is over, and the giver's score is shown once every game is.
+fn play_simul(opponents: &[String], giver: Option<Computer>, settings: &Settings, theme: Theme,
+    archive: Option<&Archive>, renderer: &dyn Renderer, lang: Lang) -> Result<(), Quit> {
+
+    let _span = info_span!("simul", boards = opponents.len()).entered();
+    // A computer giver without a name of its own goes by the name of the computer player
+    let mut profile = settings.players.profile(Piece::X).clone();
+    if let (None, Some(computer)) = (&profile.name, &giver) {
+        profile.name = Some(computer.name().to_string());
+    }
+    let players = Players::new(profile, settings.players.profile(Piece::O).clone());
+    let settings = Settings {players, x_engine: giver, ..settings.clone()};
+    let opponents = opponents.iter()
+        .map(|name| PlayerProfile {name: Some(name.clone()), glyph: None})
+        .collect();
+    let mut club = Club::simul(settings.clone(), opponents, theme);
+
+    let giver = settings.players.label(Piece::X);
+    renderer.info(&lang.text(Message::SimulIntro {giver: &giver, boards: club.tables().len()}));
+    renderer.info("");
+    show_boards(&club, renderer, lang);
+    let (mut wins, mut ties, mut losses) = (0, 0, 0);
+    while !club.is_empty() {
+        let table = play_tables(&mut club, archive, renderer, lang)?;
+        match table.game.winner() {
+            Some(Winner::X) => wins += 1,
+            Some(Winner::O) => losses += 1,
+            _ => ties += 1,
+        }
+        if let Some(archive) = archive {
+            save_to_archive(archive, archive.record_game(&table.game, &table.settings.players),
+                lang);
+        }
+        renderer.info("");
+        if !club.is_empty() {
+            renderer.info(&lang.text(Message::ClubGameOver(table.id)));
+            show_boards(&club, renderer, lang);
+        }
+    }
+    renderer.info(&lang.text(Message::SimulResult {giver: &giver, wins, ties, losses}));
+    Ok(())
+}
+
+// Shows every board of a simul at once, unless the boards are hidden in blindfold mode
+fn show_boards(club: &Club, renderer: &dyn Renderer, lang: Lang) {
+    if club.focused().variant() == Variant::Classic {
+        renderer.info(&club.overview(lang));
+    }
+}
+
+// Lists the tables of a club, marking the one that has the focus
+fn show_tables(club: &Club, renderer: &dyn Renderer, lang: Lang) {
+    for table in club.tables() {
+        let players = &table.settings.players;
+        renderer.info(&lang.text(Message::ClubTable {
+            id: table.id,
+            focused: table.id == club.focused().id,
+            x: &players.label(Piece::X),
+            o: &players.label(Piece::O),
+            moves: table.game.moves().len(),
+            current: &players.label(table.game.current_piece()),
+        }));
+    }
+}
+
+// Asks for the names of the players at a new table. Just pressing enter leaves a player without a
+// name.
+fn prompt_players(lang: Lang) -> Result<Players, Quit> {
+    let mut profiles = Vec::new();
+    for &piece in &[Piece::X, Piece::O] {
+        let line = read_line(&lang.text(Message::ClubPlayerName(piece)))?;
+        let name = Some(line.trim().to_string()).filter(|name| !name.is_empty());
+        profiles.push(PlayerProfile {name, glyph: None});
+    }
+    let o = profiles.pop().expect("there is a profile for o");
+    let x = profiles.pop().expect("there is a profile for x");
+    Ok(Players::new(x, o))
+}
+
+// Called when Ctrl-C is pressed at a club. Offers to save every unfinished game, one table at a
+// time, and returns how the players quit.
+fn quit_club(club: &Club, archive: Option<&Archive>, renderer: &dyn Renderer, lang: Lang) -> Quit {
+    for table in club.tables() {
+        // There is no need to say which game it is when there is only one
+        if club.tables().len() > 1 && !table.game.moves().is_empty() {
+            renderer.info("");
+            renderer.info(&lang.text(Message::ClubSaving(table.id)));
+        }
+        let quit = quit_game(&table.game, &table.settings, archive, renderer, lang);
+        if quit != Quit::Interrupted {
+            return quit;
+        }
+    }
+    Quit::Interrupted
+}
+
+// Plays the game at the table with the focus until it is over, going to the other tables of the
+// club whenever the players ask to. Returns the table whose game ended, after taking it away from
+// the club, or returns how the players quit if they didn't finish.
+fn play_tables(club: &mut Club, archive: Option<&Archive>, renderer: &dyn Renderer,
+    lang: Lang) -> Result<club::Table, Quit> {
+
+    // Let's continuously prompt the user for input using a loop until the game is finished.
+    // Everything about the game (the players, the clocks and so on) belongs to the table it is
+    // played at, which changes whenever the players switch to another one.
+    while !club.focused().game.is_finished() {
+        let table = club.focused_mut();
+        let players = &table.settings.players;
+        let piece = table.game.current_piece();
+        let variant = table.variant();
+
+        // First, show the current board using whichever renderer was chosen. The status header
+        // above the board includes the clocks so the players always know how much time is left.
+        if let Some(moves) = table.browsing {
+            let shown = position_after(&table.game, moves);
+            let clocks = table.clocks.as_ref();
+            renderer.board(&GameView {game: &shown, players, clocks, variant});
+            let total = table.game.moves().len();
+            renderer.info(&lang.text(Message::Browsing {moves, total}));
+        }
+        else {
+            let clocks = table.clocks.as_ref();
+            renderer.board(&GameView {game: &table.game, players, clocks, variant});
+
+            // Inform the user of who's turn it currently is. All of the text we show to the user
+            // comes from the locale module so that it can be shown in the user's language.
+            let message = lang.text(Message::CurrentPiece(&players.label(piece)));
+            renderer.turn(&table.game, players, &message);
+        }
+
+        // The computer answers right away, so it never needs the clock or any of the commands
+        if let Some(computer) = table.settings.engine(piece) {
+            let game = &mut table.game;
+            let started = Instant::now();
+            let (row, col) = match *computer {
+                Computer::Engine(difficulty) => computer_move(game, difficulty),
+                Computer::Profile(ref name, ref profile) => profile_move(game, name, profile),
+                // A plugin that can't play a legal move can't go on with the game
+                Computer::Plugin(ref name) => match plugin::player(name)
+                    .and_then(|plugin| plugin.choose_move(game)) {
+                    Ok(tile) => tile,
+                    Err(error) => {
+                        warn!(plugin = %name, %error, "the plugin failed to choose a move");
+                        renderer.error((&error).into(),
+                            &lang.text(Message::PluginMoveFailed {name, error: &error}));
+                        game.forfeit(piece).expect("game should not be over while a plugin moves");
+                        break;
+                    },
+                },
+            };
+            game.make_move(row, col).expect("the computer should only choose empty tiles");
+            game.time_last_move(MoveTime {made: clock::wall_clock(), thinking: started.elapsed()});
+            // The tags say which computer player made the move, so that it is still known once the
+            // game is saved
+            match *computer {
+                Computer::Engine(difficulty) => game.tag_last_move("engine", difficulty.name()),
+                Computer::Profile(ref name, _) => game.tag_last_move("engine", name.as_str()),
+                Computer::Plugin(ref name) => game.tag_last_move("plugin", name.as_str()),
+            }
+            bus::publish(Event::ComputerMoved {piece, players, row, col});
+            renderer.move_made(game, players, row, col);
+            bus::publish(Event::MoveMade {game, players, row, col});
+            let finished = game.is_finished();
+            next_board(club, piece, finished, renderer, lang);
+            continue;
+        }
 
         // prompt_move continuously prompts for a valid move from the user, determines exactly
-        // which position on the board that move is referring to, and then returns that move
-        let (row, col) = prompt_move();
+        // which position on the board that move is referring to, and then returns that move (or
+        // the command that the player typed instead).
+        // When the player is on the clock, they only have until their time runs out to answer.
+        // With an idle timeout, they only have until then, if that comes first.
+        let started = Instant::now();
+        let flag = table.clocks.as_ref().map(|clocks| started + clocks.remaining(piece));
+        let idle = table.settings.idle.map(|idle| started + idle.timeout);
+        // `chain` puts the two Options one after the other, and `min` picks the earlier time
+        let deadline = flag.into_iter().chain(idle).min();
+        let turn = prompt_move(renderer, lang, deadline);
+
+        // Whatever happened, the time the player spent thinking comes off of their clock
+        let elapsed = started.elapsed();
+        table.thinking += elapsed;
+        if let Some(ref mut clocks) = table.clocks {
+            clocks.spend(piece, elapsed);
+            bus::publish(Event::ClockTick {piece, remaining: clocks.remaining(piece)});
+            if clocks.flag_fell(piece) {
+                renderer.info("");
+                renderer.info(&lang.text(Message::FlagFell(&players.label(piece))));
+                table.game.forfeit(piece)
+                    .expect("game should not be over while a player is thinking");
+                break;
+            }
+        }
+        let turn = match turn {
+            Ok(turn) => turn,
+            // Ctrl-C gives the players a chance to save their games before quitting
+            Err(Quit::Interrupted) => return Err(quit_club(club, archive, renderer, lang)),
+            Err(quit) => return Err(quit),
+        };
+        // If the clock didn't run out, the only other deadline is the idle timeout. Without either
+        // one, prompt_move waits forever and always returns a move.
+        let (turn, idle_move) = match turn {
+            Some(turn) => (turn, false),
+            None => {
+                let idle = table.settings.idle.expect("move should be entered if no time ran out");
+                renderer.info("");
+                let player = players.label(piece);
+                match idle.action {
+                    IdleAction::Forfeit => {
+                        renderer.info(&lang.text(Message::IdleForfeit(&player)));
+                        table.game.forfeit(piece)
+                            .expect("game should not be over while a player is thinking");
+                        break;
+                    },
+                    // The move is made just like the player's own would be, so that it can be
+                    // taken back like any other
+                    IdleAction::Move => {
+                        table.browsing = None;
+                        let (row, col) = computer_move(&table.game, IDLE_DIFFICULTY);
+                        renderer.info(&lang.text(Message::IdleMoved {player: &player, row, col}));
+                        (Turn::Move(row, col), true)
+                    },
+                }
+            },
+        };
+        if let Turn::Command(ref command) = turn {
+            bus::publish(Event::Command(command));
+        }
+        let (row, col) = match turn {
+            // Earlier positions can only be looked at. Playing from one of them would change what
+            // happened after it, which is what undo is for.
+            Turn::Move(..) if table.browsing.is_some() => {
+                renderer.error(error::BROWSING, &lang.text(Message::BrowsingNoMoves));
+                continue;
+            },
+            Turn::Move(row, col) => (row, col),
+            // Commands don't end the player's turn. We show the board again and wait for a move.
+            Turn::Command(Command::Hint) => {
+                renderer.info(&hint(&table.game, lang));
+                continue;
+            },
+            // Undoing while looking at an earlier position takes back every move after it
+            Turn::Command(Command::Undo) if table.browsing.is_some() => {
+                let moves = table.browsing.take()
+                    .expect("the player is looking at an earlier position");
+                table.thinking = Duration::from_secs(0);
+                while table.game.moves().len() > moves {
+                    let (row, col) = table.game.undo().expect("there are moves after the position");
+                    renderer.info(&lang.text(Message::MoveUndone {row, col}));
+                    bus::publish(Event::MoveUndone {game: &table.game, row, col});
+                }
+                continue;
+            },
+            // Taking back a move starts a new turn, so the thinking starts over too
+            Turn::Command(Command::Undo) => {
+                table.thinking = Duration::from_secs(0);
+                match table.game.undo() {
+                    Some((row, col)) => {
+                        renderer.info(&lang.text(Message::MoveUndone {row, col}));
+                        bus::publish(Event::MoveUndone {game: &table.game, row, col});
+                    },
+                    None => {
+                        renderer.error(error::NOTHING_TO_UNDO, &lang.text(Message::NothingToUndo))
+                    },
+                }
+                continue;
+            },
+            Turn::Command(Command::Back) => {
+                match table.browsing.unwrap_or_else(|| table.game.moves().len()) {
+                    0 => {
+                        let message = lang.text(Message::NoEarlierPosition);
+                        renderer.error(error::NO_EARLIER_POSITION, &message)
+                    },
+                    moves => table.browsing = Some(moves - 1),
+                }
+                continue;
+            },
+            Turn::Command(Command::Forward) => {
+                match table.browsing {
+                    Some(moves) if moves + 1 < table.game.moves().len() => {
+                        table.browsing = Some(moves + 1)
+                    },
+                    // Going forward from the last move before the game as it is goes back to it
+                    Some(_) => table.browsing = None,
+                    None => renderer.error(error::NOT_BROWSING, &lang.text(Message::NotBrowsing)),
+                }
+                continue;
+            },
+            // The time spent thinking before the pause has already come off of the player's
+            // clock, and the next turn starts the clock again once the game goes on. Clearing the
+            // screen hides the board, so nobody can think about the game during the pause.
+            Turn::Command(Command::Pause) => {
+                renderer.clear();
+                renderer.info(&lang.text(Message::Paused));
+                let (started, paused) = (clock::wall_clock(), Instant::now());
+                let answer = read_line(&lang.text(Message::EnterResume));
+                let length = paused.elapsed();
+                // Even a pause that ends with Ctrl-C is saved with the game
+                table.game.record_pause(Pause {started, seconds: length.as_secs()});
+                match answer {
+                    Ok(_) => renderer.info(&lang.text(Message::Resumed(length))),
+                    Err(Quit::Interrupted) => {
+                        return Err(quit_club(club, archive, renderer, lang))
+                    },
+                    Err(quit) => return Err(quit),
+                }
+                continue;
+            },
+            Turn::Command(Command::Show) => {
+                match table.shows_left {
+                    Some(0) => {
+                        renderer.error(error::NO_SHOWS_LEFT, &lang.text(Message::NoShowsLeft))
+                    },
+                    Some(ref mut shows) => {
+                        *shows -= 1;
+                        let clocks = table.clocks.as_ref();
+                        let game = &table.game;
+                        renderer.show_board(&GameView {game, players, clocks, variant});
+                        renderer.info(&lang.text(Message::ShowsLeft(*shows)));
+                    },
+                    // When the board isn't hidden, it is about to be drawn again anyway
+                    None => {},
+                }
+                continue;
+            },
+            Turn::Command(Command::Code) => {
+                show_code(&table.game, &table.settings, renderer, lang);
+                continue;
+            },
+            // At a simul, the boards come up in turn, so the players can only look at them
+            Turn::Command(Command::Games) if club.is_simul() => {
+                show_boards(club, renderer, lang);
+                continue;
+            },
+            Turn::Command(Command::Switch(_)) | Turn::Command(Command::New) if club.is_simul() => {
+                renderer.error(error::NOT_AT_A_CLUB, &lang.text(Message::SimulFixed));
+                continue;
+            },
+            // The other tables are only there at a club night
+            Turn::Command(Command::Games) | Turn::Command(Command::Switch(_))
+                | Turn::Command(Command::New) if !club.is_open() => {
+
+                renderer.error(error::NOT_AT_A_CLUB, &lang.text(Message::ClubOnly));
+                continue;
+            },
+            Turn::Command(Command::Games) => {
+                show_tables(club, renderer, lang);
+                continue;
+            },
+            // The turn at the table being left is picked up again where it was, so the thinking
+            // time is kept too
+            Turn::Command(Command::Switch(id)) => {
+                if club.switch(id) {
+                    info!(table = club.focused().id, "switched tables");
+                    renderer.info(&lang.text(Message::ClubSwitched(club.focused().id)));
+                }
+                else {
+                    let id = id.expect("switching to the next table always works");
+                    renderer.error(error::NO_SUCH_TABLE, &lang.text(Message::ClubNoTable(id)));
+                }
+                continue;
+            },
+            Turn::Command(Command::New) => {
+                let players = match prompt_players(lang) {
+                    Ok(players) => players,
+                    Err(Quit::Interrupted) => {
+                        return Err(quit_club(club, archive, renderer, lang))
+                    },
+                    Err(quit) => return Err(quit),
+                };
+                let id = club.open_table(players);
+                info!(table = id, "set up a new table");
+                renderer.info(&lang.text(Message::ClubSwitched(id)));
+                continue;
+            },
+        };
 
         // Now that we have a move, let's attempt to make it
         // We use match to account for every case of the result
-        match game.make_move(row, col) {
-            // If the move is made successfully, we can just move on. You can think of empty
-            // curly braces as an "empty expression". We could have also used the unit value `()`.
-            Ok(()) => {},
+        match table.game.make_move(row, col) {
+            // If the move is made successfully, we let the renderer describe it and move on.
+            // Completing a move also earns the player their increment.
+            Ok(()) => {
+                if let Some(ref mut clocks) = table.clocks {
+                    clocks.add_increment(piece);
+                }
+                table.game.time_last_move(MoveTime {
+                    made: clock::wall_clock(),
+                    thinking: table.thinking,
+                });
+                table.thinking = Duration::from_secs(0);
+                // Just like the computer's own moves, the tag says who chose the move
+                if idle_move {
+                    table.game.tag_last_move("idle", IDLE_DIFFICULTY.name());
+                }
+                renderer.move_made(&table.game, players, row, col);
+                bus::publish(Event::MoveMade {game: &table.game, players, row, col});
+                let finished = table.game.is_finished();
+                next_board(club, piece, finished, renderer, lang);
+            },
             // Match allows us to conveniently match even nested types like Result and pull out the
             // fields as variables
 
@@ -80,41 +3305,124 @@ fn main() {
             // print an error message.
             // The `eprintln!` macro is exactly the same as `println!` except it prints to stderr
             // instead of stdout.
-            Err(MoveError::TileNotEmpty {other_piece, row, col}) => eprintln!(
-                // Each {} will be replaced with one of the arguments following this string
-                "The tile at position {}{} already has piece {} in it!",
-                // The row number that is displayed starts at 1, not zero, so we add 1 to get the
-                // correct value
-                row + 1,
-                // `b'A'` produces the ASCII character code for the letter A (i.e. 65)
-                // Adding col to it will produce either 65 (A), 66 (B), or 67 (C).
-                // `as u8` is necessary because b'A' has type u8 and we can't add u8 to usize
-                // without performing a conversion first.
-                // Converting it to char using `as char` will get Rust to format this as a
-                // character rather than printing the number out
-                (b'A' + col as u8) as char,
-                // match allows us to print something for each case and will tell us if something
-                // ever changes such that this is no longer complete
-                match other_piece {
-                    Piece::X => "x",
-                    Piece::O => "o",
-                },
-            ),
+            Err(ref error @ MoveError::TileNotEmpty {other_piece, row, col, since}) => {
+                let other_player = &players.label(other_piece);
+                let message = lang.text(Message::TileNotEmpty {row, col, other_player, since});
+                let rejection = Rejection::Occupied {row, col, piece: other_piece, since};
+                renderer.move_rejected(error.into(), &message, rejection)
+            },
         }
     }
 
-    // Once the loop is over, the game is finished. Let's output the results
+    // Once the loop is over, the game is finished. Let's output the results: the renderer shows
+    // the board one last time along with which piece won the game.
+    let table = club.focused();
+    let (game, players) = (&table.game, &table.settings.players);
+    let variant = table.variant();
+    let view = GameView {game, players, clocks: table.clocks.as_ref(), variant};
+    renderer.game_over(&view, &result_message(game, players, lang));
+    bus::publish(Event::GameOver {game, players});
+
+    // The game is finished, so we give its table back to the caller so it can be saved or scored
+    Ok(club.close_focused())
+}
+
+// At a simul, the giver goes on to the next board as soon as they have moved, unless that move
+// ended the game (the board is taken away then, which gives the focus to the next one anyway).
+// Every board is shown at the start of each round.
+fn next_board(club: &mut Club, piece: Piece, finished: bool, renderer: &dyn Renderer, lang: Lang) {
+    if !club.is_simul() || piece != Piece::X || finished {
+        return;
+    }
+    let round = club.next_board();
+    info!(table = club.focused().id, "went on to the next board");
+    renderer.info("");
+    if round {
+        show_boards(club, renderer, lang);
+    }
+    renderer.info(&lang.text(Message::SimulNextBoard(club.focused().id)));
+}
+
+// The position of a game after its first `moves` moves
+fn position_after(game: &Game, moves: usize) -> Game {
+    let mut position = Game::starting_with(game.first_piece()).with_rules(game.rules());
+    for (row, col) in game.moves().iter().take(moves) {
+        position.make_move(row, col).expect("the game was played with these moves");
+    }
+    position
+}
+
+// Asks the engine which move to make for the current player, logging the move it chose
+fn computer_move(game: &Game, difficulty: Difficulty) -> (usize, usize) {
+    let (row, col) = engine::choose_move(game, difficulty, &mut random::shared());
+    debug!(piece = ?game.current_piece(), difficulty = difficulty.name(), row, col,
+        "the engine chose a move");
+    (row, col)
+}
+
+// The same as computer_move, but playing by one of the engine profiles
+fn profile_move(game: &Game, name: &str, profile: &Profile) -> (usize, usize) {
+    let (row, col) = engine::choose_profile_move(game, profile, &mut random::shared());
+    debug!(piece = ?game.current_piece(), profile = name, row, col, "the engine chose a move");
+    (row, col)
+}
+
+// Shows a game that computer players already played, the same way that play_game shows it while
+// they are playing
+fn replay_game(game: &Game, settings: &Settings, renderer: &dyn Renderer, lang: Lang) {
+    let players = &settings.players;
+    // The computer never uses its clock, so the clocks always show the whole time control
+    let clocks = settings.time_control.map(Clocks::new);
+    let clocks = clocks.as_ref();
+    let variant = if settings.blindfold_shows.is_some() {
+        Variant::Blindfold
+    }
+    else {
+        Variant::Classic
+    };
+
+    let mut shown = Game::starting_with(game.first_piece()).with_rules(game.rules());
+    for (row, col) in game.moves() {
+        renderer.board(&GameView {game: &shown, players, clocks, variant});
+        let piece = shown.current_piece();
+        renderer.turn(&shown, players, &lang.text(Message::CurrentPiece(&players.label(piece))));
+        shown.make_move(row, col).expect("the game was played with these moves");
+        renderer.move_made(&shown, players, row, col);
+        bus::publish(Event::MoveMade {game: &shown, players, row, col});
+    }
+    if let (Some(reason), Some(winner)) = (game.adjudicated(), game.winner()) {
+        shown.adjudicate(winner, reason).expect("the game was adjudicated at this point");
+        renderer.info(&lang.text(Message::Adjudicated(reason)));
+    }
+
+    let view = GameView {game: &shown, players, clocks, variant};
+    renderer.game_over(&view, &result_message(&shown, players, lang));
+    bus::publish(Event::GameOver {game: &shown, players});
+}
 
-    // First, we'll print the board again
-    print_tiles(game.tiles());
+// Suggests a move for the current player: one that wins right away if there is one, otherwise one
+// that stops the other player from winning on their next turn
+fn hint(game: &Game, lang: Lang) -> String {
+    let piece = game.current_piece();
+    // `first()` gives us the first item of the Vec without taking it out
+    if let Some(&(row, col)) = game.winning_moves(piece).first() {
+        lang.text(Message::HintWin {row, col})
+    }
+    else if let Some(&(row, col)) = game.winning_moves(piece.other()).first() {
+        lang.text(Message::HintBlock {row, col})
+    }
+    else {
+        lang.text(Message::NoHint)
+    }
+}
 
-    // Then print out which piece won the game
-    // We use expect() to express that there should definitely be a winner now and if the winner
-    // method returns None, the program should exit with this error
-    match game.winner().expect("finished game should have winner") {
-        Winner::X => println!("x wins!"),
-        Winner::O => println!("o wins!"),
-        Winner::Tie => println!("Tie!"),
+// Announces which piece won the game, or that there isn't a winner yet
+fn result_message(game: &Game, players: &Players, lang: Lang) -> String {
+    match game.winner() {
+        Some(Winner::X) => lang.text(Message::GameWon(&players.label(Piece::X))),
+        Some(Winner::O) => lang.text(Message::GameWon(&players.label(Piece::O))),
+        Some(Winner::Tie) => lang.text(Message::GameTied),
+        None => lang.text(Message::GameUnfinished),
     }
 }
 
@@ -122,30 +3430,31 @@ fn main() {
 // doesn't suffer from any forward declaration issues where those declarations can get out of sync
 // with the actual function implementation.
 
-// This function returns a "tuple" of two values, the row and column of the selected move. Tuples
-// are very useful for when you have a function that needs to return two values because it saves
-// you from having to define a custom struct just for that purpose.
-fn prompt_move() -> (usize, usize) {
+// This function returns the row and column of the selected move, or the command that the user
+// typed instead.
+// If there is a deadline and the user doesn't enter a valid move before it, None is returned.
+// Pressing Ctrl-C or reaching the end of the input returns an error, and the caller decides what
+// to do about it.
+fn prompt_move(renderer: &dyn Renderer, lang: Lang, deadline: Option<Instant>)
+    -> Result<Option<Turn>, Quit> {
+
     // We'll use `loop` to continuously prompt for input until the user provides what we want. When
     // we get the answer we want, the loop will return the value and it will be used as the return
     // value of this function
     loop {
-        // Rust supports convenient `print!` and `println!` macros which support easy and
-        // customizable formatting of values from your program. Here we are just using them to
-        // prompt for some values that we want the user of our program to provide.
-        print!("Enter move (e.g. 1A): ");
-
-        // Line-buffering is when something waits until it sees a new line character before
-        // actually writing to its designated destination. Rust's stdout is line-buffered by
-        // default, so `print!` does not produce any output unless we "flush" the contents of
-        // stdout's buffer in the line below.
-        // expect() is how we "ignore" any error that could occur during this process. If an error
-        // does occur, the program will exit with the message we provided.
-        io::stdout().flush().expect("Failed to flush stdout");
-
-        // The read_line() function is something we defined below to make reading input quick and
-        // easy.
-        let line = read_line();
+        // The read_line_until() function is something we defined below to make reading input
+        // quick and easy. If time runs out, we return None right away.
+        let line = match read_line_until(&lang.text(Message::EnterMove), deadline) {
+            Ok(line) => line,
+            Err(ReadError::TimedOut) => break Ok(None),
+            Err(ReadError::Closed) => break Err(Quit::Closed),
+            Err(ReadError::Interrupted) => break Err(Quit::Interrupted),
+        };
+
+        // Commands are whole words, so they can never be confused with a move
+        if let Some(command) = Command::parse(&line) {
+            break Ok(Some(Turn::Command(command)));
+        }
 
         // We delegate reading the line as a move to the parse_move function. That function takes a
         // string and converts it to a "tuple" of two values (row, col). The read_line function
@@ -162,176 +3471,393 @@ fn prompt_move() -> (usize, usize) {
             // Rust allows us to "return" a value from a loop by providing it to break. When
             // the loop exits, this will be the return value of the function too because the loop
             // is the last statement in this function.
-            Ok((row, col)) => break (row, col),
-            // Instead of defining methods to extract the value from InvalidMove, we can use
-            // pattern matching to extract its value and print a helpful error message. The
-            // `eprintln!` macro is exactly the same as `println!` except it prints to stderr
-            // instead of stdout.
-            Err(InvalidMove(invalid_str)) => eprintln!(
-                // The `{}` is replaced with the next argument passed to eprintln. We can pass an
-                // arbitrary amount of arguments and Rust can even tell us at compile time if there
-                // is a mismatch between the number of {} and the number of additional arguments
-                // passed.
-                "Invalid move: '{}'. Please try again.",
-                invalid_str,
+            Ok((row, col)) => break Ok(Some(Turn::Move(row, col))),
+            // Instead of defining methods to extract the values from InvalidMove, we can use
+            // pattern matching to extract them and report a helpful error message.
+            Err(InvalidMove(input, reason)) => renderer.move_rejected(
+                error::INVALID_MOVE,
+                &lang.text(Message::InvalidMove {input: &input, reason: &reason}),
+                Rejection::Invalid {input: &input, reason: &reason},
             ),
         }
     }
 }
 
-// This function gets the row and column of the move the user entered. If the string doesn't
-// represent a valid move, we return Result::Err to indicate failure.
-// We pretty much always want to use &str instead of String in function arguments.
-// For learn why, see:
-// http://hermanradtke.com/2015/05/03/string-vs-str-in-rust-functions.html
-// NOTE: There are various ways that we could make this more "idiomatic" using some of the advanced
-// features of Rust. However, notice though that we don't really lose anything or make anything
-// worse for ourselves by keeping it simple. Rust lets you write nice code even if you haven't
-// mastered all of its features just yet.
-fn parse_move(input: &str) -> Result<(usize, usize), InvalidMove> {
-    // The move will be in the format 1A, 2C, 3B, etc.
-    // Let's start by rejecting any input that isn't of size 2
-    if input.len() != 2 {
-        // We use `return` to exit early from this function in case the size of the input is
-        // incorrect.
-        return Err(InvalidMove(input.to_string()));
-    }
-
-    // Let's start by getting the row number
-    // Using match allows us to easily accept the cases we want to support and reject everything
-    // else. If none of the cases match, an error will be returned.
-    let row = match &input[0..1] {
-        "1" => 0,
-        "2" => 1,
-        "3" => 2,
-        _ => return Err(InvalidMove(input.to_string())),
-    };
-
-    let col = match &input[1..2] {
-        // Rust lets us match against multiple patterns using | to separate them. This
-        // lets us accept either lowercase or uppercase versions of the letters.
-        "A" | "a" => 0,
-        "B" | "b" => 1,
-        "C" | "c" => 2,
-
-        // We didn't find a match so far, so the string must be invalid. We use the `Err`
-        // variant of Result to express that.
-        // We can convert a &str to a String using `to_string()`. InvalidMove expects a String,
-        // so we need to do this for this code to work.
-        invalid => return Err(InvalidMove(invalid.to_string())),
-    };
-
-    // The last line of the function is the return value, so we construct the tuple that we want
-    // to return with the move that the user selected
-    Ok((row, col))
-}
-
-// This function is something we've defined to make reading a line of input convenient. Rust gives
-// us a lot of control over our program so we could do many fancy things like buffer the input as
-// we read it or properly handle error conditions. However, since this is a simple application, we
-// have chosen to just exit the program when an error occurs and do no extra buffering of the
-// input. Since we're just reading a line at a time and we expect the lines to be short, this
-// should not cause problems in the majority of cases. Rust gives us the power to make that choice
-// explicitly and know that we are making it in the code.
-fn read_line() -> String {
-    // This creates a new growable/heap-allocated string. The `mut` after `let` declares that we
-    // plan to modify the string. Saying this explicitly lets the compiler automatically check that
-    // we don't modify any variables that we don't intend to. Many languages encourage you to use
-    // `const` or `final` on pretty much everything until you don't need to. In Rust, that
-    // behaviour is by default.
-    let mut input = String::new();
-    // Here, we read a line of input from the standard input stream stdin. `&mut input` passes a
-    // mutable reference to the String in the input variable. This allows the function to modify
-    // input without taking ownership of its value. That way we can return it from this function
-    // afterwards.
-    // expect() is a function that takes a Result value and exits the program with an error message
-    // if the Result value is anything other than Ok(...). This in a way is "ignoring" any error
-    // that can occur while reading input. However, instead of ignoring it implicitly, we explciitly
-    // call out that we intend to just exit the program with an error if this operation fails. This
-    // is one of the ways that Rust gives you control. Don't want to deal with a potential failure?
-    // You don't have to! But it's really nice to know where the error came from if something ever
-    // does go wrong and you want to figure out why.
-    io::stdin().read_line(&mut input).expect("Failed to read input");
-
-    // An empty string will only be returned if we reach the end of input (otherwise we always
-    // receive at least a newline character).
-    if input.is_empty() {
-        // We print a final newline because otherwise the cursor may still be at the end of one
-        // of our `print!` calls earlier.
-        println!();
-
-        // process::exit(0) indicates that the program exited successfully. This will end the
-        // program right here, and none of the rest of our code will run.
-        process::exit(0);
-    }
-
-    // read_line leaves the trailing newline on the string, so we remove it using truncate. By
-    // modifying the string in place, we avoid copying its contents after it was just allocated.
-    let len_without_newline = input.trim_right().len();
-    input.truncate(len_without_newline);
-
-    // The last expression in a function is returned from that function. We want to return the
-    // line that was read, so we put that variable on its own at the end of the function in order
-    // to provide it as the result of this function.
-    input
-}
-
-// This function is used to print out the board in a human readable way
-fn print_tiles(tiles: &Tiles) {
-    // The result of this function will be something like the following:
-    //   A B C
-    // 1 x ▢ ▢
-    // 2 ▢ ▢ o
-    // 3 ▢ ▢ ▢
-    //
-    // The boxes represent empty tiles, and x and o are placed wherever a tile is filled.
-
-    // First we print the space before the column letters
-    print!("  ");
-    // Then we look from the numbers 0 to 2.
-    // `a..b` creates a "range" of numbers from a to one less than b.
-    // `tiles[0].len()` gets the number of columns (i.e. 2)
-    // `as u8` converts the length from the type `usize` to the type `u8` so that it works in the
-    // body of the loop
-    for j in 0..tiles[0].len() as u8 {
-        // `b'A'` produces the ASCII character code for the letter A (i.e. 65)
-        // By adding j to it, we get 'A', then 'B', and then 'C'.
-        // We don't just want to print the ASCII character code, so we convert that number into
-        // a character using `as char`. That way Rust will print it correctly.
-        print!(" {}", (b'A' + j) as char);
-    }
-    // This prints the final newline after the row of column letters
-    println!();
-
-    // Now we print each row preceeded by its row number
-    // .iter().enumerate() goes through each row and provides a row number with each element using
-    // a tuple.
-    for (i, row) in tiles.iter().enumerate() {
-        // We print the row number with a space in front of it
-        print!(" {}", i + 1);
-        // Now we go through each tile in the row and print it out
-        for tile in row {
-            // Here, we match on the value of the tile. We use `*` to "dereference" the tile and
-            // match on its value of type Option<Piece>. This is just for convenience and is
-            // actually something that future versions of Rust might not even require in order to
-            // match on something as simple as this.
-            print!(" {}", match *tile {
-                // The string produced by this match will be printed in `print!`. This match works
-                // because we return the same type, &str, in each branch. Rust still requires that
-                // if a match statement produces a value, it produces a value of the same type in
-                // every branch.
-                // Notice that we don't need to create another match for the piece produced in
-                // Some(...). Rust allows us to match arbitrarily nested structures with no
-                // additional syntax.
-                Some(Piece::X) => "x",
-                Some(Piece::O) => "o",
-                None => "\u{25A2}",
+// This function is something we've defined to make reading a line of input convenient. It shows
+// the prompt, waits for the next line that the user types and returns it without the trailing
+// newline.
+fn read_line(prompt: &str) -> Result<String, Quit> {
+    match read_line_until(prompt, None) {
+        Ok(line) => Ok(line),
+        Err(ReadError::Closed) => Err(Quit::Closed),
+        // Outside of a game there is nothing to save, so Ctrl-C quits right away
+        Err(ReadError::Interrupted) => {
+            outln!();
+            Err(Quit::Interrupted)
+        },
+        // Without a deadline, read_line_until will wait forever, so it can't time out
+        Err(error) => unreachable!("reading without a deadline failed: {:?}", error),
+    }
+}
+
+// Just like read_line, except that it gives up if the user hasn't entered anything by the given
+// deadline. This is how the chess clocks can end a game while we are still waiting for the player
+// to type in their move. It also lets the caller decide what to do when Ctrl-C is pressed.
+fn read_line_until(prompt: &str, deadline: Option<Instant>) -> Result<String, ReadError> {
+    // `borrow_mut()` gives us mutable access to the input. Only one part of the program reads
+    // input at a time, so it is never borrowed twice.
+    let result = INPUT.with(|input| {
+        input.borrow_mut().as_mut().expect("input should be opened in main")
+            .read_line(prompt, deadline)
+    });
+
+    match result {
+        Err(ReadError::Closed) => {
+            // We print a final newline because otherwise the cursor may still be at the end of
+            // the prompt. Then the error goes all the way back up to main(), which ends the
+            // program (see `quit_program`), unless the player would rather carry on.
+            outln!();
+            match ON_EOF.with(Cell::get) {
+                (OnEof::Confirm, lang) if !confirm_quit(lang) => read_line_until(prompt, deadline),
+                _ => Err(ReadError::Closed),
+            }
+        },
+        Ok(line) => {
+            // The terminal shows what the player types without our help, so it has to be added
+            // to the recording separately
+            cast::record(&format!("{}\n", line));
+            Ok(line)
+        },
+        result => result,
+    }
+}
+
+// Asks whether to quit after the input ended, until the answer is yes or no. Ending the input
+// again (or pressing Ctrl-C) is taken as a yes.
+fn confirm_quit(lang: Lang) -> bool {
+    loop {
+        let result = INPUT.with(|input| {
+            input.borrow_mut().as_mut().expect("input should be opened in main")
+                .read_line(&lang.text(Message::ConfirmQuit), None)
+        });
+        match result {
+            Ok(line) => {
+                cast::record(&format!("{}\n", line));
+                if let Some(quit) = parse_yes_no(&line) {
+                    return quit;
+                }
+            },
+            Err(_) => {
+                outln!();
+                return true;
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::io::{Cursor, Write};
+    use std::rc::Rc;
+
+    use input::ReaderInput;
+    use notation::Reason;
+    use render::Layout;
+    use review::Mark;
+
+    // Collects everything a session prints. Cloning it shares the same bytes, so the test can keep
+    // one copy to read while the session writes to the other.
+    #[derive(Clone, Default)]
+    struct Output(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for Output {
+        fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+            self.0.borrow_mut().extend_from_slice(bytes);
+            Ok(bytes.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Output {
+        fn text(&self) -> String {
+            String::from_utf8(self.0.borrow().clone()).unwrap()
+        }
+    }
+
+    // Plays a whole session with the default settings, as if the players typed `lines`. Returns
+    // how the session ended along with everything it printed.
+    fn play_lines(lines: &str) -> (Result<(), Quit>, String) {
+        play_lines_with(lines, |settings, renderer| {
+            play_session(settings, None, renderer, Lang::English)
+        })
+    }
+
+    // The same as play_lines, but for any way of playing
+    fn play_lines_with(lines: &str, play: impl FnOnce(Settings, &dyn Renderer) -> Result<(), Quit>)
+        -> (Result<(), Quit>, String) {
+
+        set_input(Box::new(ReaderInput::new(Cursor::new(lines.to_string()))));
+        let output = Output::default();
+        cast::redirect(output.clone());
+
+        let renderer = GridRenderer::new(Lang::English, Theme::default(), Some(Layout::Compact));
+        let settings = Settings::from_options(&Options::default());
+        let result = play(settings, &renderer);
+        (result, output.text())
+    }
+
+    #[test]
+    fn script_errors() {
+        let settings = Settings::from_options(&Options::default());
+        // Scripted moves are explained just like the ones that are typed in
+        let error = script_position("2B 1A 4D", &settings, Lang::English).err().unwrap();
+        assert_eq!(error.code, error::INVALID_MOVE);
+        let reason = Reason::NoSuchRow("4".to_string());
+        let typed = Lang::English.text(Message::InvalidMove {input: "4D", reason: &reason});
+        assert_eq!(error.message, format!("Move 3: {}", typed));
+        match error.rejected {
+            Some(ScriptRejection::Invalid(InvalidMove(ref input, ref found))) => {
+                assert_eq!((&input[..], found), ("4D", &reason));
+            },
+            _ => panic!("the move should be invalid"),
+        }
+
+        let error = script_position("2B 2B", &settings, Lang::English).err().unwrap();
+        let typed = Lang::English.text(Message::TileNotEmpty {
+            row: 1,
+            col: 1,
+            other_player: "x",
+            since: 1,
+        });
+        assert_eq!(error.message, format!("Move 2: {}", typed));
+        match error.rejected {
+            Some(ScriptRejection::Occupied {row: 1, col: 1, piece: Piece::X, since: 1}) => {},
+            _ => panic!("the tile should be taken"),
+        }
+
+        // There's nothing more to explain about a game that is already over
+        let error = script_position("1A 2A 1B 2B 1C 3C", &settings, Lang::English).err().unwrap();
+        assert!(error.rejected.is_none());
+    }
+
+    #[test]
+    fn scripted_session() {
+        // x plays down the middle column, after trying a move that doesn't exist and a tile that
+        // is already taken
+        let (result, output) = play_lines("9Z\n2B\n1A\n1A\n1B\n3C\n3B\nmaybe\nq\n");
+        assert_eq!(result, Ok(()));
+        assert!(output.contains("Invalid move: '9Z'. Row 9 doesn't exist on a 3-tall board."));
+        assert!(output.contains(&Lang::English.text(Message::TileNotEmpty {
+            row: 0,
+            col: 0,
+            other_player: "o",
+            since: 2,
+        })));
+        assert!(output.contains(&Lang::English.text(Message::GameWon("x"))));
+        assert!(output.contains(&Lang::English.text(Message::PlayAgainHelp)));
+    }
+
+    #[test]
+    fn language_of_option_errors() {
+        let args = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+        assert_eq!(requested_lang(&args(&["--lang", "fr", "--bogus"])), Some(Lang::French));
+        assert_eq!(requested_lang(&args(&["--lang", "fr", "--lang", "en"])), Some(Lang::English));
+        assert_eq!(requested_lang(&args(&["--lang", "xx", "--bogus"])), None);
+        assert_eq!(requested_lang(&args(&["--bogus", "--lang"])), None);
+    }
+
+    #[test]
+    fn seeded_session() {
+        // The computer plays o, so the seed is shown and saved with the game. x tries every tile
+        // in turn, skipping the ones the computer already took.
+        let path = env::temp_dir().join(format!("tic-tac-toe-seed-{}", process::id()));
+        let _ = fs::remove_file(&path);
+        let (result, output) = play_lines_with("1A\n2A\n3A\n1B\n2B\n3B\n1C\n2C\n3C\nq\n",
+            |mut settings, renderer| {
+                settings.o_engine = Some(Computer::Engine(Difficulty::Easy));
+                assert!(settings.uses_random());
+                let archive = seed_session(Some(Archive::new(&path)), 42, renderer, Lang::English);
+                play_session(settings, archive.as_ref(), renderer, Lang::English)
             });
+        assert_eq!(result, Ok(()));
+        assert!(output.starts_with(&Lang::English.text(Message::Seed(42))));
+        let saved = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert!(saved.starts_with("game first=x ") && saved.contains(" seed=42 "), "{}", saved);
+    }
+
+    #[test]
+    fn rematch_carries_the_score() {
+        // x wins the first game and then a tie is played in the rematch
+        let (result, output) = play_lines(
+            "2B\n1A\n1B\n3C\n3B\nrematch\n2B\n1A\n3C\n1C\n1B\n3B\n2A\n2C\n3A\nm\n");
+        // The input runs out during the third game
+        assert_eq!(result, Err(Quit::Closed));
+        // The player who won as x is o now, so the win goes with them
+        assert!(output.contains("Score: x 0 - o 1 (ties: 0)"), "{}", output);
+        assert!(output.contains("Score: x 1 - o 0 (ties: 1)"), "{}", output);
+    }
+
+    #[test]
+    fn browse_earlier_positions() {
+        let (result, output) = play_lines(
+            "2B\n1A\nback\n3C\nback\nback\nforward\nforward\nforward\nback\nundo\n");
+        assert_eq!(result, Err(Quit::Closed));
+        let text = |message| Lang::English.text(message);
+        assert!(output.contains(&text(Message::Browsing {moves: 1, total: 2})));
+        assert!(output.contains(&text(Message::Browsing {moves: 0, total: 2})));
+        // Nothing can be played until the player is back at the game, or undoes the moves
+        assert!(output.contains(&text(Message::BrowsingNoMoves)));
+        assert!(output.contains(&text(Message::NoEarlierPosition)));
+        assert!(output.contains(&text(Message::NotBrowsing)));
+        assert!(output.contains(&text(Message::MoveUndone {row: 0, col: 0})));
+        assert!(output.ends_with(&format!("{}\n{}\n", text(Message::CurrentPiece("o")),
+            text(Message::EnterMove))));
+    }
+
+    #[test]
+    fn pause_and_resume() {
+        let (result, output) = play_lines("2B\npause\n\n1A\n");
+        assert_eq!(result, Err(Quit::Closed));
+        let text = |message| Lang::English.text(message);
+        // The screen is cleared, so the board before the pause can't be seen during it
+        assert!(output.contains(&format!("\x1b[2J\x1b[H{}\n{}", text(Message::Paused),
+            text(Message::EnterResume))));
+        assert!(output.contains("The game goes on after a pause of 0:0"));
+        assert!(output.ends_with(&format!("{}\n{}\n", text(Message::CurrentPiece("x")),
+            text(Message::EnterMove))));
+    }
+
+    #[test]
+    fn club_night() {
+        // x wins at the first table after a second one is set up for Ada and Grace
+        let (result, output) = play_lines_with(
+            "2B\nnew\nAda\nGrace\n1A\ngames\nswitch 5\nswitch 1\n1A\n1B\n3C\n3B\n",
+            |settings, renderer| play_club(&settings, None, renderer, Lang::English));
+        assert_eq!(result, Err(Quit::Closed));
+        let text = |message| Lang::English.text(message);
+        let table = |id, focused, x, o, moves, current| {
+            text(Message::ClubTable {id, focused, x, o, moves, current})
+        };
+        assert!(output.contains(&format!("{}\n{}\n", table(1, false, "x", "o", 1, "o"),
+            table(2, true, "Ada (x)", "Grace (o)", 1, "Grace (o)"))));
+        assert!(output.contains(&text(Message::ClubNoTable(5))));
+        assert!(output.contains(&text(Message::GameWon("x"))));
+        // Once the first game is over, the players go on with the one that is left
+        assert!(output.contains(&format!("{}\n{}\n", text(Message::ClubGameOver(1)),
+            table(2, true, "Ada (x)", "Grace (o)", 1, "Grace (o)"))));
+        assert!(output.ends_with(&format!("{}\n{}\n", text(Message::CurrentPiece("Grace (o)")),
+            text(Message::EnterMove))));
+
+        // The other tables are only there at a club night
+        let (_, output) = play_lines("games\n");
+        assert!(output.contains(&text(Message::ClubOnly)));
+    }
+
+    #[test]
+    fn game_from_code() {
+        // x already has the middle and o has the corner, so x wins down the middle column
+        let game = share::decode("CAKQ").unwrap();
+        let (result, output) = play_lines_with("code\n1B\n3C\n3B\n", |mut settings, renderer| {
+            settings.share_url = Some("https://example.com/ttt".to_string());
+            play_from_code(game, &settings, renderer, Lang::English)
+        });
+        assert_eq!(result, Ok(()));
+        let text = |message| Lang::English.text(message);
+        assert!(output.starts_with(&text(Message::CodeLoaded(2))));
+        assert!(output.contains(&format!("{}\n{}\n", text(Message::GameCode("EAKQ")),
+            text(Message::GameLink("https://example.com/ttt?code=EAKQ")))));
+        assert!(output.contains(&text(Message::GameWon("x"))));
+        assert!(output.contains(&text(Message::GameCode("EAKZECA"))));
+    }
+
+    #[test]
+    fn review_shows_thinking_time() {
+        let (result, output) = play_lines("2B\n1A\n1B\n3C\n3B\nanalyze\nquit\n");
+        assert_eq!(result, Err(Quit::Closed));
+        // Every move at the prompt was timed, so the review has a time for each of them
+        assert!(output.contains(&format!("{}\nThought for 0.", Lang::English.text(
+            Message::ReviewMove {player: "x", row: 1, col: 1, mark: Mark::Best, better: &[]}))));
+        assert!(output.contains("\nx thought for 0."));
+        assert!(output.contains("\no thought for 0."));
+    }
+
+    #[test]
+    fn end_of_input() {
+        // Running out of lines part way through a game ends the session on a new line
+        let (result, output) = play_lines("2B\n1A\n");
+        assert_eq!(result, Err(Quit::Closed));
+        assert_eq!(Quit::Closed.exit_code(), 0);
+        assert!(output.ends_with(&format!("{}\n", Lang::English.text(Message::EnterMove))));
+
+        // The same goes for every other prompt
+        let (result, output) = play_lines("2B\n1A\n1B\n3C\n3B\n");
+        assert_eq!(result, Err(Quit::Closed));
+        assert!(output.ends_with(&format!("{}\n", Lang::English.text(Message::PlayAgain))));
+    }
+
+    #[test]
+    fn confirm_end_of_input() {
+        // Every test runs on its own thread, so this doesn't change the other tests
+        ON_EOF.with(|current| current.set((OnEof::Confirm, Lang::English)));
+        // The input ending again while the question is asked counts as a yes
+        let (result, output) = play_lines("2B\n");
+        assert_eq!(result, Err(Quit::Closed));
+        assert!(output.ends_with(&format!("{}\n{}\n", Lang::English.text(Message::EnterMove),
+            Lang::English.text(Message::ConfirmQuit))));
+    }
+
+    // Lines typed at a terminal, where the input can end (None) and then go on
+    struct TerminalInput(Vec<Option<&'static str>>);
+
+    impl Input for TerminalInput {
+        fn read_line(&mut self, prompt: &str, _deadline: Option<Instant>)
+            -> Result<String, ReadError> {
+
+            out!("{}", prompt);
+            match self.0.remove(0) {
+                Some(line) => Ok(line.to_string()),
+                None => Err(ReadError::Closed),
+            }
         }
-        // We finish each row by printing a final new line
-        println!();
     }
 
-    // Add an extra line at the end of the board to space it out from the prompts that follow
-    println!();
+    #[test]
+    fn carry_on_after_end_of_input() {
+        ON_EOF.with(|current| current.set((OnEof::Confirm, Lang::English)));
+        set_input(Box::new(TerminalInput(vec![Some("2B"), None, Some("n"), Some("2B"), None,
+            Some("y")])));
+        let output = Output::default();
+        cast::redirect(output.clone());
+        let renderer = GridRenderer::new(Lang::English, Theme::default(), Some(Layout::Compact));
+        let settings = Settings::from_options(&Options::default());
+
+        let result = play_session(settings, None, &renderer, Lang::English);
+        assert_eq!(result, Err(Quit::Closed));
+        let output = output.text();
+        assert_eq!(output.matches(&Lang::English.text(Message::ConfirmQuit)).count(), 2);
+        // The game went on where it was left off
+        assert!(output.contains(&Lang::English.text(Message::TileNotEmpty {
+            row: 1,
+            col: 1,
+            other_player: "x",
+            since: 1,
+        })));
+    }
+
+    #[test]
+    fn fail_at_end_of_input() {
+        // The session ends without asking, just like with `--on-eof quit`. Only the exit code
+        // differs (see quit_program).
+        ON_EOF.with(|current| current.set((OnEof::Fail, Lang::English)));
+        let (result, output) = play_lines("2B\n");
+        assert_eq!(result, Err(Quit::Closed));
+        assert!(output.ends_with(&format!("{}\n", Lang::English.text(Message::EnterMove))));
+        assert!(!output.contains(&Lang::English.text(Message::ConfirmQuit)));
+    }
 }
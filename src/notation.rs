@@ -0,0 +1,325 @@
+// Parsing the notation that players use to write moves, e.g. 2B for the center of the board. The
+// parsers in this module are fed input straight from players, scripts and files, so they must
+// never panic, no matter what they are given. The fuzz targets in the `fuzz` directory check that
+// by throwing large amounts of random input at them.
+//
+// Players choose how they write the tiles with `--coordinates` (see `CoordinateStyle`). Everything
+// that people read or type uses their choice: the moves they enter, the labels around the board
+// and the moves named in messages. Files that the program saves (like the archive and puzzle
+// files) always use the letter-number style instead, so that they can be read back whichever
+// style the player who reads them chose.
+
+use std::sync::atomic::{AtomicU8, Ordering};
+
+// The style that moves are read and written in. It is chosen once, when the program starts, and
+// holds for everything it does after that, so it is kept here instead of being passed around to
+// every message and renderer.
+static STYLE: AtomicU8 = AtomicU8::new(CoordinateStyle::Letter as u8);
+
+// The ways of writing the tiles of the board
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CoordinateStyle {
+    /// The row number followed by the column letter, like 2B for the center of the board. The
+    /// column can also come first (B2).
+    #[default]
+    Letter,
+    /// The row number and the column number separated by a comma, like 2,2
+    Numeric,
+    /// The number of the tile on a phone's keypad, from 1 in the top left corner to 9 in the
+    /// bottom right, like 5
+    Keypad,
+}
+
+impl CoordinateStyle {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match &name.to_lowercase()[..] {
+            "letter" => Some(CoordinateStyle::Letter),
+            "numeric" => Some(CoordinateStyle::Numeric),
+            "keypad" => Some(CoordinateStyle::Keypad),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            CoordinateStyle::Letter => "letter",
+            CoordinateStyle::Numeric => "numeric",
+            CoordinateStyle::Keypad => "keypad",
+        }
+    }
+
+    // Writes a tile in this style
+    pub fn write(self, row: usize, col: usize) -> String {
+        match self {
+            CoordinateStyle::Letter => format!("{}{}", row + 1, (b'A' + col as u8) as char),
+            CoordinateStyle::Numeric => format!("{},{}", row + 1, col + 1),
+            CoordinateStyle::Keypad => (row * 3 + col + 1).to_string(),
+        }
+    }
+
+    // The labels shown above the columns and beside the rows of the board, or None for the keypad
+    // style, where the empty tiles show their own numbers instead
+    pub fn labels(self) -> Option<([&'static str; 3], [&'static str; 3])> {
+        match self {
+            CoordinateStyle::Letter => Some((["A", "B", "C"], ["1", "2", "3"])),
+            CoordinateStyle::Numeric => Some((["1", "2", "3"], ["1", "2", "3"])),
+            CoordinateStyle::Keypad => None,
+        }
+    }
+}
+
+// Chooses the style that moves are read and written in from now on
+pub fn set_style(style: CoordinateStyle) {
+    STYLE.store(style as u8, Ordering::Relaxed);
+}
+
+// The style that moves are read and written in
+pub fn style() -> CoordinateStyle {
+    match STYLE.load(Ordering::Relaxed) {
+        1 => CoordinateStyle::Numeric,
+        2 => CoordinateStyle::Keypad,
+        _ => CoordinateStyle::Letter,
+    }
+}
+
+// This type is used to provide an error when the user provides an invalid move string. If we
+// wanted to avoid copying the invalid string, we could use &str instead and Rust would enforce at
+// compile time that the reference remained valid until any instance of InvalidPiece containing it
+// goes out of scope. String is used for the same of simplicity. By marking the type stored in this
+// struct as `pub`, its value can be freely accessed even in patterns (for example, match
+// statements).
+// The second value says what exactly is wrong with the move, so that the player can be told.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidMove(pub String, pub Reason);
+
+// Why a move couldn't be read. Moves that are written the right way but point outside of the board
+// say which part of them is off of it, as it was typed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Reason {
+    /// The move isn't written like a move at all
+    Unreadable,
+    /// The board has no row with this number, like the 4 in 4A
+    NoSuchRow(String),
+    /// The board has no column with this letter or number, like the D in 1D
+    NoSuchColumn(String),
+    /// The keypad has no key with this number, like 0
+    NoSuchKey(String),
+}
+
+// This function gets the row and column of the move the user entered. If the string doesn't
+// represent a valid move, we return Result::Err to indicate failure.
+// We pretty much always want to use &str instead of String in function arguments.
+// For learn why, see:
+// http://hermanradtke.com/2015/05/03/string-vs-str-in-rust-functions.html
+// NOTE: There are various ways that we could make this more "idiomatic" using some of the advanced
+// features of Rust. However, notice though that we don't really lose anything or make anything
+// worse for ourselves by keeping it simple. Rust lets you write nice code even if you haven't
+// mastered all of its features just yet.
+//
+// Moves in the letter-number style are accepted whichever style was chosen, since that is the
+// style of the files that the program saves and of the examples in README.md.
+pub fn parse_move(input: &str) -> Result<(usize, usize), InvalidMove> {
+    let parsed = match style() {
+        CoordinateStyle::Letter => None,
+        CoordinateStyle::Numeric => parse_numeric(input),
+        CoordinateStyle::Keypad => parse_keypad(input),
+    };
+    match parsed {
+        Some(Ok(tile)) => Ok(tile),
+        Some(Err(reason)) => Err(InvalidMove(input.to_string(), reason)),
+        None => parse_letter(input),
+    }
+}
+
+// Reads a move in the letter-number style, like 2B
+fn parse_letter(input: &str) -> Result<(usize, usize), InvalidMove> {
+    // Moves can also be written with the column first (e.g. A1 instead of 1A), which is how many
+    // other programs write them. We swap the two characters and try again. Checking that the
+    // first character is a letter and the second is a digit means this can't keep swapping back
+    // and forth forever.
+    let bytes = input.as_bytes();
+    if bytes.len() == 2 && bytes[0].is_ascii_alphabetic() && bytes[1].is_ascii_digit() {
+        let swapped = format!("{}{}", &input[1..2], &input[0..1]);
+        // We report the move exactly as it was written if it turns out to be invalid
+        return parse_letter(&swapped).map_err(|InvalidMove(_, reason)| {
+            InvalidMove(input.to_string(), reason)
+        });
+    }
+
+    // The move will be in the format 1A, 2C, 3B, etc.
+    // Let's start by rejecting any input that isn't of size 2. `len` counts bytes, not
+    // characters, and a character like é takes up two bytes on its own. Slicing the string below
+    // in the middle of a character would panic, so we also reject anything that isn't ASCII, where
+    // every character is exactly one byte.
+    if input.len() != 2 || !input.is_ascii() {
+        // We use `return` to exit early from this function in case the size of the input is
+        // incorrect.
+        return Err(InvalidMove(input.to_string(), Reason::Unreadable));
+    }
+    // A digit and a letter are written like a move, so if they aren't one, it is because one of
+    // them is off of the board
+    let (row_label, col_label) = (&input[0..1], &input[1..2]);
+    let looks_like_move = input.as_bytes()[0].is_ascii_digit()
+        && input.as_bytes()[1].is_ascii_alphabetic();
+    let invalid = |reason| Err(InvalidMove(input.to_string(), reason));
+
+    // Let's start by getting the row number
+    // Using match allows us to easily accept the cases we want to support and reject everything
+    // else. If none of the cases match, an error will be returned.
+    let row = match row_label {
+        "1" => 0,
+        "2" => 1,
+        "3" => 2,
+        _ if looks_like_move => return invalid(Reason::NoSuchRow(row_label.to_string())),
+        _ => return invalid(Reason::Unreadable),
+    };
+
+    let col = match col_label {
+        // Rust lets us match against multiple patterns using | to separate them. This
+        // lets us accept either lowercase or uppercase versions of the letters.
+        "A" | "a" => 0,
+        "B" | "b" => 1,
+        "C" | "c" => 2,
+
+        // We didn't find a match so far, so the string must be invalid. We use the `Err`
+        // variant of Result to express that.
+        _ if looks_like_move => return invalid(Reason::NoSuchColumn(col_label.to_string())),
+        _ => return invalid(Reason::Unreadable),
+    };
+
+    // The last line of the function is the return value, so we construct the tuple that we want
+    // to return with the move that the user selected
+    Ok((row, col))
+}
+
+// Reads a move in the numeric style, like 2,2. Spaces around the comma are fine. Returns None if
+// the input isn't two numbers separated by a comma, so that it can be read in another style.
+fn parse_numeric(input: &str) -> Option<Result<(usize, usize), Reason>> {
+    let (row, col) = input.split_once(',')?;
+    let (row, col) = (row.trim(), col.trim());
+    if !is_number(row) || !is_number(col) {
+        return None;
+    }
+    Some(match (parse_number(row), parse_number(col)) {
+        (Some(row), Some(col)) => Ok((row, col)),
+        (None, _) => Err(Reason::NoSuchRow(row.to_string())),
+        (_, None) => Err(Reason::NoSuchColumn(col.to_string())),
+    })
+}
+
+// Reads a move in the keypad style, like 5. Returns None if the input isn't a number.
+fn parse_keypad(input: &str) -> Option<Result<(usize, usize), Reason>> {
+    if !is_number(input) {
+        return None;
+    }
+    Some(match input.as_bytes() {
+        &[key @ b'1'..=b'9'] => Ok((((key - b'1') / 3) as usize, ((key - b'1') % 3) as usize)),
+        _ => Err(Reason::NoSuchKey(input.to_string())),
+    })
+}
+
+// Reads a row or column number from 1 to 3, counting from 0 like the rest of the program
+fn parse_number(input: &str) -> Option<usize> {
+    match input {
+        "1" => Some(0),
+        "2" => Some(1),
+        "3" => Some(2),
+        _ => None,
+    }
+}
+
+fn is_number(input: &str) -> bool {
+    !input.is_empty() && input.bytes().all(|byte| byte.is_ascii_digit())
+}
+
+// Writes a move the same way that parse_move reads it, in the style that the player chose, e.g. 2B
+// for the center of the board
+pub fn write_move(row: usize, col: usize) -> String {
+    style().write(row, col)
+}
+
+// The moves in a script. Moves can be separated by spaces, newlines or commas. In the numeric
+// style, a comma between two numbers is part of a move instead, so "1,1, 2,2" is two moves, and so
+// is "1,1,2,2".
+pub fn script_moves(script: &str) -> impl Iterator<Item=&str> {
+    split_script(script, style() == CoordinateStyle::Numeric).into_iter()
+}
+
+fn split_script(script: &str, numeric: bool) -> Vec<&str> {
+    let mut moves = Vec::new();
+    for word in script.split_whitespace() {
+        let mut rest = word;
+        while !rest.is_empty() {
+            let bytes = rest.as_bytes();
+            // The first three bytes are ASCII, so slicing after them can't split a character
+            let end = if numeric && bytes.len() >= 3 && bytes[0].is_ascii_digit()
+                && bytes[1] == b',' && bytes[2].is_ascii_digit() {
+                3
+            }
+            else {
+                rest.find(',').unwrap_or(rest.len())
+            };
+            if end > 0 {
+                moves.push(&rest[..end]);
+            }
+            // Skips the comma after the move, if there is one
+            rest = rest[end..].strip_prefix(',').unwrap_or(&rest[end..]);
+        }
+    }
+    moves
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn moves() {
+        assert_eq!(parse_move("2B").unwrap(), (1, 1));
+        assert_eq!(parse_move("c3").unwrap(), (2, 2));
+        assert_eq!(parse_move("A1").unwrap(), (0, 0));
+        assert!(parse_move("4A").is_err());
+        assert!(parse_move("1AB").is_err());
+        // Two bytes, but only one character
+        assert!(parse_move("é").is_err());
+        assert!(parse_move("1é").is_err());
+        assert_eq!(write_move(1, 1), "2B");
+        assert_eq!(parse_move(&write_move(2, 0)).unwrap(), (2, 0));
+    }
+
+    #[test]
+    fn reasons() {
+        let reason = |input| parse_move(input).unwrap_err().1;
+        assert_eq!(reason("4A"), Reason::NoSuchRow("4".to_string()));
+        assert_eq!(reason("1d"), Reason::NoSuchColumn("d".to_string()));
+        // The move is reported as it was typed, even with the column first
+        assert_eq!(parse_move("D1").unwrap_err(),
+            InvalidMove("D1".to_string(), Reason::NoSuchColumn("D".to_string())));
+        assert_eq!(reason("1?"), Reason::Unreadable);
+        assert_eq!(reason("hello"), Reason::Unreadable);
+    }
+
+    #[test]
+    fn scripts() {
+        let moves: Vec<_> = script_moves("1A,  2B\n3C,").collect();
+        assert_eq!(moves, ["1A", "2B", "3C"]);
+    }
+
+    #[test]
+    fn styles() {
+        for style in [CoordinateStyle::Letter, CoordinateStyle::Numeric, CoordinateStyle::Keypad] {
+            assert_eq!(CoordinateStyle::from_name(style.name()), Some(style));
+        }
+        assert_eq!(CoordinateStyle::Numeric.write(0, 2), "1,3");
+        assert_eq!(CoordinateStyle::Keypad.write(2, 0), "7");
+        assert_eq!(parse_numeric("1, 3"), Some(Ok((0, 2))));
+        assert_eq!(parse_numeric("1,4"), Some(Err(Reason::NoSuchColumn("4".to_string()))));
+        assert_eq!(parse_numeric("1,B"), None);
+        assert_eq!(parse_keypad("7"), Some(Ok((2, 0))));
+        assert_eq!(parse_keypad("0"), Some(Err(Reason::NoSuchKey("0".to_string()))));
+        assert_eq!(parse_keypad("2B"), None);
+        // Only the numeric style keeps the commas between numbers
+        assert_eq!(split_script("1,1, 2,2,1A 3,3,", true), ["1,1", "2,2", "1A", "3,3"]);
+        assert_eq!(split_script("1,1, 2,2", false), ["1", "1", "2", "2"]);
+    }
+}
@@ -0,0 +1,147 @@
+// Lets the players know when something they are waiting for happens, so that they can look at
+// another window in the meantime. Each kind of event can ring the terminal's bell, pop up a
+// notification on the desktop, or both, as chosen with `--notify EVENT=HOW`:
+//
+//     --notify turn=desktop --notify computer=bell
+//
+// The notifier subscribes to the event bus (see bus.rs), so the places where things happen only
+// publish what happened and never have to know whether anyone wants to be told about it.
+//
+// Desktop notifications are shown by the programs that every desktop already has for that:
+// `notify-send` on Linux and the other Unix desktops, and `osascript` on macOS. Windows doesn't
+// have one that works without installing anything, so the bell is rung there instead.
+
+use std::process::{Command, Stdio};
+use std::thread;
+
+use bus::{Event, Subscriber};
+use locale::{Lang, Message};
+
+// The title of every desktop notification
+const TITLE: &str = "tic-tac-toe";
+
+// The events that can notify the players
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Trigger {
+    /// It became someone's turn in a game played from elsewhere, like a game followed with
+    /// `watch`
+    Turn,
+    /// A computer player finished thinking and made its move
+    Computer,
+}
+
+impl Trigger {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match &name.to_lowercase()[..] {
+            "turn" => Some(Trigger::Turn),
+            "computer" => Some(Trigger::Computer),
+            _ => None,
+        }
+    }
+}
+
+// The ways of notifying the players
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Alert {
+    /// Rings the terminal's bell. Most terminals beep or flash, and mark the window or tab.
+    Bell,
+    /// Pops up a notification on the desktop
+    Desktop,
+}
+
+impl Alert {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match &name.to_lowercase()[..] {
+            "bell" => Some(Alert::Bell),
+            "desktop" => Some(Alert::Desktop),
+            _ => None,
+        }
+    }
+}
+
+// Reads a `--notify` value, like turn=bell
+pub fn parse(text: &str) -> Option<(Trigger, Alert)> {
+    let (trigger, alert) = text.split_once('=')?;
+    Some((Trigger::from_name(trigger.trim())?, Alert::from_name(alert.trim())?))
+}
+
+// Notifies the players about the events they asked for
+#[derive(Debug, Clone)]
+pub struct Notifier {
+    /// The way to notify the players of each kind of event, in the order they were given
+    alerts: Vec<(Trigger, Alert)>,
+    lang: Lang,
+}
+
+impl Notifier {
+    pub fn new(alerts: Vec<(Trigger, Alert)>, lang: Lang) -> Self {
+        Self {alerts, lang}
+    }
+}
+
+impl Subscriber for Notifier {
+    fn notify(&mut self, event: &Event) {
+        let (trigger, text) = match *event {
+            Event::ComputerMoved {piece, players, row, col} => (Trigger::Computer,
+                self.lang.text(Message::MovePlayed {player: &players.label(piece), row, col})),
+            Event::TurnArrived {piece, players} => {
+                (Trigger::Turn, self.lang.text(Message::CurrentPiece(&players.label(piece))))
+            },
+            _ => return,
+        };
+        for &(_, alert) in self.alerts.iter().filter(|&&(wanted, _)| wanted == trigger) {
+            match alert {
+                Alert::Bell => ring(),
+                Alert::Desktop => desktop(&text),
+            }
+        }
+    }
+}
+
+// The bell is a control character that terminals react to instead of showing. It goes to stderr so
+// that it can't end up in the middle of the JSON that `--json` prints to stdout.
+fn ring() {
+    eout!("\x07");
+}
+
+// Shows a desktop notification without waiting for it, since some of the programs that show them
+// take a moment to start
+fn desktop(text: &str) {
+    let mut command = if cfg!(target_os = "macos") {
+        // AppleScript strings are in double quotes, with a backslash before the ones inside them
+        let quoted = text.replace('\\', "\\\\").replace('"', "\\\"");
+        let mut command = Command::new("osascript");
+        command.arg("-e")
+            .arg(format!("display notification \"{}\" with title \"{}\"", quoted, TITLE));
+        command
+    }
+    else if cfg!(unix) {
+        let mut command = Command::new("notify-send");
+        command.arg(TITLE).arg(text);
+        command
+    }
+    else {
+        return ring();
+    };
+
+    command.stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::null());
+    thread::spawn(move || match command.status() {
+        Ok(status) if status.success() => {},
+        Ok(status) => warn!(%status, "the desktop notification failed"),
+        Err(error) => warn!(%error, "couldn't show a desktop notification"),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn notify_values() {
+        assert_eq!(parse("turn=bell"), Some((Trigger::Turn, Alert::Bell)));
+        assert_eq!(parse("Computer = desktop"), Some((Trigger::Computer, Alert::Desktop)));
+        assert_eq!(parse("turn"), None);
+        assert_eq!(parse("move=bell"), None);
+        assert_eq!(parse("turn=email"), None);
+    }
+}
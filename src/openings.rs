@@ -0,0 +1,92 @@
+// Opening stats show how well each way of starting a game has worked out, using the games in the
+// archive. Games are grouped by their first move and again by their first two moves, treating
+// openings that only differ by turning or flipping the board as the same (see symmetry.rs). The
+// results are counted for the player who went first, since they are the one who chose the opening.
+
+use std::collections::BTreeMap;
+
+use archive::ArchivedGame;
+use game::{Piece, Winner};
+use symmetry;
+
+// How many moves are used to group the games
+const MAX_DEPTH: usize = 2;
+
+// The results of the games that started with an opening, for the player who went first
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OpeningResults {
+    pub wins: u32,
+    pub ties: u32,
+    pub losses: u32,
+}
+
+impl OpeningResults {
+    pub fn games(&self) -> u32 {
+        self.wins + self.ties + self.losses
+    }
+}
+
+// Adds up the results of every opening in the games. The openings are the canonical forms of the
+// first move and of the first two moves. A BTreeMap sorts each opening right before the openings
+// that continue it, so a first move comes just before all of its replies.
+pub fn compute(games: &[ArchivedGame]) -> BTreeMap<Vec<(usize, usize)>, OpeningResults> {
+    let mut openings: BTreeMap<_, OpeningResults> = BTreeMap::new();
+    for archived in games {
+        let game = &archived.game;
+        let first = game.first_piece();
+        // A game that was forfeited early may not have two moves, or any moves at all
+        let depth = game.moves().len().min(MAX_DEPTH);
+        for length in 1..=depth {
+            let opening: Vec<_> = game.moves().iter().take(length).collect();
+            let results = openings.entry(symmetry::canonical(&opening)).or_default();
+            match (game.winner(), first) {
+                (Some(Winner::Tie), _) => results.ties += 1,
+                (Some(Winner::X), Piece::X) | (Some(Winner::O), Piece::O) => results.wins += 1,
+                (Some(_), _) => results.losses += 1,
+                (None, _) => unreachable!("archived games should be finished"),
+            }
+        }
+    }
+    openings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use game::Game;
+    use player::Players;
+
+    fn archived(first: Piece, moves: &[(usize, usize)]) -> ArchivedGame {
+        let mut game = Game::starting_with(first);
+        for &(row, col) in moves {
+            game.make_move(row, col).unwrap();
+        }
+        ArchivedGame {game, players: Players::default()}
+    }
+
+    #[test]
+    fn grouped_under_symmetry() {
+        // The first player takes the top row after starting in the top left corner
+        let corner_win = [(0, 0), (1, 0), (0, 1), (1, 1), (0, 2)];
+        // The same game turned upside down, started by o
+        let flipped_win = [(2, 0), (1, 0), (2, 1), (1, 1), (2, 2)];
+        let center_tie = [(1, 1), (0, 0), (0, 1), (2, 1), (1, 0), (1, 2), (0, 2), (2, 0), (2, 2)];
+        let games = [
+            archived(Piece::X, &corner_win),
+            archived(Piece::O, &flipped_win),
+            archived(Piece::X, &center_tie),
+        ];
+        let openings = compute(&games);
+
+        let keys: Vec<_> = openings.keys().cloned().collect();
+        assert_eq!(keys, [
+            vec![(0, 0)],
+            // Moving below the corner is the same as moving beside it
+            vec![(0, 0), (0, 1)],
+            vec![(1, 1)],
+            vec![(1, 1), (0, 0)],
+        ]);
+        assert_eq!(openings[&vec![(0, 0)]], OpeningResults {wins: 2, ties: 0, losses: 0});
+        assert_eq!(openings[&vec![(1, 1)]], OpeningResults {wins: 0, ties: 1, losses: 0});
+    }
+}
@@ -0,0 +1,1396 @@
+// This module reads the command line arguments that were passed to the program. There are crates
+// like `clap` that can do this for us, but we only have a few options so it is easy enough to do
+// it ourselves.
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use thiserror::Error;
+use tracing::level_filters::LevelFilter;
+
+use archive::{self, TagQuery};
+use arena::Sprt;
+use broadcast::Delay;
+use clock::{Idle, IdleAction, TimeControl};
+use coach::Level;
+use engine::Difficulty;
+use export::{ExportFormat, Table};
+use game::{Game, Piece};
+use input::OnEof;
+use locale::Lang;
+use notation::CoordinateStyle;
+use notify::{self, Alert, Trigger};
+use player::{PlayerProfile, Players};
+use prefs::Prefs;
+use render::Layout;
+use rules::Rules;
+use session::Computer;
+use share;
+use speedrun;
+use theme::Theme;
+use tournament::{self, Entrant};
+
+// The widest image we are willing to draw. Bigger images take a long time to draw and wouldn't
+// look any better.
+const MAX_IMAGE_SIZE: u32 = 4096;
+
+// All of the settings that can be configured from the command line. `Default` gives us the
+// settings that are used when no arguments are provided.
+#[derive(Debug, Clone, Default)]
+pub struct Options {
+    /// Play a series of games instead of a single game (`--best-of N`)
+    pub best_of: Option<u32>,
+    /// Play by a homebrew variant of the rules instead of the normal ones (`--rules NAME`)
+    pub rules: Rules,
+    /// Tags saved with every game played, each given as `KEY=VALUE` (`--tag KEY=VALUE`, which can
+    /// be given more than once)
+    pub tags: Vec<(String, String)>,
+    /// How to let the players know about each kind of event (`--notify EVENT=HOW`, which can be
+    /// given more than once)
+    pub notify: Vec<(Trigger, Alert)>,
+    /// Append finished games to this file (`--archive FILE`)
+    pub archive: Option<PathBuf>,
+    /// Save unfinished games to the archive without asking when Ctrl-C is pressed (`--autosave`)
+    pub autosave: bool,
+    /// The language to show messages in (`--lang CODE`). If this isn't provided, the LANG
+    /// environment variable is used instead.
+    pub lang: Option<Lang>,
+    /// Describe the board in sentences for screen readers instead of drawing it (`--screen-reader`)
+    pub screen_reader: bool,
+    /// The name and glyph of the player using x (`--x-name NAME`, `--x-glyph CHAR`)
+    pub x_player: PlayerProfile,
+    /// The name and glyph of the player using o (`--o-name NAME`, `--o-glyph CHAR`)
+    pub o_player: PlayerProfile,
+    /// Give each player a chess clock (`--clock BASE[+INCREMENT]`, e.g. `5m` or `3m+2s`)
+    pub time_control: Option<TimeControl>,
+    /// How long a person can take to move before `--on-idle` happens (`--idle-timeout TIME`)
+    pub idle_timeout: Option<Duration>,
+    /// What happens when a person doesn't move in time (`--on-idle move|forfeit`)
+    pub on_idle: Option<IdleAction>,
+    /// Play these moves without prompting for input (`--moves "B2 A1 ..."` or `--moves-file FILE`)
+    pub script: Option<MoveScript>,
+    /// Save the final position of a scripted game as an SVG image (`--export-svg FILE`)
+    pub export_svg: Option<PathBuf>,
+    /// Save the final position of a scripted game as a PNG image (`--export-png FILE`)
+    pub export_png: Option<PathBuf>,
+    /// Save every position of a scripted game as an animated GIF (`--export-gif FILE`)
+    pub export_gif: Option<PathBuf>,
+    /// The width and height of PNG and GIF images in pixels (`--image-size PIXELS`)
+    pub image_size: Option<u32>,
+    /// How long each position is shown in an animated GIF (`--frame-delay MS`)
+    pub frame_delay: Option<Duration>,
+    /// Seeds everything random, so that the computer plays the same way again (`--seed N`)
+    pub seed: Option<u64>,
+    /// Record the session so it can be replayed with asciinema (`--record-cast FILE`)
+    pub record_cast: Option<PathBuf>,
+    /// Keep this file showing the board to spectators (`--broadcast FILE`)
+    pub broadcast: Option<PathBuf>,
+    /// How far behind the game the broadcast is (`--broadcast-delay DELAY`)
+    pub broadcast_delay: Option<Delay>,
+    /// Add the summary shown at the end of the session to this CSV file (`--summary-csv FILE`)
+    pub summary_csv: Option<PathBuf>,
+    /// How much to log for the people working on the program
+    /// (`--log-level off|error|warn|info|debug|trace`)
+    pub log_level: Option<LevelFilter>,
+    /// Write the log to the end of this file instead of stderr (`--log-file FILE`)
+    pub log_file: Option<PathBuf>,
+    /// Whether to write text for people or JSON for other programs
+    /// (`--output text|json|plugin:NAME`)
+    pub output: OutputFormat,
+    /// The renderer plugin that is sent the JSON instead of stdout (`--output plugin:NAME`)
+    pub renderer_plugin: Option<String>,
+    /// Load the plugins in this directory instead of the default one (`--plugins DIR`)
+    pub plugins: Option<PathBuf>,
+    /// What to do when the input ends (`--on-eof quit|confirm|fail`). None asks people at a
+    /// terminal and quits otherwise.
+    pub on_eof: Option<OnEof>,
+    /// Play through a guided lesson instead of a normal game (`--tutorial`)
+    pub tutorial: bool,
+    /// Solve puzzles instead of playing a normal game (`puzzle`)
+    pub puzzle: bool,
+    /// Play the puzzles in these files instead of the ones that come with the game
+    /// (`--puzzles FILE`, which can be given more than once)
+    pub puzzles: Option<Vec<PathBuf>>,
+    /// Write puzzles found with the solver to stdout instead of playing (`generate-puzzles`). The
+    /// puzzles come from the games in the archive if there is one.
+    pub generate_puzzles: bool,
+    /// Solve the puzzle of the day instead of playing a normal game (`daily`)
+    pub daily: bool,
+    /// Set up a position to play or analyze instead of starting from an empty board (`setup`)
+    pub setup: bool,
+    /// Race to beat the computer a few times as fast as possible (`speedrun`). The computer plays
+    /// at `--difficulty`, which has to be easy or medium.
+    pub speedrun: bool,
+    /// Play several games at once, each at its own table (`club`)
+    pub club: bool,
+    /// Give a simul against the players with these names, one board each (`simul NAMES`)
+    pub simul: Option<Vec<String>>,
+    /// The computer player that gives the simul instead of a person
+    /// (`--giver easy|medium|perfect|engine:NAME|plugin:NAME`)
+    pub giver: Option<Computer>,
+    /// The characters used to draw the board (`--theme NAME`). Glyphs chosen with `--x-glyph`
+    /// and `--o-glyph` take priority over the theme's glyphs.
+    pub theme: Option<Theme>,
+    /// How to lay out the board (`--layout auto|compact|spacious`). None picks the layout that
+    /// fits the terminal.
+    pub layout: Option<Layout>,
+    /// How tiles are written in moves, messages and around the board
+    /// (`--coordinates letter|numeric|keypad`)
+    pub coordinates: Option<CoordinateStyle>,
+    /// Hide the board and only announce the moves (`--blindfold`)
+    pub blindfold: bool,
+    /// The number of times per game that the board can be shown in blindfold mode (`--shows N`)
+    pub shows: Option<u32>,
+    /// Comment on every move, saying more at higher levels (`--coach low|medium|high`). The chat
+    /// bots coach every channel at this level unless a channel asks for another.
+    pub coach: Option<Level>,
+    /// The players who are told when the game becomes a forced win or draw
+    /// (`--announce-forced x|o|both`)
+    pub announce_forced: Vec<Piece>,
+    /// Watch the computer play against itself (`--demo`)
+    pub demo: bool,
+    /// How long to wait between moves in the demo (`--delay MS`)
+    pub delay: Option<Duration>,
+    /// Stop the demo after this many games instead of playing forever, or simulate this many
+    /// games (`--games N`)
+    pub games: Option<u32>,
+    /// How well the computer players in the demo, or the engine answering GTP commands, play
+    /// (`--difficulty easy|medium|perfect`)
+    pub difficulty: Option<Difficulty>,
+    /// Save these options as the preferences for next time (`--save-prefs`)
+    pub save_prefs: bool,
+    /// Show the lifetime stats of every named player in the archive instead of playing (`stats`)
+    pub stats: bool,
+    /// Only show the games that two players played against each other (`stats vs A B`)
+    pub versus: Option<(String, String)>,
+    /// Show the rating of every named player in the archive instead of playing (`ratings`)
+    pub ratings: bool,
+    /// Write a table of the archive to stdout instead of playing
+    /// (`export games|profiles|ratings`)
+    pub export: Option<Table>,
+    /// The format of the exported table or training data (`--format csv|npz`)
+    pub export_format: Option<ExportFormat>,
+    /// Write training data for machine learning models to a file instead of playing, from the
+    /// games in the archive or from simulated games (`export-training FILE`)
+    pub export_training: Option<PathBuf>,
+    /// Add every position of the training data turned and flipped in every way (`--augment`)
+    pub augment: bool,
+    /// Show how well each opening did in the archive instead of playing (`openings`)
+    pub openings: bool,
+    /// Walk through the game from the empty board, with the solver's verdict and the archive's
+    /// results for every move, instead of playing (`explore`)
+    pub explore: bool,
+    /// Play a round-robin tournament instead of a normal game (`tournament`)
+    pub tournament: bool,
+    /// The players in the tournament
+    /// (`--entrants easy,perfect,engine:NAME,human:NAME,plugin:NAME,...`)
+    pub entrants: Option<Vec<Entrant>>,
+    /// Play this many rounds of a Swiss tournament instead of a round robin (`--swiss ROUNDS`)
+    pub swiss: Option<u32>,
+    /// Play two computer players against each other until it is clear which is stronger (`arena`)
+    pub arena: bool,
+    /// The two computer players in the arena, which can't be plugins (`--engines FIRST,SECOND`)
+    pub engines: Option<(Computer, Computer)>,
+    /// The Elo differences that the arena decides between (`--sprt ELO0,ELO1`)
+    pub sprt: Option<Sprt>,
+    /// The most games the arena plays before giving up on a decision (`--max-games N`)
+    pub max_games: Option<u32>,
+    /// End the games between computer players in a tournament, the arena or a simulation as soon
+    /// as their result is certain (`--adjudicate`)
+    pub adjudicate: bool,
+    /// Count the leaves of the game tree this many moves deep instead of playing
+    /// (`perft DEPTH`). The counting starts from the position that `--moves` leads to.
+    pub perft: Option<u32>,
+    /// Count every position that can be reached and check the engine against them instead of
+    /// playing (`enumerate`). Also starts from the position that `--moves` leads to.
+    pub enumerate: bool,
+    /// Count positions that only differ by turning or flipping the board once (`--symmetry`)
+    pub symmetry: bool,
+    /// Measure where the search for the best moves spends its time instead of playing
+    /// (`profile`). Also starts from the position that `--moves` leads to.
+    pub profile: bool,
+    /// Save the measurements of `profile` as folded stacks for flame graphs (`--folded FILE`)
+    pub folded: Option<PathBuf>,
+    /// Write the game tree as a GraphViz graph instead of playing (`export-tree`). Also starts
+    /// from the position that `--moves` leads to.
+    pub export_tree: bool,
+    /// How many moves deep the exported game tree goes (`--depth N`)
+    pub depth: Option<u32>,
+    /// Save the exported game tree to this file instead of writing it to stdout (`--dot FILE`)
+    pub dot: Option<PathBuf>,
+    /// Answer commands in a dialect of the Go Text Protocol instead of playing (`gtp`)
+    pub gtp: bool,
+    /// Host games in Discord channels instead of playing here (`discord`)
+    pub discord: bool,
+    /// Host games in the channels of this IRC server instead of playing here (`irc SERVER`)
+    pub irc: Option<String>,
+    /// The IRC channels to join (`--channels "#games,#tic-tac-toe"`)
+    pub channels: Option<Vec<String>>,
+    /// The nickname of the bot on IRC (`--nick NAME`)
+    pub nick: Option<String>,
+    /// Play many games between two computer players as fast as possible without showing them
+    /// (`simulate`)
+    pub simulate: bool,
+    /// The computer player that plays x in the simulation (`--x random|minimax|engine:NAME|...`)
+    pub x_engine: Option<Computer>,
+    /// The computer player that plays o in the simulation (`--o random|minimax|engine:NAME|...`)
+    pub o_engine: Option<Computer>,
+    /// Replay every game in an archive to check that none of it was corrupted instead of playing
+    /// (`verify FILE`)
+    pub verify: Option<PathBuf>,
+    /// Show the game in this file, drawing the board again whenever a move is added to it
+    /// (`watch FILE`)
+    pub watch: Option<PathBuf>,
+    /// Show the code of the game that the moves lead to instead of playing it (`share`)
+    pub share: bool,
+    /// Carry on playing the game in a code made by `share` or the `code` command, or review it if
+    /// it is over (`--from-code CODE`)
+    pub from_code: Option<Game>,
+    /// Turn game codes into links to this address (`--share-url URL`)
+    pub share_url: Option<String>,
+    /// Look for damage, duplicate games and fields from other versions in an archive instead of
+    /// playing (`archive check FILE`)
+    pub check: Option<PathBuf>,
+    /// Fix the problems that `archive check` finds, where it can be done safely (`--fix`)
+    pub fix: bool,
+    /// List the games in an archive that have a tag instead of playing
+    /// (`archive tagged KEY[=VALUE] FILE`)
+    pub tagged: Option<(TagQuery, PathBuf)>,
+    /// Play the games in an archive again to find saved winners and engine moves that the program
+    /// no longer agrees with instead of playing (`archive audit FILE`)
+    pub audit: Option<PathBuf>,
+    /// Compare two games, each given as a file of moves or a game code, instead of playing
+    /// (`diff GAME1 GAME2`)
+    pub diff: Option<(String, String)>,
+    /// Show the QR code of a game code (or link) instead of playing, or save it with
+    /// `--export-png` (`export-qr`)
+    pub export_qr: bool,
+}
+
+// The kinds of output the program can produce
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Text meant to be read by the players
+    #[default]
+    Text,
+    /// One JSON object per line for every event in the game
+    Json,
+}
+
+// Where the moves for a scripted game come from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MoveScript {
+    /// The moves were written directly on the command line
+    Inline(String),
+    /// The moves should be read from this file
+    File(PathBuf),
+}
+
+// The ways that parsing the command line can go wrong
+#[derive(Debug, Clone, Error)]
+pub enum OptionsError {
+    /// An argument that we don't recognize
+    #[error("unknown argument '{0}'")]
+    UnknownArgument(String),
+    /// An option that requires a value was the last argument
+    #[error("{0} needs a value")]
+    MissingValue(String),
+    /// The value given to an option could not be used
+    #[error("'{value}' isn't a valid value for {option}")]
+    InvalidValue { option: String, value: String },
+    /// An option can only be used together with another option that wasn't provided
+    #[error("{option} can only be used with {required}")]
+    Requires { option: String, required: String },
+}
+
+impl OptionsError {
+    // The number that stands for this error (see error.rs)
+    pub fn code(&self) -> u16 {
+        match *self {
+            OptionsError::UnknownArgument(_) => 200,
+            OptionsError::MissingValue(_) => 201,
+            OptionsError::InvalidValue {..} => 202,
+            OptionsError::Requires {..} => 203,
+        }
+    }
+}
+
+impl Options {
+    // Accepting any iterator of Strings (instead of reading `std::env::args()` directly) lets us
+    // test this function without having to actually run the program with arguments.
+    // The preferences fill in any option that isn't given in `args`.
+    pub fn parse(args: impl IntoIterator<Item=String>,
+        prefs: &Prefs) -> Result<Self, OptionsError> {
+
+        let mut options = Options::default();
+
+        let mut args = args.into_iter();
+        // `while let` keeps looping for as long as the iterator produces another argument
+        while let Some(arg) = args.next() {
+            match &arg[..] {
+                "--best-of" => {
+                    let value = next_value(&arg, &mut args)?;
+                    // A series needs at least one game, so 0 is not a valid value
+                    match value.parse() {
+                        Ok(best_of) if best_of > 0 => options.best_of = Some(best_of),
+                        _ => return Err(OptionsError::InvalidValue {option: arg, value}),
+                    }
+                },
+                "--rules" => {
+                    let value = next_value(&arg, &mut args)?;
+                    match Rules::from_name(&value) {
+                        Some(rules) => options.rules = rules,
+                        None => return Err(OptionsError::InvalidValue {option: arg, value}),
+                    }
+                },
+                "--tag" => {
+                    let value = next_value(&arg, &mut args)?;
+                    match archive::parse_tag(&value) {
+                        Some(tag) => options.tags.push(tag),
+                        None => return Err(OptionsError::InvalidValue {option: arg, value}),
+                    }
+                },
+                "--notify" => {
+                    let value = next_value(&arg, &mut args)?;
+                    match notify::parse(&value) {
+                        Some(alert) => options.notify.push(alert),
+                        None => return Err(OptionsError::InvalidValue {option: arg, value}),
+                    }
+                },
+                "--archive" => options.archive = Some(next_value(&arg, &mut args)?.into()),
+                "--lang" => {
+                    let value = next_value(&arg, &mut args)?;
+                    match Lang::from_code(&value) {
+                        Some(lang) => options.lang = Some(lang),
+                        None => return Err(OptionsError::InvalidValue {option: arg, value}),
+                    }
+                },
+                "--autosave" => options.autosave = true,
+                "--screen-reader" => options.screen_reader = true,
+                "--tutorial" => options.tutorial = true,
+                "puzzle" => options.puzzle = true,
+                "generate-puzzles" => options.generate_puzzles = true,
+                "daily" => options.daily = true,
+                "setup" => options.setup = true,
+                "speedrun" => options.speedrun = true,
+                "club" => options.club = true,
+                // A simul needs at least two boards, or it would just be a normal game
+                "simul" => {
+                    let value = next_value(&arg, &mut args)?;
+                    let names: Result<Vec<_>, _> =
+                        value.split(',').map(PlayerProfile::validate_name).collect();
+                    match names {
+                        Ok(names) if names.len() >= 2 => options.simul = Some(names),
+                        _ => return Err(OptionsError::InvalidValue {option: arg, value}),
+                    }
+                },
+                "--giver" => {
+                    let value = next_value(&arg, &mut args)?;
+                    match Computer::parse(&value, &prefs.engines) {
+                        Some(computer) => options.giver = Some(computer),
+                        None => return Err(OptionsError::InvalidValue {option: arg, value}),
+                    }
+                },
+                "--puzzles" => {
+                    let path = next_value(&arg, &mut args)?.into();
+                    options.puzzles.get_or_insert_with(Vec::new).push(path);
+                },
+                "--demo" => options.demo = true,
+                "--blindfold" => options.blindfold = true,
+                "--save-prefs" => options.save_prefs = true,
+                "stats" => options.stats = true,
+                "vs" => {
+                    let first = next_value(&arg, &mut args)?;
+                    options.versus = Some((first, next_value(&arg, &mut args)?));
+                },
+                "ratings" => options.ratings = true,
+                "openings" => options.openings = true,
+                "explore" => options.explore = true,
+                "export" => {
+                    let value = next_value(&arg, &mut args)?;
+                    match Table::from_name(&value) {
+                        Some(table) => options.export = Some(table),
+                        None => return Err(OptionsError::InvalidValue {option: arg, value}),
+                    }
+                },
+                "--format" => {
+                    let value = next_value(&arg, &mut args)?;
+                    match ExportFormat::from_name(&value) {
+                        Some(format) => options.export_format = Some(format),
+                        None => return Err(OptionsError::InvalidValue {option: arg, value}),
+                    }
+                },
+                "export-training" => {
+                    options.export_training = Some(next_value(&arg, &mut args)?.into());
+                },
+                "--augment" => options.augment = true,
+                "perft" => {
+                    let value = next_value(&arg, &mut args)?;
+                    match value.parse() {
+                        Ok(depth) => options.perft = Some(depth),
+                        Err(_) => return Err(OptionsError::InvalidValue {option: arg, value}),
+                    }
+                },
+                "enumerate" => options.enumerate = true,
+                "--symmetry" => options.symmetry = true,
+                "profile" => options.profile = true,
+                "--folded" => options.folded = Some(next_value(&arg, &mut args)?.into()),
+                "export-tree" => options.export_tree = true,
+                "--depth" => {
+                    let value = next_value(&arg, &mut args)?;
+                    match value.parse() {
+                        Ok(depth) => options.depth = Some(depth),
+                        Err(_) => return Err(OptionsError::InvalidValue {option: arg, value}),
+                    }
+                },
+                "--dot" => options.dot = Some(next_value(&arg, &mut args)?.into()),
+                "gtp" => options.gtp = true,
+                "discord" => options.discord = true,
+                "irc" => options.irc = Some(next_value(&arg, &mut args)?),
+                "--channels" => {
+                    let value = next_value(&arg, &mut args)?;
+                    let channels: Vec<_> = value.split(',').map(|channel| channel.trim())
+                        .map(str::to_string).collect();
+                    // Channel names start with # (or & for channels that only exist on one server)
+                    // and can't have spaces in them
+                    let valid = channels.iter().all(|channel| {
+                        channel.len() > 1 && channel.starts_with(&['#', '&'][..])
+                            && !channel.contains(char::is_whitespace)
+                    });
+                    if !valid {
+                        return Err(OptionsError::InvalidValue {option: arg, value});
+                    }
+                    options.channels = Some(channels);
+                },
+                "--nick" => {
+                    let value = next_value(&arg, &mut args)?;
+                    if value.is_empty() || value.contains(char::is_whitespace)
+                        || value.starts_with(&['#', '&', ':'][..]) {
+                        return Err(OptionsError::InvalidValue {option: arg, value});
+                    }
+                    options.nick = Some(value);
+                },
+                "simulate" => options.simulate = true,
+                "verify" => options.verify = Some(next_value(&arg, &mut args)?.into()),
+                "watch" => options.watch = Some(next_value(&arg, &mut args)?.into()),
+                "share" => options.share = true,
+                "export-qr" => options.export_qr = true,
+                "archive" => {
+                    let value = next_value(&arg, &mut args)?;
+                    match &value[..] {
+                        "check" => {
+                            options.check = Some(next_value("archive check", &mut args)?.into());
+                        },
+                        "tagged" => {
+                            let query = next_value("archive tagged", &mut args)?;
+                            let query = match TagQuery::parse(&query) {
+                                Some(query) => query,
+                                None => return Err(OptionsError::InvalidValue {
+                                    option: "archive tagged".to_string(),
+                                    value: query,
+                                }),
+                            };
+                            let path = next_value("archive tagged", &mut args)?.into();
+                            options.tagged = Some((query, path));
+                        },
+                        "audit" => {
+                            options.audit = Some(next_value("archive audit", &mut args)?.into());
+                        },
+                        _ => return Err(OptionsError::InvalidValue {option: arg, value}),
+                    }
+                },
+                "--fix" => options.fix = true,
+                "diff" => {
+                    let first = next_value(&arg, &mut args)?;
+                    options.diff = Some((first, next_value(&arg, &mut args)?));
+                },
+                "--from-code" => {
+                    let value = next_value(&arg, &mut args)?;
+                    match share::decode(&value) {
+                        Ok(game) => options.from_code = Some(game),
+                        Err(_) => return Err(OptionsError::InvalidValue {option: arg, value}),
+                    }
+                },
+                "--share-url" => options.share_url = Some(next_value(&arg, &mut args)?),
+                "--x" | "--o" => {
+                    let value = next_value(&arg, &mut args)?;
+                    let engine = if arg == "--x" {
+                        &mut options.x_engine
+                    }
+                    else {
+                        &mut options.o_engine
+                    };
+                    // Simulations play many games at once, which plugins may not expect
+                    let computer = Computer::parse(&value, &prefs.engines)
+                        .filter(|computer| computer.profile().is_some());
+                    match computer {
+                        Some(computer) => *engine = Some(computer),
+                        None => return Err(OptionsError::InvalidValue {option: arg, value}),
+                    }
+                },
+                "tournament" => options.tournament = true,
+                "arena" => options.arena = true,
+                "--adjudicate" => options.adjudicate = true,
+                "--engines" => {
+                    let value = next_value(&arg, &mut args)?;
+                    let engines: Option<Vec<_>> = value.split(',')
+                        .map(|name| Computer::parse(name.trim(), &prefs.engines)
+                            .filter(|computer| computer.profile().is_some()))
+                        .collect();
+                    match engines.as_deref() {
+                        Some([first, second]) => {
+                            options.engines = Some((first.clone(), second.clone()))
+                        },
+                        _ => return Err(OptionsError::InvalidValue {option: arg, value}),
+                    }
+                },
+                "--sprt" => {
+                    let value = next_value(&arg, &mut args)?;
+                    let bounds: Result<Vec<f64>, _> = value.split(',')
+                        .map(|elo| elo.trim().parse()).collect();
+                    match bounds.as_deref() {
+                        // The test can only tell two different differences apart
+                        Ok(&[elo0, elo1]) if elo0 < elo1 => {
+                            options.sprt = Some(Sprt {elo0, elo1})
+                        },
+                        _ => return Err(OptionsError::InvalidValue {option: arg, value}),
+                    }
+                },
+                "--max-games" => {
+                    let value = next_value(&arg, &mut args)?;
+                    match value.parse() {
+                        Ok(games) if games > 0 => options.max_games = Some(games),
+                        _ => return Err(OptionsError::InvalidValue {option: arg, value}),
+                    }
+                },
+                "--entrants" => {
+                    let value = next_value(&arg, &mut args)?;
+                    match tournament::parse_entrants(&value, &prefs.engines) {
+                        Some(entrants) => options.entrants = Some(entrants),
+                        None => return Err(OptionsError::InvalidValue {option: arg, value}),
+                    }
+                },
+                "--swiss" => {
+                    let value = next_value(&arg, &mut args)?;
+                    match value.parse() {
+                        Ok(rounds) if rounds > 0 => options.swiss = Some(rounds),
+                        _ => return Err(OptionsError::InvalidValue {option: arg, value}),
+                    }
+                },
+                "--shows" => {
+                    let value = next_value(&arg, &mut args)?;
+                    // Zero is allowed for players who want to play the whole game from memory
+                    match value.parse() {
+                        Ok(shows) => options.shows = Some(shows),
+                        Err(_) => return Err(OptionsError::InvalidValue {option: arg, value}),
+                    }
+                },
+                "--coach" => {
+                    let value = next_value(&arg, &mut args)?;
+                    match Level::from_name(&value) {
+                        Some(level) => options.coach = Some(level),
+                        None => return Err(OptionsError::InvalidValue {option: arg, value}),
+                    }
+                },
+                "--announce-forced" => {
+                    let value = next_value(&arg, &mut args)?;
+                    options.announce_forced = match &value.to_lowercase()[..] {
+                        "x" => vec![Piece::X],
+                        "o" => vec![Piece::O],
+                        "both" => vec![Piece::X, Piece::O],
+                        _ => return Err(OptionsError::InvalidValue {option: arg, value}),
+                    };
+                },
+                "--theme" => {
+                    let value = next_value(&arg, &mut args)?;
+                    match Theme::from_name(&value) {
+                        Some(theme) => options.theme = Some(theme),
+                        None => return Err(OptionsError::InvalidValue {option: arg, value}),
+                    }
+                },
+                "--coordinates" => {
+                    let value = next_value(&arg, &mut args)?;
+                    match CoordinateStyle::from_name(&value) {
+                        Some(style) => options.coordinates = Some(style),
+                        None => return Err(OptionsError::InvalidValue {option: arg, value}),
+                    }
+                },
+                "--layout" => {
+                    let value = next_value(&arg, &mut args)?;
+                    match &value[..] {
+                        "auto" => options.layout = None,
+                        _ => match Layout::from_name(&value) {
+                            Some(layout) => options.layout = Some(layout),
+                            None => return Err(OptionsError::InvalidValue {option: arg, value}),
+                        },
+                    }
+                },
+                "--delay" => {
+                    let value = next_value(&arg, &mut args)?;
+                    match value.parse() {
+                        Ok(millis) => options.delay = Some(Duration::from_millis(millis)),
+                        Err(_) => return Err(OptionsError::InvalidValue {option: arg, value}),
+                    }
+                },
+                "--games" => {
+                    let value = next_value(&arg, &mut args)?;
+                    match value.parse() {
+                        Ok(games) if games > 0 => options.games = Some(games),
+                        _ => return Err(OptionsError::InvalidValue {option: arg, value}),
+                    }
+                },
+                "--difficulty" => {
+                    let value = next_value(&arg, &mut args)?;
+                    match Difficulty::from_name(&value) {
+                        Some(difficulty) => options.difficulty = Some(difficulty),
+                        None => return Err(OptionsError::InvalidValue {option: arg, value}),
+                    }
+                },
+                "--moves" => {
+                    options.script = Some(MoveScript::Inline(next_value(&arg, &mut args)?));
+                },
+                "--moves-file" => {
+                    options.script = Some(MoveScript::File(next_value(&arg, &mut args)?.into()));
+                },
+                "--export-svg" => options.export_svg = Some(next_value(&arg, &mut args)?.into()),
+                "--export-png" => options.export_png = Some(next_value(&arg, &mut args)?.into()),
+                "--record-cast" => options.record_cast = Some(next_value(&arg, &mut args)?.into()),
+                "--broadcast" => options.broadcast = Some(next_value(&arg, &mut args)?.into()),
+                "--broadcast-delay" => {
+                    let value = next_value(&arg, &mut args)?;
+                    match Delay::parse(&value) {
+                        Some(delay) => options.broadcast_delay = Some(delay),
+                        None => return Err(OptionsError::InvalidValue {option: arg, value}),
+                    }
+                },
+                "--summary-csv" => options.summary_csv = Some(next_value(&arg, &mut args)?.into()),
+                "--export-gif" => options.export_gif = Some(next_value(&arg, &mut args)?.into()),
+                "--log-file" => options.log_file = Some(next_value(&arg, &mut args)?.into()),
+                "--log-level" => {
+                    let value = next_value(&arg, &mut args)?;
+                    match value.parse() {
+                        Ok(level) => options.log_level = Some(level),
+                        Err(_) => return Err(OptionsError::InvalidValue {option: arg, value}),
+                    }
+                },
+                "--image-size" => {
+                    let value = next_value(&arg, &mut args)?;
+                    match value.parse() {
+                        Ok(size) if size > 0 && size <= MAX_IMAGE_SIZE => {
+                            options.image_size = Some(size)
+                        },
+                        _ => return Err(OptionsError::InvalidValue {option: arg, value}),
+                    }
+                },
+                "--seed" => {
+                    let value = next_value(&arg, &mut args)?;
+                    match value.parse() {
+                        Ok(seed) => options.seed = Some(seed),
+                        Err(_) => return Err(OptionsError::InvalidValue {option: arg, value}),
+                    }
+                },
+                "--frame-delay" => {
+                    let value = next_value(&arg, &mut args)?;
+                    match value.parse() {
+                        Ok(millis) => options.frame_delay = Some(Duration::from_millis(millis)),
+                        Err(_) => return Err(OptionsError::InvalidValue {option: arg, value}),
+                    }
+                },
+                "--output" => {
+                    let value = next_value(&arg, &mut args)?;
+                    match &value[..] {
+                        "text" => options.output = OutputFormat::Text,
+                        "json" => options.output = OutputFormat::Json,
+                        // A renderer plugin is sent the same events as JSON (see plugin.rs)
+                        _ => match value.strip_prefix("plugin:").filter(|name| !name.is_empty()) {
+                            Some(name) => {
+                                options.output = OutputFormat::Json;
+                                options.renderer_plugin = Some(name.to_string());
+                            },
+                            None => return Err(OptionsError::InvalidValue {option: arg, value}),
+                        },
+                    }
+                },
+                "--plugins" => options.plugins = Some(next_value(&arg, &mut args)?.into()),
+                "--on-eof" => {
+                    let value = next_value(&arg, &mut args)?;
+                    match OnEof::from_name(&value) {
+                        Some(on_eof) => options.on_eof = Some(on_eof),
+                        None => return Err(OptionsError::InvalidValue {option: arg, value}),
+                    }
+                },
+                "--clock" => {
+                    let value = next_value(&arg, &mut args)?;
+                    match TimeControl::parse(&value) {
+                        Some(control) => options.time_control = Some(control),
+                        None => return Err(OptionsError::InvalidValue {option: arg, value}),
+                    }
+                },
+                "--idle-timeout" => {
+                    let value = next_value(&arg, &mut args)?;
+                    match Idle::parse_timeout(&value) {
+                        Some(timeout) => options.idle_timeout = Some(timeout),
+                        None => return Err(OptionsError::InvalidValue {option: arg, value}),
+                    }
+                },
+                "--on-idle" => {
+                    let value = next_value(&arg, &mut args)?;
+                    match IdleAction::from_name(&value) {
+                        Some(action) => options.on_idle = Some(action),
+                        None => return Err(OptionsError::InvalidValue {option: arg, value}),
+                    }
+                },
+                "--x-name" | "--o-name" | "--x-glyph" | "--o-glyph" => {
+                    let value = next_value(&arg, &mut args)?;
+                    // The third character of the option tells us which player it is for
+                    let profile = if arg.starts_with("--x") {
+                        &mut options.x_player
+                    }
+                    else {
+                        &mut options.o_player
+                    };
+
+                    let result = if arg.ends_with("name") {
+                        PlayerProfile::validate_name(&value).map(|name| profile.name = Some(name))
+                    }
+                    else {
+                        PlayerProfile::validate_glyph(&value)
+                            .map(|glyph| profile.glyph = Some(glyph))
+                    };
+                    if result.is_err() {
+                        return Err(OptionsError::InvalidValue {option: arg, value});
+                    }
+                },
+                _ => return Err(OptionsError::UnknownArgument(arg)),
+            }
+        }
+
+        // Autosaving needs somewhere to save the game to
+        if options.autosave && options.archive.is_none() {
+            return Err(OptionsError::Requires {
+                option: "--autosave".to_string(),
+                required: "--archive".to_string(),
+            });
+        }
+
+        // Some settings don't mean anything without the mode they belong to
+        let dependent = [
+            ("stats", options.stats, "--archive", options.archive.is_some()),
+            ("ratings", options.ratings, "--archive", options.archive.is_some()),
+            ("openings", options.openings, "--archive", options.archive.is_some()),
+            ("vs", options.versus.is_some(), "stats", options.stats),
+            ("export", options.export.is_some(), "--archive", options.archive.is_some()),
+            ("--format", options.export_format.is_some(), "export",
+                options.export.is_some() || options.export_training.is_some()),
+            ("--augment", options.augment, "export-training", options.export_training.is_some()),
+            ("--puzzles", options.puzzles.is_some(), "puzzle", options.puzzle),
+            ("--symmetry", options.symmetry, "enumerate", options.enumerate),
+            ("--folded", options.folded.is_some(), "profile", options.profile),
+            ("--depth", options.depth.is_some(), "export-tree", options.export_tree),
+            ("--dot", options.dot.is_some(), "export-tree", options.export_tree),
+            ("--entrants", options.entrants.is_some(), "tournament", options.tournament),
+            ("--swiss", options.swiss.is_some(), "tournament", options.tournament),
+            ("arena", options.arena, "--engines", options.engines.is_some()),
+            ("--engines", options.engines.is_some(), "arena", options.arena),
+            ("--sprt", options.sprt.is_some(), "arena", options.arena),
+            ("--max-games", options.max_games.is_some(), "arena", options.arena),
+            ("--adjudicate", options.adjudicate, "tournament",
+                options.tournament || options.arena || options.simulate),
+            ("--delay", options.delay.is_some(), "--demo", options.demo),
+            ("--games", options.games.is_some(), "--demo",
+                options.demo || options.simulate || options.export_training.is_some()),
+            ("--x", options.x_engine.is_some(), "simulate",
+                options.simulate || options.export_training.is_some()),
+            ("--o", options.o_engine.is_some(), "simulate",
+                options.simulate || options.export_training.is_some()),
+            ("irc", options.irc.is_some(), "--channels", options.channels.is_some()),
+            ("--channels", options.channels.is_some(), "irc", options.irc.is_some()),
+            ("--nick", options.nick.is_some(), "irc", options.irc.is_some()),
+            ("--difficulty", options.difficulty.is_some(), "--demo",
+                options.demo || options.gtp || options.discord || options.irc.is_some()
+                    || options.speedrun),
+            ("--shows", options.shows.is_some(), "--blindfold", options.blindfold),
+            ("--giver", options.giver.is_some(), "simul", options.simul.is_some()),
+            ("--on-idle", options.on_idle.is_some(), "--idle-timeout",
+                options.idle_timeout.is_some()),
+            ("--broadcast-delay", options.broadcast_delay.is_some(), "--broadcast",
+                options.broadcast.is_some()),
+            ("share", options.share, "--moves", options.script.is_some()),
+            ("--fix", options.fix, "archive check", options.check.is_some()),
+            ("--export-svg", options.export_svg.is_some(), "--moves", options.script.is_some()),
+            ("export-qr", options.export_qr, "--moves",
+                options.script.is_some() || options.from_code.is_some()),
+            ("--export-png", options.export_png.is_some(), "--moves",
+                options.script.is_some() || options.export_qr),
+            ("--export-gif", options.export_gif.is_some(), "--moves", options.script.is_some()),
+            // GIFs have a size too, but the PNG option is the one most people will be looking for
+            ("--image-size", options.image_size.is_some(), "--export-png",
+                options.export_png.is_some() || options.export_gif.is_some()),
+            ("--frame-delay", options.frame_delay.is_some(), "--export-gif",
+                options.export_gif.is_some()),
+        ];
+        for &(option, used, required, enabled) in &dependent {
+            if used && !enabled {
+                return Err(OptionsError::Requires {
+                    option: option.to_string(),
+                    required: required.to_string(),
+                });
+            }
+        }
+
+        // NPZ files only hold arrays of numbers, so they are only for training data
+        if let (Some(_), Some(ExportFormat::Npz)) = (options.export, options.export_format) {
+            return Err(OptionsError::InvalidValue {
+                option: "--format".to_string(),
+                value: "npz".to_string(),
+            });
+        }
+
+        // The perfect computer can't be beaten, so a speedrun against it would never end
+        if let (true, Some(difficulty)) = (options.speedrun, options.difficulty) {
+            if !speedrun::can_race(difficulty) {
+                return Err(OptionsError::InvalidValue {
+                    option: "--difficulty".to_string(),
+                    value: difficulty.name().to_string(),
+                });
+            }
+        }
+
+        // This comes after the checks above since a preference like the difficulty is only used
+        // when it makes sense to
+        prefs.merge(&mut options);
+
+        // The theme only fills in the glyphs that the players didn't choose themselves
+        let theme = options.theme.unwrap_or_default();
+        options.x_player.glyph.get_or_insert_with(|| theme.x.to_string());
+        options.o_player.glyph.get_or_insert_with(|| theme.o.to_string());
+
+        // If both players used the same glyph, there would be no way to tell their pieces apart
+        let players = Players::new(options.x_player.clone(), options.o_player.clone());
+        if players.glyph(Piece::X) == players.glyph(Piece::O) {
+            return Err(OptionsError::InvalidValue {
+                option: "--o-glyph".to_string(),
+                value: players.glyph(Piece::O).to_string(),
+            });
+        }
+
+        Ok(options)
+    }
+}
+
+// Returns the value that comes after an option like `--best-of`
+fn next_value(option: &str, args: &mut impl Iterator<Item=String>) -> Result<String, OptionsError> {
+    args.next().ok_or_else(|| OptionsError::MissingValue(option.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use engine::Profile;
+
+    // Converts a list of string literals into the Strings that parse() expects
+    fn parse(args: &[&str]) -> Result<Options, OptionsError> {
+        Options::parse(args.iter().map(|arg| arg.to_string()), &Prefs::default())
+    }
+
+    #[test]
+    fn engine_profiles() {
+        let sloppy = Profile {randomness: 50, ..Profile::default()};
+        let prefs = Prefs {engines: vec![("sloppy".to_string(), sloppy)], ..Prefs::default()};
+        let parse = |args: &[&str]| Options::parse(args.iter().map(|arg| arg.to_string()), &prefs);
+
+        let options = parse(&["simulate", "--o", "engine:sloppy"]).unwrap();
+        assert_eq!(options.o_engine, Some(Computer::Profile("sloppy".to_string(), sloppy)));
+        let options = parse(&["arena", "--engines", "engine:sloppy,perfect"]).unwrap();
+        assert_eq!(options.engines.map(|(first, _)| first.profile()), Some(Some(sloppy)));
+        let options = parse(&["tournament", "--entrants", "engine:sloppy,easy"]).unwrap();
+        assert_eq!(options.entrants.unwrap()[0].name, "sloppy");
+        assert!(parse(&["simulate", "--o", "engine:careful"]).is_err());
+        assert!(parse(&["simulate", "--o", "engine:"]).is_err());
+    }
+
+    #[test]
+    fn no_arguments() {
+        let options = parse(&[]).unwrap();
+        assert_eq!(options.best_of, None);
+        assert_eq!(options.archive, None);
+    }
+
+    #[test]
+    fn best_of_and_archive() {
+        let options = parse(&["--best-of", "5", "--archive", "games.txt"]).unwrap();
+        assert_eq!(options.best_of, Some(5));
+        assert_eq!(options.archive, Some(PathBuf::from("games.txt")));
+    }
+
+    #[test]
+    fn autosave() {
+        assert!(parse(&["--archive", "games.txt", "--autosave"]).unwrap().autosave);
+        assert!(parse(&["--autosave"]).is_err());
+    }
+
+    #[test]
+    fn stats() {
+        assert!(parse(&["stats", "--archive", "games.txt"]).unwrap().stats);
+        assert!(parse(&["stats"]).is_err());
+    }
+
+    #[test]
+    fn ratings() {
+        assert!(parse(&["ratings", "--archive", "games.txt"]).unwrap().ratings);
+    }
+
+    #[test]
+    fn tournament() {
+        let options = parse(&["tournament", "--entrants", "easy,human:Ada"]).unwrap();
+        assert!(options.tournament);
+        assert_eq!(options.entrants.map(|entrants| entrants.len()), Some(2));
+        assert!(parse(&["tournament", "--entrants", "easy"]).is_err());
+        assert!(parse(&["--entrants", "easy,perfect"]).is_err());
+    }
+
+    #[test]
+    fn swiss() {
+        assert_eq!(parse(&["tournament", "--swiss", "5"]).unwrap().swiss, Some(5));
+        assert!(parse(&["tournament", "--swiss", "0"]).is_err());
+        assert!(parse(&["--swiss", "5"]).is_err());
+    }
+
+    #[test]
+    fn arena() {
+        let options = parse(&["arena", "--engines", "medium,perfect", "--sprt", "-10,5"]).unwrap();
+        assert_eq!(options.engines,
+            Some((Computer::Engine(Difficulty::Medium), Computer::Engine(Difficulty::Perfect))));
+        assert_eq!(options.sprt, Some(Sprt {elo0: -10.0, elo1: 5.0}));
+        assert!(parse(&["arena"]).is_err());
+        assert!(parse(&["arena", "--engines", "medium"]).is_err());
+        assert!(parse(&["arena", "--engines", "easy,easy", "--sprt", "5,0"]).is_err());
+        assert!(parse(&["--engines", "easy,perfect"]).is_err());
+        assert!(parse(&["arena", "--engines", "easy,plugin:corners"]).is_err());
+    }
+
+    #[test]
+    fn openings() {
+        assert!(parse(&["openings", "--archive", "games.txt"]).unwrap().openings);
+        assert!(parse(&["openings"]).is_err());
+    }
+
+    #[test]
+    fn versus() {
+        let options = parse(&["stats", "vs", "Ada", "perfect", "--archive", "games.txt"]).unwrap();
+        assert_eq!(options.versus, Some(("Ada".to_string(), "perfect".to_string())));
+        assert!(parse(&["stats", "vs", "Ada", "--archive", "games.txt"]).is_err());
+        assert!(parse(&["vs", "Ada", "Grace", "--archive", "games.txt"]).is_err());
+    }
+
+    #[test]
+    fn export() {
+        let options = parse(&["export", "ratings", "--format", "csv", "--archive", "games.txt"])
+            .unwrap();
+        assert_eq!(options.export, Some(Table::Ratings));
+        assert_eq!(options.export_format, Some(ExportFormat::Csv));
+        assert!(parse(&["export", "moves", "--archive", "games.txt"]).is_err());
+        assert!(parse(&["export", "games", "--format", "xlsx", "--archive", "games.txt"]).is_err());
+        assert!(parse(&["--format", "csv"]).is_err());
+    }
+
+    #[test]
+    fn perft() {
+        assert_eq!(parse(&["perft", "9", "--moves", "2B"]).unwrap().perft, Some(9));
+        assert!(parse(&["perft", "deep"]).is_err());
+    }
+
+    #[test]
+    fn enumerate() {
+        assert!(parse(&["enumerate", "--symmetry"]).unwrap().symmetry);
+        assert!(parse(&["--symmetry"]).is_err());
+    }
+
+    #[test]
+    fn simulate() {
+        let options = parse(&["simulate", "--games", "100000", "--x", "random", "--o", "minimax"])
+            .unwrap();
+        assert!(options.simulate);
+        assert_eq!(options.games, Some(100000));
+        assert_eq!((options.x_engine, options.o_engine), (Some(Computer::Engine(Difficulty::Easy)),
+            Some(Computer::Engine(Difficulty::Perfect))));
+        assert!(parse(&["simulate", "--x", "clever"]).is_err());
+        assert!(parse(&["--o", "random"]).is_err());
+    }
+
+    #[test]
+    fn verify() {
+        assert_eq!(parse(&["verify", "games.txt"]).unwrap().verify,
+            Some(PathBuf::from("games.txt")));
+        assert!(parse(&["verify"]).is_err());
+    }
+
+    #[test]
+    fn profile() {
+        let options = parse(&["profile", "--folded", "search.folded"]).unwrap();
+        assert!(options.profile);
+        assert_eq!(options.folded, Some(PathBuf::from("search.folded")));
+        assert!(parse(&["--folded", "search.folded"]).is_err());
+    }
+
+    #[test]
+    fn export_tree() {
+        let options = parse(&["export-tree", "--depth", "3", "--dot", "tree.dot"]).unwrap();
+        assert!(options.export_tree);
+        assert_eq!(options.depth, Some(3));
+        assert_eq!(options.dot, Some(PathBuf::from("tree.dot")));
+        assert!(parse(&["--depth", "3"]).is_err());
+        assert!(parse(&["export-tree", "--depth", "deep"]).is_err());
+    }
+
+    #[test]
+    fn gtp() {
+        let options = parse(&["gtp", "--difficulty", "easy"]).unwrap();
+        assert!(options.gtp);
+        assert_eq!(options.difficulty, Some(Difficulty::Easy));
+    }
+
+    #[test]
+    fn discord() {
+        let options = parse(&["discord", "--archive", "discord.txt"]).unwrap();
+        assert!(options.discord);
+        assert_eq!(options.archive, Some(PathBuf::from("discord.txt")));
+    }
+
+    #[test]
+    fn irc() {
+        let options = parse(&["irc", "irc.example.com", "--channels", "#games, #ttt",
+            "--nick", "ttt"]).unwrap();
+        assert_eq!(options.irc, Some("irc.example.com".to_string()));
+        assert_eq!(options.channels, Some(vec!["#games".to_string(), "#ttt".to_string()]));
+        assert_eq!(options.nick, Some("ttt".to_string()));
+        assert!(parse(&["irc", "irc.example.com"]).is_err());
+        assert!(parse(&["irc", "irc.example.com", "--channels", "games"]).is_err());
+        assert!(parse(&["irc", "irc.example.com", "--channels", "#games", "--nick", "t t"])
+            .is_err());
+    }
+
+    #[test]
+    fn adjudicate() {
+        assert!(parse(&["tournament", "--adjudicate"]).unwrap().adjudicate);
+        assert!(parse(&["simulate", "--adjudicate"]).unwrap().adjudicate);
+        assert!(parse(&["--adjudicate"]).is_err());
+    }
+
+    #[test]
+    fn watch() {
+        assert_eq!(parse(&["watch", "game.txt"]).unwrap().watch, Some(PathBuf::from("game.txt")));
+    }
+
+    #[test]
+    fn export_training() {
+        assert!(parse(&["export", "games", "--format", "npz", "--archive", "games.txt"]).is_err());
+        let options = parse(&["export-training", "data.npz", "--format", "npz", "--augment",
+            "--games", "500", "--x", "random"]).unwrap();
+        assert_eq!(options.export_training, Some(PathBuf::from("data.npz")));
+        assert_eq!((options.export_format, options.augment), (Some(ExportFormat::Npz), true));
+        assert_eq!(options.games, Some(500));
+        assert!(parse(&["--augment"]).is_err());
+    }
+
+    #[test]
+    fn explore() {
+        // The explorer still has the solver without an archive
+        assert!(parse(&["explore"]).unwrap().explore);
+    }
+
+    #[test]
+    fn lang() {
+        assert_eq!(parse(&["--lang", "fr"]).unwrap().lang, Some(Lang::French));
+        assert!(parse(&["--lang", "klingon"]).is_err());
+    }
+
+    #[test]
+    fn screen_reader() {
+        assert!(!parse(&[]).unwrap().screen_reader);
+        assert!(parse(&["--screen-reader"]).unwrap().screen_reader);
+    }
+
+    #[test]
+    fn player_profiles() {
+        let options = parse(&["--x-name", "Ada", "--o-glyph", "★"]).unwrap();
+        assert_eq!(options.x_player.name, Some("Ada".to_string()));
+        assert_eq!(options.o_player.glyph, Some("★".to_string()));
+
+        assert!(parse(&["--x-glyph", "xx"]).is_err());
+        // The glyphs have to be different from each other
+        assert!(parse(&["--x-glyph", "o"]).is_err());
+    }
+
+    #[test]
+    fn clock() {
+        assert!(parse(&[]).unwrap().time_control.is_none());
+        assert!(parse(&["--clock", "3m+2s"]).unwrap().time_control.is_some());
+        let options = parse(&["--idle-timeout", "90", "--on-idle", "forfeit"]).unwrap();
+        assert_eq!(options.idle_timeout, Some(Duration::from_secs(90)));
+        assert_eq!(options.on_idle, Some(IdleAction::Forfeit));
+        assert!(parse(&["--on-idle", "move"]).is_err());
+        assert!(parse(&["--clock", "soon"]).is_err());
+    }
+
+    #[test]
+    fn tutorial() {
+        assert!(!parse(&[]).unwrap().tutorial);
+        assert!(parse(&["--tutorial"]).unwrap().tutorial);
+    }
+
+    #[test]
+    fn puzzle() {
+        let options = parse(&["puzzle"]).unwrap();
+        assert!(options.puzzle);
+        assert!(options.puzzles.is_none());
+        let options = parse(&["puzzle", "--puzzles", "a.txt", "--puzzles", "b.txt"]).unwrap();
+        assert_eq!(options.puzzles, Some(vec![PathBuf::from("a.txt"), PathBuf::from("b.txt")]));
+        assert!(matches!(parse(&["--puzzles", "a.txt"]), Err(OptionsError::Requires {..})));
+        assert!(parse(&["generate-puzzles", "--archive", "games.txt"]).unwrap().generate_puzzles);
+        assert!(parse(&["daily"]).unwrap().daily);
+    }
+
+    #[test]
+    fn speedrun() {
+        let options = parse(&["speedrun", "--difficulty", "medium"]).unwrap();
+        assert!(options.speedrun);
+        assert_eq!(options.difficulty, Some(Difficulty::Medium));
+        assert!(matches!(parse(&["speedrun", "--difficulty", "perfect"]),
+            Err(OptionsError::InvalidValue {..})));
+    }
+
+    #[test]
+    fn game_codes() {
+        assert!(parse(&["share", "--moves", "2B 1A"]).unwrap().share);
+        assert!(parse(&["share"]).is_err());
+        let game = parse(&["--from-code", "CAKQ"]).unwrap().from_code.unwrap();
+        assert_eq!(game.moves().to_vec(), [(1, 1), (0, 0)]);
+        assert!(matches!(parse(&["--from-code", "CAFF"]), Err(OptionsError::InvalidValue {..})));
+        let options = parse(&["--share-url", "https://example.com/ttt"]).unwrap();
+        assert_eq!(options.share_url.as_deref(), Some("https://example.com/ttt"));
+        assert!(parse(&["export-qr", "--from-code", "CAKQ", "--export-png", "qr.png"]).unwrap()
+            .export_qr);
+        assert!(parse(&["export-qr", "--moves", "2B"]).unwrap().export_qr);
+        assert!(parse(&["export-qr"]).is_err());
+        let options = parse(&["archive", "check", "games.txt", "--fix"]).unwrap();
+        assert_eq!(options.check, Some(PathBuf::from("games.txt")));
+        assert!(options.fix);
+        assert!(parse(&["archive", "games.txt"]).is_err());
+        assert!(parse(&["archive", "check"]).is_err());
+        assert!(parse(&["verify", "games.txt", "--fix"]).is_err());
+        let (query, path) = parse(&["archive", "tagged", "source=irc", "games.txt"]).unwrap()
+            .tagged.unwrap();
+        assert_eq!((query.to_string(), path), ("source=irc".to_string(), "games.txt".into()));
+        assert!(parse(&["archive", "tagged", "source="]).is_err());
+        assert!(parse(&["archive", "tagged", "source"]).is_err());
+        let options = parse(&["archive", "audit", "games.txt"]).unwrap();
+        assert_eq!(options.audit, Some(PathBuf::from("games.txt")));
+        assert!(parse(&["archive", "audit"]).is_err());
+        let options = parse(&["diff", "old.txt", "CAKQS"]).unwrap();
+        assert_eq!(options.diff, Some(("old.txt".to_string(), "CAKQS".to_string())));
+        assert!(parse(&["diff", "old.txt"]).is_err());
+    }
+
+    #[test]
+    fn club() {
+        assert!(parse(&["club", "--clock", "3m+2s"]).unwrap().club);
+        assert!(!parse(&[]).unwrap().club);
+    }
+
+    #[test]
+    fn simul() {
+        let options = parse(&["simul", "Ada, Grace,Alan", "--giver", "medium"]).unwrap();
+        assert_eq!(options.simul.unwrap(), ["Ada", "Grace", "Alan"]);
+        assert_eq!(options.giver, Some(Computer::Engine(Difficulty::Medium)));
+        assert!(parse(&["simul", "Ada"]).is_err());
+        assert!(parse(&["simul", "Ada,,Grace"]).is_err());
+        assert!(matches!(parse(&["--giver", "perfect"]), Err(OptionsError::Requires {..})));
+    }
+
+    #[test]
+    fn setup() {
+        assert!(parse(&["setup"]).unwrap().setup);
+        assert!(!parse(&[]).unwrap().setup);
+    }
+
+    #[test]
+    fn theme() {
+        let options = parse(&["--theme", "dots", "--o-glyph", "★"]).unwrap();
+        assert_eq!(options.theme, Theme::from_name("dots"));
+        // The theme only provides the glyphs that weren't chosen on the command line
+        assert_eq!(options.x_player.glyph, Some("\u{25CF}".to_string()));
+        assert_eq!(options.o_player.glyph, Some("★".to_string()));
+        assert!(parse(&["--theme", "neon"]).is_err());
+    }
+
+    #[test]
+    fn coordinates() {
+        assert_eq!(parse(&["--coordinates", "keypad"]).unwrap().coordinates,
+            Some(CoordinateStyle::Keypad));
+        assert_eq!(parse(&[]).unwrap().coordinates, None);
+        assert!(parse(&["--coordinates", "chess"]).is_err());
+    }
+
+    #[test]
+    fn preferences() {
+        let prefs = Prefs {
+            theme: Theme::from_name("dots"),
+            difficulty: Some(Difficulty::Easy),
+            ..Prefs::default()
+        };
+        // A preferred difficulty is fine even when there is no demo to use it in
+        let options = Options::parse(vec!["--o-glyph".to_string(), "o".to_string()], &prefs)
+            .unwrap();
+        assert_eq!(options.x_player.glyph, Some("\u{25CF}".to_string()));
+        assert_eq!(options.o_player.glyph, Some("o".to_string()));
+        assert!(parse(&["--save-prefs"]).unwrap().save_prefs);
+    }
+
+    #[test]
+    fn demo() {
+        let options = parse(&["--demo", "--delay", "250", "--games", "3", "--difficulty", "easy"])
+            .unwrap();
+        assert!(options.demo);
+        assert_eq!(options.delay, Some(Duration::from_millis(250)));
+        assert_eq!(options.games, Some(3));
+        assert_eq!(options.difficulty, Some(Difficulty::Easy));
+
+        assert!(parse(&["--demo", "--games", "0"]).is_err());
+        // The demo settings can't be used without the demo
+        assert!(parse(&["--games", "3"]).is_err());
+    }
+
+    #[test]
+    fn layout() {
+        assert_eq!(parse(&[]).unwrap().layout, None);
+        assert_eq!(parse(&["--layout", "spacious"]).unwrap().layout, Some(Layout::Spacious));
+        assert_eq!(parse(&["--layout", "auto"]).unwrap().layout, None);
+        assert!(parse(&["--layout", "huge"]).is_err());
+    }
+
+    #[test]
+    fn rules() {
+        assert_eq!(parse(&[]).unwrap().rules, Rules::Classic);
+        assert_eq!(parse(&["--rules", "corners"]).unwrap().rules, Rules::Corners);
+        assert!(parse(&["--rules", "gomoku"]).is_err());
+    }
+
+    #[test]
+    fn tags() {
+        let options = parse(&["--tag", "event=club night", "--tag", "round=2"]).unwrap();
+        assert_eq!(options.tags, [("event".to_string(), "club night".to_string()),
+            ("round".to_string(), "2".to_string())]);
+        assert!(parse(&["--tag", "event"]).is_err());
+        assert!(parse(&["--tag", "two words=x"]).is_err());
+    }
+
+    #[test]
+    fn notify() {
+        let options = parse(&["--notify", "turn=desktop", "--notify", "turn=bell"]).unwrap();
+        assert_eq!(options.notify, [(Trigger::Turn, Alert::Desktop), (Trigger::Turn, Alert::Bell)]);
+        assert!(parse(&["--notify", "turn"]).is_err());
+    }
+
+    #[test]
+    fn on_eof() {
+        assert_eq!(parse(&[]).unwrap().on_eof, None);
+        assert_eq!(parse(&["--on-eof", "fail"]).unwrap().on_eof, Some(OnEof::Fail));
+        assert_eq!(parse(&["--on-eof", "Confirm"]).unwrap().on_eof, Some(OnEof::Confirm));
+        assert!(parse(&["--on-eof", "ignore"]).is_err());
+    }
+
+    #[test]
+    fn blindfold() {
+        assert!(!parse(&[]).unwrap().blindfold);
+        let options = parse(&["--blindfold", "--shows", "0"]).unwrap();
+        assert!(options.blindfold);
+        assert_eq!(options.shows, Some(0));
+        assert!(parse(&["--shows", "2"]).is_err());
+    }
+
+    #[test]
+    fn coach() {
+        assert_eq!(parse(&[]).unwrap().coach, None);
+        assert_eq!(parse(&["--coach", "Medium"]).unwrap().coach, Some(Level::Medium));
+        assert!(parse(&["--coach", "loud"]).is_err());
+    }
+
+    #[test]
+    fn announce_forced() {
+        assert!(parse(&[]).unwrap().announce_forced.is_empty());
+        assert_eq!(parse(&["--announce-forced", "O"]).unwrap().announce_forced, [Piece::O]);
+        assert_eq!(parse(&["--announce-forced", "both"]).unwrap().announce_forced,
+            [Piece::X, Piece::O]);
+        assert!(parse(&["--announce-forced", "all"]).is_err());
+    }
+
+    #[test]
+    fn moves() {
+        assert_eq!(parse(&["--moves", "B2 A1"]).unwrap().script,
+            Some(MoveScript::Inline("B2 A1".to_string())));
+        assert_eq!(parse(&["--moves-file", "game.txt"]).unwrap().script,
+            Some(MoveScript::File(PathBuf::from("game.txt"))));
+
+        let options = parse(&["--moves", "B2", "--export-svg", "board.svg"]).unwrap();
+        assert_eq!(options.export_svg, Some(PathBuf::from("board.svg")));
+        assert!(parse(&["--export-svg", "board.svg"]).is_err());
+        assert_eq!(parse(&["--record-cast", "game.cast"]).unwrap().record_cast,
+            Some(PathBuf::from("game.cast")));
+        let options = parse(&["--broadcast", "board.txt", "--broadcast-delay", "2moves"]).unwrap();
+        assert_eq!(options.broadcast, Some(PathBuf::from("board.txt")));
+        assert_eq!(options.broadcast_delay, Some(Delay::Moves(2)));
+        assert!(parse(&["--broadcast-delay", "30s"]).is_err());
+        assert!(parse(&["--broadcast", "board.txt", "--broadcast-delay", "soon"]).is_err());
+        assert_eq!(parse(&["--summary-csv", "sessions.csv"]).unwrap().summary_csv,
+            Some(PathBuf::from("sessions.csv")));
+
+        let options = parse(&["--moves", "B2", "--export-png", "board.png", "--image-size", "300"])
+            .unwrap();
+        assert_eq!(options.export_png, Some(PathBuf::from("board.png")));
+        assert_eq!(options.image_size, Some(300));
+        assert!(parse(&["--moves", "B2", "--export-png", "b.png", "--image-size", "0"]).is_err());
+        assert!(parse(&["--moves", "B2", "--image-size", "300"]).is_err());
+
+        let options = parse(&["--moves", "B2", "--export-gif", "game.gif", "--frame-delay", "500"])
+            .unwrap();
+        assert_eq!(options.export_gif, Some(PathBuf::from("game.gif")));
+        assert_eq!(options.frame_delay, Some(Duration::from_millis(500)));
+        assert_eq!(parse(&["--demo", "--seed", "42"]).unwrap().seed, Some(42));
+        assert!(parse(&["--seed", "-1"]).is_err());
+        assert!(parse(&["--moves", "B2", "--export-gif", "game.gif", "--image-size", "300"])
+            .is_ok());
+        assert!(parse(&["--moves", "B2", "--export-png", "b.png", "--frame-delay", "500"])
+            .is_err());
+        assert!(parse(&["--moves", "B2", "--export-gif", "g.gif", "--image-size", "99999"])
+            .is_err());
+    }
+
+    #[test]
+    fn logging() {
+        let options = parse(&["--log-level", "debug", "--log-file", "game.log"]).unwrap();
+        assert_eq!(options.log_level, Some(LevelFilter::DEBUG));
+        assert_eq!(options.log_file, Some(PathBuf::from("game.log")));
+        assert_eq!(parse(&["--log-level", "WARN"]).unwrap().log_level, Some(LevelFilter::WARN));
+        assert!(parse(&["--log-level", "loud"]).is_err());
+    }
+
+    #[test]
+    fn output() {
+        assert_eq!(parse(&[]).unwrap().output, OutputFormat::Text);
+        assert_eq!(parse(&["--output", "json"]).unwrap().output, OutputFormat::Json);
+        assert!(parse(&["--output", "xml"]).is_err());
+        let options = parse(&["--output", "plugin:curses"]).unwrap();
+        assert_eq!(options.output, OutputFormat::Json);
+        assert_eq!(options.renderer_plugin.as_deref(), Some("curses"));
+        assert!(parse(&["--output", "plugin:"]).is_err());
+    }
+
+    #[test]
+    fn invalid_best_of() {
+        assert!(parse(&["--best-of", "0"]).is_err());
+        assert!(parse(&["--best-of", "five"]).is_err());
+        assert!(parse(&["--best-of"]).is_err());
+    }
+}
@@ -0,0 +1,35 @@
+// Plays many games at once on every core, when the program is built with the `parallel` feature
+// (it is on by default). rayon gives slices, vectors and ranges `par_iter` and `into_par_iter`
+// methods, which work just like `iter` and `into_iter` except that the items are spread over
+// several threads.
+// More info: https://docs.rs/rayon
+//
+// Without the feature, the same methods are defined here and simply give back the normal
+// iterators, so the code that uses them doesn't have to change. Everything still comes out the
+// same, only slower. The code can only use what the two kinds of iterators have in common, like
+// `map`, `enumerate`, `collect` and `sum`.
+
+#[cfg(feature = "parallel")]
+pub use rayon::prelude::*;
+
+#[cfg(not(feature = "parallel"))]
+pub trait IntoParallelIterator: IntoIterator + Sized {
+    fn into_par_iter(self) -> Self::IntoIter {
+        self.into_iter()
+    }
+}
+
+#[cfg(not(feature = "parallel"))]
+impl<I: IntoIterator> IntoParallelIterator for I {}
+
+#[cfg(not(feature = "parallel"))]
+pub trait IntoParallelRefIterator<T> {
+    fn par_iter(&self) -> ::std::slice::Iter<'_, T>;
+}
+
+#[cfg(not(feature = "parallel"))]
+impl<T> IntoParallelRefIterator<T> for [T] {
+    fn par_iter(&self) -> ::std::slice::Iter<'_, T> {
+        self.iter()
+    }
+}
@@ -0,0 +1,58 @@
+// Perft ("performance test") counts the leaves of the game tree: every way that the next few moves
+// can be played from a position. The totals for the empty board are well known (there are 255168
+// different complete games of tic-tac-toe), so comparing against them is a quick way to check that
+// the rules in game.rs allow exactly the moves that they should.
+// More info: https://www.chessprogramming.org/Perft
+//
+// A game that ends before `depth` moves is still one leaf, since no more moves can be made after
+// it.
+
+use game::Game;
+
+// Counts the positions reached by playing `depth` more moves, or fewer if the game ends first
+pub fn perft(game: &Game, depth: u32) -> u64 {
+    if depth == 0 || game.winner().is_some() {
+        return 1;
+    }
+
+    let mut leaves = 0;
+    for row in 0..3 {
+        for col in 0..3 {
+            // Letting the game decide which moves are allowed is what makes this a check of the
+            // rules instead of a copy of them
+            let mut next = game.clone();
+            if next.make_move(row, col).is_ok() {
+                leaves += perft(&next, depth - 1);
+            }
+        }
+    }
+    leaves
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn known_totals() {
+        let game = Game::new();
+        let totals: Vec<_> = (0..=5).map(|depth| perft(&game, depth)).collect();
+        assert_eq!(totals, [1, 9, 72, 504, 3024, 15120]);
+        // 1440 games end after 5 moves, so the totals stop being 9 * 8 * 7 * ... and those games
+        // are leaves at every depth after that
+        assert_eq!(perft(&game, 6), (15120 - 1440) * 4 + 1440);
+        assert_eq!(perft(&game, 9), 255168);
+    }
+
+    #[test]
+    fn from_a_position() {
+        let mut game = Game::new();
+        for &(row, col) in &[(0, 0), (1, 0), (0, 1), (1, 1)] {
+            game.make_move(row, col).unwrap();
+        }
+        // x can win right away at 1C, which is a single leaf
+        assert_eq!(perft(&game, 1), 5);
+        game.make_move(0, 2).unwrap();
+        assert_eq!(perft(&game, 3), 1);
+    }
+}
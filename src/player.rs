@@ -0,0 +1,192 @@
+// Players can choose a display name and the character (or emoji!) used to draw their piece. When
+// they don't, we fall back to calling them "x" and "o" just like the pieces themselves.
+
+use thiserror::Error;
+
+use game::Piece;
+
+// The longest name we accept. Names are shown in prompts and results, so very long names would
+// make those messages hard to read.
+const MAX_NAME_LEN: usize = 20;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PlayerProfile {
+    /// The name shown in prompts and results
+    pub name: Option<String>,
+    /// The character used to draw this player's piece on the board
+    pub glyph: Option<String>,
+}
+
+// The reasons a name or glyph can be rejected
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum ProfileError {
+    #[error("the name is empty")]
+    EmptyName,
+    #[error("the name is too long")]
+    NameTooLong,
+    /// Glyphs must be exactly one character that takes up one or two columns in the terminal
+    #[error("a glyph must be one character that is one or two columns wide")]
+    InvalidGlyph,
+}
+
+impl ProfileError {
+    // The number that stands for this error (see error.rs)
+    pub fn code(&self) -> u16 {
+        match *self {
+            ProfileError::EmptyName => 210,
+            ProfileError::NameTooLong => 211,
+            ProfileError::InvalidGlyph => 212,
+        }
+    }
+}
+
+impl PlayerProfile {
+    // Checks that a name can be displayed nicely and returns it without any surrounding spaces
+    pub fn validate_name(name: &str) -> Result<String, ProfileError> {
+        let name = name.trim();
+        if name.is_empty() || name.chars().any(|c| c.is_control()) {
+            Err(ProfileError::EmptyName)
+        }
+        // `chars().count()` counts characters, unlike `len()` which counts bytes
+        else if name.chars().count() > MAX_NAME_LEN {
+            Err(ProfileError::NameTooLong)
+        }
+        else {
+            Ok(name.to_string())
+        }
+    }
+
+    // Checks that a glyph is a single character that fits in one tile of the board
+    pub fn validate_glyph(glyph: &str) -> Result<String, ProfileError> {
+        let mut chars = glyph.chars();
+        // Pattern matching on a tuple lets us check for "exactly one character" in one step
+        match (chars.next(), chars.next()) {
+            (Some(c), None) if display_width(c).is_some() => Ok(glyph.to_string()),
+            _ => Err(ProfileError::InvalidGlyph),
+        }
+    }
+}
+
+// The profiles of both players, indexed by the piece they are currently playing
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Players {
+    x: PlayerProfile,
+    o: PlayerProfile,
+}
+
+impl Players {
+    pub fn new(x: PlayerProfile, o: PlayerProfile) -> Self {
+        Self {x, o}
+    }
+
+    pub fn profile(&self, piece: Piece) -> &PlayerProfile {
+        match piece {
+            Piece::X => &self.x,
+            Piece::O => &self.o,
+        }
+    }
+
+    // The players trade pieces, so whoever was playing x now plays o and vice versa
+    pub fn swap(&mut self) {
+        // `mem::swap` exchanges two values without needing to copy either of them
+        ::std::mem::swap(&mut self.x, &mut self.o);
+    }
+
+    // The name of the player using the given piece, or the piece itself if they didn't pick one
+    pub fn name(&self, piece: Piece) -> &str {
+        // `as_ref().map(...)` lets us look at the String inside the Option without moving it
+        self.profile(piece).name.as_ref().map(|name| &name[..]).unwrap_or(piece_letter(piece))
+    }
+
+    // The character used to draw the given piece
+    pub fn glyph(&self, piece: Piece) -> &str {
+        self.profile(piece).glyph.as_ref().map(|glyph| &glyph[..]).unwrap_or(piece_letter(piece))
+    }
+
+    // How the player is referred to in messages: just "x" when nothing was customized, or the
+    // name followed by the glyph, like "Ada (★)", when it was
+    pub fn label(&self, piece: Piece) -> String {
+        let (name, glyph) = (self.name(piece), self.glyph(piece));
+        if name == glyph {
+            name.to_string()
+        }
+        else {
+            format!("{} ({})", name, glyph)
+        }
+    }
+
+    // The number of terminal columns needed to draw any piece. Emoji and many other symbols take
+    // up two columns instead of one.
+    pub fn glyph_width(&self) -> usize {
+        [Piece::X, Piece::O].iter()
+            .flat_map(|&piece| self.glyph(piece).chars())
+            .map(|c| display_width(c).unwrap_or(1))
+            .max()
+            .unwrap_or(1)
+    }
+}
+
+fn piece_letter(piece: Piece) -> &'static str {
+    match piece {
+        Piece::X => "x",
+        Piece::O => "o",
+    }
+}
+
+// Returns the number of columns a character takes up when printed in a terminal, or None if it
+// isn't something that can be printed on its own (like a space or a newline).
+// A complete answer requires the full Unicode tables (see the `unicode-width` crate). The ranges
+// below cover the wide characters that people are likely to pick: CJK characters, Hangul, full
+// width forms, and the most common emoji blocks.
+pub fn display_width(c: char) -> Option<usize> {
+    if c.is_control() || c.is_whitespace() {
+        return None;
+    }
+
+    match c as u32 {
+        // Combining marks are drawn on top of the previous character, so they have no width
+        0x0300..=0x036F | 0x200B..=0x200F | 0xFE00..=0xFE0F => None,
+        0x1100..=0x115F | 0x2E80..=0xA4CF | 0xAC00..=0xD7A3 | 0xF900..=0xFAFF |
+        0xFE30..=0xFE4F | 0xFF00..=0xFF60 | 0xFFE0..=0xFFE6 | 0x1F300..=0x1F64F |
+        0x1F900..=0x1F9FF | 0x20000..=0x3FFFD => Some(2),
+        _ => Some(1),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_players() {
+        let players = Players::default();
+        assert_eq!(players.name(Piece::X), "x");
+        assert_eq!(players.glyph(Piece::O), "o");
+        assert_eq!(players.label(Piece::O), "o");
+        assert_eq!(players.glyph_width(), 1);
+    }
+
+    #[test]
+    fn custom_players() {
+        let ada = PlayerProfile {name: Some("Ada".to_string()), glyph: Some("🐱".to_string())};
+        let mut players = Players::new(ada, PlayerProfile::default());
+        assert_eq!(players.label(Piece::X), "Ada (🐱)");
+        assert_eq!(players.glyph_width(), 2);
+
+        players.swap();
+        assert_eq!(players.name(Piece::O), "Ada");
+        assert_eq!(players.name(Piece::X), "x");
+    }
+
+    #[test]
+    fn validation() {
+        assert_eq!(PlayerProfile::validate_name("  Ada "), Ok("Ada".to_string()));
+        assert_eq!(PlayerProfile::validate_name("   "), Err(ProfileError::EmptyName));
+        assert_eq!(PlayerProfile::validate_name(&"a".repeat(21)), Err(ProfileError::NameTooLong));
+        assert!(PlayerProfile::validate_glyph("★").is_ok());
+        assert!(PlayerProfile::validate_glyph("🐱").is_ok());
+        assert_eq!(PlayerProfile::validate_glyph("ab"), Err(ProfileError::InvalidGlyph));
+        assert_eq!(PlayerProfile::validate_glyph(" "), Err(ProfileError::InvalidGlyph));
+        assert_eq!(PlayerProfile::validate_glyph(""), Err(ProfileError::InvalidGlyph));
+    }
+}
@@ -0,0 +1,313 @@
+// Plugins add renderers and computer players to the game without changing it. A plugin is a
+// dynamic library (a .so file on Linux, a .dylib on macOS or a .dll on Windows) in the plugins
+// directory, which is `plugins` next to the preferences file (see prefs.rs) unless `--plugins DIR`
+// says otherwise. When the program is built with the `plugins` feature, every library in that
+// directory is loaded as soon as it starts.
+//
+// Rust doesn't promise that two programs built by different versions of the compiler agree on how
+// their types are laid out, so plugins talk to the game through functions that follow the C
+// calling convention, just like the C library does (see ffi/src/lib.rs). That way a plugin can be
+// written in any language that can make a C library. The functions that the game looks for are
+// its ABI (Application Binary Interface):
+//
+// * `uint32_t ttt_plugin_abi_version(void)` - the version of the ABI that the plugin was written
+//   for, which has to be the same as ABI_VERSION below
+// * `const char *ttt_plugin_name(void)` - the name that the plugin is chosen by, in UTF-8
+// * `void ttt_plugin_render(const char *event)` - makes the plugin a renderer. It is given every
+//   event of the game as the same line of JSON that `--output json` prints, without the newline.
+// * `int32_t ttt_plugin_choose_move(const uint8_t *tiles, uint8_t next)` - makes the plugin a
+//   computer player. `tiles` points to the 9 tiles of the board, row by row, with 0 for an empty
+//   tile, 1 for x and 2 for o. `next` is the piece to move (1 or 2). It returns the tile to play
+//   next, from 0 to 8.
+//
+// The first two are required, and every plugin has at least one of the other two. Strings are
+// only borrowed for as long as the function is running, on both sides.
+//
+// Plugins can't add rules (see rules.rs). The rules of a game are saved in its share code by a
+// number that never changes (see share.rs), and a copy of the game without the plugin couldn't
+// read it back.
+// More info: https://docs.rs/libloading
+
+use std::convert::TryFrom;
+use std::ffi::{CString, c_char};
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+#[cfg(feature = "plugins")]
+use std::env::consts::DLL_EXTENSION;
+#[cfg(feature = "plugins")]
+use std::ffi::CStr;
+#[cfg(feature = "plugins")]
+use std::fs;
+#[cfg(feature = "plugins")]
+use std::io;
+#[cfg(feature = "plugins")]
+use std::path::Path;
+
+#[cfg(feature = "plugins")]
+use libloading::Library;
+use thiserror::Error;
+
+use game::{Game, Piece};
+#[cfg(feature = "plugins")]
+use prefs;
+
+// The version of the ABI described above. It goes up whenever one of the functions changes in a
+// way that the plugins written before wouldn't expect. New functions that a plugin doesn't have
+// to provide don't change it.
+pub const ABI_VERSION: u32 = 1;
+
+// The types of the functions that plugins export
+#[cfg(feature = "plugins")]
+type AbiVersionFn = unsafe extern "C" fn() -> u32;
+#[cfg(feature = "plugins")]
+type NameFn = unsafe extern "C" fn() -> *const c_char;
+type RenderFn = unsafe extern "C" fn(event: *const c_char);
+type ChooseMoveFn = unsafe extern "C" fn(tiles: *const u8, next: u8) -> i32;
+
+// Every plugin that was loaded. Their libraries are never closed, so that the functions they gave
+// us can be called at any time until the program ends.
+static PLUGINS: OnceLock<Vec<Plugin>> = OnceLock::new();
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum PluginError {
+    /// The library couldn't be opened, or the plugins directory couldn't be read
+    #[error("{0}")]
+    Load(String),
+    /// The plugin was written for a different version of the ABI
+    #[error("the plugin uses version {0} of the plugin ABI instead of version {ABI_VERSION}")]
+    WrongVersion(u32),
+    /// One of the functions that every plugin needs is missing
+    #[error("the plugin doesn't export {0}")]
+    MissingFunction(&'static str),
+    /// The name of the plugin is empty, or isn't UTF-8
+    #[error("the plugin's name is empty or isn't UTF-8")]
+    InvalidName,
+    /// The plugin is neither a renderer nor a computer player
+    #[error("the plugin is neither a renderer nor a computer player")]
+    NothingProvided,
+    /// Another plugin that was loaded first has the same name
+    #[error("a plugin named {0} was already loaded")]
+    Duplicate(String),
+    /// No renderer plugin has this name
+    #[error("there is no renderer plugin named {0}")]
+    NoRenderer(String),
+    /// No computer player plugin has this name
+    #[error("there is no computer player plugin named {0}")]
+    NoPlayer(String),
+    /// The computer player chose this tile, which isn't an empty tile on the board
+    #[error("the plugin chose tile {0}, which isn't an empty tile on the board")]
+    InvalidMove(i32),
+}
+
+impl PluginError {
+    // The number that stands for this error (see error.rs)
+    pub fn code(&self) -> u16 {
+        match *self {
+            PluginError::Load(_) => 700,
+            PluginError::WrongVersion(_) => 701,
+            PluginError::MissingFunction(_) => 702,
+            PluginError::InvalidName => 703,
+            PluginError::NothingProvided => 704,
+            PluginError::Duplicate(_) => 705,
+            PluginError::NoRenderer(_) => 706,
+            PluginError::NoPlayer(_) => 707,
+            PluginError::InvalidMove(_) => 708,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Plugin {
+    /// The name that the plugin is chosen by
+    pub name: String,
+    /// The library that the plugin was loaded from
+    pub path: PathBuf,
+    render: Option<RenderFn>,
+    choose_move: Option<ChooseMoveFn>,
+}
+
+impl Plugin {
+    pub fn is_renderer(&self) -> bool {
+        self.render.is_some()
+    }
+
+    pub fn is_player(&self) -> bool {
+        self.choose_move.is_some()
+    }
+
+    // Sends a line of JSON to a renderer plugin. An event with a NUL in it can't be passed as a C
+    // string, but JSON never has one, since json::string escapes it.
+    pub fn render(&self, event: &str) {
+        let render = self.render.expect("only renderer plugins are sent events");
+        let event = CString::new(event).expect("JSON events don't have a NUL in them");
+        // Safety: the plugin said it takes a C string, and it is only borrowed during the call
+        unsafe { render(event.as_ptr()) }
+    }
+
+    // Asks a computer player plugin for its move. The move is only returned if it can be played.
+    pub fn choose_move(&self, game: &Game) -> Result<(usize, usize), PluginError> {
+        let choose_move = self.choose_move.expect("only computer players are asked for moves");
+        let mut tiles = [0; 9];
+        for (tile, piece) in tiles.iter_mut().zip(game.tiles().iter().flatten()) {
+            *tile = value(*piece);
+        }
+        // Safety: the plugin said it reads 9 tiles, and they are only borrowed during the call
+        let tile = unsafe { choose_move(tiles.as_ptr(), value(Some(game.current_piece()))) };
+        match usize::try_from(tile) {
+            Ok(index) if index < 9 && tiles[index] == 0 => Ok((index / 3, index % 3)),
+            _ => Err(PluginError::InvalidMove(tile)),
+        }
+    }
+}
+
+// How pieces are written for plugins: 0 for an empty tile, 1 for x and 2 for o
+fn value(piece: Option<Piece>) -> u8 {
+    match piece {
+        None => 0,
+        Some(Piece::X) => 1,
+        Some(Piece::O) => 2,
+    }
+}
+
+// The plugins directory unless `--plugins` says otherwise: `plugins` next to the preferences
+#[cfg(feature = "plugins")]
+pub fn default_dir() -> Option<PathBuf> {
+    prefs::path().and_then(|path| Some(path.parent()?.join("plugins")))
+}
+
+// Loads every plugin in the directory, in the order of their file names. This can only be done
+// once, and the plugins that can't be loaded are skipped. Returns why each one was skipped.
+#[cfg(feature = "plugins")]
+pub fn load_all(dir: &Path) -> Vec<(PathBuf, PluginError)> {
+    let mut plugins: Vec<Plugin> = Vec::new();
+    let mut skipped = Vec::new();
+
+    // Not having a plugins directory at all just means that there aren't any plugins
+    let paths = match fs::read_dir(dir) {
+        Ok(entries) => entries.filter_map(|entry| Some(entry.ok()?.path())).collect(),
+        Err(ref error) if error.kind() == io::ErrorKind::NotFound => Vec::new(),
+        Err(error) => {
+            skipped.push((dir.to_path_buf(), PluginError::Load(error.to_string())));
+            Vec::new()
+        },
+    };
+    let mut paths: Vec<PathBuf> = paths.into_iter()
+        .filter(|path| path.extension().is_some_and(|extension| extension == DLL_EXTENSION))
+        .collect();
+    paths.sort();
+
+    for path in paths {
+        // Safety: loading a library runs its initialization code, so we have to trust whoever put
+        // it in the plugins directory, just like we trust the program itself
+        match unsafe { load(&path) } {
+            Ok(plugin) if plugins.iter().any(|other| other.name == plugin.name) => {
+                skipped.push((path, PluginError::Duplicate(plugin.name)));
+            },
+            Ok(plugin) => plugins.push(plugin),
+            Err(error) => skipped.push((path, error)),
+        }
+    }
+
+    PLUGINS.set(plugins).expect("plugins can only be loaded once");
+    skipped
+}
+
+// Opens the library and looks for the functions of the ABI in it
+#[cfg(feature = "plugins")]
+unsafe fn load(path: &Path) -> Result<Plugin, PluginError> {
+    let library = Library::new(path).map_err(|error| PluginError::Load(error.to_string()))?;
+    // Plugins are never unloaded (even the ones that are skipped, since their code may already be
+    // running), so the library lives as long as the program does
+    let library: &'static Library = Box::leak(Box::new(library));
+    // `get` looks for a function by its name, which has to end with a NUL just like a C string.
+    // The function is copied out so that it doesn't borrow the library.
+    let abi_version = *library.get::<AbiVersionFn>(b"ttt_plugin_abi_version\0")
+        .map_err(|_| PluginError::MissingFunction("ttt_plugin_abi_version"))?;
+    let version = abi_version();
+    if version != ABI_VERSION {
+        return Err(PluginError::WrongVersion(version));
+    }
+
+    let name = *library.get::<NameFn>(b"ttt_plugin_name\0")
+        .map_err(|_| PluginError::MissingFunction("ttt_plugin_name"))?;
+    let name = name();
+    if name.is_null() {
+        return Err(PluginError::InvalidName);
+    }
+    let name = CStr::from_ptr(name).to_str().map_err(|_| PluginError::InvalidName)?.to_string();
+    if name.is_empty() {
+        return Err(PluginError::InvalidName);
+    }
+
+    let render = library.get::<RenderFn>(b"ttt_plugin_render\0").ok().map(|render| *render);
+    let choose_move = library.get::<ChooseMoveFn>(b"ttt_plugin_choose_move\0").ok()
+        .map(|choose_move| *choose_move);
+    if render.is_none() && choose_move.is_none() {
+        return Err(PluginError::NothingProvided);
+    }
+
+    Ok(Plugin {name, path: path.to_path_buf(), render, choose_move})
+}
+
+// Every plugin that was loaded, or none if the program doesn't load plugins
+pub fn all() -> &'static [Plugin] {
+    PLUGINS.get().map_or(&[], Vec::as_slice)
+}
+
+// The renderer plugin with the given name
+pub fn renderer(name: &str) -> Result<&'static Plugin, PluginError> {
+    all().iter().find(|plugin| plugin.name == name && plugin.is_renderer())
+        .ok_or_else(|| PluginError::NoRenderer(name.to_string()))
+}
+
+// The computer player plugin with the given name
+pub fn player(name: &str) -> Result<&'static Plugin, PluginError> {
+    all().iter().find(|plugin| plugin.name == name && plugin.is_player())
+        .ok_or_else(|| PluginError::NoPlayer(name.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A computer player plugin without a library, for testing what the game does with its moves
+    fn player(choose_move: ChooseMoveFn) -> Plugin {
+        Plugin {
+            name: "test".to_string(),
+            path: PathBuf::new(),
+            render: None,
+            choose_move: Some(choose_move),
+        }
+    }
+
+    #[test]
+    fn moves() {
+        // Plays the first empty tile, like a plugin written in C could
+        unsafe extern "C" fn first_empty(tiles: *const u8, _next: u8) -> i32 {
+            let tiles = ::std::slice::from_raw_parts(tiles, 9);
+            tiles.iter().position(|&tile| tile == 0).map_or(-1, |tile| tile as i32)
+        }
+        unsafe extern "C" fn center(_tiles: *const u8, next: u8) -> i32 {
+            // x is 1, so it always plays in the middle, while o plays off the board
+            if next == 1 { 4 } else { 9 }
+        }
+
+        let mut game = Game::new();
+        game.make_move(0, 0).unwrap();
+        assert_eq!(player(first_empty).choose_move(&game), Ok((0, 1)));
+        assert_eq!(player(center).choose_move(&game), Err(PluginError::InvalidMove(9)));
+        game.make_move(0, 1).unwrap();
+        assert_eq!(player(center).choose_move(&game), Ok((1, 1)));
+        game.make_move(1, 1).unwrap();
+        game.make_move(2, 2).unwrap();
+        // The middle is taken
+        assert_eq!(player(center).choose_move(&game), Err(PluginError::InvalidMove(4)));
+    }
+
+    #[test]
+    fn nothing_loaded() {
+        assert!(renderer("test").is_err());
+        assert_eq!(super::player("test").unwrap_err(), PluginError::NoPlayer("test".to_string()));
+    }
+}
@@ -0,0 +1,294 @@
+// Preferences are settings that a player wants every time they play, so that they don't have to
+// type the same options again and again. They are saved in a plain text file in the platform's
+// usual place for configuration files, with one `key = value` setting per line:
+//
+//     # tic-tac-toe preferences
+//     name = Ada
+//     theme = dots
+//     difficulty = perfect
+//     lang = fr
+//     coordinates = keypad
+//
+// Options given on the command line always win over the preferences. Running the game with
+// `--save-prefs` saves the current options as the new preferences.
+//
+// The file also keeps the player's streak of daily puzzles (see daily.rs), which is saved every
+// time they solve one:
+//
+//     daily_solved = 2026-10-16
+//     daily_streak = 3
+//     daily_best = 5
+//
+// The best times of the players who finished a speedrun are kept here too (see speedrun.rs).
+//
+// Computer players with their own settings, called engine profiles, are written here by hand, one
+// setting per line (see engine.rs for what each setting does):
+//
+//     engine.aggressive.algorithm = minimax
+//     engine.aggressive.depth = 2
+//
+// They are then played against with `engine:aggressive` anywhere a computer player is chosen.
+
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use daily::{self, Streak};
+use engine::{Difficulty, Profile};
+use locale::Lang;
+use notation::CoordinateStyle;
+use options::Options;
+use player::PlayerProfile;
+use speedrun::Leaderboard;
+use storage::{self, Recovery};
+use theme::Theme;
+
+// The name of the preferences file inside of the directory for this program
+const FILE_NAME: &str = "prefs.txt";
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Prefs {
+    /// The player's name, used for x unless `--x-name` is given
+    pub name: Option<String>,
+    /// The characters used to draw the board
+    pub theme: Option<Theme>,
+    /// How well the computer plays
+    pub difficulty: Option<Difficulty>,
+    /// The language to show messages in
+    pub lang: Option<Lang>,
+    /// How tiles are written
+    pub coordinates: Option<CoordinateStyle>,
+    /// The days in a row that the daily puzzle was solved on
+    pub streak: Streak,
+    /// The best speedrun times of everyone who played on this computer
+    pub speedruns: Leaderboard,
+    /// The engine profiles, by name, in the order they first appear in the file
+    pub engines: Vec<(String, Profile)>,
+}
+
+impl Prefs {
+    // Reads the preferences from a file, along with what was done about the file if it was corrupt
+    // (see storage.rs). A missing file isn't an error since nobody has saved any preferences until
+    // they run with `--save-prefs` for the first time.
+    pub fn load(path: &Path) -> io::Result<(Self, Option<Recovery>)> {
+        let loaded = storage::read(path)?;
+        let prefs = loaded.text.map_or_else(Self::default, |text| Self::parse(&text));
+        Ok((prefs, loaded.recovery))
+    }
+
+    // Reads preferences from the text of a preferences file. Lines that we don't understand are
+    // skipped instead of being treated as errors, so that a preferences file written by a newer
+    // version of the program still works.
+    pub fn parse(text: &str) -> Self {
+        let mut prefs = Self::default();
+        for line in text.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            // `splitn(2, ...)` splits at the first `=` only, so values may contain `=` too
+            let mut parts = line.splitn(2, '=');
+            let (key, value) = match (parts.next(), parts.next()) {
+                (Some(key), Some(value)) => (key.trim(), value.trim()),
+                _ => continue,
+            };
+            match key {
+                "name" => prefs.name = PlayerProfile::validate_name(value).ok(),
+                "theme" => prefs.theme = Theme::from_name(value),
+                "difficulty" => prefs.difficulty = Difficulty::from_name(value),
+                "lang" => prefs.lang = Lang::from_code(value),
+                "coordinates" => prefs.coordinates = CoordinateStyle::from_name(value),
+                "daily_solved" => prefs.streak.last = daily::parse_date(value),
+                "daily_streak" => prefs.streak.current = value.parse().unwrap_or(0),
+                "daily_best" => prefs.streak.best = value.parse().unwrap_or(0),
+                key if key.starts_with("speedrun_") => prefs.speedruns.parse_line(key, value),
+                key if key.starts_with("engine.") => prefs.parse_engine(key, value),
+                _ => {},
+            }
+        }
+        prefs
+    }
+
+    // Reads one setting of an engine profile, like `engine.aggressive.depth = 2`. The profile is
+    // created by the first of its settings. A setting that isn't valid is skipped, but still
+    // creates the profile, so that using it doesn't fail just because of a typo in one setting.
+    fn parse_engine(&mut self, key: &str, value: &str) {
+        // `rsplit_once` splits at the last `.`, between the name and the setting
+        let (name, setting) = match key["engine.".len()..].rsplit_once('.') {
+            Some((name, setting)) if valid_engine_name(name) => (name, setting),
+            _ => return,
+        };
+        let index = match self.engines.iter().position(|(other, _)| other == name) {
+            Some(index) => index,
+            None => {
+                self.engines.push((name.to_string(), Profile::default()));
+                self.engines.len() - 1
+            },
+        };
+        let _ = self.engines[index].1.set(setting, value);
+    }
+
+    // Takes the preferences from the options given on the command line. There is no option for
+    // the streak, the speedrun times or the engine profiles, so they are kept from the preferences
+    // that were saved before.
+    pub fn from_options(options: &Options, saved: &Prefs) -> Self {
+        Self {
+            name: options.x_player.name.clone(),
+            theme: options.theme,
+            difficulty: options.difficulty,
+            lang: options.lang,
+            coordinates: options.coordinates,
+            streak: saved.streak,
+            speedruns: saved.speedruns.clone(),
+            engines: saved.engines.clone(),
+        }
+    }
+
+    // Fills in every option that wasn't given on the command line with the preferred value
+    pub fn merge(&self, options: &mut Options) {
+        if options.x_player.name.is_none() {
+            options.x_player.name = self.name.clone();
+        }
+        // `or` keeps the first Option if it has a value, and otherwise uses the second one
+        options.theme = options.theme.or(self.theme);
+        options.difficulty = options.difficulty.or(self.difficulty);
+        options.lang = options.lang.or(self.lang);
+        options.coordinates = options.coordinates.or(self.coordinates);
+    }
+
+    // Writes the preferences to a file, creating its directory first if it doesn't exist yet
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        storage::save(path, self.to_string().as_bytes())
+    }
+}
+
+// Implementing Display lets us turn the preferences into the text of the file with `to_string()`.
+// Only the preferences that were set are written.
+impl fmt::Display for Prefs {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "# tic-tac-toe preferences")?;
+        if let Some(ref name) = self.name {
+            writeln!(f, "name = {}", name)?;
+        }
+        if let Some(theme) = self.theme {
+            writeln!(f, "theme = {}", theme.name)?;
+        }
+        if let Some(difficulty) = self.difficulty {
+            writeln!(f, "difficulty = {}", difficulty.name())?;
+        }
+        if let Some(lang) = self.lang {
+            writeln!(f, "lang = {}", lang.code())?;
+        }
+        if let Some(coordinates) = self.coordinates {
+            writeln!(f, "coordinates = {}", coordinates.name())?;
+        }
+        if let Some(last) = self.streak.last {
+            writeln!(f, "daily_solved = {}", daily::date(last))?;
+            writeln!(f, "daily_streak = {}", self.streak.current)?;
+            writeln!(f, "daily_best = {}", self.streak.best)?;
+        }
+        // The time comes before the name, since a name can have spaces in it
+        for best in self.speedruns.bests() {
+            writeln!(f, "speedrun_{} = {} {}", best.difficulty.name(), best.time.as_millis(),
+                best.name)?;
+        }
+        for (name, profile) in &self.engines {
+            for (setting, value) in profile.settings() {
+                writeln!(f, "engine.{}.{} = {}", name, setting, value)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// Engine profiles are referred to inside of lists like `--entrants engine:a,engine:b`, so their
+// names can only have letters, digits, `-` and `_` in them
+fn valid_engine_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+}
+
+// Finds where the preferences file belongs. Every platform has its own convention for where
+// programs keep their configuration. Returns None if the environment doesn't tell us where the
+// user's files are.
+pub fn path() -> Option<PathBuf> {
+    // `cfg!` is true or false depending on the platform the program was compiled for
+    let config_dir = if cfg!(windows) {
+        env::var_os("APPDATA").map(PathBuf::from)
+    }
+    else if cfg!(target_os = "macos") {
+        env::var_os("HOME").map(|home| Path::new(&home).join("Library/Application Support"))
+    }
+    else {
+        // Everywhere else we follow the XDG Base Directory specification
+        // More info: https://specifications.freedesktop.org/basedir-spec/latest/
+        env::var_os("XDG_CONFIG_HOME").filter(|dir| !dir.is_empty()).map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".config")))
+    };
+    config_dir.map(|dir| dir.join("tic-tac-toe").join(FILE_NAME))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::time::Duration;
+
+    use engine::Evaluator;
+
+    #[test]
+    fn save_and_parse() {
+        let mut speedruns = Leaderboard::default();
+        speedruns.record("Grace H", Difficulty::Medium, Duration::from_millis(93810));
+        let prefs = Prefs {
+            name: Some("Ada".to_string()),
+            theme: Theme::from_name("dots"),
+            difficulty: Some(Difficulty::Perfect),
+            lang: Some(Lang::French),
+            coordinates: Some(CoordinateStyle::Numeric),
+            streak: Streak {last: Some(20742), current: 3, best: 5},
+            speedruns,
+            engines: vec![
+                ("aggressive".to_string(), Profile {depth: Some(2), ..Profile::default()}),
+                ("sloppy".to_string(), Profile {randomness: 30, ..Difficulty::Medium.into()}),
+            ],
+        };
+        assert_eq!(Prefs::parse(&prefs.to_string()), prefs);
+        assert_eq!(Prefs::parse(&Prefs::default().to_string()), Prefs::default());
+    }
+
+    #[test]
+    fn unknown_lines_are_skipped() {
+        let prefs = Prefs::parse("# comment\nsound = on\ntheme = neon\nnonsense\nlang=fr\n");
+        assert_eq!(prefs, Prefs {lang: Some(Lang::French), ..Prefs::default()});
+    }
+
+    #[test]
+    fn engine_profiles() {
+        let prefs = Prefs::parse("engine.fast.time = 200ms\nengine.fast.depth = lots\n\
+            engine.fast.evaluator = lines\nengine.bad,name.depth = 1\nengine.nosetting = 1\n");
+        let fast = Profile {
+            time: Some(Duration::from_millis(200)),
+            evaluator: Evaluator::Lines,
+            ..Profile::default()
+        };
+        assert_eq!(prefs.engines, [("fast".to_string(), fast)]);
+    }
+
+    #[test]
+    fn command_line_wins() {
+        let prefs = Prefs {
+            name: Some("Ada".to_string()),
+            lang: Some(Lang::French),
+            ..Prefs::default()
+        };
+        let mut options = Options {lang: Some(Lang::English), ..Options::default()};
+        prefs.merge(&mut options);
+        assert_eq!(options.lang, Some(Lang::English));
+        assert_eq!(options.x_player.name, Some("Ada".to_string()));
+    }
+}
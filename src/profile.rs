@@ -0,0 +1,155 @@
+// Measures where the search in solve.rs spends its time, for the `profile` command. The search
+// reports every position it visits and every part of the work it does on them (see `Hooks`), and
+// the profile adds up the time of each part, separately for each number of moves below the
+// starting position.
+//
+// Besides the totals that the command shows, the profile can be saved as "folded stacks", the
+// format read by flame graph tools like inferno (`inferno-flamegraph`) and flamegraph.pl. Each
+// line is a list of frames separated by semicolons, followed by how much time was spent there:
+//
+//     search;ply 0;ply 1;table probe 52300
+//
+// A flame graph draws each frame as a bar as wide as the time spent in it and the frames above
+// it, which makes it easy to see which part of the search is worth making faster.
+// More info: https://www.brendangregg.com/flamegraphs.html
+
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+
+use game::Game;
+use solve::{self, Hooks, Phase};
+use tt::Table;
+
+// The size of the transposition table used by the search. It is the same size as the one used
+// to make the table of perfect moves, so the profile measures the same search.
+const TABLE_MEGABYTES: usize = 4;
+
+#[derive(Debug, Clone, Default)]
+pub struct Profile {
+    /// The number of positions visited at each ply (the number of moves below the starting
+    /// position)
+    pub positions: Vec<u64>,
+    /// The time spent in each phase at each ply, indexed by the ply and then by `Phase::index`
+    pub times: Vec<[Duration; Phase::ALL.len()]>,
+    /// How long the whole search took
+    pub total: Duration,
+}
+
+impl Hooks for Profile {
+    fn visit(&mut self, ply: usize) {
+        self.grow(ply);
+        self.positions[ply] += 1;
+    }
+
+    fn phase<T, F: FnOnce() -> T>(&mut self, phase: Phase, ply: usize, work: F) -> T {
+        let start = Instant::now();
+        let result = work();
+        let elapsed = start.elapsed();
+        self.grow(ply);
+        self.times[ply][phase.index()] += elapsed;
+        result
+    }
+}
+
+impl Profile {
+    // Makes room for the given ply, since we don't know how deep the search goes until it gets
+    // there
+    fn grow(&mut self, ply: usize) {
+        if self.positions.len() <= ply {
+            self.positions.resize(ply + 1, 0);
+            self.times.resize(ply + 1, [Duration::ZERO; Phase::ALL.len()]);
+        }
+    }
+
+    // The number of positions visited at every ply together
+    pub fn total_positions(&self) -> u64 {
+        self.positions.iter().sum()
+    }
+
+    // The time spent in the given phase at every ply together
+    pub fn phase_time(&self, phase: Phase) -> Duration {
+        self.times.iter().map(|times| times[phase.index()]).sum()
+    }
+
+    // The time that wasn't spent in any of the phases, like going from one position to the next
+    // and comparing the scores. Measuring the phases takes time too, which ends up in here.
+    pub fn other_time(&self) -> Duration {
+        let phases = Phase::ALL.iter().map(|&phase| self.phase_time(phase)).sum();
+        self.total.saturating_sub(phases)
+    }
+
+    // Writes the profile as folded stacks, with the times in nanoseconds. Parts of the search
+    // that took no time at all are left out, since they wouldn't show up in a flame graph anyway.
+    pub fn write_folded<W: Write>(&self, mut out: W) -> io::Result<()> {
+        let mut stack = String::from("search");
+        for (ply, times) in self.times.iter().enumerate() {
+            stack.push_str(&format!(";ply {}", ply));
+            for &phase in &Phase::ALL {
+                let nanos = times[phase.index()].as_nanos();
+                if nanos > 0 {
+                    writeln!(out, "{};{} {}", stack, frame(phase), nanos)?;
+                }
+            }
+        }
+        let other = self.other_time().as_nanos();
+        if other > 0 {
+            writeln!(out, "search {}", other)?;
+        }
+        Ok(())
+    }
+}
+
+// The name of each phase in the flame graph. These are for the people working on the program, so
+// they aren't translated.
+fn frame(phase: Phase) -> &'static str {
+    match phase {
+        Phase::Evaluation => "evaluation",
+        Phase::TableProbe => "table probe",
+        Phase::MoveGeneration => "move generation",
+        Phase::MakeMove => "make move",
+    }
+}
+
+// Searches for the best moves from the given position with a new transposition table, measuring
+// the search as it goes
+pub fn profile(game: &Game) -> Profile {
+    let mut profile = Profile::default();
+    let mut table = Table::with_megabytes(TABLE_MEGABYTES);
+    let start = Instant::now();
+    solve::best_moves_with(game, &mut table, &mut profile);
+    profile.total = start.elapsed();
+    profile
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn profile_and_folded_stacks() {
+        let mut game = Game::new();
+        game.make_move(1, 1).unwrap();
+        let profile = profile(&game);
+        // The search starts from a single position and can go at most 8 moves deeper
+        assert_eq!(profile.positions[0], 1);
+        assert_eq!(profile.positions.len(), 9);
+        assert!(profile.total_positions() > 8);
+
+        // Measuring the search doesn't change what it finds
+        let mut table = Table::with_megabytes(TABLE_MEGABYTES);
+        let mut moves = solve::best_moves_with(&game, &mut table, &mut Profile::default());
+        moves.sort();
+        assert_eq!(moves, [(0, 0), (0, 2), (2, 0), (2, 2)]);
+
+        let mut folded = Vec::new();
+        profile.write_folded(&mut folded).unwrap();
+        let folded = String::from_utf8(folded).unwrap();
+        for line in folded.lines() {
+            let (stack, nanos) = line.rsplit_once(' ').unwrap();
+            assert!(stack.starts_with("search"));
+            assert!(nanos.parse::<u64>().unwrap() > 0);
+        }
+        assert!(folded.contains("search;ply 0;move generation "));
+        assert!(folded.contains("search;ply 0;ply 1;ply 2;table probe "));
+    }
+}
@@ -0,0 +1,504 @@
+// Puzzles for the `puzzle` command. Each puzzle is a position where the player to move has to
+// find a forced win within a number of moves, or find the only moves that hold the draw. The
+// player's moves are checked with the solver (see solve.rs), so any move that reaches the goal is
+// accepted, not only the one the puzzle was made with.
+//
+// Puzzle files are plain text, just like the archive. Each line is one puzzle, made of the moves
+// that lead to its position, what the player has to do and how many moves they have to do it in:
+//
+//     puzzle goal=win in=2 moves=1A,2B,3C,1C
+//
+// x moves first unless the line says otherwise with `first=o`. Puzzles can also be tagged with how
+// hard they are and the idea behind the first move, like `level=medium theme=fork`, which is how
+// the generator writes them (see generate.rs). Blank lines and lines that start with # are
+// skipped, so a file can explain its puzzles.
+
+use game::{Game, MoveError, Piece, Winner};
+use notation::{CoordinateStyle, parse_move};
+use random::Rng;
+use solve;
+use tt::Table;
+
+// The puzzles that come with the game, from the easiest to the hardest
+pub const BUNDLED: &str = include_str!("puzzles.txt");
+
+// How big the table that the solver remembers positions in is. The positions in a puzzle are
+// nearly over, so a small one is plenty.
+const TABLE_MEGABYTES: usize = 1;
+
+// What the player has to do in a puzzle
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Goal {
+    /// Win, however the other player defends
+    Win,
+    /// Get through the moves without giving the other player a way to win
+    Draw,
+}
+
+// How hard a puzzle is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Easy,
+    Medium,
+    Hard,
+}
+
+#[derive(Debug, Clone)]
+pub struct Puzzle {
+    /// The position the puzzle starts from
+    pub game: Game,
+    pub goal: Goal,
+    /// The number of moves the player has to reach the goal in
+    pub moves: usize,
+    pub level: Option<Level>,
+    /// The idea behind the first move of the solution
+    pub theme: Option<Idea>,
+}
+
+// Something wrong with a line of a puzzle file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Problem {
+    /// The line isn't a puzzle
+    UnknownRecord,
+    /// A field that every puzzle has is missing
+    MissingField(&'static str),
+    /// A field has a value that it can't have
+    InvalidField { field: &'static str, value: String },
+    /// A move can't be played, because it isn't a tile, its tile is taken or the game is already
+    /// over. Moves are numbered from 1.
+    IllegalMove { number: usize, notation: String },
+    /// The goal can't be reached from the position in the number of moves the puzzle gives
+    NoSolution,
+}
+
+// The idea behind a move, which is the first hint for it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Idea {
+    /// The move wins right away
+    Win,
+    /// The move stops the other player from winning on their next turn
+    Block,
+    /// The move makes two threats at once, so the other player can only block one of them
+    Fork,
+    /// The move makes a threat that the other player has to block
+    Threat,
+    /// The move doesn't make a threat or stop one
+    Quiet,
+}
+
+impl Level {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "easy" => Some(Level::Easy),
+            "medium" => Some(Level::Medium),
+            "hard" => Some(Level::Hard),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Level::Easy => "easy",
+            Level::Medium => "medium",
+            Level::Hard => "hard",
+        }
+    }
+}
+
+impl Idea {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "win" => Some(Idea::Win),
+            "block" => Some(Idea::Block),
+            "fork" => Some(Idea::Fork),
+            "threat" => Some(Idea::Threat),
+            "quiet" => Some(Idea::Quiet),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Idea::Win => "win",
+            Idea::Block => "block",
+            Idea::Fork => "fork",
+            Idea::Threat => "threat",
+            Idea::Quiet => "quiet",
+        }
+    }
+}
+
+// Each hint for a move gives away more than the one before it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hint {
+    Idea(Idea),
+    /// The row that the move is in, counting from 0
+    Row(usize),
+    Move(usize, usize),
+}
+
+// What came of a move in a puzzle
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Progress {
+    /// The move was right, and the other player answered with the given move. There is no answer
+    /// once the puzzle is solved.
+    Right(Option<(usize, usize)>),
+    /// The move doesn't reach the goal, so it was taken back
+    Wrong,
+}
+
+// How one of the player's moves in a puzzle went, for the result of the daily puzzle (see
+// daily.rs)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Try {
+    /// The move was right, and no hints were needed to find it
+    Right,
+    /// The move was right, after at least one hint
+    Hinted,
+    /// The move was taken back
+    Wrong,
+}
+
+// One try at solving a puzzle. The player makes their moves here, and the solver answers for the
+// other player with the defense that holds out the longest.
+#[derive(Debug, Clone)]
+pub struct Attempt {
+    game: Game,
+    goal: Goal,
+    /// The moves the player has left to reach the goal in
+    moves_left: usize,
+    /// The hints given for the position as it is now, which decides what the next one gives away
+    hints: usize,
+    /// The hints given for the whole puzzle
+    hints_used: usize,
+    /// How each move went, in the order they were made
+    tries: Vec<Try>,
+    solved: bool,
+    table: Table,
+}
+
+// Reads every puzzle in a puzzle file, stopping at the first line that isn't a puzzle that can be
+// solved. The line is counted from 1.
+pub fn parse(text: &str) -> Result<Vec<Puzzle>, (usize, Problem)> {
+    let mut table = Table::with_megabytes(TABLE_MEGABYTES);
+    text.lines().enumerate().filter(|&(_, line)| {
+        let line = line.trim();
+        !line.is_empty() && !line.starts_with('#')
+    }).map(|(i, line)| parse_puzzle(line.trim(), &mut table).map_err(|problem| (i + 1, problem)))
+        .collect()
+}
+
+fn parse_puzzle(line: &str, table: &mut Table) -> Result<Puzzle, Problem> {
+    let mut fields = line.split_whitespace();
+    if fields.next() != Some("puzzle") {
+        return Err(Problem::UnknownRecord);
+    }
+
+    let (mut first, mut goal, mut moves, mut notations) = (Piece::X, None, None, None);
+    let (mut level, mut theme) = (None, None);
+    let invalid = |field, value: &str| Problem::InvalidField {field, value: value.to_string()};
+    for field in fields {
+        let mut parts = field.splitn(2, '=');
+        match (parts.next(), parts.next()) {
+            (Some("first"), Some(value)) => first = match value {
+                "x" => Piece::X,
+                "o" => Piece::O,
+                _ => return Err(invalid("first", value)),
+            },
+            (Some("goal"), Some(value)) => goal = Some(match value {
+                "win" => Goal::Win,
+                "draw" => Goal::Draw,
+                _ => return Err(invalid("goal", value)),
+            }),
+            (Some("in"), Some(value)) => moves = Some(match value.parse() {
+                Ok(moves) if moves > 0 => moves,
+                _ => return Err(invalid("in", value)),
+            }),
+            (Some("moves"), Some(value)) => notations = Some(value),
+            (Some("level"), Some(value)) => {
+                level = Some(Level::from_name(value).ok_or_else(|| invalid("level", value))?)
+            },
+            (Some("theme"), Some(value)) => {
+                theme = Some(Idea::from_name(value).ok_or_else(|| invalid("theme", value))?)
+            },
+            // Fields from newer versions of the format are skipped
+            _ => {},
+        }
+    }
+
+    let goal = goal.ok_or(Problem::MissingField("goal"))?;
+    let moves = moves.ok_or(Problem::MissingField("in"))?;
+    let mut game = Game::starting_with(first);
+    let notations = notations.ok_or(Problem::MissingField("moves"))?;
+    for (i, notation) in notations.split(',').filter(|m| !m.is_empty()).enumerate() {
+        let illegal = || Problem::IllegalMove {number: i + 1, notation: notation.to_string()};
+        let (row, col) = parse_move(notation).map_err(|_| illegal())?;
+        game.make_move(row, col).map_err(|_| illegal())?;
+    }
+
+    if !reachable(&game, goal, moves, table) {
+        return Err(Problem::NoSolution);
+    }
+    Ok(Puzzle {game, goal, moves, level, theme})
+}
+
+// Writes a puzzle as a line of a puzzle file, without the line break
+pub fn write(puzzle: &Puzzle) -> String {
+    let mut line = "puzzle".to_string();
+    if let Some(level) = puzzle.level {
+        line.push_str(&format!(" level={}", level.name()));
+    }
+    if let Some(theme) = puzzle.theme {
+        line.push_str(&format!(" theme={}", theme.name()));
+    }
+    let goal = match puzzle.goal {
+        Goal::Win => "win",
+        Goal::Draw => "draw",
+    };
+    line.push_str(&format!(" goal={} in={}", goal, puzzle.moves));
+    if puzzle.game.first_piece() == Piece::O {
+        line.push_str(" first=o");
+    }
+    // Puzzle files are shared, so they are always written in the same style
+    let moves: Vec<_> = puzzle.game.moves().iter()
+        .map(|(row, col)| CoordinateStyle::Letter.write(row, col)).collect();
+    line.push_str(&format!(" moves={}", moves.join(",")));
+    line
+}
+
+impl Attempt {
+    pub fn new(puzzle: &Puzzle) -> Self {
+        Self {
+            game: puzzle.game.clone(),
+            goal: puzzle.goal,
+            moves_left: puzzle.moves,
+            hints: 0,
+            hints_used: 0,
+            tries: Vec::new(),
+            solved: false,
+            table: Table::with_megabytes(TABLE_MEGABYTES),
+        }
+    }
+
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    pub fn hints_used(&self) -> usize {
+        self.hints_used
+    }
+
+    pub fn tries(&self) -> &[Try] {
+        &self.tries
+    }
+
+    pub fn is_solved(&self) -> bool {
+        self.solved
+    }
+
+    // Makes the player's move if it reaches the goal, and then answers it
+    pub fn play(&mut self, row: usize, col: usize, rng: &mut Rng) -> Result<Progress, MoveError> {
+        let mut next = self.game.clone();
+        next.make_move(row, col)?;
+        if !self.solves(&next) {
+            self.tries.push(Try::Wrong);
+            return Ok(Progress::Wrong);
+        }
+
+        self.tries.push(if self.hints == 0 { Try::Right } else { Try::Hinted });
+        self.game = next;
+        self.moves_left -= 1;
+        self.hints = 0;
+        // A win puzzle is only over once the player wins, but a draw puzzle is over once the
+        // player has made all of their moves, or once the game ends
+        self.solved = match self.goal {
+            Goal::Win => self.game.is_finished(),
+            Goal::Draw => self.game.is_finished() || self.moves_left == 0,
+        };
+        if self.solved {
+            return Ok(Progress::Right(None));
+        }
+
+        let defenses = solve::best_moves(&self.game, &mut self.table);
+        let (row, col) = rng.choose(&defenses).expect("an unfinished game has an empty tile");
+        self.game.make_move(row, col).expect("the solver should only choose empty tiles");
+        // The other player can't win, but their move can fill the last tile
+        self.solved = self.game.is_finished();
+        Ok(Progress::Right(Some((row, col))))
+    }
+
+    // The next hint for the position. The first hint says what kind of move to look for, the
+    // second which row it is in, and every hint after that gives the move away.
+    pub fn hint(&mut self) -> Hint {
+        let (row, col) = *self.solutions().first().expect("an unsolved puzzle has a solution");
+        let hint = match self.hints {
+            0 => Hint::Idea(idea(&self.game, row, col)),
+            1 => Hint::Row(row),
+            _ => Hint::Move(row, col),
+        };
+        self.hints += 1;
+        self.hints_used += 1;
+        hint
+    }
+
+    // Every move that reaches the goal from the position as it is now, in the order of the tiles
+    pub fn solutions(&mut self) -> Vec<(usize, usize)> {
+        let game = self.game.clone();
+        solve::empty_tiles(&game).into_iter().filter(|&(row, col)| {
+            let mut next = game.clone();
+            next.make_move(row, col).expect("empty tiles are always valid moves");
+            self.solves(&next)
+        }).collect()
+    }
+
+    // Whether the player's move, which led to `next`, still reaches the goal in time
+    fn solves(&mut self, next: &Game) -> bool {
+        let player = self.game.current_piece();
+        match self.goal {
+            // Every answer has to leave the player a win in the moves they have left
+            Goal::Win => next.winner() == Some(winner(player)) || (self.moves_left > 1
+                && solve::empty_tiles(next).into_iter().all(|(row, col)| {
+                    let mut answered = next.clone();
+                    answered.make_move(row, col).expect("empty tiles are always valid moves");
+                    solve::moves_to_win(&answered, &mut self.table)
+                        .is_some_and(|moves| moves < self.moves_left)
+                })),
+            Goal::Draw => solve::perfect_result(next, &mut self.table) != winner(player.other()),
+        }
+    }
+}
+
+// Whether the goal can be reached from the position in the given number of moves
+fn reachable(game: &Game, goal: Goal, moves: usize, table: &mut Table) -> bool {
+    if game.is_finished() {
+        return false;
+    }
+    match goal {
+        Goal::Win => solve::moves_to_win(game, table).is_some_and(|needed| needed <= moves),
+        Goal::Draw => {
+            solve::perfect_result(game, table) != winner(game.current_piece().other())
+        },
+    }
+}
+
+// The kind of move that playing at (row, col) is, for the player whose turn it is
+pub fn idea(game: &Game, row: usize, col: usize) -> Idea {
+    let piece = game.current_piece();
+    if game.winning_moves(piece).contains(&(row, col)) {
+        return Idea::Win;
+    }
+    if game.winning_moves(piece.other()).contains(&(row, col)) {
+        return Idea::Block;
+    }
+    let mut after = game.clone();
+    after.make_move(row, col).expect("hints are always for empty tiles");
+    match after.winning_moves(piece).len() {
+        0 => Idea::Quiet,
+        1 => Idea::Threat,
+        _ => Idea::Fork,
+    }
+}
+
+fn winner(piece: Piece) -> Winner {
+    match piece {
+        Piece::X => Winner::X,
+        Piece::O => Winner::O,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn puzzle(line: &str) -> Puzzle {
+        parse(line).unwrap().remove(0)
+    }
+
+    #[test]
+    fn bundled_puzzles_can_be_solved() {
+        let puzzles = parse(BUNDLED).unwrap();
+        assert!(puzzles.len() >= 10);
+        for puzzle in &puzzles {
+            let mut attempt = Attempt::new(puzzle);
+            // Every puzzle has a move that goes wrong, or there would be nothing to find
+            let tiles = solve::empty_tiles(&puzzle.game).len();
+            let moves: Vec<_> = puzzle.game.moves().iter().collect();
+            assert!(attempt.solutions().len() < tiles, "{:?}", moves);
+            let mut rng = Rng::new(1);
+            // Following the last hint every time always solves the puzzle
+            while !attempt.is_solved() {
+                let (row, col) = loop {
+                    if let Hint::Move(row, col) = attempt.hint() {
+                        break (row, col);
+                    }
+                };
+                assert_ne!(attempt.play(row, col, &mut rng).unwrap(), Progress::Wrong);
+            }
+        }
+    }
+
+    #[test]
+    fn win_in_two() {
+        // x has to block at 3A, which also makes two threats at once
+        let mut attempt = Attempt::new(&puzzle("puzzle goal=win in=2 moves=1A,2B,3C,1C"));
+        assert_eq!(attempt.solutions(), [(2, 0)]);
+        assert_eq!(attempt.hint(), Hint::Idea(Idea::Block));
+        assert_eq!(attempt.hint(), Hint::Row(2));
+        assert_eq!(attempt.hint(), Hint::Move(2, 0));
+
+        let mut rng = Rng::new(1);
+        assert_eq!(attempt.play(2, 1, &mut rng).unwrap(), Progress::Wrong);
+        assert_eq!(attempt.game().moves().len(), 4);
+        assert!(matches!(attempt.play(2, 0, &mut rng), Ok(Progress::Right(Some(_)))));
+        let (row, col) = attempt.solutions()[0];
+        assert_eq!(attempt.play(row, col, &mut rng).unwrap(), Progress::Right(None));
+        assert!(attempt.is_solved());
+        assert_eq!(attempt.game().winner(), Some(Winner::X));
+        assert_eq!(attempt.hints_used(), 3);
+        assert_eq!(attempt.tries(), [Try::Wrong, Try::Hinted, Try::Right]);
+    }
+
+    #[test]
+    fn hold_the_draw() {
+        // Against a corner, only the center draws
+        let mut attempt = Attempt::new(&puzzle("puzzle goal=draw in=1 moves=1A"));
+        assert_eq!(attempt.solutions(), [(1, 1)]);
+        let mut rng = Rng::new(1);
+        assert_eq!(attempt.play(0, 1, &mut rng).unwrap(), Progress::Wrong);
+        assert_eq!(attempt.play(1, 1, &mut rng).unwrap(), Progress::Right(None));
+        assert!(attempt.is_solved());
+    }
+
+    #[test]
+    fn write_and_read_again() {
+        let line = "puzzle level=medium theme=block goal=win in=2 first=o moves=3A,2B,1C,3C";
+        let read = puzzle(line);
+        assert_eq!(read.level, Some(Level::Medium));
+        assert_eq!(read.theme, Some(Idea::Block));
+        assert_eq!(write(&read), line);
+        assert_eq!(write(&puzzle("puzzle goal=draw in=1 moves=1A")),
+            "puzzle goal=draw in=1 moves=1A");
+    }
+
+    #[test]
+    fn invalid_puzzles() {
+        assert_eq!(parse("# nothing here\n\n").unwrap().len(), 0);
+        assert_eq!(parse("game first=x").unwrap_err(), (1, Problem::UnknownRecord));
+        assert_eq!(parse("\npuzzle in=1 moves=1A").unwrap_err(),
+            (2, Problem::MissingField("goal")));
+        assert_eq!(parse("puzzle goal=lose in=1 moves=").unwrap_err(),
+            (1, Problem::InvalidField {field: "goal", value: "lose".to_string()}));
+        assert_eq!(parse("puzzle goal=win in=0 moves=").unwrap_err(),
+            (1, Problem::InvalidField {field: "in", value: "0".to_string()}));
+        assert_eq!(parse("puzzle level=tricky goal=win in=1 moves=").unwrap_err(),
+            (1, Problem::InvalidField {field: "level", value: "tricky".to_string()}));
+        assert_eq!(parse("puzzle goal=win in=1 moves=1A,1A").unwrap_err(),
+            (1, Problem::IllegalMove {number: 2, notation: "1A".to_string()}));
+        // Nobody can force a win from the empty board
+        assert_eq!(parse("puzzle goal=win in=5 moves=").unwrap_err(), (1, Problem::NoSolution));
+        // x needs three moves to win here, not two
+        assert!(parse("puzzle goal=win in=3 moves=1A,1B").is_ok());
+        assert_eq!(parse("puzzle goal=win in=2 moves=1A,1B").unwrap_err(),
+            (1, Problem::NoSolution));
+    }
+}
@@ -0,0 +1,103 @@
+// QR codes of game codes and links (see share.rs), for the `export-qr` command. Scanning one with
+// a phone is quicker than typing the code, which helps when a whole room wants the same position.
+// The `qrcode` crate works out which squares ("modules") are dark, and we draw them either in the
+// terminal or on a canvas that is saved as a PNG image.
+//
+// In the terminal, each character shows two modules, one above the other, using the half block
+// characters. That keeps the code roughly square, since characters are about twice as tall as they
+// are wide. Most terminals draw light text on a dark background, so the light modules are the ones
+// drawn with blocks, and the background shows through for the dark ones.
+
+use qrcode::{self, QrCode};
+
+use raster::Canvas;
+use theme::Color;
+
+// The number of light modules around the code. Scanners need this "quiet zone" to find the edges
+// of the code, and 4 is what the QR code standard asks for.
+const QUIET_ZONE: usize = 4;
+const DARK: Color = [0, 0, 0];
+const LIGHT: Color = [255, 255, 255];
+
+// Which modules of the QR code of `text` are dark, row by row, with the quiet zone around them
+fn modules(text: &str) -> Vec<Vec<bool>> {
+    // Game codes and links are far shorter than the most that a QR code can hold
+    let code = QrCode::new(text).expect("text should fit in a QR code");
+    let size = code.width() + QUIET_ZONE * 2;
+    (0..size).map(|y| {
+        (0..size).map(|x| {
+            let inside = |i: usize| i >= QUIET_ZONE && i < QUIET_ZONE + code.width();
+            inside(x) && inside(y) && code[(x - QUIET_ZONE, y - QUIET_ZONE)] == qrcode::Color::Dark
+        }).collect()
+    }).collect()
+}
+
+// The QR code of `text` drawn with block characters, one line per two rows of modules
+pub fn to_text(text: &str) -> Vec<String> {
+    let modules = modules(text);
+    modules.chunks(2).map(|rows| {
+        // An odd number of rows leaves the bottom half of the last line empty
+        let below = rows.get(1);
+        rows[0].iter().enumerate().map(|(x, &top_dark)| {
+            let bottom_dark = below.is_none_or(|row| row[x]);
+            match (top_dark, bottom_dark) {
+                (false, false) => '█',
+                (false, true) => '▀',
+                (true, false) => '▄',
+                (true, true) => ' ',
+            }
+        }).collect()
+    }).collect()
+}
+
+// The QR code of `text` as a PNG image that is (about) `size` pixels wide and tall. Every module
+// takes the same whole number of pixels so that its edges stay sharp, and whatever is left over
+// goes to the quiet zone.
+pub fn to_png(text: &str, size: u32) -> Vec<u8> {
+    let modules = modules(text);
+    let count = modules.len() as u32;
+    let module = (size / count).max(1);
+    let size = size.max(module * count);
+    let margin = (size - module * count) / 2;
+
+    let mut canvas = Canvas::new(size, size, LIGHT);
+    for (y, row) in modules.iter().enumerate() {
+        for (x, &dark) in row.iter().enumerate() {
+            if dark {
+                let corner = (margin + x as u32 * module, margin + y as u32 * module);
+                canvas.rect(corner, (module, module), DARK);
+            }
+        }
+    }
+    canvas.to_png()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quiet_zone() {
+        let modules = modules("CAKQS");
+        // Version 1 codes are 21 modules wide
+        assert_eq!(modules.len(), 21 + QUIET_ZONE * 2);
+        assert!(modules[..QUIET_ZONE].iter().flatten().all(|&dark| !dark));
+        // Every code starts with the dark corner of a finder pattern
+        assert!(modules[QUIET_ZONE][QUIET_ZONE]);
+    }
+
+    #[test]
+    fn text() {
+        let lines = to_text("CAKQS");
+        // Two rows of modules per line, rounding up
+        assert_eq!(lines.len(), (21 + QUIET_ZONE * 2).div_ceil(2));
+        assert!(lines.iter().all(|line| line.chars().count() == 21 + QUIET_ZONE * 2));
+        assert!(lines[0].chars().all(|c| c == '█'));
+    }
+
+    #[test]
+    fn png() {
+        let png = to_png("CAKQS", 100);
+        assert_eq!(&png[1..4], b"PNG");
+    }
+}
@@ -0,0 +1,130 @@
+// A small random number generator. The `rand` crate is the usual choice in Rust, but the computer
+// players only need to pick between a handful of moves, so a few lines of arithmetic are enough.
+// This is the "xorshift64*" generator. It is fast and its numbers look random enough for games,
+// but it must never be used for anything that needs to be secure, like passwords.
+// More info: https://en.wikipedia.org/wiki/Xorshift
+//
+// Everything random in the program comes from one shared generator, so that the whole run can be
+// played out again exactly the same way by starting it with the same seed (see `--seed`). Games
+// that are played in parallel can't share a generator without waiting on each other, so each of
+// them gets its own "stream" instead, which is worked out from the same seed.
+
+// WebAssembly in a browser has no clock to seed from (asking for the time there stops the program),
+// and the WebAssembly crates always pass in their own seeds. Leaving out the parts that need the
+// clock there makes sure they can't be used by mistake.
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::{Mutex, MutexGuard, OnceLock};
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[cfg(not(target_arch = "wasm32"))]
+static SHARED: OnceLock<Mutex<Rng>> = OnceLock::new();
+
+// A seed that is different every run, based on the current time
+#[cfg(not(target_arch = "wasm32"))]
+pub fn time_seed() -> u64 {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+    now.as_secs() ^ u64::from(now.subsec_nanos()) << 32
+}
+
+// Seeds the shared generator. This must happen before anything random is done, since the shared
+// generator seeds itself from the time the first time it is used.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn seed_shared(seed: u64) {
+    // Only fails if the generator was already seeded or used, which can't happen since main()
+    // calls this before anything else
+    if SHARED.set(Mutex::new(Rng::new(seed))).is_err() {
+        unreachable!("the shared generator should only be seeded once");
+    }
+}
+
+// The generator shared by the whole program. The lock is released when the value returned here
+// goes away, so it is best used right away, like `engine::choose_move(&game, d, &mut shared())`.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn shared() -> MutexGuard<'static, Rng> {
+    SHARED.get_or_init(|| Mutex::new(Rng::new(time_seed())))
+        .lock().expect("random number generator lock was poisoned")
+}
+
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    // The same seed always produces the same sequence of numbers
+    pub fn new(seed: u64) -> Self {
+        // The state must never be zero, since xorshift would then produce zeros forever
+        Self {state: if seed == 0 { 0x9E37_79B9_7F4A_7C15 } else { seed }}
+    }
+
+    // The generator for the `index`th stream of a seed, e.g. for the `index`th of many games that
+    // are played in parallel. The numbers only depend on the seed and the index, not on which
+    // thread ends up using the generator, so the games come out the same every time.
+    pub fn stream(seed: u64, index: u64) -> Self {
+        // Nearby seeds and indexes would give similar states, so the two are mixed together with
+        // the "splitmix64" finalizer, which makes every bit of the state depend on every bit of
+        // both of them.
+        // More info: https://prng.di.unimi.it/splitmix64.c
+        let mut z = seed.wrapping_add(index.wrapping_add(1).wrapping_mul(0x9E37_79B9_7F4A_7C15));
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        Self::new(z ^ (z >> 31))
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        // `wrapping_mul` lets the multiplication overflow instead of panicking
+        self.state.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    // Returns a number from 0 up to (but not including) `n`
+    pub fn below(&mut self, n: usize) -> usize {
+        assert!(n > 0, "cannot pick a number below zero");
+        (self.next_u64() % n as u64) as usize
+    }
+
+    // Picks one of the items at random, or returns None if there aren't any
+    pub fn choose<T: Copy>(&mut self, items: &[T]) -> Option<T> {
+        if items.is_empty() {
+            None
+        }
+        else {
+            Some(items[self.below(items.len())])
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_same_numbers() {
+        let mut a = Rng::new(42);
+        let mut b = Rng::new(42);
+        for _ in 0..10 {
+            assert_eq!(a.next_u64(), b.next_u64());
+        }
+    }
+
+    #[test]
+    fn streams_are_different() {
+        let mut first = Rng::stream(42, 0);
+        let mut second = Rng::stream(42, 1);
+        assert_eq!(first.next_u64(), Rng::stream(42, 0).next_u64());
+        assert_ne!(first.next_u64(), second.next_u64());
+    }
+
+    #[test]
+    fn below_stays_in_range() {
+        let mut rng = Rng::new(0);
+        for _ in 0..100 {
+            assert!(rng.below(3) < 3);
+        }
+        assert_eq!(rng.choose::<u8>(&[]), None);
+        assert_eq!(rng.choose(&[7]), Some(7));
+    }
+}
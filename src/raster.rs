@@ -0,0 +1,204 @@
+// A canvas is a grid of pixels that shapes can be painted onto, which is how we turn the board into
+// a PNG image. Libraries like `tiny-skia` can paint any shape, but the board only needs lines and
+// circles, which are simple enough to paint ourselves.
+//
+// Each pixel is painted based on how far its center is from the shape. Pixels right on the edge
+// of a shape are only partly covered by it, so we blend their color with what was there before.
+// This "anti-aliasing" keeps the edges smooth instead of jagged.
+
+use std::time::Duration;
+
+use gif;
+use png;
+
+use theme::Color;
+
+pub struct Canvas {
+    width: u32,
+    height: u32,
+    // The pixels are stored one row after another, starting from the top left corner
+    pixels: Vec<Color>,
+}
+
+impl Canvas {
+    // Creates a canvas filled with the background color
+    pub fn new(width: u32, height: u32, background: Color) -> Self {
+        Self {width, height, pixels: vec![background; (width * height) as usize]}
+    }
+
+    // Paints a line with round ends. Everything is measured in pixels. An opacity below 1 lets
+    // what is underneath the line show through it.
+    pub fn line(&mut self, from: (f64, f64), to: (f64, f64), width: f64, color: Color,
+        opacity: f64) {
+
+        let bounds = (from.0.min(to.0), from.1.min(to.1), from.0.max(to.0), from.1.max(to.1));
+        self.paint(bounds, width / 2.0, color, opacity, |point| segment_distance(point, from, to));
+    }
+
+    // Paints the outline of a circle
+    pub fn ring(&mut self, center: (f64, f64), radius: f64, width: f64, color: Color) {
+        let bounds = (center.0 - radius, center.1 - radius, center.0 + radius, center.1 + radius);
+        self.paint(bounds, width / 2.0, color, 1.0, |(x, y)| {
+            // The distance to the outline is how far we are from the center compared to the radius
+            ((x - center.0).hypot(y - center.1) - radius).abs()
+        });
+    }
+
+    // Fills a rectangle that starts and ends on whole pixels, like a square of a QR code. There
+    // are no partly covered pixels, so nothing needs to be blended.
+    pub fn rect(&mut self, (left, top): (u32, u32), (width, height): (u32, u32), color: Color) {
+        for y in top..(top + height).min(self.height) {
+            for x in left..(left + width).min(self.width) {
+                self.pixels[(y * self.width + x) as usize] = color;
+            }
+        }
+    }
+
+    // Paints every pixel within `reach` of a shape. `distance` tells us how far a point is from
+    // the middle of the shape's outline, and `bounds` is the box (left, top, right, bottom) that
+    // the outline fits in.
+    fn paint<F>(&mut self, bounds: (f64, f64, f64, f64), reach: f64, color: Color, opacity: f64,
+        distance: F) where F: Fn((f64, f64)) -> f64 {
+
+        // Only the pixels near the shape can be painted, so we skip the rest of the canvas.
+        // `max(0.0)` and `min` keep us from going past the edges of the canvas.
+        let left = (bounds.0 - reach - 1.0).max(0.0) as u32;
+        let top = (bounds.1 - reach - 1.0).max(0.0) as u32;
+        let right = ((bounds.2 + reach + 1.0).max(0.0) as u32).min(self.width);
+        let bottom = ((bounds.3 + reach + 1.0).max(0.0) as u32).min(self.height);
+
+        for y in top..bottom {
+            for x in left..right {
+                // We measure from the center of the pixel. A pixel is fully covered when its
+                // center is at least half a pixel inside the shape.
+                let center = (f64::from(x) + 0.5, f64::from(y) + 0.5);
+                let coverage = (reach - distance(center) + 0.5).clamp(0.0, 1.0) * opacity;
+                if coverage > 0.0 {
+                    let pixel = &mut self.pixels[(y * self.width + x) as usize];
+                    *pixel = blend(*pixel, color, coverage);
+                }
+            }
+        }
+    }
+
+    // The red, green and blue parts of every pixel one after another, which is how image
+    // formats expect to be given the pixels
+    fn rgb(&self) -> Vec<u8> {
+        self.pixels.iter().flat_map(|pixel| pixel.iter().cloned()).collect()
+    }
+
+    // Encodes the canvas as a PNG file
+    pub fn to_png(&self) -> Vec<u8> {
+        let data = self.rgb();
+
+        let mut png = Vec::new();
+        {
+            // This block makes sure the encoder is done with `png` before we return it
+            let mut encoder = png::Encoder::new(&mut png, self.width, self.height);
+            encoder.set_color(png::ColorType::Rgb);
+            encoder.set_depth(png::BitDepth::Eight);
+            // Writing to a Vec can't fail, so any error here would be a mistake in our code
+            let mut writer = encoder.write_header().expect("PNG header should be valid");
+            writer.write_image_data(&data).expect("PNG data should match the image size");
+        }
+        png
+    }
+
+    // The color of the pixel at (x, y)
+    #[cfg(test)]
+    fn pixel(&self, x: u32, y: u32) -> Color {
+        self.pixels[(y * self.width + x) as usize]
+    }
+}
+
+// Encodes the canvases as the frames of an animated GIF that loops forever. Each frame is shown
+// for the time at the same index in `delays`. Every frame must be the same size, and no bigger
+// than 65535 pixels across, since that is the largest size a GIF can have.
+pub fn to_gif(frames: &[Canvas], delays: &[Duration]) -> Vec<u8> {
+    let (width, height) = frames.first().map_or((0, 0), |frame| (frame.width, frame.height));
+    let (width, height) = (width as u16, height as u16);
+
+    let mut gif = Vec::new();
+    {
+        // No global palette is given since each frame comes with its own. Writing to a Vec can't
+        // fail, so any error here would be a mistake in our code.
+        let mut encoder = gif::Encoder::new(&mut gif, width, height, &[])
+            .expect("GIF header should be valid");
+        encoder.set_repeat(gif::Repeat::Infinite).expect("GIF repeat should be valid");
+        for (canvas, delay) in frames.iter().zip(delays) {
+            // A GIF frame can only use 256 different colors, so `from_rgb_speed` picks the 256
+            // colors that match the image best. Speed 10 is a good balance between how long that
+            // takes and how good the result looks.
+            let mut frame = gif::Frame::from_rgb_speed(width, height, &canvas.rgb(), 10);
+            // GIF delays are measured in hundredths of a second
+            frame.delay = (delay.as_millis() / 10).min(u128::from(u16::MAX)) as u16;
+            encoder.write_frame(&frame).expect("GIF frame should match the image size");
+        }
+    }
+    gif
+}
+
+// Mixes two colors. An amount of 0 gives `under`, 1 gives `over`, and anything in between gives
+// a color part way between them.
+fn blend(under: Color, over: Color, amount: f64) -> Color {
+    let mix = |a: u8, b: u8| (f64::from(a) + (f64::from(b) - f64::from(a)) * amount).round() as u8;
+    [mix(under[0], over[0]), mix(under[1], over[1]), mix(under[2], over[2])]
+}
+
+// The distance from a point to the closest point on the line segment from `a` to `b`
+fn segment_distance(point: (f64, f64), a: (f64, f64), b: (f64, f64)) -> f64 {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let length_squared = dx * dx + dy * dy;
+    // How far along the segment the closest point is, from 0 (at `a`) to 1 (at `b`)
+    let t = if length_squared == 0.0 {
+        0.0
+    }
+    else {
+        (((point.0 - a.0) * dx + (point.1 - a.1) * dy) / length_squared).clamp(0.0, 1.0)
+    };
+    (point.0 - (a.0 + t * dx)).hypot(point.1 - (a.1 + t * dy))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WHITE: Color = [255, 255, 255];
+    const BLACK: Color = [0, 0, 0];
+
+    #[test]
+    fn paints_near_the_shapes_only() {
+        let mut canvas = Canvas::new(20, 20, WHITE);
+        canvas.line((2.0, 10.0), (18.0, 10.0), 2.0, BLACK, 1.0);
+        assert_eq!(canvas.pixel(10, 10), BLACK);
+        assert_eq!(canvas.pixel(10, 5), WHITE);
+
+        canvas.ring((10.5, 10.5), 6.0, 2.0, BLACK);
+        assert_eq!(canvas.pixel(10, 4), BLACK);
+        assert_eq!(canvas.pixel(13, 13), WHITE);
+    }
+
+    #[test]
+    fn png_signature() {
+        let png = Canvas::new(4, 4, WHITE).to_png();
+        // Every PNG file starts with the same eight bytes
+        assert_eq!(&png[..8], &[0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n']);
+    }
+
+    #[test]
+    fn gif_frames() {
+        let frames = [Canvas::new(4, 4, WHITE), Canvas::new(4, 4, BLACK)];
+        let delay = Duration::from_millis(500);
+        let gif = to_gif(&frames, &[delay, delay]);
+        assert_eq!(&gif[..6], b"GIF89a");
+
+        // Reading the GIF back should give us the same frames and timing
+        let mut decoder = gif::DecodeOptions::new().read_info(&gif[..]).unwrap();
+        let mut count = 0;
+        while let Some(frame) = decoder.read_next_frame().unwrap() {
+            assert_eq!(frame.delay, 50);
+            count += 1;
+        }
+        assert_eq!(count, 2);
+    }
+}
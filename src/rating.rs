@@ -0,0 +1,123 @@
+// Ratings estimate how strong each named player is, using the Elo rating system from chess.
+// Everyone starts at the same rating. After each game, the winner takes some points from the
+// loser: a lot of points if nobody expected them to win, and only a few if they were expected to.
+// A tie moves the ratings a little closer together.
+// More info: https://en.wikipedia.org/wiki/Elo_rating_system
+//
+// Just like the stats, ratings are worked out from the games in the archive every time, so they
+// can always be checked (or recomputed with a different formula) from the saved games.
+
+use std::collections::BTreeMap;
+
+use archive::ArchivedGame;
+use game::{Piece, Winner};
+
+// The rating of a player who hasn't played any rated games yet
+pub const INITIAL_RATING: f64 = 1200.0;
+// The most points that a single game can move a rating by. Chess federations use something
+// between 10 and 40. A bigger number lets new players reach their real rating faster.
+const K_FACTOR: f64 = 32.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rating {
+    pub rating: f64,
+    /// The number of rated games that the rating is based on
+    pub games: u32,
+}
+
+impl Default for Rating {
+    fn default() -> Self {
+        Self {rating: INITIAL_RATING, games: 0}
+    }
+}
+
+// Returns the new ratings of two players after a game between them. `score` is how the game went
+// for the first player: 1 for a win, 0.5 for a tie and 0 for a loss.
+pub fn update(a: f64, b: f64, score: f64) -> (f64, f64) {
+    let change = K_FACTOR * (score - expected_score(a - b));
+    // Whatever one player gains, the other loses
+    (a + change, b - change)
+}
+
+// The score that a player rated `difference` points higher than their opponent is expected to
+// get per game. A player rated 400 points higher is expected to score about 0.9.
+pub fn expected_score(difference: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-difference / 400.0))
+}
+
+// The opposite of `expected_score`: how many points higher a player who scores `score` per game
+// must be rated than their opponent
+pub fn elo_difference(score: f64) -> f64 {
+    -400.0 * (1.0 / score - 1.0).log10()
+}
+
+// Works out the rating of every named player by going through the games in the order they were
+// played. Only games between two named players are rated, since a rating only means something
+// when we know who it was earned against. The players are sorted by name.
+pub fn compute(games: &[ArchivedGame]) -> BTreeMap<String, Rating> {
+    let mut ratings: BTreeMap<String, Rating> = BTreeMap::new();
+    for archived in games {
+        let names = (&archived.players.profile(Piece::X).name,
+            &archived.players.profile(Piece::O).name);
+        let (x, o) = match names {
+            // Someone playing against themselves can't win or lose any points
+            (Some(x), Some(o)) if x != o => (x, o),
+            _ => continue,
+        };
+        // A player who ran out of time or resigned lost the game, which is exactly what the
+        // winner says, so forfeits need no special treatment here
+        let score = match archived.game.winner() {
+            Some(Winner::X) => 1.0,
+            Some(Winner::O) => 0.0,
+            Some(Winner::Tie) => 0.5,
+            None => unreachable!("archived games should be finished"),
+        };
+
+        let (x_rating, o_rating) = (ratings.get(x).cloned().unwrap_or_default(),
+            ratings.get(o).cloned().unwrap_or_default());
+        let (new_x, new_o) = update(x_rating.rating, o_rating.rating, score);
+        ratings.insert(x.clone(), Rating {rating: new_x, games: x_rating.games + 1});
+        ratings.insert(o.clone(), Rating {rating: new_o, games: o_rating.games + 1});
+    }
+    ratings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use game::Game;
+    use player::{PlayerProfile, Players};
+
+    #[test]
+    fn rating_changes() {
+        // Between equal players, a win is worth half of the K factor and a tie changes nothing
+        assert_eq!(update(1200.0, 1200.0, 1.0), (1216.0, 1184.0));
+        assert_eq!(update(1200.0, 1200.0, 0.5), (1200.0, 1200.0));
+
+        // A tie against a stronger player is worth some points
+        let (weaker, stronger) = update(1200.0, 1400.0, 0.5);
+        assert!(weaker > 1200.0 && stronger < 1400.0);
+        // Beating a weaker player isn't worth as much as beating an equal one
+        assert!(update(1400.0, 1200.0, 1.0).0 - 1400.0 < 16.0);
+    }
+
+    #[test]
+    fn forfeits_are_losses() {
+        let mut game = Game::new();
+        game.make_move(1, 1).unwrap();
+        game.forfeit(Piece::O).unwrap();
+        let profile = |name: &str| PlayerProfile {name: Some(name.to_string()), glyph: None};
+        let rated = ArchivedGame {
+            game: game.clone(),
+            players: Players::new(profile("Ada"), profile("Grace")),
+        };
+        let unrated = ArchivedGame {
+            game,
+            players: Players::new(profile("Ada"), PlayerProfile::default()),
+        };
+
+        let ratings = compute(&[rated, unrated]);
+        assert_eq!(ratings["Ada"], Rating {rating: 1216.0, games: 1});
+        assert_eq!(ratings["Grace"], Rating {rating: 1184.0, games: 1});
+    }
+}
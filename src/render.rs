@@ -0,0 +1,1195 @@
+// A renderer decides how the game is shown to the players. Different people need the game to be
+// shown in different ways, so we define a trait that describes everything a renderer needs to do
+// and then provide a separate implementation for each style of output.
+//
+// Traits are Rust's way of describing shared behaviour. Any type that implements the Renderer
+// trait can be used wherever the program needs to show the game.
+// More info: https://doc.rust-lang.org/book/second-edition/ch10-02-traits.html
+
+use std::cell::RefCell;
+#[cfg(feature = "images")]
+use std::time::Duration;
+
+use clock::{Clocks, ClockTime};
+use coach::{self, Level};
+use error::ErrorCode;
+use forced::{self, Forced};
+use game::{Game, Piece, Tiles};
+use json;
+use locale::{Lang, Message};
+use notation::{self, CoordinateStyle, Reason};
+use player::{Players, display_width};
+use plugin::Plugin;
+#[cfg(feature = "images")]
+use raster::{self, Canvas};
+use terminal::{self, Capabilities};
+use theme::{Color, Colors, Theme};
+use tt::Table;
+
+// The kinds of game that can be played. The name of the variant is shown in the status header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Variant {
+    Classic,
+    Blindfold,
+    Tutorial,
+    Demo,
+    Puzzle,
+    Review,
+}
+
+// Everything a renderer needs to know to show a game: the board itself, but also who is playing,
+// how much time is left and what kind of game it is. Bundling all of this into one value means
+// that we can show more about the game without changing every renderer each time.
+#[derive(Debug, Clone, Copy)]
+pub struct GameView<'a> {
+    pub game: &'a Game,
+    pub players: &'a Players,
+    /// The chess clocks, if the players are using them
+    pub clocks: Option<&'a Clocks>,
+    pub variant: Variant,
+}
+
+impl<'a> GameView<'a> {
+    // A view of a game without clocks
+    pub fn new(game: &'a Game, players: &'a Players, variant: Variant) -> Self {
+        Self {game, players, clocks: None, variant}
+    }
+
+    // The line shown above the board with the move number, the players, the variant and the
+    // clocks
+    pub fn status(&self, lang: Lang) -> String {
+        // Once the game is over, there is no next move, so we show the number of the last one
+        let moves = self.game.moves().len();
+        let move_number = if self.game.is_finished() { moves } else { moves + 1 };
+        let clocks = self.clocks.map(|clocks| {
+            (ClockTime(clocks.remaining(Piece::X)), ClockTime(clocks.remaining(Piece::O)))
+        });
+        lang.text(Message::Status {
+            move_number,
+            x: &self.players.label(Piece::X),
+            o: &self.players.label(Piece::O),
+            variant: self.variant,
+            clocks,
+        })
+    }
+}
+
+// Why a move was rejected, for renderers that show more than the message itself. The message
+// already explains this to people in their own language.
+#[derive(Debug, Clone, Copy)]
+pub enum Rejection<'a> {
+    /// The text that was typed isn't a tile on the board
+    Invalid { input: &'a str, reason: &'a Reason },
+    /// The tile already had `piece` on it, which was put there by move number `since`
+    Occupied { row: usize, col: usize, piece: Piece, since: usize },
+}
+
+// The methods are given the players so that their chosen names and glyphs can be shown instead
+// of plain "x" and "o". Most messages are already translated by the time a renderer sees them,
+// so the methods with a default implementation just print that text. Renderers only need to
+// override them when they want to show something different.
+pub trait Renderer {
+    // Shows every tile on the board, along with a status header about the game
+    fn board(&self, view: &GameView);
+
+    // Draws the board when a player asks to see it with the `show` command. Only renderers that
+    // normally hide the board need to do anything different here.
+    fn show_board(&self, view: &GameView) {
+        self.board(view);
+    }
+
+    // Shows a position that is being set up with the `setup` command. The position may not be
+    // one that a game can reach yet, so there is only the tiles and who moves next. `message`
+    // says that a position is being set up.
+    fn position(&self, tiles: &Tiles, next: Piece, players: &Players, message: &str);
+
+    // Called right after a move is made so that the renderer can describe what just happened.
+    // `game` already includes the new move.
+    fn move_made(&self, game: &Game, players: &Players, row: usize, col: usize);
+
+    // Called before asking the current player for their move. `message` says whose turn it is.
+    fn turn(&self, _game: &Game, _players: &Players, message: &str) {
+        outln!("{}", message);
+    }
+
+    // Called once a game is over (or a scripted game has run out of moves). `message` announces
+    // the result.
+    fn game_over(&self, view: &GameView, message: &str) {
+        self.board(view);
+        outln!("{}", message);
+    }
+
+    // Reports something that went wrong, like a move that couldn't be made. `code` says which
+    // error it was without depending on the language (see error.rs).
+    fn error(&self, _code: ErrorCode, message: &str) {
+        eoutln!("{}", message);
+    }
+
+    // Reports a move that couldn't be made. `rejection` says exactly why, but most renderers
+    // only need the message, so by default this is just an error.
+    fn move_rejected(&self, code: ErrorCode, message: &str, _rejection: Rejection) {
+        self.error(code, message);
+    }
+
+    // Any other message for the players, like the score of a series. An empty message prints a
+    // blank line to space things out.
+    fn info(&self, message: &str) {
+        outln!("{}", message);
+    }
+
+    // Clears the screen so that the next board is drawn in the same place as the last one. This
+    // only makes sense for renderers that draw on a terminal, so by default it does nothing.
+    fn clear(&self) {}
+
+    // Called when the session ends because there was nothing left to read. People can see that
+    // for themselves, so by default it does nothing.
+    fn input_closed(&self) {}
+}
+
+// The ways the grid renderer can lay out the board
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layout {
+    /// One character between tiles, for small terminals
+    Compact,
+    /// Wider tiles with lines drawn between them, which is easier to read from a distance
+    Spacious,
+}
+
+// The number of lines that are printed along with the board on each turn (the clocks, whose turn
+// it is, the prompt and any message about the last move). The board only gets the rest.
+const LINES_AROUND_BOARD: usize = 5;
+// The number of empty lines that clear the screen when escape codes don't work and the size of the
+// terminal is unknown
+const CLEAR_LINES: usize = 50;
+
+impl Layout {
+    // Finds a layout by the name used on the command line
+    pub fn from_name(name: &str) -> Option<Self> {
+        match &name.to_lowercase()[..] {
+            "compact" => Some(Layout::Compact),
+            "spacious" => Some(Layout::Spacious),
+            _ => None,
+        }
+    }
+
+    // Picks the roomiest layout that fits in a terminal of the given size (columns, rows). When we
+    // don't know the size, the compact layout is the safest choice.
+    pub fn fit(terminal_size: Option<(usize, usize)>, board_size: usize, tile_width: usize)
+        -> Self {
+
+        // See `spacious_board` for what this layout looks like. Each tile takes up its glyph, a
+        // space on either side and a line on the right. The row numbers and the line on the left
+        // take up another 4 columns.
+        let columns = 4 + board_size * (tile_width + 3);
+        // A line of column letters, a line above every row, the bottom line and a blank line
+        let rows = 2 * board_size + 3 + LINES_AROUND_BOARD;
+        match terminal_size {
+            Some((width, height)) if width >= columns && height >= rows => Layout::Spacious,
+            _ => Layout::Compact,
+        }
+    }
+}
+
+// The default renderer which draws the board as a grid of characters
+#[derive(Debug, Clone, Copy)]
+pub struct GridRenderer {
+    lang: Lang,
+    theme: Theme,
+    /// The layout to always use, or None to pick one that fits the terminal every time the board
+    /// is drawn (so that resizing the terminal during a game works)
+    layout: Option<Layout>,
+    /// Whether the screen can be cleared with an escape code
+    ansi: bool,
+}
+
+impl GridRenderer {
+    pub fn new(lang: Lang, theme: Theme, layout: Option<Layout>) -> Self {
+        Self {lang, theme, layout, ansi: true}
+    }
+
+    // Only draws what the terminal can show (see terminal.rs)
+    pub fn with_capabilities(self, capabilities: Capabilities) -> Self {
+        Self {
+            theme: if capabilities.unicode { self.theme } else { self.theme.ascii_only() },
+            ansi: capabilities.ansi,
+            ..self
+        }
+    }
+}
+
+impl Renderer for GridRenderer {
+    // This function is used to print out the board in a human readable way
+    fn board(&self, view: &GameView) {
+        let (tiles, players) = (view.game.tiles(), view.players);
+        outln!("{}", view.status(self.lang));
+
+        // Big terminals get the easier to read layout
+        let layout = self.layout.unwrap_or_else(|| {
+            Layout::fit(terminal::size(), tiles.len(), tile_width(players, &self.theme))
+        });
+        out!("{}", grid(tiles, view.game.winning_line(), players, &self.theme, layout));
+    }
+
+    fn position(&self, tiles: &Tiles, _next: Piece, players: &Players, message: &str) {
+        outln!("{}", message);
+        let layout = self.layout.unwrap_or_else(|| {
+            Layout::fit(terminal::size(), tiles.len(), tile_width(players, &self.theme))
+        });
+        out!("{}", grid(tiles, None, players, &self.theme, layout));
+    }
+
+    fn move_made(&self, _game: &Game, _players: &Players, _row: usize, _col: usize) {
+        // The move will be visible the next time the board is drawn, so there is nothing else
+        // that we need to print here. The `_` at the start of each parameter name tells Rust
+        // that we are intentionally not using it.
+    }
+
+    fn clear(&self) {
+        // These are "ANSI escape codes" that almost every terminal understands. `\x1b[2J` erases
+        // the whole screen and `\x1b[H` moves the cursor back to the top left corner.
+        // More info: https://en.wikipedia.org/wiki/ANSI_escape_code
+        if self.ansi {
+            out!("\x1b[2J\x1b[H");
+        }
+        // Anywhere else, enough empty lines scroll everything that was there out of sight
+        else {
+            out!("{}", "\n".repeat(terminal::size().map_or(CLEAR_LINES, |(_, rows)| rows)));
+        }
+    }
+}
+
+// The rest of this section draws the board as text. Each function returns a String instead of
+// printing it, so that the tests can check exactly what the players would see (see the snapshots
+// in the tests at the bottom of this file).
+
+// The number of columns every tile takes up. If a player picked a glyph that is two columns wide
+// (like an emoji), every column is made two characters wide so that the grid still lines up. The
+// theme can ask for wider tiles, and needs room for its marks around the winning pieces.
+pub fn tile_width(players: &Players, theme: &Theme) -> usize {
+    let marks = theme.win_marks.map_or(0, |(before, after)| text_width(before) + text_width(after));
+    players.glyph_width().max(text_width(theme.empty)).max(players.glyph_width() + marks)
+        .max(theme.cell_width)
+}
+
+// Draws the tiles of the board in the given layout, followed by a blank line to space it out from
+// the prompts that follow. The pieces on the winning line, if there is one, are marked the way
+// the theme marks them.
+pub fn grid(tiles: &Tiles, winning: Option<[(usize, usize); 3]>, players: &Players, theme: &Theme,
+    layout: Layout) -> String {
+
+    let width = tile_width(players, theme);
+    let board = Board {tiles, winning, players, theme, style: notation::style()};
+    match layout {
+        Layout::Compact => compact_grid(&board, width),
+        Layout::Spacious => spacious_grid(&board, width),
+    }
+}
+
+// Puts several drawings next to each other, like the boards of a simul, each one with its title
+// above it. As many go across as fit in `columns` columns of the terminal, and the rest carry on
+// in rows below.
+pub fn side_by_side(drawings: &[(String, String)], columns: usize) -> String {
+    const GAP: usize = 4;
+    // Each drawing becomes a list of lines, as wide as its widest line
+    let blocks: Vec<(Vec<&str>, usize)> = drawings.iter().map(|(title, drawing)| {
+        let lines: Vec<&str> = Some(title.as_str()).into_iter()
+            .chain(drawing.trim_end_matches('\n').lines())
+            .collect();
+        let width = lines.iter().map(|line| line_width(line)).max().unwrap_or(0);
+        (lines, width)
+    }).collect();
+
+    let mut text = String::new();
+    let mut start = 0;
+    while start < blocks.len() {
+        // A row always gets at least one drawing, even if it is too wide for the terminal
+        let mut end = start + 1;
+        let mut width = blocks[start].1;
+        while end < blocks.len() && width + GAP + blocks[end].1 <= columns {
+            width += GAP + blocks[end].1;
+            end += 1;
+        }
+        if start > 0 {
+            text.push('\n');
+        }
+        let row = &blocks[start..end];
+        let height = row.iter().map(|(lines, _)| lines.len()).max().unwrap_or(0);
+        for i in 0..height {
+            let mut line = String::new();
+            for (lines, width) in row {
+                let part = lines.get(i).copied().unwrap_or("");
+                line.push_str(part);
+                line.push_str(&" ".repeat(width - line_width(part) + GAP));
+            }
+            text.push_str(line.trim_end());
+            text.push('\n');
+        }
+        start = end;
+    }
+    text
+}
+
+// Everything that decides what the tiles of the board look like in the terminal
+struct Board<'a> {
+    tiles: &'a Tiles,
+    winning: Option<[(usize, usize); 3]>,
+    players: &'a Players,
+    theme: &'a Theme,
+    style: CoordinateStyle,
+}
+
+impl<'a> Board<'a> {
+    // The glyph drawn on a tile. In the keypad style, the board has no labels around it, so the
+    // empty tiles show the numbers to type for them instead.
+    fn glyph(&self, row: usize, col: usize) -> String {
+        // The pattern `Some(piece)` pulls the piece out of the Option so that we can look up the
+        // glyph that its player chose. Every branch produces a String, since Rust requires a
+        // match to produce a value of the same type in every branch.
+        match self.tiles[row][col] {
+            Some(piece) => match self.theme.win_marks {
+                Some((before, after)) if self.winning.is_some_and(|line| line.contains(&(row, col)))
+                    => format!("{}{}{}", before, self.players.glyph(piece), after),
+                _ => self.players.glyph(piece).to_string(),
+            },
+            None if self.style == CoordinateStyle::Keypad => self.style.write(row, col),
+            None => self.theme.empty.to_string(),
+        }
+    }
+}
+
+// Draws the board as a small grid of characters
+fn compact_grid(board: &Board, width: usize) -> String {
+    // The result of this function will be something like the following:
+    //   A B C
+    // 1 x ▢ ▢
+    // 2 ▢ ▢ o
+    // 3 ▢ ▢ ▢
+    //
+    // The boxes represent empty tiles (unless the theme draws them differently), and x and o
+    // are placed wherever a tile is filled. The labels depend on the coordinate style (see
+    // notation.rs), and the keypad style has none at all.
+    // We build the whole board up in a String. `push_str` adds some text to the end of it.
+    let mut text = String::new();
+    // `labels` gives us the column and row labels, if the style has any
+    let labels = board.style.labels();
+
+    // The pattern `Some((columns, _))` pulls the column labels out of the Option and ignores the
+    // row labels, which are used further down
+    if let Some((columns, _)) = labels {
+        // First we add the space before the column letters
+        text.push_str("  ");
+        // Then we go through each label, e.g. "A", then "B", and then "C"
+        for column in &columns {
+            text.push_str(&format!(" {}", pad(column, width)));
+        }
+        // This adds the final newline after the row of column letters
+        text.push('\n');
+    }
+
+    // Now we add each row preceeded by its row number
+    // .iter().enumerate() goes through each row and provides a row number with each element
+    // using a tuple.
+    for (i, row) in board.tiles.iter().enumerate() {
+        // We add the row number with a space in front of it
+        text.push_str(&format!(" {}", labels.map_or(" ", |(_, rows)| rows[i])));
+        // Now we go through each tile in the row and add it, centered in its column
+        for j in 0..row.len() {
+            text.push_str(&format!(" {}", pad(&board.glyph(i, j), width)));
+        }
+        // We finish each row with a final new line
+        text.push('\n');
+    }
+
+    // Add an extra line at the end of the board to space it out from the prompts that follow
+    text.push('\n');
+    text
+}
+
+// Draws the board with lines between the tiles, like this:
+//
+//       A   B   C
+//     +---+---+---+
+//   1 | x | ▢ | ▢ |
+//     +---+---+---+
+//   2 | ▢ | ▢ | o |
+//     +---+---+---+
+//   3 | ▢ | ▢ | ▢ |
+//     +---+---+---+
+fn spacious_grid(board: &Board, width: usize) -> String {
+    // Every line between two rows is the same, so we build it once. `repeat` makes a String
+    // that contains the text it was called on the given number of times.
+    let cell_line = format!("{}+", "-".repeat(width + 2));
+    let line = format!("   +{}\n", cell_line.repeat(board.tiles[0].len()));
+
+    let labels = board.style.labels();
+    let mut text = String::new();
+    if let Some((columns, _)) = labels {
+        text.push_str("   ");
+        for column in &columns {
+            text.push_str(&format!("  {} ", pad(column, width)));
+        }
+        text.push('\n');
+    }
+    text.push_str(&line);
+
+    for (i, row) in board.tiles.iter().enumerate() {
+        text.push_str(&format!(" {} |", labels.map_or(" ", |(_, rows)| rows[i])));
+        for j in 0..row.len() {
+            text.push_str(&format!(" {} |", pad(&board.glyph(i, j), width)));
+        }
+        text.push('\n');
+        text.push_str(&line);
+    }
+    text.push('\n');
+    text
+}
+
+// Describes the board one row at a time, e.g. "Row 1: x, empty, o.", followed by a blank line.
+// We use the names of the players since screen readers don't always know how to pronounce a
+// glyph.
+pub fn spoken_rows(tiles: &Tiles, players: &Players, lang: Lang) -> String {
+    let mut text = String::new();
+    for (row, row_tiles) in tiles.iter().enumerate() {
+        let names: Vec<_> = row_tiles.iter()
+            .map(|tile| tile.map(|piece| players.name(piece)))
+            .collect();
+        text.push_str(&lang.text(Message::RowDescription {row, tiles: &names}));
+        text.push('\n');
+    }
+    text.push('\n');
+    text
+}
+
+// A renderer for people using a screen reader. Screen readers read the terminal out loud one line
+// at a time, so a grid of symbols is very hard to follow. Instead, this renderer describes the
+// board using sentences and announces each move and any threats that it creates.
+#[derive(Debug, Clone, Copy)]
+pub struct SpokenRenderer {
+    lang: Lang,
+}
+
+impl SpokenRenderer {
+    pub fn new(lang: Lang) -> Self {
+        Self {lang}
+    }
+}
+
+impl Renderer for SpokenRenderer {
+    fn board(&self, view: &GameView) {
+        outln!("{}", view.status(self.lang));
+        // Each row is read out as its own sentence
+        out!("{}", spoken_rows(view.game.tiles(), view.players, self.lang));
+    }
+
+    fn position(&self, tiles: &Tiles, _next: Piece, players: &Players, message: &str) {
+        outln!("{}", message);
+        out!("{}", spoken_rows(tiles, players, self.lang));
+    }
+
+    fn move_made(&self, game: &Game, players: &Players, row: usize, col: usize) {
+        // The piece that was just placed is whatever is now on that tile
+        let piece = game.tiles()[row][col].expect("a piece should have just been placed here");
+        outln!("{}", self.lang.text(Message::MovePlayed {player: players.name(piece), row, col}));
+
+        // Announce the threats of the player that just moved first since those are the ones the
+        // next player needs to block
+        for &threatening in &[piece, piece.other()] {
+            for (row, col) in game.winning_moves(threatening) {
+                let player = players.name(threatening);
+                outln!("{}", self.lang.text(Message::Threat {player, row, col}));
+            }
+        }
+    }
+}
+
+// Blindfold mode hides the board to train the players' memory. This renderer wraps another one,
+// suppressing the board and announcing each move in words instead. Everything else, including
+// the board shown with the `show` command and at the end of the game, comes from the wrapped
+// renderer.
+pub struct BlindfoldRenderer {
+    inner: Box<dyn Renderer>,
+    lang: Lang,
+}
+
+impl BlindfoldRenderer {
+    pub fn new(inner: Box<dyn Renderer>, lang: Lang) -> Self {
+        Self {inner, lang}
+    }
+}
+
+impl Renderer for BlindfoldRenderer {
+    fn board(&self, view: &GameView) {
+        // The whole point of blindfold mode is that the board isn't shown. The status header
+        // doesn't give anything away, so we still show that.
+        self.inner.info(&view.status(self.lang));
+    }
+
+    fn show_board(&self, view: &GameView) {
+        self.inner.board(view);
+    }
+
+    fn position(&self, tiles: &Tiles, next: Piece, players: &Players, message: &str) {
+        // Nobody can set up a position without seeing it, so it is always shown
+        self.inner.position(tiles, next, players, message);
+    }
+
+    fn move_made(&self, game: &Game, players: &Players, row: usize, col: usize) {
+        // We don't pass the move on to the wrapped renderer since it could give away more than
+        // the move itself (the spoken renderer also announces threats, for example)
+        let piece = game.tiles()[row][col].expect("a piece should have just been placed here");
+        outln!("{}", self.lang.text(Message::MovePlayed {player: players.name(piece), row, col}));
+    }
+
+    fn turn(&self, game: &Game, players: &Players, message: &str) {
+        self.inner.turn(game, players, message);
+    }
+
+    fn game_over(&self, view: &GameView, message: &str) {
+        // The board is revealed once the game is over so the players can check their memory
+        self.inner.game_over(view, message);
+    }
+
+    fn error(&self, code: ErrorCode, message: &str) {
+        self.inner.error(code, message);
+    }
+
+    fn move_rejected(&self, code: ErrorCode, message: &str, rejection: Rejection) {
+        self.inner.move_rejected(code, message, rejection);
+    }
+
+    fn info(&self, message: &str) {
+        self.inner.info(message);
+    }
+
+    fn clear(&self) {
+        self.inner.clear();
+    }
+
+    fn input_closed(&self) {
+        self.inner.input_closed();
+    }
+}
+
+// The coach watches the game through the renderer, since that is where every move ends up no
+// matter who made it. This renderer wraps another one and adds the coach's comment after each
+// move (see coach.rs). Everything else is left to the wrapped renderer.
+pub struct CoachRenderer {
+    inner: Box<dyn Renderer>,
+    level: Level,
+    lang: Lang,
+}
+
+impl CoachRenderer {
+    pub fn new(inner: Box<dyn Renderer>, level: Level, lang: Lang) -> Self {
+        Self {inner, level, lang}
+    }
+}
+
+impl Renderer for CoachRenderer {
+    fn board(&self, view: &GameView) {
+        self.inner.board(view);
+    }
+
+    fn show_board(&self, view: &GameView) {
+        self.inner.show_board(view);
+    }
+
+    fn position(&self, tiles: &Tiles, next: Piece, players: &Players, message: &str) {
+        self.inner.position(tiles, next, players, message);
+    }
+
+    fn move_made(&self, game: &Game, players: &Players, row: usize, col: usize) {
+        self.inner.move_made(game, players, row, col);
+        if let Some(comment) = coach::comment(game, self.level) {
+            let piece = game.tiles()[row][col].expect("a piece should have just been placed here");
+            self.inner.info(&self.lang.text(Message::Coach {
+                comment,
+                player: players.name(piece),
+                opponent: players.name(piece.other()),
+            }));
+        }
+    }
+
+    fn turn(&self, game: &Game, players: &Players, message: &str) {
+        self.inner.turn(game, players, message);
+    }
+
+    fn game_over(&self, view: &GameView, message: &str) {
+        self.inner.game_over(view, message);
+    }
+
+    fn error(&self, code: ErrorCode, message: &str) {
+        self.inner.error(code, message);
+    }
+
+    fn move_rejected(&self, code: ErrorCode, message: &str, rejection: Rejection) {
+        self.inner.move_rejected(code, message, rejection);
+    }
+
+    fn info(&self, message: &str) {
+        self.inner.info(message);
+    }
+
+    fn clear(&self) {
+        self.inner.clear();
+    }
+
+    fn input_closed(&self) {
+        self.inner.input_closed();
+    }
+}
+
+// Forced results are announced through the renderer for the same reason as the coach's comments.
+// This renderer wraps another one and announces a forced win or draw at the start of the turn of
+// each player who asked to hear about it (see forced.rs). Everything else is left to the wrapped
+// renderer.
+pub struct ForcedRenderer {
+    inner: Box<dyn Renderer>,
+    /// The players who are told
+    pieces: Vec<Piece>,
+    lang: Lang,
+    /// Kept from one turn to the next, so that the positions searched before are only looked up.
+    /// The renderer is only ever borrowed, so the table needs a RefCell to change.
+    table: RefCell<Table>,
+}
+
+impl ForcedRenderer {
+    pub fn new(inner: Box<dyn Renderer>, pieces: Vec<Piece>, lang: Lang) -> Self {
+        Self {inner, pieces, lang, table: RefCell::new(forced::table())}
+    }
+}
+
+impl Renderer for ForcedRenderer {
+    fn board(&self, view: &GameView) {
+        self.inner.board(view);
+    }
+
+    fn show_board(&self, view: &GameView) {
+        self.inner.show_board(view);
+    }
+
+    fn position(&self, tiles: &Tiles, next: Piece, players: &Players, message: &str) {
+        self.inner.position(tiles, next, players, message);
+    }
+
+    fn move_made(&self, game: &Game, players: &Players, row: usize, col: usize) {
+        self.inner.move_made(game, players, row, col);
+    }
+
+    fn turn(&self, game: &Game, players: &Players, message: &str) {
+        if self.pieces.contains(&game.current_piece()) {
+            let news = forced::news(game, &mut self.table.borrow_mut());
+            match news {
+                Some(Forced::Win {piece, moves}) => self.inner.info(&self.lang.text(
+                    Message::ForcedWin {player: players.name(piece), moves})),
+                Some(Forced::Draw) => self.inner.info(&self.lang.text(Message::ForcedDraw)),
+                None => {},
+            }
+        }
+        self.inner.turn(game, players, message);
+    }
+
+    fn game_over(&self, view: &GameView, message: &str) {
+        self.inner.game_over(view, message);
+    }
+
+    fn error(&self, code: ErrorCode, message: &str) {
+        self.inner.error(code, message);
+    }
+
+    fn move_rejected(&self, code: ErrorCode, message: &str, rejection: Rejection) {
+        self.inner.move_rejected(code, message, rejection);
+    }
+
+    fn info(&self, message: &str) {
+        self.inner.info(message);
+    }
+
+    fn clear(&self) {
+        self.inner.clear();
+    }
+
+    fn input_closed(&self) {
+        self.inner.input_closed();
+    }
+}
+
+// A renderer for programs instead of people. Every event is printed to stdout as a JSON object on
+// its own line, so a program driving the game can read one line at a time and hand it to a JSON
+// parser. Everything meant for people, like prompts, goes to stderr instead so that stdout only
+// ever contains JSON.
+//
+//     {"event":"turn","piece":"x","player":"x","board":[[null,null,null],...]}
+//     {"event":"move","piece":"x","player":"x","row":1,"col":1,"position":"2B","board":[...]}
+//     {"event":"error","code":"tile_not_empty","number":102,"message":"...","details":{...}}
+//     {"event":"result","winner":"x","forfeit":null,"moves":["2B",...],"board":[...]}
+//     {"event":"position","next":"o","board":[...]}
+//     {"event":"input_closed"}
+//
+// Rows and columns are numbered from 0, while `position` uses the same notation players type in.
+// Errors have both the name and the number of the error (see error.rs), neither of which ever
+// changes. Rejected moves also have the details of why they were rejected:
+//
+//     {"row":0,"col":2,"position":"1C","piece":"x","since":3}
+//     {"input":"1D","reason":"no_such_column","column":"D","size":3}
+//
+// The `reason` is one of `unreadable`, `no_such_row`, `no_such_column` or `no_such_key`. Only
+// those last three say which `row`, `column` or `key` isn't on the board.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JsonRenderer {
+    /// The renderer plugin that the events are sent to instead of stdout, if there is one
+    plugin: Option<&'static Plugin>,
+}
+
+impl JsonRenderer {
+    // Sends the events to a renderer plugin (see plugin.rs). Everything that would go to stderr
+    // still does.
+    pub fn to_plugin(plugin: &'static Plugin) -> Self {
+        Self {plugin: Some(plugin)}
+    }
+
+    fn send(&self, event: String) {
+        match self.plugin {
+            Some(plugin) => plugin.render(&event),
+            None => outln!("{}", event),
+        }
+    }
+}
+
+impl Renderer for JsonRenderer {
+    fn board(&self, _view: &GameView) {
+        // Every event below already includes the whole board, so we don't print it separately
+    }
+
+    fn position(&self, tiles: &Tiles, next: Piece, _players: &Players, _message: &str) {
+        self.send(format!(r#"{{"event":"position","next":{},"board":{}}}"#,
+            json::piece(Some(next)), json::tiles(tiles)));
+    }
+
+    fn move_made(&self, game: &Game, players: &Players, row: usize, col: usize) {
+        let piece = game.tiles()[row][col].expect("a piece should have just been placed here");
+        // `concat!` joins the pieces of the format string together so that it fits on the screen
+        self.send(format!(
+            concat!(r#"{{"event":"move","piece":{},"player":{},"row":{},"col":{},"#,
+                r#""position":{},"board":{}}}"#),
+            json::piece(Some(piece)), json::string(players.name(piece)), row, col,
+            json::string(&position(row, col)), json::tiles(game.tiles())));
+    }
+
+    fn turn(&self, game: &Game, players: &Players, _message: &str) {
+        let piece = game.current_piece();
+        self.send(format!(r#"{{"event":"turn","piece":{},"player":{},"board":{}}}"#,
+            json::piece(Some(piece)), json::string(players.name(piece)),
+            json::tiles(game.tiles())));
+    }
+
+    fn game_over(&self, view: &GameView, message: &str) {
+        let game = view.game;
+        let moves: Vec<_> = game.moves().iter()
+            .map(|(row, col)| json::string(&position(row, col)))
+            .collect();
+        self.send(format!(
+            concat!(r#"{{"event":"result","winner":{},"forfeit":{},"moves":[{}],"#,
+                r#""board":{},"message":{}}}"#),
+            json::winner(game.winner()), json::piece(game.forfeited_by()), moves.join(","),
+            json::tiles(game.tiles()), json::string(message)));
+    }
+
+    fn error(&self, code: ErrorCode, message: &str) {
+        self.send(format!(r#"{{"event":"error","code":{},"number":{},"message":{}}}"#,
+            json::string(code.name), code.number, json::string(message)));
+    }
+
+    fn move_rejected(&self, code: ErrorCode, message: &str, rejection: Rejection) {
+        self.send(format!(
+            r#"{{"event":"error","code":{},"number":{},"message":{},"details":{}}}"#,
+            json::string(code.name), code.number, json::string(message),
+            rejection_details(rejection)));
+    }
+
+    fn info(&self, message: &str) {
+        eoutln!("{}", message);
+    }
+
+    fn input_closed(&self) {
+        self.send(r#"{"event":"input_closed"}"#.to_string());
+    }
+}
+
+// The `details` object of a rejected move, as described above
+fn rejection_details(rejection: Rejection) -> String {
+    match rejection {
+        Rejection::Invalid {input, reason} => {
+            let (name, part) = match *reason {
+                Reason::Unreadable => ("unreadable", None),
+                Reason::NoSuchRow(ref row) => ("no_such_row", Some(("row", row))),
+                Reason::NoSuchColumn(ref col) => ("no_such_column", Some(("column", col))),
+                Reason::NoSuchKey(ref key) => ("no_such_key", Some(("key", key))),
+            };
+            // The board is always 3 tiles wide and 3 tiles tall
+            let part = part.map_or(String::new(), |(key, value)| {
+                format!(r#","{}":{},"size":3"#, key, json::string(value))
+            });
+            format!(r#"{{"input":{},"reason":{}{}}}"#, json::string(input), json::string(name),
+                part)
+        },
+        Rejection::Occupied {row, col, piece, since} => format!(
+            r#"{{"row":{},"col":{},"position":{},"piece":{},"since":{}}}"#,
+            row, col, json::string(&position(row, col)), json::piece(Some(piece)), since),
+    }
+}
+
+// Saving the board as an image works in two steps. First, `drawing` describes the board as a list
+// of shapes. Then the shapes are either written out as SVG or painted onto a grid of pixels to
+// make a PNG. That way both kinds of image always look the same.
+
+// The size of every tile in a drawing. The whole drawing is a 3x3 grid of tiles.
+const DRAWING_TILE: f64 = 100.0;
+// The last frame of an animation stays on the screen this many times as long as the others
+#[cfg(feature = "images")]
+const GIF_FINAL_PAUSE: u32 = 4;
+
+// A shape in a drawing of the board. Lines always have round ends.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Shape {
+    Line { from: (f64, f64), to: (f64, f64), width: f64, color: Color, opacity: f64 },
+    /// A circle that isn't filled in
+    Ring { center: (f64, f64), radius: f64, width: f64, color: Color },
+}
+
+// Describes the game as shapes, drawn in order. If someone won with three in a row, a line is
+// drawn through their pieces.
+pub fn drawing(game: &Game, colors: &Colors) -> Vec<Shape> {
+    // How far the pieces are drawn from the edges of their tile
+    const INSET: f64 = 22.0;
+
+    let tiles = game.tiles();
+    let size = DRAWING_TILE * tiles.len() as f64;
+    let mut shapes = Vec::new();
+
+    // The lines between the tiles. They stop a little before the edges of the image.
+    for i in 1..tiles.len() {
+        let at = i as f64 * DRAWING_TILE;
+        let grid_line = |from, to| {
+            Shape::Line {from, to, width: 4.0, color: colors.grid, opacity: 1.0}
+        };
+        shapes.push(grid_line((at, 8.0), (at, size - 8.0)));
+        shapes.push(grid_line((8.0, at), (size - 8.0, at)));
+    }
+
+    // x is drawn as two crossing lines and o as a circle
+    for (row, row_tiles) in tiles.iter().enumerate() {
+        for (col, tile) in row_tiles.iter().enumerate() {
+            let (left, top) = (col as f64 * DRAWING_TILE, row as f64 * DRAWING_TILE);
+            let (near, far) = (INSET, DRAWING_TILE - INSET);
+            match *tile {
+                Some(Piece::X) => {
+                    let stroke = |from, to| {
+                        Shape::Line {from, to, width: 10.0, color: colors.x, opacity: 1.0}
+                    };
+                    shapes.push(stroke((left + near, top + near), (left + far, top + far)));
+                    shapes.push(stroke((left + far, top + near), (left + near, top + far)));
+                },
+                Some(Piece::O) => shapes.push(Shape::Ring {
+                    center: (left + DRAWING_TILE / 2.0, top + DRAWING_TILE / 2.0),
+                    radius: DRAWING_TILE / 2.0 - INSET,
+                    width: 10.0,
+                    color: colors.o,
+                }),
+                None => {},
+            }
+        }
+    }
+
+    // The winning line goes from the center of its first tile to the center of its last one
+    if let Some(line) = game.winning_line() {
+        let center = |(row, col): (usize, usize)| {
+            ((col as f64 + 0.5) * DRAWING_TILE, (row as f64 + 0.5) * DRAWING_TILE)
+        };
+        // The outline is a wider line underneath, which shows around the edges of the line
+        if let Some(outline) = colors.outline {
+            shapes.push(Shape::Line {
+                from: center(line[0]),
+                to: center(line[2]),
+                width: 22.0,
+                color: outline,
+                opacity: 1.0,
+            });
+        }
+        shapes.push(Shape::Line {
+            from: center(line[0]),
+            to: center(line[2]),
+            width: 14.0,
+            color: colors.highlight,
+            opacity: 0.85,
+        });
+    }
+    shapes
+}
+
+// Draws the game as an SVG image, which can be opened by web browsers and most drawing programs
+// and scaled to any size without getting blurry. SVG is a text format, so we build it up as a
+// String.
+// More info: https://developer.mozilla.org/en-US/docs/Web/SVG
+pub fn to_svg(game: &Game, theme: &Theme) -> String {
+    let size = DRAWING_TILE * game.tiles().len() as f64;
+    let colors = &theme.colors;
+    let mut svg = format!(concat!(r#"<svg xmlns="http://www.w3.org/2000/svg" "#,
+        r#"width="{0}" height="{0}" viewBox="0 0 {0} {0}">"#, "\n",
+        r#"<rect width="{0}" height="{0}" fill="{1}"/>"#, "\n"), size, hex(colors.background));
+
+    for shape in drawing(game, colors) {
+        svg += &match shape {
+            Shape::Line {from, to, width, color, opacity} => format!(
+                concat!(r#"<line x1="{}" y1="{}" x2="{}" y2="{}" stroke="{}" "#,
+                    r#"stroke-width="{}" stroke-linecap="round" opacity="{}"/>"#),
+                from.0, from.1, to.0, to.1, hex(color), width, opacity),
+            Shape::Ring {center, radius, width, color} => format!(
+                r#"<circle cx="{}" cy="{}" r="{}" fill="none" stroke="{}" stroke-width="{}"/>"#,
+                center.0, center.1, radius, hex(color), width),
+        };
+        svg += "\n";
+    }
+
+    svg += "</svg>\n";
+    svg
+}
+
+// Draws the game as a PNG image that is `size` pixels wide and tall. PNG images are made of
+// pixels, so unlike SVG they can be shown almost anywhere, including on social media.
+#[cfg(feature = "images")]
+pub fn to_png(game: &Game, theme: &Theme, size: u32) -> Vec<u8> {
+    paint(game, theme, size).to_png()
+}
+
+// Draws every position of a game as an animated GIF that is `size` pixels wide and tall. The first
+// position is the empty board, and each one after that has one more move than the one before.
+// Each position is shown for `delay`, except for the last one, which stays on the screen for
+// longer so there is time to see how the game ended before the animation starts again.
+#[cfg(feature = "images")]
+pub fn to_gif(positions: &[Game], theme: &Theme, size: u32, delay: Duration) -> Vec<u8> {
+    let frames: Vec<_> = positions.iter().map(|game| paint(game, theme, size)).collect();
+    let mut delays = vec![delay; frames.len()];
+    if let Some(last) = delays.last_mut() {
+        *last = delay * GIF_FINAL_PAUSE;
+    }
+    raster::to_gif(&frames, &delays)
+}
+
+// Paints the game onto a canvas that is `size` pixels wide and tall
+#[cfg(feature = "images")]
+fn paint(game: &Game, theme: &Theme, size: u32) -> Canvas {
+    let colors = &theme.colors;
+    let mut canvas = Canvas::new(size, size, colors.background);
+    // Every shape is measured in drawing units, so we scale them to the size of the image
+    let scale = f64::from(size) / (DRAWING_TILE * game.tiles().len() as f64);
+    let at = |(x, y): (f64, f64)| (x * scale, y * scale);
+
+    for shape in drawing(game, colors) {
+        match shape {
+            Shape::Line {from, to, width, color, opacity} => {
+                canvas.line(at(from), at(to), width * scale, color, opacity)
+            },
+            Shape::Ring {center, radius, width, color} => {
+                canvas.ring(at(center), radius * scale, width * scale, color)
+            },
+        }
+    }
+    canvas
+}
+
+// Writes a color the way SVG (and HTML) expects, e.g. #ff8800
+fn hex(color: Color) -> String {
+    format!("#{:02x}{:02x}{:02x}", color[0], color[1], color[2])
+}
+
+// Moves are written as the row number followed by the column letter, e.g. 2B
+fn position(row: usize, col: usize) -> String {
+    format!("{}{}", row + 1, (b'A' + col as u8) as char)
+}
+
+// Adds spaces around some text until it takes up `width` columns in the terminal, keeping it in the
+// middle. When the spaces can't be split evenly, the extra one goes after the text.
+fn pad(text: &str, width: usize) -> String {
+    // `saturating_sub` stops at zero instead of overflowing if the text is already too wide
+    let spaces = width.saturating_sub(text_width(text));
+    format!("{}{}{}", " ".repeat(spaces / 2), text, " ".repeat(spaces - spaces / 2))
+}
+
+// The number of columns some text takes up in the terminal
+fn text_width(text: &str) -> usize {
+    text.chars().map(|c| display_width(c).unwrap_or(0)).sum()
+}
+
+// The number of columns a whole line takes up, counting the spaces between its words too
+fn line_width(line: &str) -> usize {
+    line.split(' ').map(text_width).sum::<usize>() + line.matches(' ').count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use player::PlayerProfile;
+    use theme::THEMES;
+
+    #[test]
+    fn padding() {
+        assert_eq!(pad("A", 1), "A");
+        assert_eq!(pad("A", 2), "A ");
+        assert_eq!(pad("🐱", 2), "🐱");
+        assert_eq!(pad("x", 3), " x ");
+    }
+
+    #[test]
+    fn drawings_side_by_side() {
+        let drawing = |title: &str, text: &str| (title.to_string(), text.to_string());
+        let drawings = [
+            drawing("One", "x o\n\n"),
+            drawing("Two", "o\nx\n"),
+            drawing("Three", "xx\n"),
+        ];
+        assert_eq!(side_by_side(&drawings, 80),
+            "One    Two    Three\nx o    o      xx\n       x\n");
+        // The third drawing doesn't fit next to the other two
+        assert_eq!(side_by_side(&drawings, 12),
+            "One    Two\nx o    o\n       x\n\nThree\nxx\n");
+    }
+
+    #[test]
+    fn rejections_in_json() {
+        let occupied = Rejection::Occupied {row: 0, col: 2, piece: Piece::X, since: 3};
+        assert_eq!(rejection_details(occupied),
+            r#"{"row":0,"col":2,"position":"1C","piece":"x","since":3}"#);
+        let reason = Reason::NoSuchColumn("D".to_string());
+        let invalid = Rejection::Invalid {input: "1D", reason: &reason};
+        assert_eq!(rejection_details(invalid),
+            r#"{"input":"1D","reason":"no_such_column","column":"D","size":3}"#);
+        let invalid = Rejection::Invalid {input: "?", reason: &Reason::Unreadable};
+        assert_eq!(rejection_details(invalid), r#"{"input":"?","reason":"unreadable"}"#);
+    }
+
+    #[test]
+    fn layout_fits_the_terminal() {
+        assert_eq!(Layout::fit(None, 3, 1), Layout::Compact);
+        assert_eq!(Layout::fit(Some((80, 24)), 3, 1), Layout::Spacious);
+        // Too narrow for wide emoji tiles
+        assert_eq!(Layout::fit(Some((18, 24)), 3, 2), Layout::Compact);
+        // Too short to fit the prompts under the board
+        assert_eq!(Layout::fit(Some((80, 10)), 3, 1), Layout::Compact);
+    }
+
+    #[test]
+    fn svg_shows_the_pieces_and_winning_line() {
+        let mut game = Game::new();
+        let theme = Theme::default();
+        let empty = to_svg(&game, &theme);
+        assert!(empty.starts_with("<svg") && empty.ends_with("</svg>\n"));
+        assert!(!empty.contains("<circle"));
+
+        for &(row, col) in &[(0, 0), (1, 1), (0, 1), (2, 2), (0, 2)] {
+            game.make_move(row, col).unwrap();
+        }
+        let svg = to_svg(&game, &theme);
+        assert_eq!(svg.matches("<circle").count(), 2);
+        // The winning line goes across the top row
+        assert!(svg.contains(r##"<line x1="50" y1="50" x2="250" y2="50" stroke="#edae49""##));
+    }
+
+    #[test]
+    fn status_header() {
+        let players = Players::default();
+        let mut game = Game::new();
+        game.make_move(1, 1).unwrap();
+        let view = GameView::new(&game, &players, Variant::Blindfold);
+        assert_eq!(view.status(Lang::English), "Move 2 | x vs o | Blindfold");
+    }
+
+    // Plays the moves in order from an empty board
+    fn play(moves: &[(usize, usize)]) -> Game {
+        let mut game = Game::new();
+        for &(row, col) in moves {
+            game.make_move(row, col).unwrap();
+        }
+        game
+    }
+
+    // An empty board, a game in progress and a game that x has won across the top row
+    fn positions() -> Vec<Game> {
+        vec![
+            Game::new(),
+            play(&[(1, 1), (0, 0), (2, 2)]),
+            play(&[(0, 0), (1, 1), (0, 1), (2, 2), (0, 2)]),
+        ]
+    }
+
+    // Draws every position with the given theme and layout, using the theme's glyphs just like
+    // the game does when the players haven't chosen their own
+    fn draw_all(theme: &Theme, layout: Layout) -> String {
+        let glyph = |glyph: &str| PlayerProfile {name: None, glyph: Some(glyph.to_string())};
+        let players = Players::new(glyph(theme.x), glyph(theme.o));
+        positions().iter()
+            .map(|game| grid(game.tiles(), game.winning_line(), &players, theme, layout)).collect()
+    }
+
+    #[test]
+    fn grid_snapshots() {
+        // Each snapshot is saved in src/snapshots. If a change to the board is on purpose, the
+        // new snapshots can be accepted with `cargo insta review` (or by running the tests with
+        // `INSTA_UPDATE=always`).
+        for theme in &THEMES {
+            for &(layout, name) in &[(Layout::Compact, "compact"), (Layout::Spacious, "spacious")] {
+                assert_snapshot!(format!("grid_{}_{}", theme.name, name),
+                    draw_all(theme, layout));
+            }
+        }
+    }
+
+    #[test]
+    fn grid_with_chosen_glyphs() {
+        // An emoji is two columns wide, so every other tile is widened to match
+        let players = Players::new(
+            PlayerProfile {name: None, glyph: Some("\u{1F431}".to_string())},
+            PlayerProfile::default(),
+        );
+        let game = play(&[(1, 1), (0, 0)]);
+        let theme = Theme::default();
+        assert_snapshot!("grid_wide_glyph_compact",
+            grid(game.tiles(), None, &players, &theme, Layout::Compact));
+        assert_snapshot!("grid_wide_glyph_spacious",
+            grid(game.tiles(), None, &players, &theme, Layout::Spacious));
+    }
+
+    #[test]
+    fn grid_coordinate_styles() {
+        let players = Players::new(PlayerProfile::default(), PlayerProfile::default());
+        let game = play(&[(1, 1), (0, 0)]);
+        let theme = Theme::default();
+        for &style in &[CoordinateStyle::Numeric, CoordinateStyle::Keypad] {
+            let width = tile_width(&players, &theme);
+            let board = Board {tiles: game.tiles(), winning: None, players: &players, theme: &theme,
+                style};
+            assert_snapshot!(format!("grid_{}_compact", style.name()), compact_grid(&board, width));
+            assert_snapshot!(format!("grid_{}_spacious", style.name()),
+                spacious_grid(&board, width));
+        }
+    }
+
+    #[test]
+    fn spoken_snapshots() {
+        let players = Players::new(PlayerProfile {name: Some("Ada".to_string()), glyph: None},
+            PlayerProfile::default());
+        for &lang in &[Lang::English, Lang::French] {
+            let text: String = positions().iter()
+                .map(|game| spoken_rows(game.tiles(), &players, lang)).collect();
+            assert_snapshot!(format!("spoken_{}", lang.code()), text);
+        }
+    }
+
+    #[test]
+    fn row_description() {
+        let row = [Some("x"), None, Some("o")];
+        let text = Lang::English.text(Message::RowDescription {row: 0, tiles: &row});
+        assert_eq!(text, "Row 1: x, empty, o.");
+    }
+}
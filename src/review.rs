@@ -0,0 +1,166 @@
+// Reviews a finished game move by move, for the "analyze" answer at the end of a game. Each move
+// is compared with what the solver would have played in its place (see solve.rs):
+//
+// * the best moves get the best result for the player the quickest, or lose the slowest
+// * an ok move still gets the same result with perfect play, only not as quickly (or it loses
+//   sooner)
+// * a mistake gives away a win or a draw
+//
+// Every move that isn't one of the best comes with the moves that would have been better, and
+// every move that was timed while the game was played comes with how long its player thought.
+
+use std::time::Duration;
+
+use engine;
+use game::{Game, Piece};
+use solve;
+use tt::Table;
+
+// How big the table that the solver remembers positions in is. The positions of one game share
+// most of what comes after them, so a small table is plenty.
+const TABLE_MEGABYTES: usize = 1;
+
+// How good a move was
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mark {
+    Best,
+    Ok,
+    Mistake,
+}
+
+// One move of the game along with what the solver thinks of it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reviewed {
+    pub row: usize,
+    pub col: usize,
+    /// The piece that made the move
+    pub piece: Piece,
+    pub mark: Mark,
+    /// The best moves in the position, or nothing if the move was one of them
+    pub better: Vec<(usize, usize)>,
+    /// How long the player thought about the move, if it was timed
+    pub thinking: Option<Duration>,
+}
+
+// What can be typed while stepping through a review
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step {
+    /// Go on to the next move
+    Next,
+    /// Go back to the move before
+    Back,
+    /// Stop reviewing
+    Done,
+}
+
+impl Step {
+    // Converts what the user typed into a step. Just pressing enter goes on to the next move
+    // since that is what people do most. Returns None if the answer wasn't recognized.
+    pub fn parse(input: &str) -> Option<Self> {
+        match &input.trim().to_lowercase()[..] {
+            "" | "n" | "next" | "s" | "suivant" => Some(Step::Next),
+            "b" | "back" | "p" | "précédent" | "precedent" => Some(Step::Back),
+            "q" | "quit" | "quitter" => Some(Step::Done),
+            _ => None,
+        }
+    }
+}
+
+// Reviews every move of a game, in the order they were played
+pub fn review(game: &Game) -> Vec<Reviewed> {
+    let mut table = Table::with_megabytes(TABLE_MEGABYTES);
+    let mut position = Game::starting_with(game.first_piece()).with_rules(game.rules());
+    let mut reviewed = Vec::new();
+    for (i, (row, col)) in game.moves().iter().enumerate() {
+        let piece = position.current_piece();
+        let before = engine::perfect_result(&position);
+        let best = solve::best_moves(&position, &mut table);
+        position.make_move(row, col).expect("the game was played with these moves");
+
+        let mark = if best.contains(&(row, col)) {
+            Mark::Best
+        }
+        else if engine::perfect_result(&position) == before {
+            Mark::Ok
+        }
+        else {
+            Mark::Mistake
+        };
+        let better = if mark == Mark::Best { Vec::new() } else { best };
+        let thinking = game.move_time(i).map(|time| time.thinking);
+        reviewed.push(Reviewed {row, col, piece, mark, better, thinking});
+    }
+    reviewed
+}
+
+// The number of moves of each mark that a player made, in the order best, ok and mistake
+pub fn counts(reviewed: &[Reviewed], piece: Piece) -> [usize; 3] {
+    let mut counts = [0; 3];
+    for mv in reviewed.iter().filter(|mv| mv.piece == piece) {
+        counts[mv.mark as usize] += 1;
+    }
+    counts
+}
+
+// The total time a player thought about their timed moves, along with the number of those moves.
+// Returns None if none of the player's moves were timed.
+pub fn thinking(reviewed: &[Reviewed], piece: Piece) -> Option<(Duration, usize)> {
+    let times: Vec<_> = reviewed.iter()
+        .filter(|mv| mv.piece == piece)
+        .filter_map(|mv| mv.thinking)
+        .collect();
+    if times.is_empty() {
+        None
+    }
+    else {
+        Some((times.iter().sum(), times.len()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use game::MoveTime;
+
+    #[test]
+    fn marks() {
+        // o doesn't block at 1C, and x misses the win there but makes two threats instead
+        let mut game = Game::new();
+        for &(row, col) in &[(0, 0), (1, 1), (0, 1), (2, 2), (2, 0), (0, 2), (1, 0)] {
+            game.make_move(row, col).unwrap();
+        }
+        let reviewed = review(&game);
+        let marks: Vec<_> = reviewed.iter().map(|mv| mv.mark).collect();
+        assert_eq!(marks, [Mark::Best, Mark::Best, Mark::Best, Mark::Mistake, Mark::Ok, Mark::Best,
+            Mark::Best]);
+        assert_eq!(reviewed[3].better, [(0, 2)]);
+        assert_eq!(reviewed[4].better, [(0, 2)]);
+        assert!(reviewed[0].better.is_empty());
+        assert_eq!(counts(&reviewed, Piece::X), [3, 1, 0]);
+        assert_eq!(counts(&reviewed, Piece::O), [2, 0, 1]);
+    }
+
+    #[test]
+    fn thinking_time() {
+        let mut game = Game::new();
+        for &(row, col, millis) in &[(1, 1, 1500), (0, 0, 0), (2, 2, 500)] {
+            game.make_move(row, col).unwrap();
+            if millis > 0 {
+                game.time_last_move(MoveTime {made: 0, thinking: Duration::from_millis(millis)});
+            }
+        }
+        let reviewed = review(&game);
+        assert_eq!(reviewed[1].thinking, None);
+        assert_eq!(thinking(&reviewed, Piece::X), Some((Duration::from_secs(2), 2)));
+        assert_eq!(thinking(&reviewed, Piece::O), None);
+    }
+
+    #[test]
+    fn steps() {
+        assert_eq!(Step::parse(""), Some(Step::Next));
+        assert_eq!(Step::parse(" Back "), Some(Step::Back));
+        assert_eq!(Step::parse("quitter"), Some(Step::Done));
+        assert_eq!(Step::parse("later"), None);
+    }
+}
@@ -0,0 +1,153 @@
+// Homebrew variants of the game, chosen with `--rules NAME`. Each one is a WinCondition (see
+// game.rs), so the board, the moves and the turns are the same as always, and only what ends the
+// game is different. The solver and the perfect computer player work out how to play each of
+// them by themselves (see engine.rs).
+//
+// A new variant only needs a type that implements WinCondition, a variant in Rules below, a name
+// for the command line and a number in game codes (see share.rs).
+
+use game::{self, Game, Piece, Tiles, WinCondition, Winner};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Rules {
+    /// The normal rules: three in a row wins
+    #[default]
+    Classic,
+    /// Three in a row wins, and so does holding all four corners
+    Corners,
+    /// Three in a row loses
+    Misere,
+}
+
+impl Rules {
+    // Finds the rules by the name used on the command line
+    pub fn from_name(name: &str) -> Option<Self> {
+        match &name.to_lowercase()[..] {
+            "classic" | "normal" => Some(Rules::Classic),
+            "corners" => Some(Rules::Corners),
+            // Misère is what the variant is called in French too
+            "misere" | "misère" => Some(Rules::Misere),
+            _ => None,
+        }
+    }
+
+    // The rules that a game is played with
+    pub fn of(game: &Game) -> Self {
+        game.rules().and_then(|condition| Self::from_name(condition.name())).unwrap_or_default()
+    }
+
+    // The name used for these rules on the command line
+    pub fn name(self) -> &'static str {
+        match self {
+            Rules::Classic => "classic",
+            Rules::Corners => "corners",
+            Rules::Misere => "misere",
+        }
+    }
+
+    // What the game is given to decide when it is over, or None for the normal rules
+    pub fn condition(self) -> Option<&'static dyn WinCondition> {
+        match self {
+            Rules::Classic => None,
+            Rules::Corners => Some(&Corners),
+            Rules::Misere => Some(&Misere),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Corners;
+
+impl WinCondition for Corners {
+    fn winner(&self, tiles: &Tiles, _last: Piece) -> Option<Winner> {
+        let corners = [tiles[0][0], tiles[0][2], tiles[2][0], tiles[2][2]];
+        let all_corners = if corners.iter().all(|&corner| corner == corners[0]) {
+            corners[0]
+        }
+        else {
+            None
+        };
+        game::three_in_a_row(tiles).or(all_corners).map(winner)
+    }
+
+    fn name(&self) -> &'static str {
+        Rules::Corners.name()
+    }
+}
+
+#[derive(Debug)]
+struct Misere;
+
+impl WinCondition for Misere {
+    fn winner(&self, tiles: &Tiles, _last: Piece) -> Option<Winner> {
+        // Whoever made the line gave the game to the other player
+        game::three_in_a_row(tiles).map(|piece| winner(piece.other()))
+    }
+
+    fn name(&self) -> &'static str {
+        Rules::Misere.name()
+    }
+}
+
+fn winner(piece: Piece) -> Winner {
+    match piece {
+        Piece::X => Winner::X,
+        Piece::O => Winner::O,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use engine;
+    use game::Game;
+
+    fn play(rules: Rules, moves: &[(usize, usize)]) -> Game {
+        let mut game = Game::new().with_rules(rules.condition());
+        for &(row, col) in moves {
+            game.make_move(row, col).unwrap();
+        }
+        game
+    }
+
+    #[test]
+    fn names() {
+        assert_eq!(Rules::from_name("Misère"), Some(Rules::Misere));
+        assert_eq!(Rules::from_name("squares"), None);
+        for &rules in &[Rules::Classic, Rules::Corners, Rules::Misere] {
+            assert_eq!(Rules::from_name(rules.name()), Some(rules));
+            assert_eq!(Rules::of(&Game::new().with_rules(rules.condition())), rules);
+        }
+    }
+
+    #[test]
+    fn corners() {
+        // x takes the fourth corner without having three in a row
+        let game = play(Rules::Corners, &[(0, 0), (0, 1), (0, 2), (1, 1), (2, 0), (1, 2), (2, 2)]);
+        assert_eq!(game.winner(), Some(Winner::X));
+        assert_eq!(game.winning_line(), None);
+        // The same moves are still going under the normal rules
+        assert_eq!(play(Rules::Classic, &game.moves().iter().collect::<Vec<_>>()).winner(), None);
+
+        // x wins with the last corner, while o can still only win with a line
+        let game = play(Rules::Corners, &[(0, 0), (1, 1), (0, 2), (0, 1), (2, 2), (1, 2)]);
+        assert_eq!(game.winning_moves(Piece::X), vec![(2, 0)]);
+        assert_eq!(game.winning_moves(Piece::O), vec![(1, 0), (2, 1)]);
+        assert_eq!(engine::perfect_moves(&game), vec![(2, 0)]);
+    }
+
+    #[test]
+    fn misere() {
+        let game = play(Rules::Misere, &[(0, 0), (1, 1), (0, 1), (2, 2), (0, 2)]);
+        assert_eq!(game.winner(), Some(Winner::O));
+        assert!(!game.is_dead());
+
+        // x would lose by finishing the top row, so the solver keeps away from it
+        let game = play(Rules::Misere, &[(0, 0), (1, 1), (0, 1), (2, 2)]);
+        assert!(!engine::perfect_moves(&game).contains(&(0, 2)));
+        // x can take the middle and then answer every move on the opposite side of the board,
+        // which never makes a line, so o can't win
+        assert_ne!(engine::perfect_result(&play(Rules::Misere, &[(1, 1)])), Winner::O);
+    }
+}
@@ -0,0 +1,182 @@
+// A series (or "match") is a group of games played one after another between the same two
+// players. In a best-of-N series, at most N games are played and whoever wins the most games wins
+// the whole series.
+
+use game::{Game, Piece, Winner};
+
+// This keeps a running count of the results in a series. Grouping these numbers into a struct lets
+// us pass the whole score around as a single value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Score {
+    pub x: u32,
+    pub o: u32,
+    pub ties: u32,
+}
+
+impl Score {
+    // Counts one more game with the given result
+    pub fn add(&mut self, winner: Winner) {
+        match winner {
+            Winner::X => self.x += 1,
+            Winner::O => self.o += 1,
+            Winner::Tie => self.ties += 1,
+        }
+    }
+
+    // The same score from the other side of the board. When the players trade pieces for a
+    // rematch, each player's wins go with them.
+    pub fn swapped(self) -> Self {
+        Self {x: self.o, o: self.x, ties: self.ties}
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Series {
+    best_of: u32,
+    // The piece that goes first in the first game of the series
+    first_piece: Piece,
+    // Every finished game in the order it was played
+    games: Vec<Game>,
+}
+
+impl Series {
+    // A series must contain at least one game. We use an assertion here because asking for a
+    // "best of 0" is a mistake in the calling code, not something we can recover from.
+    // The given piece goes first in the first game of the series.
+    pub fn starting_with(best_of: u32, first_piece: Piece) -> Self {
+        assert!(best_of > 0, "a series must contain at least one game");
+
+        Self {
+            best_of,
+            first_piece,
+            games: Vec::new(),
+        }
+    }
+
+    pub fn best_of(&self) -> u32 {
+        self.best_of
+    }
+
+    pub fn games(&self) -> &[Game] {
+        &self.games
+    }
+
+    // The players take turns going first. The first piece starts the first game, the other piece
+    // starts the second, and so on.
+    pub fn next_first_piece(&self) -> Piece {
+        if self.games.len().is_multiple_of(2) {
+            self.first_piece
+        }
+        else {
+            self.first_piece.other()
+        }
+    }
+
+    // Adds a finished game to the series. Adding an unfinished game would make the score
+    // meaningless, so we check for that.
+    pub fn record(&mut self, game: Game) {
+        assert!(game.is_finished(), "only finished games can be recorded in a series");
+        assert!(!self.is_finished(), "cannot record a game after the series is over");
+
+        self.games.push(game);
+    }
+
+    pub fn score(&self) -> Score {
+        // We start at zero and add one to the right counter for every game
+        let mut score = Score::default();
+        for game in &self.games {
+            // `record` makes sure that every game has a winner
+            score.add(game.winner().expect("series contained an unfinished game"));
+        }
+        score
+    }
+
+    // The series is over once every game has been played, or once the leader has more wins than
+    // the other player could possibly catch up with in the remaining games.
+    pub fn is_finished(&self) -> bool {
+        let score = self.score();
+        let remaining = self.best_of - self.games.len() as u32;
+        let (leader, trailer) = if score.x > score.o {
+            (score.x, score.o)
+        }
+        else {
+            (score.o, score.x)
+        };
+        remaining == 0 || leader > trailer + remaining
+    }
+
+    // Returns the winner of the series once it is finished. If both players won the same number
+    // of games, the series is a tie.
+    pub fn winner(&self) -> Option<Winner> {
+        if !self.is_finished() {
+            return None;
+        }
+
+        let score = self.score();
+        Some(if score.x > score.o {
+            Winner::X
+        }
+        else if score.o > score.x {
+            Winner::O
+        }
+        else {
+            Winner::Tie
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Plays a quick game where the first piece wins along the top row
+    fn first_piece_wins(first: Piece) -> Game {
+        let mut game = Game::starting_with(first);
+        game.make_move(0, 0).unwrap();
+        game.make_move(1, 0).unwrap();
+        game.make_move(0, 1).unwrap();
+        game.make_move(1, 1).unwrap();
+        game.make_move(0, 2).unwrap();
+        game
+    }
+
+    #[test]
+    fn alternates_first_piece() {
+        let mut series = Series::starting_with(3, Piece::X);
+        assert_eq!(series.next_first_piece(), Piece::X);
+        series.record(first_piece_wins(Piece::X));
+        assert_eq!(series.next_first_piece(), Piece::O);
+
+        let series = Series::starting_with(3, Piece::O);
+        assert_eq!(series.next_first_piece(), Piece::O);
+    }
+
+    #[test]
+    fn ends_early_when_decided() {
+        let mut series = Series::starting_with(3, Piece::X);
+        series.record(first_piece_wins(Piece::X));
+        assert!(!series.is_finished());
+        series.record(first_piece_wins(Piece::X));
+        // X has two wins and only one game remains, so O can no longer catch up
+        assert!(series.is_finished());
+        assert_eq!(series.winner(), Some(Winner::X));
+        assert_eq!(series.score(), Score {x: 2, o: 0, ties: 0});
+    }
+
+    #[test]
+    fn swapped_score() {
+        let mut score = Score::default();
+        score.add(Winner::X);
+        score.add(Winner::Tie);
+        assert_eq!(score.swapped(), Score {x: 0, o: 1, ties: 1});
+    }
+
+    #[test]
+    fn tied_series() {
+        let mut series = Series::starting_with(2, Piece::X);
+        series.record(first_piece_wins(Piece::X));
+        series.record(first_piece_wins(Piece::O));
+        assert!(series.is_finished());
+        assert_eq!(series.winner(), Some(Winner::Tie));
+    }
+}
@@ -0,0 +1,258 @@
+// A session is everything that happens from when the program starts until the players decide to
+// quit. The settings for the session are kept here so that they can be reused for every game.
+
+use clock::{Idle, IdleAction, TimeControl};
+use engine::{Difficulty, Profile};
+use game::{Game, Piece};
+use options::Options;
+use player::{PlayerProfile, Players};
+use rules::Rules;
+
+// The number of times per game the board can be shown in blindfold mode, unless `--shows` says
+// otherwise
+const DEFAULT_SHOWS: u32 = 3;
+
+// The settings that are used each time a new game (or series) is started
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Settings {
+    /// The number of games in each series. A value of 1 means that a single game is played.
+    pub best_of: u32,
+    /// The piece that goes first in the next game (or the first game of the next series)
+    pub first_piece: Piece,
+    /// The names and glyphs of the players
+    pub players: Players,
+    /// The chess clock settings, or None to play without clocks
+    pub time_control: Option<TimeControl>,
+    /// What happens when a person doesn't move in time, or None to wait for them forever
+    pub idle: Option<Idle>,
+    /// Save unfinished games to the archive without asking when Ctrl-C is pressed
+    pub autosave: bool,
+    /// In blindfold mode, the number of times per game the players can ask to see the board.
+    /// None means the board isn't hidden.
+    pub blindfold_shows: Option<u32>,
+    /// The computer player playing x, or None if a person is playing x
+    pub x_engine: Option<Computer>,
+    /// The computer player playing o, or None if a person is playing o
+    pub o_engine: Option<Computer>,
+    /// When the players asked for a rematch, the number of the rematch (1 for the first one).
+    /// Rematches are saved as part of the same match as the games before them.
+    pub rematch: Option<u32>,
+    /// End the games between computer players as soon as their result is certain (see
+    /// simulate.rs)
+    pub adjudicate: bool,
+    /// The address that game codes are turned into links to, if there is one (see share.rs)
+    pub share_url: Option<String>,
+    /// The rules that decide when each game is over (see rules.rs)
+    pub rules: Rules,
+    /// The tags given to every new game, as `(key, value)` pairs
+    pub tags: Vec<(String, String)>,
+}
+
+impl Settings {
+    // The command line options decide the settings for the first game
+    pub fn from_options(options: &Options) -> Self {
+        Self {
+            best_of: options.best_of.unwrap_or(1),
+            first_piece: Piece::X,
+            players: Players::new(options.x_player.clone(), options.o_player.clone()),
+            time_control: options.time_control,
+            idle: options.idle_timeout.map(|timeout| {
+                Idle {timeout, action: options.on_idle.unwrap_or_default()}
+            }),
+            autosave: options.autosave,
+            blindfold_shows: if options.blindfold {
+                Some(options.shows.unwrap_or(DEFAULT_SHOWS))
+            }
+            else {
+                None
+            },
+            x_engine: None,
+            o_engine: None,
+            rematch: None,
+            adjudicate: options.adjudicate,
+            share_url: options.share_url.clone(),
+            rules: options.rules,
+            tags: options.tags.clone(),
+        }
+    }
+
+    // A new game played by these settings' rules, with the given piece going first
+    pub fn new_game(&self, first_piece: Piece) -> Game {
+        let mut game = Game::starting_with(first_piece).with_rules(self.rules.condition());
+        for (key, value) in &self.tags {
+            game.tag(key.as_str(), value.as_str());
+        }
+        game
+    }
+
+    // The computer player playing the given piece, or None if a person is playing it
+    pub fn engine(&self, piece: Piece) -> Option<&Computer> {
+        match piece {
+            Piece::X => self.x_engine.as_ref(),
+            Piece::O => self.o_engine.as_ref(),
+        }
+    }
+
+    // Whether the games use the shared random numbers (see random.rs), either because the engine
+    // plays in them or because it moves for the players who don't move in time. Only the seed
+    // can play those games again the same way.
+    pub fn uses_random(&self) -> bool {
+        let idle_moves = self.idle.is_some_and(|idle| idle.action == IdleAction::Move);
+        idle_moves || [Piece::X, Piece::O].iter()
+            .any(|&piece| self.engine(piece).is_some_and(Computer::uses_random))
+    }
+}
+
+// Who picks the moves of a computer player
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Computer {
+    /// The engine (see engine.rs), playing at one of its difficulties
+    Engine(Difficulty),
+    /// The engine playing by the profile with this name from the preferences (see engine.rs)
+    Profile(String, Profile),
+    /// The computer player plugin with this name (see plugin.rs)
+    Plugin(String),
+}
+
+impl Computer {
+    // Reads a computer player as it is given on the command line: a difficulty (`easy`, `medium`
+    // or `perfect`), `engine:NAME` for one of the given profiles, or `plugin:NAME`. Whether the
+    // plugin is there is only known once the plugins are loaded.
+    pub fn parse(text: &str, profiles: &[(String, Profile)]) -> Option<Self> {
+        if let Some(name) = text.strip_prefix("engine:") {
+            let (name, profile) = profiles.iter().find(|(profile, _)| profile == name)?;
+            Some(Computer::Profile(name.clone(), *profile))
+        }
+        else if let Some(name) = text.strip_prefix("plugin:") {
+            Some(Computer::Plugin(PlayerProfile::validate_name(name).ok()?))
+        }
+        else {
+            Difficulty::from_name(text).map(Computer::Engine)
+        }
+    }
+
+    // The name that the computer player goes by
+    pub fn name(&self) -> &str {
+        match *self {
+            Computer::Engine(difficulty) => difficulty.name(),
+            Computer::Profile(ref name, _) | Computer::Plugin(ref name) => name,
+        }
+    }
+
+    // How the engine plays for this computer player, or None if a plugin plays instead
+    pub fn profile(&self) -> Option<Profile> {
+        match *self {
+            Computer::Engine(difficulty) => Some(difficulty.into()),
+            Computer::Profile(_, profile) => Some(profile),
+            Computer::Plugin(_) => None,
+        }
+    }
+
+    // Whether the computer player picks its moves with the shared random numbers. The engine does,
+    // even at the perfect difficulty, since it picks between moves that are just as good. Plugins
+    // have their own.
+    pub fn uses_random(&self) -> bool {
+        self.profile().is_some()
+    }
+}
+
+// The answers to "Play again?" at the end of each game
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlayAgain {
+    /// Start another game with exactly the same settings
+    SameSettings,
+    /// Start another game with the players trading pieces
+    SwapPieces,
+    /// Start another game with the players trading pieces, as part of the same match. The score
+    /// is carried over to the next game.
+    Rematch,
+    /// Ask for new settings before starting another game
+    ChangeSettings,
+    /// Step through the game that was just played with the solver's opinion of every move
+    Review,
+    /// Show the code of the game that was just played, so that it can be shared (see share.rs)
+    Share,
+    /// Exit the program
+    Quit,
+}
+
+impl PlayAgain {
+    // Converts what the user typed into one of the answers. Either the full word or its first
+    // letter is accepted, in any of the languages we support. Just pressing enter means "same
+    // settings" since that is the most common choice. Returns None if the answer wasn't
+    // recognized.
+    pub fn parse(input: &str) -> Option<Self> {
+        // `to_lowercase` lets us accept "Quit", "QUIT", and "quit" all the same
+        match &input.trim().to_lowercase()[..] {
+            "" | "s" | "same" | "r" | "rejouer" => Some(PlayAgain::SameSettings),
+            "w" | "swap" | "e" | "échanger" | "echanger" => Some(PlayAgain::SwapPieces),
+            "m" | "rematch" | "revanche" => Some(PlayAgain::Rematch),
+            "c" | "change" | "changer" => Some(PlayAgain::ChangeSettings),
+            "a" | "analyze" | "analyser" => Some(PlayAgain::Review),
+            "p" | "share" | "partager" => Some(PlayAgain::Share),
+            "q" | "quit" | "quitter" => Some(PlayAgain::Quit),
+            _ => None,
+        }
+    }
+}
+
+// Converts the answer to a yes or no question into a bool. Returns None if the answer wasn't
+// recognized. "o" is short for "oui", which is French for "yes".
+pub fn parse_yes_no(input: &str) -> Option<bool> {
+    match &input.trim().to_lowercase()[..] {
+        "y" | "yes" | "o" | "oui" => Some(true),
+        "n" | "no" | "non" => Some(false),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::time::Duration;
+
+    #[test]
+    fn parse_play_again() {
+        assert_eq!(PlayAgain::parse(""), Some(PlayAgain::SameSettings));
+        assert_eq!(PlayAgain::parse("Swap"), Some(PlayAgain::SwapPieces));
+        assert_eq!(PlayAgain::parse("c"), Some(PlayAgain::ChangeSettings));
+        assert_eq!(PlayAgain::parse(" q "), Some(PlayAgain::Quit));
+        assert_eq!(PlayAgain::parse("échanger"), Some(PlayAgain::SwapPieces));
+        assert_eq!(PlayAgain::parse("analyser"), Some(PlayAgain::Review));
+        assert_eq!(PlayAgain::parse("Revanche"), Some(PlayAgain::Rematch));
+        assert_eq!(PlayAgain::parse("partager"), Some(PlayAgain::Share));
+        assert_eq!(PlayAgain::parse("maybe"), None);
+    }
+
+    #[test]
+    fn yes_or_no() {
+        assert_eq!(parse_yes_no("Y"), Some(true));
+        assert_eq!(parse_yes_no("oui"), Some(true));
+        assert_eq!(parse_yes_no(" no "), Some(false));
+        assert_eq!(parse_yes_no(""), None);
+    }
+
+    #[test]
+    fn settings_from_options() {
+        let settings = Settings::from_options(&Options::default());
+        assert_eq!(settings.best_of, 1);
+        assert_eq!(settings.first_piece, Piece::X);
+        assert!(!settings.uses_random());
+    }
+
+    #[test]
+    fn random_settings() {
+        let people = Settings::from_options(&Options::default());
+        let engine = Computer::Engine(Difficulty::Perfect);
+        assert!(Settings {o_engine: Some(engine), ..people.clone()}.uses_random());
+        let plugin = Computer::Plugin("mcts".to_string());
+        assert!(!Settings {x_engine: Some(plugin), ..people.clone()}.uses_random());
+
+        // The computer moves for a player who takes too long, unless they forfeit instead
+        let timeout = Duration::from_secs(30);
+        let idle = |action| Settings {idle: Some(Idle {timeout, action}), ..people.clone()};
+        assert!(idle(IdleAction::Move).uses_random());
+        assert!(!idle(IdleAction::Forfeit).uses_random());
+    }
+}
@@ -0,0 +1,146 @@
+// The position editor for the `setup` command. Instead of playing moves in turn, the player puts
+// pieces anywhere on the board, chooses who moves next, and then plays or analyzes the game from
+// that position. Typing a tile like "1A" changes what is on it, going from empty to x to o and
+// back to empty, so any position can be set up one tile at a time.
+//
+// The position only has to make sense once the player wants to use it. Until then, the board can
+// have any pieces on it, which is why it is kept as plain tiles instead of a Game (see
+// `Game::from_position`).
+
+use game::{Game, Piece, PositionError, Tiles};
+use notation::parse_move;
+
+// What can be typed at the setup prompt
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edit {
+    /// Change what is on a tile: empty, then x, then o, then empty again
+    Toggle(usize, usize),
+    /// Choose who moves next
+    Next(Piece),
+    /// Take every piece off the board
+    Clear,
+    /// Start a game from the position
+    Play,
+    /// Show how the position ends with perfect play, and the best moves
+    Analyze,
+}
+
+impl Edit {
+    // Converts what the user typed into an edit. Just like the commands at the move prompt, the
+    // words are accepted in any of the languages we support. Returns None if the input isn't an
+    // edit.
+    pub fn parse(input: &str) -> Option<Self> {
+        let input = input.trim().to_lowercase();
+        let mut words = input.split_whitespace();
+        let edit = match (words.next(), words.next()) {
+            (Some("next"), Some(piece)) | (Some("suivant"), Some(piece)) => match piece {
+                "x" => Edit::Next(Piece::X),
+                "o" => Edit::Next(Piece::O),
+                _ => return None,
+            },
+            (Some("clear"), None) | (Some("vider"), None) => Edit::Clear,
+            (Some("play"), None) | (Some("jouer"), None) => Edit::Play,
+            (Some("analyze"), None) | (Some("analyser"), None) => Edit::Analyze,
+            (Some(tile), None) => {
+                let (row, col) = parse_move(tile).ok()?;
+                Edit::Toggle(row, col)
+            },
+            _ => return None,
+        };
+        // Nothing takes more than two words
+        match words.next() {
+            Some(_) => None,
+            None => Some(edit),
+        }
+    }
+}
+
+// The position being set up
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Setup {
+    tiles: Tiles,
+    next: Piece,
+}
+
+impl Setup {
+    // An empty board with x to move
+    pub fn new() -> Self {
+        Self {tiles: Default::default(), next: Piece::X}
+    }
+
+    pub fn tiles(&self) -> &Tiles {
+        &self.tiles
+    }
+
+    pub fn next(&self) -> Piece {
+        self.next
+    }
+
+    // Makes a change to the position. Playing and analyzing don't change anything, so they are
+    // left to the caller.
+    pub fn apply(&mut self, edit: Edit) {
+        match edit {
+            Edit::Toggle(row, col) => {
+                let tile = &mut self.tiles[row][col];
+                *tile = match *tile {
+                    None => Some(Piece::X),
+                    Some(Piece::X) => Some(Piece::O),
+                    Some(Piece::O) => None,
+                };
+            },
+            Edit::Next(piece) => self.next = piece,
+            Edit::Clear => self.tiles = Default::default(),
+            Edit::Play | Edit::Analyze => {},
+        }
+    }
+
+    // The game that the position comes from, if there is one
+    pub fn game(&self) -> Result<Game, PositionError> {
+        Game::from_position(&self.tiles, self.next)
+    }
+}
+
+impl Default for Setup {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_edits() {
+        assert_eq!(Edit::parse("1A"), Some(Edit::Toggle(0, 0)));
+        assert_eq!(Edit::parse(" Next O "), Some(Edit::Next(Piece::O)));
+        assert_eq!(Edit::parse("suivant x"), Some(Edit::Next(Piece::X)));
+        assert_eq!(Edit::parse("jouer"), Some(Edit::Play));
+        assert_eq!(Edit::parse("analyze"), Some(Edit::Analyze));
+        assert_eq!(Edit::parse("next"), None);
+        assert_eq!(Edit::parse("next z"), None);
+        assert_eq!(Edit::parse("clear all"), None);
+        assert_eq!(Edit::parse("4D"), None);
+    }
+
+    #[test]
+    fn set_up_a_position() {
+        let mut setup = Setup::new();
+        for &edit in &[Edit::Toggle(1, 1), Edit::Toggle(0, 0), Edit::Toggle(0, 0)] {
+            setup.apply(edit);
+        }
+        assert_eq!(setup.tiles()[1][1], Some(Piece::X));
+        assert_eq!(setup.tiles()[0][0], Some(Piece::O));
+        let game = setup.game().unwrap();
+        assert_eq!((game.current_piece(), game.moves().len()), (Piece::X, 2));
+
+        // x is a piece ahead, so it has to be o's turn
+        setup.apply(Edit::Toggle(0, 0));
+        assert_eq!(setup.tiles()[0][0], None);
+        assert!(setup.game().is_err());
+        setup.apply(Edit::Next(Piece::O));
+        assert!(setup.game().is_ok());
+        setup.apply(Edit::Clear);
+        assert_eq!(setup, Setup {tiles: Default::default(), next: Piece::O});
+    }
+}
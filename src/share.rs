@@ -0,0 +1,242 @@
+// Short codes for sharing games, like "CAKQS". A code holds the rules that the game was played
+// with and every move that was made, so anyone who has it can see the game again or carry on
+// playing from where it was left (with `--from-code`). A code can also be turned into a link by
+// putting it at the end of a URL (see `permalink`).
+//
+// The first byte of a code says which version of the format it uses, which rules the game was
+// played with (see rules.rs) and which piece went first. Codes made with version 1 of the format
+// don't say which rules, and those games were always played with the normal rules. The rest of
+// the bytes are the moves, packed 4 bits to a move just like in history.rs. Even a
+// whole game only takes 6 bytes, which base32 turns into 10 letters and digits. Base32 only uses
+// the capital letters and the digits 2 to 7, so a code is easy to read aloud and to type on a
+// phone, and none of its characters need to be escaped in a URL.
+// More info: https://www.rfc-editor.org/rfc/rfc4648#section-6
+
+use thiserror::Error;
+
+use game::{Game, Piece};
+use history::History;
+use rules::Rules;
+
+// The version of the format that codes are made with. Codes that say they use any other version
+// (except for version 1, which is the same without the rules) can't be read.
+const VERSION: u8 = 2;
+// The bit of the first byte that is set when o went first
+const O_FIRST: u8 = 1;
+// Where the number of the rules is in the first byte, and the bits that it takes up
+const RULES_SHIFT: u8 = 1;
+const RULES_MASK: u8 = 0b111;
+// The 32 characters of base32, in the order of the values they stand for
+const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+// The number of bits that each character stands for
+const CHAR_BITS: u32 = 5;
+
+// The reasons that a code can't be turned back into a game
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum CodeError {
+    /// The code has a character that isn't part of base32
+    #[error("'{0}' can't be part of a game code")]
+    InvalidCharacter(char),
+    /// The code is too short or too long to be a game
+    #[error("the code is too short or too long to be a game")]
+    InvalidLength,
+    /// The code was made with a version of the format that we don't know about
+    #[error("the code was made with version {0} of the format, which isn't known")]
+    UnknownVersion(u8),
+    /// The game was played with rules that we don't know about
+    #[error("the code has rules number {0}, which aren't known")]
+    UnknownRules(u8),
+    /// One of the moves isn't on the board, or doesn't follow the rules
+    #[error("one of the moves in the code can't be played")]
+    InvalidMove,
+}
+
+impl CodeError {
+    // The number that stands for this error (see error.rs)
+    pub fn code(&self) -> u16 {
+        match *self {
+            CodeError::InvalidCharacter(_) => 220,
+            CodeError::InvalidLength => 221,
+            CodeError::UnknownVersion(_) => 222,
+            CodeError::InvalidMove => 223,
+            CodeError::UnknownRules(_) => 224,
+        }
+    }
+}
+
+// The code of a game, with the moves that have been made in it so far
+pub fn encode(game: &Game) -> String {
+    let mut header = (VERSION << 4) | (rules_number(Rules::of(game)) << RULES_SHIFT);
+    if game.first_piece() == Piece::O {
+        header |= O_FIRST;
+    }
+    let moves = game.moves();
+    // Two moves fit in each byte, so an odd number of moves leaves half of the last byte empty
+    let length = moves.len().div_ceil(2);
+    let mut bytes = vec![header];
+    bytes.extend_from_slice(&moves.bits().to_le_bytes()[..length]);
+    to_base32(&bytes)
+}
+
+// Plays the moves of a code to get its game back. The code can also be given as part of a link
+// made with `permalink`, and lowercase letters are accepted too.
+pub fn decode(code: &str) -> Result<Game, CodeError> {
+    let code = code.trim().rsplit(&['=', '#', '/'][..]).next().unwrap_or("");
+    let bytes = from_base32(code)?;
+    let (header, moves) = match bytes.split_first() {
+        Some((&header, moves)) if moves.len() <= 8 => (header, moves),
+        _ => return Err(CodeError::InvalidLength),
+    };
+    let rules = match header >> 4 {
+        1 => Rules::Classic,
+        VERSION => {
+            let number = (header >> RULES_SHIFT) & RULES_MASK;
+            rules_from_number(number).ok_or(CodeError::UnknownRules(number))?
+        },
+        version => return Err(CodeError::UnknownVersion(version)),
+    };
+
+    let mut bits = [0; 8];
+    bits[..moves.len()].copy_from_slice(moves);
+    let history = History::from_bits(u64::from_le_bytes(bits)).ok_or(CodeError::InvalidMove)?;
+    let first_piece = if header & O_FIRST != 0 { Piece::O } else { Piece::X };
+    let mut game = Game::starting_with(first_piece).with_rules(rules.condition());
+    for (row, col) in history {
+        game.make_move(row, col).map_err(|_| CodeError::InvalidMove)?;
+    }
+    Ok(game)
+}
+
+// A link to a game, made by adding its code to the end of `base` (like the address of a page that
+// shows games)
+pub fn permalink(base: &str, code: &str) -> String {
+    let separator = if base.contains('?') { '&' } else { '?' };
+    format!("{}{}code={}", base, separator, code)
+}
+
+// The number that stands for the rules in a code. These never change once they are given out.
+fn rules_number(rules: Rules) -> u8 {
+    match rules {
+        Rules::Classic => 0,
+        Rules::Corners => 1,
+        Rules::Misere => 2,
+    }
+}
+
+fn rules_from_number(number: u8) -> Option<Rules> {
+    [Rules::Classic, Rules::Corners, Rules::Misere].iter().copied()
+        .find(|&rules| rules_number(rules) == number)
+}
+
+fn to_base32(bytes: &[u8]) -> String {
+    let mut code = String::new();
+    // The bits that haven't been turned into a character yet, and how many of them there are
+    let (mut buffer, mut bits) = (0u32, 0);
+    for &byte in bytes {
+        buffer = (buffer << 8) | byte as u32;
+        bits += 8;
+        while bits >= CHAR_BITS {
+            bits -= CHAR_BITS;
+            code.push(ALPHABET[(buffer >> bits) as usize & 31] as char);
+        }
+    }
+    // The bits left over are padded with zeros to make one last character
+    if bits > 0 {
+        code.push(ALPHABET[(buffer << (CHAR_BITS - bits)) as usize & 31] as char);
+    }
+    code
+}
+
+fn from_base32(code: &str) -> Result<Vec<u8>, CodeError> {
+    let mut bytes = Vec::new();
+    let (mut buffer, mut bits) = (0u32, 0);
+    for c in code.chars() {
+        let value = ALPHABET.iter().position(|&letter| letter as char == c.to_ascii_uppercase())
+            .ok_or(CodeError::InvalidCharacter(c))?;
+        buffer = (buffer << CHAR_BITS) | value as u32;
+        bits += CHAR_BITS;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+        }
+    }
+    // Whatever is left over is the padding from the last character
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn game(first_piece: Piece, moves: &[(usize, usize)]) -> Game {
+        let mut game = Game::starting_with(first_piece);
+        for &(row, col) in moves {
+            game.make_move(row, col).unwrap();
+        }
+        game
+    }
+
+    #[test]
+    fn codes() {
+        assert_eq!(encode(&Game::new()), "EA");
+        assert_eq!(encode(&game(Piece::O, &[])), "EE");
+        let moves = [(1, 1), (0, 0), (2, 2), (0, 2), (0, 1), (2, 1), (1, 0), (1, 2), (2, 0)];
+        let full = game(Piece::X, &moves);
+        assert_eq!(encode(&full).len(), 10);
+
+        for count in 0..=moves.len() {
+            for &piece in &[Piece::X, Piece::O] {
+                let game = game(piece, &moves[..count]);
+                let decoded = decode(&encode(&game)).unwrap();
+                assert_eq!(decoded.first_piece(), piece);
+                assert_eq!(decoded.moves(), game.moves());
+            }
+        }
+    }
+
+    #[test]
+    fn rules() {
+        // x holds all four corners, which only wins with the corners rules
+        let moves = [(0, 0), (0, 1), (0, 2), (1, 1), (2, 0), (1, 0), (2, 2)];
+        for &rules in &[Rules::Classic, Rules::Corners, Rules::Misere] {
+            let mut game = Game::new().with_rules(rules.condition());
+            for &(row, col) in &moves {
+                game.make_move(row, col).unwrap();
+            }
+            let decoded = decode(&encode(&game)).unwrap();
+            assert_eq!(Rules::of(&decoded), rules);
+            assert_eq!(decoded.moves(), game.moves());
+            assert_eq!(decoded.winner(), game.winner());
+        }
+
+        // Codes from before the rules were saved are games with the normal rules
+        let old = decode("CAKQ").unwrap();
+        assert_eq!(Rules::of(&old), Rules::Classic);
+        assert_eq!(encode(&old), "EAKQ");
+    }
+
+    #[test]
+    fn links() {
+        let game = game(Piece::X, &[(1, 1), (0, 0)]);
+        let code = encode(&game);
+        let link = permalink("https://example.com/ttt", &code);
+        assert_eq!(link, format!("https://example.com/ttt?code={}", code));
+        let link_with_query = permalink("https://example.com/?lang=fr", "CA");
+        assert_eq!(link_with_query, "https://example.com/?lang=fr&code=CA");
+        assert_eq!(decode(&link).unwrap().moves(), game.moves());
+        assert_eq!(decode(&format!(" {} ", code.to_lowercase())).unwrap().moves(), game.moves());
+    }
+
+    #[test]
+    fn invalid_codes() {
+        assert_eq!(decode("CA1").unwrap_err(), CodeError::InvalidCharacter('1'));
+        assert_eq!(decode("").unwrap_err(), CodeError::InvalidLength);
+        assert_eq!(decode("A").unwrap_err(), CodeError::InvalidLength);
+        assert_eq!(decode("GA").unwrap_err(), CodeError::UnknownVersion(3));
+        assert_eq!(decode(&to_base32(&[0x26])).unwrap_err(), CodeError::UnknownRules(3));
+        // The same tile twice, a tile that isn't on the board, and a gap before a move
+        for &moves in &[0x55, 0xa5, 0x50] {
+            assert_eq!(decode(&to_base32(&[0x10, moves])).unwrap_err(), CodeError::InvalidMove);
+        }
+    }
+}
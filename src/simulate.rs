@@ -0,0 +1,216 @@
+// Simulations play many games between two computer players as fast as possible, without showing
+// any of them. The results show how often each player wins and how long the games are, and the
+// time it takes shows how fast the engine is, which makes this a good way to measure the effect
+// of changes that are meant to make the engine faster.
+//
+// The games are played on every core at once (see parallel.rs). Each game gets its own random
+// number generator (see `Rng::stream`), so the results only depend on the seed, no matter how many
+// cores there are or which of them plays which game.
+//
+// Games can also be adjudicated: ended as soon as their result is certain, instead of being played
+// to the last move. That happens when nobody can get three in a row anymore (a dead position), or
+// when the solver says that one of the players wins (or that it is a tie) and the players that
+// have to play perfectly to get that result are perfect players. A perfect player never lets a
+// result that the solver promised slip away, so the result is the same either way and only the
+// moves that didn't matter are skipped.
+
+use std::iter::Sum;
+use std::ops::Range;
+use std::time::Duration;
+
+use engine::{self, Profile};
+use game::{Adjudication, Game, Piece, Winner};
+use parallel::*;
+use random::Rng;
+
+// No game of tic-tac-toe can have more moves than there are tiles
+const MAX_MOVES: usize = 9;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Simulation {
+    pub x_wins: u32,
+    pub o_wins: u32,
+    pub ties: u32,
+    /// How many games ended after each number of moves. `lengths[5]` is the number of games that
+    /// were won on the fifth move, which is as soon as a game can end.
+    pub lengths: [u32; MAX_MOVES + 1],
+    /// How many of the games were adjudicated instead of being played to the end
+    pub adjudicated: u32,
+    /// How long it took to play all of the games
+    pub time: Duration,
+}
+
+impl Simulation {
+    pub fn games(&self) -> u32 {
+        self.x_wins + self.o_wins + self.ties
+    }
+
+    // Adds a finished game to the results
+    pub fn record(&mut self, game: &Game) {
+        match game.winner() {
+            Some(Winner::X) => self.x_wins += 1,
+            Some(Winner::O) => self.o_wins += 1,
+            Some(Winner::Tie) => self.ties += 1,
+            None => unreachable!("only finished games should be recorded"),
+        }
+        self.lengths[game.moves().len()] += 1;
+        if game.adjudicated().is_some() {
+            self.adjudicated += 1;
+        }
+    }
+
+    // Adds the results of other games to these ones
+    pub fn add(&mut self, other: &Simulation) {
+        self.x_wins += other.x_wins;
+        self.o_wins += other.o_wins;
+        self.ties += other.ties;
+        for (length, count) in self.lengths.iter_mut().zip(&other.lengths) {
+            *length += count;
+        }
+        self.adjudicated += other.adjudicated;
+    }
+
+    // How many games were played every second, or None if no time has passed
+    pub fn games_per_second(&self) -> Option<f64> {
+        let seconds = self.time.as_secs_f64();
+        if seconds > 0.0 {
+            Some(f64::from(self.games()) / seconds)
+        }
+        else {
+            None
+        }
+    }
+}
+
+// Adding up the results of many simulations, which is how the games played on different cores are
+// put together
+impl Sum for Simulation {
+    fn sum<I: Iterator<Item = Simulation>>(simulations: I) -> Self {
+        simulations.fold(Simulation::default(), |mut total, simulation| {
+            total.add(&simulation);
+            total
+        })
+    }
+}
+
+// Plays a whole game between the two computer players, with x going first. If `adjudicate` is
+// true, the game ends as soon as its result is certain.
+pub fn play(x: Profile, o: Profile, adjudicate: bool, rng: &mut Rng) -> Game {
+    play_from(Game::new(), x, o, adjudicate, rng)
+}
+
+// Plays the rest of the game between the two computer players. This is how games that don't start
+// like every other one, like those played by homebrew rules (see rules.rs), are played.
+pub fn play_from(mut game: Game, x: Profile, o: Profile, adjudicate: bool,
+    rng: &mut Rng) -> Game {
+
+    while !game.is_finished() {
+        if let Some((winner, reason)) = adjudication(&game, x, o).filter(|_| adjudicate) {
+            game.adjudicate(winner, reason).expect("the game isn't over yet");
+            break;
+        }
+        let profile = if game.current_piece() == Piece::X { x } else { o };
+        let (row, col) = engine::choose_profile_move(&game, &profile, rng);
+        game.make_move(row, col).expect("the engine should only choose valid moves");
+    }
+    game
+}
+
+// The result that a game between the two computer players is certain to end with, and why, or None
+// if it still depends on how the game is played
+pub fn adjudication(game: &Game, x: Profile, o: Profile) -> Option<(Winner, Adjudication)> {
+    if game.is_finished() {
+        return None;
+    }
+    if game.is_dead() {
+        return Some((Winner::Tie, Adjudication::DeadPosition));
+    }
+    // A win only needs the winner to play perfectly, but a tie needs both players to, since a
+    // mistake by either of them could give the other one a win
+    let (x_perfect, o_perfect) = (x.is_perfect(), o.is_perfect());
+    let winner = engine::perfect_result(game);
+    let certain = match winner {
+        Winner::X => x_perfect,
+        Winner::O => o_perfect,
+        Winner::Tie => x_perfect && o_perfect,
+    };
+    if certain {
+        Some((winner, Adjudication::Solved))
+    }
+    else {
+        None
+    }
+}
+
+// Plays the games with the given numbers in parallel and adds up their results. Game `i` always
+// plays out the same way for the same seed, so splitting a simulation into several calls gives
+// the same results as doing it all at once.
+pub fn simulate(x: Profile, o: Profile, games: Range<u32>, seed: u64,
+    adjudicate: bool) -> Simulation {
+
+    games.into_par_iter().map(|i| {
+        let mut simulation = Simulation::default();
+        simulation.record(&play(x, o, adjudicate, &mut Rng::stream(seed, u64::from(i))));
+        simulation
+    }).sum()
+}
+
+// Plays the games with the given numbers in parallel, just like `simulate`, but keeps every game
+// instead of adding up their results
+pub fn games(x: Profile, o: Profile, games: Range<u32>, seed: u64) -> Vec<Game> {
+    games.into_par_iter()
+        .map(|i| play(x, o, false, &mut Rng::stream(seed, u64::from(i))))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use engine::Difficulty;
+
+    #[test]
+    fn results_and_lengths() {
+        let mut rng = Rng::new(3);
+        let mut simulation = Simulation::default();
+        let (x, o): (Profile, Profile) = (Difficulty::Easy.into(), Difficulty::Perfect.into());
+        for _ in 0..20 {
+            simulation.record(&play(x, o, false, &mut rng));
+        }
+        assert_eq!(simulation.games(), 20);
+        // Random moves can never beat a perfect player
+        assert_eq!(simulation.x_wins, 0);
+        assert_eq!(simulation.lengths.iter().sum::<u32>(), 20);
+        assert_eq!(simulation.lengths[..5], [0; 5]);
+        assert_eq!(simulation.games_per_second(), None);
+    }
+
+    #[test]
+    fn parallel_results_are_the_same() {
+        let (x, o): (Profile, Profile) = (Difficulty::Easy.into(), Difficulty::Medium.into());
+        let all = simulate(x, o, 0..200, 9, false);
+        let mut halves = simulate(x, o, 0..100, 9, false);
+        halves.add(&simulate(x, o, 100..200, 9, false));
+        assert_eq!(all, halves);
+        assert_eq!(all.games(), 200);
+        assert_ne!(all, simulate(x, o, 0..200, 10, false));
+    }
+
+    #[test]
+    fn adjudicated_games() {
+        // Two perfect players always tie, which the solver knows before the first move
+        let perfect: Profile = Difficulty::Perfect.into();
+        let game = play(perfect, perfect, true, &mut Rng::new(1));
+        assert_eq!(game.winner(), Some(Winner::Tie));
+        assert_eq!((game.adjudicated(), game.moves().len()), (Some(Adjudication::Solved), 0));
+
+        // Adjudicating never changes the results, only how long the games are
+        let (x, o) = (Difficulty::Easy.into(), perfect);
+        let played = simulate(x, o, 0..300, 4, false);
+        let adjudicated = simulate(x, o, 0..300, 4, true);
+        assert_eq!((played.x_wins, played.o_wins, played.ties),
+            (adjudicated.x_wins, adjudicated.o_wins, adjudicated.ties));
+        assert_eq!(played.adjudicated, 0);
+        assert!(adjudicated.adjudicated > 0);
+    }
+}
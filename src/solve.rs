@@ -0,0 +1,217 @@
+// Solves positions by searching every possible continuation of the game. This is what the perfect
+// computer player is built from, but it doesn't run while playing: build.rs runs it on every
+// position when the program is compiled, and the engine looks the answers up in the table that
+// comes out of it (see engine.rs).
+//
+// Tic-tac-toe is small enough that searching the whole game is quick: there are fewer than
+// 550,000 ways to play it out from an empty board.
+//
+// The search reports what it is doing to a `Hooks`, which is how `profile` measures where the
+// time goes (see profile.rs). The hooks are a generic type parameter, so Rust compiles a separate
+// copy of the search for each kind of hooks. The normal search uses `NoHooks`, whose hooks are
+// empty and compile to nothing, so measuring costs nothing unless it is asked for.
+
+use std::cmp::Ordering;
+
+use game::{Game, Piece, Winner};
+use tt::{self, Table};
+
+// The parts of the work that the search does on each position
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    /// Checking whether the game is over and scoring it if it is
+    Evaluation,
+    /// Looking for the position in the transposition table and saving its score there
+    TableProbe,
+    /// Finding the empty tiles
+    MoveGeneration,
+    /// Copying the position and making a move in the copy
+    MakeMove,
+}
+
+impl Phase {
+    // Every phase, in the order that the search goes through them
+    pub const ALL: [Phase; 4] =
+        [Phase::Evaluation, Phase::TableProbe, Phase::MoveGeneration, Phase::MakeMove];
+
+    // The position of this phase in ALL, so that the phases can be used to index arrays
+    pub fn index(self) -> usize {
+        self as usize
+    }
+}
+
+// Lets something watch the search while it runs. Both methods do nothing unless they are
+// overridden. `ply` is how many moves below the position the search started from the position
+// is.
+pub trait Hooks {
+    // Called once for every position that the search visits
+    fn visit(&mut self, _ply: usize) {}
+
+    // Does one part of the work on a position and returns its result. Overriding this lets the
+    // work be timed.
+    fn phase<T, F: FnOnce() -> T>(&mut self, _phase: Phase, _ply: usize, work: F) -> T {
+        work()
+    }
+}
+
+// The hooks of a search that nobody is watching
+pub struct NoHooks;
+
+impl Hooks for NoHooks {}
+
+// Every tile that doesn't have a piece on it yet
+pub fn empty_tiles(game: &Game) -> Vec<(usize, usize)> {
+    let mut tiles = Vec::new();
+    for (row, row_tiles) in game.tiles().iter().enumerate() {
+        for (col, tile) in row_tiles.iter().enumerate() {
+            if tile.is_none() {
+                tiles.push((row, col));
+            }
+        }
+    }
+    tiles
+}
+
+// All of the moves that lead to the best possible result for the current player
+pub fn best_moves(game: &Game, table: &mut Table) -> Vec<(usize, usize)> {
+    best_moves_with(game, table, &mut NoHooks)
+}
+
+// The same as best_moves, except that the search reports what it does to `hooks`
+pub fn best_moves_with<H: Hooks>(game: &Game, table: &mut Table,
+    hooks: &mut H) -> Vec<(usize, usize)> {
+
+    let piece = game.current_piece();
+    let mut best_score = i32::MIN;
+    let mut best = Vec::new();
+
+    hooks.visit(0);
+    let tiles = hooks.phase(Phase::MoveGeneration, 0, || empty_tiles(game));
+    for (row, col) in tiles {
+        let next = hooks.phase(Phase::MakeMove, 0, || play(game, row, col));
+        // The score is from the point of view of the player who just moved
+        let score = -score(&next, piece.other(), table, hooks, 1);
+        if score > best_score {
+            best_score = score;
+            best.clear();
+        }
+        if score == best_score {
+            best.push((row, col));
+        }
+    }
+    best
+}
+
+// How the game ends if both players play perfectly from here on
+pub fn perfect_result(game: &Game, table: &mut Table) -> Winner {
+    if let Some(winner) = game.winner() {
+        return winner;
+    }
+    let piece = game.current_piece();
+    // A positive score means that `piece` wins, and a negative one means that the other player does
+    match score(game, piece, table, &mut NoHooks, 0).cmp(&0) {
+        Ordering::Greater => winner(piece),
+        Ordering::Less => winner(piece.other()),
+        Ordering::Equal => Winner::Tie,
+    }
+}
+
+// How many more moves the current player needs to win if both players play perfectly, counting
+// the winning move, or None if they can't force a win
+pub fn moves_to_win(game: &Game, table: &mut Table) -> Option<usize> {
+    if game.is_finished() {
+        return None;
+    }
+    let piece = game.current_piece();
+    let score = score(game, piece, table, &mut NoHooks, 0);
+    if score <= 0 {
+        return None;
+    }
+    // A win on the player's nth move leaves 2n - 1 fewer tiles than there are now, and `score`
+    // gives it a score of one more than the number of tiles that are left after it
+    let tiles_left = 9 - game.moves().len() as i32;
+    Some(((tiles_left + 2 - score) / 2) as usize)
+}
+
+// A copy of the game with one more move made in it
+fn play(game: &Game, row: usize, col: usize) -> Game {
+    let mut next = game.clone();
+    next.make_move(row, col).expect("empty tiles are always valid moves");
+    next
+}
+
+fn winner(piece: Piece) -> Winner {
+    match piece {
+        Piece::X => Winner::X,
+        Piece::O => Winner::O,
+    }
+}
+
+// How good the game is for `piece`, which is the player about to move. Winning is worth more the
+// sooner it happens (and losing costs less the later it happens), so the engine wins as quickly
+// as it can and makes its opponent work for a win it can't prevent.
+//
+// This is the "negamax" form of the minimax algorithm: a position is as good for one player as it
+// is bad for the other, so we can flip the sign instead of writing two versions of this function.
+// More info: https://en.wikipedia.org/wiki/Negamax
+//
+// Positions that were already scored are looked up in the table instead of being searched again.
+fn score<H: Hooks>(game: &Game, piece: Piece, table: &mut Table, hooks: &mut H,
+    ply: usize) -> i32 {
+
+    hooks.visit(ply);
+    // The score only depends on how many tiles are left, which is 9 minus the moves made so far
+    let tiles_left = 9 - game.moves().len() as i32;
+    match hooks.phase(Phase::Evaluation, ply, || game.winner()) {
+        Some(Winner::Tie) => return 0,
+        // Usually only the player who just moved can have won, but homebrew rules (see
+        // WinCondition in game.rs) can make a move lose the game instead
+        Some(won) if won == winner(piece) => return tiles_left + 1,
+        Some(_) => return -(tiles_left + 1),
+        None => {},
+    }
+
+    // Working out the key is part of looking the position up
+    let (key, saved) = hooks.phase(Phase::TableProbe, ply, || {
+        let key = tt::key(game);
+        (key, table.get(key))
+    });
+    if let Some(score) = saved {
+        return score;
+    }
+    let tiles = hooks.phase(Phase::MoveGeneration, ply, || empty_tiles(game));
+    let best = tiles.into_iter().map(|(row, col)| {
+        let next = hooks.phase(Phase::MakeMove, ply, || play(game, row, col));
+        -score(&next, piece.other(), table, hooks, ply + 1)
+    }).max().expect("a game that isn't finished has an empty tile");
+    hooks.phase(Phase::TableProbe, ply, || table.insert(key, best, tiles_left as u8));
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn perfect_stops_a_fork() {
+        // x has opposite corners with o in the center. Taking a corner lets x fork, so o has to
+        // play on an edge instead.
+        let mut game = Game::new();
+        for &(row, col) in &[(0, 0), (1, 1), (2, 2)] {
+            game.make_move(row, col).unwrap();
+        }
+        let mut moves = best_moves(&game, &mut Table::with_megabytes(0));
+        moves.sort();
+        assert_eq!(moves, vec![(0, 1), (1, 0), (1, 2), (2, 1)]);
+        // Remembering the positions doesn't change which moves are best, even when the table
+        // already has the positions from the search before
+        let mut table = Table::with_megabytes(1);
+        for _ in 0..2 {
+            table.new_search();
+            let mut moves = best_moves(&game, &mut table);
+            moves.sort();
+            assert_eq!(moves, vec![(0, 1), (1, 0), (1, 2), (2, 1)]);
+        }
+        assert_eq!(perfect_result(&game, &mut table), Winner::Tie);
+    }
+}
@@ -0,0 +1,204 @@
+// The speedrun for the `speedrun` command. The player has to beat the computer a few times in a
+// row of games, the gauntlet, as fast as they can. Games that aren't won don't end the run, they
+// only cost time, since the player has to keep playing until they have won enough games.
+//
+// The time is measured with `Instant` around every game, so it is as precise as the computer's
+// clock and doesn't include the time between games. It does include pauses: just like in any
+// other speedrun, the clock keeps running no matter what.
+//
+// Each player's best time against each difficulty is kept in the preferences file (see
+// prefs.rs), so every profile on the computer has a place on the leaderboard:
+//
+//     speedrun_easy = 41235 Ada
+//     speedrun_medium = 93810 Grace H
+//
+// The times are in milliseconds.
+
+use std::fmt;
+use std::time::Duration;
+
+use engine::Difficulty;
+use game::{Game, Piece, Winner};
+
+// The number of games that have to be won to finish the gauntlet
+pub const WINS: u32 = 3;
+
+// Only the easy and medium computers can be beaten, so those are the only ones to race against
+pub fn can_race(difficulty: Difficulty) -> bool {
+    match difficulty {
+        Difficulty::Easy | Difficulty::Medium => true,
+        Difficulty::Perfect => false,
+    }
+}
+
+// A gauntlet that is being played
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Run {
+    /// The number of games played so far
+    pub games: u32,
+    /// The number of those games that the player won
+    pub wins: u32,
+    /// The time of every game so far, added up
+    pub time: Duration,
+}
+
+impl Run {
+    // Adds a finished game to the run, along with how long it took
+    pub fn record(&mut self, game: &Game, player: Piece, time: Duration) {
+        self.games += 1;
+        if matches!((game.winner(), player),
+            (Some(Winner::X), Piece::X) | (Some(Winner::O), Piece::O)) {
+            self.wins += 1;
+        }
+        self.time += time;
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.wins >= WINS
+    }
+
+    // The player and the computer take turns going first, starting with the player
+    pub fn next_first_piece(&self, player: Piece) -> Piece {
+        if self.games.is_multiple_of(2) {
+            player
+        }
+        else {
+            player.other()
+        }
+    }
+}
+
+// A player's best time against one of the difficulties
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Best {
+    pub name: String,
+    pub difficulty: Difficulty,
+    pub time: Duration,
+}
+
+// How a finished run compares to the player's best time
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Finish {
+    /// The run is the player's best time, and this was the best time before it (if there was one)
+    NewBest { previous: Option<Duration> },
+    /// The player has been faster before
+    Slower { best: Duration },
+}
+
+// The best time of every player against every difficulty
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Leaderboard {
+    bests: Vec<Best>,
+}
+
+impl Leaderboard {
+    pub fn bests(&self) -> &[Best] {
+        &self.bests
+    }
+
+    // Keeps the time of a finished run if it is the player's best
+    pub fn record(&mut self, name: &str, difficulty: Difficulty, time: Duration) -> Finish {
+        let best = self.bests.iter_mut()
+            .find(|best| best.name == name && best.difficulty == difficulty);
+        match best {
+            Some(best) if best.time <= time => Finish::Slower {best: best.time},
+            Some(best) => {
+                let previous = best.time;
+                best.time = time;
+                Finish::NewBest {previous: Some(previous)}
+            },
+            None => {
+                self.bests.push(Best {name: name.to_string(), difficulty, time});
+                Finish::NewBest {previous: None}
+            },
+        }
+    }
+
+    // The best times against a difficulty, from the fastest to the slowest
+    pub fn ranking(&self, difficulty: Difficulty) -> Vec<&Best> {
+        let mut ranking: Vec<_> = self.bests.iter()
+            .filter(|best| best.difficulty == difficulty)
+            .collect();
+        // Sorting is stable, so players with the same time stay in the order they got it in
+        ranking.sort_by_key(|best| best.time);
+        ranking
+    }
+
+    // Reads a `speedrun_*` line of the preferences file. Lines that don't make sense are skipped,
+    // just like every other line of the preferences.
+    pub fn parse_line(&mut self, key: &str, value: &str) {
+        let difficulty = match key.strip_prefix("speedrun_").and_then(Difficulty::from_name) {
+            Some(difficulty) if can_race(difficulty) => difficulty,
+            _ => return,
+        };
+        let mut parts = value.splitn(2, ' ');
+        let millis = parts.next().and_then(|millis| millis.parse().ok());
+        if let (Some(millis), Some(name)) = (millis, parts.next()) {
+            let time = Duration::from_millis(millis);
+            self.record(name.trim(), difficulty, time);
+        }
+    }
+}
+
+// Shows a time down to the millisecond, like "1:02.345". Just like `ClockTime`, this is a
+// newtype around Duration so that it can implement Display.
+#[derive(Debug, Clone, Copy)]
+pub struct RunTime(pub Duration);
+
+impl fmt::Display for RunTime {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let millis = self.0.as_millis();
+        let seconds = millis / 1000;
+        write!(f, "{}:{:02}.{:03}", seconds / 60, seconds % 60, millis % 1000)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gauntlet() {
+        let mut won = Game::new();
+        won.forfeit(Piece::O).unwrap();
+        let mut lost = Game::starting_with(Piece::O);
+        lost.forfeit(Piece::X).unwrap();
+
+        let mut run = Run::default();
+        assert_eq!(run.next_first_piece(Piece::X), Piece::X);
+        run.record(&won, Piece::X, Duration::from_millis(1500));
+        assert_eq!(run.next_first_piece(Piece::X), Piece::O);
+        run.record(&lost, Piece::X, Duration::from_millis(2500));
+        run.record(&won, Piece::X, Duration::from_millis(1000));
+        assert!(!run.is_finished());
+        run.record(&won, Piece::X, Duration::from_millis(1000));
+        assert!(run.is_finished());
+        assert_eq!(run, Run {games: 4, wins: 3, time: Duration::from_secs(6)});
+    }
+
+    #[test]
+    fn best_times() {
+        let mut leaderboard = Leaderboard::default();
+        let secs = Duration::from_secs;
+        assert_eq!(leaderboard.record("Ada", Difficulty::Easy, secs(50)),
+            Finish::NewBest {previous: None});
+        assert_eq!(leaderboard.record("Ada", Difficulty::Easy, secs(60)),
+            Finish::Slower {best: secs(50)});
+        assert_eq!(leaderboard.record("Ada", Difficulty::Easy, secs(40)),
+            Finish::NewBest {previous: Some(secs(50))});
+        leaderboard.record("Grace H", Difficulty::Easy, secs(30));
+        leaderboard.record("Alan", Difficulty::Medium, secs(20));
+
+        let names: Vec<_> = leaderboard.ranking(Difficulty::Easy).iter()
+            .map(|best| &best.name[..])
+            .collect();
+        assert_eq!(names, ["Grace H", "Ada"]);
+        assert_eq!(leaderboard.ranking(Difficulty::Perfect), Vec::<&Best>::new());
+    }
+
+    #[test]
+    fn precise_times() {
+        assert_eq!(RunTime(Duration::from_millis(62345)).to_string(), "1:02.345");
+        assert_eq!(RunTime(Duration::from_micros(9_999_999)).to_string(), "0:09.999");
+    }
+}
@@ -0,0 +1,236 @@
+// Lifetime stats for every player who plays under a name (see `--x-name` and the `name`
+// preference). The name is the player's profile: every game they play is saved to the archive
+// with their name on it, and `stats` adds up those games. Nothing else is stored, so the stats can
+// always be worked out again from the archive and never disagree with it.
+
+use std::collections::BTreeMap;
+
+use archive::ArchivedGame;
+use game::{Game, Piece, Winner};
+
+// How a single game went for one of its players
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Win,
+    Loss,
+    Tie,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PlayerStats {
+    pub wins: u32,
+    /// Games lost, including the ones lost by running out of time
+    pub losses: u32,
+    pub ties: u32,
+    /// The outcome of the player's most recent game and the number of games in a row that it
+    /// has been the same
+    pub streak: Option<(Outcome, u32)>,
+    /// The most games the player has won in a row
+    pub best_win_streak: u32,
+    /// How many times the player started with each move, in the games where they went first.
+    /// A BTreeMap keeps the moves in board order.
+    openings: BTreeMap<(usize, usize), u32>,
+}
+
+impl PlayerStats {
+    pub fn games(&self) -> u32 {
+        self.wins + self.losses + self.ties
+    }
+
+    // The move the player starts with most often and how many times they did, or None if they
+    // never went first. Ties go to the move that comes first on the board.
+    pub fn favorite_opening(&self) -> Option<((usize, usize), u32)> {
+        // `max_by_key` picks the last of several equal moves, so we go through them backwards
+        self.openings.iter().rev().max_by_key(|&(_, &count)| count)
+            .map(|(&opening, &count)| (opening, count))
+    }
+
+    fn record(&mut self, outcome: Outcome, opening: Option<(usize, usize)>) {
+        match outcome {
+            Outcome::Win => self.wins += 1,
+            Outcome::Loss => self.losses += 1,
+            Outcome::Tie => self.ties += 1,
+        }
+
+        let length = match self.streak {
+            Some((last, length)) if last == outcome => length + 1,
+            _ => 1,
+        };
+        self.streak = Some((outcome, length));
+        if outcome == Outcome::Win {
+            self.best_win_streak = self.best_win_streak.max(length);
+        }
+
+        if let Some(opening) = opening {
+            // `entry` finds the count for this move, or starts it at zero the first time
+            *self.openings.entry(opening).or_insert(0) += 1;
+        }
+    }
+}
+
+// How the games between two players went for the first of them
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Record {
+    pub wins: u32,
+    pub losses: u32,
+    pub ties: u32,
+}
+
+impl Record {
+    pub fn games(&self) -> u32 {
+        self.wins + self.losses + self.ties
+    }
+
+    fn record(&mut self, outcome: Outcome) {
+        match outcome {
+            Outcome::Win => self.wins += 1,
+            Outcome::Loss => self.losses += 1,
+            Outcome::Tie => self.ties += 1,
+        }
+    }
+}
+
+// The games two players played against each other, from the point of view of the first player
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HeadToHead {
+    /// The games where the first player played x
+    pub as_x: Record,
+    /// The games where the first player played o
+    pub as_o: Record,
+    /// The number of moves in all of the games together
+    moves: usize,
+}
+
+impl HeadToHead {
+    pub fn total(&self) -> Record {
+        Record {
+            wins: self.as_x.wins + self.as_o.wins,
+            losses: self.as_x.losses + self.as_o.losses,
+            ties: self.as_x.ties + self.as_o.ties,
+        }
+    }
+
+    // The average number of moves in a game between the players, or zero if they never played
+    pub fn average_moves(&self) -> f64 {
+        let games = self.total().games();
+        if games == 0 {
+            0.0
+        }
+        else {
+            self.moves as f64 / f64::from(games)
+        }
+    }
+}
+
+// Finds the games between the players named `a` and `b`, whichever pieces they played
+pub fn head_to_head(games: &[ArchivedGame], a: &str, b: &str) -> HeadToHead {
+    let mut result = HeadToHead::default();
+    for archived in games {
+        let players = &archived.players;
+        let names = (players.profile(Piece::X).name.as_deref(),
+            players.profile(Piece::O).name.as_deref());
+        let (record, a_piece) = match names {
+            (Some(x), Some(o)) if x == a && o == b => (&mut result.as_x, Piece::X),
+            (Some(x), Some(o)) if x == b && o == a => (&mut result.as_o, Piece::O),
+            _ => continue,
+        };
+        record.record(outcome(&archived.game, a_piece));
+        result.moves += archived.game.moves().len();
+    }
+    result
+}
+
+// How the finished game went for the player using `piece`
+fn outcome(game: &Game, piece: Piece) -> Outcome {
+    match (game.winner(), piece) {
+        (Some(Winner::Tie), _) => Outcome::Tie,
+        (Some(Winner::X), Piece::X) | (Some(Winner::O), Piece::O) => Outcome::Win,
+        (Some(_), _) => Outcome::Loss,
+        // The archive only gives us finished games
+        (None, _) => unreachable!("archived games should be finished"),
+    }
+}
+
+// Adds up the stats of every named player from the games in the archive, which must be in the
+// order they were played. The players are sorted by name.
+pub fn compute(games: &[ArchivedGame]) -> BTreeMap<String, PlayerStats> {
+    let mut stats = BTreeMap::new();
+    for archived in games {
+        let game = &archived.game;
+        for &piece in &[Piece::X, Piece::O] {
+            // Players without a name don't have a profile to add the game to
+            let name = match archived.players.profile(piece).name {
+                Some(ref name) => name,
+                None => continue,
+            };
+            let outcome = outcome(game, piece);
+            let opening = if game.first_piece() == piece {
+                game.moves().first()
+            }
+            else {
+                None
+            };
+            stats.entry(name.clone()).or_insert_with(PlayerStats::default)
+                .record(outcome, opening);
+        }
+    }
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use player::{PlayerProfile, Players};
+
+    // Plays the moves with Ada as x and Grace as o
+    fn archived(first: Piece, moves: &[(usize, usize)]) -> ArchivedGame {
+        let mut game = Game::starting_with(first);
+        for &(row, col) in moves {
+            game.make_move(row, col).unwrap();
+        }
+        let profile = |name: &str| PlayerProfile {name: Some(name.to_string()), glyph: None};
+        ArchivedGame {game, players: Players::new(profile("Ada"), profile("Grace"))}
+    }
+
+    #[test]
+    fn wins_losses_and_streaks() {
+        // x takes the top row
+        let x_wins = [(1, 1), (1, 0), (0, 0), (2, 2), (0, 1), (2, 1), (0, 2)];
+        let tie = [(1, 1), (0, 0), (0, 1), (2, 1), (1, 0), (1, 2), (0, 2), (2, 0), (2, 2)];
+        let games = [
+            archived(Piece::X, &x_wins),
+            archived(Piece::X, &x_wins),
+            archived(Piece::O, &tie),
+        ];
+        let stats = compute(&games);
+
+        let ada = &stats["Ada"];
+        assert_eq!((ada.wins, ada.losses, ada.ties, ada.games()), (2, 0, 1, 3));
+        assert_eq!(ada.streak, Some((Outcome::Tie, 1)));
+        assert_eq!(ada.best_win_streak, 2);
+        assert_eq!(ada.favorite_opening(), Some(((1, 1), 2)));
+
+        let grace = &stats["Grace"];
+        assert_eq!((grace.wins, grace.losses, grace.ties), (0, 2, 1));
+        assert_eq!(grace.best_win_streak, 0);
+        assert_eq!(grace.favorite_opening(), Some(((1, 1), 1)));
+    }
+
+    #[test]
+    fn head_to_head_by_piece() {
+        let x_wins = [(1, 1), (1, 0), (0, 0), (2, 2), (0, 1), (2, 1), (0, 2)];
+        let mut swapped = archived(Piece::X, &x_wins);
+        swapped.players.swap();
+        let games = [archived(Piece::X, &x_wins), swapped];
+
+        let ada = head_to_head(&games, "Ada", "Grace");
+        assert_eq!(ada.as_x, Record {wins: 1, losses: 0, ties: 0});
+        assert_eq!(ada.as_o, Record {wins: 0, losses: 1, ties: 0});
+        assert_eq!(ada.total().games(), 2);
+        assert_eq!(ada.average_moves(), 7.0);
+
+        let grace = head_to_head(&games, "Grace", "Ada");
+        assert_eq!(grace.as_x, Record {wins: 1, losses: 0, ties: 0});
+        assert_eq!(head_to_head(&games, "Ada", "Alan").total().games(), 0);
+    }
+}
@@ -0,0 +1,191 @@
+// Everything that the program saves goes through here, so that a crash, a full disk or a power cut
+// in the middle of saving never leaves half of a file behind. A file is never changed in place:
+//
+// 1. The new contents are written to a temporary file next to it (`prefs.txt.tmp`)
+// 2. The temporary file is flushed all the way to the disk with `sync_all`
+// 3. The temporary file is renamed over the old one. A rename is atomic, so anyone reading the
+//    file sees either all of the old contents or all of the new ones, never a mix of both.
+//
+// Files that are kept around (the preferences, the archive and the summary CSV) are saved with
+// `save`, which keeps the previous version in a backup file (`prefs.txt.bak`) before replacing
+// it. Files that are exported once (like images) are written with `write`, without a backup.
+//
+// Every file that is saved here is text, so a file that isn't valid UTF-8 or that has NUL bytes in
+// it (which is what a disk usually leaves behind when writing was cut off) is corrupt. `read` moves
+// a corrupt file out of the way (`prefs.txt.corrupt-1792108800`, with the time it was found)
+// instead of failing, puts the backup back in its place if there is one, and tells the caller what
+// it did, so that they can let the player know.
+
+use std::ffi::OsString;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// What `read` found in a file
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Loaded {
+    /// The text of the file, or None if there is no file yet
+    pub text: Option<String>,
+    /// What was done about the file if it was corrupt
+    pub recovery: Option<Recovery>,
+}
+
+// What was done about a corrupt file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Recovery {
+    /// Where the corrupt file was moved to, so that nothing in it is lost for good
+    pub quarantined: PathBuf,
+    /// True if the backup was put in its place, or false if there was no usable backup and the
+    /// file starts over empty
+    pub restored: bool,
+}
+
+// Reads the text of a file, recovering from the backup if the file is corrupt (see above). A
+// missing file isn't an error, since nothing has been saved yet the first time the program runs.
+pub fn read(path: &Path) -> io::Result<Loaded> {
+    let bytes = match fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(ref error) if error.kind() == io::ErrorKind::NotFound => return Ok(Loaded::default()),
+        Err(error) => return Err(error),
+    };
+    if let Some(text) = to_text(bytes) {
+        return Ok(Loaded {text: Some(text), recovery: None});
+    }
+
+    let quarantined = with_suffix(path, &format!(".corrupt-{}", now()));
+    fs::rename(path, &quarantined)?;
+    // A backup that is corrupt too is left where it is, since it might still be of use to someone
+    let backup = match fs::read(backup_path(path)) {
+        Ok(bytes) => to_text(bytes),
+        Err(ref error) if error.kind() == io::ErrorKind::NotFound => None,
+        Err(error) => return Err(error),
+    };
+    if let Some(ref text) = backup {
+        write(path, text.as_bytes())?;
+    }
+    let recovery = Recovery {quarantined, restored: backup.is_some()};
+    Ok(Loaded {text: backup, recovery: Some(recovery)})
+}
+
+// Replaces the contents of a file, keeping its previous contents in the backup file. A corrupt
+// file is never backed up, so that it can't take the place of a good backup.
+pub fn save(path: &Path, contents: &[u8]) -> io::Result<()> {
+    match fs::read(path) {
+        Ok(previous) => if to_text(previous.clone()).is_some() {
+            write(&backup_path(path), &previous)?;
+        },
+        Err(ref error) if error.kind() == io::ErrorKind::NotFound => {},
+        Err(error) => return Err(error),
+    }
+    write(path, contents)
+}
+
+// Replaces the contents of a file all at once, without keeping a backup
+pub fn write(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let temporary = with_suffix(path, ".tmp");
+    let mut file = File::create(&temporary)?;
+    file.write_all(contents)?;
+    file.sync_all()?;
+    // The file must be closed before it is renamed on some platforms
+    drop(file);
+    fs::rename(&temporary, path)?;
+    sync_dir(path)
+}
+
+// The file that `save` keeps the previous contents of the file at `path` in
+pub fn backup_path(path: &Path) -> PathBuf {
+    with_suffix(path, ".bak")
+}
+
+// The contents of a file as text, or None if they are corrupt
+fn to_text(bytes: Vec<u8>) -> Option<String> {
+    String::from_utf8(bytes).ok().filter(|text| !text.contains('\0'))
+}
+
+// Adds to the end of the file name (unlike `Path::with_extension`, which replaces the extension)
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = OsString::from(path.as_os_str());
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+// The rename is only certain to survive a power cut once the directory that holds the file is
+// flushed to the disk too. Windows doesn't allow opening a directory, and flushes it by itself.
+#[cfg(unix)]
+fn sync_dir(path: &Path) -> io::Result<()> {
+    match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => File::open(dir)?.sync_all(),
+        _ => File::open(".")?.sync_all(),
+    }
+}
+
+#[cfg(not(unix))]
+fn sync_dir(_path: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+// The number of seconds since 1970-01-01 (UTC), which keeps the quarantined files apart
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |time| time.as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::env;
+    use std::process;
+
+    // A directory of its own for each test, since the tests run at the same time
+    fn dir(test: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("tic-tac-toe-storage-{}-{}", test, process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn saves_keep_one_backup() {
+        let dir = dir("backup");
+        let path = dir.join("prefs.txt");
+        assert_eq!(read(&path).unwrap(), Loaded::default());
+
+        save(&path, b"name = Ada\n").unwrap();
+        assert!(!backup_path(&path).exists());
+        save(&path, b"name = Grace\n").unwrap();
+        save(&path, b"name = Alan\n").unwrap();
+        assert_eq!(read(&path).unwrap().text.as_deref(), Some("name = Alan\n"));
+        assert_eq!(fs::read_to_string(backup_path(&path)).unwrap(), "name = Grace\n");
+        // Nothing is left behind of the temporary files
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 2);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn corrupt_files_are_quarantined() {
+        let dir = dir("corrupt");
+        let path = dir.join("archive.txt");
+        save(&path, b"game first=x winner=x moves=2B\n").unwrap();
+        save(&path, b"game first=x winner=x moves=2B\ngame first=o\0\0\0").unwrap();
+        // The corrupt file doesn't replace the good backup when it is saved over
+        save(&path, b"\xff\xfe").unwrap();
+
+        let loaded = read(&path).unwrap();
+        assert_eq!(loaded.text.as_deref(), Some("game first=x winner=x moves=2B\n"));
+        let recovery = loaded.recovery.unwrap();
+        assert!(recovery.restored);
+        assert_eq!(fs::read(&recovery.quarantined).unwrap(), b"\xff\xfe");
+        // The backup is in place again, so the next read has nothing to recover from
+        assert_eq!(read(&path).unwrap().recovery, None);
+
+        // Without a backup, the file starts over
+        let path = dir.join("summary.csv");
+        fs::write(&path, b"\0\0\0\0").unwrap();
+        let loaded = read(&path).unwrap();
+        assert_eq!(loaded.text, None);
+        assert!(!loaded.recovery.unwrap().restored);
+        assert!(!path.exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,241 @@
+// Adds up the games played during a session so that a summary can be shown when the program exits:
+// how many games were played, how each player did, how long the games were and how long the
+// session took. With `--summary-csv`, the summary is also added to a CSV file that can be opened
+// in a spreadsheet to follow how the sessions go over time.
+//
+// Games finish deep inside the program and the program can exit from several places (quitting,
+// Ctrl-C, or the input being closed), so just like the recording in cast.rs, the summary is kept
+// in a `static` instead of being passed around. It hears about the games that finish from the
+// event bus (see bus.rs).
+
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use bus::{self, Event, Subscriber};
+use clock::ClockTime;
+use csv;
+use game::{Game, Piece, Winner};
+use locale::{Lang, Message};
+use player::Players;
+use storage::{self, Recovery};
+
+static SESSION: OnceLock<Mutex<Session>> = OnceLock::new();
+
+// The first line of the CSV file, which names each column
+const CSV_HEADER: &str = "timestamp,player,wins,losses,ties,games,average_moves,total_seconds";
+
+// How the games went for one player
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Tally {
+    pub wins: u32,
+    pub losses: u32,
+    pub ties: u32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Summary {
+    /// The number of games that were finished
+    pub games: u32,
+    /// The number of moves made in all of the finished games together
+    pub moves: usize,
+    /// How long the session took
+    pub duration: Duration,
+    /// How each player did. Players without a name are called by their piece, so "x" is whoever
+    /// played x.
+    pub players: BTreeMap<String, Tally>,
+}
+
+impl Summary {
+    fn new() -> Self {
+        Self {games: 0, moves: 0, duration: Duration::default(), players: BTreeMap::new()}
+    }
+
+    // Adds a finished game to the summary
+    pub fn record(&mut self, game: &Game, players: &Players) {
+        self.games += 1;
+        self.moves += game.moves().len();
+        for &piece in &[Piece::X, Piece::O] {
+            let tally = self.players.entry(players.name(piece).to_string()).or_default();
+            match (game.winner(), piece) {
+                (Some(Winner::Tie), _) => tally.ties += 1,
+                (Some(Winner::X), Piece::X) | (Some(Winner::O), Piece::O) => tally.wins += 1,
+                (Some(_), _) => tally.losses += 1,
+                (None, _) => unreachable!("only finished games should be added to the summary"),
+            }
+        }
+    }
+
+    // The average number of moves in a game, or zero if no games were played
+    pub fn average_moves(&self) -> f64 {
+        if self.games == 0 {
+            0.0
+        }
+        else {
+            self.moves as f64 / f64::from(self.games)
+        }
+    }
+
+    // The lines to show at the end of the session
+    pub fn lines(&self, lang: Lang) -> Vec<String> {
+        let mut lines = vec![lang.text(Message::SessionSummary {
+            games: self.games,
+            average_moves: self.average_moves(),
+            duration: ClockTime(self.duration),
+        })];
+        for (player, tally) in &self.players {
+            lines.push(lang.text(Message::SessionPlayer {
+                player,
+                wins: tally.wins,
+                losses: tally.losses,
+                ties: tally.ties,
+            }));
+        }
+        lines
+    }
+
+    // Writes one row per player to the CSV file, after the header if the file is new. `timestamp`
+    // is when the session ended, in seconds since the start of 1970.
+    pub fn write_csv<W: Write>(&self, mut out: W, header: bool, timestamp: u64) -> io::Result<()> {
+        if header {
+            writeln!(out, "{}", CSV_HEADER)?;
+        }
+        for (player, tally) in &self.players {
+            writeln!(out, "{},{},{},{},{},{},{:.1},{}", timestamp, csv::field(player), tally.wins,
+                tally.losses, tally.ties, self.games, self.average_moves(),
+                self.duration.as_secs())?;
+        }
+        Ok(())
+    }
+}
+
+struct Session {
+    summary: Summary,
+    started: Instant,
+    lang: Lang,
+    /// Where to show the summary. Programs reading JSON from stdout don't expect anything else
+    /// there, so the summary goes to stderr instead.
+    to_stderr: bool,
+    csv: Option<PathBuf>,
+}
+
+// Starts adding up the games that are played from now on. Until this is called, finished games
+// are ignored and no summary is shown.
+pub fn start(lang: Lang, to_stderr: bool, csv: Option<PathBuf>) {
+    let session = Session {summary: Summary::new(), started: Instant::now(), lang, to_stderr, csv};
+    // Only fails if a session was already started, which can't happen since main() only calls
+    // this once
+    if SESSION.set(Mutex::new(session)).is_err() {
+        unreachable!("the session should only be started once");
+    }
+    bus::subscribe(Finished);
+}
+
+// Adds every game that finishes to the session
+struct Finished;
+
+impl Subscriber for Finished {
+    fn notify(&mut self, event: &Event) {
+        if let Event::GameOver {game, players} = *event {
+            let session = SESSION.get().expect("the session starts before it is subscribed");
+            session.lock().expect("session lock was poisoned").summary.record(game, players);
+        }
+    }
+}
+
+// Shows the summary of the session and adds it to the CSV file. This is called right before the
+// program exits. Nothing is shown if no games were finished.
+pub fn finish() {
+    let session = match SESSION.get() {
+        Some(session) => session,
+        None => return,
+    };
+    let mut session = session.lock().expect("session lock was poisoned");
+    if session.summary.games == 0 {
+        return;
+    }
+    session.summary.duration = session.started.elapsed();
+
+    let lang = session.lang;
+    let mut text = String::from("\n");
+    for line in session.summary.lines(lang) {
+        text.push_str(&line);
+        text.push('\n');
+    }
+    if session.to_stderr {
+        eout!("{}", text);
+    }
+    else {
+        out!("{}", text);
+    }
+
+    if let Some(ref path) = session.csv {
+        match append_csv(&session.summary, path) {
+            Ok(None) => {},
+            Ok(Some(ref recovery)) => {
+                warn!(path = %path.display(), quarantined = %recovery.quarantined.display(),
+                    "the CSV file was corrupt");
+                eoutln!("{}", lang.text(Message::FileRecovered {path, recovery}));
+            },
+            Err(error) => {
+                warn!(path = %path.display(), %error, "failed to add the summary to the CSV file");
+                eoutln!("{}", lang.text(Message::SummaryCsvFailed {path, error: &error}));
+            },
+        }
+    }
+    // The summary was shown, so it shouldn't be shown again if something else calls this before
+    // the program exits
+    session.summary = Summary::new();
+}
+
+// Adds the summary to the end of the CSV file. Returns what was done about the file if it was
+// corrupt (see storage.rs).
+fn append_csv(summary: &Summary, path: &Path) -> io::Result<Option<Recovery>> {
+    let loaded = storage::read(path)?;
+    let mut rows = loaded.text.unwrap_or_default().into_bytes();
+    // A new (or empty) file needs the header before the first row
+    let header = rows.is_empty();
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |time| time.as_secs());
+    summary.write_csv(&mut rows, header, timestamp)?;
+    // Saving everything at once keeps the rows of one session together
+    storage::save(path, &rows)?;
+    Ok(loaded.recovery)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use player::PlayerProfile;
+
+    #[test]
+    fn summary_and_csv() {
+        let profile = |name: &str| PlayerProfile {name: Some(name.to_string()), glyph: None};
+        let mut players = Players::new(profile("Ada, the first"), profile("Grace"));
+
+        let mut x_wins = Game::new();
+        for &(row, col) in &[(0, 0), (1, 0), (0, 1), (1, 1), (0, 2)] {
+            x_wins.make_move(row, col).unwrap();
+        }
+        let mut summary = Summary::new();
+        summary.record(&x_wins, &players);
+        players.swap();
+        summary.record(&x_wins, &players);
+        summary.duration = Duration::from_secs(95);
+
+        assert_eq!(summary.games, 2);
+        assert_eq!(summary.average_moves(), 5.0);
+        assert_eq!(summary.players["Grace"], Tally {wins: 1, losses: 1, ties: 0});
+
+        let mut csv = Vec::new();
+        summary.write_csv(&mut csv, true, 1700000000).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        let lines: Vec<_> = csv.lines().collect();
+        assert_eq!(lines, [
+            CSV_HEADER,
+            "1700000000,\"Ada, the first\",1,1,0,2,5.0,95",
+            "1700000000,Grace,1,1,0,2,5.0,95",
+        ]);
+    }
+}
@@ -0,0 +1,101 @@
+// A tic-tac-toe board looks the same after it is turned a quarter turn or flipped over. Starting
+// in the top left corner is really the same move as starting in any of the other corners, just
+// seen from a different side of the board. There are 8 ways to turn and flip the board, and
+// every position belongs to a group of up to 8 positions that only differ by one of them.
+//
+// To treat those positions as one, we pick a single "canonical" member of each group: the one
+// whose moves come first when sorted. Two games that start the same way, up to turning and
+// flipping the board, have the same canonical moves.
+// More info: https://en.wikipedia.org/wiki/Dihedral_group
+
+use game::{Piece, Tiles};
+
+// The number of different ways to turn and flip the board, including leaving it as it is
+const TRANSFORMS: usize = 8;
+
+// Moves a tile to where it ends up after turning and flipping the board in the given way, from 0
+// to 7. Each of the three bits of `transform` does one thing: flip the board upside down, flip it
+// left to right and swap the rows with the columns. Together they make all 8 ways.
+pub fn transform(transform: usize, (row, col): (usize, usize)) -> (usize, usize) {
+    let (row, col) = if transform & 4 != 0 { (col, row) } else { (row, col) };
+    let row = if transform & 1 != 0 { 2 - row } else { row };
+    let col = if transform & 2 != 0 { 2 - col } else { col };
+    (row, col)
+}
+
+// The canonical form of a list of moves: the first one when sorted, out of the moves seen from
+// every side of the board
+pub fn canonical(moves: &[(usize, usize)]) -> Vec<(usize, usize)> {
+    (0..TRANSFORMS).map(|t| moves.iter().map(|&tile| transform(t, tile)).collect())
+        .min().unwrap_or_default()
+}
+
+// The canonical form of a board: the one out of the board seen from every side whose tiles come
+// first when read row by row, counting empty tiles before x and x before o
+pub fn canonical_tiles(tiles: &Tiles) -> Tiles {
+    (0..TRANSFORMS).map(|t| {
+        let mut turned = Tiles::default();
+        for (row, row_tiles) in tiles.iter().enumerate() {
+            for (col, &tile) in row_tiles.iter().enumerate() {
+                let (new_row, new_col) = transform(t, (row, col));
+                turned[new_row][new_col] = tile;
+            }
+        }
+        turned
+    }).min_by_key(|turned| {
+        // Reading the tiles as the digits of a number in base 3 gives each board its own number
+        turned.iter().flatten().fold(0, |number, tile| number * 3 + match *tile {
+            None => 0,
+            Some(Piece::X) => 1,
+            Some(Piece::O) => 2,
+        })
+    }).expect("there is always at least one way to see the board")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn canonical_openings() {
+        // Every corner is the same opening, and so is every edge
+        for &corner in &[(0, 0), (0, 2), (2, 0), (2, 2)] {
+            assert_eq!(canonical(&[corner]), [(0, 0)]);
+        }
+        for &edge in &[(0, 1), (1, 0), (1, 2), (2, 1)] {
+            assert_eq!(canonical(&[edge]), [(0, 1)]);
+        }
+        assert_eq!(canonical(&[(1, 1)]), [(1, 1)]);
+
+        // The reply to a corner opening only matters relative to the corner
+        assert_eq!(canonical(&[(2, 2), (0, 0)]), [(0, 0), (2, 2)]);
+        assert_eq!(canonical(&[(0, 2), (1, 2)]), canonical(&[(2, 0), (2, 1)]));
+        assert_ne!(canonical(&[(0, 0), (0, 1)]), canonical(&[(0, 0), (1, 2)]));
+    }
+
+    #[test]
+    fn canonical_boards() {
+        let mut corner = Tiles::default();
+        corner[2][2] = Some(Piece::X);
+        corner[1][2] = Some(Piece::O);
+        let mut other_corner = Tiles::default();
+        other_corner[0][0] = Some(Piece::X);
+        other_corner[0][1] = Some(Piece::O);
+        assert_eq!(canonical_tiles(&corner), canonical_tiles(&other_corner));
+        // Swapping the pieces gives a different board
+        other_corner[0][0] = Some(Piece::O);
+        other_corner[0][1] = Some(Piece::X);
+        assert_ne!(canonical_tiles(&corner), canonical_tiles(&other_corner));
+    }
+
+    #[test]
+    fn transforms_are_all_different() {
+        let corner_and_edge = [(0, 0), (0, 1)];
+        let mut seen: Vec<Vec<_>> = (0..TRANSFORMS)
+            .map(|t| corner_and_edge.iter().map(|&tile| transform(t, tile)).collect())
+            .collect();
+        seen.sort();
+        seen.dedup();
+        assert_eq!(seen.len(), TRANSFORMS);
+    }
+}
@@ -0,0 +1,190 @@
+// Finds out how big the terminal is, so that the board can be drawn at a size that fits, and what
+// it is able to show.
+//
+// Crates like `terminal_size` do this for every platform. On Unix-like systems, all it takes is
+// asking the terminal with the C library's `ioctl` function, so we do that ourselves. Anywhere
+// else, we fall back to the COLUMNS and LINES environment variables that many shells set.
+//
+// Most terminals understand ANSI escape codes (like the one that clears the screen) and show any
+// Unicode character, like the ▢ of empty tiles. The Windows console only understands escape codes
+// once it is asked to, which takes a call to `SetConsoleMode`, and its older fonts are missing most
+// of the characters outside of ASCII. Crates like `crossterm` hide those differences, but the few
+// calls we need are easy enough to make ourselves, just like `ioctl`.
+// More info: https://learn.microsoft.com/en-us/windows/console/console-virtual-terminal-sequences
+
+use std::env;
+use std::io::{self, IsTerminal};
+
+// What the terminal that the board is drawn on can show
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Capabilities {
+    /// Whether ANSI escape codes work, like the one that clears the screen
+    pub ansi: bool,
+    /// Whether characters outside of ASCII are shown properly
+    pub unicode: bool,
+}
+
+impl Default for Capabilities {
+    // Output that isn't going to a terminal is read by another program or saved to a file, so
+    // nothing about it needs to change
+    fn default() -> Self {
+        Capabilities {ansi: true, unicode: true}
+    }
+}
+
+// Gets the terminal ready to draw the board and finds out what it can show. This only needs to
+// happen once, before anything is drawn.
+pub fn setup() -> Capabilities {
+    if !io::stdout().is_terminal() {
+        return Capabilities::default();
+    }
+    let capabilities = setup_console();
+    debug!(ansi = capabilities.ansi, unicode = capabilities.unicode, "set up the terminal");
+    capabilities
+}
+
+// Unix-like terminals understand escape codes unless they say otherwise, and show Unicode unless
+// the locale picks another encoding
+#[cfg(not(windows))]
+fn setup_console() -> Capabilities {
+    let term = env::var("TERM").unwrap_or_default();
+    let locale = ["LC_ALL", "LC_CTYPE", "LANG"].iter()
+        .filter_map(|name| env::var(name).ok())
+        .find(|value| !value.is_empty());
+    Capabilities {
+        ansi: term != "dumb",
+        unicode: locale.as_deref().is_none_or(is_unicode_locale),
+    }
+}
+
+// The Windows console has to be asked for escape codes, and only the newer terminals (like Windows
+// Terminal and the ones in editors) come with fonts that have the characters outside of ASCII.
+// The text itself is always fine, since Rust writes it to the console as UTF-16 no matter what the
+// console's code page is.
+#[cfg(windows)]
+fn setup_console() -> Capabilities {
+    let modern = ["WT_SESSION", "TERM_PROGRAM", "TERM"].iter()
+        .any(|name| env::var_os(name).is_some());
+    Capabilities {ansi: enable_escape_codes(), unicode: modern}
+}
+
+// Turns on the console's "virtual terminal processing", which is what it calls escape codes.
+// Returns false if the console is too old to have it.
+#[cfg(windows)]
+fn enable_escape_codes() -> bool {
+    use std::os::raw::c_void;
+
+    // A handle is how Windows refers to things like the console, a bit like file descriptors
+    type Handle = *mut c_void;
+    // -11, as the unsigned number that the function expects
+    const STD_OUTPUT_HANDLE: u32 = 0xFFFF_FFF5;
+    const ENABLE_VIRTUAL_TERMINAL_PROCESSING: u32 = 0x0004;
+
+    // The functions of the Windows API use the "system" calling convention instead of C's
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetStdHandle(std_handle: u32) -> Handle;
+        fn GetConsoleMode(console: Handle, mode: *mut u32) -> i32;
+        fn SetConsoleMode(console: Handle, mode: u32) -> i32;
+    }
+
+    // Safe because the mode is only written to a u32 that we own, and a handle that isn't a
+    // console only makes the calls fail
+    unsafe {
+        let console = GetStdHandle(STD_OUTPUT_HANDLE);
+        let mut mode = 0;
+        GetConsoleMode(console, &mut mode) != 0
+            && SetConsoleMode(console, mode | ENABLE_VIRTUAL_TERMINAL_PROCESSING) != 0
+    }
+}
+
+// Locales are written like "en_US.UTF-8" or "fr_FR.ISO-8859-15@euro", with the encoding after the
+// dot. The "C" and "POSIX" locales don't name one, and are so often used by terminals that do show
+// Unicode (like the ones in containers) that we don't count them as ASCII.
+#[cfg_attr(windows, allow(dead_code))]
+fn is_unicode_locale(locale: &str) -> bool {
+    let encoding = match locale.split('@').next().unwrap_or_default().split_once('.') {
+        Some((_, encoding)) => encoding.to_lowercase(),
+        None => return true,
+    };
+    encoding == "utf-8" || encoding == "utf8"
+}
+
+// Returns the size of the terminal as (columns, rows), or None if the output isn't going to a
+// terminal or its size can't be found
+pub fn size() -> Option<(usize, usize)> {
+    // When the output is being saved to a file or read by another program, there is no screen
+    // for it to fit on
+    if !io::stdout().is_terminal() {
+        return None;
+    }
+    ioctl_size().or_else(env_size)
+}
+
+// Reads the size from the COLUMNS and LINES environment variables
+fn env_size() -> Option<(usize, usize)> {
+    let read = |name| env::var(name).ok().and_then(|value| value.trim().parse().ok());
+    match (read("COLUMNS"), read("LINES")) {
+        (Some(columns), Some(rows)) if columns > 0 && rows > 0 => Some((columns, rows)),
+        _ => None,
+    }
+}
+
+#[cfg(unix)]
+fn ioctl_size() -> Option<(usize, usize)> {
+    use std::os::raw::{c_int, c_ulong};
+
+    // The layout of this struct has to match the `winsize` struct from the C library exactly,
+    // which is what `#[repr(C)]` asks the compiler to do
+    #[repr(C)]
+    #[derive(Default)]
+    struct WinSize {
+        rows: u16,
+        columns: u16,
+        // We don't need these, and the `_` tells Rust that we never read them on purpose
+        _x_pixels: u16,
+        _y_pixels: u16,
+    }
+
+    // The request that asks for the window size has a different number on different systems
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    const TIOCGWINSZ: c_ulong = 0x5413;
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    const TIOCGWINSZ: c_ulong = 0x4008_7468;
+    const STDOUT: c_int = 1;
+
+    // The `...` means that ioctl takes a different kind of argument for each request
+    extern "C" {
+        fn ioctl(fd: c_int, request: c_ulong, ...) -> c_int;
+    }
+
+    let mut size = WinSize::default();
+    // Safe because TIOCGWINSZ only writes a `winsize` struct to the pointer we give it
+    let result = unsafe { ioctl(STDOUT, TIOCGWINSZ, &mut size as *mut WinSize) };
+    if result == 0 && size.columns > 0 && size.rows > 0 {
+        Some((usize::from(size.columns), usize::from(size.rows)))
+    }
+    else {
+        None
+    }
+}
+
+#[cfg(not(unix))]
+fn ioctl_size() -> Option<(usize, usize)> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn locales() {
+        assert!(is_unicode_locale("en_US.UTF-8"));
+        assert!(is_unicode_locale("fr_CA.utf8"));
+        assert!(is_unicode_locale("de_DE.UTF-8@euro"));
+        assert!(is_unicode_locale("C"));
+        assert!(!is_unicode_locale("en_US.ISO-8859-1"));
+        assert!(!is_unicode_locale("fr_FR.ISO-8859-15@euro"));
+    }
+}
@@ -0,0 +1,107 @@
+// Generators of random games for property tests with proptest. A property test checks that
+// something is true for hundreds of randomly generated inputs instead of a few hand-picked ones,
+// and when it finds an input where it isn't, proptest shrinks it down to the smallest input that
+// still fails.
+// More info: https://proptest-rs.github.io/proptest/
+//
+// Every generated game only ever contains legal moves, so the tests don't have to throw away
+// inputs that could never happen in a real game.
+
+use proptest::prelude::*;
+
+use game::{Game, Piece};
+
+// Every tile on the board, in order
+const ALL_TILES: [(usize, usize); 9] = [
+    (0, 0), (0, 1), (0, 2),
+    (1, 0), (1, 1), (1, 2),
+    (2, 0), (2, 1), (2, 2),
+];
+
+// Either piece
+pub fn pieces() -> impl Strategy<Value=Piece> {
+    prop_oneof![Just(Piece::X), Just(Piece::O)]
+}
+
+// A list of moves that can all be played in order from an empty board. It has anywhere from no
+// moves at all to a whole game, but it never continues after someone has won.
+pub fn move_sequences() -> impl Strategy<Value=Vec<(usize, usize)>> {
+    // Shuffling the tiles means that every tile comes up at most once, so no move is ever played
+    // on a tile that already has a piece on it
+    (Just(ALL_TILES.to_vec()).prop_shuffle(), 0..=ALL_TILES.len()).prop_map(|(tiles, length)| {
+        let mut game = Game::new();
+        tiles.into_iter().take(length).take_while(|&(row, col)| {
+            !game.is_finished() && game.make_move(row, col).is_ok()
+        }).collect()
+    })
+}
+
+// A game that was played with legal moves by either player going first. It may or may not be over.
+pub fn games() -> impl Strategy<Value=Game> {
+    (pieces(), move_sequences()).prop_map(|(first_piece, moves)| {
+        let mut game = Game::starting_with(first_piece);
+        for (row, col) in moves {
+            game.make_move(row, col).expect("generated moves should be legal");
+        }
+        game
+    })
+}
+
+// Implementing Arbitrary lets tests ask for a random value with `any::<Piece>()`
+impl Arbitrary for Piece {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Piece>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        pieces().boxed()
+    }
+}
+
+impl Arbitrary for Game {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Game>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        games().boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    proptest! {
+        #[test]
+        fn winner_never_changes(mut game in any::<Game>(), moves in move_sequences()) {
+            let winner = game.winner();
+            for (row, col) in moves {
+                let _ = game.make_move(row, col);
+                if winner.is_some() {
+                    prop_assert_eq!(game.winner(), winner);
+                }
+            }
+        }
+
+        #[test]
+        fn pieces_match_moves(game in any::<Game>()) {
+            let pieces = game.tiles().iter().flatten().filter(|tile| tile.is_some()).count();
+            prop_assert_eq!(pieces, game.moves().len());
+            // The players take turns, so neither one can be more than a move ahead
+            let first = game.tiles().iter().flatten()
+                .filter(|&&tile| tile == Some(game.first_piece())).count();
+            prop_assert!(first * 2 == pieces || first * 2 == pieces + 1);
+        }
+
+        #[test]
+        fn undo_goes_back(game in any::<Game>()) {
+            let mut undone = game.clone();
+            if let Some((row, col)) = undone.undo() {
+                prop_assert!(!undone.is_finished());
+                prop_assert_eq!(undone.tiles()[row][col], None);
+                undone.make_move(row, col).unwrap();
+                prop_assert_eq!(undone.tiles(), game.tiles());
+                prop_assert_eq!(undone.winner(), game.winner());
+            }
+        }
+    }
+}
@@ -0,0 +1,214 @@
+// A theme is a set of characters for drawing the board in the terminal, along with the colors used
+// when the board is saved as an image. Players can still pick their own glyphs with `--x-glyph`
+// and `--o-glyph`, and those take priority over the ones from the theme.
+//
+// Some of the themes are made for players who have a hard time telling the pieces or the winning
+// line apart. The `colorblind` theme never tells two things apart by their color alone: its colors
+// are ones that look different with every kind of color blindness, and the winning line is also
+// marked by its shape (brackets around the pieces in the terminal, and a dark outline in images).
+// The `high-contrast` theme draws light pieces on a black background and makes every tile three
+// characters wide in the terminal, so that the board is easier to read.
+
+// A color as its red, green and blue parts, each from 0 to 255
+pub type Color = [u8; 3];
+
+// The colors of a board saved as an image
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Colors {
+    pub background: Color,
+    /// The lines between the tiles
+    pub grid: Color,
+    pub x: Color,
+    pub o: Color,
+    /// The line drawn through three in a row
+    pub highlight: Color,
+    /// The outline drawn around the line through three in a row, so that it stands out by more
+    /// than its color, or None for no outline
+    pub outline: Option<Color>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    /// The name used to pick this theme on the command line
+    pub name: &'static str,
+    /// The glyph for x's pieces
+    pub x: &'static str,
+    /// The glyph for o's pieces
+    pub o: &'static str,
+    /// The glyph for tiles that don't have a piece yet
+    pub empty: &'static str,
+    /// The number of columns that every tile takes up in the terminal, at least. Tiles are made
+    /// wider when a glyph needs more room.
+    pub cell_width: usize,
+    /// The characters drawn on either side of each piece of three in a row in the terminal, like
+    /// [x], or None to draw the winning pieces like any others. They only make sense in themes
+    /// with glyphs that are plain ASCII, since they are kept when the glyphs fall back to ASCII.
+    pub win_marks: Option<(&'static str, &'static str)>,
+    pub colors: Colors,
+}
+
+// Every theme we provide. The first one is used when no theme is chosen.
+pub const THEMES: [Theme; 6] = [
+    Theme {
+        name: "classic",
+        x: "x",
+        o: "o",
+        empty: "\u{25A2}",
+        cell_width: 1,
+        win_marks: None,
+        colors: Colors {
+            background: [0xfd, 0xfa, 0xf3],
+            grid: [0x33, 0x33, 0x33],
+            x: [0xd1, 0x49, 0x5b],
+            o: [0x00, 0x79, 0x8c],
+            highlight: [0xed, 0xae, 0x49],
+            outline: None,
+        },
+    },
+    Theme {
+        name: "dots",
+        x: "\u{25CF}",
+        o: "\u{25CB}",
+        empty: "\u{00B7}",
+        cell_width: 1,
+        win_marks: None,
+        colors: Colors {
+            background: [0x1e, 0x1e, 0x2e],
+            grid: [0x6c, 0x70, 0x86],
+            x: [0xf5, 0xe0, 0xdc],
+            o: [0x89, 0xb4, 0xfa],
+            highlight: [0xa6, 0xe3, 0xa1],
+            outline: None,
+        },
+    },
+    Theme {
+        name: "animals",
+        x: "\u{1F431}",
+        o: "\u{1F436}",
+        empty: "\u{25A2}",
+        cell_width: 1,
+        win_marks: None,
+        colors: Colors {
+            background: [0xf1, 0xf8, 0xe9],
+            grid: [0x55, 0x8b, 0x2f],
+            x: [0xef, 0x6c, 0x00],
+            o: [0x6d, 0x4c, 0x41],
+            highlight: [0xff, 0xca, 0x28],
+            outline: None,
+        },
+    },
+    // Only uses characters that every terminal can show. This is also what the board falls back
+    // to when the terminal can't show the other glyphs (see terminal.rs).
+    Theme {
+        name: "ascii",
+        x: "x",
+        o: "o",
+        empty: ".",
+        cell_width: 1,
+        win_marks: None,
+        colors: Colors {
+            background: [0xff, 0xff, 0xff],
+            grid: [0x00, 0x00, 0x00],
+            x: [0x00, 0x00, 0x00],
+            o: [0x00, 0x00, 0x00],
+            highlight: [0x88, 0x88, 0x88],
+            outline: None,
+        },
+    },
+    // The colors are from the palette by Masataka Okabe and Kei Ito, which stays apart with every
+    // kind of color blindness. More info: https://jfly.uni-koeln.de/color/
+    Theme {
+        name: "colorblind",
+        x: "X",
+        o: "O",
+        empty: ".",
+        cell_width: 1,
+        win_marks: Some(("[", "]")),
+        colors: Colors {
+            background: [0xff, 0xff, 0xff],
+            grid: [0x55, 0x55, 0x55],
+            x: [0xd5, 0x5e, 0x00],
+            o: [0x00, 0x72, 0xb2],
+            highlight: [0xf0, 0xe4, 0x42],
+            outline: Some([0x00, 0x00, 0x00]),
+        },
+    },
+    Theme {
+        name: "high-contrast",
+        x: "X",
+        o: "O",
+        empty: ".",
+        cell_width: 3,
+        win_marks: Some((">", "<")),
+        colors: Colors {
+            background: [0x00, 0x00, 0x00],
+            grid: [0xff, 0xff, 0xff],
+            x: [0xff, 0xff, 0x00],
+            o: [0x00, 0xff, 0xff],
+            highlight: [0xff, 0x00, 0xff],
+            outline: Some([0xff, 0xff, 0xff]),
+        },
+    },
+];
+
+impl Theme {
+    // Finds a theme by its name
+    pub fn from_name(name: &str) -> Option<Self> {
+        // `cloned` copies the theme out of the array instead of giving us a reference to it
+        THEMES.iter().find(|theme| theme.name == name.to_lowercase()).cloned()
+    }
+
+    // The same theme with every glyph that isn't plain ASCII swapped for the one from the `ascii`
+    // theme, for terminals that can't show them. The colors of images stay the same.
+    pub fn ascii_only(self) -> Self {
+        let ascii = THEMES[3];
+        let pick = |glyph: &'static str, fallback| if glyph.is_ascii() { glyph } else { fallback };
+        Theme {
+            x: pick(self.x, ascii.x),
+            o: pick(self.o, ascii.o),
+            empty: pick(self.empty, ascii.empty),
+            ..self
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        THEMES[0]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use player::PlayerProfile;
+
+    #[test]
+    fn theme_names() {
+        assert_eq!(Theme::from_name("Dots").map(|theme| theme.name), Some("dots"));
+        assert_eq!(Theme::from_name("neon"), None);
+        assert_eq!(Theme::default().x, "x");
+        assert_eq!(Theme::from_name("High-Contrast").map(|theme| theme.cell_width), Some(3));
+    }
+
+    #[test]
+    fn ascii_fallback() {
+        let classic = Theme::default().ascii_only();
+        assert_eq!((classic.x, classic.o, classic.empty), ("x", "o", "."));
+        assert_eq!(classic.colors, Theme::default().colors);
+        let dots = Theme::from_name("dots").unwrap().ascii_only();
+        assert_eq!((dots.name, dots.x, dots.empty), ("dots", "x", "."));
+    }
+
+    #[test]
+    fn glyphs_fit_on_the_board() {
+        for theme in &THEMES {
+            for glyph in &[theme.x, theme.o, theme.empty] {
+                assert!(PlayerProfile::validate_glyph(glyph).is_ok(), "bad glyph {:?}", glyph);
+            }
+            if let Some((before, after)) = theme.win_marks {
+                assert!(before.is_ascii() && after.is_ascii(), "bad marks in {}", theme.name);
+            }
+        }
+    }
+}
@@ -0,0 +1,335 @@
+// A round-robin tournament: every entrant plays every other entrant twice, once with each piece.
+// Entrants are computer players at one of the difficulties, or people taking a seat at the
+// keyboard. The results are shown in a crosstable, which lists how many points each entrant
+// scored against each of the others:
+//
+//      #  Entrant    1    2    3  Score
+//      1  perfect    -  1.5    2    3.5
+//      2  medium   0.5    -  1.5      2
+//      3  easy       0  0.5    -    0.5
+//
+// A win is worth 1 point and a tie is worth half a point to each player.
+//
+// With many entrants, a round robin takes a very long time, since the number of games grows with
+// the square of the number of entrants. A Swiss tournament only plays a few rounds instead. In
+// each round, entrants are paired with someone who has about the same score as them, and the pair
+// plays two games, one with each piece. After a handful of rounds, the strongest entrants end up
+// at the top without everyone having to play everyone.
+// More info: https://en.wikipedia.org/wiki/Swiss-system_tournament
+
+use engine::Profile;
+use game::Winner;
+use player::PlayerProfile;
+use session::Computer;
+
+// A player in the tournament
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entrant {
+    /// The name that the entrant's games are saved under in the archive
+    pub name: String,
+    /// The computer player playing for this entrant, or None if a person is playing
+    pub engine: Option<Computer>,
+}
+
+// Reads a list of entrants separated by commas. Each entrant is a computer player, like `perfect`,
+// `engine:NAME` or `plugin:NAME` (see Computer::parse), or `human:NAME` for a person. Adding `*N`
+// enters N of the same entrant, so that a big pool of computer players can be entered with
+// something like `easy*30,perfect*30`. Entrants with the same name are numbered so that they can
+// be told apart, e.g. "medium" and "medium #2".
+// Returns None if any entrant can't be read or if there are fewer than two of them.
+pub fn parse_entrants(list: &str, profiles: &[(String, Profile)]) -> Option<Vec<Entrant>> {
+    let mut entrants = Vec::new();
+    // The names before any numbers were added to them
+    let mut names: Vec<String> = Vec::new();
+    for entrant in list.split(',').map(str::trim) {
+        // `split_once` splits at the first `*`, if there is one
+        let (entrant, count) = match entrant.split_once('*') {
+            Some((entrant, count)) => (entrant.trim(), count.trim().parse().ok()?),
+            None => (entrant, 1),
+        };
+        let (name, engine) = if let Some(name) = entrant.strip_prefix("human:") {
+            (PlayerProfile::validate_name(name).ok()?, None)
+        }
+        else {
+            let computer = Computer::parse(entrant, profiles)?;
+            (computer.name().to_string(), Some(computer))
+        };
+
+        for _ in 0..count {
+            // `filter(...).count()` counts the entrants that already have this name
+            let taken = names.iter().filter(|&other| *other == name).count();
+            entrants.push(Entrant {
+                name: if taken == 0 { name.clone() } else { format!("{} #{}", name, taken + 1) },
+                engine: engine.clone(),
+            });
+            names.push(name.clone());
+        }
+    }
+
+    if entrants.len() >= 2 {
+        Some(entrants)
+    }
+    else {
+        None
+    }
+}
+
+// The entrants that play each game, in the order the games are played. The first entrant of each
+// pair plays x and goes first. Each pair of entrants plays twice in a row, trading pieces for the
+// second game.
+pub fn pairings(entrants: usize) -> Vec<(usize, usize)> {
+    let mut pairings = Vec::new();
+    for a in 0..entrants {
+        for b in a + 1..entrants {
+            pairings.push((a, b));
+            pairings.push((b, a));
+        }
+    }
+    pairings
+}
+
+// Pairs up the entrants for the next round of a Swiss tournament. Returns the pairs, along with
+// the entrant who sits out this round if there is an odd number of them.
+//
+// Entrants are ranked by their score so far, and then each entrant, from the top down, is paired
+// with the highest ranked entrant left that they haven't played yet. If they have already played
+// everyone left, they play the highest ranked one again. Official Swiss tournaments use much more
+// detailed rules (like balancing who goes first), but this gives the same kind of pairings.
+pub fn swiss_pairings(table: &Crosstable) -> (Vec<(usize, usize)>, Option<usize>) {
+    let mut unpaired = table.ranking(|entrant| table.total(entrant));
+
+    // The lowest ranked entrant who hasn't sat out yet gets the bye. Sitting out is worth a win,
+    // which keeps them from falling behind through no fault of their own.
+    let bye = if unpaired.len() % 2 == 1 {
+        let position = unpaired.iter().rposition(|&entrant| table.byes[entrant] == 0)
+            .unwrap_or(unpaired.len() - 1);
+        Some(unpaired.remove(position))
+    }
+    else {
+        None
+    };
+
+    let mut pairs = Vec::new();
+    while !unpaired.is_empty() {
+        let top = unpaired.remove(0);
+        let opponent = unpaired.iter().position(|&other| table.games[top][other] == 0)
+            .unwrap_or(0);
+        pairs.push((top, unpaired.remove(opponent)));
+    }
+    (pairs, bye)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Crosstable {
+    names: Vec<String>,
+    // `half_points[a][b]` is the number of half points that entrant `a` scored against `b`.
+    // Counting half points lets us use whole numbers, so adding up the score is always exact.
+    half_points: Vec<Vec<u32>>,
+    // `games[a][b]` is the number of games that entrants `a` and `b` played against each other
+    games: Vec<Vec<u32>>,
+    // The half points each entrant got for sitting out a round of a Swiss tournament
+    byes: Vec<u32>,
+}
+
+impl Crosstable {
+    pub fn new(names: Vec<String>) -> Self {
+        let count = names.len();
+        Self {
+            names,
+            half_points: vec![vec![0; count]; count],
+            games: vec![vec![0; count]; count],
+            byes: vec![0; count],
+        }
+    }
+
+    // Adds the result of a game between entrant `x` (playing x) and entrant `o` (playing o)
+    pub fn record(&mut self, x: usize, o: usize, winner: Winner) {
+        let (x_half_points, o_half_points) = match winner {
+            Winner::X => (2, 0),
+            Winner::O => (0, 2),
+            Winner::Tie => (1, 1),
+        };
+        self.half_points[x][o] += x_half_points;
+        self.half_points[o][x] += o_half_points;
+        self.games[x][o] += 1;
+        self.games[o][x] += 1;
+    }
+
+    // Gives an entrant who sits out a round of a Swiss tournament the points for winning both
+    // games of the round
+    pub fn record_bye(&mut self, entrant: usize) {
+        self.byes[entrant] += 4;
+    }
+
+    // The entrant's total score, in half points
+    fn total(&self, entrant: usize) -> u32 {
+        self.half_points[entrant].iter().sum::<u32>() + self.byes[entrant]
+    }
+
+    // The Buchholz score breaks ties between entrants with the same score. It adds up the scores
+    // of everyone the entrant played against, so that a score earned against strong opponents
+    // counts for more than the same score earned against weak ones.
+    fn buchholz(&self, entrant: usize) -> u32 {
+        (0..self.names.len()).filter(|&other| self.games[entrant][other] > 0)
+            .map(|other| self.total(other)).sum()
+    }
+
+    // The entrants sorted from the highest score to the lowest, using `tie_break` to order
+    // entrants with the same score. Entrants that are still tied stay in the order they were
+    // entered.
+    fn ranking<F>(&self, tie_break: F) -> Vec<usize> where F: Fn(usize) -> u32 {
+        let mut ranking: Vec<usize> = (0..self.names.len()).collect();
+        // `sort_by_key` keeps equal entrants in order. `Reverse` puts the highest score first.
+        ranking.sort_by_key(|&entrant| {
+            ::std::cmp::Reverse((self.total(entrant), tie_break(entrant)))
+        });
+        ranking
+    }
+
+    // The width of the column of names, which fits the longest name and the column heading
+    fn name_width(&self, heading: &str) -> usize {
+        self.names.iter().map(|name| name.chars().count()).chain(Some(heading.chars().count()))
+            .max().unwrap_or(0)
+    }
+
+    // The lines of the standings of a Swiss tournament, with the entrants sorted by their score
+    // and then by their Buchholz score. `labels` are the words used for the entrant, score and
+    // Buchholz columns.
+    pub fn standings(&self, labels: (&str, &str, &str)) -> Vec<String> {
+        let name_width = self.name_width(labels.0);
+        let mut lines = vec![format!(" #  {:<width$}  {}  {}", labels.0, labels.1, labels.2,
+            width = name_width)];
+        for (rank, entrant) in self.ranking(|entrant| self.buchholz(entrant)).into_iter()
+            .enumerate() {
+
+            lines.push(format!("{:>2}  {:<name$}  {:>score$}  {:>buchholz$}", rank + 1,
+                self.names[entrant], points(self.total(entrant)), points(self.buchholz(entrant)),
+                name = name_width, score = labels.1.len(), buchholz = labels.2.len()));
+        }
+        lines
+    }
+
+    // The lines of the crosstable, with the entrants sorted from the highest score to the lowest.
+    // Entrants with the same score stay in the order they were entered. `labels` are the words
+    // used for the entrant and score columns.
+    pub fn lines(&self, labels: (&str, &str)) -> Vec<String> {
+        let standings = self.ranking(|_| 0);
+        let name_width = self.name_width(labels.0);
+
+        let mut header = format!(" #  {:<width$}", labels.0, width = name_width);
+        for rank in 1..=standings.len() {
+            header += &format!("{:>5}", rank);
+        }
+        header += &format!("  {}", labels.1);
+        let mut lines = vec![header];
+
+        for (rank, &entrant) in standings.iter().enumerate() {
+            let mut line = format!("{:>2}  {:<width$}", rank + 1, self.names[entrant],
+                width = name_width);
+            for &opponent in &standings {
+                let cell = if opponent == entrant {
+                    "-".to_string()
+                }
+                else {
+                    points(self.half_points[entrant][opponent])
+                };
+                line += &format!("{:>5}", cell);
+            }
+            line += &format!("  {:>width$}", points(self.total(entrant)), width = labels.1.len());
+            lines.push(line);
+        }
+        lines
+    }
+}
+
+// Writes a number of half points as points, e.g. 3 half points is "1.5"
+fn points(half_points: u32) -> String {
+    if half_points.is_multiple_of(2) {
+        (half_points / 2).to_string()
+    }
+    else {
+        format!("{}.5", half_points / 2)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use engine::Difficulty;
+
+    #[test]
+    fn entrants() {
+        let list = "perfect, medium,medium,human:Ada,plugin:corners";
+        let entrants = parse_entrants(list, &[]).unwrap();
+        let names: Vec<_> = entrants.iter().map(|entrant| &entrant.name[..]).collect();
+        assert_eq!(names, ["perfect", "medium", "medium #2", "Ada", "corners"]);
+        assert_eq!(entrants[0].engine, Some(Computer::Engine(Difficulty::Perfect)));
+        assert_eq!(entrants[3].engine, None);
+        assert_eq!(entrants[4].engine, Some(Computer::Plugin("corners".to_string())));
+
+        let pool = parse_entrants("easy*3,perfect", &[]).unwrap();
+        let names: Vec<_> = pool.iter().map(|entrant| &entrant.name[..]).collect();
+        assert_eq!(names, ["easy", "easy #2", "easy #3", "perfect"]);
+
+        assert!(parse_entrants("perfect", &[]).is_none());
+        assert!(parse_entrants("perfect*1", &[]).is_none());
+        assert!(parse_entrants("perfect,genius", &[]).is_none());
+        assert!(parse_entrants("perfect,human:", &[]).is_none());
+        assert!(parse_entrants("perfect,plugin:", &[]).is_none());
+
+        // Profiles are entered under their own names, and only the ones that were given exist
+        let profiles = [("sloppy".to_string(), Profile {randomness: 50, ..Profile::default()})];
+        let entrants = parse_entrants("engine:sloppy,perfect", &profiles).unwrap();
+        assert_eq!(entrants[0].name, "sloppy");
+        let sloppy = Computer::Profile("sloppy".to_string(), profiles[0].1);
+        assert_eq!(entrants[0].engine, Some(sloppy));
+        assert!(parse_entrants("engine:sloppy,engine:careful", &profiles).is_none());
+    }
+
+    #[test]
+    fn everyone_plays_everyone_twice() {
+        assert_eq!(pairings(3), [(0, 1), (1, 0), (0, 2), (2, 0), (1, 2), (2, 1)]);
+    }
+
+    #[test]
+    fn crosstable() {
+        let mut table = Crosstable::new(vec!["easy".to_string(), "perfect".to_string()]);
+        table.record(0, 1, Winner::O);
+        table.record(1, 0, Winner::Tie);
+        assert_eq!(table.lines(("Entrant", "Score")), [
+            " #  Entrant    1    2  Score",
+            " 1  perfect    -  1.5    1.5",
+            " 2  easy     0.5    -    0.5",
+        ]);
+    }
+
+    #[test]
+    fn swiss_rounds() {
+        let names = ["a", "b", "c", "d", "e"].iter().map(|name| name.to_string()).collect();
+        let mut table = Crosstable::new(names);
+
+        // Nobody has any points yet, so the entrants are paired in the order they were entered
+        // and the last one sits out
+        let (pairs, bye) = swiss_pairings(&table);
+        assert_eq!((pairs, bye), (vec![(0, 1), (2, 3)], Some(4)));
+        for &(x, o) in &[(0, 1), (1, 0), (2, 3), (3, 2)] {
+            // The first entrant of each pair wins both of their games
+            table.record(x, o, if x < o { Winner::X } else { Winner::O });
+        }
+        table.record_bye(4);
+
+        // The winners meet, someone else sits out, and nobody plays the same opponent twice
+        let (pairs, bye) = swiss_pairings(&table);
+        assert_eq!((pairs, bye), (vec![(0, 2), (4, 1)], Some(3)));
+
+        assert_eq!(table.standings(("Entrant", "Score", "Buchholz")), [
+            " #  Entrant  Score  Buchholz",
+            " 1  a            2         0",
+            " 2  c            2         0",
+            " 3  e            2         0",
+            " 4  b            0         2",
+            " 5  d            0         2",
+        ]);
+    }
+}
@@ -0,0 +1,270 @@
+// Training data for machine learning models, for the `export-training` command. Every position of
+// a finished game becomes one sample with four parts:
+//
+// * the position: the 9 tiles read row by row, with 1 for x, -1 for o and 0 for an empty tile
+// * the side to move: 1 for x or -1 for o
+// * the outcome: how the game that the position came from ended, with 1 if x won, -1 if o won and
+//   0 for a tie. This is the result that was actually played, not the perfect one.
+// * the best move: the tile (0 to 8, read row by row) of the first move that the solver picks
+//   for the side to move (see `engine::perfect_moves`)
+//
+// Positions can also be turned and flipped in all 8 ways (see symmetry.rs). A model can't know
+// that those are really the same position, so showing it all of them means that it learns the
+// same thing from every side of the board. A position that looks the same from more than one side
+// (like the empty board) is only added once.
+//
+// The samples can be saved as CSV, or as an NPZ file: the zip archive of NumPy arrays that
+// `numpy.load` reads. The zip archive is simple enough to write by hand, since its files are stored
+// without being compressed.
+// More info: https://numpy.org/doc/stable/reference/generated/numpy.lib.format.html
+
+use std::io::{self, Write};
+
+use crc32fast;
+
+use engine;
+use game::{Game, Piece, Winner};
+use symmetry;
+
+// The number of different ways to turn and flip the board (see symmetry.rs)
+const TRANSFORMS: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sample {
+    /// The tiles, row by row: 1 for x, -1 for o and 0 for an empty tile
+    pub tiles: [i8; 9],
+    /// 1 if x moves next, or -1 if o does
+    pub to_move: i8,
+    /// 1 if x won the game, -1 if o won it, or 0 if it was a tie
+    pub outcome: i8,
+    /// The tile of the best move, from 0 to 8
+    pub best_move: i8,
+}
+
+// The samples of every position in the game, turned and flipped in every way if `augment` is true.
+// Games that aren't over, or that were forfeited, don't have any, since their result doesn't come
+// from the moves on the board.
+pub fn samples(game: &Game, augment: bool) -> Vec<Sample> {
+    let outcome = match game.winner() {
+        Some(_) if game.forfeited_by().is_some() => return Vec::new(),
+        Some(Winner::X) => 1,
+        Some(Winner::O) => -1,
+        Some(Winner::Tie) => 0,
+        None => return Vec::new(),
+    };
+    let transforms = if augment { TRANSFORMS } else { 1 };
+    let moves = game.moves().to_vec();
+
+    let mut samples = Vec::new();
+    // An adjudicated game ends before its last position is finished, so that one gets a sample
+    // too
+    for length in 0..=moves.len() {
+        let first = samples.len();
+        for transform in 0..transforms {
+            let mut position = Game::starting_with(game.first_piece());
+            for &tile in &moves[..length] {
+                let (row, col) = symmetry::transform(transform, tile);
+                position.make_move(row, col).expect("the moves of a game are valid on any side");
+            }
+            if position.is_finished() {
+                break;
+            }
+            let sample = sample(&position, outcome);
+            if !samples[first..].contains(&sample) {
+                samples.push(sample);
+            }
+        }
+    }
+    samples
+}
+
+fn sample(position: &Game, outcome: i8) -> Sample {
+    let mut tiles = [0; 9];
+    for (tile, piece) in tiles.iter_mut().zip(position.tiles().iter().flatten()) {
+        *tile = value(*piece);
+    }
+    let (row, col) = engine::perfect_moves(position)[0];
+    Sample {
+        tiles,
+        to_move: value(Some(position.current_piece())),
+        outcome,
+        best_move: (row * 3 + col) as i8,
+    }
+}
+
+fn value(piece: Option<Piece>) -> i8 {
+    match piece {
+        Some(Piece::X) => 1,
+        Some(Piece::O) => -1,
+        None => 0,
+    }
+}
+
+// Writes the samples as CSV, starting with a line that names the columns
+pub fn write_csv<W: Write>(mut out: W, samples: &[Sample]) -> io::Result<()> {
+    writeln!(out, "t0,t1,t2,t3,t4,t5,t6,t7,t8,to_move,outcome,best_move")?;
+    for sample in samples {
+        let mut values: Vec<_> = sample.tiles.iter().map(|tile| tile.to_string()).collect();
+        values.extend([sample.to_move, sample.outcome, sample.best_move].iter()
+            .map(|value| value.to_string()));
+        writeln!(out, "{}", values.join(","))?;
+    }
+    Ok(())
+}
+
+// The samples as an NPZ file with four arrays of 8-bit integers: `positions` (one row of 9 tiles
+// per sample), `to_move`, `outcome` and `best_move`
+pub fn to_npz(samples: &[Sample]) -> Vec<u8> {
+    let positions: Vec<_> = samples.iter().flat_map(|sample| sample.tiles.iter().cloned())
+        .collect();
+    let column = |part: fn(&Sample) -> i8| samples.iter().map(part).collect::<Vec<_>>();
+    let count = samples.len();
+    to_zip(&[
+        ("positions.npy", to_npy(&format!("({}, 9)", count), &positions)),
+        ("to_move.npy", to_npy(&format!("({},)", count), &column(|sample| sample.to_move))),
+        ("outcome.npy", to_npy(&format!("({},)", count), &column(|sample| sample.outcome))),
+        ("best_move.npy", to_npy(&format!("({},)", count), &column(|sample| sample.best_move))),
+    ])
+}
+
+// A NumPy array of 8-bit integers with the given shape (written the way Python writes tuples).
+// The header is padded with spaces so that the data starts at a multiple of 64 bytes.
+fn to_npy(shape: &str, values: &[i8]) -> Vec<u8> {
+    let mut header = format!("{{'descr': '|i1', 'fortran_order': False, 'shape': {}, }}", shape);
+    // The magic string, the version and the length of the header come first, and the header
+    // ends with a newline
+    let used = 10 + header.len() + 1;
+    header.push_str(&" ".repeat(used.next_multiple_of(64) - used));
+    header.push('\n');
+
+    let mut bytes = b"\x93NUMPY\x01\x00".to_vec();
+    bytes.extend_from_slice(&(header.len() as u16).to_le_bytes());
+    bytes.extend_from_slice(header.as_bytes());
+    bytes.extend(values.iter().map(|&value| value as u8));
+    bytes
+}
+
+// A zip archive with the given files stored in it as they are. Every file is dated 1980-01-01,
+// the earliest date that a zip archive can hold, so the same samples always give the same file.
+fn to_zip(files: &[(&str, Vec<u8>)]) -> Vec<u8> {
+    // The fields that the entry of a file has in both the file's own header and the list of files
+    // at the end: the version needed to read it (2.0), the flags, the compression method
+    // (stored), the time, the date, the checksum and the sizes
+    let fields = |data: &[u8]| {
+        let mut fields = Vec::new();
+        for value in &[20u16, 0, 0, 0, 0x21] {
+            fields.extend_from_slice(&value.to_le_bytes());
+        }
+        fields.extend_from_slice(&crc32fast::hash(data).to_le_bytes());
+        fields.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        fields.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        fields
+    };
+
+    let mut zip = Vec::new();
+    let mut directory = Vec::new();
+    for &(name, ref data) in files {
+        let offset = zip.len() as u32;
+        zip.extend_from_slice(b"PK\x03\x04");
+        zip.extend(fields(data));
+        // The length of the name, then no extra fields
+        zip.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        zip.extend_from_slice(&[0; 2]);
+        zip.extend_from_slice(name.as_bytes());
+        zip.extend_from_slice(data);
+
+        directory.extend_from_slice(b"PK\x01\x02");
+        // Made by version 2.0
+        directory.extend_from_slice(&20u16.to_le_bytes());
+        directory.extend(fields(data));
+        directory.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        // No extra fields, comment, disk number or attributes
+        directory.extend_from_slice(&[0; 12]);
+        directory.extend_from_slice(&offset.to_le_bytes());
+        directory.extend_from_slice(name.as_bytes());
+    }
+
+    // The end of the list of files says how many there are, and where the list is
+    let start = zip.len() as u32;
+    zip.extend_from_slice(&directory);
+    zip.extend_from_slice(b"PK\x05\x06");
+    zip.extend_from_slice(&[0; 4]);
+    zip.extend_from_slice(&(files.len() as u16).to_le_bytes());
+    zip.extend_from_slice(&(files.len() as u16).to_le_bytes());
+    zip.extend_from_slice(&(directory.len() as u32).to_le_bytes());
+    zip.extend_from_slice(&start.to_le_bytes());
+    // No comment
+    zip.extend_from_slice(&[0; 2]);
+    zip
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn game(moves: &[(usize, usize)]) -> Game {
+        let mut game = Game::new();
+        for &(row, col) in moves {
+            game.make_move(row, col).unwrap();
+        }
+        game
+    }
+
+    #[test]
+    fn samples_of_a_game() {
+        let won = game(&[(0, 0), (1, 1), (0, 1), (2, 2), (0, 2)]);
+        let samples = samples(&won, false);
+        // Every position before the last move
+        assert_eq!(samples.len(), 5);
+        assert_eq!(samples[0], Sample {tiles: [0; 9], to_move: 1, outcome: 1, best_move: 0});
+        assert_eq!(samples[1].tiles, [1, 0, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(samples[1].to_move, -1);
+        // The only move that doesn't lose to the corner is the center
+        assert_eq!(samples[1].best_move, 4);
+        // x can win on the top row
+        assert_eq!(samples[4].best_move, 2);
+
+        assert!(super::samples(&game(&[(1, 1)]), false).is_empty());
+    }
+
+    #[test]
+    fn augmentation() {
+        let won = game(&[(0, 0), (1, 1), (0, 1), (2, 2), (0, 2)]);
+        let samples = samples(&won, true);
+        // The empty board looks the same from every side, and a corner can only be turned into
+        // the other 3 corners, but the later positions are different from every side
+        assert_eq!(samples[0].tiles, [0; 9]);
+        assert_eq!(samples[1].tiles, [1, 0, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(samples.len(), 1 + 4 + 4 + 8 + 8);
+        assert!(samples.iter().all(|sample| sample.outcome == 1));
+    }
+
+    #[test]
+    fn csv() {
+        let samples = samples(&game(&[(0, 0), (1, 1), (0, 1), (2, 2), (0, 2)]), false);
+        let mut text = Vec::new();
+        write_csv(&mut text, &samples[..1]).unwrap();
+        assert_eq!(String::from_utf8(text).unwrap(),
+            "t0,t1,t2,t3,t4,t5,t6,t7,t8,to_move,outcome,best_move\n0,0,0,0,0,0,0,0,0,1,1,0\n");
+    }
+
+    #[test]
+    fn npy() {
+        let npy = to_npy("(2,)", &[1, -1]);
+        assert_eq!(&npy[..6], b"\x93NUMPY");
+        assert_eq!(npy.len(), 128 + 2);
+        assert_eq!(npy[127], b'\n');
+        assert_eq!(&npy[128..], &[1, 255]);
+    }
+
+    #[test]
+    fn npz() {
+        let samples = samples(&game(&[(0, 0), (1, 1), (0, 1), (2, 2), (0, 2)]), false);
+        let npz = to_npz(&samples);
+        assert_eq!(&npz[..4], b"PK\x03\x04");
+        // The end of the list of files says that there are four of them
+        let end = npz.len() - 22;
+        assert_eq!(&npz[end..end + 4], b"PK\x05\x06");
+        assert_eq!(&npz[end + 8..end + 10], &[4, 0]);
+    }
+}
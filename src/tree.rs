@@ -0,0 +1,170 @@
+// Draws the game tree from a position as a GraphViz graph, for the `export-tree` command. Each
+// position is a box labeled with the moves that lead to it, and each arrow is a move. The boxes
+// are colored by how the game ends from there with perfect play, which shows at a glance which
+// moves throw a game away.
+//
+// Even a few moves deep, the full tree has thousands of positions. Most of them are the same
+// position reached in a different order, or the same board turned or flipped (see symmetry.rs),
+// so each of those is drawn only once. Starting in any of the corners is then just one box. This
+// makes the drawing a graph rather than a tree, since a box can have arrows from several others.
+//
+// GraphViz turns the graph into an image, e.g. `dot -Tsvg tree.dot -o tree.svg`.
+// More info: https://graphviz.org/doc/info/lang.html
+
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use engine;
+use game::{Game, Tiles, Winner};
+use notation;
+use solve;
+use symmetry;
+
+// The colors of the boxes, by how the game ends with perfect play
+const X_WINS_COLOR: &str = "#9ecae1";
+const O_WINS_COLOR: &str = "#fcae91";
+const TIE_COLOR: &str = "#e0e0e0";
+
+#[derive(Debug, Clone)]
+struct Node {
+    /// The first game found that reaches this position. The moves after it are made from here.
+    game: Game,
+    /// The moves from the starting position to this one
+    moves: Vec<(usize, usize)>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Tree {
+    /// The positions in the order they were found, starting with the starting position
+    nodes: Vec<Node>,
+    /// The moves between the positions, as the index of the position before the move, the index
+    /// of the position after it and the move itself
+    edges: Vec<(usize, usize, (usize, usize))>,
+}
+
+impl Tree {
+    // Finds every position up to `depth` moves after `start`. The positions are found one move
+    // at a time (breadth first), so that each one is labeled with one of the shortest ways to
+    // reach it.
+    pub fn new(start: &Game, depth: u32) -> Self {
+        let mut tree = Tree {nodes: Vec::new(), edges: Vec::new()};
+        let mut indexes = HashMap::new();
+        let start_index = tree.add(start.clone(), Vec::new(), &mut indexes);
+
+        let mut level = vec![start_index];
+        for _ in 0..depth {
+            let mut next_level = Vec::new();
+            for parent in level {
+                // Nothing comes after a finished game
+                if tree.nodes[parent].game.is_finished() {
+                    continue;
+                }
+                // Symmetric moves reach the same position, which only needs one arrow
+                let mut children = Vec::new();
+                for (row, col) in solve::empty_tiles(&tree.nodes[parent].game) {
+                    let mut game = tree.nodes[parent].game.clone();
+                    game.make_move(row, col).expect("empty tiles are always valid moves");
+                    let key = symmetry::canonical_tiles(game.tiles());
+                    let child = match indexes.get(&key) {
+                        Some(&child) => child,
+                        None => {
+                            let mut moves = tree.nodes[parent].moves.clone();
+                            moves.push((row, col));
+                            let child = tree.add(game, moves, &mut indexes);
+                            next_level.push(child);
+                            child
+                        },
+                    };
+                    if !children.contains(&child) {
+                        children.push(child);
+                        tree.edges.push((parent, child, (row, col)));
+                    }
+                }
+            }
+            level = next_level;
+        }
+        tree
+    }
+
+    fn add(&mut self, game: Game, moves: Vec<(usize, usize)>,
+        indexes: &mut HashMap<Tiles, usize>) -> usize {
+
+        let index = self.nodes.len();
+        // The tiles are enough to tell positions apart, since they also decide whose turn it is
+        indexes.insert(symmetry::canonical_tiles(game.tiles()), index);
+        self.nodes.push(Node {game, moves});
+        index
+    }
+
+    // The number of positions in the tree
+    pub fn positions(&self) -> usize {
+        self.nodes.len()
+    }
+
+    // Writes the tree in the DOT language that GraphViz reads
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::new();
+        // Writing to a String can't fail, so `unwrap` never panics here
+        writeln!(dot, "digraph tree {{").unwrap();
+        writeln!(dot, "    node [shape=box, style=filled, fontname=\"monospace\"];").unwrap();
+        for (index, node) in self.nodes.iter().enumerate() {
+            let label = if node.moves.is_empty() {
+                "start".to_string()
+            }
+            else {
+                node.moves.iter().map(|&tile| notation(tile)).collect::<Vec<_>>().join(" ")
+            };
+            let color = match engine::perfect_result(&node.game) {
+                Winner::X => X_WINS_COLOR,
+                Winner::O => O_WINS_COLOR,
+                Winner::Tie => TIE_COLOR,
+            };
+            // A double border marks the positions where the game is already over
+            let border = if node.game.is_finished() { ", peripheries=2" } else { "" };
+            writeln!(dot, "    n{} [label=\"{}\", fillcolor=\"{}\"{}];", index, label, color,
+                border).unwrap();
+        }
+        for &(parent, child, tile) in &self.edges {
+            writeln!(dot, "    n{} -> n{} [label=\"{}\"];", parent, child, notation(tile))
+                .unwrap();
+        }
+        writeln!(dot, "}}").unwrap();
+        dot
+    }
+}
+
+// Positions are written in the coordinate style that the player chose, e.g. 2B
+fn notation((row, col): (usize, usize)) -> String {
+    notation::write_move(row, col)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn first_moves() {
+        // There are only three different first moves: a corner, an edge and the center
+        let tree = Tree::new(&Game::new(), 1);
+        assert_eq!(tree.positions(), 4);
+        assert_snapshot!(tree.to_dot());
+
+        assert_eq!(Tree::new(&Game::new(), 0).positions(), 1);
+        // 12 different positions after two moves, and 38 after three
+        assert_eq!(Tree::new(&Game::new(), 2).positions(), 1 + 3 + 12);
+        assert_eq!(Tree::new(&Game::new(), 3).positions(), 1 + 3 + 12 + 38);
+    }
+
+    #[test]
+    fn finished_games_end_the_tree() {
+        let mut game = Game::new();
+        for &(row, col) in &[(0, 0), (1, 1), (0, 1), (2, 2)] {
+            game.make_move(row, col).unwrap();
+        }
+        // x wins right away at 1C, which ends that branch even though the tree could go deeper
+        let dot = Tree::new(&game, 2).to_dot();
+        assert!(dot.contains(&format!("[label=\"1C\", fillcolor=\"{}\", peripheries=2]",
+            X_WINS_COLOR)));
+        assert!(!dot.contains("\"1C "));
+    }
+}
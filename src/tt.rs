@@ -0,0 +1,156 @@
+// A transposition table remembers the score of every position the engine has already searched, so
+// that it doesn't have to search it again when the same position comes up through a different
+// order of moves (a "transposition"). Playing 1A then 2B reaches the same board as 2B then 1A, and
+// the further a search looks ahead, the more often that happens.
+// More info: https://www.chessprogramming.org/Transposition_Table
+//
+// Remembering every position would be fine on a 3x3 board, which only has a few thousand of them,
+// but bigger boards have far too many. So the table has a fixed size, chosen in megabytes, and
+// each position can only go in one slot. When two positions want the same slot, the table keeps
+// the one that is more likely to be useful: the one from the current search, or if both are from
+// the same search, the one that took more work to find.
+//
+// The same table is used for every search while build.rs solves the game (see solve.rs), and while
+// the engine plays a game by homebrew rules (see engine.rs). Scores found for earlier positions
+// are still right, so they are kept, but each new search makes them "older" so that they are the
+// first to be replaced.
+
+use std::mem;
+
+use game::{Game, Piece};
+
+// Marks a slot that doesn't have a position in it. No position has this key, since keys only use
+// the first few bits (see `key`).
+const EMPTY: u64 = u64::MAX;
+
+#[derive(Debug, Clone, Copy)]
+struct Entry {
+    key: u64,
+    score: i32,
+    /// How much work the score took to find: the number of empty tiles when it was searched
+    work: u8,
+    /// Which search the entry was made during
+    age: u8,
+}
+
+const EMPTY_ENTRY: Entry = Entry {key: EMPTY, score: 0, work: 0, age: 0};
+
+#[derive(Debug, Clone)]
+pub struct Table {
+    /// The slots are only allocated when the first score is saved, so that a table that is never
+    /// used doesn't cost anything
+    entries: Vec<Entry>,
+    /// The number of slots
+    capacity: usize,
+    /// The age of the current search
+    age: u8,
+}
+
+impl Table {
+    // A table that takes up at most the given number of megabytes. A table of zero megabytes
+    // doesn't remember anything.
+    pub fn with_megabytes(megabytes: usize) -> Self {
+        let capacity = megabytes * 1024 * 1024 / mem::size_of::<Entry>();
+        Self {entries: Vec::new(), capacity, age: 0}
+    }
+
+    // Starts a new search, which makes every score saved so far older
+    pub fn new_search(&mut self) {
+        self.age = self.age.wrapping_add(1);
+    }
+
+    // The score saved for the position with the given key, if there is one
+    pub fn get(&self, key: u64) -> Option<i32> {
+        let entry = self.entries.get(self.index(key)?)?;
+        if entry.key == key {
+            Some(entry.score)
+        }
+        else {
+            None
+        }
+    }
+
+    // Saves the score of a position, unless its slot holds a position that is more useful.
+    // `work` is the number of empty tiles that were searched to find the score.
+    pub fn insert(&mut self, key: u64, score: i32, work: u8) {
+        let index = match self.index(key) {
+            Some(index) => index,
+            None => return,
+        };
+        if self.entries.is_empty() {
+            self.entries = vec![EMPTY_ENTRY; self.capacity];
+        }
+
+        let slot = &mut self.entries[index];
+        // An empty slot, the same position or a position from an older search can always be
+        // replaced. Otherwise, the position that took more work to find is kept.
+        if slot.key == EMPTY || slot.key == key || slot.age != self.age || slot.work <= work {
+            *slot = Entry {key, score, work, age: self.age};
+        }
+    }
+
+    // The slot for a key, or None if the table has no slots
+    fn index(&self, key: u64) -> Option<usize> {
+        if self.capacity == 0 {
+            return None;
+        }
+        // Multiplying by a large odd number spreads similar keys out over the whole table
+        Some((key.wrapping_mul(0x9E37_79B9_7F4A_7C15) >> 16) as usize % self.capacity)
+    }
+}
+
+// A number that is different for every position. Each tile is a digit in base 3 (empty, x or o),
+// and the last bit says whose turn it is. The tiles alone aren't enough, since either piece can
+// go first.
+pub fn key(game: &Game) -> u64 {
+    let tiles = game.tiles().iter().flatten().fold(0, |key, tile| key * 3 + match *tile {
+        None => 0,
+        Some(Piece::X) => 1,
+        Some(Piece::O) => 2,
+    });
+    tiles * 2 + if game.current_piece() == Piece::X { 0 } else { 1 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn size_and_lookup() {
+        let mut table = Table::with_megabytes(1);
+        assert_eq!(table.capacity, 1024 * 1024 / mem::size_of::<Entry>());
+        assert_eq!(table.get(7), None);
+        table.insert(7, 3, 4);
+        assert_eq!(table.get(7), Some(3));
+
+        // A table without any room never remembers anything
+        let mut empty = Table::with_megabytes(0);
+        empty.insert(7, 3, 4);
+        assert_eq!(empty.get(7), None);
+    }
+
+    #[test]
+    fn replacement_and_aging() {
+        let mut table = Table::with_megabytes(1);
+        // Find two keys that want the same slot
+        let first = 1;
+        let second = (2..).find(|&key| table.index(key) == table.index(first)).unwrap();
+
+        table.insert(first, 5, 6);
+        // Less work than what is already there, so the first position stays
+        table.insert(second, -2, 3);
+        assert_eq!((table.get(first), table.get(second)), (Some(5), None));
+
+        // Once a new search starts, the old position makes way for a new one
+        table.new_search();
+        table.insert(second, -2, 3);
+        assert_eq!((table.get(first), table.get(second)), (None, Some(-2)));
+    }
+
+    #[test]
+    fn keys_include_the_turn() {
+        let game = Game::new();
+        assert_eq!(key(&game), 0);
+        assert_ne!(key(&game), key(&Game::starting_with(Piece::O)));
+    }
+}
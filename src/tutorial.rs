@@ -0,0 +1,112 @@
+// The tutorial teaches a new player the game by playing through a complete game with them. The
+// student plays x and the tutorial plays o. Before each of the student's moves, the tutorial
+// explains which move to make and why. The explanations aren't written out for each move ahead of
+// time. Instead, they come from looking at the threats on the board, so they always match what
+// the student sees.
+
+use game::{Game, Piece};
+use locale::Message;
+use player::Players;
+
+// The piece that the student plays
+pub const STUDENT: Piece = Piece::X;
+
+// Every move of the lesson, starting with the student. The student takes two opposite corners, and
+// o makes a threat that the student has to block. Blocking it also makes a "fork" with two
+// threats at once, so o can't stop the student from winning.
+//
+//    A B C
+//  1 x ▢ o
+//  2 o o ▢
+//  3 x x x
+pub const SCRIPT: [(usize, usize); 7] = [(0, 0), (1, 1), (2, 2), (0, 2), (2, 0), (1, 0), (2, 1)];
+
+// Explains why the student should play at (row, col) in the game as it is now
+pub fn advice<'a>(game: &Game, players: &'a Players, row: usize, col: usize) -> Message<'a> {
+    let opponent = STUDENT.other();
+
+    // Winning is always the best move, and otherwise we must stop the other player from winning
+    if game.winning_moves(STUDENT).contains(&(row, col)) {
+        return Message::TutorialWin {row, col};
+    }
+    if game.winning_moves(opponent).contains(&(row, col)) {
+        return Message::TutorialBlock {row, col, opponent: players.name(opponent)};
+    }
+
+    // We try the move on a copy of the game to see how many threats it would create
+    let mut after = game.clone();
+    after.make_move(row, col).expect("tutorial moves should always be valid");
+    if after.winning_moves(STUDENT).len() >= 2 {
+        return Message::TutorialFork {row, col};
+    }
+
+    Message::TutorialLines {row, col, lines: lines_through(row, col)}
+}
+
+// Points out what the student's last move did, if it did something worth mentioning
+pub fn commentary<'a>(game: &Game, players: &'a Players) -> Option<Message<'a>> {
+    if !game.is_finished() && game.winning_moves(STUDENT).len() >= 2 {
+        Some(Message::TutorialForkMade {opponent: players.name(STUDENT.other())})
+    }
+    else {
+        None
+    }
+}
+
+// The number of ways to win that use the given tile. Every tile is part of its row and its column.
+// The tiles on the diagonals (the corners and the center) are part of one or two more lines.
+fn lines_through(row: usize, col: usize) -> usize {
+    let mut lines = 2;
+    if row == col {
+        lines += 1;
+    }
+    if row + col == 2 {
+        lines += 1;
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use game::Winner;
+
+    #[test]
+    fn script_wins_for_the_student() {
+        let mut game = Game::starting_with(STUDENT);
+        for &(row, col) in &SCRIPT {
+            game.make_move(row, col).unwrap();
+        }
+        assert_eq!(game.winner(), Some(Winner::X));
+    }
+
+    #[test]
+    fn lines() {
+        assert_eq!(lines_through(1, 1), 4);
+        assert_eq!(lines_through(0, 2), 3);
+        assert_eq!(lines_through(1, 0), 2);
+    }
+
+    #[test]
+    fn advice_follows_the_threats() {
+        let players = Players::default();
+        let mut game = Game::starting_with(STUDENT);
+        let advised: Vec<_> = SCRIPT.iter().enumerate().filter_map(|(i, &(row, col))| {
+            let message = if i % 2 == 0 {
+                Some(advice(&game, &players, row, col))
+            }
+            else {
+                None
+            };
+            game.make_move(row, col).unwrap();
+            message
+        }).collect();
+
+        match advised[..] {
+            [Message::TutorialLines {lines: 3, ..}, Message::TutorialLines {lines: 3, ..},
+                Message::TutorialBlock {row: 2, col: 0, ..},
+                Message::TutorialWin {row: 2, col: 1}] => {},
+            _ => panic!("unexpected advice: {:?}", advised),
+        }
+    }
+}
@@ -0,0 +1,171 @@
+// Following a game as it is written to a file, for the `watch` command. The file has the same
+// moves as a `--moves-file` (e.g. "2B 1A 3C"), and whoever is playing adds each move to the end
+// of it. That is handy for showing a game played by mail or on another computer on a second
+// screen.
+//
+// The file is checked a few times a second. Its size and the time it was last changed tell us
+// when there is something new to read, so an unchanged file is never read again. Only the moves
+// that weren't there before are played, so the board is never built from scratch unless the
+// moves that were already shown change (like when a move is taken back or a new game starts).
+
+use std::fs::{self, Metadata};
+use std::io;
+use std::path::Path;
+use std::time::SystemTime;
+
+use game::Game;
+use notation::{parse_move, script_moves};
+
+// What the file looked like the last time it was read. Either one changes when something is
+// written to the file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stamp {
+    modified: Option<SystemTime>,
+    len: u64,
+}
+
+impl Stamp {
+    pub fn new(metadata: &Metadata) -> Self {
+        Self {modified: metadata.modified().ok(), len: metadata.len()}
+    }
+}
+
+// What changed in the file since the last time it was read
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Update {
+    /// True if the moves that were already shown changed, so the game was played again from the
+    /// start
+    pub restarted: bool,
+    /// The tiles of the moves that were played, in order
+    pub moves: Vec<(usize, usize)>,
+    /// The number (counting from 1) and notation of a move that couldn't be played. The moves
+    /// after it are left until it is fixed.
+    pub invalid: Option<(usize, String)>,
+}
+
+// A game that is being followed in a file
+#[derive(Debug, Clone, Default)]
+pub struct Watch {
+    /// The moves of the file that were played, exactly as they were written
+    played: Vec<String>,
+    stamp: Option<Stamp>,
+}
+
+impl Watch {
+    // Returns true if the file changed since it was last read
+    pub fn is_changed(&self, metadata: &Metadata) -> bool {
+        self.stamp != Some(Stamp::new(metadata))
+    }
+
+    // Catches the game up with the text of the file after it changed. `stamp` is what the file
+    // looked like when it was read.
+    pub fn update(&mut self, game: &mut Game, text: &str, stamp: Stamp) -> Update {
+        self.stamp = Some(stamp);
+        let moves: Vec<_> = script_moves(text).collect();
+        let mut update = Update::default();
+        let same = self.played.len() <= moves.len()
+            && self.played.iter().zip(&moves).all(|(played, notation)| played == notation);
+        if !same {
+            *game = Game::starting_with(game.first_piece());
+            self.played.clear();
+            update.restarted = true;
+        }
+
+        let (before, new) = (self.played.len(), &moves[self.played.len()..]);
+        if let Err(index) = apply_moves(game, new) {
+            update.invalid = Some((before + index + 1, new[index].to_string()));
+        }
+        update.moves = game.moves().iter().skip(before).collect();
+        self.played.extend(new[..update.moves.len()].iter().map(|notation| notation.to_string()));
+        update
+    }
+}
+
+// Plays the moves on the game one after the other. If one of them can't be played, the game is
+// left with the moves before it, and the index of the move that couldn't be played is returned.
+pub fn apply_moves(game: &mut Game, moves: &[&str]) -> Result<(), usize> {
+    for (i, notation) in moves.iter().enumerate() {
+        let (row, col) = parse_move(notation).map_err(|_| i)?;
+        game.make_move(row, col).map_err(|_| i)?;
+    }
+    Ok(())
+}
+
+// Reads the file if it changed since the last time, and catches the game up with it. Returns None
+// if nothing changed.
+pub fn poll(watch: &mut Watch, game: &mut Game, path: &Path) -> io::Result<Option<Update>> {
+    let metadata = fs::metadata(path)?;
+    if !watch.is_changed(&metadata) {
+        return Ok(None);
+    }
+    let text = fs::read_to_string(path)?;
+    Ok(Some(watch.update(game, &text, Stamp::new(&metadata))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::env;
+    use std::process;
+
+    use game::Piece;
+
+    fn stamp(len: u64) -> Stamp {
+        Stamp {modified: None, len}
+    }
+
+    #[test]
+    fn new_moves_are_played() {
+        let mut watch = Watch::default();
+        let mut game = Game::new();
+        let update = watch.update(&mut game, "2B 1A", stamp(5));
+        assert_eq!(update.moves, [(1, 1), (0, 0)]);
+        assert!(!update.restarted);
+
+        // Only the moves after the ones that were already played are new
+        let update = watch.update(&mut game, "2B 1A\n3C,", stamp(9));
+        assert_eq!(update, Update {moves: vec![(2, 2)], ..Update::default()});
+        assert_eq!(game.moves().len(), 3);
+        assert_eq!(watch.update(&mut game, "2B 1A\n3C,", stamp(10)), Update::default());
+    }
+
+    #[test]
+    fn changed_moves_start_over() {
+        let mut watch = Watch::default();
+        let mut game = Game::starting_with(Piece::O);
+        watch.update(&mut game, "2B 1A 3C", stamp(8));
+        let update = watch.update(&mut game, "2B 1C", stamp(5));
+        assert!(update.restarted);
+        assert_eq!(update.moves, [(1, 1), (0, 2)]);
+        assert_eq!(game.moves().len(), 2);
+        assert_eq!(game.first_piece(), Piece::O);
+    }
+
+    #[test]
+    fn invalid_moves_wait() {
+        let mut watch = Watch::default();
+        let mut game = Game::new();
+        // The last move is only half written, or it is a tile that is already taken
+        let update = watch.update(&mut game, "2B 1A 3", stamp(7));
+        assert_eq!(update.moves, [(1, 1), (0, 0)]);
+        assert_eq!(update.invalid, Some((3, "3".to_string())));
+        let update = watch.update(&mut game, "2B 1A 3C 2B", stamp(11));
+        assert_eq!(update.moves, [(2, 2)]);
+        assert_eq!(update.invalid, Some((4, "2B".to_string())));
+        assert_eq!(game.moves().len(), 3);
+    }
+
+    #[test]
+    fn changes_are_noticed() {
+        let path = env::temp_dir().join(format!("tic-tac-toe-watch-{}", process::id()));
+        fs::write(&path, "2B").unwrap();
+        let (mut watch, mut game) = (Watch::default(), Game::new());
+        assert_eq!(poll(&mut watch, &mut game, &path).unwrap().unwrap().moves, [(1, 1)]);
+        assert_eq!(poll(&mut watch, &mut game, &path).unwrap(), None);
+        fs::write(&path, "2B 1A").unwrap();
+        assert_eq!(poll(&mut watch, &mut game, &path).unwrap().unwrap().moves, [(0, 0)]);
+        fs::remove_file(&path).unwrap();
+        assert!(poll(&mut watch, &mut game, &path).is_err());
+    }
+}
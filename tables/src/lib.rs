@@ -0,0 +1,114 @@
+// Works out the best moves for every position of the game before the program is even compiled.
+// The build scripts of the terminal and web versions (build.rs) call `generate`, and the table it
+// writes is embedded in the program by engine.rs, so the perfect computer player never has to
+// search while it plays. Its moves cost nothing more than looking them up, and there is nothing to
+// work out when the program starts.
+// More info: https://doc.rust-lang.org/cargo/reference/build-scripts.html
+//
+// The search itself is the one in solve.rs. Just like the web version, this crate uses `#[path]`
+// to include it along with the modules it needs. Cargo knows about every file that a crate is
+// built from, so changing any of them builds this crate again and makes the build scripts run
+// again with the new search.
+
+extern crate thiserror;
+
+// Only part of each module is used to make the table
+#[allow(dead_code)]
+#[path = "../../src/game.rs"]
+mod game;
+#[allow(dead_code)]
+#[path = "../../src/history.rs"]
+mod history;
+#[allow(dead_code)]
+#[path = "../../src/tt.rs"]
+mod tt;
+#[allow(dead_code)]
+#[path = "../../src/solve.rs"]
+mod solve;
+
+use std::collections::HashSet;
+use std::env;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+use game::{Game, Piece, Winner};
+use tt::Table;
+
+// There is an entry for every possible key (see tt::key): 3 kinds of tile on each of the 9 tiles,
+// and 2 players who could be next to move. Most keys belong to positions that can't happen.
+const POSITIONS: usize = 19683 * 2;
+// The size of the transposition table used by the search. The whole game fits many times over.
+const TABLE_MEGABYTES: usize = 4;
+// The number of entries written on each line of the generated file
+const PER_LINE: usize = 16;
+
+// Writes the table to perfect.rs in the directory that Cargo gives the build script that calls
+// this function
+pub fn generate() {
+    // The best moves of each position as a number where bit `row * 3 + col` is set for each of
+    // them, and how the game ends with perfect play from each position
+    let mut moves = vec![0u16; POSITIONS];
+    let mut results = vec![None; POSITIONS];
+    let mut table = Table::with_megabytes(TABLE_MEGABYTES);
+    let mut visited = HashSet::new();
+    for &first_piece in &[Piece::X, Piece::O] {
+        visit(&Game::starting_with(first_piece), &mut table, &mut visited, &mut moves,
+            &mut results);
+    }
+
+    let mut code = String::new();
+    code.push_str("// Made by the tables crate. Don't change it by hand!\n");
+    writeln!(code, "const PERFECT_POSITIONS: usize = {};", POSITIONS).unwrap();
+    write_array(&mut code, "PERFECT_MOVES", "u16", &moves, |mask| mask.to_string());
+    write_array(&mut code, "PERFECT_RESULTS", "Option<Winner>", &results, |result| {
+        match result {
+            Some(Winner::X) => "Some(Winner::X)",
+            Some(Winner::O) => "Some(Winner::O)",
+            Some(Winner::Tie) => "Some(Winner::Tie)",
+            None => "None",
+        }.to_string()
+    });
+
+    let out_dir = env::var("OUT_DIR").expect("Cargo should set OUT_DIR for build scripts");
+    fs::write(Path::new(&out_dir).join("perfect.rs"), code)
+        .expect("the table should be written to OUT_DIR");
+}
+
+// Solves a position and every position after it. Positions that were already visited are skipped.
+fn visit(game: &Game, table: &mut Table, visited: &mut HashSet<u64>, moves: &mut [u16],
+    results: &mut [Option<Winner>]) {
+
+    let key = tt::key(game);
+    if !visited.insert(key) {
+        return;
+    }
+
+    let index = key as usize;
+    table.new_search();
+    results[index] = Some(solve::perfect_result(game, table));
+    if game.is_finished() {
+        return;
+    }
+    for (row, col) in solve::best_moves(game, table) {
+        moves[index] |= 1 << (row * 3 + col);
+    }
+
+    for (row, col) in solve::empty_tiles(game) {
+        let mut next = game.clone();
+        next.make_move(row, col).expect("empty tiles are always valid moves");
+        visit(&next, table, visited, moves, results);
+    }
+}
+
+// Writes a `static` array with the given name and type, using `value` to write each entry
+fn write_array<T, F>(code: &mut String, name: &str, kind: &str, values: &[T], value: F)
+    where F: Fn(&T) -> String {
+
+    writeln!(code, "static {}: [{}; PERFECT_POSITIONS] = [", name, kind).unwrap();
+    for line in values.chunks(PER_LINE) {
+        let entries: Vec<_> = line.iter().map(&value).collect();
+        writeln!(code, "    {},", entries.join(", ")).unwrap();
+    }
+    code.push_str("];\n");
+}
@@ -0,0 +1,11 @@
+// The library has a perfect computer player too, so it embeds the same table of perfect moves as
+// the terminal version, made by the same crate (see ../build.rs)
+
+extern crate tic_tac_toe_tables;
+
+fn main() {
+    tic_tac_toe_tables::generate();
+    // The table only depends on the tables crate, and Cargo already runs this script again
+    // whenever that changes
+    println!("cargo:rerun-if-changed=build.rs");
+}
@@ -0,0 +1,213 @@
+// The rules and computer players of the game as a WebAssembly library for JavaScript, to be
+// published on npm and used by any web page or bundler, separately from the browser version in
+// ../web. wasm-pack builds the package, along with TypeScript typings for everything marked with
+// `#[wasm_bindgen]` (see README.md).
+// More info: https://rustwasm.github.io/docs/wasm-pack/
+//
+// Just like the browser version, only the modules of the terminal version that never read input
+// or print anything are included with `#[path]`. Everything that needs a terminal, files or
+// threads lives in other modules, so none of it is ever compiled for WebAssembly.
+//
+// The computer players run entirely in the browser. Their random numbers come from a seed rather
+// than the clock, so a game played with the same seed and the same moves always goes the same
+// way. wasm-bindgen turns the `///` comments into the documentation in the typings.
+
+extern crate wasm_bindgen;
+extern crate thiserror;
+
+// The library only uses part of each module, so we tell Rust not to warn about the rest
+#[allow(dead_code)]
+#[path = "../../src/game.rs"]
+mod game;
+#[allow(dead_code)]
+#[path = "../../src/history.rs"]
+mod history;
+#[allow(dead_code)]
+#[path = "../../src/random.rs"]
+mod random;
+#[allow(dead_code)]
+#[path = "../../src/engine.rs"]
+mod engine;
+#[allow(dead_code)]
+#[path = "../../src/tt.rs"]
+mod tt;
+#[allow(dead_code)]
+#[path = "../../src/solve.rs"]
+mod solve;
+
+use wasm_bindgen::prelude::*;
+
+use engine::Difficulty;
+use game::{MoveError, Piece, Winner};
+use random::Rng;
+
+/// A tile of the board. Rows and columns are numbered from 0 to 2, starting at the top left
+/// corner.
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Move {
+    pub row: usize,
+    pub col: usize,
+}
+
+impl Move {
+    fn new((row, col): (usize, usize)) -> Self {
+        Move {row, col}
+    }
+}
+
+/// A game of tic-tac-toe. Pieces and results are the strings "x", "o" and "tie".
+#[wasm_bindgen]
+pub struct Game {
+    game: game::Game,
+    rng: Rng,
+}
+
+#[wasm_bindgen]
+impl Game {
+    /// Starts a new game with x to move. The seed decides which of several equally good moves
+    /// `bestMove` picks, so the same seed always gives the same games. Without a seed, the seed
+    /// is 0.
+    #[wasm_bindgen(constructor)]
+    pub fn new(seed: Option<f64>) -> Game {
+        // JavaScript numbers are all floating point, so the seed is turned into a u64 bit for bit
+        Game {game: game::Game::new(), rng: Rng::new(seed.unwrap_or(0.0).to_bits())}
+    }
+
+    /// Places the current player's piece at the given row and column. Throws an error if the
+    /// move can't be made, in which case nothing changes.
+    #[wasm_bindgen(js_name = makeMove)]
+    pub fn make_move(&mut self, row: usize, col: usize) -> Result<(), JsError> {
+        self.game.make_move(row, col).map_err(|error| JsError::new(&move_error(&error)))
+    }
+
+    /// The move the computer would make for the current player at the given difficulty ("easy",
+    /// "medium" or "perfect", the default), or undefined if the game is over. The move isn't
+    /// made.
+    #[wasm_bindgen(js_name = bestMove)]
+    pub fn best_move(&mut self, difficulty: Option<String>) -> Option<Move> {
+        if self.game.is_finished() {
+            return None;
+        }
+        let difficulty = difficulty.as_deref()
+            .and_then(Difficulty::from_name)
+            .unwrap_or(Difficulty::Perfect);
+        Some(Move::new(engine::choose_move(&self.game, difficulty, &mut self.rng)))
+    }
+
+    /// Takes back the last move and returns where it was made, or undefined if there were no
+    /// moves to take back
+    pub fn undo(&mut self) -> Option<Move> {
+        self.game.undo().map(Move::new)
+    }
+
+    /// Every move made so far, in the order they were made
+    pub fn moves(&self) -> Vec<Move> {
+        self.game.moves().iter().map(Move::new).collect()
+    }
+
+    /// The moves that can be made now: every empty tile, or none once the game is over
+    #[wasm_bindgen(js_name = legalMoves)]
+    pub fn legal_moves(&self) -> Vec<Move> {
+        if self.game.is_finished() {
+            return Vec::new();
+        }
+        solve::empty_tiles(&self.game).into_iter().map(Move::new).collect()
+    }
+
+    /// The piece at the given row and column: "x", "o" or undefined if the tile is empty
+    pub fn tile(&self, row: usize, col: usize) -> Option<String> {
+        let tile = self.game.tiles().get(row).and_then(|tiles| tiles.get(col)).cloned();
+        tile.unwrap_or(None).map(|piece| piece_name(piece).to_string())
+    }
+
+    /// The piece whose turn it is: "x" or "o"
+    #[wasm_bindgen(getter, js_name = currentPiece)]
+    pub fn current_piece(&self) -> String {
+        piece_name(self.game.current_piece()).to_string()
+    }
+
+    /// "x", "o" or "tie" once the game is over, and undefined before that
+    #[wasm_bindgen(getter)]
+    pub fn winner(&self) -> Option<String> {
+        self.game.winner().map(|winner| winner_name(winner).to_string())
+    }
+
+    #[wasm_bindgen(getter, js_name = isFinished)]
+    pub fn is_finished(&self) -> bool {
+        self.game.is_finished()
+    }
+
+    /// How the game ends from here if both players play perfectly: "x", "o" or "tie"
+    #[wasm_bindgen(js_name = perfectResult)]
+    pub fn perfect_result(&self) -> String {
+        winner_name(engine::perfect_result(&self.game)).to_string()
+    }
+}
+
+// The message of the error thrown by `makeMove`
+fn move_error(error: &MoveError) -> String {
+    match *error {
+        MoveError::GameAlreadyOver => "the game is already over".to_string(),
+        MoveError::InvalidPosition {row, col} => {
+            format!("({}, {}) is not on the board", row, col)
+        },
+        MoveError::TileNotEmpty {other_piece, row, col, ..} => {
+            format!("({}, {}) already has {} on it", row, col, piece_name(other_piece))
+        },
+    }
+}
+
+fn piece_name(piece: Piece) -> &'static str {
+    match piece {
+        Piece::X => "x",
+        Piece::O => "o",
+    }
+}
+
+fn winner_name(winner: Winner) -> &'static str {
+    match winner {
+        Winner::X => "x",
+        Winner::O => "o",
+        Winner::Tie => "tie",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Plays the computer against itself. JavaScript errors can only be made in WebAssembly, so
+    // the tests don't make any moves that would throw one.
+    fn self_play(seed: f64, difficulty: &str) -> Vec<Move> {
+        let mut game = Game::new(Some(seed));
+        while let Some(Move {row, col}) = game.best_move(Some(difficulty.to_string())) {
+            game.make_move(row, col).unwrap();
+        }
+        game.moves()
+    }
+
+    #[test]
+    fn play_against_the_computer() {
+        let mut game = Game::new(None);
+        game.make_move(1, 1).unwrap();
+        assert_eq!(game.tile(1, 1), Some("x".to_string()));
+        assert_eq!(game.tile(3, 0), None);
+        assert_eq!(game.legal_moves().len(), 8);
+        assert_eq!(game.current_piece(), "o");
+
+        // The only moves that don't lose against the center are the corners
+        let Move {row, col} = game.best_move(None).unwrap();
+        assert!(row != 1 && col != 1);
+        assert_eq!(game.undo(), Some(Move {row: 1, col: 1}));
+        assert_eq!(game.winner(), None);
+    }
+
+    #[test]
+    fn the_computer_is_deterministic() {
+        assert_eq!(self_play(4.0, "easy"), self_play(4.0, "easy"));
+        assert_eq!(self_play(1.5, "perfect").len(), 9, "perfect players always tie");
+        assert_eq!(move_error(&MoveError::InvalidPosition {row: 3, col: 0}),
+            "(3, 0) is not on the board");
+    }
+}
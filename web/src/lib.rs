@@ -0,0 +1,126 @@
+// The browser version of the game. This crate is compiled to WebAssembly, which browsers can run
+// alongside JavaScript. The JavaScript in index.html draws the board and handles clicks, and
+// calls into this crate for everything else.
+//
+// The rules of the game and the computer player are exactly the same as in the terminal version.
+// Instead of copying them, `#[path]` tells Rust to use the files from the terminal version as
+// modules of this crate. Those files never read input or print anything, so they work in a
+// browser just as well as in a terminal.
+//
+// `wasm-bindgen` generates the code that lets JavaScript create a `WebGame` and call its methods.
+// More info: https://rustwasm.github.io/docs/wasm-bindgen/
+
+extern crate wasm_bindgen;
+extern crate thiserror;
+
+// The browser only uses part of each module, so we tell Rust not to warn about the rest
+#[allow(dead_code)]
+#[path = "../../src/game.rs"]
+mod game;
+#[allow(dead_code)]
+#[path = "../../src/history.rs"]
+mod history;
+#[allow(dead_code)]
+#[path = "../../src/random.rs"]
+mod random;
+#[allow(dead_code)]
+#[path = "../../src/engine.rs"]
+mod engine;
+#[allow(dead_code)]
+#[path = "../../src/tt.rs"]
+mod tt;
+#[allow(dead_code)]
+#[path = "../../src/solve.rs"]
+mod solve;
+
+use wasm_bindgen::prelude::*;
+
+use engine::Difficulty;
+use game::{Game, Piece, Winner};
+use random::Rng;
+
+// A game played in the browser. JavaScript numbers the tiles from 0 to 8, going across each row
+// from the top left corner, so that is how the tiles are numbered here too.
+#[wasm_bindgen]
+pub struct WebGame {
+    game: Game,
+    rng: Rng,
+}
+
+#[wasm_bindgen]
+impl WebGame {
+    // Starts a new game. There is no clock to seed the random numbers from in the browser, so
+    // JavaScript passes in a seed (e.g. from `Math.random()`).
+    #[wasm_bindgen(constructor)]
+    pub fn new(seed: f64) -> WebGame {
+        WebGame {game: Game::new(), rng: Rng::new(seed.to_bits())}
+    }
+
+    // Places the current player's piece on the given tile. Returns false if that isn't a legal
+    // move, in which case nothing changes.
+    pub fn play(&mut self, tile: usize) -> bool {
+        self.game.make_move(tile / 3, tile % 3).is_ok()
+    }
+
+    // Lets the computer make a move for the current player at the given difficulty ("easy",
+    // "medium" or "perfect"). Returns the tile it played on, or -1 if the game is already over.
+    pub fn computer_move(&mut self, difficulty: &str) -> i32 {
+        if self.game.is_finished() {
+            return -1;
+        }
+        let difficulty = Difficulty::from_name(difficulty).unwrap_or(Difficulty::Perfect);
+        let (row, col) = engine::choose_move(&self.game, difficulty, &mut self.rng);
+        self.game.make_move(row, col).expect("the engine should only choose empty tiles");
+        (row * 3 + col) as i32
+    }
+
+    // The piece on a tile: "x", "o" or "" if it is empty
+    pub fn tile(&self, tile: usize) -> String {
+        piece_name(self.game.tiles()[tile / 3][tile % 3]).to_string()
+    }
+
+    // The piece whose turn it is: "x" or "o"
+    pub fn current_piece(&self) -> String {
+        piece_name(Some(self.game.current_piece())).to_string()
+    }
+
+    // The result of the game: "x", "o", "tie" or "" if it isn't over yet
+    pub fn winner(&self) -> String {
+        match self.game.winner() {
+            Some(Winner::X) => "x",
+            Some(Winner::O) => "o",
+            Some(Winner::Tie) => "tie",
+            None => "",
+        }.to_string()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.game.is_finished()
+    }
+}
+
+fn piece_name(piece: Option<Piece>) -> &'static str {
+    match piece {
+        Some(Piece::X) => "x",
+        Some(Piece::O) => "o",
+        None => "",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn play_against_the_computer() {
+        let mut web = WebGame::new(0.5);
+        assert!(web.play(4));
+        assert!(!web.play(4), "a tile can't be played twice");
+        assert_eq!(web.tile(4), "x");
+
+        let tile = web.computer_move("perfect");
+        assert_eq!(web.tile(tile as usize), "o");
+        assert_eq!(web.current_piece(), "x");
+        assert_eq!(web.winner(), "");
+    }
+}